@@ -12,7 +12,7 @@ use datafusion::arrow::record_batch::RecordBatch;
 
 use crate::ctx::{ResultSet, SessionContext};
 use crate::exec::connect_database;
-use crate::functions::{display_all_functions, Function};
+use crate::functions::{display_all_functions, user_functions_sql, Function};
 use crate::print_format::PrintFormat;
 use crate::print_options::PrintOptions;
 use crate::Result;
@@ -83,14 +83,23 @@ impl Command {
                 Ok(())
             }
             Self::Quit => Err(anyhow!("Unexpected quit, this should be handled outside")),
-            Self::ListFunctions => display_all_functions(),
+            Self::ListFunctions => {
+                display_all_functions()?;
+                println!("User-defined functions:");
+                let resp = ctx.sql(user_functions_sql(None)).await?;
+                let results = SessionContext::parse_response(resp).await?;
+                print_options.print_batches(&results, now)
+            }
             Self::SearchFunctions(function) => {
                 if let Ok(func) = function.parse::<Function>() {
                     let details = func.function_details()?;
                     println!("{}", details);
                     Ok(())
                 } else {
-                    Err(anyhow!("{function} is not a supported function"))
+                    // Fall back to the user-defined functions of the current tenant
+                    let resp = ctx.sql(user_functions_sql(Some(function))).await?;
+                    let results = SessionContext::parse_response(resp).await?;
+                    print_options.print_batches(&results, now)
                 }
             }
             Self::OutputFormat(_) => Err(anyhow!(
@@ -21,9 +21,11 @@ pub enum Function {
     DescribeDatabase,
     Insert,
     DropTable,
+    CreateFunction,
+    DropFunction,
 }
 
-const ALL_FUNCTIONS: [Function; 9] = [
+const ALL_FUNCTIONS: [Function; 11] = [
     Function::CreateTable,
     Function::CreateTableAs,
     Function::DropTable,
@@ -33,6 +35,8 @@ const ALL_FUNCTIONS: [Function; 9] = [
     Function::DescribeTable,
     Function::DescribeDatabase,
     Function::Show,
+    Function::CreateFunction,
+    Function::DropFunction,
 ];
 
 impl Function {
@@ -150,6 +154,32 @@ Command:     DROP TABLE
 Description: remove a table
 Syntax:
 DROP TABLE [ IF EXISTS ] name [, ...]
+"#
+            }
+            Function::CreateFunction => {
+                r#"
+Command:     CREATE FUNCTION
+Description: define a new user-defined function in the current tenant
+Syntax:
+CREATE [ OR REPLACE ] [ AGGREGATE ] FUNCTION [ IF NOT EXISTS ] name
+    ( [ argument_name data_type [, ...] ] )
+    RETURNS data_type
+    LANGUAGE { SQL | WASM }
+    AS 'definition'
+    [ WITH ( fuel = number, max_memory = number ) ]
+
+The definition of a SQL function is an expression of its arguments.
+The definition of a WASM function is a base64 encoded WebAssembly module,
+which exports 'eval' for a scalar function, or 'init', 'update' and 'merge'
+for an aggregate function.
+"#
+            }
+            Function::DropFunction => {
+                r#"
+Command:     DROP FUNCTION
+Description: remove a user-defined function
+Syntax:
+DROP FUNCTION [ IF EXISTS ] name
 "#
             }
         };
@@ -169,6 +199,8 @@ impl FromStr for Function {
             "CREATE TABLE AS" => Self::CreateTableAs,
             "INSERT" => Self::Insert,
             "DROP TABLE" => Self::DropTable,
+            "CREATE FUNCTION" => Self::CreateFunction,
+            "DROP FUNCTION" => Self::DropFunction,
             _ => return Err(()),
         })
     }
@@ -186,6 +218,8 @@ impl fmt::Display for Function {
             Function::DescribeDatabase => write!(f, "DESCRIBE DATABASE"),
             Function::Insert => write!(f, "INSERT"),
             Function::DropTable => write!(f, "DROP TABLE"),
+            Function::CreateFunction => write!(f, "CREATE FUNCTION"),
+            Function::DropFunction => write!(f, "DROP FUNCTION"),
         }
     }
 }
@@ -203,3 +237,17 @@ pub fn display_all_functions() -> Result<()> {
     println!("{}", pretty_format_batches(&[batch]).unwrap());
     Ok(())
 }
+
+/// Query the user-defined functions of the current tenant,
+/// only the function with the given name if it is specified.
+pub fn user_functions_sql(name: Option<&str>) -> String {
+    let sql = "SELECT function_name, function_type, language, arguments, return_type, definition \
+        FROM information_schema.functions";
+    match name {
+        Some(name) => format!(
+            "{sql} WHERE function_name = '{}'",
+            name.trim().to_lowercase().replace('\'', "''")
+        ),
+        None => format!("{sql} ORDER BY function_name"),
+    }
+}
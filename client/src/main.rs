@@ -59,7 +59,7 @@ struct CliArgs {
     #[arg(long, value_parser = try_parse_target_partitions)]
     target_partitions: Option<usize>,
 
-    /// Optionally, specify the micro batch stream trigger interval. e.g. once, 1m, 10s
+    /// Optionally, specify the micro batch stream trigger interval. e.g. once, 1m, 10s, continuous, watermark, watermark:5m
     #[arg(short, long)]
     stream_trigger_interval: Option<String>,
 
@@ -13,6 +13,8 @@ pub const APPLICATION_TSV: &str = "text/tsv";
 pub const APPLICATION_JSON: &str = "application/json";
 pub const APPLICATION_NDJSON: &str = "application/nd-json";
 pub const APPLICATION_TABLE: &str = "text/table";
+pub const APPLICATION_ARROW_STREAM: &str = "application/vnd.apache.arrow.stream";
+pub const APPLICATION_PARQUET: &str = "application/vnd.apache.parquet";
 pub const APPLICATION_STAR: &str = "application/*";
 pub const STAR_STAR: &str = "*/*";
 
@@ -26,6 +28,7 @@ pub const DB: &str = "db";
 pub const TABLE: &str = "table";
 pub const TARGET_PARTITIONS: &str = "target_partitions";
 pub const STREAM_TRIGGER_INTERVAL: &str = "stream_trigger_interval";
+pub const VNODE_ENDPOINTS: &str = "vnode_endpoints";
 
 // encoding
 pub const GZIP: &str = "gzip";
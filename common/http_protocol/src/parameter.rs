@@ -11,6 +11,44 @@ pub struct SqlParam {
     pub stream_trigger_interval: Option<String>,
 }
 
+/// Parameters of the Prometheus HTTP API `/api/v1/query` and `/api/v1/query_range`.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct PromQueryParam {
+    pub query: Option<String>,
+    // Evaluation timestamp of instant queries, RFC3339 or unix timestamp in seconds
+    pub time: Option<String>,
+    pub start: Option<String>,
+    pub end: Option<String>,
+    // Query resolution step width of range queries, duration or float number of seconds
+    pub step: Option<String>,
+    pub timeout: Option<String>,
+    pub tenant: Option<String>,
+    pub db: Option<String>,
+}
+
+/// Parameters of the Loki HTTP API `/loki/api/v1/query_range` and `/loki/api/v1/labels`.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct LokiQueryParam {
+    pub query: Option<String>,
+    // Nanosecond unix timestamp, unix timestamp in seconds or RFC3339
+    pub start: Option<String>,
+    pub end: Option<String>,
+    // Query resolution step width of metric queries, duration or float number of seconds
+    pub step: Option<String>,
+    // Max number of log lines returned by log queries
+    pub limit: Option<usize>,
+    // forward or backward
+    pub direction: Option<String>,
+    pub tenant: Option<String>,
+    pub db: Option<String>,
+    // Table holding the logs
+    pub table: Option<String>,
+    // Column holding the log line, msg by default
+    pub line_column: Option<String>,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub struct WriteParam {
@@ -50,6 +88,13 @@ pub struct GetOperationParam {
     pub span_kind: Option<String>,
 }
 
+#[derive(Debug, Deserialize, Serialize)]
+pub struct GetDependenciesParam {
+    #[serde(rename = "endTs")]
+    pub end_ts: Option<i64>, // endTs=xxx (msec since epoch)
+    pub lookback: Option<i64>, // lookback=xxx (msec)
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct FindTracesParam {
     pub service: Option<String>,   // service=xxx
@@ -0,0 +1,189 @@
+//! Audit records of the DDL, DCL and query activity.
+//!
+//! The records are passed to an [`AuditSink`], which decides where they are stored, e.g. the
+//! query servers store them in the `cluster_schema.audit_log` table.
+
+use std::collections::HashSet;
+use std::fmt::Display;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use config::common::AuditConfig;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AuditCategory {
+    /// Definition of the databases, tables, tenants and other objects
+    Ddl,
+    /// Users, roles, privileges and row-level security policies
+    Dcl,
+    /// Queries and writes of the data
+    Query,
+}
+
+impl AuditCategory {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ddl => "ddl",
+            Self::Dcl => "dcl",
+            Self::Query => "query",
+        }
+    }
+}
+
+impl Display for AuditCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for AuditCategory {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "ddl" => Ok(Self::Ddl),
+            "dcl" => Ok(Self::Dcl),
+            "query" => Ok(Self::Query),
+            _ => Err(format!(
+                "unknown audit category '{}', expected 'ddl', 'dcl' or 'query'",
+                s
+            )),
+        }
+    }
+}
+
+/// The audited categories of the config, unknown categories are ignored.
+pub fn audit_categories(config: &AuditConfig) -> HashSet<AuditCategory> {
+    if !config.enable {
+        return HashSet::new();
+    }
+    config
+        .categories
+        .iter()
+        .filter_map(|category| category.parse().ok())
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditRecord {
+    /// Nanosecond timestamp of the end of the statement
+    pub time: i64,
+    pub category: AuditCategory,
+    pub tenant: Option<String>,
+    pub user: Option<String>,
+    pub client_addr: Option<String>,
+    pub statement: String,
+    /// The error message if the statement failed
+    pub error: Option<String>,
+    pub duration: Duration,
+}
+
+impl AuditRecord {
+    pub fn is_success(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+pub trait AuditSink: Send + Sync {
+    /// Whether the statements of the category are audited
+    fn is_enabled(&self, category: AuditCategory) -> bool;
+
+    /// Store the record, must not block the caller.
+    fn record(&self, record: AuditRecord);
+}
+
+pub type AuditSinkRef = Arc<dyn AuditSink>;
+
+const PASSWORD_MASK: &str = "*****";
+
+/// Replace the quoted values of the `PASSWORD` options in the statement with `*****`,
+/// so that the passwords of `CREATE USER` and `ALTER USER` are not stored in the audit log.
+pub fn mask_passwords(statement: &str) -> String {
+    const KEYWORD: &str = "password";
+
+    let lower = statement.to_ascii_lowercase();
+    let mut masked = String::with_capacity(statement.len());
+    let mut pos = 0;
+    while let Some(offset) = lower[pos..].find(KEYWORD) {
+        let keyword_end = pos + offset + KEYWORD.len();
+        masked.push_str(&statement[pos..keyword_end]);
+        pos = keyword_end;
+
+        let rest = &statement[pos..];
+        let value_start = rest.len() - rest.trim_start().len();
+        let Some(after_eq) = rest[value_start..].strip_prefix('=') else {
+            continue;
+        };
+        let after_eq_trimmed = after_eq.trim_start();
+        let Some(quote) = after_eq_trimmed
+            .chars()
+            .next()
+            .filter(|c| *c == '\'' || *c == '"')
+        else {
+            continue;
+        };
+        let quoted = &after_eq_trimmed[1..];
+        let Some(value_len) = quoted.find(quote) else {
+            continue;
+        };
+
+        let prefix_len = rest.len() - after_eq_trimmed.len();
+        masked.push_str(&rest[..prefix_len]);
+        masked.push(quote);
+        masked.push_str(PASSWORD_MASK);
+        masked.push(quote);
+        pos += prefix_len + 1 + value_len + 1;
+    }
+    masked.push_str(&statement[pos..]);
+    masked
+}
+
+#[cfg(test)]
+mod test {
+    use config::common::AuditConfig;
+
+    use super::{audit_categories, mask_passwords, AuditCategory};
+
+    #[test]
+    fn test_parse_category() {
+        assert_eq!("DDL".parse::<AuditCategory>(), Ok(AuditCategory::Ddl));
+        assert_eq!("dcl".parse::<AuditCategory>(), Ok(AuditCategory::Dcl));
+        assert_eq!("Query".parse::<AuditCategory>(), Ok(AuditCategory::Query));
+        assert!("dml".parse::<AuditCategory>().is_err());
+    }
+
+    #[test]
+    fn test_audit_categories() {
+        let mut config = AuditConfig::default();
+        assert!(audit_categories(&config).is_empty());
+
+        config.enable = true;
+        config.categories = vec!["query".to_string(), "unknown".to_string()];
+        let categories = audit_categories(&config);
+        assert_eq!(categories.len(), 1);
+        assert!(categories.contains(&AuditCategory::Query));
+    }
+
+    #[test]
+    fn test_mask_passwords() {
+        assert_eq!(
+            mask_passwords("CREATE USER u WITH PASSWORD='123456', comment = 'c'"),
+            "CREATE USER u WITH PASSWORD='*****', comment = 'c'"
+        );
+        assert_eq!(
+            mask_passwords("alter user u set password = \"abc'd\""),
+            "alter user u set password = \"*****\""
+        );
+        assert_eq!(
+            mask_passwords("SELECT password FROM t WHERE password = 'x'"),
+            "SELECT password FROM t WHERE password = '*****'"
+        );
+        assert_eq!(
+            mask_passwords("ALTER USER u SET must_change_password = true"),
+            "ALTER USER u SET must_change_password = true"
+        );
+        assert_eq!(mask_passwords("password='unclosed"), "password='unclosed");
+    }
+}
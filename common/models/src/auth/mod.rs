@@ -3,7 +3,7 @@ use openssl::error::ErrorStack;
 pub use password::{bcrypt_hash, bcrypt_verify};
 use snafu::{Backtrace, Location, Snafu};
 
-use crate::auth::privilege::DatabasePrivilege;
+use crate::auth::privilege::{DatabasePrivilege, TablePrivilege};
 
 pub mod auth_cache;
 mod password;
@@ -60,12 +60,28 @@ pub enum AuthError {
         role: String,
     },
 
+    #[snafu(display(
+        "The privilege {:?} on table {}.{} not found in the role",
+        privilege.privilege,
+        db,
+        table
+    ))]
+    TablePrivilegeNotFound {
+        db: String,
+        table: String,
+        privilege: TablePrivilege,
+        role: String,
+    },
+
     #[snafu(display("The user {} already exists", user))]
     UserAlreadyExists { user: String },
 
     #[snafu(display("The user {} not found", user))]
     UserNotFound { user: String },
 
+    #[snafu(display("Authentication by {} failed: {}", provider, err))]
+    ExternalAuthentication { provider: String, err: String },
+
     #[snafu(display("{}", err))]
     Metadata { err: String },
 
@@ -1,3 +1,4 @@
+use std::collections::BTreeSet;
 use std::fmt::Display;
 use std::hash::Hash;
 
@@ -120,6 +121,8 @@ pub enum TenantObjectPrivilege {
     // T: database_name
     // None: all databases in this tenant
     Database(DatabasePrivilege, Option<String>),
+    // database_name, table_name
+    Table(TablePrivilege, String, String),
 }
 
 impl Display for TenantObjectPrivilege {
@@ -142,6 +145,19 @@ impl Display for TenantObjectPrivilege {
                     write!(f, "{:?} on all databases", p)
                 }
             },
+            Self::Table(p, db, table) => match &p.columns {
+                Some(columns) => {
+                    let columns = columns.iter().cloned().collect::<Vec<_>>().join(", ");
+                    write!(
+                        f,
+                        "{:?} on columns ({}) of table {}.{}",
+                        p.privilege, columns, db, table
+                    )
+                }
+                None => {
+                    write!(f, "{:?} on table {}.{}", p.privilege, db, table)
+                }
+            },
         }
     }
 }
@@ -156,11 +172,45 @@ impl PrivilegeChecker for TenantObjectPrivilege {
             (Self::Database(s, Some(s_t)), Self::Database(o, Some(o_t))) => {
                 s_t == o_t && s.check_privilege(o)
             }
+            (Self::Database(s, None), Self::Table(o, _, _)) => s.check_privilege(&o.privilege),
+            (Self::Database(s, Some(s_db)), Self::Table(o, o_db, _)) => {
+                s_db == o_db && s.check_privilege(&o.privilege)
+            }
+            (Self::Table(s, s_db, s_t), Self::Table(o, o_db, o_t)) => {
+                s_db == o_db && s_t == o_t && s.check_privilege(o)
+            }
             (l, r) => l == r,
         }
     }
 }
 
+/// The privilege on a table, which may only allow some columns of the table.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TablePrivilege {
+    pub privilege: DatabasePrivilege,
+    // Some: the allowed columns
+    // None: all columns of the table
+    pub columns: Option<BTreeSet<String>>,
+}
+
+impl TablePrivilege {
+    pub fn new(privilege: DatabasePrivilege, columns: Option<BTreeSet<String>>) -> Self {
+        Self { privilege, columns }
+    }
+}
+
+impl PrivilegeChecker for TablePrivilege {
+    fn check_privilege(&self, other: &Self) -> bool {
+        let columns_allowed = match (&self.columns, &other.columns) {
+            (None, _) => true,
+            (Some(s), Some(o)) => o.is_subset(s),
+            (Some(_), None) => false,
+        };
+
+        columns_allowed && self.privilege.check_privilege(&other.privilege)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum DatabasePrivilege {
     Read,
@@ -188,3 +238,49 @@ impl PrivilegeChecker for DatabasePrivilege {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeSet;
+
+    use super::{DatabasePrivilege, PrivilegeChecker, TablePrivilege, TenantObjectPrivilege};
+
+    fn table(privilege: DatabasePrivilege, columns: Option<&[&str]>) -> TenantObjectPrivilege {
+        let columns = columns.map(|c| c.iter().map(|c| c.to_string()).collect::<BTreeSet<_>>());
+        TenantObjectPrivilege::Table(
+            TablePrivilege::new(privilege, columns),
+            "db".to_string(),
+            "t".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_table_privilege() {
+        let db_read =
+            TenantObjectPrivilege::Database(DatabasePrivilege::Read, Some("db".to_string()));
+        let other_db_read =
+            TenantObjectPrivilege::Database(DatabasePrivilege::Read, Some("db2".to_string()));
+        let all_db_write = TenantObjectPrivilege::Database(DatabasePrivilege::Write, None);
+
+        let read_a = table(DatabasePrivilege::Read, Some(&["a"]));
+        let read_ab = table(DatabasePrivilege::Read, Some(&["a", "b"]));
+        let read_all = table(DatabasePrivilege::Read, None);
+        let write_all = table(DatabasePrivilege::Write, None);
+
+        assert!(db_read.check_privilege(&read_ab));
+        assert!(db_read.check_privilege(&read_all));
+        assert!(!db_read.check_privilege(&write_all));
+        assert!(!other_db_read.check_privilege(&read_a));
+        assert!(all_db_write.check_privilege(&write_all));
+
+        assert!(read_ab.check_privilege(&read_a));
+        assert!(!read_a.check_privilege(&read_ab));
+        assert!(!read_ab.check_privilege(&read_all));
+        assert!(read_all.check_privilege(&read_ab));
+        assert!(!read_all.check_privilege(&write_all));
+        assert!(write_all.check_privilege(&read_a));
+
+        // Table privileges never grant the privileges on the database
+        assert!(!read_all.check_privilege(&db_read));
+    }
+}
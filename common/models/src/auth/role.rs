@@ -6,7 +6,9 @@ use std::sync::Arc;
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 
-use super::privilege::{DatabasePrivilege, GlobalPrivilege, Privilege, TenantObjectPrivilege};
+use super::privilege::{
+    DatabasePrivilege, GlobalPrivilege, Privilege, TablePrivilege, TenantObjectPrivilege,
+};
 use super::AuthResult;
 use crate::auth::AuthError;
 use crate::oid::{Id, Identifier};
@@ -161,6 +163,9 @@ pub struct CustomTenantRole<T> {
     // database_name -> privileges
     // only add database privilege
     additional_privileges: HashMap<String, DatabasePrivilege>,
+    // database_name -> table_name -> privileges
+    #[serde(default)]
+    table_privileges: HashMap<String, HashMap<String, TablePrivilege>>,
 }
 
 impl<T> CustomTenantRole<T> {
@@ -177,6 +182,7 @@ impl<T> CustomTenantRole<T> {
             name,
             system_role,
             additional_privileges,
+            table_privileges: HashMap::new(),
         }
     }
 
@@ -187,6 +193,10 @@ impl<T> CustomTenantRole<T> {
     pub fn additional_privileges(&self) -> &HashMap<String, DatabasePrivilege> {
         &self.additional_privileges
     }
+
+    pub fn table_privileges(&self) -> &HashMap<String, HashMap<String, TablePrivilege>> {
+        &self.table_privileges
+    }
 }
 
 impl<T: Id> CustomTenantRole<T> {
@@ -207,7 +217,28 @@ impl<T: Id> CustomTenantRole<T> {
             })
             .collect::<HashSet<Privilege<T>>>();
 
-        privileges.union(&additiona_privileges).cloned().collect()
+        let table_privileges = self
+            .table_privileges
+            .iter()
+            .flat_map(|(db_name, tables)| {
+                tables.iter().map(|(table_name, privilege)| {
+                    Privilege::TenantObject(
+                        TenantObjectPrivilege::Table(
+                            privilege.clone(),
+                            db_name.clone(),
+                            table_name.clone(),
+                        ),
+                        Some(tenant_id.clone()),
+                    )
+                })
+            })
+            .collect::<HashSet<Privilege<T>>>();
+
+        privileges
+            .union(&additiona_privileges)
+            .cloned()
+            .chain(table_privileges)
+            .collect()
     }
 
     pub fn grant_privilege(
@@ -245,6 +276,48 @@ impl<T: Id> CustomTenantRole<T> {
     }
 }
 
+impl<T> CustomTenantRole<T> {
+    /// Replace the privilege on the table if it exists
+    pub fn grant_table_privilege(
+        &mut self,
+        database_name: String,
+        table_name: String,
+        privilege: TablePrivilege,
+    ) -> AuthResult<()> {
+        self.table_privileges
+            .entry(database_name)
+            .or_default()
+            .insert(table_name, privilege);
+
+        Ok(())
+    }
+
+    pub fn revoke_table_privilege(
+        &mut self,
+        database_name: &str,
+        table_name: &str,
+        privilege: &TablePrivilege,
+    ) -> AuthResult<bool> {
+        let tables = match self.table_privileges.get_mut(database_name) {
+            Some(tables) if tables.get(table_name) == Some(privilege) => tables,
+            _ => {
+                return Err(AuthError::TablePrivilegeNotFound {
+                    db: database_name.to_string(),
+                    table: table_name.to_string(),
+                    privilege: privilege.to_owned(),
+                    role: self.name.to_owned(),
+                })
+            }
+        };
+
+        let removed = tables.remove(table_name).is_some();
+        if tables.is_empty() {
+            self.table_privileges.remove(database_name);
+        }
+        Ok(removed)
+    }
+}
+
 impl<T> Identifier<T> for CustomTenantRole<T> {
     fn id(&self) -> &T {
         &self.id
@@ -6,7 +6,8 @@ use derive_builder::Builder;
 use serde::{Deserialize, Serialize};
 
 use super::privilege::{
-    DatabasePrivilege, GlobalPrivilege, Privilege, PrivilegeChecker, TenantObjectPrivilege,
+    DatabasePrivilege, GlobalPrivilege, Privilege, PrivilegeChecker, TablePrivilege,
+    TenantObjectPrivilege,
 };
 use super::role::{TenantRoleIdentifier, UserRole};
 use super::{rsa_utils, AuthError, AuthResult};
@@ -71,6 +72,25 @@ impl User {
         );
         self.check_privilege(&privilege)
     }
+
+    /// The row-level security policies of the table do not apply to the user
+    /// with the full privilege on the table.
+    pub fn can_bypass_row_policies(
+        &self,
+        tenant_id: Oid,
+        database_name: &str,
+        table_name: &str,
+    ) -> bool {
+        let privilege = Privilege::TenantObject(
+            TenantObjectPrivilege::Table(
+                TablePrivilege::new(DatabasePrivilege::Full, None),
+                database_name.to_string(),
+                table_name.to_string(),
+            ),
+            Some(tenant_id),
+        );
+        self.check_privilege(&privilege)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
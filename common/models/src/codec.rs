@@ -1,3 +1,6 @@
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+use std::hash::{Hash, Hasher};
 use std::str::FromStr;
 
 use serde::{Deserialize, Serialize};
@@ -14,14 +17,16 @@ pub const BIGINT_CODEC: [Encoding; 5] = [
 pub const TIMESTAMP_CODEC: [Encoding; 5] = BIGINT_CODEC;
 pub const UNSIGNED_BIGINT_CODEC: [Encoding; 5] = BIGINT_CODEC;
 
-pub const DOUBLE_CODEC: [Encoding; 4] = [
+pub const DOUBLE_CODEC: [Encoding; 6] = [
     Encoding::Default,
     Encoding::Null,
     Encoding::Gorilla,
     Encoding::Quantile,
+    Encoding::Chimp,
+    Encoding::Alp,
 ];
 
-pub const STRING_CODEC: [Encoding; 7] = [
+pub const STRING_CODEC: [Encoding; 8] = [
     Encoding::Default,
     Encoding::Null,
     Encoding::Gzip,
@@ -29,28 +34,85 @@ pub const STRING_CODEC: [Encoding; 7] = [
     Encoding::Zstd,
     Encoding::Snappy,
     Encoding::Zlib,
+    Encoding::ZstdDict,
 ];
 
 pub const BOOLEAN_CODEC: [Encoding; 3] = [Encoding::Default, Encoding::Null, Encoding::BitPack];
 
+/// Maximum absolute error a lossy codec may introduce into a value.
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct CodecTolerance(f64);
+
+impl CodecTolerance {
+    pub fn new(tolerance: f64) -> Result<Self, String> {
+        if tolerance.is_finite() && tolerance >= 0.0 {
+            Ok(Self(tolerance))
+        } else {
+            Err(format!(
+                "codec tolerance must be a non-negative finite number, got {tolerance}"
+            ))
+        }
+    }
+
+    pub fn value(&self) -> f64 {
+        self.0
+    }
+}
+
+impl PartialEq for CodecTolerance {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.to_bits() == other.0.to_bits()
+    }
+}
+
+impl Eq for CodecTolerance {}
+
+impl Hash for CodecTolerance {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.to_bits().hash(state)
+    }
+}
+
+impl PartialOrd for CodecTolerance {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for CodecTolerance {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+/// Encodings are persisted by the index of their variants, so new variants must be
+/// appended. The identifier written to encoded pages is [`Encoding::id`].
 #[derive(
     Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize, Hash, Default, Ord, PartialOrd,
 )]
 pub enum Encoding {
     #[default]
-    Default = 0,
-    Null = 1,
-    Delta = 2,
-    Quantile = 3,
-    Gzip = 4,
-    Bzip = 5,
-    Gorilla = 6,
-    Snappy = 7,
-    Zstd = 8,
-    Zlib = 9,
-    BitPack = 10,
-    DeltaTs = 11,
-    Unknown = 15,
+    Default,
+    Null,
+    Delta,
+    Quantile,
+    Gzip,
+    Bzip,
+    Gorilla,
+    Snappy,
+    Zstd,
+    Zlib,
+    BitPack,
+    DeltaTs,
+    Unknown,
+    Chimp,
+    Alp,
+    ZstdDict,
+    /// Lossy, Swinging Door Trending with the given tolerance.
+    Sdt(CodecTolerance),
+    /// Lossy, deadband filter with the given tolerance.
+    Deadband(CodecTolerance),
 }
 
 impl Encoding {
@@ -67,7 +129,43 @@ impl Encoding {
     }
 
     pub fn is_double_encoding(&self) -> bool {
-        DOUBLE_CODEC.contains(self)
+        DOUBLE_CODEC.contains(self) || self.is_lossy()
+    }
+
+    /// Lossy encodings only keep values within their tolerance.
+    pub fn is_lossy(&self) -> bool {
+        matches!(self, Encoding::Sdt(_) | Encoding::Deadband(_))
+    }
+
+    pub fn tolerance(&self) -> Option<CodecTolerance> {
+        match self {
+            Encoding::Sdt(t) | Encoding::Deadband(t) => Some(*t),
+            _ => None,
+        }
+    }
+
+    /// The identifier written as the first byte of an encoded page.
+    pub fn id(&self) -> u8 {
+        match self {
+            Encoding::Default => 0,
+            Encoding::Null => 1,
+            Encoding::Delta => 2,
+            Encoding::Quantile => 3,
+            Encoding::Gzip => 4,
+            Encoding::Bzip => 5,
+            Encoding::Gorilla => 6,
+            Encoding::Snappy => 7,
+            Encoding::Zstd => 8,
+            Encoding::Zlib => 9,
+            Encoding::BitPack => 10,
+            Encoding::DeltaTs => 11,
+            Encoding::Chimp => 12,
+            Encoding::Alp => 13,
+            Encoding::ZstdDict => 14,
+            Encoding::Unknown => 15,
+            Encoding::Sdt(_) => 16,
+            Encoding::Deadband(_) => 17,
+        }
     }
 
     pub fn is_string_encoding(&self) -> bool {
@@ -92,7 +190,21 @@ impl Encoding {
             Encoding::Zstd => "ZSTD",
             Encoding::Zlib => "ZLIB",
             Encoding::BitPack => "BITPACK",
+            Encoding::Chimp => "CHIMP",
+            Encoding::Alp => "ALP",
+            Encoding::ZstdDict => "ZSTD_DICT",
             Encoding::Unknown => "UNKNOWN",
+            Encoding::Sdt(_) => "SDT",
+            Encoding::Deadband(_) => "DEADBAND",
+        }
+    }
+}
+
+impl Display for Encoding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.tolerance() {
+            Some(t) => write!(f, "{}({})", self.as_str(), t.value()),
+            None => write!(f, "{}", self.as_str()),
         }
     }
 }
@@ -113,7 +225,26 @@ impl FromStr for Encoding {
             "ZSTD" => Ok(Self::Zstd),
             "ZLIB" => Ok(Self::Zlib),
             "BITPACK" => Ok(Self::BitPack),
-            _ => Err(s.to_string()),
+            "CHIMP" => Ok(Self::Chimp),
+            "ALP" => Ok(Self::Alp),
+            "ZSTD_DICT" => Ok(Self::ZstdDict),
+            upper => {
+                let (name, arg) = upper
+                    .strip_suffix(')')
+                    .and_then(|v| v.split_once('('))
+                    .ok_or_else(|| s.to_string())?;
+                let tolerance = arg
+                    .trim()
+                    .parse::<f64>()
+                    .ok()
+                    .and_then(|t| CodecTolerance::new(t).ok())
+                    .ok_or_else(|| s.to_string())?;
+                match name.trim() {
+                    "SDT" => Ok(Self::Sdt(tolerance)),
+                    "DEADBAND" => Ok(Self::Deadband(tolerance)),
+                    _ => Err(s.to_string()),
+                }
+            }
         }
     }
 }
@@ -133,7 +264,73 @@ impl From<u8> for Encoding {
             9 => Encoding::Zlib,
             10 => Encoding::BitPack,
             11 => Encoding::DeltaTs,
+            12 => Encoding::Chimp,
+            13 => Encoding::Alp,
+            14 => Encoding::ZstdDict,
+            // The tolerance only matters when encoding, pages decode without it.
+            16 => Encoding::Sdt(CodecTolerance::default()),
+            17 => Encoding::Deadband(CodecTolerance::default()),
             _ => Encoding::Unknown,
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::{CodecTolerance, Encoding};
+
+    #[test]
+    fn test_lossy_encoding_from_str() {
+        let sdt = Encoding::from_str("sdt(0.5)").unwrap();
+        assert_eq!(sdt, Encoding::Sdt(CodecTolerance::new(0.5).unwrap()));
+        assert_eq!(sdt.to_string(), "SDT(0.5)");
+        assert_eq!(Encoding::from_str(&sdt.to_string()).unwrap(), sdt);
+
+        let deadband = Encoding::from_str("DEADBAND( 2 )").unwrap();
+        assert_eq!(deadband.to_string(), "DEADBAND(2)");
+        assert!(deadband.is_double_encoding());
+        assert!(!deadband.is_string_encoding());
+        assert_eq!(Encoding::from(deadband.id()).as_str(), "DEADBAND");
+
+        assert!(Encoding::from_str("SDT(-1)").is_err());
+        assert!(Encoding::from_str("SDT(NaN)").is_err());
+        assert!(Encoding::from_str("GORILLA(1)").is_err());
+        assert_eq!(Encoding::Gorilla.to_string(), "GORILLA");
+    }
+
+    #[test]
+    fn test_encoding_persisted_format() {
+        // Encodings persisted by earlier versions must still decode to the same variants.
+        for (encoding, index) in [
+            (Encoding::Default, 0_u32),
+            (Encoding::Gorilla, 6),
+            (Encoding::DeltaTs, 11),
+            (Encoding::Unknown, 12),
+            (Encoding::Chimp, 13),
+            (Encoding::Alp, 14),
+            (Encoding::ZstdDict, 15),
+        ] {
+            let bytes = bincode::serialize(&encoding).unwrap();
+            assert_eq!(bytes, index.to_le_bytes(), "{encoding}");
+            assert_eq!(bincode::deserialize::<Encoding>(&bytes).unwrap(), encoding);
+        }
+    }
+
+    #[test]
+    fn test_lossy_encoding_persisted_format() {
+        let tolerance = CodecTolerance::new(0.5).unwrap();
+        for (encoding, index) in [
+            (Encoding::Sdt(tolerance), 16_u32),
+            (Encoding::Deadband(tolerance), 17),
+        ] {
+            let bytes = bincode::serialize(&encoding).unwrap();
+            let mut expected = index.to_le_bytes().to_vec();
+            expected.extend_from_slice(&0.5_f64.to_le_bytes());
+            assert_eq!(bytes, expected, "{encoding}");
+            assert_eq!(bincode::deserialize::<Encoding>(&bytes).unwrap(), encoding);
+            assert_eq!(Encoding::from(encoding.id()).as_str(), encoding.as_str());
+        }
+    }
+}
@@ -9,6 +9,7 @@ pub use value_type::{PhysicalDType, ValueType};
 
 pub mod arrow;
 pub mod arrow_array;
+pub mod audit;
 pub mod auth;
 pub mod codec;
 pub mod column_data;
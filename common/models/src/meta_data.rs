@@ -8,6 +8,8 @@ use crate::node_info::NodeStatus;
 use crate::oid::Oid;
 use crate::predicate::domain::TimeRange;
 use crate::schema::database_schema::DatabaseSchema;
+use crate::schema::function_schema::FunctionSchema;
+use crate::schema::policy_schema::PolicySchema;
 use crate::schema::resource_info::ResourceInfo;
 use crate::schema::table_schema::TableSchema;
 
@@ -243,6 +245,10 @@ pub struct TenantMetaData {
     pub dbs: HashMap<String, DatabaseInfo>,
     pub roles: HashMap<String, CustomTenantRole<Oid>>,
     pub members: HashMap<String, TenantRoleIdentifier>,
+    #[serde(default)]
+    pub functions: HashMap<String, FunctionSchema>,
+    #[serde(default)]
+    pub policies: HashMap<String, PolicySchema>,
 }
 
 impl TenantMetaData {
@@ -252,6 +258,8 @@ impl TenantMetaData {
             dbs: HashMap::new(),
             roles: HashMap::new(),
             members: HashMap::new(),
+            functions: HashMap::new(),
+            policies: HashMap::new(),
         }
     }
 
@@ -1435,12 +1435,175 @@ impl Display for Domain {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TagPatternKind {
+    /// SQL LIKE pattern with `%` and `_` wildcards and `\` escapes.
+    Like,
+    /// Regular expression, matching anywhere in the value unless anchored.
+    Regex,
+}
+
+/// A string predicate on a tag that cannot be expressed as a `Domain`,
+/// like `host LIKE 'web-%'` or `host ~ '^web-.*'`.
+///
+/// Series without a value for the tag never match, as the predicate is NULL.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TagPattern {
+    kind: TagPatternKind,
+    pattern: String,
+    case_insensitive: bool,
+    negated: bool,
+}
+
+impl TagPattern {
+    pub fn like(pattern: impl Into<String>, case_insensitive: bool, negated: bool) -> Self {
+        Self {
+            kind: TagPatternKind::Like,
+            pattern: pattern.into(),
+            case_insensitive,
+            negated,
+        }
+    }
+
+    pub fn regex(pattern: impl Into<String>, case_insensitive: bool, negated: bool) -> Self {
+        Self {
+            kind: TagPatternKind::Regex,
+            pattern: pattern.into(),
+            case_insensitive,
+            negated,
+        }
+    }
+
+    /// The literal prefix of every matching value, used to narrow the scanned tag values.
+    pub fn prefix(&self) -> Option<String> {
+        if self.negated || self.case_insensitive {
+            return None;
+        }
+        let prefix = match self.kind {
+            TagPatternKind::Like => like_literal_prefix(&self.pattern),
+            TagPatternKind::Regex => regex_literal_prefix(&self.pattern),
+        };
+        (!prefix.is_empty()).then_some(prefix)
+    }
+
+    /// Compiles the pattern, returns None if it is not a valid pattern.
+    pub fn matcher(&self) -> Option<TagPatternMatcher> {
+        let pattern = match self.kind {
+            TagPatternKind::Like => like_to_regex(&self.pattern),
+            TagPatternKind::Regex => self.pattern.clone(),
+        };
+        let regex = regex::bytes::RegexBuilder::new(&pattern)
+            .case_insensitive(self.case_insensitive)
+            .build()
+            .ok()?;
+        Some(TagPatternMatcher {
+            regex,
+            negated: self.negated,
+        })
+    }
+}
+
+impl Display for TagPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let op = match (self.kind, self.case_insensitive, self.negated) {
+            (TagPatternKind::Like, false, false) => "LIKE",
+            (TagPatternKind::Like, false, true) => "NOT LIKE",
+            (TagPatternKind::Like, true, false) => "ILIKE",
+            (TagPatternKind::Like, true, true) => "NOT ILIKE",
+            (TagPatternKind::Regex, false, false) => "~",
+            (TagPatternKind::Regex, false, true) => "!~",
+            (TagPatternKind::Regex, true, false) => "~*",
+            (TagPatternKind::Regex, true, true) => "!~*",
+        };
+        write!(f, "{op} '{}'", self.pattern)
+    }
+}
+
+pub struct TagPatternMatcher {
+    regex: regex::bytes::Regex,
+    negated: bool,
+}
+
+impl TagPatternMatcher {
+    pub fn is_match(&self, value: &[u8]) -> bool {
+        self.regex.is_match(value) != self.negated
+    }
+}
+
+fn like_literal_prefix(pattern: &str) -> String {
+    let mut prefix = String::new();
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '%' | '_' => break,
+            '\\' => match chars.next() {
+                Some(escaped) => prefix.push(escaped),
+                None => break,
+            },
+            c => prefix.push(c),
+        }
+    }
+    prefix
+}
+
+/// Only regular expressions anchored with `^` have a literal prefix,
+/// Prometheus matchers are anchored as `^(?:...)$`.
+fn regex_literal_prefix(pattern: &str) -> String {
+    let mut prefix = String::new();
+    // The branches of an alternation may not share the prefix.
+    if pattern.contains('|') {
+        return prefix;
+    }
+    let Some(rest) = pattern.strip_prefix('^') else {
+        return prefix;
+    };
+    let rest = rest.strip_prefix("(?:").unwrap_or(rest);
+
+    let mut chars = rest.chars().peekable();
+    while let Some(c) = chars.next() {
+        let literal = match c {
+            '\\' => match chars.next() {
+                Some(escaped) if escaped.is_ascii_punctuation() => escaped,
+                _ => break,
+            },
+            // The group may be optional, e.g. `^(?:web)?`
+            ')' if matches!(chars.peek(), Some('*' | '?' | '{')) => return String::new(),
+            '.' | '[' | ']' | '(' | ')' | '{' | '}' | '*' | '+' | '?' | '^' | '$' => break,
+            c => c,
+        };
+        // These quantifiers make the last literal optional.
+        if matches!(chars.peek(), Some('*' | '?' | '{')) {
+            break;
+        }
+        prefix.push(literal);
+    }
+    prefix
+}
+
+fn like_to_regex(pattern: &str) -> String {
+    let mut regex = String::from("(?s)^");
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '%' => regex.push_str(".*"),
+            '_' => regex.push('.'),
+            '\\' => match chars.next() {
+                Some(escaped) => regex.push_str(&regex::escape(escaped.encode_utf8(&mut [0; 4]))),
+                None => regex.push_str(r"\\"),
+            },
+            c => regex.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+        }
+    }
+    regex.push('$');
+    regex
+}
+
 /// ColumnDomains is internally represented as a normalized map of each column to its
 ///
 /// respective allowable value domain(ValueSet). Conceptually, these ValueSet can be thought of
 ///
 /// as being AND'ed together to form the representative predicate.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ColumnDomains<T>
 where
     T: Eq + Hash + Clone,
@@ -1448,6 +1611,9 @@ where
     // None means no matching record.
     // Empty map means match all records.
     column_to_domain: Option<HashMap<T, Domain>>,
+    // Patterns AND'ed with the domain of their column.
+    #[serde(default)]
+    column_to_patterns: HashMap<T, Vec<TagPattern>>,
 }
 
 impl<T: Eq + Hash + Clone> Default for ColumnDomains<T> {
@@ -1460,11 +1626,13 @@ impl<T: Eq + Hash + Clone> ColumnDomains<T> {
     pub fn all() -> Self {
         Self {
             column_to_domain: Some(HashMap::default()),
+            column_to_patterns: HashMap::default(),
         }
     }
     pub fn none() -> Self {
         Self {
             column_to_domain: None,
+            column_to_patterns: HashMap::default(),
         }
     }
     pub fn of(col: T, domain: &Domain) -> Self {
@@ -1476,9 +1644,15 @@ impl<T: Eq + Hash + Clone> ColumnDomains<T> {
         result
     }
 
+    pub fn of_pattern(col: T, pattern: TagPattern) -> Self {
+        let mut result = ColumnDomains::all();
+        result.column_to_patterns.insert(col, vec![pattern]);
+        result
+    }
+
     pub fn is_all(&self) -> bool {
         if let Some(map) = &self.column_to_domain {
-            return map.is_empty();
+            return map.is_empty() && self.column_to_patterns.is_empty();
         }
         false
     }
@@ -1508,9 +1682,15 @@ impl<T: Eq + Hash + Clone> ColumnDomains<T> {
                     .map(|(k, v)| (k.unwrap(), v))
                     .collect()
             });
+        let new_column_to_patterns: HashMap<U, Vec<TagPattern>> = self
+            .column_to_patterns
+            .iter()
+            .filter_map(|(k, v)| f(k).map(|k| (k, v.clone())))
+            .collect();
 
         ColumnDomains {
             column_to_domain: new_column_to_domain,
+            column_to_patterns: new_column_to_patterns,
         }
     }
 
@@ -1529,9 +1709,19 @@ impl<T: Eq + Hash + Clone> ColumnDomains<T> {
                         })
                         .or_insert_with(|| v2.clone());
                 });
+                // All patterns of a column must match
+                for (k2, p2) in other.column_to_patterns.iter() {
+                    let p1 = self.column_to_patterns.entry(k2.clone()).or_default();
+                    for pattern in p2 {
+                        if !p1.contains(pattern) {
+                            p1.push(pattern.clone());
+                        }
+                    }
+                }
             }
             (_, None) => {
                 self.column_to_domain = None;
+                self.column_to_patterns.clear();
             }
             (None, _) => {}
         }
@@ -1558,10 +1748,17 @@ impl<T: Eq + Hash + Clone> ColumnDomains<T> {
                     };
                 });
                 self.column_to_domain = Some(result);
+                // Only the patterns on both sides still hold for the union
+                self.column_to_patterns.retain(|k1, p1| {
+                    let p2 = other.column_to_patterns.get(k1);
+                    p1.retain(|p| p2.is_some_and(|p2| p2.contains(p)));
+                    !p1.is_empty()
+                });
             }
             (_, None) => {}
             (None, _) => {
                 self.column_to_domain = other.column_to_domain.clone();
+                self.column_to_patterns = other.column_to_patterns.clone();
             }
         }
     }
@@ -1605,6 +1802,27 @@ impl<T: Eq + Hash + Clone> ColumnDomains<T> {
     pub fn domains(&self) -> Option<&HashMap<T, Domain>> {
         self.column_to_domain.as_ref()
     }
+
+    /// Returns the patterns that the values of each column must all match,
+    /// in addition to its domain
+    pub fn patterns(&self) -> &HashMap<T, Vec<TagPattern>> {
+        &self.column_to_patterns
+    }
+}
+
+impl<T> std::fmt::Debug for ColumnDomains<T>
+where
+    T: Eq + Hash + Clone + std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut s = f.debug_struct("ColumnDomains");
+        s.field("column_to_domain", &self.column_to_domain);
+        // Omitted when empty, so plans without patterns are displayed as before
+        if !self.column_to_patterns.is_empty() {
+            s.field("column_to_patterns", &self.column_to_patterns);
+        }
+        s.finish()
+    }
 }
 
 impl<T> Display for ColumnDomains<T>
@@ -1614,13 +1832,18 @@ where
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{{ ")?;
         if let Some(ref column_to_domain) = self.column_to_domain {
-            let max_i = column_to_domain.len() - 1;
-            for (i, (column, domain)) in column_to_domain.iter().enumerate() {
-                write!(f, "{column}: {domain}")?;
-                if i < max_i {
-                    write!(f, ", ")?;
-                }
-            }
+            let items = column_to_domain
+                .iter()
+                .map(|(column, domain)| format!("{column}: {domain}"))
+                .chain(
+                    self.column_to_patterns
+                        .iter()
+                        .flat_map(|(column, patterns)| {
+                            patterns.iter().map(move |p| format!("{column} {p}"))
+                        }),
+                )
+                .collect::<Vec<_>>();
+            write!(f, "{}", items.join(", "))?;
         }
         write!(f, " }}")
     }
@@ -1726,6 +1949,16 @@ impl Predicate {
         self
     }
 
+    /// Limit the data to scan by the domains, the columns of the domains are not
+    /// required to be in the projection, and are matched by names.
+    pub fn intersect_domains(mut self, domains: &ColumnDomains<Column>) -> Self {
+        let unqualified = |c: &Column| Some(Column::from_name(&c.name));
+        self.pushed_down_domains = self.pushed_down_domains.translate_column(unqualified);
+        self.pushed_down_domains
+            .intersect(&domains.translate_column(unqualified));
+        self
+    }
+
     /// resolve and extract supported filter
     /// convert filter to ColumnDomains and set self
     pub fn push_down_filter(
@@ -2166,6 +2399,45 @@ mod tests {
         };
     }
 
+    #[test]
+    fn test_tag_pattern() {
+        let like = TagPattern::like(r"web\_%", false, false);
+        assert_eq!(like.prefix().as_deref(), Some("web_"));
+        let matcher = like.matcher().unwrap();
+        assert!(matcher.is_match(b"web_01"));
+        assert!(!matcher.is_match(b"web-01"));
+        assert!(!matcher.is_match(b"aweb_01"));
+
+        let not_like = TagPattern::like("%01", false, true);
+        assert_eq!(not_like.prefix(), None);
+        let matcher = not_like.matcher().unwrap();
+        assert!(matcher.is_match(b"web-02"));
+        assert!(!matcher.is_match(b"web-01"));
+
+        let regex = TagPattern::regex("^(?:web-0.*)$", false, false);
+        assert_eq!(regex.prefix().as_deref(), Some("web-0"));
+        assert!(regex.matcher().unwrap().is_match(b"web-01"));
+
+        assert_eq!(TagPattern::regex("web-.*", false, false).prefix(), None);
+        assert_eq!(TagPattern::regex("^web|^db", false, false).prefix(), None);
+        assert_eq!(
+            TagPattern::regex(r"^web\.s?", false, false)
+                .prefix()
+                .as_deref(),
+            Some("web.")
+        );
+        assert_eq!(
+            TagPattern::regex("^(?:web)?db", false, false).prefix(),
+            None
+        );
+        assert_eq!(TagPattern::regex("^WEB", true, false).prefix(), None);
+        assert!(TagPattern::regex("^WEB", true, false)
+            .matcher()
+            .unwrap()
+            .is_match(b"web"));
+        assert!(TagPattern::regex("(", false, false).matcher().is_none());
+    }
+
     #[test]
     fn test_serialize_physical_expr_node_wrap() {
         let expr = create_physical_expr(
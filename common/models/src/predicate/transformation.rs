@@ -5,11 +5,11 @@ use datafusion::arrow::datatypes::DataType;
 use datafusion::common::tree_node::{TreeNode, TreeNodeVisitor, VisitRecursion};
 use datafusion::common::Result as DFResult;
 use datafusion::error::DataFusionError;
-use datafusion::logical_expr::{BinaryExpr, Operator};
+use datafusion::logical_expr::{BinaryExpr, Like, Operator};
 use datafusion::prelude::{Column, Expr};
 use datafusion::scalar::ScalarValue;
 
-use super::domain::{ColumnDomains, Domain, Range};
+use super::domain::{ColumnDomains, Domain, Range, TagPattern};
 use crate::schema::TIME_FIELD_NAME;
 
 type Result<T> = result::Result<T, DataFusionError>;
@@ -187,11 +187,7 @@ impl TreeNodeVisitor for RowExpressionToDomainsVisitor<'_> {
             // | Expr::QualifiedWildcard { .. }
             // | Expr::GetIndexedField { .. } => {}
             Expr::Column(_) | Expr::Literal(_) => Ok(VisitRecursion::Continue),
-            Expr::BinaryExpr(BinaryExpr {
-                left: _,
-                op,
-                right: _,
-            }) => {
+            Expr::BinaryExpr(BinaryExpr { left, op, right }) => {
                 match op {
                     Operator::Eq
                     | Operator::NotEq
@@ -204,6 +200,20 @@ impl TreeNodeVisitor for RowExpressionToDomainsVisitor<'_> {
                         // support
                         Ok(VisitRecursion::Continue)
                     }
+                    Operator::RegexMatch
+                    | Operator::RegexIMatch
+                    | Operator::RegexNotMatch
+                    | Operator::RegexNotIMatch => {
+                        let case_insensitive =
+                            matches!(op, Operator::RegexIMatch | Operator::RegexNotIMatch);
+                        let negated =
+                            matches!(op, Operator::RegexNotMatch | Operator::RegexNotIMatch);
+                        let domains = Self::pattern_to_column_domains(left, right, |p| {
+                            TagPattern::regex(p, case_insensitive, negated)
+                        });
+                        self.ctx.current_domain_stack.push_back(domains);
+                        Ok(VisitRecursion::Skip)
+                    }
                     _ => {
                         // not support
                         self.ctx
@@ -213,10 +223,27 @@ impl TreeNodeVisitor for RowExpressionToDomainsVisitor<'_> {
                     }
                 }
             }
+            Expr::Like(like) | Expr::ILike(like) => {
+                let case_insensitive = matches!(expr, Expr::ILike(_));
+                let Like {
+                    negated,
+                    expr,
+                    pattern,
+                    escape_char,
+                } = like;
+                // Only the default escape character is supported
+                let domains = if matches!(escape_char, None | Some('\\')) {
+                    Self::pattern_to_column_domains(expr, pattern, |p| {
+                        TagPattern::like(p, case_insensitive, *negated)
+                    })
+                } else {
+                    ColumnDomains::all()
+                };
+                self.ctx.current_domain_stack.push_back(domains);
+                Ok(VisitRecursion::Skip)
+            }
             // TODO Currently not supported, follow-up support needs to implement the corresponding expression in post_visit
-            Expr::Like(_)
-            | Expr::ILike(_)
-            | Expr::SimilarTo(_)
+            Expr::SimilarTo(_)
             | Expr::Not(_)
             | Expr::IsNotNull(_)
             | Expr::IsNull(_)
@@ -295,6 +322,21 @@ impl RowExpressionToDomainsVisitor<'_> {
             .unwrap_or_else(ColumnDomains::all))
     }
 
+    /// Convert `column <pattern op> 'literal'` to a pattern on the column,
+    /// other expressions match all records.
+    fn pattern_to_column_domains(
+        expr: &Expr,
+        pattern: &Expr,
+        to_pattern: impl FnOnce(&str) -> TagPattern,
+    ) -> ColumnDomains<Column> {
+        match (expr, pattern) {
+            (Expr::Column(col), Expr::Literal(ScalarValue::Utf8(Some(pattern)))) => {
+                ColumnDomains::of_pattern(col.to_owned(), to_pattern(pattern))
+            }
+            _ => ColumnDomains::all(),
+        }
+    }
+
     /// Convert nsc to RangeValueSet
     ///
     /// Note: nsc must supports ordering, i.e. is_orderable == true
@@ -780,6 +822,7 @@ mod tests {
     ///   s1 like '%上证180' and time >= '2022-10-10 00:00:00'
     ///   ===>
     ///   time: ['2022-10-10 00:00:00', _)
+    ///   s1 LIKE '%上证180'
     #[test]
     fn test_simple_and_to_domain_0() {
         let filter1 = Expr::Like(Like::new(
@@ -801,7 +844,11 @@ mod tests {
 
         let i1_domain = Domain::of_ranges(&[i1]).unwrap();
 
-        let except_column_domains = ColumnDomains::of(Column::from_name("time"), &i1_domain);
+        let mut except_column_domains = ColumnDomains::of(Column::from_name("time"), &i1_domain);
+        except_column_domains.intersect(&ColumnDomains::of_pattern(
+            Column::from_name("s1"),
+            TagPattern::like("%上证180", false, false),
+        ));
 
         let result = get_domains(&and);
 
@@ -1072,6 +1119,87 @@ mod tests {
         );
     }
 
+    /// pattern test - 1
+    /// eg.
+    ///   s1 ~ '^web-.*' and s1 not like '%-01' and s2 = 'a'
+    ///   ===>
+    ///   s2: ['a', 'a']
+    ///   s1 ~ '^web-.*'
+    ///   s1 NOT LIKE '%-01'
+    #[test]
+    fn test_pattern_to_domain_1() {
+        let filter1 = binary_expr(col("s1"), Operator::RegexMatch, lit("^web-.*"));
+        let filter2 = Expr::Like(Like::new(
+            true,
+            Box::new(col("s1")),
+            Box::new(lit("%-01")),
+            None,
+        ));
+        let filter3 = binary_expr(col("s2"), Operator::Eq, lit("a"));
+
+        let and = and(and(filter1, filter2), filter3);
+
+        let column_domain = get_domains(&and).unwrap();
+
+        let s1 = Column::from_name("s1");
+        let s2 = Column::from_name("s2");
+        let s2_domain = Domain::of_ranges(&[Range::eq(
+            &DataType::Utf8,
+            &ScalarValue::Utf8(Some("a".to_string())),
+        )])
+        .unwrap();
+        assert_eq!(column_domain.domains().unwrap().get(&s2), Some(&s2_domain));
+        assert_eq!(
+            column_domain.patterns().get(&s1),
+            Some(&vec![
+                TagPattern::like("%-01", false, true),
+                TagPattern::regex("^web-.*", false, false),
+            ])
+        );
+    }
+
+    /// pattern test - 2
+    /// eg.
+    ///   s1 ~* 'web' or s1 ilike 'web%'
+    ///   ===>
+    ///   All
+    ///
+    ///   s1 like 'web%' or s1 like 'web%'
+    ///   ===>
+    ///   s1 LIKE 'web%'
+    #[test]
+    fn test_pattern_to_domain_2() {
+        let filter1 = binary_expr(col("s1"), Operator::RegexIMatch, lit("web"));
+        let filter2 = Expr::ILike(Like::new(
+            false,
+            Box::new(col("s1")),
+            Box::new(lit("web%")),
+            None,
+        ));
+        let or_expr = or(filter1, filter2);
+        assert!(get_domains(&or_expr).unwrap().is_all());
+
+        let like = Expr::Like(Like::new(
+            false,
+            Box::new(col("s1")),
+            Box::new(lit("web%")),
+            None,
+        ));
+        let or_expr = or(like.clone(), like);
+        let column_domain = get_domains(&or_expr).unwrap();
+        assert_eq!(
+            column_domain,
+            ColumnDomains::of_pattern(
+                Column::from_name("s1"),
+                TagPattern::like("web%", false, false)
+            )
+        );
+
+        // The pattern is not a literal
+        let filter = binary_expr(col("s1"), Operator::RegexMatch, col("s2"));
+        assert!(get_domains(&filter).unwrap().is_all());
+    }
+
     /// delete selection expression test - 1
     /// eg.
     ///   cpu = 'cpu0' and time >= '2022-10-10 00:00:00' and time <= '2022-11-10 00:00:00'
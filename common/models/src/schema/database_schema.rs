@@ -111,6 +111,7 @@ pub struct DatabaseOptionsBuilder {
     shard_num: Option<u64>,
     vnode_duration: Option<CnosDuration>,
     replica: Option<u64>,
+    max_series: Option<u64>,
 }
 
 impl Default for DatabaseOptionsBuilder {
@@ -126,6 +127,7 @@ impl DatabaseOptionsBuilder {
             shard_num: None,
             vnode_duration: None,
             replica: None,
+            max_series: None,
         }
     }
 
@@ -149,6 +151,11 @@ impl DatabaseOptionsBuilder {
         self
     }
 
+    pub fn with_max_series(&mut self, max_series: u64) -> &mut Self {
+        self.max_series = Some(max_series);
+        self
+    }
+
     pub fn build(self) -> DatabaseOptions {
         let ttl = self.ttl.unwrap_or(DatabaseOptions::DEFAULT_TTL);
         let shard_num = self.shard_num.unwrap_or(DatabaseOptions::DEFAULT_SHARD_NUM);
@@ -156,7 +163,11 @@ impl DatabaseOptionsBuilder {
             .vnode_duration
             .unwrap_or(DatabaseOptions::DEFAULT_VNODE_DURATION);
         let replica = self.replica.unwrap_or(DatabaseOptions::DEFAULT_REPLICA);
-        DatabaseOptions::new(ttl, shard_num, vnode_duration, replica)
+        let mut options = DatabaseOptions::new(ttl, shard_num, vnode_duration, replica);
+        if let Some(max_series) = self.max_series {
+            options.set_max_series(max_series);
+        }
+        options
     }
 }
 
@@ -166,6 +177,9 @@ pub struct DatabaseOptions {
     shard_num: u64,
     vnode_duration: CnosDuration,
     replica: u64,
+    /// Maximum number of series of the database, 0 means unlimited.
+    #[serde(default)]
+    max_series: u64,
 }
 
 impl DatabaseOptions {
@@ -185,6 +199,7 @@ impl DatabaseOptions {
             shard_num,
             vnode_duration,
             replica,
+            max_series: 0,
         }
     }
 
@@ -220,6 +235,14 @@ impl DatabaseOptions {
         self.replica = replica;
     }
 
+    pub fn max_series(&self) -> u64 {
+        self.max_series
+    }
+
+    pub fn set_max_series(&mut self, max_series: u64) {
+        self.max_series = max_series;
+    }
+
     pub fn apply_builder(&mut self, builder: &DatabaseOptionsBuilder) {
         if let Some(ref ttl) = builder.ttl {
             self.ttl = ttl.clone();
@@ -233,6 +256,9 @@ impl DatabaseOptions {
         if let Some(replica) = builder.replica {
             self.replica = replica;
         }
+        if let Some(max_series) = builder.max_series {
+            self.max_series = max_series;
+        }
     }
 }
 
@@ -243,6 +269,7 @@ impl Default for DatabaseOptions {
             shard_num: DatabaseOptions::DEFAULT_SHARD_NUM,
             vnode_duration: DatabaseOptions::DEFAULT_VNODE_DURATION,
             replica: DatabaseOptions::DEFAULT_REPLICA,
+            max_series: 0,
         }
     }
 }
@@ -0,0 +1,130 @@
+//! User-defined functions created by `CREATE FUNCTION`, which are visible in their tenant.
+
+use std::fmt::{Display, Formatter};
+
+use arrow_schema::DataType;
+use serde::{Deserialize, Serialize};
+
+/// Fuel of a WebAssembly function for each batch of rows, about the number of executed instructions.
+pub const DEFAULT_WASM_FUEL: u64 = 100_000_000;
+/// Maximum size in bytes of the memories of a WebAssembly function instance.
+pub const DEFAULT_WASM_MAX_MEMORY: u64 = 16 * 1024 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FunctionSchema {
+    pub tenant: String,
+    pub name: String,
+    pub kind: FunctionKind,
+    pub args: Vec<FunctionArg>,
+    pub return_type: DataType,
+    pub body: FunctionBody,
+    /// Version of the meta data when the function was created or replaced,
+    /// which identifies the compiled module of a WASM function.
+    #[serde(default)]
+    pub version: u64,
+}
+
+impl FunctionSchema {
+    pub fn arg_types(&self) -> Vec<DataType> {
+        self.args.iter().map(|arg| arg.data_type.clone()).collect()
+    }
+
+    /// Arguments in the form of `x BIGINT, y DOUBLE`.
+    pub fn args_sql(&self) -> String {
+        self.args
+            .iter()
+            .map(|arg| format!("{} {}", arg.name, data_type_sql(&arg.data_type)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    pub fn language(&self) -> &'static str {
+        match self.body {
+            FunctionBody::Sql(_) => "SQL",
+            FunctionBody::Wasm(_) => "WASM",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FunctionKind {
+    Scalar,
+    Aggregate,
+}
+
+impl Display for FunctionKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Scalar => write!(f, "SCALAR"),
+            Self::Aggregate => write!(f, "AGGREGATE"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FunctionArg {
+    pub name: String,
+    pub data_type: DataType,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FunctionBody {
+    /// SQL expression of the arguments, which replaces the calls of the function when planning.
+    Sql(String),
+    Wasm(WasmFunction),
+}
+
+/// A WebAssembly module executed in a sandbox.
+///
+/// A scalar function module exports `eval`, which is called with the arguments of each row.
+/// An aggregate function module exports `init() -> state`, `update(state, args...) -> state`
+/// and `merge(state, state) -> state`, the type of the state is the return type.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WasmFunction {
+    /// Base64 encoded binary of the module.
+    pub module: String,
+    pub fuel: u64,
+    pub max_memory: u64,
+}
+
+/// The SQL name of the data types supported by user-defined functions.
+pub fn data_type_sql(data_type: &DataType) -> String {
+    match data_type {
+        DataType::Int64 => "BIGINT".to_string(),
+        DataType::UInt64 => "BIGINT UNSIGNED".to_string(),
+        DataType::Float64 => "DOUBLE".to_string(),
+        DataType::Boolean => "BOOLEAN".to_string(),
+        DataType::Utf8 => "STRING".to_string(),
+        DataType::Timestamp(_, _) => "TIMESTAMP".to_string(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use arrow_schema::DataType;
+
+    use super::{FunctionArg, FunctionBody, FunctionKind, FunctionSchema};
+
+    #[test]
+    fn test_function_schema_serde() {
+        let function = FunctionSchema {
+            tenant: "cnosdb".to_string(),
+            name: "f_to_c".to_string(),
+            kind: FunctionKind::Scalar,
+            args: vec![FunctionArg {
+                name: "f".to_string(),
+                data_type: DataType::Float64,
+            }],
+            return_type: DataType::Float64,
+            body: FunctionBody::Sql("(f - 32) / 1.8".to_string()),
+            version: 0,
+        };
+        assert_eq!(function.args_sql(), "f DOUBLE");
+        assert_eq!(function.language(), "SQL");
+
+        let json = serde_json::to_string(&function).unwrap();
+        let decoded = serde_json::from_str::<FunctionSchema>(&json).unwrap();
+        assert_eq!(decoded, function);
+    }
+}
@@ -9,10 +9,13 @@
 
 pub mod database_schema;
 pub mod external_table_schema;
+pub mod function_schema;
+pub mod policy_schema;
 pub mod query_info;
 pub mod resource_info;
 pub mod stream_table_schema;
 pub mod table_schema;
+pub mod table_statistics;
 pub mod tenant;
 pub mod tskv_table_schema;
 
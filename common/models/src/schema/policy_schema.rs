@@ -0,0 +1,65 @@
+//! Row-level security policies created by `CREATE POLICY`, which are visible in their tenant.
+
+use serde::{Deserialize, Serialize};
+
+use crate::auth::role::TenantRoleIdentifier;
+
+/// Only the rows of the table matching the `using` expression are visible to the roles.
+///
+/// The policies of a table apply to the users without the full privilege on the table,
+/// if none of them applies to the role of a user, no rows are visible to the user.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicySchema {
+    pub tenant: String,
+    pub name: String,
+    pub database: String,
+    pub table: String,
+    // Empty: all roles
+    pub roles: Vec<String>,
+    // SQL expression of the columns of the table
+    pub using: String,
+}
+
+impl PolicySchema {
+    pub fn applies_to(&self, role: Option<&TenantRoleIdentifier>) -> bool {
+        if self.roles.is_empty() {
+            return true;
+        }
+
+        role.is_some_and(|role| self.roles.iter().any(|r| r == role.name()))
+    }
+
+    pub fn is_on_table(&self, database: &str, table: &str) -> bool {
+        self.database == database && self.table == table
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::PolicySchema;
+    use crate::auth::role::{SystemTenantRole, TenantRoleIdentifier};
+
+    #[test]
+    fn test_policy_applies_to() {
+        let mut policy = PolicySchema {
+            tenant: "cnosdb".to_string(),
+            name: "p".to_string(),
+            database: "public".to_string(),
+            table: "t".to_string(),
+            roles: vec![],
+            using: "tenant_tag = current_user()".to_string(),
+        };
+        let custom = TenantRoleIdentifier::Custom("r1".to_string());
+        let member = TenantRoleIdentifier::System(SystemTenantRole::Member);
+
+        assert!(policy.applies_to(None));
+        assert!(policy.applies_to(Some(&custom)));
+
+        policy.roles = vec!["r1".to_string()];
+        assert!(!policy.applies_to(None));
+        assert!(policy.applies_to(Some(&custom)));
+        assert!(!policy.applies_to(Some(&member)));
+        assert!(policy.is_on_table("public", "t"));
+        assert!(!policy.is_on_table("public", "t2"));
+    }
+}
@@ -0,0 +1,271 @@
+use std::collections::BTreeMap;
+
+use datafusion::arrow::datatypes::{Schema, TimeUnit};
+use datafusion::physical_plan::{ColumnStatistics as DFColumnStatistics, Statistics};
+use datafusion::scalar::ScalarValue;
+use serde::{Deserialize, Serialize};
+use snafu::ResultExt;
+
+use crate::errors::InvalidSerdeMessageSnafu;
+use crate::predicate::domain::{TimeRange, TimeRanges};
+use crate::schema::tskv_table_schema::{ColumnType, TskvTableSchema};
+use crate::{ColumnId, ModelResult};
+
+/// Statistics of a tskv table collected by `ANALYZE TABLE`, or of the table in some vnodes.
+///
+/// The numbers are estimated from the metadata of the data files and the index,
+/// and the table may be written after it is analyzed, so they are never exact.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableStatistics {
+    pub row_count: u64,
+    /// Time range of the rows, `None` if there is no row.
+    pub time_range: Option<TimeRange>,
+    /// Columns without statistics are absent.
+    pub columns: BTreeMap<ColumnId, ColumnStatistics>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ColumnStatistics {
+    pub distinct_count: Option<u64>,
+    pub null_count: Option<u64>,
+}
+
+impl TableStatistics {
+    pub fn encode(&self) -> ModelResult<Vec<u8>> {
+        bincode::serialize(self).context(InvalidSerdeMessageSnafu)
+    }
+
+    pub fn decode(buf: &[u8]) -> ModelResult<TableStatistics> {
+        bincode::deserialize::<TableStatistics>(buf).context(InvalidSerdeMessageSnafu)
+    }
+
+    /// Merge the statistics of the table in other vnodes.
+    ///
+    /// The rows of different replication sets are different, but the values of a column
+    /// may be the same, so the number of distinct values is the maximum of them.
+    pub fn merge(&mut self, other: &TableStatistics) {
+        self.row_count += other.row_count;
+        self.time_range = match (self.time_range, other.time_range) {
+            (Some(mut a), Some(b)) => {
+                a.merge(&b);
+                Some(a)
+            }
+            (a, b) => a.or(b),
+        };
+        for (column_id, other) in other.columns.iter() {
+            let column = self.columns.entry(*column_id).or_default();
+            column.distinct_count = match (column.distinct_count, other.distinct_count) {
+                (Some(a), Some(b)) => Some(a.max(b)),
+                (a, b) => a.or(b),
+            };
+            column.null_count = match (column.null_count, other.null_count) {
+                (Some(a), Some(b)) => Some(a + b),
+                (a, b) => a.or(b),
+            };
+        }
+    }
+
+    /// Convert to the statistics of a scan of the `projection` of the table in `time_ranges`,
+    /// assuming that the rows are evenly distributed in the time range of the table.
+    pub fn to_df_statistics(
+        &self,
+        table: &TskvTableSchema,
+        projection: &Schema,
+        time_ranges: &TimeRanges,
+    ) -> Statistics {
+        let (scanned_time_range, selectivity) = match self.time_range {
+            Some(time_range) => scanned_time_range(&time_range, time_ranges),
+            None => (None, 0.0),
+        };
+        let num_rows = (self.row_count as f64 * selectivity).round() as u64;
+
+        let column_statistics = projection
+            .fields()
+            .iter()
+            .map(|field| {
+                let Some(column) = table.column(field.name()) else {
+                    return DFColumnStatistics::default();
+                };
+                match &column.column_type {
+                    ColumnType::Time(unit) => DFColumnStatistics {
+                        null_count: Some(0),
+                        min_value: scanned_time_range
+                            .map(|range| timestamp_scalar(unit, range.min_ts)),
+                        max_value: scanned_time_range
+                            .map(|range| timestamp_scalar(unit, range.max_ts)),
+                        distinct_count: None,
+                    },
+                    _ => match self.columns.get(&column.id) {
+                        Some(statistics) => DFColumnStatistics {
+                            null_count: statistics.null_count.map(|count| {
+                                (count as f64 * selectivity).round().min(num_rows as f64) as usize
+                            }),
+                            min_value: None,
+                            max_value: None,
+                            distinct_count: statistics
+                                .distinct_count
+                                .map(|count| count.min(num_rows) as usize),
+                        },
+                        None => DFColumnStatistics::default(),
+                    },
+                }
+            })
+            .collect();
+
+        Statistics {
+            num_rows: Some(num_rows as usize),
+            total_byte_size: None,
+            column_statistics: Some(column_statistics),
+            is_exact: false,
+        }
+    }
+}
+
+/// The time range of the table scanned in `time_ranges`,
+/// and the ratio of the scanned time to the time range of the table.
+fn scanned_time_range(
+    table_time_range: &TimeRange,
+    time_ranges: &TimeRanges,
+) -> (Option<TimeRange>, f64) {
+    if time_ranges.is_boundless() {
+        return (Some(*table_time_range), 1.0);
+    }
+
+    let mut scanned: Option<TimeRange> = None;
+    let mut scanned_time = 0_f64;
+    for time_range in time_ranges.time_ranges() {
+        if let Some(range) = table_time_range.intersect(&time_range) {
+            scanned_time += (range.max_ts as f64 - range.min_ts as f64) + 1.0;
+            match scanned.as_mut() {
+                Some(scanned) => scanned.merge(&range),
+                None => scanned = Some(range),
+            }
+        }
+    }
+    let total_time = (table_time_range.max_ts as f64 - table_time_range.min_ts as f64) + 1.0;
+
+    (scanned, (scanned_time / total_time).min(1.0))
+}
+
+fn timestamp_scalar(unit: &TimeUnit, timestamp: i64) -> ScalarValue {
+    match unit {
+        TimeUnit::Second => ScalarValue::TimestampSecond(Some(timestamp), None),
+        TimeUnit::Millisecond => ScalarValue::TimestampMillisecond(Some(timestamp), None),
+        TimeUnit::Microsecond => ScalarValue::TimestampMicrosecond(Some(timestamp), None),
+        TimeUnit::Nanosecond => ScalarValue::TimestampNanosecond(Some(timestamp), None),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use datafusion::arrow::datatypes::TimeUnit;
+    use datafusion::scalar::ScalarValue;
+
+    use super::{ColumnStatistics, TableStatistics};
+    use crate::predicate::domain::{TimeRange, TimeRanges};
+    use crate::schema::tskv_table_schema::{ColumnType, TableColumn, TskvTableSchema};
+    use crate::ValueType;
+
+    fn statistics(
+        row_count: u64,
+        time_range: (i64, i64),
+        columns: Vec<(u32, Option<u64>, Option<u64>)>,
+    ) -> TableStatistics {
+        TableStatistics {
+            row_count,
+            time_range: Some(TimeRange::new(time_range.0, time_range.1)),
+            columns: columns
+                .into_iter()
+                .map(|(id, distinct_count, null_count)| {
+                    (
+                        id,
+                        ColumnStatistics {
+                            distinct_count,
+                            null_count,
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut merged = TableStatistics::default();
+        merged.merge(&statistics(10, (5, 20), vec![(1, Some(3), None), (2, None, Some(1))]));
+        merged.merge(&statistics(30, (0, 10), vec![(1, Some(2), None), (2, None, Some(4))]));
+        merged.merge(&TableStatistics::default());
+
+        assert_eq!(
+            merged,
+            statistics(40, (0, 20), vec![(1, Some(3), None), (2, None, Some(5))])
+        );
+    }
+
+    #[test]
+    fn test_encode_decode() {
+        let statistics = statistics(100, (-1, 1), vec![(1, Some(3), Some(0))]);
+        let buf = statistics.encode().unwrap();
+        assert_eq!(TableStatistics::decode(&buf).unwrap(), statistics);
+    }
+
+    #[test]
+    fn test_to_df_statistics() {
+        let table = TskvTableSchema::new(
+            "cnosdb".to_string(),
+            "public".to_string(),
+            "air".to_string(),
+            vec![
+                TableColumn::new_time_column(0, TimeUnit::Nanosecond),
+                TableColumn::new_tag_column(1, "station".to_string()),
+                TableColumn::new(
+                    2,
+                    "pressure".to_string(),
+                    ColumnType::Field(ValueType::Float),
+                    Default::default(),
+                ),
+            ],
+        );
+        let projection = table.to_arrow_schema();
+        let statistics = statistics(
+            1000,
+            (0, 999),
+            vec![(1, Some(20), None), (2, Some(900), Some(100))],
+        );
+
+        let all = statistics.to_df_statistics(&table, &projection, &TimeRanges::all());
+        assert_eq!(all.num_rows, Some(1000));
+        assert!(!all.is_exact);
+        let columns = all.column_statistics.unwrap();
+        assert_eq!(
+            columns[0].min_value,
+            Some(ScalarValue::TimestampNanosecond(Some(0), None))
+        );
+        assert_eq!(
+            columns[0].max_value,
+            Some(ScalarValue::TimestampNanosecond(Some(999), None))
+        );
+        assert_eq!(columns[1].distinct_count, Some(20));
+        assert_eq!(columns[2].distinct_count, Some(900));
+        assert_eq!(columns[2].null_count, Some(100));
+
+        let time_ranges = TimeRanges::new(vec![TimeRange::new(900, 2000)]);
+        let part = statistics.to_df_statistics(&table, &projection, &time_ranges);
+        assert_eq!(part.num_rows, Some(100));
+        let columns = part.column_statistics.unwrap();
+        assert_eq!(
+            columns[0].min_value,
+            Some(ScalarValue::TimestampNanosecond(Some(900), None))
+        );
+        assert_eq!(columns[1].distinct_count, Some(20));
+        assert_eq!(columns[2].distinct_count, Some(100));
+        assert_eq!(columns[2].null_count, Some(10));
+
+        let time_ranges = TimeRanges::new(vec![TimeRange::new(2000, 3000)]);
+        let none = statistics.to_df_statistics(&table, &projection, &time_ranges);
+        assert_eq!(none.num_rows, Some(0));
+        assert_eq!(none.column_statistics.unwrap()[0].min_value, None);
+    }
+}
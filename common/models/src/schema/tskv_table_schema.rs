@@ -17,11 +17,13 @@ use serde::de::{MapAccess, SeqAccess, Visitor};
 use serde::ser::SerializeStruct;
 use serde::{Deserialize, Serialize, Serializer};
 use snafu::ResultExt;
+use utils::duration::CnosDuration;
 use utils::precision::Precision;
 
 use crate::codec::Encoding;
 use crate::errors::{InternalSnafu, InvalidSerdeMessageSnafu};
 use crate::gis::data_type::{Geometry, GeometryType};
+use crate::schema::table_statistics::TableStatistics;
 use crate::schema::{
     COLUMN_ENCODING_META_KEY, COLUMN_ID_META_KEY, DATABASE_NAME, DEFAULT_CATALOG, DEFAULT_DATABASE,
     GIS_SRID_META_KEY, GIS_SUB_TYPE_META_KEY, IS_TAG, NEXT_COLUMN_ID, SCHEMA_VERSION, TABLE_NAME,
@@ -44,6 +46,12 @@ pub struct TskvTableSchema {
     //ColumnName -> ColumnsIndex
     columns_index: HashMap<String, usize>,
     fields_ids: HashMap<ColumnId, usize>,
+    /// Time to live of the data of the table, uses the TTL of database if not set.
+    ttl: Option<CnosDuration>,
+    /// Maximum number of series of the table, 0 means unlimited.
+    max_series: u64,
+    /// Statistics collected by the last `ANALYZE TABLE`.
+    statistics: Option<TableStatistics>,
 }
 
 impl Serialize for TskvTableSchema {
@@ -51,7 +59,7 @@ impl Serialize for TskvTableSchema {
     where
         S: Serializer,
     {
-        let mut state = serializer.serialize_struct("TskvTableSchema", 6)?;
+        let mut state = serializer.serialize_struct("TskvTableSchema", 10)?;
         state.serialize_field("tenant", &self.tenant)?;
         state.serialize_field("db", &self.db)?;
         state.serialize_field("name", &self.name)?;
@@ -59,6 +67,9 @@ impl Serialize for TskvTableSchema {
         state.serialize_field("next_column_id", &self.next_column_id)?;
         state.serialize_field("columns", &self.columns)?;
         state.serialize_field("columns_index", &self.columns_index)?;
+        state.serialize_field("ttl", &self.ttl)?;
+        state.serialize_field("max_series", &self.max_series)?;
+        state.serialize_field("statistics", &self.statistics)?;
         state.end()
     }
 }
@@ -101,6 +112,9 @@ impl<'de> Deserialize<'de> for TskvTableSchema {
                 let columns_index = seq
                     .next_element::<HashMap<String, usize>>()?
                     .ok_or_else(|| serde::de::Error::invalid_length(6, &self))?;
+                let ttl = seq.next_element::<Option<CnosDuration>>()?.flatten();
+                let max_series = seq.next_element::<u64>()?.unwrap_or_default();
+                let statistics = seq.next_element::<Option<TableStatistics>>()?.flatten();
                 let fields_ids = TskvTableSchema::build_fields_ids(&columns);
                 Ok(TskvTableSchema {
                     tenant,
@@ -111,6 +125,9 @@ impl<'de> Deserialize<'de> for TskvTableSchema {
                     columns,
                     columns_index,
                     fields_ids,
+                    ttl,
+                    max_series,
+                    statistics,
                 })
             }
 
@@ -125,6 +142,9 @@ impl<'de> Deserialize<'de> for TskvTableSchema {
                 let mut next_column_id = None;
                 let mut columns = None;
                 let mut columns_index = None;
+                let mut ttl = None;
+                let mut max_series = None;
+                let mut statistics = None;
                 while let Some(key) = map.next_key()? {
                     match key {
                         "tenant" => {
@@ -169,6 +189,24 @@ impl<'de> Deserialize<'de> for TskvTableSchema {
                             }
                             columns_index = Some(map.next_value::<HashMap<String, usize>>()?);
                         }
+                        "ttl" => {
+                            if ttl.is_some() {
+                                return Err(serde::de::Error::duplicate_field("ttl"));
+                            }
+                            ttl = Some(map.next_value::<Option<CnosDuration>>()?);
+                        }
+                        "max_series" => {
+                            if max_series.is_some() {
+                                return Err(serde::de::Error::duplicate_field("max_series"));
+                            }
+                            max_series = Some(map.next_value::<u64>()?);
+                        }
+                        "statistics" => {
+                            if statistics.is_some() {
+                                return Err(serde::de::Error::duplicate_field("statistics"));
+                            }
+                            statistics = Some(map.next_value::<Option<TableStatistics>>()?);
+                        }
                         _ => {
                             return Err(serde::de::Error::unknown_field(
                                 key,
@@ -180,6 +218,9 @@ impl<'de> Deserialize<'de> for TskvTableSchema {
                                     "next_column_id",
                                     "columns",
                                     "columns_index",
+                                    "ttl",
+                                    "max_series",
+                                    "statistics",
                                 ],
                             ))?;
                         }
@@ -208,6 +249,9 @@ impl<'de> Deserialize<'de> for TskvTableSchema {
                     columns,
                     columns_index,
                     fields_ids,
+                    ttl: ttl.flatten(),
+                    max_series: max_series.unwrap_or_default(),
+                    statistics: statistics.flatten(),
                 })
             }
         }
@@ -221,6 +265,9 @@ impl<'de> Deserialize<'de> for TskvTableSchema {
                 "next_column_id",
                 "columns",
                 "columns_index",
+                "ttl",
+                "max_series",
+                "statistics",
             ],
             TskvTableSchemaVisitor,
         )
@@ -254,6 +301,9 @@ impl Default for TskvTableSchema {
             columns: Default::default(),
             columns_index: Default::default(),
             fields_ids: Default::default(),
+            ttl: None,
+            max_series: 0,
+            statistics: None,
         }
     }
 }
@@ -328,6 +378,9 @@ impl TskvTableSchema {
             columns,
             columns_index,
             fields_ids,
+            ttl: None,
+            max_series: 0,
+            statistics: None,
         }
     }
 
@@ -510,6 +563,43 @@ impl TskvTableSchema {
         self.next_column_id
     }
 
+    pub fn ttl(&self) -> Option<&CnosDuration> {
+        self.ttl.as_ref()
+    }
+
+    pub fn set_ttl(&mut self, ttl: Option<CnosDuration>) {
+        self.ttl = ttl;
+    }
+
+    pub fn max_series(&self) -> u64 {
+        self.max_series
+    }
+
+    pub fn set_max_series(&mut self, max_series: u64) {
+        self.max_series = max_series;
+    }
+
+    pub fn statistics(&self) -> Option<&TableStatistics> {
+        self.statistics.as_ref()
+    }
+
+    pub fn set_statistics(&mut self, statistics: Option<TableStatistics>) {
+        self.statistics = statistics;
+    }
+
+    /// Returns the min timestamp value the table allowed to store,
+    /// returns None if the table has no TTL.
+    pub fn time_to_expired(&self) -> Option<i64> {
+        let ttl = self.ttl.as_ref()?;
+        let precision = self.time_column_precision();
+        let now = match precision {
+            Precision::MS => crate::utils::now_timestamp_millis(),
+            Precision::US => crate::utils::now_timestamp_micros(),
+            Precision::NS => crate::utils::now_timestamp_nanos(),
+        };
+        Some(now.saturating_sub(ttl.to_precision(precision)))
+    }
+
     pub fn size(&self) -> usize {
         let mut size = 0;
         for i in self.columns.iter() {
@@ -609,7 +699,7 @@ impl From<&TableColumn> for ArrowField {
         map.insert(COLUMN_ID_META_KEY.to_string(), column.id.to_string());
         map.insert(
             COLUMN_ENCODING_META_KEY.to_string(),
-            column.encoding.as_str().to_string(),
+            column.encoding.to_string(),
         );
 
         // 通过 SRID_META_KEY 标记 Geometry 类型的列
@@ -12,6 +12,8 @@ use crate::errors::DumpSnafu;
 use crate::oid::{Identifier, Oid};
 use crate::schema::database_schema::DatabaseSchema;
 use crate::schema::external_table_schema::ExternalTableSchema;
+use crate::schema::function_schema::{data_type_sql, FunctionBody, FunctionKind, FunctionSchema};
+use crate::schema::policy_schema::PolicySchema;
 use crate::schema::stream_table_schema::StreamTable;
 use crate::schema::table_schema::TableSchema;
 use crate::schema::tenant::Tenant;
@@ -174,6 +176,9 @@ impl ToDDLSql for DatabaseSchema {
         res.push_str(format!("shard {} ", self.options.shard_num()).as_str());
         res.push_str(format!("replica {} ", self.options.replica()).as_str());
         res.push_str(format!("vnode_duration '{}' ", self.options.vnode_duration()).as_str());
+        if self.options.max_series() > 0 {
+            res.push_str(format!("max_series {} ", self.options.max_series()).as_str());
+        }
 
         if res.trim().ends_with("with") {
             res = res.trim().trim_end_matches("with").trim().to_string();
@@ -207,6 +212,65 @@ impl ToDDLSql for CustomTenantRole<Oid> {
     }
 }
 
+// CREATE FUNCTION
+impl ToDDLSql for FunctionSchema {
+    fn to_ddl_sql(&self, if_not_exists: bool) -> Result<String> {
+        let mut res = String::new();
+        res.push_str("create ");
+        if self.kind == FunctionKind::Aggregate {
+            res.push_str("aggregate ");
+        }
+        res.push_str("function ");
+        if if_not_exists {
+            res.push_str("if not exists ");
+        }
+        res.push_str(&format!(
+            "\"{}\"({}) returns {} language {} ",
+            self.name,
+            self.args_sql(),
+            data_type_sql(&self.return_type),
+            self.language().to_lowercase(),
+        ));
+        match &self.body {
+            FunctionBody::Sql(expr) => res.push_str(&format!(
+                "as {};",
+                SqlParserValue::SingleQuotedString(expr.clone())
+            )),
+            FunctionBody::Wasm(wasm) => res.push_str(&format!(
+                "as '{}' with (fuel={}, max_memory={});",
+                wasm.module, wasm.fuel, wasm.max_memory
+            )),
+        }
+        Ok(res)
+    }
+}
+
+// CREATE POLICY
+impl ToDDLSql for PolicySchema {
+    fn to_ddl_sql(&self, if_not_exists: bool) -> Result<String> {
+        let mut res = String::new();
+        res.push_str("create policy ");
+        if if_not_exists {
+            res.push_str("if not exists ");
+        }
+        res.push_str(&format!(
+            "\"{}\" on \"{}\".\"{}\" ",
+            self.name, self.database, self.table
+        ));
+        if !self.roles.is_empty() {
+            let roles = self
+                .roles
+                .iter()
+                .map(|r| format!("\"{}\"", r))
+                .collect::<Vec<_>>()
+                .join(", ");
+            res.push_str(&format!("to {} ", roles));
+        }
+        res.push_str(&format!("using ({});", self.using));
+        Ok(res)
+    }
+}
+
 pub fn role_to_sql(role: &CustomTenantRole<Oid>) -> Result<Vec<String>> {
     let mut res = vec![];
     let role_sql = role.to_ddl_sql(false)?;
@@ -231,6 +295,36 @@ pub fn privilege_to_sql(role: &CustomTenantRole<Oid>) -> Vec<String> {
         .collect()
 }
 
+// GRANT privilege on table, which is dumped after the tables
+pub fn table_privilege_to_sql(role: &CustomTenantRole<Oid>) -> Vec<String> {
+    let mut res = vec![];
+    for (d, tables) in role.table_privileges() {
+        for (t, p) in tables {
+            let columns = match &p.columns {
+                Some(columns) => {
+                    let columns = columns
+                        .iter()
+                        .map(|c| format!("\"{}\"", c))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    format!(" ({})", columns)
+                }
+                None => String::new(),
+            };
+            res.push(format!(
+                "grant {}{} on table \"{}\".\"{}\" to \"{}\";",
+                p.privilege.as_str(),
+                columns,
+                d,
+                t,
+                role.name()
+            ));
+        }
+    }
+    res.sort();
+    res
+}
+
 // Add member
 pub fn add_member_to_sql(tenant_name: &str, user: &str, role: &str) -> String {
     format!(
@@ -299,7 +393,7 @@ impl ToDDLSql for TskvTableSchema {
                 }
 
                 if c.encoding != Encoding::Default {
-                    res.push_str(format!(" CODEC({})", c.encoding.as_str()).as_str());
+                    res.push_str(format!(" CODEC({})", c.encoding).as_str());
                 }
                 res.push_str(", ");
             });
@@ -506,6 +600,10 @@ mod test {
     use crate::auth::user::{UserDesc, UserOptionsBuilder};
     use crate::schema::database_schema::{DatabaseConfig, DatabaseOptions, DatabaseSchema};
     use crate::schema::external_table_schema::ExternalTableSchema;
+    use crate::schema::function_schema::{
+        FunctionArg, FunctionBody, FunctionKind, FunctionSchema, WasmFunction,
+    };
+    use crate::schema::policy_schema::PolicySchema;
     use crate::schema::stream_table_schema::{StreamTable, Watermark};
     use crate::schema::tenant::{Tenant, TenantOptionsBuilder};
     use crate::schema::tskv_table_schema::{ColumnType, TableColumn, TskvTableSchema};
@@ -700,4 +798,57 @@ remote_bucket = {max = 100, initial = 0, refill = 100, interval = 100}
             r#"create stream table "test"."test_stream" ("visibility" DOUBLE, "temperature" DOUBLE, "pressure" DOUBLE, "station" STRING) with (db='test', table='air', event_time_column='time') engine = tskv;"#
         );
     }
+
+    #[test]
+    fn create_function() {
+        let mut function = FunctionSchema {
+            tenant: "cnosdb".to_string(),
+            name: "f_to_c".to_string(),
+            kind: FunctionKind::Scalar,
+            args: vec![FunctionArg {
+                name: "f".to_string(),
+                data_type: DataType::Float64,
+            }],
+            return_type: DataType::Float64,
+            body: FunctionBody::Sql("(f - 32) / 1.8 || 'c'".to_string()),
+            version: 0,
+        };
+        assert_eq!(
+            function.to_ddl_sql(true).unwrap(),
+            r#"create function if not exists "f_to_c"(f DOUBLE) returns DOUBLE language sql as '(f - 32) / 1.8 || ''c''';"#
+        );
+
+        function.kind = FunctionKind::Aggregate;
+        function.body = FunctionBody::Wasm(WasmFunction {
+            module: "AGFzbQEAAAA=".to_string(),
+            fuel: 1000,
+            max_memory: 65536,
+        });
+        assert_eq!(
+            function.to_ddl_sql(false).unwrap(),
+            r#"create aggregate function "f_to_c"(f DOUBLE) returns DOUBLE language wasm as 'AGFzbQEAAAA=' with (fuel=1000, max_memory=65536);"#
+        );
+    }
+
+    #[test]
+    fn create_policy() {
+        let mut policy = PolicySchema {
+            tenant: "cnosdb".to_string(),
+            name: "p1".to_string(),
+            database: "public".to_string(),
+            table: "air".to_string(),
+            roles: vec![],
+            using: "station = current_user()".to_string(),
+        };
+        assert_eq!(
+            policy.to_ddl_sql(false).unwrap(),
+            r#"create policy "p1" on "public"."air" using (station = current_user());"#
+        );
+
+        policy.roles = vec!["r1".to_string(), "r2".to_string()];
+        assert_eq!(
+            policy.to_ddl_sql(true).unwrap(),
+            r#"create policy if not exists "p1" on "public"."air" to "r1", "r2" using (station = current_user());"#
+        );
+    }
 }
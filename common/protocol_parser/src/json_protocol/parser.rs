@@ -33,6 +33,7 @@ pub enum JsonProtocol {
     Ndjson(BTreeMap<String, serde_json::Value>),
     Loki(BTreeMap<String, serde_json::Value>),
     TraceServiceReq(BTreeMap<String, serde_json::Value>),
+    OtlpLogsReq(BTreeMap<String, serde_json::Value>),
 }
 
 impl JsonProtocol {
@@ -42,6 +43,7 @@ impl JsonProtocol {
             JsonProtocol::Ndjson(log) => log,
             JsonProtocol::Loki(log) => log,
             JsonProtocol::TraceServiceReq(log) => log,
+            JsonProtocol::OtlpLogsReq(log) => log,
         }
     }
 }
@@ -19,6 +19,7 @@ pub mod json_protocol;
 pub mod line_protocol;
 pub mod lines_convert;
 pub mod open_tsdb;
+pub mod otlp;
 
 #[derive(Debug, Snafu)]
 #[snafu(visibility(pub))]
@@ -0,0 +1,205 @@
+use std::collections::BTreeMap;
+
+use protos::logs_service::ExportLogsServiceRequest;
+
+use super::{any_value_to_string, normalize_name, resource_scope_tags, to_hex};
+use crate::json_protocol::parser::{parse_to_line, JsonProtocol, Result};
+use crate::Line;
+
+/// Table of the log records if the request does not specify one.
+pub const DEFAULT_OTLP_LOG_TABLE: &str = "otlp_logs";
+/// Column holding the time of a log record.
+pub const OTLP_LOG_TIME_COLUMN: &str = "time";
+/// Column holding the body of a log record, the same as the line of a Loki log.
+pub const OTLP_LOG_BODY_COLUMN: &str = "msg";
+pub const OTLP_LOG_SEVERITY_TEXT_COLUMN: &str = "severity_text";
+pub const OTLP_LOG_SEVERITY_NUMBER_COLUMN: &str = "severity_number";
+pub const OTLP_LOG_OBSERVED_TIME_COLUMN: &str = "observed_time";
+pub const OTLP_LOG_TRACE_ID_COLUMN: &str = "trace_id";
+pub const OTLP_LOG_SPAN_ID_COLUMN: &str = "span_id";
+pub const OTLP_LOG_FLAGS_COLUMN: &str = "flags";
+
+/// Log records of an OTLP logs export request.
+pub struct OtlpLogs {
+    pub logs: Vec<JsonProtocol>,
+    /// Comma separated columns of `logs` to store as tags: the resource and
+    /// scope attributes and the severity text.
+    pub tag_columns: String,
+}
+
+impl OtlpLogs {
+    /// Convert the log records into lines of `table`.
+    pub fn to_lines<'a>(&'a self, table: &'a str) -> Result<Vec<Line<'a>>> {
+        self.logs
+            .iter()
+            .map(|log| parse_to_line(log, table, OTLP_LOG_TIME_COLUMN, &self.tag_columns))
+            .collect()
+    }
+}
+
+/// Flatten every log record into the columns of a row, the log record
+/// attributes are stored as string fields unless a tag has the same name.
+pub fn otlp_logs_to_json(req: ExportLogsServiceRequest) -> OtlpLogs {
+    let mut logs = Vec::new();
+    let mut tag_columns = vec![OTLP_LOG_SEVERITY_TEXT_COLUMN.to_string()];
+    for resource_logs in req.resource_logs {
+        for scope_logs in resource_logs.scope_logs {
+            let tags =
+                resource_scope_tags(resource_logs.resource.as_ref(), scope_logs.scope.as_ref());
+            for key in tags.keys() {
+                if !tag_columns.contains(key) {
+                    tag_columns.push(key.clone());
+                }
+            }
+
+            for record in scope_logs.log_records {
+                let mut fields: BTreeMap<String, serde_json::Value> = tags
+                    .iter()
+                    .map(|(k, v)| (k.clone(), serde_json::Value::String(v.clone())))
+                    .collect();
+
+                let time = match record.time_unix_nano {
+                    0 => record.observed_time_unix_nano,
+                    time => time,
+                };
+                if time != 0 {
+                    fields.insert(OTLP_LOG_TIME_COLUMN.to_string(), (time as i64).into());
+                }
+                if record.observed_time_unix_nano != 0 {
+                    fields.insert(
+                        OTLP_LOG_OBSERVED_TIME_COLUMN.to_string(),
+                        (record.observed_time_unix_nano as i64).into(),
+                    );
+                }
+                let body = record
+                    .body
+                    .as_ref()
+                    .map(any_value_to_string)
+                    .unwrap_or_default();
+                fields.insert(
+                    OTLP_LOG_BODY_COLUMN.to_string(),
+                    serde_json::Value::String(body),
+                );
+                if !record.severity_text.is_empty() {
+                    fields.insert(
+                        OTLP_LOG_SEVERITY_TEXT_COLUMN.to_string(),
+                        serde_json::Value::String(record.severity_text),
+                    );
+                }
+                fields.insert(
+                    OTLP_LOG_SEVERITY_NUMBER_COLUMN.to_string(),
+                    record.severity_number.into(),
+                );
+                if !record.trace_id.is_empty() {
+                    fields.insert(
+                        OTLP_LOG_TRACE_ID_COLUMN.to_string(),
+                        serde_json::Value::String(to_hex(&record.trace_id)),
+                    );
+                }
+                if !record.span_id.is_empty() {
+                    fields.insert(
+                        OTLP_LOG_SPAN_ID_COLUMN.to_string(),
+                        serde_json::Value::String(to_hex(&record.span_id)),
+                    );
+                }
+                fields.insert(OTLP_LOG_FLAGS_COLUMN.to_string(), record.flags.into());
+
+                for attribute in &record.attributes {
+                    let value = attribute
+                        .value
+                        .as_ref()
+                        .map(any_value_to_string)
+                        .unwrap_or_default();
+                    fields
+                        .entry(normalize_name(&attribute.key))
+                        .or_insert(serde_json::Value::String(value));
+                }
+
+                logs.push(JsonProtocol::OtlpLogsReq(fields));
+            }
+        }
+    }
+
+    OtlpLogs {
+        logs,
+        tag_columns: tag_columns.join(","),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use protos::common::any_value::Value;
+    use protos::common::{AnyValue, KeyValue};
+    use protos::logs::{LogRecord, ResourceLogs, ScopeLogs};
+    use protos::logs_service::ExportLogsServiceRequest;
+    use protos::resource::Resource;
+    use serde_json::json;
+
+    use super::otlp_logs_to_json;
+
+    fn kv(key: &str, value: Value) -> KeyValue {
+        KeyValue {
+            key: key.to_string(),
+            value: Some(AnyValue { value: Some(value) }),
+        }
+    }
+
+    #[test]
+    fn test_otlp_logs_to_json() {
+        let req = ExportLogsServiceRequest {
+            resource_logs: vec![ResourceLogs {
+                resource: Some(Resource {
+                    attributes: vec![kv("service.name", Value::StringValue("api".to_string()))],
+                    dropped_attributes_count: 0,
+                }),
+                scope_logs: vec![ScopeLogs {
+                    scope: None,
+                    log_records: vec![LogRecord {
+                        time_unix_nano: 0,
+                        observed_time_unix_nano: 1_700_000_000_000_000_000,
+                        severity_number: 17,
+                        severity_text: "ERROR".to_string(),
+                        body: Some(AnyValue {
+                            value: Some(Value::StringValue("GET /ping 500".to_string())),
+                        }),
+                        attributes: vec![
+                            kv("http.status_code", Value::IntValue(500)),
+                            // A log record attribute never overwrites a tag
+                            kv("service.name", Value::StringValue("other".to_string())),
+                        ],
+                        span_id: vec![0, 1, 0xab],
+                        ..Default::default()
+                    }],
+                    schema_url: String::new(),
+                }],
+                schema_url: String::new(),
+            }],
+        };
+
+        let otlp_logs = otlp_logs_to_json(req);
+        assert_eq!(otlp_logs.tag_columns, "severity_text,service_name");
+        assert_eq!(otlp_logs.logs.len(), 1);
+
+        let fields = otlp_logs.logs[0].get_fields();
+        assert_eq!(
+            serde_json::to_value(fields).unwrap(),
+            json!({
+                "flags": 0,
+                "http_status_code": "500",
+                "msg": "GET /ping 500",
+                "observed_time": 1_700_000_000_000_000_000_i64,
+                "service_name": "api",
+                "severity_number": 17,
+                "severity_text": "ERROR",
+                "span_id": "0001ab",
+                "time": 1_700_000_000_000_000_000_i64,
+            })
+        );
+
+        let lines = otlp_logs.to_lines("logs").unwrap();
+        let line = &lines[0];
+        assert_eq!(line.timestamp, 1_700_000_000_000_000_000);
+        assert_eq!(line.tags.len(), 2);
+        assert_eq!(line.fields.len(), 6);
+    }
+}
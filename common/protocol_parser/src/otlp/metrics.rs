@@ -0,0 +1,452 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+use protos::common::KeyValue;
+use protos::metrics::exponential_histogram_data_point::Buckets;
+use protos::metrics::metric::Data;
+use protos::metrics::number_data_point::Value;
+use protos::metrics::{
+    DataPointFlags, ExponentialHistogramDataPoint, HistogramDataPoint, NumberDataPoint,
+    SummaryDataPoint,
+};
+use protos::metrics_service::ExportMetricsServiceRequest;
+use protos::FieldValue;
+
+use super::{insert_attributes, normalize_name, resource_scope_tags};
+use crate::Line;
+
+/// Field holding the value of a sample.
+pub const OTLP_METRIC_VALUE_FIELD: &str = "value";
+/// Tag holding the upper bound of a histogram bucket.
+pub const OTLP_METRIC_LE_TAG: &str = "le";
+/// Tag holding the quantile of a summary.
+pub const OTLP_METRIC_QUANTILE_TAG: &str = "quantile";
+
+/// Convert the data points of the metrics into lines, in the same layout as
+/// Prometheus: every metric is written to a table named after it, with the
+/// resource, scope and data point attributes as tags and a `value` field.
+///
+/// Histograms and exponential histograms are written to the `<name>_bucket`
+/// table with cumulative counts by `le`, and to the `<name>_count` and
+/// `<name>_sum` tables. Summaries are written to the `<name>` table by
+/// `quantile`, and to the `<name>_count` and `<name>_sum` tables.
+pub fn otlp_metrics_to_lines(
+    req: ExportMetricsServiceRequest,
+    default_time: i64,
+) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+    for resource_metrics in req.resource_metrics {
+        for scope_metrics in resource_metrics.scope_metrics {
+            let scope_tags = resource_scope_tags(
+                resource_metrics.resource.as_ref(),
+                scope_metrics.scope.as_ref(),
+            );
+            for metric in scope_metrics.metrics {
+                let mut converter = MetricConverter {
+                    name: normalize_name(&metric.name),
+                    scope_tags: &scope_tags,
+                    default_time,
+                    lines: &mut lines,
+                };
+                match metric.data {
+                    Some(Data::Gauge(gauge)) => converter.number_points(&gauge.data_points),
+                    Some(Data::Sum(sum)) => converter.number_points(&sum.data_points),
+                    Some(Data::Histogram(histogram)) => {
+                        converter.histogram_points(&histogram.data_points)
+                    }
+                    Some(Data::ExponentialHistogram(histogram)) => {
+                        converter.exponential_histogram_points(&histogram.data_points)
+                    }
+                    Some(Data::Summary(summary)) => converter.summary_points(&summary.data_points),
+                    None => {}
+                }
+            }
+        }
+    }
+    lines
+}
+
+struct MetricConverter<'a> {
+    name: String,
+    scope_tags: &'a BTreeMap<String, String>,
+    default_time: i64,
+    lines: &'a mut Vec<Line<'static>>,
+}
+
+impl MetricConverter<'_> {
+    fn number_points(&mut self, points: &[NumberDataPoint]) {
+        for point in points {
+            if no_recorded_value(point.flags) {
+                continue;
+            }
+            let value = match point.value {
+                Some(Value::AsDouble(v)) => v,
+                Some(Value::AsInt(v)) => v as f64,
+                None => continue,
+            };
+            let tags = self.point_tags(&point.attributes);
+            let time = self.timestamp(point.time_unix_nano);
+            self.push(self.name.clone(), &tags, value, time);
+        }
+    }
+
+    fn histogram_points(&mut self, points: &[HistogramDataPoint]) {
+        for point in points {
+            if no_recorded_value(point.flags) {
+                continue;
+            }
+            let mut tags = self.point_tags(&point.attributes);
+            let time = self.timestamp(point.time_unix_nano);
+
+            let bounds = point.explicit_bounds.iter().copied().chain([f64::INFINITY]);
+            let mut cumulative = 0;
+            for (bound, count) in bounds.zip(&point.bucket_counts) {
+                cumulative += count;
+                self.push_bucket(&mut tags, bound, cumulative, time);
+            }
+            if point.bucket_counts.len() <= point.explicit_bounds.len() {
+                // The buckets are absent, the count is still the number of values below +Inf
+                self.push_bucket(&mut tags, f64::INFINITY, point.count, time);
+            }
+            self.push_count_and_sum(&tags, point.count, point.sum, time);
+        }
+    }
+
+    fn exponential_histogram_points(&mut self, points: &[ExponentialHistogramDataPoint]) {
+        for point in points {
+            if no_recorded_value(point.flags) {
+                continue;
+            }
+            let mut tags = self.point_tags(&point.attributes);
+            let time = self.timestamp(point.time_unix_nano);
+
+            let mut cumulative = 0;
+            for (bound, count) in exponential_buckets(point) {
+                cumulative += count;
+                self.push_bucket(&mut tags, bound, cumulative, time);
+            }
+            self.push_bucket(&mut tags, f64::INFINITY, point.count, time);
+            self.push_count_and_sum(&tags, point.count, point.sum, time);
+        }
+    }
+
+    fn summary_points(&mut self, points: &[SummaryDataPoint]) {
+        for point in points {
+            if no_recorded_value(point.flags) {
+                continue;
+            }
+            let mut tags = self.point_tags(&point.attributes);
+            let time = self.timestamp(point.time_unix_nano);
+
+            for quantile in &point.quantile_values {
+                tags.insert(
+                    OTLP_METRIC_QUANTILE_TAG.to_string(),
+                    quantile.quantile.to_string(),
+                );
+                self.push(self.name.clone(), &tags, quantile.value, time);
+            }
+            tags.remove(OTLP_METRIC_QUANTILE_TAG);
+            self.push_count_and_sum(&tags, point.count, Some(point.sum), time);
+        }
+    }
+
+    fn point_tags(&self, attributes: &[KeyValue]) -> BTreeMap<String, String> {
+        let mut tags = self.scope_tags.clone();
+        insert_attributes(&mut tags, attributes);
+        tags
+    }
+
+    fn timestamp(&self, time_unix_nano: u64) -> i64 {
+        if time_unix_nano == 0 {
+            self.default_time
+        } else {
+            time_unix_nano as i64
+        }
+    }
+
+    fn push_bucket(&mut self, tags: &mut BTreeMap<String, String>, le: f64, count: u64, time: i64) {
+        tags.insert(OTLP_METRIC_LE_TAG.to_string(), format_le(le));
+        self.push(format!("{}_bucket", self.name), tags, count as f64, time);
+        tags.remove(OTLP_METRIC_LE_TAG);
+    }
+
+    fn push_count_and_sum(
+        &mut self,
+        tags: &BTreeMap<String, String>,
+        count: u64,
+        sum: Option<f64>,
+        time: i64,
+    ) {
+        self.push(format!("{}_count", self.name), tags, count as f64, time);
+        if let Some(sum) = sum {
+            self.push(format!("{}_sum", self.name), tags, sum, time);
+        }
+    }
+
+    fn push(&mut self, table: String, tags: &BTreeMap<String, String>, value: f64, time: i64) {
+        let tags = tags
+            .iter()
+            .map(|(k, v)| (Cow::Owned(k.clone()), Cow::Owned(v.clone())))
+            .collect();
+        let fields = vec![(
+            Cow::Borrowed(OTLP_METRIC_VALUE_FIELD),
+            FieldValue::F64(value),
+        )];
+        self.lines
+            .push(Line::new(Cow::Owned(table), tags, fields, time));
+    }
+}
+
+fn no_recorded_value(flags: u32) -> bool {
+    flags & DataPointFlags::NoRecordedValueMask as u32 != 0
+}
+
+fn format_le(le: f64) -> String {
+    if le == f64::INFINITY {
+        "+Inf".to_string()
+    } else {
+        le.to_string()
+    }
+}
+
+/// Upper bounds and counts of the buckets of an exponential histogram, in
+/// ascending order of the bounds, the values in the zero bucket are counted
+/// below the zero threshold.
+fn exponential_buckets(point: &ExponentialHistogramDataPoint) -> Vec<(f64, u64)> {
+    // Bucket `index` holds the values in (base^index, base^(index+1)],
+    // where base = 2^(2^-scale)
+    let bound = |index: i64| (index as f64 * (-point.scale as f64).exp2()).exp2();
+    let indexed = |buckets: &Buckets| {
+        buckets
+            .bucket_counts
+            .iter()
+            .enumerate()
+            .map(|(i, count)| (buckets.offset as i64 + i as i64, *count))
+            .collect::<Vec<_>>()
+    };
+
+    let mut res = vec![];
+    if let Some(negative) = &point.negative {
+        // The negative bucket `index` holds the values in [-base^(index+1), -base^index)
+        for (index, count) in indexed(negative).into_iter().rev() {
+            res.push((-bound(index), count));
+        }
+    }
+    res.push((point.zero_threshold, point.zero_count));
+    if let Some(positive) = &point.positive {
+        for (index, count) in indexed(positive) {
+            res.push((bound(index + 1), count));
+        }
+    }
+    res
+}
+
+#[cfg(test)]
+mod test {
+    use protos::common::any_value::Value as AnyValueValue;
+    use protos::common::{AnyValue, InstrumentationScope, KeyValue};
+    use protos::metrics::exponential_histogram_data_point::Buckets;
+    use protos::metrics::metric::Data;
+    use protos::metrics::number_data_point::Value;
+    use protos::metrics::{
+        ExponentialHistogram, ExponentialHistogramDataPoint, Gauge, Histogram, HistogramDataPoint,
+        Metric, NumberDataPoint, ResourceMetrics, ScopeMetrics,
+    };
+    use protos::metrics_service::ExportMetricsServiceRequest;
+    use protos::resource::Resource;
+    use protos::FieldValue;
+
+    use super::otlp_metrics_to_lines;
+    use crate::Line;
+
+    fn string_kv(key: &str, value: &str) -> KeyValue {
+        KeyValue {
+            key: key.to_string(),
+            value: Some(AnyValue {
+                value: Some(AnyValueValue::StringValue(value.to_string())),
+            }),
+        }
+    }
+
+    fn request(metrics: Vec<Metric>) -> ExportMetricsServiceRequest {
+        ExportMetricsServiceRequest {
+            resource_metrics: vec![ResourceMetrics {
+                resource: Some(Resource {
+                    attributes: vec![string_kv("service.name", "api")],
+                    dropped_attributes_count: 0,
+                }),
+                scope_metrics: vec![ScopeMetrics {
+                    scope: Some(InstrumentationScope {
+                        name: "meter".to_string(),
+                        ..Default::default()
+                    }),
+                    metrics,
+                    schema_url: String::new(),
+                }],
+                schema_url: String::new(),
+            }],
+        }
+    }
+
+    fn sample(line: &Line) -> (String, Vec<(String, String)>, f64, i64) {
+        let tags = line
+            .tags
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        let value = match line.fields.as_slice() {
+            [(name, FieldValue::F64(v))] if name == "value" => *v,
+            fields => panic!("unexpected fields {:?}", fields),
+        };
+        (line.table.to_string(), tags, value, line.timestamp)
+    }
+
+    #[test]
+    fn test_gauge_to_lines() {
+        let req = request(vec![Metric {
+            name: "http.server.active_requests".to_string(),
+            data: Some(Data::Gauge(Gauge {
+                data_points: vec![
+                    NumberDataPoint {
+                        attributes: vec![string_kv("method", "GET")],
+                        time_unix_nano: 10,
+                        value: Some(Value::AsInt(3)),
+                        ..Default::default()
+                    },
+                    // No recorded value
+                    NumberDataPoint {
+                        time_unix_nano: 20,
+                        value: Some(Value::AsDouble(1.0)),
+                        flags: 1,
+                        ..Default::default()
+                    },
+                    NumberDataPoint {
+                        value: Some(Value::AsDouble(1.5)),
+                        ..Default::default()
+                    },
+                ],
+            })),
+            ..Default::default()
+        }]);
+
+        let lines = otlp_metrics_to_lines(req, 100);
+        let samples = lines.iter().map(sample).collect::<Vec<_>>();
+        let tags = |extra: &[(&str, &str)]| {
+            let mut tags = vec![
+                ("otel_scope_name".to_string(), "meter".to_string()),
+                ("service_name".to_string(), "api".to_string()),
+            ];
+            tags.extend(extra.iter().map(|(k, v)| (k.to_string(), v.to_string())));
+            tags.sort();
+            tags
+        };
+        assert_eq!(
+            samples,
+            vec![
+                (
+                    "http_server_active_requests".to_string(),
+                    tags(&[("method", "GET")]),
+                    3.0,
+                    10
+                ),
+                (
+                    "http_server_active_requests".to_string(),
+                    tags(&[]),
+                    1.5,
+                    100
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_histogram_to_lines() {
+        let req = request(vec![Metric {
+            name: "latency".to_string(),
+            data: Some(Data::Histogram(Histogram {
+                data_points: vec![HistogramDataPoint {
+                    time_unix_nano: 10,
+                    count: 6,
+                    sum: Some(4.5),
+                    bucket_counts: vec![1, 2, 3],
+                    explicit_bounds: vec![0.5, 1.0],
+                    ..Default::default()
+                }],
+                aggregation_temporality: 2,
+            })),
+            ..Default::default()
+        }]);
+
+        let lines = otlp_metrics_to_lines(req, 100);
+        let samples = lines
+            .iter()
+            .map(sample)
+            .map(|(table, tags, value, _)| {
+                let le = tags
+                    .into_iter()
+                    .find(|(k, _)| k == "le")
+                    .map(|(_, v)| v)
+                    .unwrap_or_default();
+                (table, le, value)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            samples,
+            vec![
+                ("latency_bucket".to_string(), "0.5".to_string(), 1.0),
+                ("latency_bucket".to_string(), "1".to_string(), 3.0),
+                ("latency_bucket".to_string(), "+Inf".to_string(), 6.0),
+                ("latency_count".to_string(), String::new(), 6.0),
+                ("latency_sum".to_string(), String::new(), 4.5),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_exponential_histogram_to_lines() {
+        let req = request(vec![Metric {
+            name: "size".to_string(),
+            data: Some(Data::ExponentialHistogram(ExponentialHistogram {
+                data_points: vec![ExponentialHistogramDataPoint {
+                    time_unix_nano: 10,
+                    count: 7,
+                    scale: 0,
+                    zero_count: 1,
+                    positive: Some(Buckets {
+                        offset: 1,
+                        bucket_counts: vec![2, 3],
+                    }),
+                    negative: Some(Buckets {
+                        offset: 0,
+                        bucket_counts: vec![1],
+                    }),
+                    ..Default::default()
+                }],
+                aggregation_temporality: 2,
+            })),
+            ..Default::default()
+        }]);
+
+        let lines = otlp_metrics_to_lines(req, 100);
+        let buckets = lines
+            .iter()
+            .map(sample)
+            .filter(|(table, ..)| table == "size_bucket")
+            .map(|(_, tags, value, _)| {
+                let le = tags.into_iter().find(|(k, _)| k == "le").unwrap().1;
+                (le, value)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            buckets,
+            vec![
+                ("-1".to_string(), 1.0),
+                ("0".to_string(), 2.0),
+                ("4".to_string(), 4.0),
+                ("8".to_string(), 7.0),
+                ("+Inf".to_string(), 7.0),
+            ]
+        );
+        assert_eq!(lines.len(), 6);
+    }
+}
@@ -0,0 +1,112 @@
+use std::collections::BTreeMap;
+
+use protos::common::any_value::Value;
+use protos::common::{AnyValue, InstrumentationScope, KeyValue};
+use protos::resource::Resource;
+
+pub mod logs;
+pub mod metrics;
+
+/// Tag holding the name of the instrumentation scope.
+pub const OTLP_SCOPE_NAME_TAG: &str = "otel_scope_name";
+/// Tag holding the version of the instrumentation scope.
+pub const OTLP_SCOPE_VERSION_TAG: &str = "otel_scope_version";
+
+/// Replace the characters that are not allowed in a table or label name by '_',
+/// e.g. `service.name` is stored as `service_name`.
+pub fn normalize_name(name: &str) -> String {
+    let mut normalized: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if normalized.starts_with(|c: char| c.is_ascii_digit()) {
+        normalized.insert(0, '_');
+    }
+    normalized
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .fold(String::new(), |acc, byte| acc + &format!("{:02x}", byte))
+}
+
+pub(crate) fn any_value_to_json(value: &AnyValue) -> serde_json::Value {
+    match &value.value {
+        Some(Value::StringValue(v)) => serde_json::Value::String(v.clone()),
+        Some(Value::BoolValue(v)) => serde_json::Value::Bool(*v),
+        Some(Value::IntValue(v)) => serde_json::Value::Number((*v).into()),
+        Some(Value::DoubleValue(v)) => serde_json::Number::from_f64(*v)
+            .map(serde_json::Value::Number)
+            .unwrap_or(serde_json::Value::Null),
+        Some(Value::ArrayValue(v)) => {
+            serde_json::Value::Array(v.values.iter().map(any_value_to_json).collect())
+        }
+        Some(Value::KvlistValue(v)) => serde_json::Value::Object(
+            v.values
+                .iter()
+                .map(|kv| {
+                    let value = kv
+                        .value
+                        .as_ref()
+                        .map(any_value_to_json)
+                        .unwrap_or(serde_json::Value::Null);
+                    (kv.key.clone(), value)
+                })
+                .collect(),
+        ),
+        Some(Value::BytesValue(v)) => serde_json::Value::String(to_hex(v)),
+        None => serde_json::Value::Null,
+    }
+}
+
+/// Strings are kept as they are, other values are encoded as JSON.
+pub(crate) fn any_value_to_string(value: &AnyValue) -> String {
+    match any_value_to_json(value) {
+        serde_json::Value::String(v) => v,
+        serde_json::Value::Null => String::new(),
+        v => v.to_string(),
+    }
+}
+
+/// Insert the attributes with a non-empty value into `tags`, the attributes
+/// overwrite the tags already in `tags` with the same name.
+pub(crate) fn insert_attributes(tags: &mut BTreeMap<String, String>, attributes: &[KeyValue]) {
+    for attribute in attributes {
+        let value = attribute
+            .value
+            .as_ref()
+            .map(any_value_to_string)
+            .unwrap_or_default();
+        if !value.is_empty() {
+            tags.insert(normalize_name(&attribute.key), value);
+        }
+    }
+}
+
+/// Tags shared by everything produced by an instrumentation scope of a resource.
+pub(crate) fn resource_scope_tags(
+    resource: Option<&Resource>,
+    scope: Option<&InstrumentationScope>,
+) -> BTreeMap<String, String> {
+    let mut tags = BTreeMap::new();
+    if let Some(resource) = resource {
+        insert_attributes(&mut tags, &resource.attributes);
+    }
+    if let Some(scope) = scope {
+        insert_attributes(&mut tags, &scope.attributes);
+        if !scope.name.is_empty() {
+            tags.insert(OTLP_SCOPE_NAME_TAG.to_string(), scope.name.clone());
+        }
+        if !scope.version.is_empty() {
+            tags.insert(OTLP_SCOPE_VERSION_TAG.to_string(), scope.version.clone());
+        }
+    }
+    tags
+}
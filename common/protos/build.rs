@@ -53,6 +53,10 @@ fn main() -> Result<(), AnyError> {
             ("otlp_resource.proto", "resource"),
             ("otlp_trace.proto", "trace"),
             ("otlp_trace_service.proto", "trace_service"),
+            ("otlp_metrics.proto", "metrics"),
+            ("otlp_metrics_service.proto", "metrics_service"),
+            ("otlp_logs.proto", "logs"),
+            ("otlp_logs_service.proto", "logs_service"),
         ],
     )?;
 
@@ -150,6 +154,23 @@ fn compile_protobuf_models<P: AsRef<Path>, S: AsRef<str>>(
                 &mut sub_mod_rs,
                 "#[path = \"opentelemetry.proto.collector.trace.rs\"]"
             )?;
+        } else if mod_name == "metrics" {
+            writeln!(
+                &mut sub_mod_rs,
+                "#[path = \"opentelemetry.proto.metrics.rs\"]"
+            )?;
+        } else if mod_name == "metrics_service" {
+            writeln!(
+                &mut sub_mod_rs,
+                "#[path = \"opentelemetry.proto.collector.metrics.rs\"]"
+            )?;
+        } else if mod_name == "logs" {
+            writeln!(&mut sub_mod_rs, "#[path = \"opentelemetry.proto.logs.rs\"]")?;
+        } else if mod_name == "logs_service" {
+            writeln!(
+                &mut sub_mod_rs,
+                "#[path = \"opentelemetry.proto.collector.logs.rs\"]"
+            )?;
         }
         writeln!(&mut sub_mod_rs, "pub mod {mod_name};")?;
     }
@@ -40,6 +40,16 @@ pub struct BatchBytesResponse {
     pub data: ::prost::alloc::vec::Vec<u8>,
 }
 /// --------------------------------------------------------------------
+/// The maximum number of series of the replica set, computed from max_series and the series
+/// of the other replica sets before proposing the write, the limits not set are unlimited.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SeriesLimits {
+    #[prost(uint64, optional, tag = "1")]
+    pub database: ::core::option::Option<u64>,
+    #[prost(map = "string, uint64", tag = "2")]
+    pub tables: ::std::collections::HashMap<::prost::alloc::string::String, u64>,
+}
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct WriteDataRequest {
@@ -47,6 +57,8 @@ pub struct WriteDataRequest {
     pub data: ::prost::alloc::vec::Vec<u8>,
     #[prost(uint32, tag = "2")]
     pub precision: u32,
+    #[prost(message, optional, tag = "3")]
+    pub series_limits: ::core::option::Option<SeriesLimits>,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -111,6 +123,9 @@ pub struct RaftWriteCommand {
     pub replica_id: u32,
     #[prost(oneof = "raft_write_command::Command", tags = "4, 5, 6, 7, 8")]
     pub command: ::core::option::Option<raft_write_command::Command>,
+    /// Time the command is proposed in nanoseconds, 0 if unknown
+    #[prost(int64, tag = "9")]
+    pub timestamp: i64,
 }
 /// Nested message and enum types in `RaftWriteCommand`.
 pub mod raft_write_command {
@@ -144,6 +159,22 @@ pub struct FetchChecksumRequest {
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FetchSeriesCardinalityRequest {
+    #[prost(uint32, tag = "1")]
+    pub vnode_id: u32,
+    #[prost(string, optional, tag = "2")]
+    pub table: ::core::option::Option<::prost::alloc::string::String>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FetchTableStatisticsRequest {
+    #[prost(uint32, tag = "1")]
+    pub vnode_id: u32,
+    #[prost(string, tag = "2")]
+    pub table: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct OpenRaftNodeRequest {
     #[prost(string, tag = "1")]
     pub tenant: ::prost::alloc::string::String,
@@ -222,10 +253,52 @@ pub struct BuildRaftGroupRequest {
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BackupVnodeRequest {
+    #[prost(string, tag = "1")]
+    pub db_name: ::prost::alloc::string::String,
+    #[prost(uint32, tag = "2")]
+    pub vnode_id: u32,
+    #[prost(string, tag = "3")]
+    pub url: ::prost::alloc::string::String,
+    #[prost(map = "string, string", tag = "4")]
+    pub options: ::std::collections::HashMap<
+        ::prost::alloc::string::String,
+        ::prost::alloc::string::String,
+    >,
+    #[prost(int64, tag = "5")]
+    pub backup_id: i64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RestoreVnodeRequest {
+    #[prost(string, tag = "1")]
+    pub db_name: ::prost::alloc::string::String,
+    #[prost(uint32, tag = "2")]
+    pub vnode_id: u32,
+    #[prost(uint32, tag = "3")]
+    pub replica_id: u32,
+    #[prost(string, tag = "4")]
+    pub url: ::prost::alloc::string::String,
+    #[prost(map = "string, string", tag = "5")]
+    pub options: ::std::collections::HashMap<
+        ::prost::alloc::string::String,
+        ::prost::alloc::string::String,
+    >,
+    #[prost(int64, tag = "6")]
+    pub backup_id: i64,
+    #[prost(uint32, tag = "7")]
+    pub backup_vnode_id: u32,
+    #[prost(bool, tag = "8")]
+    pub replay_wal: bool,
+    #[prost(int64, optional, tag = "9")]
+    pub as_of: ::core::option::Option<i64>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct AdminCommand {
     #[prost(string, tag = "1")]
     pub tenant: ::prost::alloc::string::String,
-    #[prost(oneof = "admin_command::Command", tags = "2, 3, 4, 5, 6, 7, 8, 9, 10, 11")]
+    #[prost(oneof = "admin_command::Command", tags = "2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15")]
     pub command: ::core::option::Option<admin_command::Command>,
 }
 /// Nested message and enum types in `AdminCommand`.
@@ -253,6 +326,14 @@ pub mod admin_command {
         LearnerToFollower(super::LearnerToFollowerRequest),
         #[prost(message, tag = "11")]
         BuildRaftGroup(super::BuildRaftGroupRequest),
+        #[prost(message, tag = "12")]
+        BackupVnode(super::BackupVnodeRequest),
+        #[prost(message, tag = "13")]
+        RestoreVnode(super::RestoreVnodeRequest),
+        #[prost(message, tag = "14")]
+        FetchSeriesCardinality(super::FetchSeriesCardinalityRequest),
+        #[prost(message, tag = "15")]
+        FetchTableStatistics(super::FetchTableStatisticsRequest),
     }
 }
 /// --------------------------------------------------------------------
@@ -272,6 +353,82 @@ pub struct QueryRecordBatchRequest {
     #[prost(bytes = "vec", tag = "3")]
     pub aggs: ::prost::alloc::vec::Vec<u8>,
 }
+/// --------------------------------------------------------------------
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SubscribeCursor {
+    #[prost(uint32, tag = "1")]
+    pub replica_id: u32,
+    #[prost(uint64, tag = "2")]
+    pub index: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SubscribeRequest {
+    #[prost(string, tag = "1")]
+    pub tenant: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub db: ::prost::alloc::string::String,
+    /// Empty means all tables of the database.
+    #[prost(string, repeated, tag = "3")]
+    pub tables: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// Replication sets without a cursor start from the next applied change.
+    #[prost(message, repeated, tag = "4")]
+    pub cursors: ::prost::alloc::vec::Vec<SubscribeCursor>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SubscribeResponse {
+    #[prost(uint32, tag = "1")]
+    pub replica_id: u32,
+    /// Raft log index of the change, resume after it by passing it as cursor.
+    #[prost(uint64, tag = "2")]
+    pub index: u64,
+    #[prost(enumeration = "ChangeOperation", tag = "3")]
+    pub operation: i32,
+    /// Empty if the change is not on a single table, like UPDATE_TAGS.
+    #[prost(string, tag = "4")]
+    pub table: ::prost::alloc::string::String,
+    /// Arrow IPC encoded record batch of the written rows for WRITE, the
+    /// encoded request of the command otherwise, like DropColumnRequest.
+    #[prost(bytes = "vec", tag = "5")]
+    pub data: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum ChangeOperation {
+    Write = 0,
+    DropTable = 1,
+    DropColumn = 2,
+    DeleteFromTable = 3,
+    UpdateTags = 4,
+}
+impl ChangeOperation {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            ChangeOperation::Write => "WRITE",
+            ChangeOperation::DropTable => "DROP_TABLE",
+            ChangeOperation::DropColumn => "DROP_COLUMN",
+            ChangeOperation::DeleteFromTable => "DELETE_FROM_TABLE",
+            ChangeOperation::UpdateTags => "UPDATE_TAGS",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "WRITE" => Some(Self::Write),
+            "DROP_TABLE" => Some(Self::DropTable),
+            "DROP_COLUMN" => Some(Self::DropColumn),
+            "DELETE_FROM_TABLE" => Some(Self::DeleteFromTable),
+            "UPDATE_TAGS" => Some(Self::UpdateTags),
+            _ => None,
+        }
+    }
+}
 /// Generated client implementations.
 pub mod tskv_service_client {
     #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
@@ -505,6 +662,31 @@ pub mod tskv_service_client {
                 .insert(GrpcMethod::new("kv_service.TSKVService", "AdminRequest"));
             self.inner.unary(req, path, codec).await
         }
+        pub async fn subscribe(
+            &mut self,
+            request: impl tonic::IntoRequest<super::SubscribeRequest>,
+        ) -> std::result::Result<
+            tonic::Response<tonic::codec::Streaming<super::SubscribeResponse>>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/kv_service.TSKVService/Subscribe",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("kv_service.TSKVService", "Subscribe"));
+            self.inner.server_streaming(req, path, codec).await
+        }
     }
 }
 /// Generated server implementations.
@@ -568,6 +750,16 @@ pub mod tskv_service_server {
             tonic::Response<super::BatchBytesResponse>,
             tonic::Status,
         >;
+        /// Server streaming response type for the Subscribe method.
+        type SubscribeStream: futures_core::Stream<
+                Item = std::result::Result<super::SubscribeResponse, tonic::Status>,
+            >
+            + Send
+            + 'static;
+        async fn subscribe(
+            &self,
+            request: tonic::Request<super::SubscribeRequest>,
+        ) -> std::result::Result<tonic::Response<Self::SubscribeStream>, tonic::Status>;
     }
     /// --------------------------------------------------------------------
     #[derive(Debug)]
@@ -920,6 +1112,51 @@ pub mod tskv_service_server {
                     };
                     Box::pin(fut)
                 }
+                "/kv_service.TSKVService/Subscribe" => {
+                    #[allow(non_camel_case_types)]
+                    struct SubscribeSvc<T: TskvService>(pub Arc<T>);
+                    impl<
+                        T: TskvService,
+                    > tonic::server::ServerStreamingService<super::SubscribeRequest>
+                    for SubscribeSvc<T> {
+                        type Response = super::SubscribeResponse;
+                        type ResponseStream = T::SubscribeStream;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::ResponseStream>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::SubscribeRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move { (*inner).subscribe(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = SubscribeSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => {
                     Box::pin(async move {
                         Ok(
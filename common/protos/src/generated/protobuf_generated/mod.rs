@@ -12,3 +12,11 @@ pub mod resource;
 pub mod trace;
 #[path = "opentelemetry.proto.collector.trace.rs"]
 pub mod trace_service;
+#[path = "opentelemetry.proto.metrics.rs"]
+pub mod metrics;
+#[path = "opentelemetry.proto.collector.metrics.rs"]
+pub mod metrics_service;
+#[path = "opentelemetry.proto.logs.rs"]
+pub mod logs;
+#[path = "opentelemetry.proto.collector.logs.rs"]
+pub mod logs_service;
@@ -0,0 +1,81 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use derive_traits::Keys;
+use serde::{Deserialize, Serialize};
+
+use crate::check::{CheckConfig, CheckConfigItemResult, CheckConfigResult};
+use crate::codec::duration;
+
+pub const AUDIT_CATEGORIES: [&str; 3] = ["ddl", "dcl", "query"];
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Keys)]
+pub struct AuditConfig {
+    #[serde(default = "AuditConfig::default_enable")]
+    pub enable: bool,
+
+    /// Categories of the audited statements, 'ddl', 'dcl' or 'query'.
+    #[serde(default = "AuditConfig::default_categories")]
+    pub categories: Vec<String>,
+
+    /// The records older than this duration are removed from cluster_schema.audit_log.
+    #[serde(with = "duration", default = "AuditConfig::default_retention")]
+    pub retention: Duration,
+}
+
+impl AuditConfig {
+    fn default_enable() -> bool {
+        false
+    }
+
+    fn default_categories() -> Vec<String> {
+        vec!["ddl".to_string(), "dcl".to_string()]
+    }
+
+    fn default_retention() -> Duration {
+        Duration::from_secs(30 * 24 * 60 * 60)
+    }
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        Self {
+            enable: Self::default_enable(),
+            categories: Self::default_categories(),
+            retention: Self::default_retention(),
+        }
+    }
+}
+
+impl CheckConfig for AuditConfig {
+    fn check(&self, _: &crate::tskv::Config) -> Option<CheckConfigResult> {
+        let config_name = Arc::new("audit".to_string());
+        let mut ret = CheckConfigResult::default();
+
+        for category in self.categories.iter() {
+            if !AUDIT_CATEGORIES.contains(&category.to_ascii_lowercase().as_str()) {
+                ret.add_error(CheckConfigItemResult {
+                    config: config_name.clone(),
+                    item: "categories".to_string(),
+                    message: format!(
+                        "unknown category '{}', must be one of {:?}",
+                        category, AUDIT_CATEGORIES
+                    ),
+                });
+            }
+        }
+        if self.retention < Duration::from_secs(60 * 60) {
+            ret.add_warn(CheckConfigItemResult {
+                config: config_name,
+                item: "retention".to_string(),
+                message: "'retention' maybe too small(less than 1 hour)".to_string(),
+            });
+        }
+
+        if ret.is_empty() {
+            None
+        } else {
+            Some(ret)
+        }
+    }
+}
@@ -1,5 +1,7 @@
+mod audit_config;
 mod limiter_config;
 mod log_config;
 
+pub use audit_config::*;
 pub use limiter_config::*;
 pub use log_config::*;
@@ -0,0 +1,273 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use derive_traits::Keys;
+use serde::{Deserialize, Serialize};
+
+use crate::check::{CheckConfig, CheckConfigItemResult, CheckConfigResult};
+use crate::codec::duration;
+
+/// External authentication, the users still need to be created in CnosDB,
+/// but their credentials are verified by the LDAP server or the OpenID Connect provider.
+#[derive(Default, Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Keys)]
+pub struct AuthConfig {
+    /// Grant the roles of the tenants to the external users by their groups.
+    #[serde(default = "Default::default")]
+    pub group_roles: Vec<GroupRoleConfig>,
+    pub ldap: Option<LdapConfig>,
+    pub oidc: Option<OidcConfig>,
+}
+
+impl CheckConfig for AuthConfig {
+    fn check(&self, all_config: &super::Config) -> Option<CheckConfigResult> {
+        let mut ret = CheckConfigResult::default();
+
+        if let Some(ref ldap) = self.ldap {
+            if let Some(r) = ldap.check(all_config) {
+                ret.add_all(r);
+            }
+        }
+        if let Some(ref oidc) = self.oidc {
+            if let Some(r) = oidc.check(all_config) {
+                ret.add_all(r);
+            }
+        }
+        for group_role in self.group_roles.iter() {
+            if let Some(r) = group_role.check(all_config) {
+                ret.add_all(r);
+            }
+        }
+        if (self.ldap.is_some() || self.oidc.is_some()) && !all_config.query.auth_enabled {
+            ret.add_warn(CheckConfigItemResult {
+                config: Arc::new("auth".to_string()),
+                item: "ldap/oidc".to_string(),
+                message: "external authentication is ignored if 'query.auth_enabled' is false"
+                    .to_string(),
+            });
+        }
+
+        if ret.is_empty() {
+            None
+        } else {
+            Some(ret)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Keys)]
+pub struct LdapConfig {
+    /// 'ldap://host:port' or 'ldaps://host:port'
+    #[serde(default = "LdapConfig::default_url")]
+    pub url: String,
+    /// The DN to bind as, '{user}' is replaced with the escaped user name.
+    #[serde(default = "LdapConfig::default_user_dn_template")]
+    pub user_dn_template: String,
+    /// Search the groups of the user under this DN if set.
+    pub group_search_base: Option<String>,
+    /// '{dn}' is replaced with the escaped DN of the user.
+    #[serde(default = "LdapConfig::default_group_search_filter")]
+    pub group_search_filter: String,
+    /// The attribute of the group entries used as the group name.
+    #[serde(default = "LdapConfig::default_group_attribute")]
+    pub group_attribute: String,
+    #[serde(with = "duration", default = "LdapConfig::default_timeout")]
+    pub timeout: Duration,
+}
+
+impl LdapConfig {
+    fn default_url() -> String {
+        "ldap://127.0.0.1:389".to_string()
+    }
+
+    fn default_user_dn_template() -> String {
+        "uid={user},ou=people,dc=example,dc=com".to_string()
+    }
+
+    fn default_group_search_filter() -> String {
+        "(member={dn})".to_string()
+    }
+
+    fn default_group_attribute() -> String {
+        "cn".to_string()
+    }
+
+    fn default_timeout() -> Duration {
+        Duration::from_secs(5)
+    }
+}
+
+impl Default for LdapConfig {
+    fn default() -> Self {
+        Self {
+            url: Self::default_url(),
+            user_dn_template: Self::default_user_dn_template(),
+            group_search_base: None,
+            group_search_filter: Self::default_group_search_filter(),
+            group_attribute: Self::default_group_attribute(),
+            timeout: Self::default_timeout(),
+        }
+    }
+}
+
+impl CheckConfig for LdapConfig {
+    fn check(&self, _: &super::Config) -> Option<CheckConfigResult> {
+        let config_name = Arc::new("auth.ldap".to_string());
+        let mut ret = CheckConfigResult::default();
+
+        if !self.url.starts_with("ldap://") && !self.url.starts_with("ldaps://") {
+            ret.add_error(CheckConfigItemResult {
+                config: config_name.clone(),
+                item: "url".to_string(),
+                message: "'url' must start with 'ldap://' or 'ldaps://'".to_string(),
+            });
+        }
+        if !self.user_dn_template.contains("{user}") {
+            ret.add_error(CheckConfigItemResult {
+                config: config_name.clone(),
+                item: "user_dn_template".to_string(),
+                message: "'user_dn_template' must contain '{user}'".to_string(),
+            });
+        }
+        if self.group_search_base.is_some() && !self.group_search_filter.contains("{dn}") {
+            ret.add_warn(CheckConfigItemResult {
+                config: config_name,
+                item: "group_search_filter".to_string(),
+                message: "'group_search_filter' does not contain '{dn}'".to_string(),
+            });
+        }
+
+        if ret.is_empty() {
+            None
+        } else {
+            Some(ret)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Keys)]
+pub struct OidcConfig {
+    /// The expected 'iss' claim of the tokens if set.
+    pub issuer: Option<String>,
+    /// The expected 'aud' claim of the tokens if set.
+    pub audience: Option<String>,
+    /// Load the JSON Web Key Set from the local file, exclusive with 'jwks_url'.
+    pub jwks_file: Option<String>,
+    /// Fetch the JSON Web Key Set from the URL, exclusive with 'jwks_file'.
+    pub jwks_url: Option<String>,
+    #[serde(with = "duration", default = "OidcConfig::default_jwks_refresh_interval")]
+    pub jwks_refresh_interval: Duration,
+    /// The accepted signing algorithms, e.g. 'RS256', 'ES256'.
+    #[serde(default = "OidcConfig::default_algorithms")]
+    pub algorithms: Vec<String>,
+    /// The claim of the CnosDB user name.
+    #[serde(default = "OidcConfig::default_user_claim")]
+    pub user_claim: String,
+    /// The claim of the groups, an array of strings.
+    #[serde(default = "OidcConfig::default_groups_claim")]
+    pub groups_claim: String,
+}
+
+impl OidcConfig {
+    fn default_jwks_refresh_interval() -> Duration {
+        Duration::from_secs(60 * 60)
+    }
+
+    fn default_algorithms() -> Vec<String> {
+        vec!["RS256".to_string()]
+    }
+
+    fn default_user_claim() -> String {
+        "sub".to_string()
+    }
+
+    fn default_groups_claim() -> String {
+        "groups".to_string()
+    }
+}
+
+impl Default for OidcConfig {
+    fn default() -> Self {
+        Self {
+            issuer: None,
+            audience: None,
+            jwks_file: None,
+            jwks_url: None,
+            jwks_refresh_interval: Self::default_jwks_refresh_interval(),
+            algorithms: Self::default_algorithms(),
+            user_claim: Self::default_user_claim(),
+            groups_claim: Self::default_groups_claim(),
+        }
+    }
+}
+
+impl CheckConfig for OidcConfig {
+    fn check(&self, _: &super::Config) -> Option<CheckConfigResult> {
+        let config_name = Arc::new("auth.oidc".to_string());
+        let mut ret = CheckConfigResult::default();
+
+        if self.jwks_file.is_some() == self.jwks_url.is_some() {
+            ret.add_error(CheckConfigItemResult {
+                config: config_name.clone(),
+                item: "jwks_file/jwks_url".to_string(),
+                message: "exactly one of 'jwks_file' and 'jwks_url' must be set".to_string(),
+            });
+        }
+        if self.algorithms.is_empty() {
+            ret.add_error(CheckConfigItemResult {
+                config: config_name.clone(),
+                item: "algorithms".to_string(),
+                message: "'algorithms' is empty".to_string(),
+            });
+        }
+        if self.audience.is_none() {
+            ret.add_warn(CheckConfigItemResult {
+                config: config_name,
+                item: "audience".to_string(),
+                message: "'audience' is not set, the tokens issued for other clients are accepted"
+                    .to_string(),
+            });
+        }
+
+        if ret.is_empty() {
+            None
+        } else {
+            Some(ret)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, Keys)]
+pub struct GroupRoleConfig {
+    /// The group of the external user
+    pub group: String,
+    pub tenant: String,
+    /// 'owner', 'member' or a custom role of the tenant
+    pub role: String,
+}
+
+impl CheckConfig for GroupRoleConfig {
+    fn check(&self, _: &super::Config) -> Option<CheckConfigResult> {
+        let config_name = Arc::new("auth.group_roles".to_string());
+        let mut ret = CheckConfigResult::default();
+
+        for (item, value) in [
+            ("group", &self.group),
+            ("tenant", &self.tenant),
+            ("role", &self.role),
+        ] {
+            if value.is_empty() {
+                ret.add_error(CheckConfigItemResult {
+                    config: config_name.clone(),
+                    item: item.to_string(),
+                    message: format!("'{}' is empty", item),
+                });
+            }
+        }
+
+        if ret.is_empty() {
+            None
+        } else {
+            Some(ret)
+        }
+    }
+}
@@ -1,3 +1,4 @@
+mod auth_config;
 mod cache_config;
 mod cluster_config;
 mod deployment_config;
@@ -13,6 +14,7 @@ mod wal_config;
 use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
+pub use auth_config::*;
 pub use cache_config::*;
 pub use cluster_config::*;
 pub use deployment_config::*;
@@ -31,7 +33,7 @@ pub use trace::*;
 pub use wal_config::*;
 
 use crate::check::{CheckConfig, CheckConfigResult};
-use crate::common::LogConfig;
+use crate::common::{AuditConfig, LogConfig};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default, Keys)]
 pub struct Config {
@@ -82,6 +84,14 @@ pub struct Config {
     /// Tracing configs.
     #[serde(default = "Default::default")]
     pub trace: TraceConfig,
+
+    /// Audit log configs.
+    #[serde(default = "Default::default")]
+    pub audit: AuditConfig,
+
+    /// External authentication configs.
+    #[serde(default = "Default::default")]
+    pub auth: AuthConfig,
 }
 
 impl Config {
@@ -168,6 +178,12 @@ pub fn check_config(path: impl AsRef<Path>, show_warnings: bool) {
             if let Some(c) = cfg.cluster.check(&cfg) {
                 check_results.add_all(c)
             }
+            if let Some(c) = cfg.audit.check(&cfg) {
+                check_results.add_all(c)
+            }
+            if let Some(c) = cfg.auth.check(&cfg) {
+                check_results.add_all(c)
+            }
 
             check_results.introspect();
             check_results.show_warnings = show_warnings;
@@ -74,6 +74,9 @@ pub struct StorageConfig {
 
     #[serde(default = "StorageConfig::default_tsm_meta_compress")]
     pub tsm_meta_compress: String,
+
+    /// Object store to move the TSM files of old data to, disabled if not set.
+    pub cold_tier: Option<ColdTierConfig>,
 }
 
 impl StorageConfig {
@@ -172,12 +175,13 @@ impl Default for StorageConfig {
             max_datablock_size: Self::default_max_datablock_size(),
             index_cache_capacity: Self::default_index_cache_capacity(),
             tsm_meta_compress: Self::default_tsm_meta_compress(),
+            cold_tier: None,
         }
     }
 }
 
 impl CheckConfig for StorageConfig {
-    fn check(&self, _: &super::Config) -> Option<CheckConfigResult> {
+    fn check(&self, all_config: &super::Config) -> Option<CheckConfigResult> {
         let config_name = Arc::new("storage".to_string());
         let mut ret = CheckConfigResult::default();
 
@@ -232,6 +236,12 @@ impl CheckConfig for StorageConfig {
             });
         }
 
+        if let Some(ref cold_tier) = self.cold_tier {
+            if let Some(r) = cold_tier.check(all_config) {
+                ret.add_all(r);
+            }
+        }
+
         if self.tsm_meta_compress != "zstd"
             || self.tsm_meta_compress != "snappy"
             || self.tsm_meta_compress != "null"
@@ -252,3 +262,120 @@ impl CheckConfig for StorageConfig {
         }
     }
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Keys)]
+pub struct ColdTierConfig {
+    /// Location of the object store, e.g. 's3://bucket/path', 'gcs://bucket/path',
+    /// 'azblob://container/path' or 'file:///path'.
+    #[serde(default = "ColdTierConfig::default_url")]
+    pub url: String,
+
+    /// TSM files whose data are all older than this duration are moved to the object store.
+    #[serde(
+        with = "duration",
+        default = "ColdTierConfig::default_cold_duration"
+    )]
+    pub cold_duration: Duration,
+
+    #[serde(
+        with = "duration",
+        default = "ColdTierConfig::default_check_interval"
+    )]
+    pub check_interval: Duration,
+
+    /// Size of the blocks read from the object store and cached in memory.
+    #[serde(with = "bytes_num", default = "ColdTierConfig::default_block_size")]
+    pub block_size: u64,
+
+    #[serde(default = "ColdTierConfig::default_max_cached_blocks")]
+    pub max_cached_blocks: usize,
+
+    // Options of S3
+    pub region: Option<String>,
+    pub endpoint_url: Option<String>,
+    pub access_key_id: Option<String>,
+    pub secret_access_key: Option<String>,
+
+    // Options of GCS
+    pub service_account_path: Option<String>,
+
+    // Options of Azure Blob Storage
+    pub account_name: Option<String>,
+    pub access_key: Option<String>,
+}
+
+impl ColdTierConfig {
+    fn default_url() -> String {
+        "file:///var/lib/cnosdb/cold".to_string()
+    }
+
+    fn default_cold_duration() -> Duration {
+        Duration::from_secs(30 * 24 * 60 * 60)
+    }
+
+    fn default_check_interval() -> Duration {
+        Duration::from_secs(10 * 60)
+    }
+
+    fn default_block_size() -> u64 {
+        1024 * 1024
+    }
+
+    fn default_max_cached_blocks() -> usize {
+        1024
+    }
+}
+
+impl Default for ColdTierConfig {
+    fn default() -> Self {
+        Self {
+            url: Self::default_url(),
+            cold_duration: Self::default_cold_duration(),
+            check_interval: Self::default_check_interval(),
+            block_size: Self::default_block_size(),
+            max_cached_blocks: Self::default_max_cached_blocks(),
+            region: None,
+            endpoint_url: None,
+            access_key_id: None,
+            secret_access_key: None,
+            service_account_path: None,
+            account_name: None,
+            access_key: None,
+        }
+    }
+}
+
+impl CheckConfig for ColdTierConfig {
+    fn check(&self, _: &super::Config) -> Option<CheckConfigResult> {
+        let config_name = Arc::new("storage.cold_tier".to_string());
+        let mut ret = CheckConfigResult::default();
+
+        if self.url.is_empty() {
+            ret.add_error(CheckConfigItemResult {
+                config: config_name.clone(),
+                item: "url".to_string(),
+                message: "'url' is empty".to_string(),
+            });
+        }
+        if self.cold_duration < Duration::from_secs(60 * 60) {
+            ret.add_warn(CheckConfigItemResult {
+                config: config_name.clone(),
+                item: "cold_duration".to_string(),
+                message: "'cold_duration' maybe too small(less than 1 hour)".to_string(),
+            });
+        }
+        if self.block_size < 64 * 1024 {
+            ret.add_warn(CheckConfigItemResult {
+                config: config_name,
+                item: "block_size".to_string(),
+                message: "'block_size' maybe too small(less than 64K)".to_string(),
+            });
+        }
+
+        if ret.is_empty() {
+            None
+        } else {
+            Some(ret)
+        }
+    }
+}
@@ -1,5 +1,6 @@
 #![recursion_limit = "256"]
 
+use std::collections::HashMap;
 use std::fmt::Debug;
 use std::pin::Pin;
 use std::sync::atomic::AtomicUsize;
@@ -15,6 +16,7 @@ use models::meta_data::{
 };
 use models::object_reference::ResolvedTable;
 use models::predicate::domain::{ResolvedPredicate, ResolvedPredicateRef};
+use models::schema::table_statistics::TableStatistics;
 use models::schema::tskv_table_schema::TskvTableSchemaRef;
 use protocol_parser::Line;
 use protos::kv_service::{RaftWriteCommand, UpdateSetValue};
@@ -33,6 +35,7 @@ pub mod metrics;
 pub mod raft;
 pub mod reader;
 pub mod resource_manager;
+pub mod series_limit;
 pub mod service;
 pub mod service_mock;
 pub mod tskv_executor;
@@ -112,6 +115,26 @@ pub trait Coordinator: Send + Sync {
 
     async fn compact_vnodes(&self, tenant: &str, vnode_ids: Vec<VnodeId>) -> CoordinatorResult<()>;
 
+    /// Take an incremental backup of the leader vnodes of a database into an object store.
+    async fn backup_database(
+        &self,
+        tenant: &str,
+        db: &str,
+        url: &str,
+        options: HashMap<String, String>,
+    ) -> CoordinatorResult<()>;
+
+    /// Restore a backup into a new database, replaying WAL up to `as_of` if given.
+    /// The database is hidden until the restore finishes, and is dropped if it fails.
+    async fn restore_database(
+        &self,
+        tenant: &str,
+        db: &str,
+        url: &str,
+        options: HashMap<String, String>,
+        as_of: Option<i64>,
+    ) -> CoordinatorResult<()>;
+
     /// A manager to manage vnode.
     async fn replication_manager(
         &self,
@@ -126,6 +149,24 @@ pub trait Coordinator: Send + Sync {
         replica_id: ReplicationSetId,
     ) -> CoordinatorResult<Vec<RecordBatch>>;
 
+    /// Get the series cardinality of the leader vnode of each replica set of a database,
+    /// the RecordBatch of each vnode is in `tskv::series_cardinality_schema`.
+    async fn series_cardinality(
+        &self,
+        tenant: &str,
+        db: &str,
+        table: Option<&str>,
+    ) -> CoordinatorResult<Vec<(VnodeId, RecordBatch)>>;
+
+    /// Get the estimated statistics of a table, merged from the leader vnode
+    /// of each replica set of the database.
+    async fn table_statistics(
+        &self,
+        tenant: &str,
+        db: &str,
+        table: &str,
+    ) -> CoordinatorResult<TableStatistics>;
+
     fn metrics(&self) -> &Arc<CoordServiceMetrics>;
 
     async fn update_tags_value(
@@ -83,6 +83,12 @@ impl RaftNodesManager {
         }
     }
 
+    /// Index of the last raft log entry applied to the local node of the group.
+    pub async fn last_applied_index(&self, group_id: u32) -> Option<u64> {
+        let node = self.raft_nodes.read().await.get_node(group_id).ok()??;
+        node.raft_metrics().last_applied.map(|log_id| log_id.index)
+    }
+
     pub async fn start_all_raft_node(
         runtime: Arc<Runtime>,
         manager: Arc<RaftNodesManager>,
@@ -175,6 +175,11 @@ impl TskvEngineStorage {
                 }
             })?;
         }
+        self.storage
+            .change_feed()
+            .publish(self.vnode_id, ctx.index, || {
+                parse_prost_bytes::<RaftWriteCommand>(req).ok()
+            });
 
         Ok(vec![])
     }
@@ -39,10 +39,11 @@ impl TskvRaftWriter {
         total_memory: usize,
         memory_pool: MemoryPoolRef,
         raft_manager: Arc<RaftNodesManager>,
-        request: RaftWriteCommand,
+        mut request: RaftWriteCommand,
         counter: Arc<AtomicUsize>,
     ) -> TskvRaftWriter {
         counter.fetch_add(1, Ordering::SeqCst);
+        request.timestamp = models::utils::now_timestamp_nanos();
         TskvRaftWriter {
             meta,
             node_id,
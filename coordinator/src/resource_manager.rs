@@ -171,6 +171,7 @@ impl ResourceManager {
                     replica_id: replica.id,
                     tenant: tenant_name.to_string(),
                     db_name: db_name.to_string(),
+                    timestamp: 0,
                     command: Some(raft_write_command::Command::DropTable(request)),
                 };
 
@@ -235,6 +236,7 @@ impl ResourceManager {
                             replica_id: replica.id,
                             tenant: tenant_name.to_string(),
                             db_name: table_schema.db.to_string(),
+                            timestamp: 0,
                             command: Some(raft_write_command::Command::DropColumn(request)),
                         };
 
@@ -297,6 +299,7 @@ impl ResourceManager {
                 replica_id: replica.id,
                 tenant: tenant_name.to_string(),
                 db_name: db_name.to_string(),
+                timestamp: 0,
                 command: Some(raft_write_command::Command::UpdateTags(
                     update_tags_request.clone(),
                 )),
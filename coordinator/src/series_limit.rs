@@ -0,0 +1,154 @@
+//! Limits the series of the databases and tables with `max_series`.
+//!
+//! The series of a database are spread over the vnodes of all its replica sets,
+//! each vnode only knows its own series. So before proposing a write to a replica
+//! set, the coordinator computes how many series the replica set may have from
+//! `max_series` and the series of the other replica sets, and puts it into the
+//! write request, every replica of the replica set applies the same limits.
+//!
+//! The series of the other replica sets are counted for each write of the limited
+//! databases and tables, the series created by the concurrent writes to other
+//! replica sets may still exceed the limits.
+
+use std::collections::HashMap;
+
+use datafusion::arrow::array::{Array, StringArray, UInt64Array};
+use datafusion::arrow::record_batch::RecordBatch;
+use models::meta_data::ReplicationSetId;
+use protos::kv_service::SeriesLimits;
+
+use crate::errors::{CommonSnafu, CoordinatorResult};
+
+/// Series count of the tables in each replica set of a database.
+#[derive(Debug, Default)]
+pub struct ReplicaSeriesCounts {
+    replicas: HashMap<ReplicationSetId, HashMap<String, u64>>,
+}
+
+impl ReplicaSeriesCounts {
+    /// Add the series cardinality of the leader vnode of a replica set,
+    /// the `record_batch` is in `tskv::series_cardinality_schema`.
+    pub fn add_replica(
+        &mut self,
+        replica_id: ReplicationSetId,
+        record_batch: &RecordBatch,
+    ) -> CoordinatorResult<()> {
+        let (Some(table_names), Some(series_counts)) = (
+            record_batch
+                .columns()
+                .first()
+                .and_then(|c| c.as_any().downcast_ref::<StringArray>()),
+            record_batch
+                .columns()
+                .get(1)
+                .and_then(|c| c.as_any().downcast_ref::<UInt64Array>()),
+        ) else {
+            return Err(CommonSnafu {
+                msg: format!(
+                    "Invalid series cardinality schema of replica set {replica_id}: {}",
+                    record_batch.schema()
+                ),
+            }
+            .build());
+        };
+
+        // The series count of a table is repeated in the rows of its tag keys.
+        let tables = self.replicas.entry(replica_id).or_default();
+        for i in 0..record_batch.num_rows() {
+            tables.insert(table_names.value(i).to_string(), series_counts.value(i));
+        }
+
+        Ok(())
+    }
+
+    /// The limits of the series of `replica_id`, `max_series` minus the series
+    /// of the other replica sets, the limits of 0 are not set.
+    pub fn series_limits(
+        &self,
+        replica_id: ReplicationSetId,
+        db_max_series: u64,
+        table_max_series: &HashMap<String, u64>,
+    ) -> SeriesLimits {
+        let others = || {
+            self.replicas
+                .iter()
+                .filter(move |(id, _)| **id != replica_id)
+                .map(|(_, tables)| tables)
+        };
+
+        let database = (db_max_series > 0).then(|| {
+            let count = others().flat_map(|tables| tables.values()).sum::<u64>();
+            db_max_series.saturating_sub(count)
+        });
+        let tables = table_max_series
+            .iter()
+            .filter(|(_, max_series)| **max_series > 0)
+            .map(|(table, max_series)| {
+                let count = others().filter_map(|tables| tables.get(table)).sum::<u64>();
+                (table.clone(), max_series.saturating_sub(count))
+            })
+            .collect();
+
+        SeriesLimits { database, tables }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use datafusion::arrow::array::{StringArray, UInt64Array};
+    use datafusion::arrow::datatypes::{DataType, Field, Schema};
+    use datafusion::arrow::record_batch::RecordBatch;
+
+    use super::ReplicaSeriesCounts;
+
+    fn cardinality(rows: &[(&str, u64)]) -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("table_name", DataType::Utf8, false),
+            Field::new("series_count", DataType::UInt64, false),
+            Field::new("tag_key", DataType::Utf8, true),
+            Field::new("tag_value_count", DataType::UInt64, true),
+        ]));
+        RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(StringArray::from_iter_values(rows.iter().map(|r| r.0))),
+                Arc::new(UInt64Array::from_iter_values(rows.iter().map(|r| r.1))),
+                Arc::new(StringArray::from(vec![Some("host"); rows.len()])),
+                Arc::new(UInt64Array::from(vec![Some(1); rows.len()])),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_series_limits() {
+        let mut counts = ReplicaSeriesCounts::default();
+        counts
+            .add_replica(1, &cardinality(&[("air", 3), ("air", 3), ("sea", 2)]))
+            .unwrap();
+        counts.add_replica(2, &cardinality(&[("air", 4)])).unwrap();
+        counts.add_replica(3, &cardinality(&[("sea", 6)])).unwrap();
+
+        let tables = HashMap::from([("air".to_string(), 5), ("sea".to_string(), 0)]);
+        let limits = counts.series_limits(1, 12, &tables);
+        assert_eq!(limits.database, Some(2));
+        assert_eq!(limits.tables, HashMap::from([("air".to_string(), 1)]));
+
+        let limits = counts.series_limits(2, 0, &tables);
+        assert_eq!(limits.database, None);
+        assert_eq!(limits.tables, HashMap::from([("air".to_string(), 2)]));
+
+        // The other replica sets have reached the limits.
+        let limits = counts.series_limits(3, 8, &tables);
+        assert_eq!(limits.database, Some(0));
+        assert_eq!(limits.tables, HashMap::from([("air".to_string(), 0)]));
+
+        // A new replica set.
+        let limits = counts.series_limits(4, 20, &HashMap::new());
+        assert_eq!(limits.database, Some(5));
+        assert!(limits.tables.is_empty());
+    }
+}
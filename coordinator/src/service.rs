@@ -31,7 +31,10 @@ use models::meta_data::{
 use models::object_reference::ResolvedTable;
 use models::oid::Identifier;
 use models::predicate::domain::{ResolvedPredicate, ResolvedPredicateRef, TimeRange, TimeRanges};
+use models::schema::database_schema::DatabaseSchema;
 use models::schema::resource_info::{ResourceInfo, ResourceOperator};
+use models::schema::table_schema::TableSchema;
+use models::schema::table_statistics::TableStatistics;
 use models::schema::tskv_table_schema::{ColumnType, TskvTableSchemaRef};
 use models::schema::{DEFAULT_CATALOG, TIME_FIELD_NAME, USAGE_SCHEMA};
 use models::utils::now_timestamp_nanos;
@@ -47,13 +50,15 @@ use snafu::{IntoError, OptionExt, ResultExt};
 use tokio::runtime::Runtime;
 use trace::span_ext::SpanExt;
 use trace::{debug, error, info, Span, SpanContext};
+use tskv::backup::{BackupStorage, DatabaseBackup};
 use tskv::EngineRef;
 use utils::precision::{timestamp_convert, Precision};
 use utils::BkdrHasher;
 
 use crate::errors::{
     ArrowSnafu, BincodeSerdeSnafu, ColumnNotFoundSnafu, CommonSnafu, CoordinatorError,
-    CoordinatorResult, FieldsIsEmptySnafu, MetaSnafu,
+    CoordinatorResult, FieldsIsEmptySnafu, InvalidFlatbufferSnafu, MetaSnafu, ModelSnafu,
+    ProtoPointsSnafu, TskvSnafu,
 };
 use crate::metrics::LPReporter;
 use crate::raft::manager::RaftNodesManager;
@@ -62,6 +67,7 @@ use crate::reader::table_scan::opener::TemporaryTableScanOpener;
 use crate::reader::tag_scan::opener::TemporaryTagScanOpener;
 use crate::reader::{CheckFuture, CheckedCoordinatorRecordBatchStream};
 use crate::resource_manager::ResourceManager;
+use crate::series_limit::ReplicaSeriesCounts;
 use crate::tskv_executor::{TskvAdminRequest, TskvLeaderExecutor};
 use crate::{
     get_replica_all_info, get_vnode_all_info, Coordinator, QueryOption, ReplicationCmdType,
@@ -361,6 +367,140 @@ impl CoordService {
         }
     }
 
+    async fn series_cardinality_on_node(
+        &self,
+        tenant: &str,
+        node_id: NodeId,
+        vnode_id: VnodeId,
+        table: Option<&str>,
+    ) -> CoordinatorResult<(VnodeId, RecordBatch)> {
+        let request = AdminCommand {
+            tenant: tenant.to_string(),
+            command: Some(FetchSeriesCardinality(FetchSeriesCardinalityRequest {
+                vnode_id,
+                table: table.map(|t| t.to_string()),
+            })),
+        };
+
+        let data = self.admin_command_on_node(node_id, request).await?;
+        match record_batch_decode(&data) {
+            Ok(r) => Ok((vnode_id, r)),
+            Err(e) => Err(ArrowSnafu.into_error(e)),
+        }
+    }
+
+    /// The series limits of a write to the replica set `replica_id`,
+    /// None if neither the database nor the written tables set `max_series`.
+    async fn series_limits(
+        &self,
+        tenant: &str,
+        db: &str,
+        replica_id: ReplicationSetId,
+        points: &[u8],
+    ) -> CoordinatorResult<Option<SeriesLimits>> {
+        let meta_client = self.meta.tenant_meta(tenant).await.ok_or_else(|| {
+            CoordinatorError::TenantNotFound {
+                name: tenant.to_string(),
+            }
+        })?;
+        let db_max_series = meta_client
+            .get_db_schema(db)
+            .context(MetaSnafu)?
+            .map(|schema| schema.options().max_series())
+            .unwrap_or_default();
+
+        let fb_points =
+            flatbuffers::root::<protos::models::Points>(points).context(InvalidFlatbufferSnafu)?;
+        let mut table_max_series = HashMap::new();
+        for table in fb_points.tables_iter_ext().context(ProtoPointsSnafu)? {
+            let table = table.tab_ext().context(ProtoPointsSnafu)?;
+            if let Some(schema) = meta_client
+                .get_tskv_table_schema(db, table)
+                .context(MetaSnafu)?
+            {
+                if schema.max_series() > 0 {
+                    table_max_series.insert(table.to_string(), schema.max_series());
+                }
+            }
+        }
+        if db_max_series == 0 && table_max_series.is_empty() {
+            return Ok(None);
+        }
+
+        let counts = self.replica_series_counts(tenant, db, replica_id).await?;
+        Ok(Some(counts.series_limits(
+            replica_id,
+            db_max_series,
+            &table_max_series,
+        )))
+    }
+
+    /// Count the series of the tables in the leader vnodes of the replica sets
+    /// of a database, except the replica set `exclude`.
+    async fn replica_series_counts(
+        &self,
+        tenant: &str,
+        db: &str,
+        exclude: ReplicationSetId,
+    ) -> CoordinatorResult<ReplicaSeriesCounts> {
+        let meta = self.meta.tenant_meta(tenant).await.ok_or_else(|| {
+            CoordinatorError::TenantNotFound {
+                name: tenant.to_string(),
+            }
+        })?;
+        let database =
+            meta.get_db_info(db)
+                .context(MetaSnafu)?
+                .ok_or_else(|| CoordinatorError::Meta {
+                    source: MetaError::DatabaseNotFound {
+                        database: db.to_string(),
+                    },
+                })?;
+
+        let mut req_futures = vec![];
+        for replica in database.buckets.iter().flat_map(|b| b.shard_group.iter()) {
+            if replica.id == exclude {
+                continue;
+            }
+            let replica_id = replica.id;
+            let request = self.series_cardinality_on_node(
+                tenant,
+                replica.leader_node_id,
+                replica.leader_vnode_id,
+                None,
+            );
+            req_futures
+                .push(async move { Ok::<_, CoordinatorError>((replica_id, request.await?.1)) });
+        }
+        let record_batches = futures::future::try_join_all(req_futures).await?;
+
+        let mut counts = ReplicaSeriesCounts::default();
+        for (replica_id, record_batch) in record_batches.iter() {
+            counts.add_replica(*replica_id, record_batch)?;
+        }
+
+        Ok(counts)
+    }
+
+    async fn table_statistics_on_node(
+        &self,
+        tenant: &str,
+        node_id: NodeId,
+        vnode_id: VnodeId,
+        table: &str,
+    ) -> CoordinatorResult<TableStatistics> {
+        let request = AdminCommand {
+            tenant: tenant.to_string(),
+            command: Some(FetchTableStatistics(FetchTableStatisticsRequest {
+                vnode_id,
+                table: table.to_string(),
+            })),
+        };
+
+        let data = self.admin_command_on_node(node_id, request).await?;
+        TableStatistics::decode(&data).context(ModelSnafu)
+    }
+
     async fn push_points_to_requests<'a>(
         &'a self,
         tenant: &'a str,
@@ -396,14 +536,17 @@ impl CoordService {
 
         let mut requests: Vec<Pin<Box<dyn Future<Output = Result<(), CoordinatorError>> + Send>>> =
             Vec::new();
+        let series_limits = self.series_limits(tenant, db, info.id, &points).await?;
         let request = WriteDataRequest {
             precision: precision as u32,
             data: Arc::unwrap_or_clone(points),
+            series_limits,
         };
         let request = RaftWriteCommand {
             replica_id: info.id,
             db_name: db.to_string(),
             tenant: tenant.to_string(),
+            timestamp: 0,
 
             command: Some(raft_write_command::Command::WriteData(request)),
         };
@@ -471,6 +614,102 @@ impl CoordService {
 
         Ok(())
     }
+
+    /// Create the tables and buckets of the backup in the restoring database `db`, then
+    /// restore the vnodes of each replication set from the backup.
+    #[allow(clippy::too_many_arguments)]
+    async fn restore_database_vnodes(
+        &self,
+        meta: &MetaClientRef,
+        tenant: &str,
+        db: &str,
+        url: &str,
+        options: &HashMap<String, String>,
+        as_of: Option<i64>,
+        backup: &DatabaseBackup,
+    ) -> CoordinatorResult<()> {
+        for table in backup.database.tables.values() {
+            if let TableSchema::TsKvTableSchema(table) = table {
+                let mut table = table.as_ref().clone();
+                table.tenant = tenant.to_string();
+                table.db = db.to_string();
+                meta.create_table(&TableSchema::TsKvTableSchema(Arc::new(table)))
+                    .await
+                    .context(MetaSnafu)?;
+            }
+        }
+
+        for bucket in backup.database.buckets.iter() {
+            let new_bucket = meta
+                .create_bucket(db, bucket.start_time)
+                .await
+                .context(MetaSnafu)?;
+            for (replica, new_replica) in bucket.shard_group.iter().zip(new_bucket.shard_group) {
+                let backup_vnode_id = match backup.replica_vnodes.get(&replica.id) {
+                    Some(vnode_id) => *vnode_id,
+                    None => continue,
+                };
+
+                let cmd = AdminCommand {
+                    tenant: tenant.to_string(),
+                    command: Some(BuildRaftGroup(BuildRaftGroupRequest {
+                        db_name: db.to_string(),
+                        replica_id: new_replica.id,
+                    })),
+                };
+                self.admin_command_on_node(new_replica.leader_node_id, cmd)
+                    .await?;
+
+                // Followers only load files, WAL is replayed on the leader through raft.
+                let mut vnodes = new_replica.vnodes.clone();
+                vnodes.sort_by_key(|v| v.id == new_replica.leader_vnode_id);
+                for vnode in vnodes {
+                    let is_leader = vnode.id == new_replica.leader_vnode_id;
+                    let cmd = AdminCommand {
+                        tenant: tenant.to_string(),
+                        command: Some(RestoreVnode(RestoreVnodeRequest {
+                            db_name: db.to_string(),
+                            vnode_id: vnode.id,
+                            replica_id: new_replica.id,
+                            url: url.to_string(),
+                            options: options.clone(),
+                            backup_id: backup.backup_id,
+                            backup_vnode_id,
+                            replay_wal: is_leader,
+                            as_of: if is_leader { as_of } else { None },
+                        })),
+                    };
+                    self.admin_command_on_node(vnode.node_id, cmd).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Drop the database whose restore failed, errors are only logged.
+    async fn drop_restoring_database(&self, meta: &MetaClientRef, tenant: &str, db: &str) {
+        let buckets = match meta.get_db_info(db) {
+            Ok(info) => info.map_or(vec![], |info| info.buckets),
+            Err(e) => {
+                error!("get restoring database {tenant}.{db} failed: {e}");
+                vec![]
+            }
+        };
+        for bucket in buckets {
+            for replica in bucket.shard_group {
+                let cmd_type = ReplicationCmdType::DestoryRaftGroup(replica.id);
+                if let Err(e) = self.replication_manager(tenant, cmd_type).await {
+                    error!(
+                        "destroy raft group {} of {tenant}.{db} failed: {e}",
+                        replica.id
+                    );
+                }
+            }
+        }
+        if let Err(e) = meta.drop_db(db).await {
+            error!("drop restoring database {tenant}.{db} failed: {e}");
+        }
+    }
 }
 
 //***************************** Coordinator Interface ***************************************** */
@@ -870,6 +1109,7 @@ impl Coordinator for CoordService {
                 replica_id: replica.id,
                 tenant: table.tenant().to_string(),
                 db_name: table.database().to_string(),
+                timestamp: 0,
                 command: Some(raft_write_command::Command::DeleteFromTable(request)),
             };
 
@@ -1001,6 +1241,124 @@ impl Coordinator for CoordService {
         return Ok(());
     }
 
+    async fn backup_database(
+        &self,
+        tenant: &str,
+        db: &str,
+        url: &str,
+        options: HashMap<String, String>,
+    ) -> CoordinatorResult<()> {
+        let meta =
+            self.tenant_meta(tenant)
+                .await
+                .ok_or_else(|| CoordinatorError::TenantNotFound {
+                    name: tenant.to_string(),
+                })?;
+        let database =
+            meta.get_db_info(db)
+                .context(MetaSnafu)?
+                .ok_or_else(|| CoordinatorError::Meta {
+                    source: MetaError::DatabaseNotFound {
+                        database: db.to_string(),
+                    },
+                })?;
+        let storage = BackupStorage::new(url, &options).context(TskvSnafu)?;
+        let backup_id = now_timestamp_nanos();
+
+        // Only the leader vnode of each replica set is backed up.
+        let mut replica_vnodes = HashMap::new();
+        let mut req_futures = vec![];
+        for bucket in database.buckets.iter() {
+            for replica in bucket.shard_group.iter() {
+                replica_vnodes.insert(replica.id, replica.leader_vnode_id);
+                let cmd = AdminCommand {
+                    tenant: tenant.to_string(),
+                    command: Some(BackupVnode(BackupVnodeRequest {
+                        db_name: db.to_string(),
+                        vnode_id: replica.leader_vnode_id,
+                        url: url.to_string(),
+                        options: options.clone(),
+                        backup_id,
+                    })),
+                };
+                req_futures.push(self.admin_command_on_node(replica.leader_node_id, cmd));
+            }
+        }
+        for res in futures::future::join_all(req_futures).await {
+            res?;
+        }
+
+        let backup = DatabaseBackup {
+            backup_id,
+            database,
+            replica_vnodes,
+        };
+        storage
+            .put_database_backup(&backup)
+            .await
+            .context(TskvSnafu)?;
+        info!("backup database {tenant}.{db} to {url} finished, backup id: {backup_id}");
+
+        Ok(())
+    }
+
+    async fn restore_database(
+        &self,
+        tenant: &str,
+        db: &str,
+        url: &str,
+        options: HashMap<String, String>,
+        as_of: Option<i64>,
+    ) -> CoordinatorResult<()> {
+        let meta =
+            self.tenant_meta(tenant)
+                .await
+                .ok_or_else(|| CoordinatorError::TenantNotFound {
+                    name: tenant.to_string(),
+                })?;
+        let storage = BackupStorage::new(url, &options).context(TskvSnafu)?;
+        let backup = storage
+            .database_backup(as_of)
+            .await
+            .context(TskvSnafu)?
+            .context(CommonSnafu {
+                msg: format!("no backup found in {url} for the given time"),
+            })?;
+
+        if meta.get_db_schema(db).context(MetaSnafu)?.is_some() {
+            return Err(CoordinatorError::Meta {
+                source: MetaError::DatabaseAlreadyExists {
+                    database: db.to_string(),
+                },
+            });
+        }
+        // The database stays hidden until all vnodes are restored, and is dropped if the
+        // restore fails, so a partially restored database is never visible.
+        let schema = &backup.database.schema;
+        let mut new_schema =
+            DatabaseSchema::new(tenant, db, schema.options().clone(), schema.config());
+        new_schema.set_db_is_hidden(true);
+        meta.create_db(new_schema).await.context(MetaSnafu)?;
+
+        if let Err(e) = self
+            .restore_database_vnodes(&meta, tenant, db, url, &options, as_of, &backup)
+            .await
+        {
+            error!("restore database {tenant}.{db} from {url} failed, drop it: {e}");
+            self.drop_restoring_database(&meta, tenant, db).await;
+            return Err(e);
+        }
+        meta.set_db_is_hidden(tenant, db, false)
+            .await
+            .context(MetaSnafu)?;
+        info!(
+            "restore database {tenant}.{db} from {url} finished, backup id: {}",
+            backup.backup_id
+        );
+
+        Ok(())
+    }
+
     async fn replica_checksum(
         &self,
         tenant: &str,
@@ -1035,6 +1393,86 @@ impl Coordinator for CoordService {
         Ok(record_batches)
     }
 
+    async fn series_cardinality(
+        &self,
+        tenant: &str,
+        db: &str,
+        table: Option<&str>,
+    ) -> CoordinatorResult<Vec<(VnodeId, RecordBatch)>> {
+        let meta =
+            self.tenant_meta(tenant)
+                .await
+                .ok_or_else(|| CoordinatorError::TenantNotFound {
+                    name: tenant.to_string(),
+                })?;
+        let database =
+            meta.get_db_info(db)
+                .context(MetaSnafu)?
+                .ok_or_else(|| CoordinatorError::Meta {
+                    source: MetaError::DatabaseNotFound {
+                        database: db.to_string(),
+                    },
+                })?;
+
+        // Vnodes of a replica set have the same series, only the leader is counted.
+        let mut req_futures = vec![];
+        for bucket in database.buckets.iter() {
+            for replica in bucket.shard_group.iter() {
+                req_futures.push(self.series_cardinality_on_node(
+                    tenant,
+                    replica.leader_node_id,
+                    replica.leader_vnode_id,
+                    table,
+                ));
+            }
+        }
+        let record_batches = futures::future::try_join_all(req_futures).await?;
+
+        Ok(record_batches)
+    }
+
+    async fn table_statistics(
+        &self,
+        tenant: &str,
+        db: &str,
+        table: &str,
+    ) -> CoordinatorResult<TableStatistics> {
+        let meta =
+            self.tenant_meta(tenant)
+                .await
+                .ok_or_else(|| CoordinatorError::TenantNotFound {
+                    name: tenant.to_string(),
+                })?;
+        let database =
+            meta.get_db_info(db)
+                .context(MetaSnafu)?
+                .ok_or_else(|| CoordinatorError::Meta {
+                    source: MetaError::DatabaseNotFound {
+                        database: db.to_string(),
+                    },
+                })?;
+
+        // Vnodes of a replica set have the same data, only the leader is analyzed.
+        let mut req_futures = vec![];
+        for bucket in database.buckets.iter() {
+            for replica in bucket.shard_group.iter() {
+                req_futures.push(self.table_statistics_on_node(
+                    tenant,
+                    replica.leader_node_id,
+                    replica.leader_vnode_id,
+                    table,
+                ));
+            }
+        }
+
+        let mut statistics = TableStatistics::default();
+        for vnode_statistics in futures::future::try_join_all(req_futures).await? {
+            statistics.merge(&vnode_statistics);
+        }
+
+        Ok(statistics)
+    }
+
     fn metrics(&self) -> &Arc<CoordServiceMetrics> {
         &self.metrics
     }
@@ -1139,6 +1577,7 @@ impl Coordinator for CoordService {
                 replica_id: replica.id,
                 tenant: tenant.to_string(),
                 db_name: db.to_string(),
+                timestamp: 0,
                 command: Some(raft_write_command::Command::UpdateTags(
                     update_tags_request.clone(),
                 )),
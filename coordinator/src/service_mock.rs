@@ -1,5 +1,6 @@
 #![allow(dead_code, unused_variables)]
 
+use std::collections::HashMap;
 use std::fmt::Debug;
 use std::sync::atomic::AtomicUsize;
 use std::sync::Arc;
@@ -13,6 +14,7 @@ use meta::model::{MetaClientRef, MetaRef};
 use models::meta_data::{ReplicationSet, ReplicationSetId, VnodeId, VnodeInfo, VnodeStatus};
 use models::object_reference::ResolvedTable;
 use models::predicate::domain::{ResolvedPredicate, ResolvedPredicateRef};
+use models::schema::table_statistics::TableStatistics;
 use models::schema::tskv_table_schema::TskvTableSchemaRef;
 use protocol_parser::Line;
 use protos::kv_service::{RaftWriteCommand, UpdateSetValue};
@@ -53,6 +55,27 @@ impl Coordinator for MockCoordinator {
         todo!()
     }
 
+    async fn backup_database(
+        &self,
+        tenant: &str,
+        db: &str,
+        url: &str,
+        options: HashMap<String, String>,
+    ) -> CoordinatorResult<()> {
+        todo!()
+    }
+
+    async fn restore_database(
+        &self,
+        tenant: &str,
+        db: &str,
+        url: &str,
+        options: HashMap<String, String>,
+        as_of: Option<i64>,
+    ) -> CoordinatorResult<()> {
+        todo!()
+    }
+
     fn tskv_raft_writer(&self, request: RaftWriteCommand) -> TskvRaftWriter {
         todo!()
     }
@@ -220,6 +243,24 @@ impl Coordinator for MockCoordinator {
         Ok(vec![])
     }
 
+    async fn series_cardinality(
+        &self,
+        tenant: &str,
+        db: &str,
+        table: Option<&str>,
+    ) -> CoordinatorResult<Vec<(VnodeId, RecordBatch)>> {
+        Ok(vec![])
+    }
+
+    async fn table_statistics(
+        &self,
+        tenant: &str,
+        db: &str,
+        table: &str,
+    ) -> CoordinatorResult<TableStatistics> {
+        Ok(TableStatistics::default())
+    }
+
     fn metrics(&self) -> &Arc<CoordServiceMetrics> {
         todo!()
     }
@@ -0,0 +1,51 @@
+use reqwest::StatusCode;
+use serial_test::serial;
+
+use crate::utils::global::E2eContext;
+use crate::{check_response, cluster_def};
+
+#[test]
+#[serial]
+fn restore_failed_cleanup() {
+    println!("Test begin restore_failed_cleanup");
+
+    let mut ctx = E2eContext::new("backup_tests", "restore_failed_cleanup");
+    let mut executor = ctx.build_executor(cluster_def::one_data(1));
+    let host_port = executor.cluster_definition().data_cluster_def[0].http_host_port;
+
+    executor.startup();
+
+    let client = executor.case_context().data_client(0);
+    let backup_dir = executor.case_context().test_dir().join("backup");
+    let _ = std::fs::remove_dir_all(&backup_dir);
+    std::fs::create_dir_all(&backup_dir).unwrap();
+    let url = format!("file://{}", backup_dir.display());
+    let sql_url = format!("http://{host_port}/api/v1/sql?db=public");
+
+    check_response!(client.post(&sql_url, "create database db1;"));
+    check_response!(client.post(
+        format!("http://{host_port}/api/v1/write?db=db1"),
+        "cpu,host=a usage=1 1\ncpu,host=b usage=2 2",
+    ));
+    check_response!(client.post(&sql_url, &format!("backup database db1 to '{url}';")));
+
+    // Remove the shipped files of the vnodes, so restoring the vnodes fails.
+    for entry in std::fs::read_dir(&backup_dir).unwrap() {
+        let entry = entry.unwrap();
+        if entry.file_name().to_string_lossy().starts_with("vnode_") {
+            std::fs::remove_dir_all(entry.path()).unwrap();
+        }
+    }
+    let resp = client
+        .post(&sql_url, &format!("restore database db2 from '{url}';"))
+        .unwrap();
+    assert_ne!(resp.status(), StatusCode::OK);
+
+    // The partially restored database is dropped.
+    let resp = check_response!(client.post(&sql_url, "show databases;"));
+    assert_eq!(
+        resp.text().unwrap(),
+        "database_name\ncluster_schema\ndb1\npublic\nusage_schema\n"
+    );
+    check_response!(client.post(&sql_url, "create database db2;"));
+}
@@ -13,6 +13,7 @@ mod coordinator_tests;
 mod chaos_tests;
 
 mod auth_tests;
+mod backup_tests;
 mod client_tests;
 mod computing_storage_tests;
 mod flush_tests;
@@ -12,7 +12,7 @@ use arrow_flight::FlightInfo;
 use datafusion::arrow::error::ArrowError;
 use datafusion::arrow::record_batch::RecordBatch;
 use datafusion::arrow::util::pretty;
-use datafusion::{arrow, assert_batches_eq};
+use datafusion::{arrow, assert_batches_eq, assert_batches_sorted_eq};
 use futures::TryStreamExt;
 use tonic::transport::{Channel, Endpoint};
 
@@ -1525,3 +1525,114 @@ async fn test_select() {
 
     assert_batches_eq!(expected, &actual);
 }
+
+#[tokio::test]
+async fn test_flight_sql_vnode_endpoints() {
+    let mut client = authed_client().await;
+
+    let db_name = "vnode_endpoints_test";
+    clean_env(&mut client, db_name).await;
+
+    let flight_info = client
+        .execute(
+            format!("CREATE DATABASE {db_name} WITH TTL '100000d' SHARD 2;"),
+            None,
+        )
+        .await
+        .unwrap();
+    let actual = fetch_result_and_print(flight_info, &mut client).await;
+    assert!(actual.is_empty());
+    client.set_header("db", db_name);
+
+    let flight_info = client
+        .execute(
+            "CREATE TABLE air (temperature DOUBLE, TAGS(station));".to_string(),
+            None,
+        )
+        .await
+        .unwrap();
+    let actual = fetch_result_and_print(flight_info, &mut client).await;
+    assert!(actual.is_empty());
+    let flight_info = client
+        .execute(
+            "INSERT air(TIME, station, temperature) VALUES
+            (1, 'XiaoMaiDao', 50.0), (2, 'XiaoMaiDao', 51.0),
+            (1, 'LianYunGang', 60.0), (2, 'LianYunGang', 61.0);"
+                .to_string(),
+            None,
+        )
+        .await
+        .unwrap();
+    let actual = fetch_result_and_print(flight_info, &mut client).await;
+    let expected: Vec<&str> = vec!["+------+", "| rows |", "+------+", "| 4    |", "+------+"];
+    assert_batches_eq!(expected, &actual);
+
+    client.set_header("vnode_endpoints", "true");
+
+    // A scan of a table is split into the partitions of its replica sets.
+    let flight_info = client
+        .execute("SELECT station, temperature FROM air;".to_string(), None)
+        .await
+        .unwrap();
+    assert_eq!(flight_info.endpoint.len(), 2);
+    let actual = fetch_result_and_print(flight_info, &mut client).await;
+    let expected = [
+        "+-------------+-------------+",
+        "| station     | temperature |",
+        "+-------------+-------------+",
+        "| LianYunGang | 60.0        |",
+        "| LianYunGang | 61.0        |",
+        "| XiaoMaiDao  | 50.0        |",
+        "| XiaoMaiDao  | 51.0        |",
+        "+-------------+-------------+",
+    ];
+    assert_batches_sorted_eq!(expected, &actual);
+
+    // Aggregations and joins are not split, they are executed by the coordinator.
+    let flight_info = client
+        .execute(
+            "SELECT station, count(*) AS cnt, max(temperature) AS max_temperature
+            FROM air GROUP BY station ORDER BY station;"
+                .to_string(),
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(flight_info.endpoint.len(), 1);
+    let actual = fetch_result_and_print(flight_info, &mut client).await;
+    let expected = [
+        "+-------------+-----+-----------------+",
+        "| station     | cnt | max_temperature |",
+        "+-------------+-----+-----------------+",
+        "| LianYunGang | 2   | 61.0            |",
+        "| XiaoMaiDao  | 2   | 51.0            |",
+        "+-------------+-----+-----------------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+
+    let flight_info = client
+        .execute(
+            "SELECT a.station, a.temperature, b.temperature AS other_temperature
+            FROM air a JOIN air b ON a.station = b.station AND a.time < b.time
+            ORDER BY a.station;"
+                .to_string(),
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(flight_info.endpoint.len(), 1);
+    let actual = fetch_result_and_print(flight_info, &mut client).await;
+    let expected = [
+        "+-------------+-------------+-------------------+",
+        "| station     | temperature | other_temperature |",
+        "+-------------+-------------+-------------------+",
+        "| LianYunGang | 60.0        | 61.0              |",
+        "| XiaoMaiDao  | 50.0        | 51.0              |",
+        "+-------------+-------------+-------------------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+
+    client.set_header("vnode_endpoints", "false");
+    clean_env(&mut client, db_name).await;
+    check_close(&mut client).await;
+}
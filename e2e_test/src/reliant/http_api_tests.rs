@@ -69,6 +69,28 @@ fn test_v1_sql_path() {
         &HeaderValue::from_static("application/nd-json")
     );
 
+    // accept: application/vnd.apache.arrow.stream
+    let mut req = client.request_with_auth(Method::POST, url).body(body);
+    req = req.headers(headers! {
+        ACCEPT.as_str() => "application/vnd.apache.arrow.stream"
+    });
+    let resp = check_response!(client.execute(req.build().unwrap()));
+    assert_eq!(
+        resp.headers().get(CONTENT_TYPE).unwrap(),
+        &HeaderValue::from_static("application/vnd.apache.arrow.stream")
+    );
+
+    // accept: application/vnd.apache.parquet
+    let mut req = client.request_with_auth(Method::POST, url).body(body);
+    req = req.headers(headers! {
+        ACCEPT.as_str() => "application/vnd.apache.parquet"
+    });
+    let resp = check_response!(client.execute(req.build().unwrap()));
+    assert_eq!(
+        resp.headers().get(CONTENT_TYPE).unwrap(),
+        &HeaderValue::from_static("application/vnd.apache.parquet")
+    );
+
     // accept: application/*
     let mut req = client.request_with_auth(Method::POST, url).body(body);
     req = req.headers(headers! {
@@ -1,4 +1,4 @@
-use http_protocol::header::{self, PRIVATE_KEY};
+use http_protocol::header::{self, BEARER_PREFIX, PRIVATE_KEY};
 use models::schema::DEFAULT_CATALOG;
 use spi::server::dbms::DBMSRef;
 use tonic::metadata::MetadataMap;
@@ -27,6 +27,21 @@ impl CallHeaderAuthenticator for BasicCallHeaderAuthenticator {
     async fn authenticate(&self, req_headers: &MetadataMap) -> Result<Self::AuthResult, Status> {
         debug!("authenticate, request headers: {:?}", req_headers);
 
+        let tenant = utils::get_value_from_header(req_headers, header::TENANT, "");
+
+        // the bearer token issued by the external identity provider, e.g. OIDC
+        if let Some(token) = utils::get_value_from_auth_header(req_headers, BEARER_PREFIX) {
+            let user = self
+                .instance
+                .authenticate_token(&token, tenant.as_deref().unwrap_or(DEFAULT_CATALOG))
+                .await
+                .map_err(|e| Status::unauthenticated(e.to_string()))?;
+
+            debug!("authenticate success, user: {}", user.desc().name());
+
+            return Ok(CommonAuthResult { user });
+        }
+
         let authorization = utils::get_value_from_auth_header(req_headers, "")
             .ok_or_else(|| Status::unauthenticated("authorization field not present"))?;
         let private_key = utils::get_value_from_header(req_headers, PRIVATE_KEY, "");
@@ -44,8 +59,6 @@ impl CallHeaderAuthenticator for BasicCallHeaderAuthenticator {
         .try_get_basic_auth()
         .map_err(|e| Status::invalid_argument(e.to_string()))?;
 
-        let tenant = utils::get_value_from_header(req_headers, header::TENANT, "");
-
         let user = self
             .instance
             .authenticate(&user_info, tenant.as_deref().unwrap_or(DEFAULT_CATALOG))
@@ -93,4 +106,20 @@ mod test {
 
         assert_eq!(req_headers.len(), 1)
     }
+
+    #[tokio::test]
+    async fn test_bearer_token() {
+        let instance = Arc::new(DatabaseManagerSystemMock {});
+        let authenticator = BasicCallHeaderAuthenticator::new(instance);
+
+        let mut req_headers = MetadataMap::default();
+        let val = AsciiMetadataValue::from_static("Bearer xx.yy.zz");
+        req_headers.insert(AUTHORIZATION.as_str(), val);
+
+        let auth_result = authenticator
+            .authenticate(&req_headers)
+            .await
+            .expect("authenticate");
+        assert_eq!(auth_result.identity().desc().name(), "token");
+    }
 }
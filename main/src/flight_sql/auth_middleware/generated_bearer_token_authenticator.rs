@@ -56,16 +56,27 @@ where
         // Check if headers contain a bearer token and if so, validate the token.
         if let Some(bearer_token) = utils::get_value_from_auth_header(req_headers, BEARER_PREFIX) {
             // get user_info from cache by token
-            let user = self
-                .bearer_to_identifier
-                .get(&bearer_token)
-                .ok_or_else(|| Status::unauthenticated("token has expired or not exist"))?;
-
-            debug!("authenticate success, bearer_token exists");
-
+            if let Some(user) = self.bearer_to_identifier.get(&bearer_token) {
+                debug!("authenticate success, bearer_token exists");
+
+                return Ok(GeneratedBearerTokenAuthResult {
+                    user,
+                    bearer_token: Some(bearer_token),
+                });
+            }
+
+            debug!("bearer_token not generated, delegate to initial_authenticator");
+
+            // The token may be issued by the external identity provider, which is
+            // validated for each request, so no new bearer token is generated.
+            let auth_result = self
+                .initial_authenticator
+                .authenticate(req_headers)
+                .await
+                .map_err(|_| Status::unauthenticated("token has expired or not exist"))?;
             return Ok(GeneratedBearerTokenAuthResult {
-                user,
-                bearer_token: Some(bearer_token),
+                user: auth_result.identity(),
+                bearer_token: None,
             });
         }
 
@@ -1,3 +1,4 @@
+use std::net::SocketAddr;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::time::Duration;
@@ -9,12 +10,12 @@ use arrow_flight::sql::{
     ActionBeginTransactionResult, ActionCancelQueryRequest, ActionCancelQueryResult,
     ActionClosePreparedStatementRequest, ActionCreatePreparedStatementRequest,
     ActionCreatePreparedStatementResult, ActionCreatePreparedSubstraitPlanRequest,
-    ActionEndSavepointRequest, ActionEndTransactionRequest, CommandGetCatalogs,
+    ActionEndSavepointRequest, ActionEndTransactionRequest, Any, CancelResult, CommandGetCatalogs,
     CommandGetCrossReference, CommandGetDbSchemas, CommandGetExportedKeys, CommandGetImportedKeys,
     CommandGetPrimaryKeys, CommandGetSqlInfo, CommandGetTableTypes, CommandGetTables,
     CommandGetXdbcTypeInfo, CommandPreparedStatementQuery, CommandPreparedStatementUpdate,
-    CommandStatementQuery, CommandStatementSubstraitPlan, CommandStatementUpdate, ProstMessageExt,
-    SqlInfo, TicketStatementQuery,
+    CommandStatementQuery, CommandStatementSubstraitPlan, CommandStatementUpdate, EndSavepoint,
+    EndTransaction, ProstMessageExt, SqlInfo, SubstraitPlan, TicketStatementQuery,
 };
 use arrow_flight::{
     utils as flight_utils, Action, FlightData, FlightDescriptor, FlightEndpoint, FlightInfo,
@@ -22,13 +23,15 @@ use arrow_flight::{
 };
 use datafusion::arrow::datatypes::{Schema, SchemaRef, ToByteSlice};
 use futures::Stream;
-use http_protocol::header::{DB, STREAM_TRIGGER_INTERVAL, TARGET_PARTITIONS, TENANT};
+use http_protocol::header::{
+    DB, STREAM_TRIGGER_INTERVAL, TARGET_PARTITIONS, TENANT, VNODE_ENDPOINTS,
+};
 use models::auth::user::User;
 use models::oid::UuidGenerator;
 use moka::sync::Cache;
 use prost::bytes::Bytes;
 use prost::Message;
-use spi::query::config::StreamTriggerInterval;
+use spi::query::config::{ScanVnodes, StreamTriggerInterval};
 use spi::query::execution::{Output, QueryStateMachineRef};
 use spi::query::logical_planner::Plan;
 use spi::server::dbms::DBMSRef;
@@ -41,15 +44,46 @@ use trace::{debug, Span, SpanContext};
 use super::auth_middleware::CallHeaderAuthenticator;
 use crate::flight_sql::auth_middleware::AuthResult;
 use crate::flight_sql::utils;
+use crate::flight_sql::vnode_endpoints::{TicketVnodePartitionQuery, VnodeLocator};
 use crate::status;
 
 const UNKNOWN_AFFECTED_ROWS_COUNT: i64 = -1;
+/// Content of the query of a substrait plan, shown in the running queries.
+const SUBSTRAIT_PLAN_QUERY: &str = "<substrait plan>";
+
+/// A statement of flight sql, in sql or in a serialized substrait plan.
+enum Statement {
+    Sql(String),
+    Substrait(Bytes),
+}
+
+impl From<String> for Statement {
+    fn from(sql: String) -> Self {
+        Self::Sql(sql)
+    }
+}
 
+impl From<&str> for Statement {
+    fn from(sql: &str) -> Self {
+        Self::Sql(sql.to_string())
+    }
+}
+
+/// Transactions are read only, because the writes of CnosDB can not be rolled back.
+///
+/// The statements in a transaction are executed with the context (tenant, database and session
+/// options) taken when the transaction began, so committing or rolling back a transaction, or
+/// releasing or rolling back a savepoint, only releases it.
 pub struct FlightSqlServiceImpl<T> {
     instance: DBMSRef,
     authenticator: T,
     id_generator: UuidGenerator,
     result_cache: Cache<Vec<u8>, (Option<Plan>, QueryStateMachineRef)>,
+    // transaction id -> context of the transaction
+    transactions: Cache<Vec<u8>, Context>,
+    // savepoint id -> transaction id
+    savepoints: Cache<Vec<u8>, Vec<u8>>,
+    vnode_locator: Option<VnodeLocator>,
 }
 
 impl<T> FlightSqlServiceImpl<T> {
@@ -59,14 +93,32 @@ impl<T> FlightSqlServiceImpl<T> {
             // The query results are only cached for 2 minutes and expire after 2 minutes
             .time_to_live(Duration::from_secs(2 * 60))
             .build();
+        // The transactions idle for 10 minutes are released
+        let transactions = Cache::builder()
+            .time_to_idle(Duration::from_secs(10 * 60))
+            .build();
+        let savepoints = Cache::builder()
+            .time_to_idle(Duration::from_secs(10 * 60))
+            .support_invalidation_closures()
+            .build();
 
         Self {
             instance,
             authenticator,
             id_generator: Default::default(),
             result_cache,
+            transactions,
+            savepoints,
+            vnode_locator: None,
         }
     }
+
+    /// Split the statement queries into the endpoints of vnodes
+    /// if the request has the `vnode_endpoints: true` header.
+    pub fn with_vnode_locator(mut self, vnode_locator: VnodeLocator) -> Self {
+        self.vnode_locator = Some(vnode_locator);
+        self
+    }
 }
 
 impl<T> FlightSqlServiceImpl<T>
@@ -75,8 +127,10 @@ where
 {
     async fn pre_precess_statement_query_req(
         &self,
-        sql: impl Into<String>,
+        statement: impl Into<Statement>,
         req_headers: &MetadataMap,
+        client_addr: Option<SocketAddr>,
+        transaction_id: Option<&[u8]>,
         span_ctx: Option<&SpanContext>,
     ) -> Result<(Option<Plan>, QueryStateMachineRef), Status> {
         // auth request
@@ -86,35 +140,76 @@ where
         };
         let user = auth_result.identity();
 
-        // construct context by user_info and headers(parse tenant & default database)
+        // construct context by user_info and headers(parse tenant & default database),
+        // or use the context of the transaction
         let ctx = {
             let _span = Span::from_context("construct context", span_ctx);
-            self.construct_context(user, req_headers)?
+            match transaction_id {
+                Some(transaction_id) => self.get_transaction_context(transaction_id, &user)?,
+                None => self.construct_context(user, req_headers, client_addr)?,
+            }
+        };
+
+        self.build_statement_plan(statement, ctx, span_ctx).await
+    }
+
+    async fn build_statement_plan(
+        &self,
+        statement: impl Into<Statement>,
+        ctx: Context,
+        span_ctx: Option<&SpanContext>,
+    ) -> Result<(Option<Plan>, QueryStateMachineRef), Status> {
+        let statement = statement.into();
+        let content = match &statement {
+            Statement::Sql(sql) => sql.clone(),
+            Statement::Substrait(_) => SUBSTRAIT_PLAN_QUERY.to_string(),
         };
 
         // build query state machine
         let query_state_machine = {
             let span = Span::from_context("build query_state_machine", span_ctx);
-            self.build_query_state_machine(sql.into(), ctx, span.context().as_ref())
+            self.build_query_state_machine(content, ctx, span.context().as_ref())
                 .await?
         };
 
         // build logical plan
-        let logical_plan = self.build_logical_plan(query_state_machine.clone()).await?;
+        let logical_plan = match statement {
+            Statement::Sql(_) => self.build_logical_plan(query_state_machine.clone()).await?,
+            Statement::Substrait(plan) => {
+                self.build_substrait_logical_plan(query_state_machine.clone(), &plan)
+                    .await?
+            }
+        };
 
         Ok((logical_plan, query_state_machine))
     }
 
     async fn pre_precess_statement_query_req_and_save(
         &self,
-        sql: impl Into<String>,
+        statement: impl Into<Statement>,
         req_headers: &MetadataMap,
+        client_addr: Option<SocketAddr>,
+        transaction_id: Option<&[u8]>,
         span_ctx: Option<&SpanContext>,
     ) -> Result<(Vec<u8>, SchemaRef), Status> {
         let (logical_plan, query_state_machine) = self
-            .pre_precess_statement_query_req(sql, req_headers, span_ctx)
+            .pre_precess_statement_query_req(
+                statement,
+                req_headers,
+                client_addr,
+                transaction_id,
+                span_ctx,
+            )
             .await?;
 
+        Ok(self.save_statement(logical_plan, query_state_machine))
+    }
+
+    fn save_statement(
+        &self,
+        logical_plan: Option<Plan>,
+        query_state_machine: QueryStateMachineRef,
+    ) -> (Vec<u8>, SchemaRef) {
         let schema = logical_plan
             .as_ref()
             .map(|e| e.schema())
@@ -127,17 +222,24 @@ where
         self.result_cache
             .insert(result_ident.clone(), (logical_plan, query_state_machine));
 
-        Ok((result_ident, schema))
+        (result_ident, schema)
     }
 
     async fn precess_flight_info_req(
         &self,
-        sql: impl Into<String>,
+        statement: impl Into<Statement>,
         request: Request<FlightDescriptor>,
+        transaction_id: Option<&[u8]>,
         span_ctx: Option<&SpanContext>,
     ) -> Result<Response<FlightInfo>, Status> {
         let (result_ident, schema) = self
-            .pre_precess_statement_query_req_and_save(sql, request.metadata(), span_ctx)
+            .pre_precess_statement_query_req_and_save(
+                statement,
+                request.metadata(),
+                request.remote_addr(),
+                transaction_id,
+                span_ctx,
+            )
             .await?;
 
         let ticket = TicketStatementQuery {
@@ -155,6 +257,115 @@ where
         Ok(Response::new(flight_info))
     }
 
+    /// Split the statement query into the endpoints of vnodes, see [`VnodeLocator`],
+    /// or return a single endpoint like [`Self::precess_flight_info_req`] if it can not be split.
+    async fn precess_vnode_flight_info_req(
+        &self,
+        sql: String,
+        request: Request<FlightDescriptor>,
+        vnode_locator: &VnodeLocator,
+        span_ctx: Option<&SpanContext>,
+    ) -> Result<Response<FlightInfo>, Status> {
+        let (logical_plan, query_state_machine) = self
+            .pre_precess_statement_query_req(
+                sql.as_str(),
+                request.metadata(),
+                request.remote_addr(),
+                None,
+                span_ctx,
+            )
+            .await?;
+
+        let partitions = match &logical_plan {
+            Some(plan) => {
+                let _span = Span::from_context("split into vnode partitions", span_ctx);
+                vnode_locator.partitions(plan).await?
+            }
+            None => None,
+        };
+
+        let (endpoints, schema) = match partitions {
+            Some(partitions) => {
+                let schema = logical_plan
+                    .as_ref()
+                    .map(|e| e.schema())
+                    .unwrap_or(Arc::new(Schema::empty()));
+                let session = &query_state_machine.session;
+                let endpoints = partitions
+                    .into_iter()
+                    .map(|partition| {
+                        let ticket = TicketVnodePartitionQuery {
+                            query: sql.clone(),
+                            tenant: session.tenant().to_string(),
+                            database: session.default_database().to_string(),
+                            vnode_ids: partition.vnode_ids,
+                        };
+                        partition.locations.into_iter().fold(
+                            FlightEndpoint::new().with_ticket(Ticket {
+                                ticket: ticket.as_any().encode_to_vec().into(),
+                            }),
+                            |endpoint, location| endpoint.with_location(location),
+                        )
+                    })
+                    .collect();
+                // Each partition is executed as a new query, this one is only used for planning.
+                query_state_machine.finish();
+                (endpoints, schema)
+            }
+            None => {
+                let (result_ident, schema) = self.save_statement(logical_plan, query_state_machine);
+                let ticket = TicketStatementQuery {
+                    statement_handle: result_ident.into(),
+                };
+                let endpoint = FlightEndpoint::new().with_ticket(Ticket {
+                    ticket: ticket.as_any().encode_to_vec().into(),
+                });
+                (vec![endpoint], schema)
+            }
+        };
+
+        let flight_info = self.construct_flight_info_with_endpoints(
+            endpoints,
+            schema.as_ref(),
+            UNKNOWN_AFFECTED_ROWS_COUNT,
+            request.into_inner(),
+        )?;
+
+        Ok(Response::new(flight_info))
+    }
+
+    async fn precess_create_prepared_statement_req(
+        &self,
+        statement: impl Into<Statement>,
+        req_headers: &MetadataMap,
+        client_addr: Option<SocketAddr>,
+        transaction_id: Option<&[u8]>,
+        span_ctx: Option<&SpanContext>,
+    ) -> Result<ActionCreatePreparedStatementResult, Status> {
+        let (result_ident, schema) = self
+            .pre_precess_statement_query_req_and_save(
+                statement,
+                req_headers,
+                client_addr,
+                transaction_id,
+                span_ctx,
+            )
+            .await?;
+
+        let IpcMessage(dataset_schema) = utils::schema_to_ipc_message(schema.as_ref())
+            .map_err(|e| status!("Schema to ipc message", e))?;
+        // JDBC:
+        //    - schema.getFields().isEmpty() ? StatementType.UPDATE : StatementType.SELECT;
+        //    - long updateCount = statementType.equals(StatementType.UPDATE) ? preparedStatement.executeUpdate() : -1L;
+        let result = ActionCreatePreparedStatementResult {
+            prepared_statement_handle: result_ident.into(),
+            dataset_schema,
+            ..Default::default()
+        };
+
+        Ok(result)
+    }
+
     fn construct_flight_info(
         &self,
         result_ident: impl Into<Bytes>,
@@ -167,18 +378,40 @@ where
         };
         let endpoint = FlightEndpoint::new().with_ticket(ticket);
 
-        let flight_info = FlightInfo::new()
-            .try_with_schema(schema)
-            .map_err(|e| status!("Unable to encode schema", e))?
-            .with_endpoint(endpoint)
+        self.construct_flight_info_with_endpoints(
+            vec![endpoint],
+            schema,
+            total_records,
+            flight_descriptor,
+        )
+    }
+
+    fn construct_flight_info_with_endpoints(
+        &self,
+        endpoints: Vec<FlightEndpoint>,
+        schema: &Schema,
+        total_records: i64,
+        flight_descriptor: FlightDescriptor,
+    ) -> Result<FlightInfo, Status> {
+        let flight_info = endpoints.into_iter().fold(
+            FlightInfo::new()
+                .try_with_schema(schema)
+                .map_err(|e| status!("Unable to encode schema", e))?,
+            |flight_info, endpoint| flight_info.with_endpoint(endpoint),
+        );
+
+        Ok(flight_info
             .with_descriptor(flight_descriptor)
             .with_total_records(total_records)
-            .with_ordered(false);
-
-        Ok(flight_info)
+            .with_ordered(false))
     }
 
-    fn construct_context(&self, user: User, metadata: &MetadataMap) -> Result<Context, Status> {
+    fn construct_context(
+        &self,
+        user: User,
+        metadata: &MetadataMap,
+        client_addr: Option<SocketAddr>,
+    ) -> Result<Context, Status> {
         // parse tenant & default database
         let tenant = utils::get_value_from_header(metadata, TENANT, "");
         let db = utils::get_value_from_header(metadata, DB, "");
@@ -206,11 +439,32 @@ where
             .with_database(db)
             .with_target_partitions(target_partitions)
             .with_stream_trigger_interval(stream_trigger_interval)
+            .with_client_addr(client_addr.map(|addr| addr.to_string()))
             .build();
 
         Ok(ctx)
     }
 
+    /// The context of the transaction, which must be began by the same user.
+    fn get_transaction_context(
+        &self,
+        transaction_id: &[u8],
+        user: &User,
+    ) -> Result<Context, Status> {
+        let ctx = self
+            .transactions
+            .get(transaction_id)
+            .ok_or_else(|| transaction_not_found(transaction_id))?;
+        if ctx.user().desc().name() != user.desc().name() {
+            return Err(Status::permission_denied(format!(
+                "The transaction({:?}) does not belong to user {}",
+                transaction_id,
+                user.desc().name()
+            )));
+        }
+        Ok(ctx)
+    }
+
     async fn build_query_state_machine(
         &self,
         sql: impl Into<String>,
@@ -239,6 +493,19 @@ where
         Ok(logical_plan)
     }
 
+    async fn build_substrait_logical_plan(
+        &self,
+        query_state_machine: QueryStateMachineRef,
+        substrait_plan: &[u8],
+    ) -> Result<Option<Plan>, Status> {
+        let logical_plan = self
+            .instance
+            .build_substrait_logical_plan(query_state_machine, substrait_plan)
+            .await
+            .map_err(|e| status!("Build substrait logical plan", e))?;
+        Ok(logical_plan)
+    }
+
     async fn execute_logical_plan(
         &self,
         logical_plan: Option<Plan>,
@@ -284,6 +551,15 @@ where
         let (logical_plan, query_state_machine) =
             self.get_plan_and_qsm(statement_handle, span_ctx.cloned())?;
 
+        self.execute_and_fetch_plan_result_set(logical_plan, query_state_machine)
+            .await
+    }
+
+    async fn execute_and_fetch_plan_result_set(
+        &self,
+        logical_plan: Option<Plan>,
+        query_state_machine: QueryStateMachineRef,
+    ) -> Result<<Self as FlightService>::DoGetStream, Status> {
         // execute plan
         let query_result = self
             .execute_logical_plan(logical_plan, query_state_machine)
@@ -403,11 +679,29 @@ where
 
         let span = get_span(request.extensions(), "flight sql get_flight_info_statement");
 
-        // ignore transaction_id
-        let CommandStatementQuery { query: sql, .. } = query;
+        let CommandStatementQuery {
+            query: sql,
+            transaction_id,
+        } = query;
+
+        // split the query into the endpoints of vnodes, if it is not in a transaction
+        let split_by_vnodes = utils::get_value_from_header(request.metadata(), VNODE_ENDPOINTS, "")
+            .is_some_and(|e| e.eq_ignore_ascii_case("true"));
+        if let (true, None, Some(vnode_locator)) =
+            (split_by_vnodes, &transaction_id, &self.vnode_locator)
+        {
+            return self
+                .precess_vnode_flight_info_req(sql, request, vnode_locator, span.context().as_ref())
+                .await;
+        }
 
-        self.precess_flight_info_req(sql, request, span.context().as_ref())
-            .await
+        self.precess_flight_info_req(
+            sql,
+            request,
+            transaction_id.as_deref(),
+            span.context().as_ref(),
+        )
+        .await
     }
 
     /// Fetch meta of the prepared statement.
@@ -465,6 +759,7 @@ where
             ORDER BY 
                 CATALOG_NAME",
             request,
+            None,
             span.context().as_ref(),
         )
         .await
@@ -510,6 +805,7 @@ where
                     CATALOG_NAME, DB_SCHEMA_NAME"
             ),
             request,
+            None,
             span.context().as_ref(),
         )
         .await
@@ -568,7 +864,7 @@ where
                 TABLE_TYPE, CATALOG_NAME, DB_SCHEMA_NAME, TABLE_NAME"
         );
 
-        self.precess_flight_info_req(sql, request, span.context().as_ref())
+        self.precess_flight_info_req(sql, request, None, span.context().as_ref())
             .await
     }
 
@@ -592,6 +888,7 @@ where
             FROM 
                 (VALUES('TABLE'),('VIEW'),('LOCAL TEMPORARY')) t(TABLE_TYPE)",
             request,
+            None,
             span.context().as_ref(),
         )
         .await
@@ -733,6 +1030,63 @@ where
         Ok(Response::new(output))
     }
 
+    /// Fetch the result set of a partition of the ad-hoc SQL query split by vnodes
+    ///
+    /// [`TicketVnodePartitionQuery`] is the result obtained after calling [`Self::get_flight_info_statement`]
+    /// with the header `vnode_endpoints: true`
+    async fn do_get_fallback(
+        &self,
+        request: Request<Ticket>,
+        message: Any,
+    ) -> Result<Response<<Self as FlightService>::DoGetStream>, Status> {
+        debug!(
+            "do_get_fallback: message: {:?}, request: {:?}",
+            message, request
+        );
+
+        let span = get_span(request.extensions(), "flight sql do_get_vnode_partition");
+        let span_ctx = span.context();
+
+        let TicketVnodePartitionQuery {
+            query,
+            tenant,
+            database,
+            vnode_ids,
+        } = message
+            .unpack()
+            .map_err(|e| status!("Unable to unpack ticket", e))?
+            .ok_or_else(|| {
+                Status::unimplemented(format!(
+                    "do_get: The defined request is invalid: {}",
+                    message.type_url
+                ))
+            })?;
+
+        // auth request
+        let auth_result = {
+            let _span = Span::from_context("authenticate", span_ctx.as_ref());
+            self.authenticator.authenticate(request.metadata()).await?
+        };
+
+        // the tskv table scan is restricted to the vnodes of the partition
+        let ctx = ContextBuilder::new(auth_result.identity())
+            .with_tenant(Some(tenant))
+            .with_database(Some(database))
+            .with_scan_vnodes(Some(ScanVnodes(vnode_ids)))
+            .with_client_addr(request.remote_addr().map(|addr| addr.to_string()))
+            .build();
+
+        let (logical_plan, query_state_machine) = self
+            .build_statement_plan(query, ctx, span_ctx.as_ref())
+            .await?;
+
+        let output = self
+            .execute_and_fetch_plan_result_set(logical_plan, query_state_machine)
+            .await?;
+
+        Ok(Response::new(output))
+    }
+
     /// TODO support
     /// wait for <https://github.com/cnosdb/cnosdb/issues/642>
     async fn do_get_catalogs(
@@ -866,12 +1220,21 @@ where
 
         let span = get_span(request.extensions(), "flight sql do_put_statement_update");
         let span_ctx = span.context();
-        // ignore transaction_id
-        let CommandStatementUpdate { query, .. } = ticket;
+        let CommandStatementUpdate {
+            query,
+            transaction_id,
+        } = ticket;
+        check_update_not_in_transaction(transaction_id.as_deref())?;
         let req_headers = request.metadata();
 
         let (logical_plan, query_state_machine) = self
-            .pre_precess_statement_query_req(query, req_headers, span_ctx.as_ref())
+            .pre_precess_statement_query_req(
+                query,
+                req_headers,
+                request.remote_addr(),
+                None,
+                span_ctx.as_ref(),
+            )
             .await?;
 
         // execute plan
@@ -944,29 +1307,19 @@ where
             request.extensions(),
             "flight sql do_action_create_prepared_statement",
         );
-        // ignore transaction_id
-        let ActionCreatePreparedStatementRequest { query: sql, .. } = query;
-
-        let (result_ident, schema) = self
-            .pre_precess_statement_query_req_and_save(
-                sql,
-                request.metadata(),
-                span.context().as_ref(),
-            )
-            .await?;
-
-        let IpcMessage(dataset_schema) = utils::schema_to_ipc_message(schema.as_ref())
-            .map_err(|e| status!("Schema to ipc message", e))?;
-        // JDBC:
-        //    - schema.getFields().isEmpty() ? StatementType.UPDATE : StatementType.SELECT;
-        //    - long updateCount = statementType.equals(StatementType.UPDATE) ? preparedStatement.executeUpdate() : -1L;
-        let result = ActionCreatePreparedStatementResult {
-            prepared_statement_handle: result_ident.into(),
-            dataset_schema,
-            ..Default::default()
-        };
+        let ActionCreatePreparedStatementRequest {
+            query: sql,
+            transaction_id,
+        } = query;
 
-        Ok(result)
+        self.precess_create_prepared_statement_req(
+            sql,
+            request.metadata(),
+            request.remote_addr(),
+            transaction_id.as_deref(),
+            span.context().as_ref(),
+        )
+        .await
     }
 
     /// Close a previously created prepared statement.
@@ -990,76 +1343,272 @@ where
         debug!("register_sql_info: _id: {:?}, request: {:?}", _id, _result);
     }
 
+    /// Create a prepared statement of the substrait plan, the same as
+    /// [`Self::do_action_create_prepared_statement`].
     async fn do_action_create_prepared_substrait_plan(
         &self,
-        _query: ActionCreatePreparedSubstraitPlanRequest,
-        _request: Request<Action>,
+        query: ActionCreatePreparedSubstraitPlanRequest,
+        request: Request<Action>,
     ) -> Result<ActionCreatePreparedStatementResult, Status> {
-        Err(Status::unimplemented(
-            "Implement do_action_create_prepared_substrait_plan",
-        ))
+        debug!(
+            "do_action_create_prepared_substrait_plan: query: {:?}, request: {:?}",
+            query, request
+        );
+
+        let span = get_span(
+            request.extensions(),
+            "flight sql do_action_create_prepared_substrait_plan",
+        );
+        let ActionCreatePreparedSubstraitPlanRequest {
+            plan,
+            transaction_id,
+        } = query;
+        let plan = substrait_plan_bytes(plan)?;
+
+        self.precess_create_prepared_statement_req(
+            Statement::Substrait(plan),
+            request.metadata(),
+            request.remote_addr(),
+            transaction_id.as_deref(),
+            span.context().as_ref(),
+        )
+        .await
     }
 
+    /// Begin a read only transaction, see [`FlightSqlServiceImpl`].
     async fn do_action_begin_transaction(
         &self,
-        _query: ActionBeginTransactionRequest,
-        _request: Request<Action>,
+        query: ActionBeginTransactionRequest,
+        request: Request<Action>,
     ) -> Result<ActionBeginTransactionResult, Status> {
-        Err(Status::unimplemented(
-            "Implement do_action_begin_transaction",
-        ))
+        debug!(
+            "do_action_begin_transaction: query: {:?}, request: {:?}",
+            query, request
+        );
+
+        let _span = get_span(
+            request.extensions(),
+            "flight sql do_action_begin_transaction",
+        );
+
+        let req_headers = request.metadata();
+        let auth_result = self.authenticator.authenticate(req_headers).await?;
+        let ctx =
+            self.construct_context(auth_result.identity(), req_headers, request.remote_addr())?;
+
+        let transaction_id = self.id_generator.next_id().to_le_bytes().to_vec();
+        self.transactions.insert(transaction_id.clone(), ctx);
+
+        Ok(ActionBeginTransactionResult {
+            transaction_id: transaction_id.into(),
+        })
     }
 
+    /// Commit or rollback the transaction, both of them only release the transaction,
+    /// because the transaction is read only.
     async fn do_action_end_transaction(
         &self,
-        _query: ActionEndTransactionRequest,
-        _request: Request<Action>,
+        query: ActionEndTransactionRequest,
+        request: Request<Action>,
     ) -> Result<(), Status> {
-        Err(Status::unimplemented("Implement do_action_end_transaction"))
+        debug!(
+            "do_action_end_transaction: query: {:?}, request: {:?}",
+            query, request
+        );
+
+        let _span = get_span(request.extensions(), "flight sql do_action_end_transaction");
+
+        let auth_result = self.authenticator.authenticate(request.metadata()).await?;
+        let ActionEndTransactionRequest {
+            transaction_id,
+            action,
+        } = query;
+
+        match EndTransaction::from_i32(action) {
+            Some(EndTransaction::Commit) | Some(EndTransaction::Rollback) => {
+                self.get_transaction_context(&transaction_id, &auth_result.identity())?;
+                self.transactions.invalidate(&transaction_id[..]);
+                self.savepoints
+                    .invalidate_entries_if({
+                        let transaction_id = transaction_id.to_vec();
+                        move |_, t| *t == transaction_id
+                    })
+                    .map_err(|e| status!("Release savepoints", e))?;
+                Ok(())
+            }
+            _ => Err(Status::invalid_argument(format!(
+                "Unsupported end transaction action: {}",
+                action
+            ))),
+        }
     }
 
     async fn do_action_begin_savepoint(
         &self,
-        _query: ActionBeginSavepointRequest,
-        _request: Request<Action>,
+        query: ActionBeginSavepointRequest,
+        request: Request<Action>,
     ) -> Result<ActionBeginSavepointResult, Status> {
-        Err(Status::unimplemented("Implement do_action_begin_savepoint"))
+        debug!(
+            "do_action_begin_savepoint: query: {:?}, request: {:?}",
+            query, request
+        );
+
+        let _span = get_span(request.extensions(), "flight sql do_action_begin_savepoint");
+
+        let auth_result = self.authenticator.authenticate(request.metadata()).await?;
+        let ActionBeginSavepointRequest { transaction_id, .. } = query;
+        self.get_transaction_context(&transaction_id, &auth_result.identity())?;
+
+        let savepoint_id = self.id_generator.next_id().to_le_bytes().to_vec();
+        self.savepoints
+            .insert(savepoint_id.clone(), transaction_id.to_vec());
+
+        Ok(ActionBeginSavepointResult {
+            savepoint_id: savepoint_id.into(),
+        })
     }
 
+    /// Release or rollback the savepoint, both of them only release the savepoint,
+    /// because the transaction is read only.
     async fn do_action_end_savepoint(
         &self,
-        _query: ActionEndSavepointRequest,
-        _request: Request<Action>,
+        query: ActionEndSavepointRequest,
+        request: Request<Action>,
     ) -> Result<(), Status> {
-        Err(Status::unimplemented("Implement do_action_end_savepoint"))
+        debug!(
+            "do_action_end_savepoint: query: {:?}, request: {:?}",
+            query, request
+        );
+
+        let _span = get_span(request.extensions(), "flight sql do_action_end_savepoint");
+
+        let auth_result = self.authenticator.authenticate(request.metadata()).await?;
+        let ActionEndSavepointRequest {
+            savepoint_id,
+            action,
+        } = query;
+
+        match EndSavepoint::from_i32(action) {
+            Some(EndSavepoint::Release) | Some(EndSavepoint::Rollback) => {
+                let transaction_id = self.savepoints.get(&savepoint_id[..]).ok_or_else(|| {
+                    Status::not_found(format!(
+                        "The savepoint({:?}) does not exist or has been released",
+                        savepoint_id
+                    ))
+                })?;
+                self.get_transaction_context(&transaction_id, &auth_result.identity())?;
+                self.savepoints.invalidate(&savepoint_id[..]);
+                Ok(())
+            }
+            _ => Err(Status::invalid_argument(format!(
+                "Unsupported end savepoint action: {}",
+                action
+            ))),
+        }
     }
 
+    /// Cancel the query of the flight info returned by `get_flight_info_*`,
+    /// if its result set has not been fetched.
     async fn do_action_cancel_query(
         &self,
-        _query: ActionCancelQueryRequest,
-        _request: Request<Action>,
+        query: ActionCancelQueryRequest,
+        request: Request<Action>,
     ) -> Result<ActionCancelQueryResult, Status> {
-        Err(Status::unimplemented("Implement do_action_cancel_query"))
+        debug!(
+            "do_action_cancel_query: query: {:?}, request: {:?}",
+            query, request
+        );
+
+        let _span = get_span(request.extensions(), "flight sql do_action_cancel_query");
+
+        let _ = self.authenticator.authenticate(request.metadata()).await?;
+        let flight_info = FlightInfo::decode(query.info)
+            .map_err(|e| Status::invalid_argument(format!("Decode flight info: {}", e)))?;
+
+        let mut result = CancelResult::NotCancellable;
+        for ticket in flight_info.endpoint.into_iter().filter_map(|e| e.ticket) {
+            let Some(statement_handle) = statement_handle_of_ticket(&ticket)? else {
+                continue;
+            };
+            if let Some((_, query_state_machine)) = self.result_cache.get(&statement_handle) {
+                self.instance.cancel(&query_state_machine.query_id);
+                self.result_cache.invalidate(&statement_handle);
+                result = CancelResult::Cancelled;
+            }
+        }
+
+        Ok(ActionCancelQueryResult {
+            result: result as i32,
+        })
     }
 
+    /// Execute the substrait plan and return the number of affected rows.
     async fn do_put_substrait_plan(
         &self,
-        _ticket: CommandStatementSubstraitPlan,
-        _request: Request<Streaming<FlightData>>,
+        ticket: CommandStatementSubstraitPlan,
+        request: Request<Streaming<FlightData>>,
     ) -> Result<i64, Status> {
-        Err(Status::unimplemented(
-            "do_put_substrait_plan not implemented",
-        ))
+        debug!(
+            "do_put_substrait_plan: query: {:?}, request: {:?}",
+            ticket, request
+        );
+
+        let span = get_span(request.extensions(), "flight sql do_put_substrait_plan");
+        let span_ctx = span.context();
+        let CommandStatementSubstraitPlan {
+            plan,
+            transaction_id,
+        } = ticket;
+        check_update_not_in_transaction(transaction_id.as_deref())?;
+        let plan = substrait_plan_bytes(plan)?;
+
+        let (logical_plan, query_state_machine) = self
+            .pre_precess_statement_query_req(
+                Statement::Substrait(plan),
+                request.metadata(),
+                request.remote_addr(),
+                None,
+                span_ctx.as_ref(),
+            )
+            .await?;
+
+        // execute plan
+        let query_result = self
+            .execute_logical_plan(logical_plan, query_state_machine)
+            .await?;
+
+        Ok(query_result.result().affected_rows().await)
     }
 
+    /// Execute the substrait plan, the same as [`Self::get_flight_info_statement`].
     async fn get_flight_info_substrait_plan(
         &self,
-        _query: CommandStatementSubstraitPlan,
-        _request: Request<FlightDescriptor>,
+        query: CommandStatementSubstraitPlan,
+        request: Request<FlightDescriptor>,
     ) -> Result<Response<FlightInfo>, Status> {
-        Err(Status::unimplemented(
-            "get_flight_info_substrait_plan not implemented",
-        ))
+        debug!(
+            "get_flight_info_substrait_plan: query: {:?}, request: {:?}",
+            query, request
+        );
+
+        let span = get_span(
+            request.extensions(),
+            "flight sql get_flight_info_substrait_plan",
+        );
+
+        let CommandStatementSubstraitPlan {
+            plan,
+            transaction_id,
+        } = query;
+        let plan = substrait_plan_bytes(plan)?;
+
+        self.precess_flight_info_req(
+            Statement::Substrait(plan),
+            request,
+            transaction_id.as_deref(),
+            span.context().as_ref(),
+        )
+        .await
     }
 
     async fn get_flight_info_xdbc_type_info(
@@ -1088,6 +1637,47 @@ fn get_span(extensions: &Extensions, child_span_name: &'static str) -> Span {
     Span::from_context(child_span_name, span_context)
 }
 
+fn transaction_not_found(transaction_id: &[u8]) -> Status {
+    Status::not_found(format!(
+        "The transaction({:?}) does not exist or has ended",
+        transaction_id
+    ))
+}
+
+/// The writes can not be rolled back, so they are not allowed in the read only transactions.
+fn check_update_not_in_transaction(transaction_id: Option<&[u8]>) -> Result<(), Status> {
+    match transaction_id {
+        Some(_) => Err(Status::invalid_argument(
+            "Update is not supported in a transaction, transactions are read only",
+        )),
+        None => Ok(()),
+    }
+}
+
+fn substrait_plan_bytes(plan: Option<SubstraitPlan>) -> Result<Bytes, Status> {
+    plan.map(|p| p.plan)
+        .ok_or_else(|| Status::invalid_argument("Substrait plan is required"))
+}
+
+/// The handle of the cached statement of the ticket returned by `get_flight_info_*`.
+fn statement_handle_of_ticket(ticket: &Ticket) -> Result<Option<Vec<u8>>, Status> {
+    let any = Any::decode(ticket.ticket.clone())
+        .map_err(|e| Status::invalid_argument(format!("Decode ticket: {}", e)))?;
+    if let Some(t) = any
+        .unpack::<TicketStatementQuery>()
+        .map_err(|e| status!("Unpack ticket", e))?
+    {
+        return Ok(Some(t.statement_handle.to_vec()));
+    }
+    if let Some(q) = any
+        .unpack::<CommandPreparedStatementQuery>()
+        .map_err(|e| status!("Unpack ticket", e))?
+    {
+        return Ok(Some(q.prepared_statement_handle.to_vec()));
+    }
+    Ok(None)
+}
+
 #[cfg(test)]
 mod test {
     use std::collections::HashMap;
@@ -1096,9 +1686,13 @@ mod test {
     use arrow_flight::flight_service_client::FlightServiceClient;
     use arrow_flight::flight_service_server::FlightServiceServer;
     use arrow_flight::sql::client::FlightSqlServiceClient;
-    use arrow_flight::sql::{Any, CommandStatementQuery};
+    use arrow_flight::sql::server::FlightSqlService;
+    use arrow_flight::sql::{
+        ActionBeginSavepointRequest, ActionBeginTransactionRequest, ActionEndSavepointRequest,
+        ActionEndTransactionRequest, Any, CommandStatementQuery, EndSavepoint, EndTransaction,
+    };
     use arrow_flight::utils::flight_data_to_batches;
-    use arrow_flight::{FlightDescriptor, HandshakeRequest, IpcMessage};
+    use arrow_flight::{Action, FlightDescriptor, HandshakeRequest, IpcMessage};
     use datafusion::arrow::buffer::Buffer;
     use datafusion::arrow::datatypes::Schema;
     use datafusion::arrow::{self, ipc};
@@ -1113,7 +1707,9 @@ mod test {
 
     use crate::flight_sql::auth_middleware::basic_call_header_authenticator::BasicCallHeaderAuthenticator;
     use crate::flight_sql::auth_middleware::generated_bearer_token_authenticator::GeneratedBearerTokenAuthenticator;
-    use crate::flight_sql::flight_sql_server::FlightSqlServiceImpl;
+    use crate::flight_sql::flight_sql_server::{
+        check_update_not_in_transaction, FlightSqlServiceImpl,
+    };
     use crate::flight_sql::utils;
 
     async fn run_test_server() {
@@ -1219,6 +1815,112 @@ mod test {
         }
     }
 
+    fn basic_auth_request<T>(message: T) -> Request<T> {
+        let mut req = Request::new(message);
+        req.metadata_mut().insert(
+            AUTHORIZATION.as_str(),
+            MetadataValue::from_static("Basic cm9vdDo="),
+        );
+        req
+    }
+
+    #[tokio::test]
+    async fn test_transaction() {
+        let instance = Arc::new(DatabaseManagerSystemMock {});
+        let authenticator = GeneratedBearerTokenAuthenticator::new(
+            BasicCallHeaderAuthenticator::new(instance.clone()),
+        );
+        let service = FlightSqlServiceImpl::new(instance, authenticator);
+
+        let transaction_id = service
+            .do_action_begin_transaction(
+                ActionBeginTransactionRequest {},
+                basic_auth_request(Action::default()),
+            )
+            .await
+            .unwrap()
+            .transaction_id;
+
+        // query in the transaction
+        let cmd = CommandStatementQuery {
+            query: "select 1;".to_string(),
+            transaction_id: Some(transaction_id.clone()),
+        };
+        let fd = FlightDescriptor::new_cmd(Any::pack(&cmd).unwrap().encode_to_vec());
+        service
+            .get_flight_info_statement(cmd, basic_auth_request(fd))
+            .await
+            .unwrap();
+
+        // update is not allowed in the transaction
+        assert!(check_update_not_in_transaction(Some(&transaction_id[..])).is_err());
+
+        let savepoint_id = service
+            .do_action_begin_savepoint(
+                ActionBeginSavepointRequest {
+                    transaction_id: transaction_id.clone(),
+                    name: "sp".to_string(),
+                },
+                basic_auth_request(Action::default()),
+            )
+            .await
+            .unwrap()
+            .savepoint_id;
+        service
+            .do_action_end_savepoint(
+                ActionEndSavepointRequest {
+                    savepoint_id: savepoint_id.clone(),
+                    action: EndSavepoint::Rollback as i32,
+                },
+                basic_auth_request(Action::default()),
+            )
+            .await
+            .unwrap();
+        let status = service
+            .do_action_end_savepoint(
+                ActionEndSavepointRequest {
+                    savepoint_id,
+                    action: EndSavepoint::Release as i32,
+                },
+                basic_auth_request(Action::default()),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::NotFound);
+
+        let end_transaction = |action: EndTransaction| ActionEndTransactionRequest {
+            transaction_id: transaction_id.clone(),
+            action: action as i32,
+        };
+        let status = service
+            .do_action_end_transaction(
+                end_transaction(EndTransaction::Unspecified),
+                basic_auth_request(Action::default()),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        service
+            .do_action_end_transaction(
+                end_transaction(EndTransaction::Commit),
+                basic_auth_request(Action::default()),
+            )
+            .await
+            .unwrap();
+
+        // the transaction has ended
+        let cmd = CommandStatementQuery {
+            query: "select 1;".to_string(),
+            transaction_id: Some(transaction_id.clone()),
+        };
+        let fd = FlightDescriptor::new_cmd(Any::pack(&cmd).unwrap().encode_to_vec());
+        let status = service
+            .get_flight_info_statement(cmd, basic_auth_request(fd))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::NotFound);
+    }
+
     async fn flight_channel(host: &str, port: u16) -> Channel {
         Endpoint::new(format!("http://{}:{}", host, port))
             .unwrap()
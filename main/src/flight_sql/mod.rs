@@ -2,6 +2,7 @@ use std::net::SocketAddr;
 
 use arrow_flight::flight_service_server::FlightServiceServer;
 use config::tskv::TLSConfig;
+use coordinator::service::CoordinatorRef;
 use spi::server::dbms::DBMSRef;
 use tokio::sync::oneshot;
 use tonic::transport::{Identity, Server, ServerTlsConfig};
@@ -9,6 +10,7 @@ use trace::http::tower_layer::TraceLayer;
 use trace::info;
 
 use self::flight_sql_server::FlightSqlServiceImpl;
+use self::vnode_endpoints::VnodeLocator;
 use crate::flight_sql::auth_middleware::basic_call_header_authenticator::BasicCallHeaderAuthenticator;
 use crate::flight_sql::auth_middleware::generated_bearer_token_authenticator::GeneratedBearerTokenAuthenticator;
 use crate::server::ServiceHandle;
@@ -17,9 +19,11 @@ use crate::spi::service::Service;
 mod auth_middleware;
 pub mod flight_sql_server;
 mod utils;
+mod vnode_endpoints;
 
 pub struct FlightSqlServiceAdapter {
     dbms: DBMSRef,
+    coord: CoordinatorRef,
 
     addr: SocketAddr,
     tls_config: Option<TLSConfig>,
@@ -30,12 +34,14 @@ pub struct FlightSqlServiceAdapter {
 impl FlightSqlServiceAdapter {
     pub fn new(
         dbms: DBMSRef,
+        coord: CoordinatorRef,
         addr: SocketAddr,
         tls_config: Option<TLSConfig>,
         auto_generate_span: bool,
     ) -> Self {
         Self {
             dbms,
+            coord,
             addr,
             tls_config,
             auto_generate_span,
@@ -69,8 +75,15 @@ impl Service for FlightSqlServiceAdapter {
         let authenticator = GeneratedBearerTokenAuthenticator::new(
             BasicCallHeaderAuthenticator::new(self.dbms.clone()),
         );
-        let svc =
-            FlightServiceServer::new(FlightSqlServiceImpl::new(self.dbms.clone(), authenticator));
+        let vnode_locator = VnodeLocator::new(
+            self.coord.clone(),
+            self.addr.port(),
+            self.tls_config.is_some(),
+        );
+        let svc = FlightServiceServer::new(
+            FlightSqlServiceImpl::new(self.dbms.clone(), authenticator)
+                .with_vnode_locator(vnode_locator),
+        );
 
         let server = server
             .layer(trace_layer)
@@ -0,0 +1,201 @@
+use std::sync::Arc;
+
+use arrow_flight::sql::{Any, ProstMessageExt};
+use coordinator::service::CoordinatorRef;
+use datafusion::logical_expr::LogicalPlan;
+use datafusion::sql::TableReference;
+use models::meta_data::VnodeId;
+use models::object_reference::Resolve;
+use models::predicate::domain::{ColumnDomains, ResolvedPredicate, TimeRanges};
+use models::schema::tskv_table_schema::TskvTableSchemaRef;
+use prost::Message;
+use query::data_source::table_source::{TableHandle, TableSourceAdapter};
+use spi::query::logical_planner::Plan;
+use tonic::Status;
+
+use crate::status;
+
+/// Ticket of a partition of a query, which is executed by any node of the `locations`
+/// of its endpoint with the tskv table scan restricted to the `vnode_ids`.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TicketVnodePartitionQuery {
+    #[prost(string, tag = "1")]
+    pub query: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub tenant: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub database: ::prost::alloc::string::String,
+    #[prost(uint32, repeated, tag = "4")]
+    pub vnode_ids: ::prost::alloc::vec::Vec<u32>,
+}
+
+impl ProstMessageExt for TicketVnodePartitionQuery {
+    fn type_url() -> &'static str {
+        "type.googleapis.com/cnosdb.flight.sql.TicketVnodePartitionQuery"
+    }
+
+    fn as_any(&self) -> Any {
+        Any {
+            type_url: Self::type_url().to_string(),
+            value: self.encode_to_vec().into(),
+        }
+    }
+}
+
+/// The vnodes of a replication set and the flight addresses of the nodes owning them,
+/// the node of the leader vnode is the first.
+#[derive(Debug, PartialEq)]
+pub struct VnodePartition {
+    pub vnode_ids: Vec<VnodeId>,
+    pub locations: Vec<String>,
+}
+
+/// Split the queries into the partitions of vnodes, so that the clients fetch
+/// the partitions in parallel from the data nodes.
+///
+/// The flight services of all nodes are assumed to listen on the same port.
+pub struct VnodeLocator {
+    coord: CoordinatorRef,
+    flight_port: u16,
+    tls: bool,
+}
+
+impl VnodeLocator {
+    pub fn new(coord: CoordinatorRef, flight_port: u16, tls: bool) -> Self {
+        Self {
+            coord,
+            flight_port,
+            tls,
+        }
+    }
+
+    /// The partitions of the plan, `None` if the plan can not be split by vnodes,
+    /// which is only a projection or filter of a tskv table scan.
+    pub async fn partitions(&self, plan: &Plan) -> Result<Option<Vec<VnodePartition>>, Status> {
+        let table = match plan {
+            Plan::Query(query) if !query.is_explain() => scanned_tskv_table(&query.df_plan),
+            _ => None,
+        };
+        let table = match table {
+            Some(table) => table,
+            None => return Ok(None),
+        };
+
+        let table_name = TableReference::bare(&table.name)
+            .resolve_object(&table.tenant, &table.db)
+            .map_err(|e| status!("Resolve table", e))?;
+        // The predicate is applied when each partition is executed
+        let predicate =
+            ResolvedPredicate::new(Arc::new(TimeRanges::all()), ColumnDomains::all(), None)
+                .map_err(|e| status!("Resolve predicate", e))?;
+        let shards = self
+            .coord
+            .table_vnodes(&table_name, Arc::new(predicate))
+            .await
+            .map_err(|e| status!("Get vnodes of table", e))?;
+        if shards.is_empty() {
+            return Ok(None);
+        }
+
+        let meta = self.coord.meta_manager();
+        let mut partitions = Vec::with_capacity(shards.len());
+        for mut shard in shards {
+            shard
+                .vnodes
+                .sort_by_key(|vnode| vnode.id != shard.leader_vnode_id);
+
+            let mut vnode_ids = Vec::with_capacity(shard.vnodes.len());
+            let mut locations = Vec::with_capacity(shard.vnodes.len());
+            for vnode in shard.vnodes {
+                let vnode_info =
+                    coordinator::get_vnode_all_info(meta.clone(), &table.tenant, vnode.id)
+                        .await
+                        .map_err(|e| status!("Get vnode info", e))?;
+                let node = meta
+                    .node_info_by_id(vnode_info.node_id)
+                    .await
+                    .map_err(|e| status!("Get node info", e))?;
+                let location = self.location(&node.grpc_addr);
+
+                vnode_ids.push(vnode.id);
+                if !locations.contains(&location) {
+                    locations.push(location);
+                }
+            }
+            partitions.push(VnodePartition {
+                vnode_ids,
+                locations,
+            });
+        }
+
+        Ok(Some(partitions))
+    }
+
+    /// The flight address of the node listening on `grpc_addr`.
+    fn location(&self, grpc_addr: &str) -> String {
+        let host = grpc_addr
+            .rsplit_once(':')
+            .map(|(host, _)| host)
+            .unwrap_or(grpc_addr);
+        let scheme = if self.tls { "grpc+tls" } else { "grpc+tcp" };
+        format!("{}://{}:{}", scheme, host, self.flight_port)
+    }
+}
+
+fn scanned_tskv_table(plan: &LogicalPlan) -> Option<TskvTableSchemaRef> {
+    match plan {
+        LogicalPlan::Projection(p) => scanned_tskv_table(&p.input),
+        LogicalPlan::Filter(f) => scanned_tskv_table(&f.input),
+        LogicalPlan::SubqueryAlias(s) => scanned_tskv_table(&s.input),
+        LogicalPlan::TableScan(scan) => {
+            let table_source = scan.source.as_any().downcast_ref::<TableSourceAdapter>()?;
+            match table_source.table_handle() {
+                TableHandle::Tskv(table) => Some(table.table_schema()),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use arrow_flight::sql::{Any, ProstMessageExt};
+    use coordinator::service_mock::MockCoordinator;
+
+    use super::{TicketVnodePartitionQuery, VnodeLocator};
+
+    #[test]
+    fn test_location() {
+        let locator = VnodeLocator::new(Arc::new(MockCoordinator::default()), 8904, false);
+        assert_eq!(
+            locator.location("192.168.0.2:8903"),
+            "grpc+tcp://192.168.0.2:8904"
+        );
+
+        let locator = VnodeLocator::new(Arc::new(MockCoordinator::default()), 8904, true);
+        assert_eq!(
+            locator.location("data-node-1"),
+            "grpc+tls://data-node-1:8904"
+        );
+    }
+
+    #[test]
+    fn test_ticket_vnode_partition_query() {
+        let ticket = TicketVnodePartitionQuery {
+            query: "select * from air".to_string(),
+            tenant: "cnosdb".to_string(),
+            database: "public".to_string(),
+            vnode_ids: vec![3, 4],
+        };
+        let any = ticket.as_any();
+        assert!(any.is::<TicketVnodePartitionQuery>());
+        assert_eq!(
+            any.unpack::<TicketVnodePartitionQuery>().unwrap(),
+            Some(ticket.clone())
+        );
+        assert_eq!(Any::pack(&ticket).unwrap(), any);
+    }
+}
@@ -10,7 +10,11 @@ pub enum HttpApiType {
 
     ApiV1Sql,
     ApiV1PromRead,
+    ApiV1PromQuery,
+    ApiV1PromQueryRange,
     ApiV1ESLogWrite,
+    LokiApiV1QueryRange,
+    LokiApiV1Labels,
 
     ApiV1Ping,
     DebugBacktrace,
@@ -23,11 +27,14 @@ pub enum HttpApiType {
     Metrics,
     ApiV1DumpSqlDdl,
     ApiV1Traces,
+    ApiV1OtlpMetrics,
+    ApiV1OtlpLogs,
     ApiTraces,
     ApiTracesID,
     ApiServices,
     ApiOperations,
     ApiServicesOperations,
+    ApiDependencies,
 }
 
 impl Display for HttpApiType {
@@ -51,9 +58,21 @@ impl Display for HttpApiType {
             HttpApiType::ApiV1PromRead => {
                 write!(f, "api/v1/prom/read")
             }
+            HttpApiType::ApiV1PromQuery => {
+                write!(f, "api/v1/query")
+            }
+            HttpApiType::ApiV1PromQueryRange => {
+                write!(f, "api/v1/query_range")
+            }
             HttpApiType::ApiV1ESLogWrite => {
                 write!(f, "api/v1/es/write")
             }
+            HttpApiType::LokiApiV1QueryRange => {
+                write!(f, "loki/api/v1/query_range")
+            }
+            HttpApiType::LokiApiV1Labels => {
+                write!(f, "loki/api/v1/labels")
+            }
             HttpApiType::ApiV1Ping => {
                 write!(f, "api/v1/ping")
             }
@@ -87,6 +106,12 @@ impl Display for HttpApiType {
             HttpApiType::ApiV1Traces => {
                 write!(f, "api/v1/traces")
             }
+            HttpApiType::ApiV1OtlpMetrics => {
+                write!(f, "api/v1/otlp/metrics")
+            }
+            HttpApiType::ApiV1OtlpLogs => {
+                write!(f, "api/v1/otlp/logs")
+            }
             HttpApiType::ApiTraces => {
                 write!(f, "api/traces")
             }
@@ -102,6 +127,9 @@ impl Display for HttpApiType {
             HttpApiType::ApiServicesOperations => {
                 write!(f, "api/services/name/operations")
             }
+            HttpApiType::ApiDependencies => {
+                write!(f, "api/dependencies")
+            }
         }
     }
 }
@@ -115,12 +143,19 @@ pub fn metrics_record_db(api: &HttpApiType) -> bool {
         | HttpApiType::ApiV1PromWrite
         | HttpApiType::ApiV1ESLogWrite
         | HttpApiType::ApiV1PromRead
+        | HttpApiType::ApiV1PromQuery
+        | HttpApiType::ApiV1PromQueryRange
+        | HttpApiType::LokiApiV1QueryRange
+        | HttpApiType::LokiApiV1Labels
         | HttpApiType::ApiV1Traces
+        | HttpApiType::ApiV1OtlpMetrics
+        | HttpApiType::ApiV1OtlpLogs
         | HttpApiType::ApiTraces
         | HttpApiType::ApiTracesID
         | HttpApiType::ApiServices
         | HttpApiType::ApiOperations
-        | HttpApiType::ApiServicesOperations => true,
+        | HttpApiType::ApiServicesOperations
+        | HttpApiType::ApiDependencies => true,
         HttpApiType::ApiV1Sql
         | HttpApiType::ApiV1Ping
         | HttpApiType::DebugBacktrace
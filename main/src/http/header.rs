@@ -1,5 +1,5 @@
 use base64::prelude::{Engine, BASE64_STANDARD};
-use http_protocol::header::{APPLICATION_CSV, BASIC_PREFIX};
+use http_protocol::header::{APPLICATION_CSV, BASIC_PREFIX, BEARER_PREFIX};
 use models::auth::user::UserInfo;
 use warp::http::header::{HeaderName, HeaderValue};
 
@@ -82,6 +82,13 @@ impl Header {
         self.table.clone()
     }
 
+    /// The bearer token of the authorization, e.g. a JSON Web Token issued by the OIDC provider
+    pub fn get_bearer_token(&self) -> Option<&str> {
+        self.authorization
+            .strip_prefix(BEARER_PREFIX)
+            .filter(|token| !token.is_empty())
+    }
+
     pub fn try_get_basic_auth(&self) -> Result<UserInfo, HttpError> {
         let private_key = self
             .private_key
@@ -224,4 +231,18 @@ mod tests {
         let header = Header::with(None, None, None, auth);
         assert!(header.try_get_basic_auth().is_err());
     }
+
+    #[test]
+    fn test_header_bearer_token() {
+        let header = Header::with(None, None, None, format!("{}{}", BEARER_PREFIX, "xx.yy.zz"));
+        assert_eq!(header.get_bearer_token(), Some("xx.yy.zz"));
+        assert!(header.try_get_basic_auth().is_err());
+
+        let header = Header::with(None, None, None, BEARER_PREFIX.to_string());
+        assert_eq!(header.get_bearer_token(), None);
+
+        let auth = format!("{}{}", BASIC_PREFIX, BASE64_STANDARD.encode("xx:xx"));
+        let header = Header::with(None, None, None, auth);
+        assert_eq!(header.get_bearer_token(), None);
+    }
 }
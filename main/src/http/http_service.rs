@@ -16,13 +16,14 @@ use derive_traits::error_code::UnknownCodeWithMessage;
 use futures::TryStreamExt;
 use http_protocol::encoding::Encoding;
 use http_protocol::header::{
-    ACCEPT, APPLICATION_JSON, AUTHORIZATION, DB, PRIVATE_KEY, TABLE, TENANT,
+    ACCEPT, APPLICATION_JSON, AUTHORIZATION, DB, PRIVATE_KEY, SNAPPY, TABLE, TENANT,
 };
 use http_protocol::parameter::{
-    DebugParam, DumpParam, FindTracesParam, GetOperationParam, LogParam, SqlParam, WriteParam,
+    DebugParam, DumpParam, FindTracesParam, GetDependenciesParam, GetOperationParam, LogParam,
+    LokiQueryParam, PromQueryParam, SqlParam, WriteParam,
 };
 use http_protocol::response::ErrorResponse;
-use http_protocol::status_code::OK;
+use http_protocol::status_code::{BAD_REQUEST, OK, UNPROCESSABLE_ENTITY};
 use meta::error::{MetaError, MetaResult};
 use meta::limiter::RequestLimiter;
 use meta::model::MetaRef;
@@ -30,9 +31,11 @@ use metrics::count::U64Counter;
 use metrics::metric_register::MetricsRegister;
 use metrics::prom_reporter::PromReporter;
 use models::auth::privilege::{DatabasePrivilege, Privilege, TenantObjectPrivilege};
+use models::auth::user::User;
 use models::oid::{Identifier, Oid};
 use models::schema::{DEFAULT_CATALOG, DEFAULT_DATABASE};
 use models::utils::now_timestamp_nanos;
+use prost::Message;
 use protocol_parser::json_protocol::parser::{
     parse_json_to_eslog, parse_json_to_lokilog, parse_json_to_ndjsonlog, parse_protobuf_to_lokilog,
     parse_protobuf_to_otlptrace, parse_to_line, JsonProtocol,
@@ -40,13 +43,30 @@ use protocol_parser::json_protocol::parser::{
 use protocol_parser::json_protocol::JsonType;
 use protocol_parser::line_protocol::line_protocol_to_lines;
 use protocol_parser::open_tsdb::open_tsdb_to_lines;
+use protocol_parser::otlp::logs::{
+    otlp_logs_to_json, DEFAULT_OTLP_LOG_TABLE, OTLP_LOG_TIME_COLUMN,
+};
+use protocol_parser::otlp::metrics::otlp_metrics_to_lines;
 use protocol_parser::{DataPoint, Line};
+use protos::logs_service::ExportLogsServiceRequest;
+use protos::metrics_service::ExportMetricsServiceRequest;
+use query::loki::logql::parser::{
+    parse_step as parse_loki_step, parse_timestamp as parse_loki_timestamp,
+};
+use query::loki::server::LokiSqlServer;
+use query::loki::DEFAULT_LOKI_LINE_COLUMN;
+use query::prom::promql::parser::{
+    parse_step as parse_prom_step, parse_timestamp as parse_prom_timestamp,
+};
 use query::prom::remote_server::PromRemoteSqlServer;
 use reqwest::header::{HeaderName, HeaderValue, ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_TYPE};
 use snafu::{IntoError, ResultExt};
 use spi::query::config::StreamTriggerInterval;
 use spi::server::dbms::DBMSRef;
-use spi::server::prom::PromRemoteServerRef;
+use spi::server::loki::{LokiDirection, LokiRangeQuery, LokiServerRef};
+use spi::server::prom::{
+    PromRemoteServerRef, RemoteReadResponse, SAMPLES_CONTENT_TYPE, STREAMED_XOR_CHUNKS_CONTENT_TYPE,
+};
 use spi::service::protocol::{Context, ContextBuilder, Query};
 use spi::QueryError;
 use tokio::sync::oneshot;
@@ -70,11 +90,11 @@ use crate::http::metrics::HttpMetrics;
 use crate::http::response::{HttpResponse, ResponseBuilder};
 use crate::http::result_format::{get_result_format_from_header, ResultFormat};
 use crate::http::QuerySnafu;
-use crate::opentelemetry::jaeger_model::{Operation, Process, Trace};
+use crate::opentelemetry::jaeger_model::{DependencyLink, Operation, Process, Trace};
 use crate::opentelemetry::otlp_to_jaeger::{
-    FilterType, OtlpToJaeger, LIBRARY_NAME_COL_NAME, LIBRARY_VERSION_COL_NAME,
-    OPERATION_NAME_COL_NAME, PARENT_SPAN_ID_COL_NAME, SERVICE_NAME_COL_NAME, SPAN_ID_COL_NAME,
-    SPAN_KIND_COL_NAME, STATUS_CODE_COL_NAME, TRACE_ID_COL_NAME, TRACE_STATE_COL_NAME,
+    FilterType, OtlpToJaeger, DEFAULT_DEPENDENCIES_LOOKBACK_NANOS, OPERATION_NAME_COL_NAME,
+    SERVICE_NAME_COL_NAME, SPAN_KIND_COL_NAME, START_TIME_COL_NAME, TAG_COL_NAMES,
+    TRACE_ID_COL_NAME,
 };
 use crate::server;
 use crate::server::ServiceHandle;
@@ -108,6 +128,7 @@ pub struct HttpService {
     dbms: DBMSRef,
     coord: CoordinatorRef,
     prs: PromRemoteServerRef,
+    loki: LokiServerRef,
     handle: Option<ServiceHandle<()>>,
     query_body_limit: u64,
     write_body_limit: u64,
@@ -132,6 +153,7 @@ impl HttpService {
         let http_metrics = Arc::new(HttpMetrics::new(&metrics_register));
 
         let prs = Arc::new(PromRemoteSqlServer::new(dbms.clone(), coord.clone()));
+        let loki = Arc::new(LokiSqlServer::new(dbms.clone(), coord.clone()));
 
         Self {
             tls_config,
@@ -139,6 +161,7 @@ impl HttpService {
             dbms,
             coord,
             prs,
+            loki,
             handle: None,
             query_body_limit,
             write_body_limit,
@@ -233,6 +256,13 @@ impl HttpService {
         warp::any().map(move || prs.clone())
     }
 
+    fn with_loki_server(
+        &self,
+    ) -> impl Filter<Extract = (LokiServerRef,), Error = Infallible> + Clone {
+        let loki = self.loki.clone();
+        warp::any().map(move || loki.clone())
+    }
+
     fn with_metrics_register(
         &self,
     ) -> impl Filter<Extract = (Arc<MetricsRegister>,), Error = Infallible> + Clone {
@@ -264,6 +294,10 @@ impl HttpService {
             .or(self.debug_pprof())
             .or(self.debug_jeprof())
             .or(self.prom_remote_read())
+            .or(self.prom_query())
+            .or(self.prom_query_range())
+            .or(self.loki_query_range())
+            .or(self.loki_labels())
             .or(self.backtrace())
             .or(self.print_raft())
             .or(self.dump_ddl_sql())
@@ -280,11 +314,14 @@ impl HttpService {
             .or(self.get_es_template())
             .or(self.write_es_log())
             .or(self.write_otlp_trace())
+            .or(self.write_otlp_metrics())
+            .or(self.write_otlp_logs())
             .or(self.search_traces())
             .or(self.get_trace())
             .or(self.get_services())
             .or(self.get_operations())
             .or(self.get_operations_by_service())
+            .or(self.get_dependencies())
     }
 
     fn routes_store(
@@ -367,6 +404,7 @@ impl HttpService {
             .and(self.with_http_metrics())
             .and(self.with_hostaddr())
             .and(self.handle_span_header())
+            .and(warp::addr::remote())
             // construct_query
             .and_then(
                 |mut req: Bytes,
@@ -377,7 +415,8 @@ impl HttpService {
                  coord: CoordinatorRef,
                  metrics: Arc<HttpMetrics>,
                  addr: String,
-                 parent_span_ctx: Option<SpanContext>| async move {
+                 parent_span_ctx: Option<SpanContext>,
+                 client_addr: Option<SocketAddr>| async move {
                     let start = Instant::now();
                     debug!(
                         "Receive http sql request, header: {:?}, param: {:?}",
@@ -397,12 +436,13 @@ impl HttpService {
                         let mut span = Span::enter_with_parent("authenticate", &span);
 
                         // Parse req、header and param to construct query request
-                        let query = construct_query(req, &header, param, dbms.clone(), coord)
-                            .await
-                            .map_err(|e| {
-                                error!("Failed to construct query, err: {:?}", e);
-                                reject::custom(e)
-                            })?;
+                        let query =
+                            construct_query(req, &header, param, dbms.clone(), coord, client_addr)
+                                .await
+                                .map_err(|e| {
+                                    error!("Failed to construct query, err: {:?}", e);
+                                    reject::custom(e)
+                                })?;
                         record_context_in_span(&mut span, query.context());
                         query
                     };
@@ -1221,8 +1261,27 @@ impl HttpService {
                                 error!("Failed to handle prom remote read request, err: {:?}", e);
                                 reject::custom(QuerySnafu.into_error(e))
                             })
-                            .inspect(|b| {
-                                http_query_data_out.inc(b.len() as u64);
+                            .map(|response| match response {
+                                RemoteReadResponse::Samples(body) => {
+                                    http_query_data_out.inc(body.len() as u64);
+                                    ResponseBuilder::new(OK)
+                                        .insert_header((CONTENT_TYPE, SAMPLES_CONTENT_TYPE))
+                                        .insert_header((CONTENT_ENCODING, SNAPPY))
+                                        .build(body)
+                                }
+                                RemoteReadResponse::StreamedXorChunks(stream) => {
+                                    let stream = stream.inspect_ok(move |frame| {
+                                        http_query_data_out.inc(frame.len() as u64);
+                                    });
+                                    ResponseBuilder::new(OK)
+                                        .insert_header((
+                                            CONTENT_TYPE,
+                                            STREAMED_XOR_CHUNKS_CONTENT_TYPE,
+                                        ))
+                                        .build_stream_response(Response::new(Body::wrap_stream(
+                                            stream,
+                                        )))
+                                }
                             })
                     };
 
@@ -1368,6 +1427,192 @@ impl HttpService {
             )
     }
 
+    /// Query parameters of the Prometheus HTTP API,
+    /// sent either as URL query string (GET) or as url-encoded form (POST).
+    fn prom_query_param(
+        &self,
+    ) -> impl Filter<Extract = (PromQueryParam,), Error = warp::Rejection> + Clone {
+        warp::get()
+            .and(warp::query::<PromQueryParam>())
+            .or(warp::post()
+                .and(warp::body::content_length_limit(self.query_body_limit))
+                .and(warp::body::form::<PromQueryParam>()))
+            .unify()
+    }
+
+    fn prom_query(
+        &self,
+    ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+        warp::path!("api" / "v1" / "query")
+            .and(self.prom_query_param())
+            .and(self.handle_header())
+            .and(self.with_dbms())
+            .and(self.with_meta())
+            .and(self.with_coord())
+            .and(self.with_http_metrics())
+            .and(self.with_prom_remote_server())
+            .and(self.with_hostaddr())
+            .and(self.handle_span_header())
+            .and_then(
+                |param: PromQueryParam,
+                 header: Header,
+                 dbms: DBMSRef,
+                 meta: MetaRef,
+                 coord: CoordinatorRef,
+                 metrics: Arc<HttpMetrics>,
+                 prs: PromRemoteServerRef,
+                 addr: String,
+                 parent_span_ctx: Option<SpanContext>| async move {
+                    prom_query_handle(
+                        param,
+                        header,
+                        dbms,
+                        meta,
+                        coord,
+                        metrics,
+                        prs,
+                        addr,
+                        parent_span_ctx,
+                        HttpApiType::ApiV1PromQuery,
+                    )
+                    .await
+                },
+            )
+    }
+
+    fn prom_query_range(
+        &self,
+    ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+        warp::path!("api" / "v1" / "query_range")
+            .and(self.prom_query_param())
+            .and(self.handle_header())
+            .and(self.with_dbms())
+            .and(self.with_meta())
+            .and(self.with_coord())
+            .and(self.with_http_metrics())
+            .and(self.with_prom_remote_server())
+            .and(self.with_hostaddr())
+            .and(self.handle_span_header())
+            .and_then(
+                |param: PromQueryParam,
+                 header: Header,
+                 dbms: DBMSRef,
+                 meta: MetaRef,
+                 coord: CoordinatorRef,
+                 metrics: Arc<HttpMetrics>,
+                 prs: PromRemoteServerRef,
+                 addr: String,
+                 parent_span_ctx: Option<SpanContext>| async move {
+                    prom_query_handle(
+                        param,
+                        header,
+                        dbms,
+                        meta,
+                        coord,
+                        metrics,
+                        prs,
+                        addr,
+                        parent_span_ctx,
+                        HttpApiType::ApiV1PromQueryRange,
+                    )
+                    .await
+                },
+            )
+    }
+
+    /// Query parameters of the Loki HTTP API,
+    /// sent either as URL query string (GET) or as url-encoded form (POST).
+    fn loki_query_param(
+        &self,
+    ) -> impl Filter<Extract = (LokiQueryParam,), Error = warp::Rejection> + Clone {
+        warp::get()
+            .and(warp::query::<LokiQueryParam>())
+            .or(warp::post()
+                .and(warp::body::content_length_limit(self.query_body_limit))
+                .and(warp::body::form::<LokiQueryParam>()))
+            .unify()
+    }
+
+    fn loki_query_range(
+        &self,
+    ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+        warp::path!("loki" / "api" / "v1" / "query_range")
+            .and(self.loki_query_param())
+            .and(self.handle_header())
+            .and(self.with_dbms())
+            .and(self.with_meta())
+            .and(self.with_coord())
+            .and(self.with_http_metrics())
+            .and(self.with_loki_server())
+            .and(self.with_hostaddr())
+            .and(self.handle_span_header())
+            .and_then(
+                |param: LokiQueryParam,
+                 header: Header,
+                 dbms: DBMSRef,
+                 meta: MetaRef,
+                 coord: CoordinatorRef,
+                 metrics: Arc<HttpMetrics>,
+                 loki: LokiServerRef,
+                 addr: String,
+                 parent_span_ctx: Option<SpanContext>| async move {
+                    loki_query_handle(
+                        param,
+                        header,
+                        dbms,
+                        meta,
+                        coord,
+                        metrics,
+                        loki,
+                        addr,
+                        parent_span_ctx,
+                        HttpApiType::LokiApiV1QueryRange,
+                    )
+                    .await
+                },
+            )
+    }
+
+    fn loki_labels(
+        &self,
+    ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+        warp::path!("loki" / "api" / "v1" / "labels")
+            .and(self.loki_query_param())
+            .and(self.handle_header())
+            .and(self.with_dbms())
+            .and(self.with_meta())
+            .and(self.with_coord())
+            .and(self.with_http_metrics())
+            .and(self.with_loki_server())
+            .and(self.with_hostaddr())
+            .and(self.handle_span_header())
+            .and_then(
+                |param: LokiQueryParam,
+                 header: Header,
+                 dbms: DBMSRef,
+                 meta: MetaRef,
+                 coord: CoordinatorRef,
+                 metrics: Arc<HttpMetrics>,
+                 loki: LokiServerRef,
+                 addr: String,
+                 parent_span_ctx: Option<SpanContext>| async move {
+                    loki_query_handle(
+                        param,
+                        header,
+                        dbms,
+                        meta,
+                        coord,
+                        metrics,
+                        loki,
+                        addr,
+                        parent_span_ctx,
+                        HttpApiType::LokiApiV1Labels,
+                    )
+                    .await
+                },
+            )
+    }
+
     fn dump_ddl_sql(&self) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
         async fn dump_sql_ddl_impl(meta: MetaRef, tenant: Option<String>) -> MetaResult<String> {
             let cluster = meta.cluster();
@@ -1750,21 +1995,8 @@ impl HttpService {
                             reject::custom(e)
                         })?;
 
-                    let time_column =
-                        "ResourceSpans/ScopeSpans/Span/start_time_unix_nano".to_string();
-                    let tag_columns = [
-                        LIBRARY_NAME_COL_NAME,
-                        LIBRARY_VERSION_COL_NAME,
-                        STATUS_CODE_COL_NAME,
-                        SPAN_ID_COL_NAME,
-                        OPERATION_NAME_COL_NAME,
-                        PARENT_SPAN_ID_COL_NAME,
-                        SPAN_ID_COL_NAME,
-                        TRACE_ID_COL_NAME,
-                        TRACE_STATE_COL_NAME,
-                        SERVICE_NAME_COL_NAME,
-                    ]
-                    .join(",");
+                    let time_column = START_TIME_COL_NAME.to_string();
+                    let tag_columns = TAG_COL_NAMES.join(",");
 
                     let resp = coord_write_log(
                         &coord,
@@ -1812,99 +2044,329 @@ impl HttpService {
             )
     }
 
-    fn search_traces(
+    fn write_otlp_metrics(
         &self,
     ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
-        warp::path!("api" / "traces")
-            .and(warp::get())
+        warp::path!("api" / "v1" / "otlp" / "metrics")
+            .and(warp::post())
+            .and(warp::body::content_length_limit(self.write_body_limit))
+            .and(warp::body::bytes())
+            .and(warp::header::optional::<String>("content-type"))
             .and(self.handle_header())
-            .and(warp::query::<FindTracesParam>())
             .and(self.with_dbms())
             .and(self.with_coord())
             .and(self.with_http_metrics())
             .and(self.with_hostaddr())
+            .and(self.handle_span_header())
             .and_then(
-                |header: Header,
-                 param: FindTracesParam,
+                |mut req: Bytes,
+                 content_type: Option<String>,
+                 header: Header,
                  dbms: DBMSRef,
                  coord: CoordinatorRef,
                  metrics: Arc<HttpMetrics>,
-                 addr: String| async move {
+                 addr: String,
+                 parent_span_ctx: Option<SpanContext>| async move {
                     let start = Instant::now();
-                    // authenticate
-                    let sql_param = SqlParam {
-                        tenant: header.get_tenant().clone(),
-                        db: header.get_db().clone(),
-                        chunked: None,
-                        target_partitions: None,
-                        stream_trigger_interval: None,
+                    let span =
+                        Span::from_context("rest otlp metrics write", parent_span_ctx.as_ref());
+                    let span_context = span.context();
+
+                    check_otlp_content_type(content_type).map_err(reject::custom)?;
+                    let content_encoding = get_content_encoding_from_header(&header)?;
+                    if let Some(encoding) = content_encoding {
+                        req = encoding.decode(req).map_err(|e| {
+                            error!("Failed to decode request, err: {:?}", e);
+                            reject::custom(HttpError::DecodeRequest { source: e })
+                        })?;
+                    }
+
+                    let write_param = WriteParam {
+                        precision: None,
+                        tenant: header.get_tenant(),
+                        db: header.get_db(),
                     };
-                    let _ = construct_read_context(&header, sql_param, dbms, coord.clone(), false)
+                    let ctx = {
+                        let mut span = Span::enter_with_parent("construct write context", &span);
+                        let ctx = construct_write_context_and_check_privilege(
+                            header,
+                            write_param,
+                            dbms,
+                            coord.clone(),
+                        )
                         .await
                         .map_err(|e| {
-                            error!("Failed to construct query, err: {:?}", e);
+                            error!("Failed to construct write context, err: {:?}", e);
                             reject::custom(e)
                         })?;
+                        record_context_in_span(&mut span, &ctx);
+                        ctx
+                    };
 
-                    let traces = if let Some(trace_ids) = param.trace_ids {
-                        let mut traces = Vec::new();
-                        for trace_id in trace_ids.split(',').collect::<Vec<&str>>() {
-                            traces.push(
-                                Self::get_trace_inner(coord.clone(), &header, trace_id.to_string())
-                                    .await
-                                    .map_err(|e| {
-                                        error!("Failed to get trace, err: {:?}", e);
-                                        reject::custom(e)
-                                    })?,
-                            );
-                        }
-                        traces
-                    } else {
-                        // get param
-                        let tenant = header
-                            .get_tenant()
-                            .clone()
-                            .unwrap_or(DEFAULT_CATALOG.to_string());
-                        let db = header
-                            .get_db()
-                            .clone()
-                            .unwrap_or(DEFAULT_DATABASE.to_string());
-                        let table = header.get_table().clone().ok_or(HttpError::ParseLog {
-                            source: protocol_parser::JsonLogError::Common {
-                                content: "table param is None".to_string(),
-                            },
-                        })?;
+                    let req_len = req.len();
+                    http_limiter_check_write(&coord.meta_manager(), ctx.tenant(), req_len).await?;
 
-                        // contruct stream by query param
-                        let iterators = OtlpToJaeger::get_tskv_iterator(
-                            coord,
-                            FilterType::FindTraces(Some(param)),
-                            tenant,
-                            db,
-                            table,
-                        )
-                        .await
-                        .map_err(|e| HttpError::Query { source: e })?;
+                    let export_req = ExportMetricsServiceRequest::decode(req).map_err(|e| {
+                        error!("Failed to parse otlp metrics request, err: {:?}", e);
+                        reject::custom(HttpError::ParseOtlpProtocol { source: e })
+                    })?;
+                    let lines = otlp_metrics_to_lines(export_req, now_timestamp_nanos());
 
-                        // read recordbatch from stream
-                        let mut record_batch_vec = Vec::new();
-                        for mut iter in iterators {
-                            while let Some(record_batch) = iter
-                                .try_next()
-                                .await
-                                .map_err(|e| HttpError::Coordinator { source: e })?
-                            {
-                                record_batch_vec.push(record_batch);
-                            }
-                        }
+                    let resp = coord_write_points_with_span_recorder(
+                        &coord,
+                        ctx.tenant(),
+                        ctx.database(),
+                        Precision::NS,
+                        lines,
+                        span_context.as_ref(),
+                    )
+                    .await;
 
-                        // contruct Trace from recordbatch
-                        let mut trace_map: HashMap<String, Trace> = HashMap::default();
-                        for batch in record_batch_vec {
-                            let column_trace_id = batch
-                                .column_by_name(TRACE_ID_COL_NAME)
-                                .ok_or(HttpError::FetchResult {
-                                    reason: format!("column {} is not exist", TRACE_ID_COL_NAME),
+                    http_record_write_metrics(
+                        &metrics,
+                        &ctx,
+                        &addr,
+                        req_len,
+                        start,
+                        HttpApiType::ApiV1OtlpMetrics,
+                    );
+                    let result_size = size_of_val(&resp);
+                    let value_size = match &resp {
+                        Ok(value) => size_of_val(value),
+                        Err(error) => size_of_val(error),
+                    };
+
+                    let total_size = result_size + value_size + req_len;
+                    http_response_time_and_flow_metrics(
+                        &metrics,
+                        &addr,
+                        total_size,
+                        start,
+                        HttpApiType::ApiV1OtlpMetrics,
+                    );
+
+                    resp.map(|_| ResponseBuilder::ok()).map_err(|e| {
+                        error!(
+                            "Failed to handle http write otlp metrics request, err: {:?}",
+                            e
+                        );
+                        reject::custom(e)
+                    })
+                },
+            )
+    }
+
+    fn write_otlp_logs(
+        &self,
+    ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+        warp::path!("api" / "v1" / "otlp" / "logs")
+            .and(warp::post())
+            .and(warp::body::content_length_limit(self.write_body_limit))
+            .and(warp::body::bytes())
+            .and(warp::header::optional::<String>("content-type"))
+            .and(self.handle_header())
+            .and(self.with_dbms())
+            .and(self.with_coord())
+            .and(self.with_http_metrics())
+            .and(self.with_hostaddr())
+            .and(self.handle_span_header())
+            .and_then(
+                |mut req: Bytes,
+                 content_type: Option<String>,
+                 header: Header,
+                 dbms: DBMSRef,
+                 coord: CoordinatorRef,
+                 metrics: Arc<HttpMetrics>,
+                 addr: String,
+                 parent_span_ctx: Option<SpanContext>| async move {
+                    let start = Instant::now();
+                    let span = Span::from_context("rest otlp logs write", parent_span_ctx.as_ref());
+                    let span_context = span.context();
+
+                    let table = header
+                        .get_table()
+                        .clone()
+                        .unwrap_or(DEFAULT_OTLP_LOG_TABLE.to_string());
+
+                    check_otlp_content_type(content_type).map_err(reject::custom)?;
+                    let content_encoding = get_content_encoding_from_header(&header)?;
+                    if let Some(encoding) = content_encoding {
+                        req = encoding.decode(req).map_err(|e| {
+                            error!("Failed to decode request, err: {:?}", e);
+                            reject::custom(HttpError::DecodeRequest { source: e })
+                        })?;
+                    }
+
+                    let write_param = WriteParam {
+                        precision: None,
+                        tenant: header.get_tenant(),
+                        db: header.get_db(),
+                    };
+                    let ctx = {
+                        let mut span = Span::enter_with_parent("construct write context", &span);
+                        let ctx = construct_write_context_and_check_privilege(
+                            header,
+                            write_param,
+                            dbms,
+                            coord.clone(),
+                        )
+                        .await
+                        .map_err(|e| {
+                            error!("Failed to construct write context, err: {:?}", e);
+                            reject::custom(e)
+                        })?;
+                        record_context_in_span(&mut span, &ctx);
+                        ctx
+                    };
+
+                    let req_len = req.len();
+                    http_limiter_check_write(&coord.meta_manager(), ctx.tenant(), req_len).await?;
+
+                    let export_req = ExportLogsServiceRequest::decode(req).map_err(|e| {
+                        error!("Failed to parse otlp logs request, err: {:?}", e);
+                        reject::custom(HttpError::ParseOtlpProtocol { source: e })
+                    })?;
+                    let otlp_logs = otlp_logs_to_json(export_req);
+
+                    let resp = coord_write_log(
+                        &coord,
+                        ctx.tenant(),
+                        ctx.database(),
+                        &table,
+                        otlp_logs.logs,
+                        Some(OTLP_LOG_TIME_COLUMN.to_string()),
+                        Some(otlp_logs.tag_columns),
+                        span_context.as_ref(),
+                    )
+                    .await;
+
+                    http_record_write_metrics(
+                        &metrics,
+                        &ctx,
+                        &addr,
+                        req_len,
+                        start,
+                        HttpApiType::ApiV1OtlpLogs,
+                    );
+                    let result_size = size_of_val(&resp);
+                    let value_size = match &resp {
+                        Ok(value) => size_of_val(value),
+                        Err(error) => size_of_val(error),
+                    };
+
+                    let total_size = result_size + value_size + req_len;
+                    http_response_time_and_flow_metrics(
+                        &metrics,
+                        &addr,
+                        total_size,
+                        start,
+                        HttpApiType::ApiV1OtlpLogs,
+                    );
+
+                    resp.map(|_| ResponseBuilder::ok()).map_err(|e| {
+                        error!(
+                            "Failed to handle http write otlp logs request, err: {:?}",
+                            e
+                        );
+                        reject::custom(e)
+                    })
+                },
+            )
+    }
+
+    fn search_traces(
+        &self,
+    ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+        warp::path!("api" / "traces")
+            .and(warp::get())
+            .and(self.handle_header())
+            .and(warp::query::<FindTracesParam>())
+            .and(self.with_dbms())
+            .and(self.with_coord())
+            .and(self.with_http_metrics())
+            .and(self.with_hostaddr())
+            .and_then(
+                |header: Header,
+                 param: FindTracesParam,
+                 dbms: DBMSRef,
+                 coord: CoordinatorRef,
+                 metrics: Arc<HttpMetrics>,
+                 addr: String| async move {
+                    let start = Instant::now();
+                    // authenticate
+                    let sql_param = SqlParam {
+                        tenant: header.get_tenant().clone(),
+                        db: header.get_db().clone(),
+                        chunked: None,
+                        target_partitions: None,
+                        stream_trigger_interval: None,
+                    };
+                    let _ = construct_read_context(&header, sql_param, dbms, coord.clone(), false)
+                        .await
+                        .map_err(|e| {
+                            error!("Failed to construct query, err: {:?}", e);
+                            reject::custom(e)
+                        })?;
+
+                    let traces = if let Some(trace_ids) = param.trace_ids {
+                        let mut traces = Vec::new();
+                        for trace_id in trace_ids.split(',').collect::<Vec<&str>>() {
+                            traces.push(
+                                Self::get_trace_inner(coord.clone(), &header, trace_id.to_string())
+                                    .await
+                                    .map_err(|e| {
+                                        error!("Failed to get trace, err: {:?}", e);
+                                        reject::custom(e)
+                                    })?,
+                            );
+                        }
+                        traces
+                    } else {
+                        // get param
+                        let tenant = header
+                            .get_tenant()
+                            .clone()
+                            .unwrap_or(DEFAULT_CATALOG.to_string());
+                        let db = header
+                            .get_db()
+                            .clone()
+                            .unwrap_or(DEFAULT_DATABASE.to_string());
+                        let table = header.get_table().clone().ok_or(HttpError::ParseLog {
+                            source: protocol_parser::JsonLogError::Common {
+                                content: "table param is None".to_string(),
+                            },
+                        })?;
+
+                        // contruct stream by query param
+                        let iterators = OtlpToJaeger::get_tskv_iterator(
+                            coord,
+                            FilterType::FindTraces(Some(param)),
+                            tenant,
+                            db,
+                            table,
+                        )
+                        .await
+                        .map_err(|e| HttpError::Query { source: e })?;
+
+                        // read recordbatch from stream
+                        let mut record_batch_vec = Vec::new();
+                        for mut iter in iterators {
+                            while let Some(record_batch) = iter
+                                .try_next()
+                                .await
+                                .map_err(|e| HttpError::Coordinator { source: e })?
+                            {
+                                record_batch_vec.push(record_batch);
+                            }
+                        }
+
+                        // contruct Trace from recordbatch
+                        let mut trace_map: HashMap<String, Trace> = HashMap::default();
+                        for batch in record_batch_vec {
+                            let column_trace_id = batch
+                                .column_by_name(TRACE_ID_COL_NAME)
+                                .ok_or(HttpError::FetchResult {
+                                    reason: format!("column {} is not exist", TRACE_ID_COL_NAME),
                                 })?
                                 .as_any()
                                 .downcast_ref::<StringArray>()
@@ -2524,6 +2986,109 @@ impl HttpService {
                 },
             )
     }
+
+    fn get_dependencies(
+        &self,
+    ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+        warp::path!("api" / "dependencies")
+            .and(warp::get())
+            .and(self.handle_header())
+            .and(warp::query::<GetDependenciesParam>())
+            .and(self.with_dbms())
+            .and(self.with_coord())
+            .and(self.with_http_metrics())
+            .and(self.with_hostaddr())
+            .and_then(
+                |header: Header,
+                 param: GetDependenciesParam,
+                 dbms: DBMSRef,
+                 coord: CoordinatorRef,
+                 metrics: Arc<HttpMetrics>,
+                 addr: String| async move {
+                    let start = Instant::now();
+                    // authenticate
+                    let sql_param = SqlParam {
+                        tenant: header.get_tenant().clone(),
+                        db: header.get_db().clone(),
+                        chunked: None,
+                        target_partitions: None,
+                        stream_trigger_interval: None,
+                    };
+                    let _ = construct_read_context(&header, sql_param, dbms, coord.clone(), false)
+                        .await
+                        .map_err(|e| {
+                            error!("Failed to construct query, err: {:?}", e);
+                            reject::custom(e)
+                        })?;
+
+                    // get param
+                    let tenant = header
+                        .get_tenant()
+                        .clone()
+                        .unwrap_or(DEFAULT_CATALOG.to_string());
+                    let db = header
+                        .get_db()
+                        .clone()
+                        .unwrap_or(DEFAULT_DATABASE.to_string());
+                    let table = header.get_table().clone().ok_or(HttpError::ParseLog {
+                        source: protocol_parser::JsonLogError::Common {
+                            content: "table param is None".to_string(),
+                        },
+                    })?;
+                    let end_ts = param
+                        .end_ts
+                        .map(|ms| ms * 1_000_000)
+                        .unwrap_or_else(now_timestamp_nanos);
+                    let lookback = param
+                        .lookback
+                        .map(|ms| ms * 1_000_000)
+                        .unwrap_or(DEFAULT_DEPENDENCIES_LOOKBACK_NANOS);
+
+                    let data = OtlpToJaeger::get_dependencies(
+                        coord,
+                        tenant,
+                        db,
+                        table,
+                        end_ts - lookback,
+                        end_ts,
+                    )
+                    .await
+                    .map_err(|e| HttpError::Query { source: e })?;
+
+                    #[derive(serde::Serialize)]
+                    struct Dependencies {
+                        data: Vec<DependencyLink>,
+                        total: u64,
+                        limit: u64,
+                        offset: u64,
+                        errors: Option<String>,
+                    }
+                    let total = data.len() as u64;
+                    let resp = Dependencies {
+                        data,
+                        total,
+                        limit: 0,
+                        offset: 0,
+                        errors: None,
+                    };
+
+                    http_response_time_and_flow_metrics(
+                        &metrics,
+                        &addr,
+                        size_of_val(&resp),
+                        start,
+                        HttpApiType::ApiDependencies,
+                    );
+
+                    // return
+                    let builder = ResponseBuilder::new(OK).insert_header((
+                        CONTENT_TYPE,
+                        HeaderValue::from_static("application/json"),
+                    ));
+                    Ok::<_, Rejection>(builder.json(&resp))
+                },
+            )
+    }
 }
 
 #[async_trait::async_trait]
@@ -2617,8 +3182,12 @@ async fn construct_query(
     param: SqlParam,
     dbms: DBMSRef,
     coord: CoordinatorRef,
+    client_addr: Option<SocketAddr>,
 ) -> Result<Query, HttpError> {
-    let context = construct_read_context(header, param, dbms, coord, true).await?;
+    let context = construct_read_context_builder(header, param, dbms, coord, true)
+        .await?
+        .with_client_addr(client_addr.map(|addr| addr.to_string()))
+        .build();
 
     Ok(Query::new(
         context,
@@ -2626,20 +3195,29 @@ async fn construct_query(
     ))
 }
 
-async fn construct_read_context(
+pub(crate) async fn construct_read_context(
     header: &Header,
     param: SqlParam,
     dbms: DBMSRef,
     coord: CoordinatorRef,
     is_sql: bool,
 ) -> Result<Context, HttpError> {
-    let user_info = header.try_get_basic_auth()?;
+    let context = construct_read_context_builder(header, param, dbms, coord, is_sql)
+        .await?
+        .build();
+
+    Ok(context)
+}
 
+async fn construct_read_context_builder(
+    header: &Header,
+    param: SqlParam,
+    dbms: DBMSRef,
+    coord: CoordinatorRef,
+    is_sql: bool,
+) -> Result<ContextBuilder, HttpError> {
     let tenant = param.tenant;
-    let user = dbms
-        .authenticate(&user_info, tenant.as_deref().unwrap_or(DEFAULT_CATALOG))
-        .await
-        .context(QuerySnafu)?;
+    let user = authenticate(header, tenant.as_deref().unwrap_or(DEFAULT_CATALOG), &dbms).await?;
 
     if !is_sql
         && coord.get_config().query.auth_enabled
@@ -2656,7 +3234,7 @@ async fn construct_read_context(
         });
     }
 
-    let context = ContextBuilder::new(user)
+    let builder = ContextBuilder::new(user)
         .with_tenant(tenant)
         .with_database(param.db)
         .with_target_partitions(param.target_partitions)
@@ -2669,10 +3247,24 @@ async fn construct_read_context(
                         .map_err(|reason| HttpError::InvalidHeader { reason })
                 })
                 .transpose()?,
-        )
-        .build();
+        );
 
-    Ok(context)
+    Ok(builder)
+}
+
+/// Authenticate the bearer token or the basic authorization of the request
+async fn authenticate(header: &Header, tenant: &str, dbms: &DBMSRef) -> Result<User, HttpError> {
+    if let Some(token) = header.get_bearer_token() {
+        return dbms
+            .authenticate_token(token, tenant)
+            .await
+            .context(QuerySnafu);
+    }
+
+    let user_info = header.try_get_basic_auth()?;
+    dbms.authenticate(&user_info, tenant)
+        .await
+        .context(QuerySnafu)
 }
 
 async fn construct_write_context(
@@ -2680,15 +3272,11 @@ async fn construct_write_context(
     param: WriteParam,
     dbms: DBMSRef,
 ) -> Result<Context, HttpError> {
-    let user_info = header.try_get_basic_auth()?;
     let tenant = param.tenant;
     let db = param.db;
     let precision = param.precision;
 
-    let user = dbms
-        .authenticate(&user_info, tenant.as_deref().unwrap_or(DEFAULT_CATALOG))
-        .await
-        .context(QuerySnafu)?;
+    let user = authenticate(header, tenant.as_deref().unwrap_or(DEFAULT_CATALOG), &dbms).await?;
 
     let context = ContextBuilder::new(user)
         .with_tenant(tenant)
@@ -2707,7 +3295,7 @@ fn _construct_write_db_privilege(tenant_id: Oid, database: &str) -> Privilege<Oi
 }
 
 // construct context and check privilege
-async fn construct_write_context_and_check_privilege(
+pub(crate) async fn construct_write_context_and_check_privilege(
     header: Header,
     param: WriteParam,
     dbms: DBMSRef,
@@ -2840,6 +3428,21 @@ fn try_parse_log_req(
     }
 }
 
+/// OTLP/HTTP requests are only accepted in the binary protobuf encoding.
+fn check_otlp_content_type(content_type: Option<String>) -> Result<(), HttpError> {
+    match content_type {
+        Some(content_type) if content_type != "application/x-protobuf" => {
+            Err(HttpError::InvalidHeader {
+                reason: format!(
+                    "unsupported content-type {}, expect application/x-protobuf",
+                    content_type
+                ),
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
 async fn coord_write_log(
     coord: &CoordinatorRef,
     tenant: &str,
@@ -2929,7 +3532,7 @@ async fn sql_handle(
     );
 
     let span = Span::from_context("build response", span_ctx);
-    if !query.context().chunked() {
+    if !query.context().chunked() || !fmt.supports_chunked() {
         let result = resp.wrap_batches_to_response().await;
         if let Err(err) = &result {
             if tskv::TskvError::vnode_broken_code(err.error_code().code()) {
@@ -2961,6 +3564,282 @@ async fn sql_handle(
     }
 }
 
+/// Handle `/api/v1/query` and `/api/v1/query_range`, and respond in the format of the Prometheus HTTP API.
+async fn prom_query_handle(
+    param: PromQueryParam,
+    header: Header,
+    dbms: DBMSRef,
+    meta: MetaRef,
+    coord: CoordinatorRef,
+    metrics: Arc<HttpMetrics>,
+    prs: PromRemoteServerRef,
+    addr: String,
+    parent_span_ctx: Option<SpanContext>,
+    api_type: HttpApiType,
+) -> Result<Response, Rejection> {
+    let start = Instant::now();
+    debug!(
+        "Receive rest prom query request, header: {:?}, param: {:?}",
+        header, param
+    );
+    let span = Span::from_context(format!("rest {}", api_type), parent_span_ctx.as_ref());
+
+    let context = {
+        let mut span = Span::enter_with_parent("construct context", &span);
+        let sql_param = SqlParam {
+            tenant: param.tenant.clone().or_else(|| header.get_tenant()),
+            db: param.db.clone().or_else(|| header.get_db()),
+            chunked: None,
+            target_partitions: None,
+            stream_trigger_interval: None,
+        };
+        let ctx = construct_read_context(&header, sql_param, dbms, coord, false)
+            .await
+            .map_err(|e| {
+                error!("Failed to construct read context, err: {:?}", e);
+                reject::custom(e)
+            })?;
+        record_context_in_span(&mut span, &ctx);
+        ctx
+    };
+    let query = param.query.clone().unwrap_or_default();
+    let req_len = query.len();
+
+    http_limiter_check_query(&meta, context.tenant(), req_len)
+        .await
+        .map_err(|e| {
+            error!("Failed to check query limiter, err: {:?}", e);
+            reject::custom(e)
+        })?;
+
+    let result = {
+        let span = Span::enter_with_parent("promql", &span);
+        let span_ctx = span.context();
+        let time_param = |name: &str, value: &Option<String>| {
+            value
+                .as_deref()
+                .ok_or_else(|| QueryError::InvalidParam {
+                    reason: format!("missing parameter \"{}\"", name),
+                })
+                .and_then(parse_prom_timestamp)
+        };
+        let result = match api_type {
+            HttpApiType::ApiV1PromQueryRange => {
+                let range = time_param("start", &param.start).and_then(|start| {
+                    let end = time_param("end", &param.end)?;
+                    let step = param
+                        .step
+                        .as_deref()
+                        .ok_or_else(|| QueryError::InvalidParam {
+                            reason: "missing parameter \"step\"".to_string(),
+                        })
+                        .and_then(parse_prom_step)?;
+                    Ok((start, end, step))
+                });
+                match range {
+                    Ok((start, end, step)) => {
+                        prs.range_query(&context, &query, start, end, step, span_ctx.as_ref())
+                            .await
+                    }
+                    Err(e) => Err(e),
+                }
+            }
+            _ => {
+                let time = match &param.time {
+                    Some(_) => time_param("time", &param.time),
+                    None => Ok(now_timestamp_nanos() / 1_000_000),
+                };
+                match time {
+                    Ok(time) => {
+                        prs.instant_query(&context, &query, time, span_ctx.as_ref())
+                            .await
+                    }
+                    Err(e) => Err(e),
+                }
+            }
+        };
+        if let Err(e) = &result {
+            span.error(e.to_string());
+            error!("Failed to handle prom query request, err: {:?}", e);
+        }
+        result
+    };
+
+    http_record_query_metrics(&metrics, &context, &addr, req_len, start, api_type);
+
+    let resp = match result {
+        Ok(value) => ResponseBuilder::new(OK).json(&serde_json::json!({
+            "status": "success",
+            "data": value.to_json(),
+        })),
+        Err(e) => {
+            let (status, error_type) = match e {
+                QueryError::InvalidPromQL { .. } | QueryError::InvalidParam { .. } => {
+                    (BAD_REQUEST, "bad_data")
+                }
+                _ => (UNPROCESSABLE_ENTITY, "execution"),
+            };
+            ResponseBuilder::new(status).json(&serde_json::json!({
+                "status": "error",
+                "errorType": error_type,
+                "error": e.to_string(),
+            }))
+        }
+    };
+
+    http_response_time_and_flow_metrics(&metrics, &addr, req_len, start, api_type);
+    Ok(resp)
+}
+
+/// Handle `/loki/api/v1/query_range` and `/loki/api/v1/labels`, and respond in the format of the Loki HTTP API.
+async fn loki_query_handle(
+    param: LokiQueryParam,
+    header: Header,
+    dbms: DBMSRef,
+    meta: MetaRef,
+    coord: CoordinatorRef,
+    metrics: Arc<HttpMetrics>,
+    loki: LokiServerRef,
+    addr: String,
+    parent_span_ctx: Option<SpanContext>,
+    api_type: HttpApiType,
+) -> Result<Response, Rejection> {
+    let start = Instant::now();
+    debug!(
+        "Receive rest loki query request, header: {:?}, param: {:?}",
+        header, param
+    );
+    let span = Span::from_context(format!("rest {}", api_type), parent_span_ctx.as_ref());
+
+    let context = {
+        let mut span = Span::enter_with_parent("construct context", &span);
+        let sql_param = SqlParam {
+            tenant: param.tenant.clone().or_else(|| header.get_tenant()),
+            db: param.db.clone().or_else(|| header.get_db()),
+            chunked: None,
+            target_partitions: None,
+            stream_trigger_interval: None,
+        };
+        let ctx = construct_read_context(&header, sql_param, dbms, coord, false)
+            .await
+            .map_err(|e| {
+                error!("Failed to construct read context, err: {:?}", e);
+                reject::custom(e)
+            })?;
+        record_context_in_span(&mut span, &ctx);
+        ctx
+    };
+    let query = param.query.clone().unwrap_or_default();
+    let req_len = query.len();
+
+    http_limiter_check_query(&meta, context.tenant(), req_len)
+        .await
+        .map_err(|e| {
+            error!("Failed to check query limiter, err: {:?}", e);
+            reject::custom(e)
+        })?;
+
+    let result = {
+        let span = Span::enter_with_parent("logql", &span);
+        let span_ctx = span.context();
+        let table = param
+            .table
+            .clone()
+            .or_else(|| header.get_table())
+            .ok_or_else(|| QueryError::InvalidParam {
+                reason: "missing parameter \"table\"".to_string(),
+            });
+        let result = match (api_type, table) {
+            (_, Err(e)) => Err(e),
+            (HttpApiType::LokiApiV1Labels, Ok(table)) => loki
+                .labels(&context, &table)
+                .await
+                .map(|labels| serde_json::json!(labels)),
+            (_, Ok(table)) => match loki_range_query(&param, table, query) {
+                Ok(range_query) => loki
+                    .query_range(&context, &range_query, span_ctx.as_ref())
+                    .await
+                    .map(|value| value.to_json()),
+                Err(e) => Err(e),
+            },
+        };
+        if let Err(e) = &result {
+            span.error(e.to_string());
+            error!("Failed to handle loki query request, err: {:?}", e);
+        }
+        result
+    };
+
+    http_record_query_metrics(&metrics, &context, &addr, req_len, start, api_type);
+
+    let resp = match result {
+        Ok(data) => ResponseBuilder::new(OK).json(&serde_json::json!({
+            "status": "success",
+            "data": data,
+        })),
+        Err(e) => {
+            let status = match e {
+                QueryError::InvalidLogQL { .. }
+                | QueryError::InvalidParam { .. }
+                | QueryError::NotImplemented { .. } => BAD_REQUEST,
+                _ => UNPROCESSABLE_ENTITY,
+            };
+            ResponseBuilder::new(status).json(&serde_json::json!({
+                "status": "error",
+                "code": status.as_u16(),
+                "message": e.to_string(),
+            }))
+        }
+    };
+
+    http_response_time_and_flow_metrics(&metrics, &addr, req_len, start, api_type);
+    Ok(resp)
+}
+
+/// Build a range query from the parameters, with the same defaults as Loki:
+/// the last hour, about 250 steps, and the latest 100 lines.
+fn loki_range_query(
+    param: &LokiQueryParam,
+    table: String,
+    query: String,
+) -> Result<LokiRangeQuery, QueryError> {
+    let end = match &param.end {
+        Some(end) => parse_loki_timestamp(end)?,
+        None => now_timestamp_nanos(),
+    };
+    let start = match &param.start {
+        Some(start) => parse_loki_timestamp(start)?,
+        None => end - 3_600_000_000_000,
+    };
+    if end < start {
+        return Err(QueryError::InvalidParam {
+            reason: "end timestamp must not be before start time".to_string(),
+        });
+    }
+    let step = match &param.step {
+        Some(step) => parse_loki_step(step)?,
+        None => ((end - start) / 250_000_000_000).max(1) * 1_000_000_000,
+    };
+    let direction = match &param.direction {
+        Some(direction) => direction.parse::<LokiDirection>()?,
+        None => LokiDirection::default(),
+    };
+
+    Ok(LokiRangeQuery {
+        table,
+        line_column: param
+            .line_column
+            .clone()
+            .unwrap_or_else(|| DEFAULT_LOKI_LINE_COLUMN.to_string()),
+        query,
+        start,
+        end,
+        step,
+        limit: param.limit.unwrap_or(100),
+        direction,
+    })
+}
+
 async fn http_limiter_check_query(
     meta: &MetaRef,
     tenant: &str,
@@ -2975,7 +3854,7 @@ async fn http_limiter_check_query(
     Ok(())
 }
 
-async fn http_limiter_check_write(
+pub(crate) async fn http_limiter_check_write(
     meta: &MetaRef,
     tenant: &str,
     req_len: usize,
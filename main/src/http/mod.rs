@@ -173,6 +173,7 @@ impl From<&Error> for Response {
             | Error::ParseAuth { .. }
             | Error::TraceHttp { .. }
             | Error::DecodeRequest { .. }
+            | Error::ParseOtlpProtocol { .. }
             | Error::ParseOpentsdbProtocol { .. }
             | Error::ParseOpentsdbJsonProtocol { .. } => ResponseBuilder::bad_request(&error_resp),
             _ => ResponseBuilder::internal_server_error(),
@@ -26,7 +26,7 @@ use warp::reply::Response;
 use warp::{hyper, Reply};
 
 use super::header::IntoHeaderPair;
-use super::result_format::ResultFormat;
+use super::result_format::{ArrowStreamEncoder, ResultFormat};
 use super::{Error as HttpError, MetaSnafu, QuerySnafu};
 
 #[derive(Default)]
@@ -128,6 +128,7 @@ pub struct HttpResponse {
     format: ResultFormat,
     encoding: Option<Encoding>,
     schema: Option<SchemaRef>,
+    arrow_stream: Option<ArrowStreamEncoder>,
     http_query_data_out: U64Counter,
     limiter: Arc<dyn RequestLimiter>,
     stream_state: HttpResponseStreamState,
@@ -142,11 +143,14 @@ impl HttpResponse {
         limiter: Arc<dyn RequestLimiter>,
     ) -> Self {
         let schema = result.schema();
+        let arrow_stream =
+            (format == ResultFormat::ArrowStream).then(|| ArrowStreamEncoder::new(schema.clone()));
         Self {
             result,
             format,
             encoding,
             schema: Some(schema),
+            arrow_stream,
             limiter,
             stream_state: HttpResponseStreamState::PollNext,
             http_query_data_out,
@@ -154,7 +158,12 @@ impl HttpResponse {
     }
 
     pub async fn wrap_batches_to_response(self) -> Result<Response, HttpError> {
-        let actual = self.result.chunk_result().await.context(QuerySnafu)?;
+        let mut actual = self.result.chunk_result().await.context(QuerySnafu)?;
+        if actual.is_empty() && self.format.requires_schema() {
+            if let Some(schema) = self.schema.clone() {
+                actual.push(RecordBatch::new_empty(schema));
+            }
+        }
         self.format.wrap_batches_to_response(
             &actual,
             true,
@@ -178,47 +187,48 @@ impl HttpResponse {
     ) -> Result<HttpResponseStreamState, HttpError> {
         match opt_result_batch {
             None => {
-                if let Some(schema) = self.schema.take() {
+                let buffer = if let Some(encoder) = self.arrow_stream.as_mut() {
+                    encoder.finish()
+                } else if let Some(schema) = self.schema.take() {
                     let has_headers = !schema.fields().is_empty();
                     let rb = RecordBatch::new_empty(schema);
-                    let mut buffer =
-                        self.format
-                            .format_batches(&[rb], has_headers)
-                            .map_err(|e| HttpError::FetchResult {
-                                reason: format!("{}", e),
-                            })?;
-                    if let Some(encoding) = self.encoding {
-                        buffer = encoding
-                            .encode(buffer)
-                            .map_err(|e| HttpError::EncodeResponse { source: e })?;
-                    }
-                    self.schema = None;
-                    let limiter = self.limiter.clone();
-                    let buffer_len = buffer.len();
-                    self.http_query_data_out.inc(buffer_len as u64);
-                    let future = async move {
-                        limiter
-                            .check_http_data_out(buffer_len)
-                            .await
-                            .context(MetaSnafu)
-                    };
-                    Ok(HttpResponseStreamState::CheckLimiter(
-                        Box::pin(future),
-                        buffer,
-                        true,
-                    ))
+                    self.format.format_batches(&[rb], has_headers)
                 } else {
-                    Ok(HttpResponseStreamState::Finish)
+                    return Ok(HttpResponseStreamState::Finish);
+                };
+                let mut buffer = buffer.map_err(|e| HttpError::FetchResult {
+                    reason: format!("{}", e),
+                })?;
+                if let Some(encoding) = self.encoding {
+                    buffer = encoding
+                        .encode(buffer)
+                        .map_err(|e| HttpError::EncodeResponse { source: e })?;
                 }
+                self.schema = None;
+                let limiter = self.limiter.clone();
+                let buffer_len = buffer.len();
+                self.http_query_data_out.inc(buffer_len as u64);
+                let future = async move {
+                    limiter
+                        .check_http_data_out(buffer_len)
+                        .await
+                        .context(MetaSnafu)
+                };
+                Ok(HttpResponseStreamState::CheckLimiter(
+                    Box::pin(future),
+                    buffer,
+                    true,
+                ))
             }
             Some(Ok(rb)) => {
                 if rb.num_rows() > 0 {
-                    let mut buffer = self
-                        .format
-                        .format_batches(&[rb], self.schema.is_some())
-                        .map_err(|e| HttpError::FetchResult {
-                            reason: format!("{}", e),
-                        })?;
+                    let buffer = match self.arrow_stream.as_mut() {
+                        Some(encoder) => encoder.encode(&rb),
+                        None => self.format.format_batches(&[rb], self.schema.is_some()),
+                    };
+                    let mut buffer = buffer.map_err(|e| HttpError::FetchResult {
+                        reason: format!("{}", e),
+                    })?;
                     if let Some(encoding) = self.encoding.as_ref() {
                         buffer = encoding
                             .encode(buffer)
@@ -1,14 +1,21 @@
 use std::str::FromStr;
 
 use datafusion::arrow::csv::writer::WriterBuilder;
-use datafusion::arrow::error::Result as ArrowResult;
+use datafusion::arrow::datatypes::SchemaRef;
+use datafusion::arrow::error::{ArrowError, Result as ArrowResult};
+use datafusion::arrow::ipc::writer::{
+    write_message, DictionaryTracker, IpcDataGenerator, IpcWriteOptions,
+};
 use datafusion::arrow::json::{ArrayWriter, LineDelimitedWriter};
 use datafusion::arrow::record_batch::RecordBatch;
 use datafusion::arrow::util::pretty::pretty_format_batches;
+use datafusion::parquet::arrow::ArrowWriter;
+use datafusion::parquet::errors::ParquetError;
 use http_protocol::encoding::Encoding;
 use http_protocol::header::{
-    APPLICATION_CSV, APPLICATION_JSON, APPLICATION_NDJSON, APPLICATION_PREFIX, APPLICATION_STAR,
-    APPLICATION_TABLE, APPLICATION_TSV, CONTENT_TYPE, STAR_STAR, TEXT_PREFIX,
+    APPLICATION_ARROW_STREAM, APPLICATION_CSV, APPLICATION_JSON, APPLICATION_NDJSON,
+    APPLICATION_PARQUET, APPLICATION_PREFIX, APPLICATION_STAR, APPLICATION_TABLE, APPLICATION_TSV,
+    CONTENT_TYPE, STAR_STAR, TEXT_PREFIX,
 };
 use http_protocol::status_code::OK;
 use metrics::count::U64Counter;
@@ -53,6 +60,86 @@ fn batches_with_sep(
     Ok(bytes)
 }
 
+fn batches_to_arrow_stream(batches: &[RecordBatch]) -> ArrowResult<Vec<u8>> {
+    let mut encoder = ArrowStreamEncoder::new(batches[0].schema());
+    let mut bytes = vec![];
+    for batch in batches {
+        bytes.extend(encoder.encode(batch)?);
+    }
+    bytes.extend(encoder.finish()?);
+    Ok(bytes)
+}
+
+fn batches_to_parquet(batches: &[RecordBatch]) -> ArrowResult<Vec<u8>> {
+    let to_arrow_error = |e: ParquetError| ArrowError::ExternalError(Box::new(e));
+    let mut bytes = vec![];
+    {
+        let mut writer =
+            ArrowWriter::try_new(&mut bytes, batches[0].schema(), None).map_err(to_arrow_error)?;
+        for batch in batches {
+            writer.write(batch).map_err(to_arrow_error)?;
+        }
+        writer.close().map_err(to_arrow_error)?;
+    }
+    Ok(bytes)
+}
+
+/// The end-of-stream marker of the Arrow IPC streaming format:
+/// a continuation marker followed by a zero message length.
+const ARROW_STREAM_EOS: [u8; 8] = [0xff, 0xff, 0xff, 0xff, 0, 0, 0, 0];
+
+/// Encodes record batches into the Arrow IPC streaming format one by one,
+/// so that a chunked response is sent batch by batch.
+pub struct ArrowStreamEncoder {
+    schema: SchemaRef,
+    data_gen: IpcDataGenerator,
+    dictionary_tracker: DictionaryTracker,
+    options: IpcWriteOptions,
+    schema_written: bool,
+}
+
+impl ArrowStreamEncoder {
+    pub fn new(schema: SchemaRef) -> Self {
+        Self {
+            schema,
+            data_gen: IpcDataGenerator::default(),
+            dictionary_tracker: DictionaryTracker::new(false),
+            options: IpcWriteOptions::default(),
+            schema_written: false,
+        }
+    }
+
+    /// Encode the batch, preceded by the schema if it is the first one.
+    pub fn encode(&mut self, batch: &RecordBatch) -> ArrowResult<Vec<u8>> {
+        let mut bytes = self.encode_schema()?;
+        let (dictionaries, batch) =
+            self.data_gen
+                .encoded_batch(batch, &mut self.dictionary_tracker, &self.options)?;
+        for dictionary in dictionaries {
+            write_message(&mut bytes, dictionary, &self.options)?;
+        }
+        write_message(&mut bytes, batch, &self.options)?;
+        Ok(bytes)
+    }
+
+    /// Encode the end of the stream, preceded by the schema if there is no batch.
+    pub fn finish(&mut self) -> ArrowResult<Vec<u8>> {
+        let mut bytes = self.encode_schema()?;
+        bytes.extend_from_slice(&ARROW_STREAM_EOS);
+        Ok(bytes)
+    }
+
+    fn encode_schema(&mut self) -> ArrowResult<Vec<u8>> {
+        let mut bytes = vec![];
+        if !self.schema_written {
+            let schema = self.data_gen.schema_to_bytes(&self.schema, &self.options);
+            write_message(&mut bytes, schema, &self.options)?;
+            self.schema_written = true;
+        }
+        Ok(bytes)
+    }
+}
+
 /// Allow records to be printed in different formats
 #[derive(Debug, PartialEq, Eq, clap::ValueEnum, Clone)]
 pub enum ResultFormat {
@@ -61,6 +148,8 @@ pub enum ResultFormat {
     Json,
     NdJson,
     Table,
+    ArrowStream,
+    Parquet,
 }
 
 impl ResultFormat {
@@ -71,9 +160,22 @@ impl ResultFormat {
             Self::Json => APPLICATION_JSON,
             Self::NdJson => APPLICATION_NDJSON,
             Self::Table => APPLICATION_TABLE,
+            Self::ArrowStream => APPLICATION_ARROW_STREAM,
+            Self::Parquet => APPLICATION_PARQUET,
         }
     }
 
+    /// Whether the result is valid only if it contains the schema, even if there is no row.
+    pub fn requires_schema(&self) -> bool {
+        matches!(self, Self::ArrowStream | Self::Parquet)
+    }
+
+    /// Whether the result can be responded batch by batch. The metadata of a
+    /// parquet file is written after all the batches, so it is responded at once.
+    pub fn supports_chunked(&self) -> bool {
+        !matches!(self, Self::Parquet)
+    }
+
     pub fn format_batches(
         &self,
         batches: &[RecordBatch],
@@ -90,6 +192,8 @@ impl ResultFormat {
                 batches_to_json!(LineDelimitedWriter, batches)
             }
             Self::Table => Ok(pretty_format_batches(batches)?.to_string().into_bytes()),
+            Self::ArrowStream => batches_to_arrow_stream(batches),
+            Self::Parquet => batches_to_parquet(batches),
         }
     }
 
@@ -130,6 +234,14 @@ impl TryFrom<&str> for ResultFormat {
             return Ok(ResultFormat::Csv);
         }
 
+        if s == APPLICATION_ARROW_STREAM {
+            return Ok(ResultFormat::ArrowStream);
+        }
+
+        if s == APPLICATION_PARQUET {
+            return Ok(ResultFormat::Parquet);
+        }
+
         if let Some(fmt) = s.strip_prefix(APPLICATION_PREFIX) {
             return ResultFormat::from_str(fmt)
                 .map_err(|reason| HttpError::InvalidHeader { reason });
@@ -166,13 +278,83 @@ pub fn get_result_format_from_header(header: &Header) -> Result<ResultFormat, Re
 
 #[cfg(test)]
 mod tests {
+    use std::io::Cursor;
     use std::sync::Arc;
 
+    use bytes::Bytes;
     use datafusion::arrow::array::Int32Array;
     use datafusion::arrow::datatypes::{DataType, Field, Schema};
+    use datafusion::arrow::ipc::reader::StreamReader;
+    use datafusion::parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
 
     use super::*;
 
+    fn test_batch(values: Vec<i32>) -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, false)]));
+        RecordBatch::try_new(schema, vec![Arc::new(Int32Array::from(values))]).unwrap()
+    }
+
+    #[test]
+    fn test_result_format_from_accept() {
+        assert_eq!(
+            ResultFormat::try_from(APPLICATION_ARROW_STREAM).unwrap(),
+            ResultFormat::ArrowStream
+        );
+        assert_eq!(
+            ResultFormat::try_from(APPLICATION_PARQUET).unwrap(),
+            ResultFormat::Parquet
+        );
+        assert_eq!(
+            ResultFormat::try_from("application/csv").unwrap(),
+            ResultFormat::Csv
+        );
+        assert!(ResultFormat::try_from("application/vnd.apache.orc").is_err());
+    }
+
+    #[test]
+    fn test_arrow_stream_encoder() {
+        let batches = vec![test_batch(vec![1, 2, 3]), test_batch(vec![4, 5])];
+
+        let mut encoder = ArrowStreamEncoder::new(batches[0].schema());
+        let mut bytes = vec![];
+        for batch in &batches {
+            bytes.extend(encoder.encode(batch).unwrap());
+        }
+        bytes.extend(encoder.finish().unwrap());
+        assert_eq!(
+            bytes,
+            ResultFormat::ArrowStream
+                .format_batches(&batches, true)
+                .unwrap()
+        );
+
+        let reader = StreamReader::try_new(Cursor::new(bytes), None).unwrap();
+        let read = reader.collect::<ArrowResult<Vec<_>>>().unwrap();
+        assert_eq!(read, batches);
+
+        // Only the schema and the end of the stream
+        let mut encoder = ArrowStreamEncoder::new(batches[0].schema());
+        let bytes = encoder.finish().unwrap();
+        let reader = StreamReader::try_new(Cursor::new(bytes), None).unwrap();
+        assert_eq!(reader.schema(), batches[0].schema());
+        assert_eq!(reader.count(), 0);
+    }
+
+    #[test]
+    fn test_format_batches_to_parquet() {
+        let batches = vec![test_batch(vec![1, 2, 3]), test_batch(vec![4, 5])];
+        let bytes = ResultFormat::Parquet
+            .format_batches(&batches, true)
+            .unwrap();
+
+        let reader = ParquetRecordBatchReaderBuilder::try_new(Bytes::from(bytes))
+            .unwrap()
+            .build()
+            .unwrap();
+        let rows = reader.map(|batch| batch.unwrap().num_rows()).sum::<usize>();
+        assert_eq!(rows, 5);
+    }
+
     #[test]
     fn test_format_batches_with_sep() {
         let batches = vec![];
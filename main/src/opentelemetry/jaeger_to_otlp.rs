@@ -0,0 +1,414 @@
+use std::collections::BTreeMap;
+
+use protocol_parser::json_protocol::parser::JsonProtocol;
+use protos::jaeger_api_v2::{Duration, KeyValue, Span, SpanRefType, Timestamp, ValueType};
+use protos::trace::span::SpanKind;
+use protos::trace::status::StatusCode;
+use tonic::Status;
+
+use super::otlp_to_jaeger::{
+    DURATION_COL_NAME, FLAGS_COL_NAME, LIBRARY_NAME_COL_NAME, LIBRARY_VERSION_COL_NAME,
+    OPERATION_NAME_COL_NAME, PARENT_SPAN_ID_COL_NAME, SERVICE_NAME_COL_NAME, SPAN_ID_COL_NAME,
+    SPAN_KIND_COL_NAME, START_TIME_COL_NAME, STATUS_CODE_COL_NAME, STATUS_MESSAGE_COL_NAME,
+    TRACE_ID_COL_NAME, TRACE_STATE_COL_NAME,
+};
+
+const RESOURCE_ATTRIBUTES_PREFIX: &str = "ResourceSpans/Resource/attributes/";
+const SPAN_PREFIX: &str = "ResourceSpans/ScopeSpans/Span/";
+const END_TIME_COL_NAME: &str = "ResourceSpans/ScopeSpans/Span/end_time_unix_nano";
+
+/// Convert a span written by the Jaeger collector into the columns of a span
+/// written by OTLP, so that the spans of both are read by the Jaeger query API.
+///
+/// The tags that OTLP keeps out of the attributes (`span.kind`, `otel.status_code`,
+/// `otel.library.name`, ...) are mapped back to their OTLP columns, the first
+/// `CHILD_OF` reference is the parent span and the other references are links.
+///
+/// Returns an invalid argument status if the ids or the start time are missing,
+/// or the times are out of the range of nanosecond timestamps.
+pub fn jaeger_span_to_otlp(span: Span) -> Result<JsonProtocol, Status> {
+    if span.trace_id.is_empty() || span.span_id.is_empty() {
+        return Err(Status::invalid_argument(
+            "trace_id and span_id of the span are required",
+        ));
+    }
+    let out_of_range = |name: &str| {
+        Status::invalid_argument(format!(
+            "{} of span {} is out of range",
+            name, span.operation_name
+        ))
+    };
+    let start_time = match span.start_time.as_ref() {
+        Some(start_time) => {
+            timestamp_nanos(start_time).ok_or_else(|| out_of_range("start_time"))?
+        }
+        None => {
+            return Err(Status::invalid_argument(format!(
+                "start_time of span {} is required",
+                span.operation_name
+            )))
+        }
+    };
+    let duration = match span.duration.as_ref() {
+        Some(duration) => duration_nanos(duration)
+            .filter(|d| *d >= 0)
+            .ok_or_else(|| out_of_range("duration"))?,
+        None => 0,
+    };
+    let end_time = start_time
+        .checked_add(duration)
+        .ok_or_else(|| out_of_range("duration"))?;
+    let log_times = span
+        .logs
+        .iter()
+        .map(|log| match log.timestamp.as_ref() {
+            Some(time) => timestamp_nanos(time).ok_or_else(|| out_of_range("log timestamp")),
+            None => Ok(0),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut fields = BTreeMap::new();
+
+    if let Some(process) = span.process {
+        fields.insert(
+            SERVICE_NAME_COL_NAME.to_string(),
+            serde_json::Value::String(process.service_name),
+        );
+        for tag in process.tags {
+            insert_string(
+                &mut fields,
+                RESOURCE_ATTRIBUTES_PREFIX.to_string() + &tag.key,
+                key_value_to_string(&tag),
+            );
+        }
+    }
+
+    insert_string(&mut fields, TRACE_ID_COL_NAME, to_hex(&span.trace_id));
+    insert_string(&mut fields, SPAN_ID_COL_NAME, to_hex(&span.span_id));
+    insert_string(&mut fields, TRACE_STATE_COL_NAME, String::new());
+    insert_string(&mut fields, OPERATION_NAME_COL_NAME, span.operation_name);
+    fields.insert(FLAGS_COL_NAME.to_string(), span.flags.into());
+
+    let mut parent_span_id = None;
+    let mut link_i = 0;
+    for reference in span.references {
+        if parent_span_id.is_none() && reference.ref_type == SpanRefType::ChildOf as i32 {
+            parent_span_id = Some(to_hex(&reference.span_id));
+            continue;
+        }
+        let prefix = format!("{}Link_{}/", SPAN_PREFIX, link_i);
+        insert_string(
+            &mut fields,
+            prefix.clone() + "trace_id",
+            to_hex(&reference.trace_id),
+        );
+        insert_string(&mut fields, prefix + "span_id", to_hex(&reference.span_id));
+        link_i += 1;
+    }
+    insert_string(
+        &mut fields,
+        PARENT_SPAN_ID_COL_NAME,
+        parent_span_id.unwrap_or_default(),
+    );
+
+    fields.insert(START_TIME_COL_NAME.to_string(), start_time.into());
+    fields.insert(END_TIME_COL_NAME.to_string(), end_time.into());
+    fields.insert(DURATION_COL_NAME.to_string(), duration.into());
+
+    let mut span_kind = SpanKind::Unspecified;
+    let mut status_code = StatusCode::Unset;
+    for tag in span.tags {
+        let value = key_value_to_string(&tag);
+        match tag.key.as_str() {
+            "span.kind" => span_kind = to_otlp_span_kind(&value),
+            "otel.status_code" => {
+                status_code = StatusCode::from_str_name(&format!("STATUS_CODE_{}", value))
+                    .unwrap_or(status_code)
+            }
+            "error" => {
+                if value == "true" {
+                    status_code = StatusCode::Error
+                }
+            }
+            "otel.status_description" => insert_string(&mut fields, STATUS_MESSAGE_COL_NAME, value),
+            "otel.library.name" | "otel.scope.name" => {
+                insert_string(&mut fields, LIBRARY_NAME_COL_NAME, value)
+            }
+            "otel.library.version" | "otel.scope.version" => {
+                insert_string(&mut fields, LIBRARY_VERSION_COL_NAME, value)
+            }
+            _ => insert_string(
+                &mut fields,
+                format!("{}attributes/{}", SPAN_PREFIX, tag.key),
+                value,
+            ),
+        }
+    }
+    insert_string(
+        &mut fields,
+        SPAN_KIND_COL_NAME,
+        span_kind.as_str_name().to_string(),
+    );
+    insert_string(
+        &mut fields,
+        STATUS_CODE_COL_NAME,
+        status_code.as_str_name().to_string(),
+    );
+
+    for (i, (log, time)) in span.logs.into_iter().zip(log_times).enumerate() {
+        let prefix = format!("{}Event_{}/", SPAN_PREFIX, i);
+        fields.insert(prefix.clone() + "time_unix_nano", time.into());
+        let mut name = String::new();
+        for field in log.fields {
+            let value = key_value_to_string(&field);
+            if field.key == "event" {
+                name = value;
+            } else {
+                insert_string(
+                    &mut fields,
+                    prefix.clone() + "attributes/" + &field.key,
+                    value,
+                );
+            }
+        }
+        insert_string(&mut fields, prefix + "name", name);
+    }
+
+    Ok(JsonProtocol::TraceServiceReq(fields))
+}
+
+fn insert_string(
+    fields: &mut BTreeMap<String, serde_json::Value>,
+    key: impl Into<String>,
+    value: String,
+) {
+    fields.insert(key.into(), serde_json::Value::String(value));
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .fold(String::new(), |acc, byte| acc + &format!("{:02x}", byte))
+}
+
+/// None if the timestamp is out of the range of nanosecond timestamps.
+pub(crate) fn timestamp_nanos(ts: &Timestamp) -> Option<i64> {
+    ts.seconds
+        .checked_mul(1_000_000_000)?
+        .checked_add(ts.nanos as i64)
+}
+
+fn duration_nanos(duration: &Duration) -> Option<i64> {
+    duration
+        .seconds
+        .checked_mul(1_000_000_000)?
+        .checked_add(duration.nanos as i64)
+}
+
+fn key_value_to_string(kv: &KeyValue) -> String {
+    match ValueType::from_i32(kv.v_type) {
+        Some(ValueType::Bool) => kv.v_bool.to_string(),
+        Some(ValueType::Int64) => kv.v_int64.to_string(),
+        Some(ValueType::Float64) => kv.v_float64.to_string(),
+        Some(ValueType::Binary) => to_hex(&kv.v_binary),
+        Some(ValueType::String) | None => kv.v_str.clone(),
+    }
+}
+
+fn to_otlp_span_kind(span_kind: &str) -> SpanKind {
+    match span_kind {
+        "internal" => SpanKind::Internal,
+        "server" => SpanKind::Server,
+        "client" => SpanKind::Client,
+        "producer" => SpanKind::Producer,
+        "consumer" => SpanKind::Consumer,
+        _ => SpanKind::Unspecified,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use protos::jaeger_api_v2::{
+        Duration, KeyValue, Log, Process, Span, SpanRef, SpanRefType, Timestamp, ValueType,
+    };
+
+    use super::jaeger_span_to_otlp;
+    use crate::opentelemetry::otlp_to_jaeger::{
+        DURATION_COL_NAME, PARENT_SPAN_ID_COL_NAME, SERVICE_NAME_COL_NAME, SPAN_KIND_COL_NAME,
+        START_TIME_COL_NAME, STATUS_CODE_COL_NAME, TRACE_ID_COL_NAME,
+    };
+
+    fn string_tag(key: &str, value: &str) -> KeyValue {
+        KeyValue {
+            key: key.to_string(),
+            v_type: ValueType::String as i32,
+            v_str: value.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_jaeger_span_to_otlp() {
+        let span = Span {
+            trace_id: vec![0, 0xab],
+            span_id: vec![2],
+            operation_name: "GET /ping".to_string(),
+            references: vec![
+                SpanRef {
+                    trace_id: vec![0, 0xab],
+                    span_id: vec![1],
+                    ref_type: SpanRefType::ChildOf as i32,
+                },
+                SpanRef {
+                    trace_id: vec![0, 0xcd],
+                    span_id: vec![3],
+                    ref_type: SpanRefType::FollowsFrom as i32,
+                },
+            ],
+            start_time: Some(Timestamp {
+                seconds: 1,
+                nanos: 500,
+            }),
+            duration: Some(Duration {
+                seconds: 0,
+                nanos: 1_000,
+            }),
+            tags: vec![
+                string_tag("span.kind", "server"),
+                KeyValue {
+                    key: "error".to_string(),
+                    v_type: ValueType::Bool as i32,
+                    v_bool: true,
+                    ..Default::default()
+                },
+                KeyValue {
+                    key: "http.status_code".to_string(),
+                    v_type: ValueType::Int64 as i32,
+                    v_int64: 500,
+                    ..Default::default()
+                },
+            ],
+            logs: vec![Log {
+                timestamp: Some(Timestamp {
+                    seconds: 1,
+                    nanos: 700,
+                }),
+                fields: vec![
+                    string_tag("event", "exception"),
+                    string_tag("message", "timeout"),
+                ],
+            }],
+            process: Some(Process {
+                service_name: "api".to_string(),
+                tags: vec![string_tag("host.name", "node1")],
+            }),
+            ..Default::default()
+        };
+
+        let span = jaeger_span_to_otlp(span).unwrap();
+        let fields = span.get_fields();
+        let get = |key: &str| fields.get(key).unwrap().clone();
+        assert_eq!(get(TRACE_ID_COL_NAME), "00ab");
+        assert_eq!(get(PARENT_SPAN_ID_COL_NAME), "01");
+        assert_eq!(get(SERVICE_NAME_COL_NAME), "api");
+        assert_eq!(get(SPAN_KIND_COL_NAME), "SPAN_KIND_SERVER");
+        assert_eq!(get(STATUS_CODE_COL_NAME), "STATUS_CODE_ERROR");
+        assert_eq!(get(START_TIME_COL_NAME), 1_000_000_500);
+        assert_eq!(get(DURATION_COL_NAME), 1_000);
+        assert_eq!(
+            get("ResourceSpans/ScopeSpans/Span/end_time_unix_nano"),
+            1_000_001_500
+        );
+        assert_eq!(get("ResourceSpans/Resource/attributes/host.name"), "node1");
+        assert_eq!(
+            get("ResourceSpans/ScopeSpans/Span/attributes/http.status_code"),
+            "500"
+        );
+        assert_eq!(get("ResourceSpans/ScopeSpans/Span/Link_0/span_id"), "03");
+        assert_eq!(
+            get("ResourceSpans/ScopeSpans/Span/Event_0/name"),
+            "exception"
+        );
+        assert_eq!(
+            get("ResourceSpans/ScopeSpans/Span/Event_0/attributes/message"),
+            "timeout"
+        );
+        assert!(!fields.contains_key("ResourceSpans/ScopeSpans/Span/attributes/error"));
+    }
+
+    #[test]
+    fn test_malformed_jaeger_span_to_otlp() {
+        let valid_span = || Span {
+            trace_id: vec![0, 0xab],
+            span_id: vec![2],
+            operation_name: "GET /ping".to_string(),
+            start_time: Some(Timestamp {
+                seconds: 1,
+                nanos: 0,
+            }),
+            duration: Some(Duration {
+                seconds: 1,
+                nanos: 0,
+            }),
+            ..Default::default()
+        };
+        assert!(jaeger_span_to_otlp(valid_span()).is_ok());
+
+        let mut malformed_spans = vec![];
+        // Missing ids
+        malformed_spans.push(Span {
+            trace_id: vec![],
+            ..valid_span()
+        });
+        malformed_spans.push(Span {
+            span_id: vec![],
+            ..valid_span()
+        });
+        // Missing start time
+        malformed_spans.push(Span {
+            start_time: None,
+            ..valid_span()
+        });
+        // Times out of range
+        malformed_spans.push(Span {
+            start_time: Some(Timestamp {
+                seconds: i64::MAX,
+                nanos: 0,
+            }),
+            ..valid_span()
+        });
+        malformed_spans.push(Span {
+            start_time: Some(Timestamp {
+                seconds: i64::MAX / 1_000_000_000,
+                nanos: 0,
+            }),
+            duration: Some(Duration {
+                seconds: 100,
+                nanos: 0,
+            }),
+            ..valid_span()
+        });
+        malformed_spans.push(Span {
+            duration: Some(Duration {
+                seconds: -1,
+                nanos: 0,
+            }),
+            ..valid_span()
+        });
+        malformed_spans.push(Span {
+            logs: vec![Log {
+                timestamp: Some(Timestamp {
+                    seconds: i64::MIN,
+                    nanos: 0,
+                }),
+                fields: vec![string_tag("event", "exception")],
+            }],
+            ..valid_span()
+        });
+
+        for span in malformed_spans {
+            let status = jaeger_span_to_otlp(span.clone()).unwrap_err();
+            assert_eq!(status.code(), tonic::Code::InvalidArgument, "{span:?}");
+        }
+    }
+}
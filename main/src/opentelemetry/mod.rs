@@ -1,2 +1,3 @@
 pub mod jaeger_model;
+pub mod jaeger_to_otlp;
 pub mod otlp_to_jaeger;
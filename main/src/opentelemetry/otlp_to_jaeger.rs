@@ -4,13 +4,17 @@ use std::sync::Arc;
 use coordinator::service::CoordinatorRef;
 use coordinator::SendableCoordinatorRecordBatchStream;
 use datafusion::arrow::array::{Array, Float64Array, StringArray, TimestampNanosecondArray};
+use datafusion::arrow::datatypes::{DataType, Field, Schema};
 use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::common::cast::{as_int64_array, as_string_array};
+use datafusion::datasource::MemTable;
 use datafusion::execution::context::SessionState;
 use datafusion::execution::runtime_env::RuntimeEnv;
 use datafusion::logical_expr::{col, lit};
-use datafusion::prelude::SessionConfig;
+use datafusion::prelude::{SessionConfig, SessionContext};
 use datafusion::scalar::ScalarValue;
 use datafusion::sql::sqlparser::parser::ParserError;
+use futures::TryStreamExt;
 use http_protocol::parameter::FindTracesParam;
 use models::predicate::domain::Predicate;
 use models::schema::table_schema::TableSchema;
@@ -22,11 +26,11 @@ use protos::trace::status::StatusCode;
 use query::data_source::split::tskv::TableLayoutHandle;
 use query::data_source::split::SplitManager;
 use snafu::ResultExt;
-use spi::{CoordinatorSnafu, ModelsSnafu, QueryError};
+use spi::{ArrowSnafu, CoordinatorSnafu, DatafusionSnafu, ModelsSnafu, QueryError};
 use tonic::Status;
 use tskv::reader::QueryOption;
 
-use super::jaeger_model::{KeyValue, Log, Process, Reference, ReferenceType, Span};
+use super::jaeger_model::{DependencyLink, KeyValue, Log, Process, Reference, ReferenceType, Span};
 
 pub const TRACE_ID_COL_NAME: &str = "ResourceSpans/ScopeSpans/Span/trace_id";
 pub const SPAN_ID_COL_NAME: &str = "ResourceSpans/ScopeSpans/Span/span_id";
@@ -49,6 +53,32 @@ pub const SPAN_DROPPED_EVENTS_COUNT_COL_NAME: &str =
 pub const SPAN_DROPPED_LINKS_COUNT_COL_NAME: &str =
     "ResourceSpans/ScopeSpans/Span/dropped_links_count";
 pub const TRACE_STATE_COL_NAME: &str = "ResourceSpans/ScopeSpans/Span/trace_state";
+pub const START_TIME_COL_NAME: &str = "ResourceSpans/ScopeSpans/Span/start_time_unix_nano";
+
+/// Columns of a span that are stored as tags.
+pub const TAG_COL_NAMES: [&str; 9] = [
+    LIBRARY_NAME_COL_NAME,
+    LIBRARY_VERSION_COL_NAME,
+    STATUS_CODE_COL_NAME,
+    OPERATION_NAME_COL_NAME,
+    PARENT_SPAN_ID_COL_NAME,
+    SPAN_ID_COL_NAME,
+    TRACE_ID_COL_NAME,
+    TRACE_STATE_COL_NAME,
+    SERVICE_NAME_COL_NAME,
+];
+
+/// The dependencies are computed from the spans of the last day by default.
+pub const DEFAULT_DEPENDENCIES_LOOKBACK_NANOS: i64 = 24 * 60 * 60 * 1_000_000_000;
+
+/// Count the calls between two services, a call is a span whose parent span
+/// belongs to another service.
+const DEPENDENCIES_SQL: &str = "SELECT parent.service_name AS parent, \
+    child.service_name AS child, count(*) AS call_count \
+    FROM spans AS child JOIN spans AS parent \
+    ON child.trace_id = parent.trace_id AND child.parent_span_id = parent.span_id \
+    WHERE child.service_name <> parent.service_name \
+    GROUP BY parent.service_name, child.service_name";
 
 pub enum FilterType {
     GetTraceID(String),
@@ -56,6 +86,8 @@ pub enum FilterType {
     GetOperation(String, String),
     FindTraces(Option<FindTracesParam>),
     FindTraceIDs(Option<FindTracesParam>),
+    /// Spans started in the time range `[start, end]` in nanoseconds.
+    GetDependencies(i64, i64),
 }
 
 pub struct OtlpToJaeger {}
@@ -258,6 +290,37 @@ impl OtlpToJaeger {
                     };
                     (tskv_table_schema, filter_expr, limit)
                 }
+                FilterType::GetDependencies(start, end) => {
+                    let time_col = col(format!("{}.\"time\"", table));
+                    let filter_expr = time_col
+                        .clone()
+                        .gt_eq(lit(ScalarValue::TimestampNanosecond(Some(*start), None)))
+                        .and(
+                            time_col.lt_eq(lit(ScalarValue::TimestampNanosecond(Some(*end), None))),
+                        );
+
+                    (
+                        Arc::new(TskvTableSchema::new(
+                            tenant,
+                            db,
+                            table,
+                            tskv_table_schema
+                                .columns()
+                                .iter()
+                                .filter(|col| {
+                                    col.name == TRACE_ID_COL_NAME
+                                        || col.name == SPAN_ID_COL_NAME
+                                        || col.name == PARENT_SPAN_ID_COL_NAME
+                                        || col.name == SERVICE_NAME_COL_NAME
+                                        || col.name == "time"
+                                })
+                                .cloned()
+                                .collect(),
+                        )),
+                        Some(filter_expr),
+                        None,
+                    )
+                }
             };
 
             let schema = tskv_table_schema.to_arrow_schema();
@@ -303,6 +366,83 @@ impl OtlpToJaeger {
         Ok(iterators)
     }
 
+    /// Compute the dependencies between services from the parent-child relationship
+    /// of the spans started in `[start, end]`.
+    pub async fn get_dependencies(
+        coord: CoordinatorRef,
+        tenant: String,
+        db: String,
+        table: String,
+        start: i64,
+        end: i64,
+    ) -> Result<Vec<DependencyLink>, QueryError> {
+        let iterators = Self::get_tskv_iterator(
+            coord,
+            FilterType::GetDependencies(start, end),
+            tenant,
+            db,
+            table,
+        )
+        .await?;
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("trace_id", DataType::Utf8, true),
+            Field::new("span_id", DataType::Utf8, true),
+            Field::new("parent_span_id", DataType::Utf8, true),
+            Field::new("service_name", DataType::Utf8, true),
+        ]));
+        let mut batches = Vec::new();
+        for mut iter in iterators {
+            while let Some(batch) = iter.try_next().await.context(CoordinatorSnafu)? {
+                let columns = [
+                    TRACE_ID_COL_NAME,
+                    SPAN_ID_COL_NAME,
+                    PARENT_SPAN_ID_COL_NAME,
+                    SERVICE_NAME_COL_NAME,
+                ]
+                .iter()
+                .map(|name| {
+                    batch
+                        .column_by_name(name)
+                        .cloned()
+                        .ok_or_else(|| QueryError::ColumnNotFound {
+                            col: name.to_string(),
+                        })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+                batches.push(RecordBatch::try_new(schema.clone(), columns).context(ArrowSnafu)?);
+            }
+        }
+
+        let ctx = SessionContext::new();
+        let spans = MemTable::try_new(schema, vec![batches]).context(DatafusionSnafu)?;
+        ctx.register_table("spans", Arc::new(spans))
+            .context(DatafusionSnafu)?;
+        let result = ctx
+            .sql(DEPENDENCIES_SQL)
+            .await
+            .context(DatafusionSnafu)?
+            .collect()
+            .await
+            .context(DatafusionSnafu)?;
+
+        let mut dependencies = Vec::new();
+        for batch in result {
+            let parent = as_string_array(batch.column(0)).context(DatafusionSnafu)?;
+            let child = as_string_array(batch.column(1)).context(DatafusionSnafu)?;
+            let call_count = as_int64_array(batch.column(2)).context(DatafusionSnafu)?;
+            for i in 0..batch.num_rows() {
+                dependencies.push(DependencyLink {
+                    parent: parent.value(i).to_string(),
+                    child: child.value(i).to_string(),
+                    call_count: call_count.value(i) as u64,
+                });
+            }
+        }
+
+        Ok(dependencies)
+    }
+
     fn normal_decode_kv(col_name: &str, value: AnyValue) -> KeyValue {
         if let Some(Value::StringValue(v_str)) = value.value {
             KeyValue {
@@ -4,10 +4,16 @@ use std::sync::Arc;
 use config::tskv::TLSConfig;
 use coordinator::service::CoordinatorRef;
 use metrics::metric_register::MetricsRegister;
+use protos::jaeger_storage_v1::dependencies_reader_plugin_server::DependenciesReaderPluginServer;
+use protos::jaeger_storage_v1::plugin_capabilities_server::PluginCapabilitiesServer;
+use protos::jaeger_storage_v1::span_writer_plugin_server::SpanWriterPluginServer;
 use protos::kv_service::tskv_service_server::TskvServiceServer;
+use protos::logs_service::logs_service_server::LogsServiceServer;
+use protos::metrics_service::metrics_service_server::MetricsServiceServer;
 use protos::raft_service::raft_service_server::RaftServiceServer;
 use protos::DEFAULT_GRPC_SERVER_MESSAGE_LEN;
 use replication::network_grpc::RaftCBServer;
+use spi::server::dbms::DBMSRef;
 use tokio::runtime::Runtime;
 use tokio::sync::oneshot;
 use tonic::codec::CompressionEncoding;
@@ -15,6 +21,8 @@ use tonic::transport::{Identity, Server, ServerTlsConfig};
 use trace::http::tower_layer::TraceLayer;
 use tskv::EngineRef;
 
+use crate::rpc::jaeger::JaegerStorageServiceImpl;
+use crate::rpc::otlp::OtlpServiceImpl;
 use crate::rpc::tskv::TskvServiceImpl;
 use crate::server::ServiceHandle;
 use crate::spi::service::Service;
@@ -25,6 +33,7 @@ pub struct GrpcService {
     runtime: Arc<Runtime>,
     kv_inst: EngineRef,
     coord: CoordinatorRef,
+    dbms: DBMSRef,
     tls_config: Option<TLSConfig>,
     metrics_register: Arc<MetricsRegister>,
     auto_generate_span: bool,
@@ -37,6 +46,7 @@ impl GrpcService {
         runtime: Arc<Runtime>,
         kv_inst: EngineRef,
         coord: CoordinatorRef,
+        dbms: DBMSRef,
         addr: SocketAddr,
         tls_config: Option<TLSConfig>,
         metrics_register: Arc<MetricsRegister>,
@@ -48,6 +58,7 @@ impl GrpcService {
             runtime,
             kv_inst,
             coord,
+            dbms,
             tls_config,
             metrics_register,
             auto_generate_span,
@@ -95,6 +106,27 @@ impl Service for GrpcService {
         let mut raft_grpc_service = RaftServiceServer::new(RaftCBServer::new(multi_raft))
             .max_decoding_message_size(DEFAULT_GRPC_SERVER_MESSAGE_LEN);
 
+        let otlp_service_impl = OtlpServiceImpl {
+            coord: self.coord.clone(),
+            dbms: self.dbms.clone(),
+        };
+        let mut otlp_metrics_grpc_service = MetricsServiceServer::new(otlp_service_impl.clone())
+            .max_decoding_message_size(DEFAULT_GRPC_SERVER_MESSAGE_LEN);
+        let mut otlp_logs_grpc_service = LogsServiceServer::new(otlp_service_impl)
+            .max_decoding_message_size(DEFAULT_GRPC_SERVER_MESSAGE_LEN);
+
+        let jaeger_service_impl = JaegerStorageServiceImpl {
+            coord: self.coord.clone(),
+            dbms: self.dbms.clone(),
+        };
+        let mut jaeger_span_writer_grpc_service =
+            SpanWriterPluginServer::new(jaeger_service_impl.clone())
+                .max_decoding_message_size(DEFAULT_GRPC_SERVER_MESSAGE_LEN);
+        let mut jaeger_dependencies_grpc_service =
+            DependenciesReaderPluginServer::new(jaeger_service_impl.clone())
+                .max_decoding_message_size(DEFAULT_GRPC_SERVER_MESSAGE_LEN);
+        let jaeger_capabilities_grpc_service = PluginCapabilitiesServer::new(jaeger_service_impl);
+
         if self.enable_gzip {
             tskv_grpc_service = tskv_grpc_service
                 .accept_compressed(CompressionEncoding::Gzip)
@@ -103,13 +135,34 @@ impl Service for GrpcService {
             raft_grpc_service = raft_grpc_service
                 .accept_compressed(CompressionEncoding::Gzip)
                 .send_compressed(CompressionEncoding::Gzip);
+
+            otlp_metrics_grpc_service = otlp_metrics_grpc_service
+                .accept_compressed(CompressionEncoding::Gzip)
+                .send_compressed(CompressionEncoding::Gzip);
+
+            otlp_logs_grpc_service = otlp_logs_grpc_service
+                .accept_compressed(CompressionEncoding::Gzip)
+                .send_compressed(CompressionEncoding::Gzip);
+
+            jaeger_span_writer_grpc_service = jaeger_span_writer_grpc_service
+                .accept_compressed(CompressionEncoding::Gzip)
+                .send_compressed(CompressionEncoding::Gzip);
+
+            jaeger_dependencies_grpc_service = jaeger_dependencies_grpc_service
+                .accept_compressed(CompressionEncoding::Gzip)
+                .send_compressed(CompressionEncoding::Gzip);
         }
 
         let mut grpc_builder =
             build_grpc_server!(&self.tls_config, self.auto_generate_span, "grpc");
         let grpc_router = grpc_builder
             .add_service(tskv_grpc_service)
-            .add_service(raft_grpc_service);
+            .add_service(raft_grpc_service)
+            .add_service(otlp_metrics_grpc_service)
+            .add_service(otlp_logs_grpc_service)
+            .add_service(jaeger_span_writer_grpc_service)
+            .add_service(jaeger_dependencies_grpc_service)
+            .add_service(jaeger_capabilities_grpc_service);
         let server = grpc_router.serve_with_shutdown(self.addr, async {
             rx.await.ok();
             info!("grpc server graceful shutdown!");
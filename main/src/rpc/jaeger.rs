@@ -0,0 +1,139 @@
+use coordinator::service::CoordinatorRef;
+use http_protocol::header::TABLE;
+use models::utils::now_timestamp_nanos;
+use prost::Message;
+use protocol_parser::json_protocol::parser::parse_to_line;
+use protos::jaeger_api_v2::{DependencyLink, Timestamp};
+use protos::jaeger_storage_v1::dependencies_reader_plugin_server::DependenciesReaderPlugin;
+use protos::jaeger_storage_v1::plugin_capabilities_server::PluginCapabilities;
+use protos::jaeger_storage_v1::span_writer_plugin_server::SpanWriterPlugin;
+use protos::jaeger_storage_v1::{
+    CapabilitiesRequest, CapabilitiesResponse, CloseWriterRequest, CloseWriterResponse,
+    GetDependenciesRequest, GetDependenciesResponse, WriteSpanRequest, WriteSpanResponse,
+};
+use spi::server::dbms::DBMSRef;
+use tonic::{Request, Response, Status};
+use trace::error;
+
+use crate::opentelemetry::jaeger_to_otlp::{jaeger_span_to_otlp, timestamp_nanos};
+use crate::opentelemetry::otlp_to_jaeger::{
+    OtlpToJaeger, DEFAULT_DEPENDENCIES_LOOKBACK_NANOS, START_TIME_COL_NAME, TAG_COL_NAMES,
+};
+use crate::rpc::utils::{get_span, metadata_value, read_context, write_context, write_lines};
+
+/// Table of the spans if the request does not specify one.
+pub const DEFAULT_JAEGER_SPAN_TABLE: &str = "jaeger_spans";
+
+/// Jaeger remote storage: the spans written by the Jaeger collector are stored in
+/// the same layout as the OTLP traces, and are read by the Jaeger query HTTP API.
+///
+/// The spans are written to the table in the `table` metadata, or to
+/// `jaeger_spans` if it is absent.
+#[derive(Clone)]
+pub struct JaegerStorageServiceImpl {
+    pub coord: CoordinatorRef,
+    pub dbms: DBMSRef,
+}
+
+#[async_trait::async_trait]
+impl SpanWriterPlugin for JaegerStorageServiceImpl {
+    async fn write_span(
+        &self,
+        request: Request<WriteSpanRequest>,
+    ) -> Result<Response<WriteSpanResponse>, Status> {
+        let span = get_span(&request, "grpc jaeger span write");
+        let ctx = write_context(
+            &self.coord,
+            &self.dbms,
+            request.metadata(),
+            request.get_ref().encoded_len(),
+        )
+        .await?;
+        let table = metadata_value(request.metadata(), TABLE)
+            .unwrap_or_else(|| DEFAULT_JAEGER_SPAN_TABLE.to_string());
+
+        let jaeger_span = request
+            .into_inner()
+            .span
+            .ok_or_else(|| Status::invalid_argument("span is required"))?;
+        let otlp_span = jaeger_span_to_otlp(jaeger_span)?;
+        let tag_columns = TAG_COL_NAMES.join(",");
+        let line = parse_to_line(&otlp_span, &table, START_TIME_COL_NAME, &tag_columns)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        write_lines(&self.coord, &ctx, vec![line], &span).await?;
+
+        Ok(Response::new(WriteSpanResponse {}))
+    }
+
+    async fn close(
+        &self,
+        _request: Request<CloseWriterRequest>,
+    ) -> Result<Response<CloseWriterResponse>, Status> {
+        Ok(Response::new(CloseWriterResponse {}))
+    }
+}
+
+#[async_trait::async_trait]
+impl DependenciesReaderPlugin for JaegerStorageServiceImpl {
+    async fn get_dependencies(
+        &self,
+        request: Request<GetDependenciesRequest>,
+    ) -> Result<Response<GetDependenciesResponse>, Status> {
+        let ctx = read_context(&self.coord, &self.dbms, request.metadata()).await?;
+        let table = metadata_value(request.metadata(), TABLE)
+            .unwrap_or_else(|| DEFAULT_JAEGER_SPAN_TABLE.to_string());
+
+        let request = request.into_inner();
+        let request_time = |time: Option<&Timestamp>, name: &str| {
+            time.map(|t| {
+                timestamp_nanos(t)
+                    .ok_or_else(|| Status::invalid_argument(format!("{name} is out of range")))
+            })
+            .transpose()
+        };
+        let end = request_time(request.end_time.as_ref(), "end_time")?
+            .unwrap_or_else(now_timestamp_nanos);
+        let start = request_time(request.start_time.as_ref(), "start_time")?
+            .unwrap_or(end.saturating_sub(DEFAULT_DEPENDENCIES_LOOKBACK_NANOS));
+
+        let dependencies = OtlpToJaeger::get_dependencies(
+            self.coord.clone(),
+            ctx.tenant().to_string(),
+            ctx.database().to_string(),
+            table,
+            start,
+            end,
+        )
+        .await
+        .map_err(|e| {
+            error!("Failed to get dependencies, err: {:?}", e);
+            Status::internal(e.to_string())
+        })?;
+
+        Ok(Response::new(GetDependenciesResponse {
+            dependencies: dependencies
+                .into_iter()
+                .map(|link| DependencyLink {
+                    parent: link.parent,
+                    child: link.child,
+                    call_count: link.call_count,
+                    source: String::new(),
+                })
+                .collect(),
+        }))
+    }
+}
+
+#[async_trait::async_trait]
+impl PluginCapabilities for JaegerStorageServiceImpl {
+    async fn capabilities(
+        &self,
+        _request: Request<CapabilitiesRequest>,
+    ) -> Result<Response<CapabilitiesResponse>, Status> {
+        Ok(Response::new(CapabilitiesResponse {
+            archive_span_reader: false,
+            archive_span_writer: false,
+            streaming_span_writer: false,
+        }))
+    }
+}
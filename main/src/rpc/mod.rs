@@ -1,2 +1,5 @@
 pub mod grpc_service;
+pub mod jaeger;
+pub mod otlp;
 pub mod tskv;
+pub mod utils;
@@ -0,0 +1,78 @@
+use coordinator::service::CoordinatorRef;
+use http_protocol::header::TABLE;
+use models::utils::now_timestamp_nanos;
+use prost::Message;
+use protocol_parser::otlp::logs::{otlp_logs_to_json, DEFAULT_OTLP_LOG_TABLE};
+use protocol_parser::otlp::metrics::otlp_metrics_to_lines;
+use protos::logs_service::logs_service_server::LogsService;
+use protos::logs_service::{ExportLogsServiceRequest, ExportLogsServiceResponse};
+use protos::metrics_service::metrics_service_server::MetricsService;
+use protos::metrics_service::{ExportMetricsServiceRequest, ExportMetricsServiceResponse};
+use spi::server::dbms::DBMSRef;
+use tonic::{Request, Response, Status};
+
+use crate::rpc::utils::{get_span, metadata_value, write_context, write_lines};
+
+/// OTLP/gRPC receiver of the metrics and logs signals.
+///
+/// The request is authenticated in the same way as an HTTP write, by the
+/// `authorization`, `tenant` and `db` metadata. Log records are written to the
+/// table in the `table` metadata, or to `otlp_logs` if it is absent.
+#[derive(Clone)]
+pub struct OtlpServiceImpl {
+    pub coord: CoordinatorRef,
+    pub dbms: DBMSRef,
+}
+
+#[async_trait::async_trait]
+impl MetricsService for OtlpServiceImpl {
+    async fn export(
+        &self,
+        request: Request<ExportMetricsServiceRequest>,
+    ) -> Result<Response<ExportMetricsServiceResponse>, Status> {
+        let span = get_span(&request, "grpc otlp metrics write");
+        let ctx = write_context(
+            &self.coord,
+            &self.dbms,
+            request.metadata(),
+            request.get_ref().encoded_len(),
+        )
+        .await?;
+
+        let lines = otlp_metrics_to_lines(request.into_inner(), now_timestamp_nanos());
+        write_lines(&self.coord, &ctx, lines, &span).await?;
+
+        Ok(Response::new(ExportMetricsServiceResponse {
+            partial_success: None,
+        }))
+    }
+}
+
+#[async_trait::async_trait]
+impl LogsService for OtlpServiceImpl {
+    async fn export(
+        &self,
+        request: Request<ExportLogsServiceRequest>,
+    ) -> Result<Response<ExportLogsServiceResponse>, Status> {
+        let span = get_span(&request, "grpc otlp logs write");
+        let ctx = write_context(
+            &self.coord,
+            &self.dbms,
+            request.metadata(),
+            request.get_ref().encoded_len(),
+        )
+        .await?;
+        let table = metadata_value(request.metadata(), TABLE)
+            .unwrap_or_else(|| DEFAULT_OTLP_LOG_TABLE.to_string());
+
+        let otlp_logs = otlp_logs_to_json(request.into_inner());
+        let lines = otlp_logs
+            .to_lines(&table)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        write_lines(&self.coord, &ctx, lines, &span).await?;
+
+        Ok(Response::new(ExportLogsServiceResponse {
+            partial_success: None,
+        }))
+    }
+}
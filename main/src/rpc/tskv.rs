@@ -1,32 +1,37 @@
+use std::collections::{HashMap, HashSet};
 use std::pin::Pin;
 use std::sync::Arc;
 
 use coordinator::errors::{
-    encode_grpc_response, ArrowSnafu, CommonSnafu, CoordinatorResult, TskvSnafu,
+    encode_grpc_response, ArrowSnafu, CommonSnafu, CoordinatorResult, ModelSnafu, TskvSnafu,
 };
 use coordinator::service::CoordinatorRef;
 use futures::{Stream, TryStreamExt};
 use meta::model::MetaRef;
 use metrics::metric_register::MetricsRegister;
-use models::meta_data::VnodeInfo;
+use models::meta_data::{ReplicationSetId, VnodeId, VnodeInfo};
 use models::predicate::domain::{self, PushedAggregateFunction, QueryArgs, QueryExpr};
 use models::record_batch_encode;
 use protos::kv_service::tskv_service_server::TskvService;
 use protos::kv_service::*;
 use protos::models::{PingBody, PingBodyBuilder};
+use protos::models_helper::to_prost_bytes;
 use snafu::ResultExt;
 use tokio::io::AsyncReadExt;
 use tokio::runtime::Runtime;
-use tokio::sync::mpsc;
+use tokio::sync::{broadcast, mpsc};
 use tokio_stream::wrappers::ReceiverStream;
 use tonic::{Extensions, Request, Response, Status};
 use trace::span_ext::SpanExt;
 use trace::{debug, error, info, Span, SpanContext};
+use tskv::backup::{BackupStorage, VnodeBackupManifest};
+use tskv::cdc::{write_data_to_record_batches, ChangeEvent};
 use tskv::error::TskvResult;
 use tskv::reader::query_executor::QueryExecutor;
 use tskv::reader::serialize::TonicRecordBatchEncoder;
 use tskv::reader::{QueryOption, SendableTskvRecordBatchStream};
 use tskv::EngineRef;
+use utils::precision::Precision;
 
 type ResponseStream<T> = Pin<Box<dyn Stream<Item = Result<T, tonic::Status>> + Send>>;
 
@@ -84,6 +89,26 @@ impl TskvServiceImpl {
                 Ok(data)
             }
 
+            admin_command::Command::FetchSeriesCardinality(req) => {
+                let record = self
+                    .kv_inst
+                    .get_series_cardinality(req.vnode_id, req.table.as_deref())
+                    .await
+                    .context(TskvSnafu)?;
+                let data = record_batch_encode(&record).context(ArrowSnafu)?;
+                Ok(data)
+            }
+
+            admin_command::Command::FetchTableStatistics(req) => {
+                let statistics = self
+                    .kv_inst
+                    .get_table_statistics(req.vnode_id, &req.table)
+                    .await
+                    .context(TskvSnafu)?;
+                let data = statistics.encode().context(ModelSnafu)?;
+                Ok(data)
+            }
+
             admin_command::Command::AddRaftFollower(command) => {
                 self.coord
                     .raft_manager()
@@ -151,6 +176,200 @@ impl TskvServiceImpl {
                     .await?;
                 Ok(vec![])
             }
+            admin_command::Command::BackupVnode(command) => {
+                let storage =
+                    BackupStorage::new(&command.url, &command.options).context(TskvSnafu)?;
+                self.kv_inst
+                    .backup_vnode(command.vnode_id, &storage, command.backup_id)
+                    .await
+                    .context(TskvSnafu)?;
+                Ok(vec![])
+            }
+            admin_command::Command::RestoreVnode(command) => {
+                let storage =
+                    BackupStorage::new(&command.url, &command.options).context(TskvSnafu)?;
+                let manifest = storage
+                    .vnode_manifest(command.backup_vnode_id, command.backup_id)
+                    .await
+                    .context(TskvSnafu)?
+                    .ok_or_else(|| {
+                        CommonSnafu {
+                            msg: format!(
+                                "Backup {} of vnode {} not found",
+                                command.backup_id, command.backup_vnode_id
+                            ),
+                        }
+                        .build()
+                    })?;
+                self.kv_inst
+                    .restore_vnode(
+                        tenant,
+                        &command.db_name,
+                        command.vnode_id,
+                        &storage,
+                        &manifest,
+                    )
+                    .await
+                    .context(TskvSnafu)?;
+                if command.replay_wal {
+                    self.replay_backup_wal(tenant, command, &storage, &manifest)
+                        .await?;
+                }
+                Ok(vec![])
+            }
+        }
+    }
+
+    /// Write the commands in the shipped WAL segments of the backup to the replication set
+    /// by raft, so that all vnodes of the replication set apply them.
+    async fn replay_backup_wal(
+        &self,
+        tenant: &str,
+        command: &RestoreVnodeRequest,
+        storage: &BackupStorage,
+        manifest: &VnodeBackupManifest,
+    ) -> CoordinatorResult<()> {
+        // The latest manifest contains all WAL segments shipped by now.
+        let wal_manifest = storage
+            .latest_vnode_manifest(command.backup_vnode_id)
+            .await
+            .context(TskvSnafu)?
+            .unwrap_or_else(|| manifest.clone());
+        let dir = self
+            .kv_inst
+            .get_storage_options()
+            .path()
+            .join(format!("restore_{}", command.vnode_id));
+        let replica = coordinator::get_replica_by_meta(
+            self.coord.meta_manager(),
+            tenant,
+            &command.db_name,
+            command.replica_id,
+        )
+        .await?;
+
+        let mut reader =
+            storage.wal_reader(&wal_manifest, manifest.last_seq(), command.as_of, &dir);
+        let mut replayed = 0_usize;
+        let result = loop {
+            let mut request = match reader.next_command().await {
+                Ok(Some(request)) => request,
+                Ok(None) => break Ok(()),
+                Err(e) => break Err(e).context(TskvSnafu),
+            };
+            request.tenant = tenant.to_string();
+            request.db_name = command.db_name.clone();
+            request.replica_id = command.replica_id;
+            match request.command.as_mut() {
+                Some(raft_write_command::Command::DropTable(cmd)) => {
+                    cmd.db = command.db_name.clone();
+                }
+                Some(raft_write_command::Command::DropColumn(cmd)) => {
+                    cmd.db = command.db_name.clone();
+                }
+                Some(raft_write_command::Command::UpdateTags(cmd)) => {
+                    cmd.db = command.db_name.clone();
+                }
+                Some(raft_write_command::Command::DeleteFromTable(cmd)) => {
+                    cmd.tenant = tenant.to_string();
+                    cmd.database = command.db_name.clone();
+                    cmd.vnode_id = command.vnode_id;
+                }
+                Some(raft_write_command::Command::WriteData(_)) | None => {}
+            }
+            if let Err(e) = self
+                .coord
+                .write_replica_by_raft(replica.clone(), request, None)
+                .await
+            {
+                break Err(e);
+            }
+            replayed += 1;
+        };
+        drop(reader);
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+        info!(
+            "Replayed {} WAL entries of backup {} to replica {}",
+            replayed, command.backup_id, command.replica_id
+        );
+
+        result
+    }
+
+    /// Send the changes of the replication sets to the subscriber. The changes after the
+    /// cursors are read from WAL first, then the changes applied from now on are followed.
+    async fn send_changes(
+        &self,
+        request: SubscribeRequest,
+        replicas: HashMap<ReplicationSetId, VnodeId>,
+        send: &mpsc::Sender<Result<SubscribeResponse, Status>>,
+    ) -> Result<(), Status> {
+        let tables = request.tables.iter().cloned().collect::<HashSet<_>>();
+        let cursors = request
+            .cursors
+            .iter()
+            .map(|c| (c.replica_id, c.index))
+            .collect::<HashMap<_, _>>();
+        let (mut receiver, applied) = self.kv_inst.change_feed().subscribe();
+
+        // Index of the last change sent of each replication set.
+        let mut positions = HashMap::with_capacity(replicas.len());
+        for (&replica_id, &vnode_id) in replicas.iter() {
+            let applied_index = match applied.get(&vnode_id) {
+                Some(index) => *index,
+                None => self
+                    .coord
+                    .raft_manager()
+                    .last_applied_index(replica_id)
+                    .await
+                    .unwrap_or_default(),
+            };
+            let mut position = applied_index;
+            if let Some(&cursor) = cursors.get(&replica_id) {
+                let events = self
+                    .kv_inst
+                    .read_changes(vnode_id, cursor, applied_index)
+                    .await
+                    .map_err(|err| Status::out_of_range(err.to_string()))?;
+                for event in events.iter() {
+                    if !send_change(event, &tables, send).await? {
+                        return Ok(());
+                    }
+                }
+                position = position.max(cursor);
+            }
+            positions.insert(replica_id, position);
+        }
+
+        loop {
+            let event = tokio::select! {
+                _ = send.closed() => return Ok(()),
+                event = receiver.recv() => match event {
+                    Ok(event) => event,
+                    Err(broadcast::error::RecvError::Lagged(count)) => {
+                        return Err(Status::data_loss(format!(
+                            "Subscriber lagged behind {count} changes, resubscribe from the cursors"
+                        )));
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return Ok(()),
+                },
+            };
+
+            let command = &event.command;
+            if command.tenant != request.tenant
+                || command.db_name != request.db
+                || replicas.get(&command.replica_id) != Some(&event.vnode_id)
+            {
+                continue;
+            }
+            let position = positions.entry(command.replica_id).or_default();
+            if event.index <= *position {
+                continue;
+            }
+            *position = event.index;
+            if !send_change(&event, &tables, send).await? {
+                return Ok(());
+            }
         }
     }
 
@@ -359,6 +578,56 @@ impl TskvService for TskvServiceImpl {
         Ok(tonic::Response::new(Box::pin(encoded_stream)))
     }
 
+    type SubscribeStream = ResponseStream<SubscribeResponse>;
+    async fn subscribe(
+        &self,
+        request: Request<SubscribeRequest>,
+    ) -> Result<Response<Self::SubscribeStream>, Status> {
+        let inner = request.into_inner();
+
+        let client = self.coord.tenant_meta(&inner.tenant).await.ok_or_else(|| {
+            self.internal_status(format!("Not Found tenant({}) meta", inner.tenant))
+        })?;
+        let db_info = client
+            .get_db_info(&inner.db)
+            .map_err(|err| self.internal_status(format!("Meta for database: {:?}", err)))?
+            .ok_or_else(|| self.internal_status(format!("Not Found Database({})", inner.db)))?;
+
+        // Only the replication sets with a vnode on this node are followed.
+        let node_id = self.coord.meta_manager().node_id();
+        let replicas = db_info
+            .buckets
+            .iter()
+            .flat_map(|bucket| bucket.shard_group.iter())
+            .filter_map(|replica| {
+                replica
+                    .vnodes
+                    .iter()
+                    .find(|vnode| vnode.node_id == node_id)
+                    .map(|vnode| (replica.id, vnode.id))
+            })
+            .collect::<HashMap<_, _>>();
+        info!(
+            "Subscribe changes of {}.{} tables {:?} on replication sets {:?}",
+            inner.tenant,
+            inner.db,
+            inner.tables,
+            replicas.keys()
+        );
+
+        let (send, recv) = mpsc::channel(1024);
+        let service = self.clone();
+        tokio::spawn(async move {
+            if let Err(status) = service.send_changes(inner, replicas, &send).await {
+                let _ = send.send(Err(status)).await;
+            }
+        });
+
+        let out_stream = ReceiverStream::new(recv);
+
+        Ok(tonic::Response::new(Box::pin(out_stream)))
+    }
+
     type TagScanStream = ResponseStream<BatchBytesResponse>;
     async fn tag_scan(
         &self,
@@ -395,6 +664,82 @@ impl TskvService for TskvServiceImpl {
     }
 }
 
+/// Send the change to the subscriber, returns false if the subscriber is gone.
+async fn send_change(
+    event: &ChangeEvent,
+    tables: &HashSet<String>,
+    send: &mpsc::Sender<Result<SubscribeResponse, Status>>,
+) -> Result<bool, Status> {
+    let responses =
+        change_responses(event, tables).map_err(|err| Status::internal(err.to_string()))?;
+    for response in responses {
+        if send.send(Ok(response)).await.is_err() {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
+/// Convert the change to responses, a write is split into a response for each table.
+fn change_responses(
+    event: &ChangeEvent,
+    tables: &HashSet<String>,
+) -> CoordinatorResult<Vec<SubscribeResponse>> {
+    let accept = |table: &str| tables.is_empty() || tables.contains(table);
+    let replica_id = event.command.replica_id;
+    let (operation, table, data) = match event.command.command.as_ref() {
+        Some(raft_write_command::Command::WriteData(cmd)) => {
+            let precision = Precision::from(cmd.precision as u8);
+            let batches =
+                write_data_to_record_batches(&cmd.data, precision, accept).context(TskvSnafu)?;
+            let mut responses = Vec::with_capacity(batches.len());
+            for (table, batch) in batches {
+                responses.push(SubscribeResponse {
+                    replica_id,
+                    index: event.index,
+                    operation: ChangeOperation::Write as i32,
+                    table,
+                    data: record_batch_encode(&batch).context(ArrowSnafu)?,
+                });
+            }
+            return Ok(responses);
+        }
+        Some(raft_write_command::Command::DropTable(cmd)) => (
+            ChangeOperation::DropTable,
+            cmd.table.clone(),
+            to_prost_bytes(cmd),
+        ),
+        Some(raft_write_command::Command::DropColumn(cmd)) => (
+            ChangeOperation::DropColumn,
+            cmd.table.clone(),
+            to_prost_bytes(cmd),
+        ),
+        Some(raft_write_command::Command::DeleteFromTable(cmd)) => (
+            ChangeOperation::DeleteFromTable,
+            cmd.table.clone(),
+            to_prost_bytes(cmd),
+        ),
+        Some(raft_write_command::Command::UpdateTags(cmd)) if !cmd.dry_run => (
+            ChangeOperation::UpdateTags,
+            String::new(),
+            to_prost_bytes(cmd),
+        ),
+        _ => return Ok(vec![]),
+    };
+    if !table.is_empty() && !accept(&table) {
+        return Ok(vec![]);
+    }
+
+    Ok(vec![SubscribeResponse {
+        replica_id,
+        index: event.index,
+        operation: operation as i32,
+        table,
+        data,
+    }])
+}
+
 fn get_span(extensions: &Extensions, child_span_name: &'static str) -> Span {
     let context = extensions.get::<SpanContext>();
     Span::from_context(child_span_name, context)
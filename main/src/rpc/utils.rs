@@ -0,0 +1,127 @@
+use coordinator::service::CoordinatorRef;
+use derive_traits::ErrorCode;
+use http_protocol::header::{AUTHORIZATION, DB, PRIVATE_KEY, TENANT};
+use http_protocol::parameter::{SqlParam, WriteParam};
+use protocol_parser::Line;
+use spi::server::dbms::DBMSRef;
+use spi::service::protocol::Context;
+use spi::QueryError;
+use tonic::metadata::MetadataMap;
+use tonic::{Request, Status};
+use trace::span_ext::SpanExt;
+use trace::{error, Span, SpanContext};
+use utils::precision::Precision;
+
+use crate::http::header::Header;
+use crate::http::http_service::{
+    construct_read_context, construct_write_context_and_check_privilege, http_limiter_check_write,
+};
+use crate::http::Error as HttpError;
+
+pub(crate) fn get_span<T>(request: &Request<T>, name: &'static str) -> Span {
+    let context = request.extensions().get::<SpanContext>();
+    Span::from_context(name, context)
+}
+
+pub(crate) fn metadata_value(metadata: &MetadataMap, key: &str) -> Option<String> {
+    metadata
+        .get(key)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.to_string())
+}
+
+/// The request is authenticated in the same way as an HTTP request, by the
+/// `authorization`, `tenant` and `db` metadata.
+fn metadata_to_header(metadata: &MetadataMap) -> Header {
+    Header::with_private_key(
+        None,
+        None,
+        None,
+        metadata_value(metadata, AUTHORIZATION.as_str()).unwrap_or_default(),
+        metadata_value(metadata, PRIVATE_KEY),
+        metadata_value(metadata, TENANT),
+        metadata_value(metadata, DB),
+        None,
+    )
+}
+
+pub(crate) async fn write_context(
+    coord: &CoordinatorRef,
+    dbms: &DBMSRef,
+    metadata: &MetadataMap,
+    req_len: usize,
+) -> Result<Context, Status> {
+    let header = metadata_to_header(metadata);
+    let param = WriteParam {
+        precision: None,
+        tenant: header.get_tenant(),
+        db: header.get_db(),
+    };
+
+    let ctx =
+        construct_write_context_and_check_privilege(header, param, dbms.clone(), coord.clone())
+            .await
+            .map_err(http_error_to_status)?;
+    http_limiter_check_write(&coord.meta_manager(), ctx.tenant(), req_len)
+        .await
+        .map_err(http_error_to_status)?;
+
+    Ok(ctx)
+}
+
+pub(crate) async fn read_context(
+    coord: &CoordinatorRef,
+    dbms: &DBMSRef,
+    metadata: &MetadataMap,
+) -> Result<Context, Status> {
+    let header = metadata_to_header(metadata);
+    let param = SqlParam {
+        tenant: header.get_tenant(),
+        db: header.get_db(),
+        chunked: None,
+        target_partitions: None,
+        stream_trigger_interval: None,
+    };
+
+    construct_read_context(&header, param, dbms.clone(), coord.clone(), false)
+        .await
+        .map_err(http_error_to_status)
+}
+
+pub(crate) async fn write_lines(
+    coord: &CoordinatorRef,
+    ctx: &Context,
+    lines: Vec<Line<'_>>,
+    span: &Span,
+) -> Result<(), Status> {
+    coord
+        .write_lines(
+            ctx.tenant(),
+            ctx.database(),
+            Precision::NS,
+            lines,
+            span.context().as_ref(),
+        )
+        .await
+        .map_err(|e| {
+            error!("Failed to write lines, err: {:?}", e);
+            span.error(e.to_string());
+            Status::internal(e.to_string())
+        })?;
+    Ok(())
+}
+
+fn http_error_to_status(e: HttpError) -> Status {
+    let message = e.error_code().message();
+    match e {
+        HttpError::ParseAuth { .. } | HttpError::InvalidHeader { .. } => {
+            Status::unauthenticated(message)
+        }
+        HttpError::Query {
+            source: QueryError::InsufficientPrivileges { .. },
+        } => Status::permission_denied(message),
+        // Failed to authenticate the user
+        HttpError::Query { .. } => Status::unauthenticated(message),
+        _ => Status::internal(message),
+    }
+}
@@ -167,7 +167,9 @@ impl ServiceBuilder {
             server.add_service(Box::new(http_service));
         }
 
-        if let Some(grpc_service) = self.create_grpc_if_enabled(kv_inst.clone(), coord.clone()) {
+        if let Some(grpc_service) =
+            self.create_grpc_if_enabled(kv_inst.clone(), coord.clone(), dbms.clone())
+        {
             server.add_service(Box::new(grpc_service));
         }
 
@@ -196,7 +198,9 @@ impl ServiceBuilder {
             server.add_service(Box::new(http_service));
         }
 
-        if let Some(flight_sql_service) = self.create_flight_sql_if_enabled(dbms.clone()) {
+        if let Some(flight_sql_service) =
+            self.create_flight_sql_if_enabled(dbms.clone(), coord.clone())
+        {
             server.add_service(Box::new(flight_sql_service));
         }
 
@@ -234,11 +238,15 @@ impl ServiceBuilder {
             server.add_service(Box::new(http_service));
         }
 
-        if let Some(grpc_service) = self.create_grpc_if_enabled(kv_inst.clone(), coord.clone()) {
+        if let Some(grpc_service) =
+            self.create_grpc_if_enabled(kv_inst.clone(), coord.clone(), dbms.clone())
+        {
             server.add_service(Box::new(grpc_service));
         }
 
-        if let Some(flight_sql_service) = self.create_flight_sql_if_enabled(dbms.clone()) {
+        if let Some(flight_sql_service) =
+            self.create_flight_sql_if_enabled(dbms.clone(), coord.clone())
+        {
             server.add_service(Box::new(flight_sql_service));
         }
 
@@ -364,7 +372,12 @@ impl ServiceBuilder {
         ))
     }
 
-    fn create_grpc_if_enabled(&self, kv: EngineRef, coord: CoordinatorRef) -> Option<GrpcService> {
+    fn create_grpc_if_enabled(
+        &self,
+        kv: EngineRef,
+        coord: CoordinatorRef,
+        dbms: DBMSRef,
+    ) -> Option<GrpcService> {
         let default_grpc_addr = match self.config.service.grpc_listen_port {
             Some(port) => build_default_address(port),
             None => return None,
@@ -388,6 +401,7 @@ impl ServiceBuilder {
             self.runtime.clone(),
             kv,
             coord,
+            dbms,
             addr,
             None,
             self.metrics_register.clone(),
@@ -405,7 +419,11 @@ impl ServiceBuilder {
         Some(TcpService::new(coord, default_tcp_addr))
     }
 
-    fn create_flight_sql_if_enabled(&self, dbms: DBMSRef) -> Option<FlightSqlServiceAdapter> {
+    fn create_flight_sql_if_enabled(
+        &self,
+        dbms: DBMSRef,
+        coord: CoordinatorRef,
+    ) -> Option<FlightSqlServiceAdapter> {
         let default_flight_sql_addr = match self.config.service.flight_rpc_listen_port {
             Some(port) => build_default_address(port),
             None => return None,
@@ -428,6 +446,7 @@ impl ServiceBuilder {
 
         Some(FlightSqlServiceAdapter::new(
             dbms,
+            coord,
             addr,
             tls_config,
             self.config.trace.auto_generate_span,
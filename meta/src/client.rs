@@ -1,10 +1,12 @@
 use std::collections::HashSet;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use metrics::count::U64Counter;
 use metrics::duration::{DurationHistogram, DurationHistogramOptions};
 use metrics::metric_register::MetricsRegister;
+use models::audit::{AuditRecord, AuditSinkRef};
+use models::utils::now_timestamp_nanos;
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 use tracing::info;
@@ -25,6 +27,8 @@ pub struct MetaHttpClient {
     read_meta_response_time: DurationHistogram,
     write_meta_response_time: DurationHistogram,
     watch_meta_response_time: DurationHistogram,
+    /// Shared by the clones, the sink may be set after the clients are created.
+    audit_sink: Arc<RwLock<Option<AuditSinkRef>>>,
 }
 
 impl MetaHttpClient {
@@ -77,9 +81,15 @@ impl MetaHttpClient {
             read_meta_response_time,
             write_meta_response_time,
             watch_meta_response_time,
+            audit_sink: Arc::new(RwLock::new(None)),
         }
     }
 
+    /// Record the DDL and DCL commands written by this client and its clones to the sink.
+    pub fn set_audit_sink(&self, audit_sink: AuditSinkRef) {
+        *self.audit_sink.write() = Some(audit_sink);
+    }
+
     pub async fn read<T>(&self, req: &ReadCommand) -> MetaResult<T>
     where
         T: for<'a> Deserialize<'a>,
@@ -100,6 +110,17 @@ impl MetaHttpClient {
     }
 
     pub async fn write<T>(&self, req: &WriteCommand) -> MetaResult<T>
+    where
+        T: for<'a> Deserialize<'a>,
+    {
+        let start = Instant::now();
+        let result = self.do_write(req).await;
+        self.audit_write_command(req, &result, start.elapsed());
+
+        result
+    }
+
+    async fn do_write<T>(&self, req: &WriteCommand) -> MetaResult<T>
     where
         T: for<'a> Deserialize<'a>,
     {
@@ -118,6 +139,35 @@ impl MetaHttpClient {
         result
     }
 
+    /// The command is recorded once by the node proposing it, not by the replicas applying it.
+    fn audit_write_command<T>(
+        &self,
+        req: &WriteCommand,
+        result: &MetaResult<T>,
+        duration: Duration,
+    ) {
+        let Some(audit_sink) = self.audit_sink.read().clone() else {
+            return;
+        };
+        let Some((category, tenant, statement)) = req.audit_summary() else {
+            return;
+        };
+        if !audit_sink.is_enabled(category) {
+            return;
+        }
+
+        audit_sink.record(AuditRecord {
+            time: now_timestamp_nanos(),
+            category,
+            tenant: tenant.map(|tenant| tenant.to_string()),
+            user: None,
+            client_addr: None,
+            statement,
+            error: result.as_ref().err().map(|err| err.to_string()),
+            duration,
+        });
+    }
+
     pub async fn watch<T>(&self, req: &(String, String, HashSet<String>, u64)) -> MetaResult<T>
     where
         T: for<'a> Deserialize<'a>,
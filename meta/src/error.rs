@@ -182,6 +182,22 @@ pub enum MetaError {
     #[snafu(display("cannot revoke the privilege {privilege} of role"))]
     #[error_code(code = 56)]
     PrivilegeCannotRevoke { privilege: TenantObjectPrivilege },
+
+    #[snafu(display("The function {} already exists", function))]
+    #[error_code(code = 57)]
+    FunctionAlreadyExists { function: String },
+
+    #[snafu(display("The function {} not found", function))]
+    #[error_code(code = 58)]
+    FunctionNotFound { function: String },
+
+    #[snafu(display("The policy {} already exists", policy))]
+    #[error_code(code = 59)]
+    PolicyAlreadyExists { policy: String },
+
+    #[snafu(display("The policy {} not found", policy))]
+    #[error_code(code = 60)]
+    PolicyNotFound { policy: String },
 }
 
 impl MetaError {
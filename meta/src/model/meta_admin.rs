@@ -8,6 +8,7 @@ use std::sync::Arc;
 use config::common::{RequestLimiterConfig, TenantLimiterConfig, TenantObjectLimiterConfig};
 use config::tskv::Config;
 use metrics::metric_register::MetricsRegister;
+use models::audit::AuditSinkRef;
 use models::auth::user::{admin_user, User, UserDesc, UserOptions};
 use models::meta_data::*;
 use models::node_info::NodeStatus;
@@ -159,6 +160,11 @@ impl AdminMeta {
         self.client.meta_leader().await
     }
 
+    /// Record the DDL and DCL commands written by this node, including the tenants' commands.
+    pub fn set_audit_sink(&self, audit_sink: AuditSinkRef) {
+        self.client.set_audit_sink(audit_sink);
+    }
+
     pub fn sys_info() -> SysInfo {
         let mut info = SysInfo::default();
 
@@ -639,7 +645,7 @@ impl AdminMeta {
             self.cluster(),
             tenant_info,
             self.meta_addrs(),
-            self.metrics_register.clone(),
+            self.client.clone(),
         )
         .await?;
 
@@ -7,13 +7,15 @@ use std::sync::Arc;
 use client::MetaHttpClient;
 use config::common::TenantObjectLimiterConfig;
 use metrics::metric_register::MetricsRegister;
-use models::auth::privilege::{DatabasePrivilege, Privilege};
+use models::auth::privilege::{DatabasePrivilege, Privilege, TablePrivilege};
 use models::auth::role::{CustomTenantRole, SystemTenantRole, TenantRoleIdentifier};
 use models::auth::user::UserDesc;
 use models::meta_data::*;
 use models::oid::{Identifier, Oid};
 use models::schema::database_schema::DatabaseSchema;
 use models::schema::external_table_schema::ExternalTableSchema;
+use models::schema::function_schema::FunctionSchema;
+use models::schema::policy_schema::PolicySchema;
 use models::schema::resource_info::ResourceInfo;
 use models::schema::table_schema::TableSchema;
 use models::schema::tenant::Tenant;
@@ -54,14 +56,14 @@ impl TenantMeta {
         cluster: String,
         tenant: Tenant,
         meta_url: String,
-        metrics_register: Arc<MetricsRegister>,
+        client: MetaHttpClient,
     ) -> MetaResult<Arc<Self>> {
         let client = Arc::new(Self {
             cluster,
             tenant,
-            meta_url: meta_url.clone(),
+            meta_url,
             data: RwLock::new(TenantMetaData::new()),
-            client: MetaHttpClient::new(&meta_url, metrics_register),
+            client,
         });
 
         client.sync_all_tenant_metadata().await?;
@@ -353,6 +355,36 @@ impl TenantMeta {
         self.client.write::<()>(&req).await
     }
 
+    pub async fn grant_table_privilege_to_custom_role(
+        &self,
+        table_privileges: Vec<(TablePrivilege, String, String)>,
+        role_name: &str,
+    ) -> MetaResult<()> {
+        let req = command::WriteCommand::GrantTablePrivileges(
+            self.cluster.clone(),
+            table_privileges,
+            role_name.to_string(),
+            self.tenant_name(),
+        );
+
+        self.client.write::<()>(&req).await
+    }
+
+    pub async fn revoke_table_privilege_from_custom_role(
+        &self,
+        table_privileges: Vec<(TablePrivilege, String, String)>,
+        role_name: &str,
+    ) -> MetaResult<()> {
+        let req = command::WriteCommand::RevokeTablePrivileges(
+            self.cluster.clone(),
+            table_privileges,
+            role_name.to_string(),
+            self.tenant_name(),
+        );
+
+        self.client.write::<()>(&req).await
+    }
+
     pub async fn drop_custom_role(&self, role_name: &str) -> MetaResult<bool> {
         let req = command::WriteCommand::DropRole(
             self.cluster.clone(),
@@ -369,6 +401,86 @@ impl TenantMeta {
     }
     // tenant role end
 
+    // tenant function start
+
+    pub async fn create_function(
+        &self,
+        function: FunctionSchema,
+        or_replace: bool,
+    ) -> MetaResult<()> {
+        let req = command::WriteCommand::CreateFunction(self.cluster.clone(), function, or_replace);
+
+        self.write_with_data(&req).await
+    }
+
+    /// Return false if the function does not exist.
+    pub async fn drop_function(&self, function_name: &str) -> MetaResult<bool> {
+        let req = command::WriteCommand::DropFunction(
+            self.cluster.clone(),
+            function_name.to_string(),
+            self.tenant_name(),
+        );
+
+        match self.write_with_data(&req).await {
+            Ok(()) => Ok(true),
+            Err(MetaError::FunctionNotFound { .. }) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn function(&self, function_name: &str) -> Option<FunctionSchema> {
+        self.data.read().functions.get(function_name).cloned()
+    }
+
+    pub fn functions(&self) -> Vec<FunctionSchema> {
+        self.data.read().functions.values().cloned().collect()
+    }
+
+    // tenant function end
+
+    // tenant policy start
+
+    pub async fn create_policy(&self, policy: PolicySchema, or_replace: bool) -> MetaResult<()> {
+        let req = command::WriteCommand::CreatePolicy(self.cluster.clone(), policy, or_replace);
+
+        self.write_with_data(&req).await
+    }
+
+    /// Return false if the policy does not exist.
+    pub async fn drop_policy(&self, policy_name: &str) -> MetaResult<bool> {
+        let req = command::WriteCommand::DropPolicy(
+            self.cluster.clone(),
+            policy_name.to_string(),
+            self.tenant_name(),
+        );
+
+        match self.write_with_data(&req).await {
+            Ok(()) => Ok(true),
+            Err(MetaError::PolicyNotFound { .. }) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn policy(&self, policy_name: &str) -> Option<PolicySchema> {
+        self.data.read().policies.get(policy_name).cloned()
+    }
+
+    pub fn policies(&self) -> Vec<PolicySchema> {
+        self.data.read().policies.values().cloned().collect()
+    }
+
+    pub fn table_policies(&self, database: &str, table: &str) -> Vec<PolicySchema> {
+        self.data
+            .read()
+            .policies
+            .values()
+            .filter(|p| p.is_on_table(database, table))
+            .cloned()
+            .collect()
+    }
+
+    // tenant policy end
+
     async fn write_with_data(&self, req: &command::WriteCommand) -> MetaResult<()> {
         let rsp = self.client.write::<TenantMetaData>(req).await?;
 
@@ -851,6 +963,8 @@ impl TenantMeta {
 
     // **[6]    /cluster_name/tenants/tenant/roles/name -> [CustomTenantRole<Oid>]
     // **[6]    /cluster_name/tenants/tenant/members/oid -> [TenantRoleIdentifier]
+    // **[6]    /cluster_name/tenants/tenant/functions/name -> [FunctionSchema]
+    // **[6]    /cluster_name/tenants/tenant/policies/name -> [PolicySchema]
     pub async fn process_watch_log(&self, entry: &EntryLog) -> MetaResult<()> {
         let mut cache = self.data.write();
         if cache.version >= entry.ver {
@@ -923,6 +1037,24 @@ impl TenantMeta {
             } else if entry.tye == command::ENTRY_LOG_TYPE_DEL {
                 cache.members.remove(key);
             }
+        } else if len == 6 && strs[4] == key_path::FUNCTIONS && strs[2] == key_path::TENANTS {
+            let key = strs[5];
+            if entry.tye == command::ENTRY_LOG_TYPE_SET {
+                if let Ok(info) = serde_json::from_str::<FunctionSchema>(&entry.val) {
+                    cache.functions.insert(key.to_owned(), info);
+                }
+            } else if entry.tye == command::ENTRY_LOG_TYPE_DEL {
+                cache.functions.remove(key);
+            }
+        } else if len == 6 && strs[4] == key_path::POLICIES && strs[2] == key_path::TENANTS {
+            let key = strs[5];
+            if entry.tye == command::ENTRY_LOG_TYPE_SET {
+                if let Ok(info) = serde_json::from_str::<PolicySchema>(&entry.val) {
+                    cache.policies.insert(key.to_owned(), info);
+                }
+            } else if entry.tye == command::ENTRY_LOG_TYPE_DEL {
+                cache.policies.remove(key);
+            }
         } else if len == 6 && strs[4] == key_path::ROLES && strs[2] == key_path::TENANTS {
             let key = strs[5];
             if entry.tye == command::ENTRY_LOG_TYPE_SET {
@@ -2,12 +2,15 @@
 
 use std::collections::{HashMap, HashSet};
 
-use models::auth::privilege::DatabasePrivilege;
+use models::audit::AuditCategory;
+use models::auth::privilege::{DatabasePrivilege, TablePrivilege};
 use models::auth::role::{SystemTenantRole, TenantRoleIdentifier};
 use models::auth::user::{UserDesc, UserOptions};
 use models::meta_data::*;
-use models::oid::Oid;
+use models::oid::{Identifier, Oid};
 use models::schema::database_schema::DatabaseSchema;
+use models::schema::function_schema::FunctionSchema;
+use models::schema::policy_schema::PolicySchema;
 use models::schema::query_info::QueryInfo;
 use models::schema::resource_info::ResourceInfo;
 use models::schema::table_schema::TableSchema;
@@ -130,6 +133,31 @@ pub enum WriteCommand {
     // cluster, privileges, role_name, tenant_name
     RevokePrivileges(String, Vec<(DatabasePrivilege, String)>, String, String),
 
+    // cluster, function, or_replace
+    CreateFunction(String, FunctionSchema, bool),
+    // cluster, function_name, tenant_name
+    DropFunction(String, String, String),
+
+    // cluster, (privilege, database_name, table_name), role_name, tenant_name
+    GrantTablePrivileges(
+        String,
+        Vec<(TablePrivilege, String, String)>,
+        String,
+        String,
+    ),
+    // cluster, (privilege, database_name, table_name), role_name, tenant_name
+    RevokeTablePrivileges(
+        String,
+        Vec<(TablePrivilege, String, String)>,
+        String,
+        String,
+    ),
+
+    // cluster, policy, or_replace
+    CreatePolicy(String, PolicySchema, bool),
+    // cluster, policy_name, tenant_name
+    DropPolicy(String, String, String),
+
     Set {
         key: String,
         value: String,
@@ -155,6 +183,208 @@ pub enum WriteCommand {
     MoveQueryInfo(String, NodeId, NodeId),
 }
 
+impl WriteCommand {
+    /// The audit category, the tenant and the summary of the command,
+    /// None if the command does not change the definition of objects or the access control.
+    pub fn audit_summary(&self) -> Option<(AuditCategory, Option<&str>, String)> {
+        let summary = match self {
+            WriteCommand::CreateDB(_, tenant, schema) => (
+                AuditCategory::Ddl,
+                Some(tenant.as_str()),
+                format!("create database {}", schema.database_name()),
+            ),
+            WriteCommand::AlterDB(_, tenant, schema) => (
+                AuditCategory::Ddl,
+                Some(tenant.as_str()),
+                format!("alter database {}", schema.database_name()),
+            ),
+            WriteCommand::SetDBIsHidden(_, tenant, db, is_hidden) => (
+                AuditCategory::Ddl,
+                Some(tenant.as_str()),
+                format!("set database {} hidden {}", db, is_hidden),
+            ),
+            WriteCommand::DropDB(_, tenant, db) => (
+                AuditCategory::Ddl,
+                Some(tenant.as_str()),
+                format!("drop database {}", db),
+            ),
+            WriteCommand::CreateTable(_, tenant, schema) => (
+                AuditCategory::Ddl,
+                Some(tenant.as_str()),
+                format!("create table {}.{}", schema.db(), schema.name()),
+            ),
+            WriteCommand::UpdateTable(_, tenant, schema) => (
+                AuditCategory::Ddl,
+                Some(tenant.as_str()),
+                format!("alter table {}.{}", schema.db(), schema.name()),
+            ),
+            WriteCommand::DropTable(_, tenant, db, table) => (
+                AuditCategory::Ddl,
+                Some(tenant.as_str()),
+                format!("drop table {}.{}", db, table),
+            ),
+            WriteCommand::CreateTenant(_, tenant) => (
+                AuditCategory::Ddl,
+                Some(tenant.name()),
+                format!("create tenant {}", tenant.name()),
+            ),
+            WriteCommand::AlterTenant(_, tenant, _) => (
+                AuditCategory::Ddl,
+                Some(tenant.as_str()),
+                format!("alter tenant {}", tenant),
+            ),
+            WriteCommand::SetTenantIsHidden(_, tenant, is_hidden) => (
+                AuditCategory::Ddl,
+                Some(tenant.as_str()),
+                format!("set tenant {} hidden {}", tenant, is_hidden),
+            ),
+            WriteCommand::RenameTenant(_, old_name, new_name) => (
+                AuditCategory::Ddl,
+                Some(old_name.as_str()),
+                format!("rename tenant {} to {}", old_name, new_name),
+            ),
+            WriteCommand::DropTenant(_, tenant) => (
+                AuditCategory::Ddl,
+                Some(tenant.as_str()),
+                format!("drop tenant {}", tenant),
+            ),
+            WriteCommand::CreateFunction(_, function, or_replace) => (
+                AuditCategory::Ddl,
+                Some(function.tenant.as_str()),
+                format!(
+                    "create {}function {}",
+                    if *or_replace { "or replace " } else { "" },
+                    function.name
+                ),
+            ),
+            WriteCommand::DropFunction(_, function_name, tenant) => (
+                AuditCategory::Ddl,
+                Some(tenant.as_str()),
+                format!("drop function {}", function_name),
+            ),
+            WriteCommand::CreateUser(_, user) => (
+                AuditCategory::Dcl,
+                None,
+                format!("create user {}", user.name()),
+            ),
+            WriteCommand::AlterUser(_, name, _) => {
+                (AuditCategory::Dcl, None, format!("alter user {}", name))
+            }
+            WriteCommand::RenameUser(_, old_name, new_name) => (
+                AuditCategory::Dcl,
+                None,
+                format!("rename user {} to {}", old_name, new_name),
+            ),
+            WriteCommand::DropUser(_, name) => {
+                (AuditCategory::Dcl, None, format!("drop user {}", name))
+            }
+            WriteCommand::AddMemberToTenant(_, user_id, role, tenant) => (
+                AuditCategory::Dcl,
+                Some(tenant.as_str()),
+                format!("add member {} as {}", user_id, role.name()),
+            ),
+            WriteCommand::RemoveMemberFromTenant(_, user_id, tenant) => (
+                AuditCategory::Dcl,
+                Some(tenant.as_str()),
+                format!("remove member {}", user_id),
+            ),
+            WriteCommand::ReasignMemberRole(_, user_id, role, tenant) => (
+                AuditCategory::Dcl,
+                Some(tenant.as_str()),
+                format!("set member {} as {}", user_id, role.name()),
+            ),
+            WriteCommand::CreateRole(_, role_name, _, _, tenant) => (
+                AuditCategory::Dcl,
+                Some(tenant.as_str()),
+                format!("create role {}", role_name),
+            ),
+            WriteCommand::DropRole(_, role_name, tenant) => (
+                AuditCategory::Dcl,
+                Some(tenant.as_str()),
+                format!("drop role {}", role_name),
+            ),
+            WriteCommand::GrantPrivileges(_, privileges, role_name, tenant) => (
+                AuditCategory::Dcl,
+                Some(tenant.as_str()),
+                format!(
+                    "grant {} to role {}",
+                    privileges
+                        .iter()
+                        .map(|(privilege, db)| format!("{:?} on database {}", privilege, db))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    role_name
+                ),
+            ),
+            WriteCommand::RevokePrivileges(_, privileges, role_name, tenant) => (
+                AuditCategory::Dcl,
+                Some(tenant.as_str()),
+                format!(
+                    "revoke {} from role {}",
+                    privileges
+                        .iter()
+                        .map(|(privilege, db)| format!("{:?} on database {}", privilege, db))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    role_name
+                ),
+            ),
+            WriteCommand::GrantTablePrivileges(_, privileges, role_name, tenant) => (
+                AuditCategory::Dcl,
+                Some(tenant.as_str()),
+                format!(
+                    "grant {} to role {}",
+                    table_privileges_summary(privileges),
+                    role_name
+                ),
+            ),
+            WriteCommand::RevokeTablePrivileges(_, privileges, role_name, tenant) => (
+                AuditCategory::Dcl,
+                Some(tenant.as_str()),
+                format!(
+                    "revoke {} from role {}",
+                    table_privileges_summary(privileges),
+                    role_name
+                ),
+            ),
+            WriteCommand::CreatePolicy(_, policy, or_replace) => (
+                AuditCategory::Dcl,
+                Some(policy.tenant.as_str()),
+                format!(
+                    "create {}policy {}",
+                    if *or_replace { "or replace " } else { "" },
+                    policy.name
+                ),
+            ),
+            WriteCommand::DropPolicy(_, policy_name, tenant) => (
+                AuditCategory::Dcl,
+                Some(tenant.as_str()),
+                format!("drop policy {}", policy_name),
+            ),
+            _ => return None,
+        };
+
+        Some(summary)
+    }
+}
+
+fn table_privileges_summary(privileges: &[(TablePrivilege, String, String)]) -> String {
+    privileges
+        .iter()
+        .map(|(privilege, db, table)| match &privilege.columns {
+            Some(columns) => format!(
+                "{:?} ({}) on table {}.{}",
+                privilege.privilege,
+                columns.iter().cloned().collect::<Vec<_>>().join(", "),
+                db,
+                table
+            ),
+            None => format!("{:?} on table {}.{}", privilege.privilege, db, table),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 /******************* read command *************************/
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum ReadCommand {
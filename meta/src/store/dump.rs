@@ -4,10 +4,14 @@ use models::auth::role::{CustomTenantRole, TenantRoleIdentifier};
 use models::auth::user::{UserDesc, ROOT};
 use models::oid::{Identifier, Oid};
 use models::schema::database_schema::DatabaseSchema;
+use models::schema::function_schema::FunctionSchema;
+use models::schema::policy_schema::PolicySchema;
 use models::schema::table_schema::TableSchema;
 use models::schema::tenant::Tenant;
 use models::schema::{DEFAULT_CATALOG, DEFAULT_DATABASE, USAGE_SCHEMA};
-use models::sql::{add_member_to_sql, create_table_sqls, role_to_sql, ToDDLSql};
+use models::sql::{
+    add_member_to_sql, create_table_sqls, role_to_sql, table_privilege_to_sql, ToDDLSql,
+};
 
 use crate::error::MetaResult;
 use crate::store::key_path::KeyPath;
@@ -118,6 +122,16 @@ pub fn dump_tenant(
         res.append(&mut role_to_sql(role)?)
     }
 
+    // dump function
+    let functions_key = KeyPath::functions(cluster, tenant);
+    let functions = storage
+        .children_data::<FunctionSchema>(&functions_key)?
+        .into_iter()
+        .collect::<BTreeMap<_, _>>();
+    for (_, function) in functions.iter() {
+        res.push(function.to_ddl_sql(false)?)
+    }
+
     // dump member
     let members_key = KeyPath::members(cluster, tenant);
     let mut members = storage
@@ -157,5 +171,20 @@ pub fn dump_tenant(
         }
     }
 
+    // dump the table privileges of role, which need the tables
+    for (_, role) in roles.iter() {
+        res.append(&mut table_privilege_to_sql(role))
+    }
+
+    // dump policy, which needs the tables
+    let policies_key = KeyPath::policies(cluster, tenant);
+    let policies = storage
+        .children_data::<PolicySchema>(&policies_key)?
+        .into_iter()
+        .collect::<BTreeMap<_, _>>();
+    for (_, policy) in policies.iter() {
+        res.push(policy.to_ddl_sql(false)?)
+    }
+
     Ok(res)
 }
@@ -5,6 +5,8 @@ use models::oid::Oid;
 // **    /cluster_name/tenants/tenant ->
 // **    /cluster_name/tenants/tenant/roles/roles ->
 // **    /cluster_name/tenants/tenant/members/user_id ->
+// **    /cluster_name/tenants/tenant/functions/name ->
+// **    /cluster_name/tenants/tenant/policies/name ->
 // **    /cluster_name/tenants/tenant/limiter ->
 // **    /cluster_name/auto_incr_id -> id
 // **    /cluster_name/data_nodes/node_id -> [NodeInfo] 集群、数据节点等信息
@@ -20,6 +22,8 @@ pub const BUCKETS: &str = "buckets";
 pub const SCHEMAS: &str = "schemas";
 pub const TENANTS: &str = "tenants";
 pub const MEMBERS: &str = "members";
+pub const FUNCTIONS: &str = "functions";
+pub const POLICIES: &str = "policies";
 pub const LIMITER: &str = "limiter";
 pub const DATA_NODES: &str = "data_nodes";
 pub const AUTO_INCR_ID: &str = "auto_incr_id";
@@ -128,6 +132,28 @@ impl KeyPath {
         format!("/{}/tenants/{}/members", cluster, tenant_name)
     }
 
+    pub fn function(cluster: &str, tenant_name: &str, function_name: &str) -> String {
+        format!(
+            "/{}/tenants/{}/functions/{}",
+            cluster, tenant_name, function_name
+        )
+    }
+
+    pub fn functions(cluster: &str, tenant_name: &str) -> String {
+        format!("/{}/tenants/{}/functions", cluster, tenant_name)
+    }
+
+    pub fn policy(cluster: &str, tenant_name: &str, policy_name: &str) -> String {
+        format!(
+            "/{}/tenants/{}/policies/{}",
+            cluster, tenant_name, policy_name
+        )
+    }
+
+    pub fn policies(cluster: &str, tenant_name: &str) -> String {
+        format!("/{}/tenants/{}/policies", cluster, tenant_name)
+    }
+
     pub fn limiter(cluster: &str, tenant_name: &str) -> String {
         format!("/{cluster}/tenants/{tenant_name}/limiter")
     }
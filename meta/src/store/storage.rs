@@ -4,12 +4,14 @@ use std::fs;
 use std::path::Path;
 use std::sync::Arc;
 
-use models::auth::privilege::DatabasePrivilege;
+use models::auth::privilege::{DatabasePrivilege, TablePrivilege, TenantObjectPrivilege};
 use models::auth::role::{CustomTenantRole, SystemTenantRole, TenantRoleIdentifier};
 use models::auth::user::{UserDesc, UserOptions};
 use models::meta_data::*;
 use models::oid::{Identifier, Oid, UuidGenerator};
 use models::schema::database_schema::DatabaseSchema;
+use models::schema::function_schema::FunctionSchema;
+use models::schema::policy_schema::PolicySchema;
 use models::schema::query_info::QueryInfo;
 use models::schema::resource_info::ResourceInfo;
 use models::schema::table_schema::TableSchema;
@@ -387,6 +389,9 @@ impl StateMachine {
             self.children_data::<CustomTenantRole<Oid>>(&KeyPath::roles(cluster, tenant))?;
         meta.members =
             self.children_data::<TenantRoleIdentifier>(&KeyPath::members(cluster, tenant))?;
+        meta.functions =
+            self.children_data::<FunctionSchema>(&KeyPath::functions(cluster, tenant))?;
+        meta.policies = self.children_data::<PolicySchema>(&KeyPath::policies(cluster, tenant))?;
         let db_schemas =
             self.children_data::<DatabaseSchema>(&KeyPath::tenant_dbs(cluster, tenant))?;
 
@@ -757,6 +762,12 @@ impl StateMachine {
             WriteCommand::DropRole(cluster, role_name, tenant_name) => {
                 response_encode(self.process_drop_role(cluster, role_name, tenant_name))
             }
+            WriteCommand::CreateFunction(cluster, function, or_replace) => {
+                response_encode(self.process_create_function(cluster, function, *or_replace))
+            }
+            WriteCommand::DropFunction(cluster, function_name, tenant_name) => {
+                response_encode(self.process_drop_function(cluster, function_name, tenant_name))
+            }
             WriteCommand::GrantPrivileges(cluster, privileges, role_name, tenant_name) => {
                 response_encode(self.process_grant_privileges(
                     cluster,
@@ -773,6 +784,28 @@ impl StateMachine {
                     tenant_name,
                 ))
             }
+            WriteCommand::GrantTablePrivileges(cluster, privileges, role_name, tenant_name) => {
+                response_encode(self.process_grant_table_privileges(
+                    cluster,
+                    privileges,
+                    role_name,
+                    tenant_name,
+                ))
+            }
+            WriteCommand::RevokeTablePrivileges(cluster, privileges, role_name, tenant_name) => {
+                response_encode(self.process_revoke_table_privileges(
+                    cluster,
+                    privileges,
+                    role_name,
+                    tenant_name,
+                ))
+            }
+            WriteCommand::CreatePolicy(cluster, policy, or_replace) => {
+                response_encode(self.process_create_policy(cluster, policy, *or_replace))
+            }
+            WriteCommand::DropPolicy(cluster, policy_name, tenant_name) => {
+                response_encode(self.process_drop_policy(cluster, policy_name, tenant_name))
+            }
             WriteCommand::RetainID(cluster, count) => {
                 response_encode(self.process_retain_id(cluster, *count))
             }
@@ -1010,6 +1043,8 @@ impl StateMachine {
             let _ = self.remove(it);
         }
 
+        self.remove_policies_on(cluster, tenant, db_name, None)?;
+
         Ok(())
     }
 
@@ -1027,9 +1062,28 @@ impl StateMachine {
             });
         }
 
+        self.remove_policies_on(cluster, tenant, db_name, Some(table_name))?;
         self.remove(&key)
     }
 
+    /// Remove the policies on the table, or on all tables of the database if table_name is None
+    fn remove_policies_on(
+        &self,
+        cluster: &str,
+        tenant: &str,
+        db_name: &str,
+        table_name: Option<&str>,
+    ) -> MetaResult<()> {
+        let policies = self.children_data::<PolicySchema>(&KeyPath::policies(cluster, tenant))?;
+        for (policy_name, policy) in policies.iter() {
+            if policy.database == db_name && table_name.map_or(true, |t| policy.table == t) {
+                self.remove(&KeyPath::policy(cluster, tenant, policy_name))?;
+            }
+        }
+
+        Ok(())
+    }
+
     fn process_create_db(
         &self,
         cluster: &str,
@@ -1432,6 +1486,18 @@ impl StateMachine {
             self.process_drop_role(cluster, role.name(), name)?;
         }
 
+        // drop function in the tenant
+        let functions = self.children_data::<FunctionSchema>(&KeyPath::functions(cluster, name))?;
+        for function_name in functions.keys() {
+            self.remove(&KeyPath::function(cluster, name, function_name))?;
+        }
+
+        // drop policy in the tenant
+        let policies = self.children_data::<PolicySchema>(&KeyPath::policies(cluster, name))?;
+        for policy_name in policies.keys() {
+            self.remove(&KeyPath::policy(cluster, name, policy_name))?;
+        }
+
         // drop tenant meta
         let key = KeyPath::tenant(cluster, name);
         let limiter_key = KeyPath::limiter(cluster, name);
@@ -1554,6 +1620,94 @@ impl StateMachine {
         Ok(true)
     }
 
+    fn process_create_function(
+        &self,
+        cluster: &str,
+        function: &FunctionSchema,
+        or_replace: bool,
+    ) -> MetaResult<TenantMetaData> {
+        let key = KeyPath::function(cluster, &function.tenant, &function.name);
+
+        if !or_replace && self.contains_key(&key)? {
+            return Err(MetaError::FunctionAlreadyExists {
+                function: function.name.clone(),
+            });
+        }
+
+        // The version written by the following insert.
+        let function = FunctionSchema {
+            version: self.version()? + 1,
+            ..function.clone()
+        };
+        self.insert(&key, &value_encode(&function)?)?;
+
+        self.to_tenant_meta_data(cluster, &function.tenant)
+    }
+
+    fn process_drop_function(
+        &self,
+        cluster: &str,
+        function_name: &str,
+        tenant_name: &str,
+    ) -> MetaResult<TenantMetaData> {
+        let key = KeyPath::function(cluster, tenant_name, function_name);
+
+        if !self.contains_key(&key)? {
+            return Err(MetaError::FunctionNotFound {
+                function: function_name.to_string(),
+            });
+        }
+
+        self.remove(&key)?;
+
+        self.to_tenant_meta_data(cluster, tenant_name)
+    }
+
+    fn process_create_policy(
+        &self,
+        cluster: &str,
+        policy: &PolicySchema,
+        or_replace: bool,
+    ) -> MetaResult<TenantMetaData> {
+        let table_key =
+            KeyPath::tenant_schema_name(cluster, &policy.tenant, &policy.database, &policy.table);
+        if !self.contains_key(&table_key)? {
+            return Err(MetaError::TableNotFound {
+                table: policy.table.clone(),
+            });
+        }
+
+        let key = KeyPath::policy(cluster, &policy.tenant, &policy.name);
+        if !or_replace && self.contains_key(&key)? {
+            return Err(MetaError::PolicyAlreadyExists {
+                policy: policy.name.clone(),
+            });
+        }
+
+        self.insert(&key, &value_encode(policy)?)?;
+
+        self.to_tenant_meta_data(cluster, &policy.tenant)
+    }
+
+    fn process_drop_policy(
+        &self,
+        cluster: &str,
+        policy_name: &str,
+        tenant_name: &str,
+    ) -> MetaResult<TenantMetaData> {
+        let key = KeyPath::policy(cluster, tenant_name, policy_name);
+
+        if !self.contains_key(&key)? {
+            return Err(MetaError::PolicyNotFound {
+                policy: policy_name.to_string(),
+            });
+        }
+
+        self.remove(&key)?;
+
+        self.to_tenant_meta_data(cluster, tenant_name)
+    }
+
     fn process_grant_privileges(
         &self,
         cluster: &str,
@@ -1609,6 +1763,70 @@ impl StateMachine {
         }
     }
 
+    fn process_grant_table_privileges(
+        &self,
+        cluster: &str,
+        privileges: &[(TablePrivilege, String, String)],
+        role_name: &str,
+        tenant_name: &str,
+    ) -> MetaResult<()> {
+        let key = KeyPath::role(cluster, tenant_name, role_name);
+        if let Some(mut role) = self.get_struct::<CustomTenantRole<Oid>>(&key)? {
+            for (privilege, database_name, table_name) in privileges {
+                let key =
+                    KeyPath::tenant_schema_name(cluster, tenant_name, database_name, table_name);
+                if !self.contains_key(&key)? {
+                    return Err(MetaError::TableNotFound {
+                        table: format!("{database_name}.{table_name}"),
+                    });
+                }
+                let _ = role.grant_table_privilege(
+                    database_name.clone(),
+                    table_name.clone(),
+                    privilege.clone(),
+                );
+            }
+
+            Ok(self.insert(&key, &value_encode(&role)?)?)
+        } else {
+            Err(MetaError::RoleNotFound {
+                role: role_name.to_string(),
+            })
+        }
+    }
+
+    fn process_revoke_table_privileges(
+        &self,
+        cluster: &str,
+        privileges: &[(TablePrivilege, String, String)],
+        role_name: &str,
+        tenant_name: &str,
+    ) -> MetaResult<()> {
+        let key = KeyPath::role(cluster, tenant_name, role_name);
+        if let Some(mut role) = self.get_struct::<CustomTenantRole<Oid>>(&key)? {
+            for (privilege, database_name, table_name) in privileges {
+                if role
+                    .revoke_table_privilege(database_name, table_name, privilege)
+                    .is_err()
+                {
+                    return Err(MetaError::PrivilegeCannotRevoke {
+                        privilege: TenantObjectPrivilege::Table(
+                            privilege.clone(),
+                            database_name.to_string(),
+                            table_name.to_string(),
+                        ),
+                    });
+                }
+            }
+
+            Ok(self.insert(&key, &value_encode(&role)?)?)
+        } else {
+            Err(MetaError::RoleNotFound {
+                role: role_name.to_string(),
+            })
+        }
+    }
+
     fn process_limiter_request(
         &self,
         cluster: &str,
@@ -1,47 +1,196 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use config::tskv::{AuthConfig, GroupRoleConfig};
 use meta::model::MetaRef;
-use models::auth::user::{AuthType, User, UserInfo};
+use models::auth::role::{SystemTenantRole, TenantRoleIdentifier};
+use models::auth::user::{admin_user, AuthType, User, UserInfo};
 use models::auth::AuthError;
 use models::oid::{Identifier, Oid};
-use spi::query::auth::AccessControl;
-use trace::warn;
+use spi::query::auth::{AccessControl, AuthenticatorRef, Credentials, ExternalIdentity};
+use trace::{debug, warn};
+
+use super::ldap::LdapAuthenticator;
+use super::oidc::OidcAuthenticator;
 
 pub type Result<T> = std::result::Result<T, AuthError>;
 
+/// The LDAP and OpenID Connect authenticators of the config
+pub fn external_authenticators(config: &AuthConfig) -> Result<Vec<AuthenticatorRef>> {
+    let mut authenticators: Vec<AuthenticatorRef> = vec![];
+    if let Some(ldap) = &config.ldap {
+        authenticators.push(Arc::new(LdapAuthenticator::new(ldap)));
+    }
+    if let Some(oidc) = &config.oidc {
+        authenticators.push(Arc::new(OidcAuthenticator::new(oidc)?));
+    }
+    Ok(authenticators)
+}
+
 #[derive(Clone)]
 pub struct AccessControlImpl {
     inner: AccessControlNoCheck,
+    // verify the credentials if the local password or RSA key does not match
+    authenticators: Vec<AuthenticatorRef>,
+    group_roles: Vec<GroupRoleConfig>,
 }
 
 impl AccessControlImpl {
     pub fn new(inner: AccessControlNoCheck) -> Self {
-        Self { inner }
+        Self {
+            inner,
+            authenticators: vec![],
+            group_roles: vec![],
+        }
+    }
+
+    pub fn with_external_authenticators(
+        mut self,
+        authenticators: Vec<AuthenticatorRef>,
+        group_roles: Vec<GroupRoleConfig>,
+    ) -> Self {
+        self.authenticators = authenticators;
+        self.group_roles = group_roles;
+        self
+    }
+
+    async fn local_access_check(&self, user_info: &UserInfo, tenant_name: &str) -> Result<User> {
+        let user = self.inner.access_check(user_info, tenant_name).await?;
+        AuthType::from(user.desc().options()).access_check(user_info)?;
+        Ok(user)
+    }
+
+    async fn external_access_check(
+        &self,
+        credentials: Credentials<'_>,
+        tenant_name: &str,
+    ) -> Result<User> {
+        let mut last_err = None;
+        for authenticator in self.authenticators.iter() {
+            match authenticator.authenticate(credentials).await {
+                Ok(Some(identity)) => return self.external_user(identity, tenant_name).await,
+                Ok(None) => {}
+                Err(err) => last_err = Some(err),
+            }
+        }
+
+        Err(
+            last_err.unwrap_or_else(|| AuthError::ExternalAuthentication {
+                provider: "external".to_string(),
+                err: "the credentials are not supported".to_string(),
+            }),
+        )
+    }
+
+    /// The user verified by the external identity provider, which gets the privileges of
+    /// its member role in the tenant and the roles mapped from its groups.
+    async fn external_user(&self, identity: ExternalIdentity, tenant_name: &str) -> Result<User> {
+        let meta_manager = &self.inner.meta_manager;
+        let user_desc = meta_manager
+            .user(&identity.user)
+            .await
+            .map_err(|err| AuthError::Metadata {
+                err: err.to_string(),
+            })?
+            .ok_or_else(|| AuthError::UserNotFound {
+                user: identity.user.clone(),
+            })?;
+        if user_desc.is_admin() {
+            return Ok(admin_user(user_desc, None));
+        }
+
+        let client = meta_manager
+            .tenant_meta(tenant_name)
+            .await
+            .ok_or_else(|| AuthError::TenantNotFound)?;
+        let tenant_id = *client.tenant().id();
+        let metadata_err = |err: meta::error::MetaError| AuthError::Metadata {
+            err: err.to_string(),
+        };
+
+        let mut role = client
+            .member_role(user_desc.id(), true)
+            .await
+            .map_err(metadata_err)?;
+        let mut privileges = match role {
+            Some(_) => client
+                .user_privileges(&user_desc)
+                .await
+                .map_err(metadata_err)?,
+            None => HashSet::new(),
+        };
+
+        let group_roles = self
+            .group_roles
+            .iter()
+            .filter(|e| e.tenant == tenant_name && identity.groups.contains(&e.group));
+        for group_role in group_roles {
+            let (identifier, role_privileges) =
+                match SystemTenantRole::try_from(group_role.role.as_str()) {
+                    Ok(sys_role) => (
+                        TenantRoleIdentifier::System(sys_role.clone()),
+                        sys_role.to_privileges(&tenant_id),
+                    ),
+                    Err(_) => match client
+                        .custom_role(&group_role.role)
+                        .await
+                        .map_err(metadata_err)?
+                    {
+                        Some(custom_role) => (
+                            TenantRoleIdentifier::Custom(group_role.role.clone()),
+                            custom_role.to_privileges(&tenant_id),
+                        ),
+                        None => {
+                            warn!(
+                                "The role {} of group {} not found in tenant {}",
+                                group_role.role, group_role.group, tenant_name
+                            );
+                            continue;
+                        }
+                    },
+                };
+            privileges.extend(role_privileges);
+            role.get_or_insert(identifier);
+        }
+
+        if role.is_none() {
+            return Err(AuthError::MemberNotFound);
+        }
+        Ok(User::new(user_desc, privileges, role))
     }
 }
 
 #[async_trait::async_trait]
 impl AccessControl for AccessControlImpl {
     async fn access_check(&self, user_info: &UserInfo, tenant_name: &str) -> Result<User> {
-        let user = self
-            .inner
-            .access_check(user_info, tenant_name)
-            .await
-            .map_err(|_err| AuthError::AccessDenied {
-                user_name: user_info.user.clone(),
-                auth_type: "xxx".to_owned(),
-                err: "username or password invalid".to_owned(),
-            })?;
+        let mut user = self.local_access_check(user_info, tenant_name).await;
+        if user.is_err() && !self.authenticators.is_empty() {
+            user = self
+                .external_access_check(Credentials::Basic(user_info), tenant_name)
+                .await;
+        }
 
-        let user_options = user.desc().options();
-        // access check
-        AuthType::from(user_options)
-            .access_check(user_info)
-            .map_err(|_err| AuthError::AccessDenied {
+        user.map_err(|err| {
+            debug!("access check of user {}, error: {}", user_info.user, err);
+            AuthError::AccessDenied {
                 user_name: user_info.user.clone(),
                 auth_type: "xxx".to_owned(),
                 err: "username or password invalid".to_owned(),
-            })?;
+            }
+        })
+    }
 
-        Ok(user)
+    async fn access_check_token(&self, token: &str, tenant_name: &str) -> Result<User> {
+        self.external_access_check(Credentials::Bearer(token), tenant_name)
+            .await
+            .map_err(|err| {
+                debug!("access check of bearer token, error: {}", err);
+                AuthError::AccessDenied {
+                    user_name: "".to_owned(),
+                    auth_type: "bearer token".to_owned(),
+                    err: "token invalid".to_owned(),
+                }
+            })
     }
 
     async fn tenant_id(&self, tenant_name: &str) -> Result<Oid> {
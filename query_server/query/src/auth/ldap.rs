@@ -0,0 +1,251 @@
+use async_trait::async_trait;
+use config::tskv::LdapConfig;
+use ldap3::{
+    dn_escape, ldap_escape, Ldap, LdapConnAsync, LdapConnSettings, LdapError, Scope, SearchEntry,
+};
+use models::auth::AuthError;
+use spi::query::auth::{Authenticator, Credentials, ExternalIdentity, Result};
+use trace::warn;
+
+pub const LDAP: &str = "ldap";
+
+/// The LDAP server, the [`LdapAuthenticator`] can be tested with an in-process directory.
+#[async_trait]
+pub trait LdapDirectory {
+    /// Bind as the DN with the password, and return the groups of the DN.
+    async fn bind_and_search_groups(
+        &self,
+        dn: &str,
+        password: &str,
+    ) -> std::result::Result<Vec<String>, String>;
+}
+
+/// Verify the password of the basic authorization by binding to the LDAP server as the user.
+pub struct LdapAuthenticator<D> {
+    user_dn_template: String,
+    directory: D,
+}
+
+impl LdapAuthenticator<Ldap3Directory> {
+    pub fn new(config: &LdapConfig) -> Self {
+        Self::with_directory(config, Ldap3Directory::new(config.clone()))
+    }
+}
+
+impl<D> LdapAuthenticator<D> {
+    pub fn with_directory(config: &LdapConfig, directory: D) -> Self {
+        Self {
+            user_dn_template: config.user_dn_template.clone(),
+            directory,
+        }
+    }
+
+    fn user_dn(&self, user: &str) -> String {
+        self.user_dn_template.replace("{user}", &dn_escape(user))
+    }
+}
+
+#[async_trait]
+impl<D> Authenticator for LdapAuthenticator<D>
+where
+    D: LdapDirectory + Send + Sync,
+{
+    fn name(&self) -> &str {
+        LDAP
+    }
+
+    async fn authenticate(&self, credentials: Credentials<'_>) -> Result<Option<ExternalIdentity>> {
+        let Credentials::Basic(user_info) = credentials else {
+            return Ok(None);
+        };
+        // the bind with an empty password is an unauthenticated bind, which always succeeds
+        if user_info.password.is_empty() {
+            return Err(AuthError::ExternalAuthentication {
+                provider: LDAP.to_string(),
+                err: "empty password".to_string(),
+            });
+        }
+
+        let dn = self.user_dn(&user_info.user);
+        let groups = self
+            .directory
+            .bind_and_search_groups(&dn, &user_info.password)
+            .await
+            .map_err(|err| AuthError::ExternalAuthentication {
+                provider: LDAP.to_string(),
+                err,
+            })?;
+
+        Ok(Some(ExternalIdentity {
+            user: user_info.user.clone(),
+            groups,
+        }))
+    }
+}
+
+pub struct Ldap3Directory {
+    config: LdapConfig,
+}
+
+impl Ldap3Directory {
+    pub fn new(config: LdapConfig) -> Self {
+        Self { config }
+    }
+
+    async fn search_groups(
+        &self,
+        ldap: &mut Ldap,
+        dn: &str,
+        password: &str,
+    ) -> std::result::Result<Vec<String>, LdapError> {
+        ldap.with_timeout(self.config.timeout)
+            .simple_bind(dn, password)
+            .await?
+            .success()?;
+
+        let Some(base) = &self.config.group_search_base else {
+            return Ok(vec![]);
+        };
+        let filter = self
+            .config
+            .group_search_filter
+            .replace("{dn}", &ldap_escape(dn));
+        let (entries, _) = ldap
+            .with_timeout(self.config.timeout)
+            .search(
+                base,
+                Scope::Subtree,
+                &filter,
+                vec![self.config.group_attribute.as_str()],
+            )
+            .await?
+            .success()?;
+
+        let groups = entries
+            .into_iter()
+            .flat_map(|entry| {
+                let mut entry = SearchEntry::construct(entry);
+                entry
+                    .attrs
+                    .remove(&self.config.group_attribute)
+                    .unwrap_or_default()
+            })
+            .collect();
+        Ok(groups)
+    }
+}
+
+#[async_trait]
+impl LdapDirectory for Ldap3Directory {
+    async fn bind_and_search_groups(
+        &self,
+        dn: &str,
+        password: &str,
+    ) -> std::result::Result<Vec<String>, String> {
+        let settings = LdapConnSettings::new().set_conn_timeout(self.config.timeout);
+        let (conn, mut ldap) = LdapConnAsync::with_settings(settings, &self.config.url)
+            .await
+            .map_err(|err| err.to_string())?;
+        tokio::spawn(async move {
+            if let Err(err) = conn.drive().await {
+                warn!("LDAP connection error: {}", err);
+            }
+        });
+
+        let groups = self.search_groups(&mut ldap, dn, password).await;
+        let _ = ldap.unbind().await;
+        groups.map_err(|err| err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use async_trait::async_trait;
+    use config::tskv::LdapConfig;
+    use models::auth::user::UserInfo;
+    use spi::query::auth::{Authenticator, Credentials};
+
+    use super::{LdapAuthenticator, LdapDirectory};
+
+    /// In-process directory of the DNs, passwords and groups
+    struct LdapDirectoryStub {
+        entries: HashMap<String, (String, Vec<String>)>,
+    }
+
+    #[async_trait]
+    impl LdapDirectory for LdapDirectoryStub {
+        async fn bind_and_search_groups(
+            &self,
+            dn: &str,
+            password: &str,
+        ) -> Result<Vec<String>, String> {
+            match self.entries.get(dn) {
+                Some((pwd, groups)) if pwd == password => Ok(groups.clone()),
+                _ => Err("Invalid credentials".to_string()),
+            }
+        }
+    }
+
+    fn user_info(user: &str, password: &str) -> UserInfo {
+        UserInfo {
+            user: user.to_string(),
+            password: password.to_string(),
+            private_key: None,
+        }
+    }
+
+    fn authenticator() -> LdapAuthenticator<LdapDirectoryStub> {
+        let entries = HashMap::from([
+            (
+                "uid=alice,ou=people,dc=example,dc=com".to_string(),
+                ("secret".to_string(), vec!["dev".to_string()]),
+            ),
+            (
+                "uid=admin,ou=admins,ou=people,dc=example,dc=com".to_string(),
+                ("secret".to_string(), vec!["admins".to_string()]),
+            ),
+        ]);
+        LdapAuthenticator::with_directory(&LdapConfig::default(), LdapDirectoryStub { entries })
+    }
+
+    #[tokio::test]
+    async fn test_ldap_authenticate() {
+        let authenticator = authenticator();
+
+        let alice = user_info("alice", "secret");
+        let identity = authenticator
+            .authenticate(Credentials::Basic(&alice))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(identity.user, "alice");
+        assert_eq!(identity.groups, vec!["dev".to_string()]);
+
+        let wrong_password = user_info("alice", "wrong");
+        assert!(authenticator
+            .authenticate(Credentials::Basic(&wrong_password))
+            .await
+            .is_err());
+
+        let empty_password = user_info("alice", "");
+        assert!(authenticator
+            .authenticate(Credentials::Basic(&empty_password))
+            .await
+            .is_err());
+
+        // the special characters of the user name are escaped in the DN
+        let injected = user_info("admin,ou=admins", "secret");
+        assert!(authenticator
+            .authenticate(Credentials::Basic(&injected))
+            .await
+            .is_err());
+
+        assert!(authenticator
+            .authenticate(Credentials::Bearer("token"))
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
@@ -1 +1,4 @@
 pub mod auth_control;
+pub mod ldap;
+pub mod oidc;
+pub mod row_level_policy;
@@ -0,0 +1,342 @@
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use config::tskv::OidcConfig;
+use jsonwebtoken::jwk::{Jwk, JwkSet};
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use models::auth::AuthError;
+use parking_lot::Mutex;
+use serde_json::{Map, Value};
+use spi::query::auth::{Authenticator, Credentials, ExternalIdentity, Result};
+use trace::warn;
+
+pub const OIDC: &str = "oidc";
+
+/// Reload the key set at most once in this interval for the tokens signed by unknown keys.
+const MIN_JWKS_RELOAD_INTERVAL: Duration = Duration::from_secs(10);
+const JWKS_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+fn oidc_error(err: impl ToString) -> AuthError {
+    AuthError::ExternalAuthentication {
+        provider: OIDC.to_string(),
+        err: err.to_string(),
+    }
+}
+
+pub enum JwksSource {
+    File(PathBuf),
+    Url {
+        url: String,
+        client: reqwest::Client,
+    },
+}
+
+impl JwksSource {
+    pub fn new(config: &OidcConfig) -> Result<Self> {
+        match (&config.jwks_file, &config.jwks_url) {
+            (Some(path), None) => Ok(Self::File(PathBuf::from(path))),
+            (None, Some(url)) => {
+                let client = reqwest::Client::builder()
+                    .timeout(JWKS_REQUEST_TIMEOUT)
+                    .build()
+                    .map_err(oidc_error)?;
+                Ok(Self::Url {
+                    url: url.clone(),
+                    client,
+                })
+            }
+            _ => Err(oidc_error(
+                "exactly one of 'jwks_file' and 'jwks_url' must be set",
+            )),
+        }
+    }
+
+    async fn load(&self) -> std::result::Result<JwkSet, String> {
+        match self {
+            Self::File(path) => {
+                let content = tokio::fs::read(path)
+                    .await
+                    .map_err(|err| format!("read {}: {}", path.display(), err))?;
+                serde_json::from_slice(&content)
+                    .map_err(|err| format!("parse {}: {}", path.display(), err))
+            }
+            Self::Url { url, client } => client
+                .get(url)
+                .send()
+                .await
+                .and_then(|resp| resp.error_for_status())
+                .map_err(|err| format!("fetch {}: {}", url, err))?
+                .json::<JwkSet>()
+                .await
+                .map_err(|err| format!("parse {}: {}", url, err)),
+        }
+    }
+}
+
+struct CachedJwks {
+    jwks: Arc<JwkSet>,
+    loaded_at: Option<Instant>,
+    attempted_at: Option<Instant>,
+}
+
+/// Verify the JSON Web Tokens issued by the OpenID Connect provider with its JSON Web Key Set,
+/// the key set is reloaded after the refresh interval or if the token is signed by an unknown key.
+pub struct OidcAuthenticator {
+    config: OidcConfig,
+    algorithms: Vec<Algorithm>,
+    source: JwksSource,
+    cache: Mutex<CachedJwks>,
+}
+
+impl OidcAuthenticator {
+    pub fn new(config: &OidcConfig) -> Result<Self> {
+        let algorithms = config
+            .algorithms
+            .iter()
+            .map(|alg| {
+                Algorithm::from_str(alg)
+                    .map_err(|_| oidc_error(format!("unsupported algorithm '{}'", alg)))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            config: config.clone(),
+            algorithms,
+            source: JwksSource::new(config)?,
+            cache: Mutex::new(CachedJwks {
+                jwks: Arc::new(JwkSet { keys: vec![] }),
+                loaded_at: None,
+                attempted_at: None,
+            }),
+        })
+    }
+
+    async fn jwks(&self, force_reload: bool) -> Arc<JwkSet> {
+        let jwks = {
+            let mut cache = self.cache.lock();
+            let expired = cache
+                .loaded_at
+                .map_or(true, |t| t.elapsed() >= self.config.jwks_refresh_interval);
+            let throttled = cache
+                .attempted_at
+                .is_some_and(|t| t.elapsed() < MIN_JWKS_RELOAD_INTERVAL);
+            if !(expired || force_reload) || throttled {
+                return cache.jwks.clone();
+            }
+            cache.attempted_at = Some(Instant::now());
+            cache.jwks.clone()
+        };
+
+        match self.source.load().await {
+            Ok(loaded) => {
+                let loaded = Arc::new(loaded);
+                let mut cache = self.cache.lock();
+                cache.jwks = loaded.clone();
+                cache.loaded_at = Some(Instant::now());
+                loaded
+            }
+            Err(err) => {
+                warn!("Failed to load the JSON Web Key Set, {}", err);
+                jwks
+            }
+        }
+    }
+
+    fn find_key<'a>(jwks: &'a JwkSet, kid: Option<&str>) -> Option<&'a Jwk> {
+        match kid {
+            Some(kid) => jwks.find(kid),
+            // the key id is optional if there is only one key
+            None if jwks.keys.len() == 1 => jwks.keys.first(),
+            None => None,
+        }
+    }
+
+    fn validation(&self, alg: Algorithm) -> Validation {
+        let mut validation = Validation::new(alg);
+        if let Some(issuer) = &self.config.issuer {
+            validation.set_issuer(&[issuer]);
+        }
+        match &self.config.audience {
+            Some(audience) => validation.set_audience(&[audience]),
+            None => validation.validate_aud = false,
+        }
+        validation
+    }
+
+    fn identity(&self, claims: &Map<String, Value>) -> Result<ExternalIdentity> {
+        let user = claims
+            .get(&self.config.user_claim)
+            .and_then(Value::as_str)
+            .ok_or_else(|| oidc_error(format!("claim '{}' not found", self.config.user_claim)))?;
+        let groups = match claims.get(&self.config.groups_claim) {
+            Some(Value::Array(groups)) => groups
+                .iter()
+                .filter_map(Value::as_str)
+                .map(|e| e.to_string())
+                .collect(),
+            Some(Value::String(group)) => vec![group.clone()],
+            _ => vec![],
+        };
+
+        Ok(ExternalIdentity {
+            user: user.to_string(),
+            groups,
+        })
+    }
+}
+
+#[async_trait]
+impl Authenticator for OidcAuthenticator {
+    fn name(&self) -> &str {
+        OIDC
+    }
+
+    async fn authenticate(&self, credentials: Credentials<'_>) -> Result<Option<ExternalIdentity>> {
+        let Credentials::Bearer(token) = credentials else {
+            return Ok(None);
+        };
+
+        let header = decode_header(token).map_err(oidc_error)?;
+        // the algorithm of the token is restricted, e.g. a RSA public key can not be used as a HMAC secret
+        if !self.algorithms.contains(&header.alg) {
+            return Err(oidc_error(format!(
+                "unsupported algorithm {:?}",
+                header.alg
+            )));
+        }
+
+        let kid = header.kid.as_deref();
+        let mut jwks = self.jwks(false).await;
+        if Self::find_key(&jwks, kid).is_none() {
+            // the keys may be rotated
+            jwks = self.jwks(true).await;
+        }
+        let jwk = Self::find_key(&jwks, kid)
+            .ok_or_else(|| oidc_error(format!("signing key {:?} not found", kid)))?;
+        let key = DecodingKey::from_jwk(jwk).map_err(oidc_error)?;
+
+        let token_data = decode::<Map<String, Value>>(token, &key, &self.validation(header.alg))
+            .map_err(oidc_error)?;
+
+        self.identity(&token_data.claims).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Write;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use base64::prelude::{Engine, BASE64_URL_SAFE_NO_PAD};
+    use config::tskv::OidcConfig;
+    use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
+    use models::auth::user::UserInfo;
+    use serde_json::json;
+    use spi::query::auth::{Authenticator, Credentials};
+
+    use super::OidcAuthenticator;
+
+    const SECRET: &[u8] = b"cnosdb-oidc-test-secret";
+
+    /// Sign the claims like an in-process identity provider
+    fn token(kid: &str, secret: &[u8], claims: serde_json::Value) -> String {
+        let mut header = Header::new(Algorithm::HS256);
+        header.kid = Some(kid.to_string());
+        encode(&header, &claims, &EncodingKey::from_secret(secret)).unwrap()
+    }
+
+    fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    }
+
+    fn authenticator(jwks_file: &tempfile::NamedTempFile) -> OidcAuthenticator {
+        let config = OidcConfig {
+            issuer: Some("https://sso.example.com".to_string()),
+            audience: Some("cnosdb".to_string()),
+            jwks_file: Some(jwks_file.path().to_string_lossy().to_string()),
+            algorithms: vec!["HS256".to_string()],
+            user_claim: "preferred_username".to_string(),
+            ..Default::default()
+        };
+        OidcAuthenticator::new(&config).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_oidc_authenticate() {
+        let jwks = json!({
+            "keys": [{
+                "kty": "oct",
+                "kid": "k1",
+                "alg": "HS256",
+                "k": BASE64_URL_SAFE_NO_PAD.encode(SECRET),
+            }]
+        });
+        let mut jwks_file = tempfile::NamedTempFile::new().unwrap();
+        jwks_file.write_all(jwks.to_string().as_bytes()).unwrap();
+        let authenticator = authenticator(&jwks_file);
+
+        let claims = json!({
+            "iss": "https://sso.example.com",
+            "aud": "cnosdb",
+            "exp": now() + 600,
+            "preferred_username": "alice",
+            "groups": ["dev", "ops"],
+        });
+        let identity = authenticator
+            .authenticate(Credentials::Bearer(&token("k1", SECRET, claims.clone())))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(identity.user, "alice");
+        assert_eq!(identity.groups, vec!["dev".to_string(), "ops".to_string()]);
+
+        // signed by another key
+        let forged = token("k1", b"another-secret", claims.clone());
+        assert!(authenticator
+            .authenticate(Credentials::Bearer(&forged))
+            .await
+            .is_err());
+
+        // unknown key id
+        let unknown_key = token("k2", SECRET, claims.clone());
+        assert!(authenticator
+            .authenticate(Credentials::Bearer(&unknown_key))
+            .await
+            .is_err());
+
+        // expired
+        let mut expired_claims = claims.clone();
+        expired_claims["exp"] = json!(now() - 600);
+        let expired = token("k1", SECRET, expired_claims);
+        assert!(authenticator
+            .authenticate(Credentials::Bearer(&expired))
+            .await
+            .is_err());
+
+        // issued for another client
+        let mut other_audience_claims = claims;
+        other_audience_claims["aud"] = json!("another");
+        let other_audience = token("k1", SECRET, other_audience_claims);
+        assert!(authenticator
+            .authenticate(Credentials::Bearer(&other_audience))
+            .await
+            .is_err());
+
+        let user_info = UserInfo {
+            user: "alice".to_string(),
+            password: "secret".to_string(),
+            private_key: None,
+        };
+        assert!(authenticator
+            .authenticate(Credentials::Basic(&user_info))
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
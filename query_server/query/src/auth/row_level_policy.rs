@@ -0,0 +1,200 @@
+//! Row-level security policies created by `CREATE POLICY`.
+//!
+//! The scans of the tables having policies are filtered by the `USING` expressions of the
+//! policies that apply to the role of the current user, see [`RowLevelPolicyRewriter`].
+
+use std::sync::Arc;
+
+use datafusion::common::tree_node::{Transformed, TreeNode, VisitRecursion};
+use datafusion::common::{DFSchema, Result as DFResult};
+use datafusion::logical_expr::expr::{Exists, InSubquery};
+use datafusion::logical_expr::utils::from_plan;
+use datafusion::logical_expr::{lit, Filter, LogicalPlan, Subquery, TableScan};
+use datafusion::prelude::Expr;
+use datafusion::sql::planner::{PlannerContext, SqlToRel};
+use models::auth::user::User;
+use models::oid::Oid;
+use models::schema::policy_schema::PolicySchema;
+use spi::QueryResult;
+
+use crate::data_source::source_downcast_adapter;
+use crate::metadata::ContextProviderExtension;
+use crate::sql::sql_function::parse_sql_function_body;
+
+/// Filter the scanned rows by the row-level security policies on the tables.
+///
+/// Users having all privileges on a table are not restricted by its policies. For other users,
+/// a row is visible if any of the policies applying to their role accepts it, and no row is
+/// visible if none of the policies applies.
+pub struct RowLevelPolicyRewriter<'a, 'b, S: ContextProviderExtension> {
+    schema_provider: &'a S,
+    df_planner: &'a SqlToRel<'b, S>,
+    user: &'a User,
+    tenant_id: Oid,
+}
+
+impl<'a, 'b, S: ContextProviderExtension> RowLevelPolicyRewriter<'a, 'b, S> {
+    pub fn new(
+        schema_provider: &'a S,
+        df_planner: &'a SqlToRel<'b, S>,
+        user: &'a User,
+        tenant_id: Oid,
+    ) -> Self {
+        Self {
+            schema_provider,
+            df_planner,
+            user,
+            tenant_id,
+        }
+    }
+
+    pub fn rewrite(&self, plan: LogicalPlan) -> QueryResult<LogicalPlan> {
+        Ok(self.rewrite_plan(plan)?)
+    }
+
+    fn rewrite_plan(&self, plan: LogicalPlan) -> DFResult<LogicalPlan> {
+        plan.transform_up(&|plan| match plan {
+            LogicalPlan::TableScan(scan) => self.filter_scan(scan),
+            plan => self.rewrite_subqueries(plan),
+        })
+    }
+
+    fn filter_scan(&self, scan: TableScan) -> DFResult<Transformed<LogicalPlan>> {
+        match self.policy_predicate(&scan)? {
+            Some(predicate) => {
+                let input = Arc::new(LogicalPlan::TableScan(scan));
+                let filter = Filter::try_new(predicate, input)?;
+                Ok(Transformed::Yes(LogicalPlan::Filter(filter)))
+            }
+            None => Ok(Transformed::No(LogicalPlan::TableScan(scan))),
+        }
+    }
+
+    /// The predicate of the policies on the scanned table, None if the user is not restricted
+    fn policy_predicate(&self, scan: &TableScan) -> DFResult<Option<Expr>> {
+        let Ok(adapter) = source_downcast_adapter(&scan.source) else {
+            return Ok(None);
+        };
+        self.table_predicate(
+            adapter.database_name(),
+            adapter.table_name(),
+            &scan.projected_schema,
+        )
+    }
+
+    /// Whether the rows of the table visible to the user are restricted by its policies
+    pub fn is_restricted(&self, database: &str, table: &str) -> bool {
+        !self
+            .schema_provider
+            .get_table_policies(database, table)
+            .is_empty()
+            && !self
+                .user
+                .can_bypass_row_policies(self.tenant_id, database, table)
+    }
+
+    /// The predicate of the policies on the table planned against `schema`,
+    /// None if the user is not restricted
+    pub fn table_predicate(
+        &self,
+        database: &str,
+        table: &str,
+        schema: &DFSchema,
+    ) -> DFResult<Option<Expr>> {
+        let policies = self.schema_provider.get_table_policies(database, table);
+        if policies.is_empty()
+            || self
+                .user
+                .can_bypass_row_policies(self.tenant_id, database, table)
+        {
+            return Ok(None);
+        }
+
+        let predicate = policies
+            .iter()
+            .filter(|policy| policy.applies_to(self.user.role()))
+            .map(|policy| self.policy_to_expr(policy, schema))
+            .collect::<DFResult<Vec<_>>>()?
+            .into_iter()
+            .reduce(Expr::or)
+            .unwrap_or_else(|| lit(false));
+
+        Ok(Some(predicate))
+    }
+
+    fn policy_to_expr(&self, policy: &PolicySchema, schema: &DFSchema) -> DFResult<Expr> {
+        self.df_planner.sql_to_expr(
+            parse_sql_function_body(&policy.using)?,
+            schema,
+            &mut PlannerContext::new(),
+        )
+    }
+
+    /// The tables scanned in the subqueries are filtered too
+    fn rewrite_subqueries(&self, plan: LogicalPlan) -> DFResult<Transformed<LogicalPlan>> {
+        let exprs = plan.expressions();
+        if !exprs.iter().any(contains_subquery) {
+            return Ok(Transformed::No(plan));
+        }
+
+        let new_exprs = exprs
+            .into_iter()
+            .map(|expr| expr.transform_up(&|expr| self.rewrite_subquery_expr(expr)))
+            .collect::<DFResult<Vec<_>>>()?;
+        let inputs = plan.inputs().into_iter().cloned().collect::<Vec<_>>();
+
+        Ok(Transformed::Yes(from_plan(&plan, &new_exprs, &inputs)?))
+    }
+
+    fn rewrite_subquery_expr(&self, expr: Expr) -> DFResult<Transformed<Expr>> {
+        let expr = match expr {
+            Expr::ScalarSubquery(subquery) => {
+                Expr::ScalarSubquery(self.rewrite_subquery(subquery)?)
+            }
+            Expr::InSubquery(InSubquery {
+                expr,
+                subquery,
+                negated,
+            }) => Expr::InSubquery(InSubquery {
+                expr,
+                subquery: self.rewrite_subquery(subquery)?,
+                negated,
+            }),
+            Expr::Exists(Exists { subquery, negated }) => Expr::Exists(Exists {
+                subquery: self.rewrite_subquery(subquery)?,
+                negated,
+            }),
+            expr => return Ok(Transformed::No(expr)),
+        };
+        Ok(Transformed::Yes(expr))
+    }
+
+    fn rewrite_subquery(&self, subquery: Subquery) -> DFResult<Subquery> {
+        let Subquery {
+            subquery,
+            outer_ref_columns,
+        } = subquery;
+        let plan = self.rewrite_plan(subquery.as_ref().clone())?;
+
+        Ok(Subquery {
+            subquery: Arc::new(plan),
+            outer_ref_columns,
+        })
+    }
+}
+
+/// Whether the expression contains a subquery
+pub fn contains_subquery(expr: &Expr) -> bool {
+    let mut found = false;
+    let _ = expr.apply(&mut |e| {
+        if matches!(
+            e,
+            Expr::ScalarSubquery(_) | Expr::InSubquery(_) | Expr::Exists(_)
+        ) {
+            found = true;
+            return Ok(VisitRecursion::Stop);
+        }
+        Ok(VisitRecursion::Continue)
+    });
+    found
+}
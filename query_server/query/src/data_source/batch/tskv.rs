@@ -20,15 +20,18 @@ use datafusion::optimizer::utils::{conjunction, split_conjunction};
 use datafusion::physical_expr::PhysicalExpr;
 use datafusion::physical_plan::empty::EmptyExec;
 use datafusion::physical_plan::memory::MemoryExec;
-use datafusion::physical_plan::{project_schema, ExecutionPlan};
-use datafusion::prelude::Column;
+use datafusion::physical_plan::{project_schema, ExecutionPlan, Statistics};
+use datafusion::prelude::{col, lit, Column};
+use datafusion::scalar::ScalarValue;
 use meta::error::MetaError;
 use meta::model::MetaClientRef;
 use models::arrow::{DataType, Field, Schema};
-use models::predicate::domain::{Predicate, PredicateRef, PushedAggregateFunction};
+use models::predicate::domain::{Predicate, PredicateRef, PushedAggregateFunction, TimeRanges};
+use models::predicate::transformation::RowExpressionToDomainsVisitor;
 use models::schema::tskv_table_schema::{TskvTableSchema, TskvTableSchemaRef};
 use models::schema::TIME_FIELD_NAME;
 use trace::debug;
+use utils::precision::Precision;
 
 use crate::data_source::batch::filter_expr_rewriter::{has_udf_function, rewrite_filters};
 use crate::data_source::sink::tskv::TskvRecordBatchSinkProvider;
@@ -271,6 +274,17 @@ impl ClusterTable {
         }
     }
 
+    /// Returns the filter `time >= <min timestamp>` if the table has TTL.
+    fn ttl_filter(&self) -> Option<Expr> {
+        let min_ts = self.schema.time_to_expired()?;
+        let min_ts = match self.schema.time_column_precision() {
+            Precision::MS => ScalarValue::TimestampMillisecond(Some(min_ts), None),
+            Precision::US => ScalarValue::TimestampMicrosecond(Some(min_ts), None),
+            Precision::NS => ScalarValue::TimestampNanosecond(Some(min_ts), None),
+        };
+        Some(col(Column::from_name(TIME_FIELD_NAME)).gt_eq(lit(min_ts)))
+    }
+
     pub fn table_schema(&self) -> TskvTableSchemaRef {
         self.schema.clone()
     }
@@ -297,6 +311,13 @@ impl TableProvider for ClusterTable {
         TableType::Base
     }
 
+    fn statistics(&self) -> Option<Statistics> {
+        let schema = self.schema.to_arrow_schema();
+        self.schema
+            .statistics()
+            .map(|s| s.to_df_statistics(&self.schema, &schema, &TimeRanges::all()))
+    }
+
     async fn scan(
         &self,
         ctx: &SessionState,
@@ -331,10 +352,15 @@ impl TableProvider for ClusterTable {
 
         let filters = rewrite_filters(filters, df_schema.clone())?;
         // Generate physical expressions using projected schema
-        let filter = Arc::new(
-            Predicate::push_down_filter(filters, &df_schema, &arrow_schema, limit)
-                .map_err(|e| DataFusionError::External(Box::new(e)))?,
-        );
+        let mut predicate = Predicate::push_down_filter(filters, &df_schema, &arrow_schema, limit)
+            .map_err(|e| DataFusionError::External(Box::new(e)))?;
+        // Expired rows of the table are never returned, even if the time column is not projected.
+        if let Some(ttl_filter) = self.ttl_filter() {
+            let ttl_domains = RowExpressionToDomainsVisitor::expr_to_column_domains(&ttl_filter)
+                .map_err(|e| DataFusionError::External(Box::new(e)))?;
+            predicate = predicate.intersect_domains(&ttl_domains);
+        }
+        let filter = Arc::new(predicate);
 
         if let Some(agg_with_grouping) = agg_with_grouping {
             debug!("Create aggregate filter tskv scan.");
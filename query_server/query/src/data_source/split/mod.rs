@@ -6,6 +6,7 @@ use datafusion::sql::TableReference;
 use models::object_reference::Resolve;
 use models::predicate::PlacedSplit;
 use snafu::ResultExt;
+use spi::query::config::ScanVnodes;
 use spi::{AnalyzePushedFilterSnafu, CoordinatorSnafu, QueryResult};
 use trace::debug;
 
@@ -33,7 +34,7 @@ impl SplitManager {
 
     pub async fn splits(
         &self,
-        _ctx: &SessionState,
+        ctx: &SessionState,
         table_layout: TableLayoutHandle,
     ) -> QueryResult<Vec<PlacedSplit>> {
         let TableLayoutHandle {
@@ -53,12 +54,17 @@ impl SplitManager {
             .resolve(&table)
             .context(AnalyzePushedFilterSnafu)?;
 
-        let shards = self
+        let mut shards = self
             .coord
             .table_vnodes(&table_name, resolved_predicate.clone())
             .await
             .context(CoordinatorSnafu)?;
 
+        // Only scan the vnodes of the partition if the query is split by vnodes
+        if let Some(ScanVnodes(vnodes)) = ctx.config().get_extension::<ScanVnodes>().as_deref() {
+            shards.retain(|shard| shard.vnodes.iter().any(|v| vnodes.contains(&v.id)));
+        }
+
         let splits = shards
             .into_iter()
             .enumerate()
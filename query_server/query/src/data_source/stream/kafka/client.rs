@@ -0,0 +1,106 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use rskafka::client::partition::{OffsetAt, PartitionClient, UnknownTopicHandling};
+use rskafka::client::ClientBuilder;
+use spi::QueryError;
+
+use super::kafka_error;
+
+/// Max time the broker waits for new records before answering a fetch request
+const FETCH_MAX_WAIT_MS: i32 = 500;
+
+pub type KafkaConnectorRef = Arc<dyn KafkaConnector + Send + Sync>;
+pub type PartitionConsumerRef = Arc<dyn PartitionConsumer + Send + Sync>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KafkaRecord {
+    pub offset: i64,
+    pub value: Option<Vec<u8>>,
+}
+
+/// Connect to the brokers of a Kafka-protocol cluster
+#[async_trait]
+pub trait KafkaConnector {
+    async fn connect(
+        &self,
+        brokers: &[String],
+        topic: &str,
+        partition: i32,
+    ) -> Result<PartitionConsumerRef, QueryError>;
+}
+
+/// Read the records of a topic partition
+#[async_trait]
+pub trait PartitionConsumer {
+    /// Offset of the first record available in the partition
+    async fn earliest_offset(&self) -> Result<i64, QueryError>;
+
+    /// Offset of the next record to be written to the partition
+    async fn high_watermark(&self) -> Result<i64, QueryError>;
+
+    /// Fetch the records starting at `offset`, at most about `max_bytes`.
+    /// Returns an empty list if there is no record at `offset` yet.
+    async fn fetch(&self, offset: i64, max_bytes: i32) -> Result<Vec<KafkaRecord>, QueryError>;
+}
+
+#[derive(Debug, Default)]
+pub struct RsKafkaConnector {}
+
+#[async_trait]
+impl KafkaConnector for RsKafkaConnector {
+    async fn connect(
+        &self,
+        brokers: &[String],
+        topic: &str,
+        partition: i32,
+    ) -> Result<PartitionConsumerRef, QueryError> {
+        let client = ClientBuilder::new(brokers.to_vec())
+            .build()
+            .await
+            .map_err(kafka_error)?;
+        let client = client
+            .partition_client(topic, partition, UnknownTopicHandling::Error)
+            .await
+            .map_err(kafka_error)?;
+
+        Ok(Arc::new(RsKafkaPartitionConsumer { client }))
+    }
+}
+
+struct RsKafkaPartitionConsumer {
+    client: PartitionClient,
+}
+
+#[async_trait]
+impl PartitionConsumer for RsKafkaPartitionConsumer {
+    async fn earliest_offset(&self) -> Result<i64, QueryError> {
+        self.client
+            .get_offset(OffsetAt::Earliest)
+            .await
+            .map_err(kafka_error)
+    }
+
+    async fn high_watermark(&self) -> Result<i64, QueryError> {
+        self.client
+            .get_offset(OffsetAt::Latest)
+            .await
+            .map_err(kafka_error)
+    }
+
+    async fn fetch(&self, offset: i64, max_bytes: i32) -> Result<Vec<KafkaRecord>, QueryError> {
+        let (records, _high_watermark) = self
+            .client
+            .fetch_records(offset, 1..max_bytes, FETCH_MAX_WAIT_MS)
+            .await
+            .map_err(kafka_error)?;
+
+        Ok(records
+            .into_iter()
+            .map(|r| KafkaRecord {
+                offset: r.offset,
+                value: r.record.value,
+            })
+            .collect())
+    }
+}
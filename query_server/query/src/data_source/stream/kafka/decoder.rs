@@ -0,0 +1,459 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use apache_avro::types::Value as AvroValue;
+use apache_avro::Schema as AvroSchema;
+use chrono::DateTime;
+use datafusion::arrow::array::new_empty_array;
+use datafusion::arrow::datatypes::{DataType, SchemaRef, TimeUnit};
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::common::Result as DFResult;
+use datafusion::scalar::ScalarValue;
+use protocol_parser::line_protocol::line_protocol_to_lines;
+use protos::FieldValue;
+use utils::precision::Precision;
+
+/// Format of the messages of a topic
+#[derive(Debug, Clone)]
+pub enum MessageFormat {
+    LineProtocol,
+    Json,
+    Avro(Box<AvroSchema>),
+}
+
+impl MessageFormat {
+    pub fn try_new(format: &str, avro_schema: Option<&str>) -> Result<Self, String> {
+        match format.to_ascii_lowercase().as_str() {
+            "line_protocol" => Ok(Self::LineProtocol),
+            "json" => Ok(Self::Json),
+            "avro" => {
+                let schema = avro_schema.ok_or("avro format requires an avro schema")?;
+                let schema = AvroSchema::parse_str(schema).map_err(|e| e.to_string())?;
+                Ok(Self::Avro(Box::new(schema)))
+            }
+            other => Err(format!(
+                "unsupported format '{other}', expected one of line_protocol, json, avro"
+            )),
+        }
+    }
+}
+
+/// Value decoded from a message, converted to the type of the column when building the batch
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Boolean(bool),
+    Int(i64),
+    UInt(u64),
+    Float(f64),
+    String(String),
+    /// Nanoseconds since the epoch
+    Timestamp(i64),
+}
+
+/// Decode the messages of a topic into the rows of a stream table
+pub struct MessageDecoder {
+    format: MessageFormat,
+    /// Column receiving the timestamp of line protocol
+    time_column: String,
+}
+
+impl MessageDecoder {
+    pub fn new(format: MessageFormat, time_column: impl Into<String>) -> Self {
+        Self {
+            format,
+            time_column: time_column.into(),
+        }
+    }
+
+    /// Decode a message, which may contain several rows
+    pub fn decode(&self, message: &[u8]) -> Result<Vec<HashMap<String, Value>>, String> {
+        match &self.format {
+            MessageFormat::LineProtocol => self.decode_line_protocol(message),
+            MessageFormat::Json => decode_json(message),
+            MessageFormat::Avro(schema) => decode_avro(schema, message).map(|row| vec![row]),
+        }
+    }
+
+    fn decode_line_protocol(&self, message: &[u8]) -> Result<Vec<HashMap<String, Value>>, String> {
+        let lines = std::str::from_utf8(message).map_err(|e| e.to_string())?;
+        let lines = line_protocol_to_lines(lines, 0).map_err(|e| e.to_string())?;
+
+        Ok(lines
+            .into_iter()
+            .map(|line| {
+                let mut row = HashMap::with_capacity(line.tags.len() + line.fields.len() + 1);
+                for (k, v) in line.tags {
+                    row.insert(k.to_string(), Value::String(v.to_string()));
+                }
+                for (k, v) in line.fields {
+                    let v = match v {
+                        FieldValue::U64(v) => Value::UInt(v),
+                        FieldValue::I64(v) => Value::Int(v),
+                        FieldValue::Str(v) => Value::String(String::from_utf8_lossy(&v).into()),
+                        FieldValue::F64(v) => Value::Float(v),
+                        FieldValue::Bool(v) => Value::Boolean(v),
+                    };
+                    row.insert(k.to_string(), v);
+                }
+                row.insert(self.time_column.clone(), Value::Timestamp(line.timestamp));
+                row
+            })
+            .collect())
+    }
+}
+
+/// A message contains one or more JSON objects
+fn decode_json(message: &[u8]) -> Result<Vec<HashMap<String, Value>>, String> {
+    serde_json::Deserializer::from_slice(message)
+        .into_iter::<serde_json::Map<String, serde_json::Value>>()
+        .map(|object| {
+            let object = object.map_err(|e| e.to_string())?;
+            Ok(object
+                .into_iter()
+                .map(|(k, v)| {
+                    let v = match v {
+                        serde_json::Value::Null => Value::Null,
+                        serde_json::Value::Bool(v) => Value::Boolean(v),
+                        serde_json::Value::Number(v) => {
+                            if let Some(v) = v.as_i64() {
+                                Value::Int(v)
+                            } else if let Some(v) = v.as_u64() {
+                                Value::UInt(v)
+                            } else {
+                                Value::Float(v.as_f64().unwrap_or(f64::NAN))
+                            }
+                        }
+                        serde_json::Value::String(v) => Value::String(v),
+                        v => Value::String(v.to_string()),
+                    };
+                    (k, v)
+                })
+                .collect())
+        })
+        .collect()
+}
+
+/// A message contains a single avro datum, without the schema
+fn decode_avro(schema: &AvroSchema, message: &[u8]) -> Result<HashMap<String, Value>, String> {
+    let mut reader = message;
+    match apache_avro::from_avro_datum(schema, &mut reader, None).map_err(|e| e.to_string())? {
+        AvroValue::Record(fields) => fields
+            .into_iter()
+            .map(|(k, v)| Ok((k, avro_to_value(v)?)))
+            .collect(),
+        other => Err(format!("expect an avro record, but found {other:?}")),
+    }
+}
+
+fn avro_to_value(value: AvroValue) -> Result<Value, String> {
+    let value = match value {
+        AvroValue::Null => Value::Null,
+        AvroValue::Boolean(v) => Value::Boolean(v),
+        AvroValue::Int(v) => Value::Int(v as i64),
+        AvroValue::Long(v) => Value::Int(v),
+        AvroValue::Float(v) => Value::Float(v as f64),
+        AvroValue::Double(v) => Value::Float(v),
+        AvroValue::String(v) | AvroValue::Enum(_, v) => Value::String(v),
+        AvroValue::TimestampMillis(v) => Value::Timestamp(v.saturating_mul(1_000_000)),
+        AvroValue::TimestampMicros(v) => Value::Timestamp(v.saturating_mul(1_000)),
+        AvroValue::Union(_, v) => avro_to_value(*v)?,
+        other => return Err(format!("unsupported avro value {other:?}")),
+    };
+
+    Ok(value)
+}
+
+/// Build a [`RecordBatch`] of the schema from decoded rows, missing columns are null.
+pub struct BatchBuilder {
+    schema: SchemaRef,
+    columns: Vec<Vec<ScalarValue>>,
+}
+
+impl BatchBuilder {
+    pub fn new(schema: SchemaRef) -> Self {
+        let columns = vec![vec![]; schema.fields().len()];
+        Self { schema, columns }
+    }
+
+    pub fn append_row(&mut self, mut row: HashMap<String, Value>) -> Result<(), String> {
+        // Convert the whole row first, so that a bad row leaves no partial columns
+        let values = self
+            .schema
+            .fields()
+            .iter()
+            .map(|f| {
+                let value = row.remove(f.name()).unwrap_or(Value::Null);
+                if value == Value::Null && !f.is_nullable() {
+                    return Err(format!("missing value of column {}", f.name()));
+                }
+                to_scalar(value, f.data_type())
+                    .map_err(|e| format!("invalid value of column {}: {e}", f.name()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.columns
+            .iter_mut()
+            .zip(values)
+            .for_each(|(column, value)| column.push(value));
+
+        Ok(())
+    }
+
+    pub fn finish(self) -> DFResult<RecordBatch> {
+        let arrays = self
+            .columns
+            .into_iter()
+            .zip(self.schema.fields())
+            .map(|(column, field)| {
+                if column.is_empty() {
+                    Ok(new_empty_array(field.data_type()))
+                } else {
+                    ScalarValue::iter_to_array(column)
+                }
+            })
+            .collect::<DFResult<Vec<_>>>()?;
+
+        Ok(RecordBatch::try_new(self.schema, arrays)?)
+    }
+}
+
+fn parse<T: FromStr>(v: &str) -> Result<T, String>
+where
+    T::Err: ToString,
+{
+    v.trim().parse::<T>().map_err(|e| e.to_string())
+}
+
+fn to_scalar(value: Value, data_type: &DataType) -> Result<ScalarValue, String> {
+    if value == Value::Null {
+        return ScalarValue::try_from(data_type).map_err(|e| e.to_string());
+    }
+
+    let scalar = match data_type {
+        DataType::Boolean => ScalarValue::Boolean(Some(match value {
+            Value::Boolean(v) => v,
+            Value::String(v) => parse(&v)?,
+            v => return Err(format!("{v:?} is not a boolean")),
+        })),
+        DataType::Int64 => ScalarValue::Int64(Some(match value {
+            Value::Int(v) | Value::Timestamp(v) => v,
+            Value::UInt(v) => i64::try_from(v).map_err(|e| e.to_string())?,
+            Value::Float(v) if v.fract() == 0.0 => v as i64,
+            Value::String(v) => parse(&v)?,
+            v => return Err(format!("{v:?} is not a bigint")),
+        })),
+        DataType::UInt64 => ScalarValue::UInt64(Some(match value {
+            Value::UInt(v) => v,
+            Value::Int(v) => u64::try_from(v).map_err(|e| e.to_string())?,
+            Value::Float(v) if v.fract() == 0.0 && v >= 0.0 => v as u64,
+            Value::String(v) => parse(&v)?,
+            v => return Err(format!("{v:?} is not a bigint unsigned")),
+        })),
+        DataType::Float64 => ScalarValue::Float64(Some(match value {
+            Value::Float(v) => v,
+            Value::Int(v) => v as f64,
+            Value::UInt(v) => v as f64,
+            Value::String(v) => parse(&v)?,
+            v => return Err(format!("{v:?} is not a double")),
+        })),
+        DataType::Utf8 => ScalarValue::Utf8(Some(match value {
+            Value::String(v) => v,
+            Value::Boolean(v) => v.to_string(),
+            Value::Int(v) | Value::Timestamp(v) => v.to_string(),
+            Value::UInt(v) => v.to_string(),
+            Value::Float(v) => v.to_string(),
+            Value::Null => unreachable!(),
+        })),
+        DataType::Timestamp(unit, tz) => {
+            let precision = match unit {
+                TimeUnit::Second => return Err("timestamp(s) is unsupported".to_string()),
+                TimeUnit::Millisecond => Precision::MS,
+                TimeUnit::Microsecond => Precision::US,
+                TimeUnit::Nanosecond => Precision::NS,
+            };
+            let v = match value {
+                // Integers are in the unit of the column
+                Value::Int(v) => v,
+                Value::UInt(v) => i64::try_from(v).map_err(|e| e.to_string())?,
+                Value::Timestamp(ns) => from_nanos(ns, precision),
+                Value::String(v) => {
+                    let ns = DateTime::parse_from_rfc3339(v.trim())
+                        .map_err(|e| e.to_string())?
+                        .timestamp_nanos_opt()
+                        .ok_or("timestamp out of range")?;
+                    from_nanos(ns, precision)
+                }
+                v => return Err(format!("{v:?} is not a timestamp")),
+            };
+            match precision {
+                Precision::MS => ScalarValue::TimestampMillisecond(Some(v), tz.clone()),
+                Precision::US => ScalarValue::TimestampMicrosecond(Some(v), tz.clone()),
+                Precision::NS => ScalarValue::TimestampNanosecond(Some(v), tz.clone()),
+            }
+        }
+        other => return Err(format!("column type {other} is unsupported")),
+    };
+
+    Ok(scalar)
+}
+
+fn from_nanos(ns: i64, precision: Precision) -> i64 {
+    match precision {
+        Precision::MS => ns.div_euclid(1_000_000),
+        Precision::US => ns.div_euclid(1_000),
+        Precision::NS => ns,
+    }
+}
+
+/// Decode the messages into a batch of `schema`, messages failed to be decoded are skipped.
+pub fn decode_messages<'a>(
+    decoder: &MessageDecoder,
+    schema: SchemaRef,
+    messages: impl IntoIterator<Item = (i64, &'a [u8])>,
+) -> DFResult<RecordBatch> {
+    let mut builder = BatchBuilder::new(schema);
+    for (offset, message) in messages {
+        let rows = match decoder.decode(message) {
+            Ok(rows) => rows,
+            Err(err) => {
+                trace::warn!("Skip the message at offset {offset} failed to be decoded: {err}");
+                continue;
+            }
+        };
+        for row in rows {
+            if let Err(err) = builder.append_row(row) {
+                trace::warn!("Skip a row of the message at offset {offset}: {err}");
+            }
+        }
+    }
+
+    builder.finish()
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use apache_avro::types::Record;
+    use datafusion::arrow::array::{
+        Float64Array, Int64Array, StringArray, TimestampNanosecondArray,
+    };
+    use datafusion::arrow::datatypes::{DataType, Field, Schema, SchemaRef, TimeUnit};
+
+    use super::{decode_messages, MessageDecoder, MessageFormat};
+
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new(
+                "time",
+                DataType::Timestamp(TimeUnit::Nanosecond, None),
+                false,
+            ),
+            Field::new("host", DataType::Utf8, true),
+            Field::new("usage", DataType::Float64, true),
+            Field::new("count", DataType::Int64, true),
+        ]))
+    }
+
+    fn assert_batch(decoder: &MessageDecoder, messages: &[&[u8]]) {
+        let batch = decode_messages(
+            decoder,
+            schema(),
+            messages.iter().enumerate().map(|(i, m)| (i as i64, *m)),
+        )
+        .unwrap();
+
+        let time = batch
+            .column(0)
+            .as_any()
+            .downcast_ref::<TimestampNanosecondArray>()
+            .unwrap();
+        assert_eq!(time.values(), &[1_000_000_000, 2_000_000_000]);
+        let host = batch
+            .column(1)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        assert_eq!(host.iter().collect::<Vec<_>>(), vec![Some("a"), Some("b")]);
+        let usage = batch
+            .column(2)
+            .as_any()
+            .downcast_ref::<Float64Array>()
+            .unwrap();
+        assert_eq!(usage.iter().collect::<Vec<_>>(), vec![Some(0.5), Some(1.0)]);
+        let count = batch
+            .column(3)
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .unwrap();
+        assert_eq!(count.iter().collect::<Vec<_>>(), vec![Some(1), None]);
+    }
+
+    #[test]
+    fn test_decode_line_protocol() {
+        let decoder = MessageDecoder::new(MessageFormat::LineProtocol, "time");
+        assert_batch(
+            &decoder,
+            &[
+                b"cpu,host=a usage=0.5,count=1i 1000000000",
+                b"not line protocol",
+                b"cpu,host=b usage=1 2000000000",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_decode_json() {
+        let decoder = MessageDecoder::new(MessageFormat::Json, "time");
+        assert_batch(
+            &decoder,
+            &[
+                br#"{"time": 1000000000, "host": "a", "usage": 0.5, "count": 1}
+                    {"time": "1970-01-01T00:00:02Z", "host": "b", "usage": 1}"#,
+                br#"{"time": "yesterday", "host": "c"}"#,
+                b"[1, 2]",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_decode_avro() {
+        let raw_schema = r#"{
+            "type": "record",
+            "name": "cpu",
+            "fields": [
+                {"name": "time", "type": {"type": "long", "logicalType": "timestamp-micros"}},
+                {"name": "host", "type": "string"},
+                {"name": "usage", "type": "double"},
+                {"name": "count", "type": ["null", "long"]}
+            ]
+        }"#;
+        let format = MessageFormat::try_new("avro", Some(raw_schema)).unwrap();
+        let MessageFormat::Avro(schema) = &format else {
+            panic!("expect avro format");
+        };
+
+        let encode = |time: i64, host: &str, usage: f64, count: Option<i64>| {
+            let mut record = Record::new(schema).unwrap();
+            record.put("time", apache_avro::types::Value::TimestampMicros(time));
+            record.put("host", host);
+            record.put("usage", usage);
+            record.put("count", count);
+            apache_avro::to_avro_datum(schema, record).unwrap()
+        };
+        let messages = [
+            encode(1_000_000, "a", 0.5, Some(1)),
+            encode(2_000_000, "b", 1.0, None),
+        ];
+
+        let decoder = MessageDecoder::new(format.clone(), "time");
+        assert_batch(
+            &decoder,
+            &messages.iter().map(Vec::as_slice).collect::<Vec<_>>(),
+        );
+
+        assert!(MessageFormat::try_new("avro", None).is_err());
+        assert!(MessageFormat::try_new("csv", None).is_err());
+    }
+}
@@ -0,0 +1,92 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use datafusion::logical_expr::type_coercion::is_timestamp;
+use meta::model::MetaClientRef;
+use models::schema::stream_table_schema::StreamTable;
+use spi::query::datasource::stream::checker::SchemaChecker;
+use spi::query::datasource::stream::{StreamProviderFactory, StreamProviderRef};
+use spi::QueryError;
+
+use super::client::KafkaConnectorRef;
+use super::decoder::MessageDecoder;
+use super::provider::KafkaStreamProvider;
+use super::KafkaOptions;
+use crate::data_source::stream::EVENT_TIME_COLUMN_OPTION;
+
+pub const KAFKA_STREAM_PROVIDER: &str = "kafka";
+
+pub struct KafkaStreamProviderFactory {
+    connector: KafkaConnectorRef,
+}
+
+impl KafkaStreamProviderFactory {
+    pub fn new(connector: KafkaConnectorRef) -> Self {
+        Self { connector }
+    }
+}
+
+impl SchemaChecker<StreamTable> for KafkaStreamProviderFactory {
+    fn check(&self, _client: &MetaClientRef, table: &StreamTable) -> Result<(), QueryError> {
+        if table.stream_type() != KAFKA_STREAM_PROVIDER {
+            return Err(QueryError::Internal { reason: format!("The {KAFKA_STREAM_PROVIDER} stream data source cannot handle the {} stream table", table.stream_type()) });
+        }
+
+        let table_name = table.name();
+        let _ = KafkaOptions::try_new(table_name, table.extra_options())?;
+
+        // The columns can not be inferred from the topic, they must be declared
+        let schema = table.schema();
+        if schema.fields().is_empty() {
+            return Err(QueryError::Internal {
+                reason: format!("The columns of {KAFKA_STREAM_PROVIDER} stream table {table_name} must be specified"),
+            });
+        }
+
+        let mut duplicated_cols = HashSet::new();
+        for f in schema.fields() {
+            if !duplicated_cols.insert(f.name()) {
+                return Err(QueryError::SameColumnName {
+                    column: f.name().to_string(),
+                });
+            }
+        }
+
+        // check 'event_time_column'
+        let field = schema.field_with_name(&table.watermark().column)?;
+        if !is_timestamp(field.data_type()) {
+            return Err(QueryError::InvalidTableOption {
+                option_name: EVENT_TIME_COLUMN_OPTION.to_string(),
+                table_name: table_name.to_string(),
+                reason: format!(
+                    "The data type of column '{}' is not timestamp.",
+                    table.watermark().column
+                ),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl StreamProviderFactory for KafkaStreamProviderFactory {
+    fn create(
+        &self,
+        _meta: MetaClientRef,
+        table: &StreamTable,
+    ) -> Result<StreamProviderRef, QueryError> {
+        let options = KafkaOptions::try_new(table.name(), table.extra_options())?;
+        let watermark = table.watermark();
+        let decoder = MessageDecoder::new(options.format.clone(), &watermark.column);
+        let id = format!("{}.{}.{}", table.tenant(), table.db(), table.name());
+
+        Ok(Arc::new(KafkaStreamProvider::new(
+            id,
+            watermark.clone(),
+            options,
+            decoder,
+            table.schema(),
+            self.connector.clone(),
+        )))
+    }
+}
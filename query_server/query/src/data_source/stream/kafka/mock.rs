@@ -0,0 +1,94 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use spi::QueryError;
+
+use super::client::{KafkaConnector, KafkaRecord, PartitionConsumer, PartitionConsumerRef};
+use super::kafka_error;
+
+type Partitions = Arc<Mutex<HashMap<(String, i32), Vec<Vec<u8>>>>>;
+
+/// In-process broker keeping the records in memory, only for tests
+#[derive(Debug, Default, Clone)]
+pub struct MockKafkaBroker {
+    partitions: Partitions,
+}
+
+impl MockKafkaBroker {
+    /// Append a record to the partition of the topic, returns the offset of the record
+    pub fn produce(&self, topic: &str, partition: i32, value: impl Into<Vec<u8>>) -> i64 {
+        let mut partitions = self.partitions.lock();
+        let records = partitions
+            .entry((topic.to_string(), partition))
+            .or_default();
+        records.push(value.into());
+        records.len() as i64 - 1
+    }
+}
+
+#[async_trait]
+impl KafkaConnector for MockKafkaBroker {
+    async fn connect(
+        &self,
+        _brokers: &[String],
+        topic: &str,
+        partition: i32,
+    ) -> Result<PartitionConsumerRef, QueryError> {
+        let key = (topic.to_string(), partition);
+        if !self.partitions.lock().contains_key(&key) {
+            return Err(kafka_error(format!(
+                "unknown topic or partition: {topic}/{partition}"
+            )));
+        }
+
+        Ok(Arc::new(MockPartitionConsumer {
+            partitions: self.partitions.clone(),
+            key,
+        }))
+    }
+}
+
+struct MockPartitionConsumer {
+    partitions: Partitions,
+    key: (String, i32),
+}
+
+#[async_trait]
+impl PartitionConsumer for MockPartitionConsumer {
+    async fn earliest_offset(&self) -> Result<i64, QueryError> {
+        Ok(0)
+    }
+
+    async fn high_watermark(&self) -> Result<i64, QueryError> {
+        Ok(self
+            .partitions
+            .lock()
+            .get(&self.key)
+            .map(|records| records.len() as i64)
+            .unwrap_or_default())
+    }
+
+    async fn fetch(&self, offset: i64, max_bytes: i32) -> Result<Vec<KafkaRecord>, QueryError> {
+        let partitions = self.partitions.lock();
+        let records = partitions.get(&self.key).map(Vec::as_slice).unwrap_or(&[]);
+
+        // Like a broker, return at least one record even if it exceeds `max_bytes`
+        let mut size = 0;
+        Ok(records
+            .iter()
+            .enumerate()
+            .skip(offset.max(0) as usize)
+            .take_while(|(_, value)| {
+                let fits = size == 0 || size + value.len() <= max_bytes as usize;
+                size += value.len();
+                fits
+            })
+            .map(|(offset, value)| KafkaRecord {
+                offset: offset as i64,
+                value: Some(value.clone()),
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,203 @@
+use std::collections::HashMap;
+
+use spi::QueryError;
+
+use self::decoder::MessageFormat;
+use self::factory::KAFKA_STREAM_PROVIDER;
+
+pub mod client;
+pub mod decoder;
+pub mod factory;
+#[cfg(test)]
+pub mod mock;
+pub mod provider;
+pub mod scan;
+
+const KAFKA_BROKERS_KEY: &str = "brokers";
+const KAFKA_TOPIC_KEY: &str = "topic";
+const KAFKA_PARTITION_KEY: &str = "partition";
+const KAFKA_FORMAT_KEY: &str = "format";
+const KAFKA_AVRO_SCHEMA_KEY: &str = "avro_schema";
+
+const DEFAULT_FORMAT: &str = "json";
+
+fn kafka_error(err: impl ToString) -> QueryError {
+    QueryError::StreamSource {
+        stream_type: KAFKA_STREAM_PROVIDER.to_string(),
+        reason: err.to_string(),
+    }
+}
+
+/// Options of a kafka stream table, a stream table consumes a single partition of the topic.
+#[derive(Debug, Clone)]
+pub struct KafkaOptions {
+    pub brokers: Vec<String>,
+    pub topic: String,
+    pub partition: i32,
+    pub format: MessageFormat,
+}
+
+impl KafkaOptions {
+    pub fn try_new(table: &str, options: &HashMap<String, String>) -> Result<Self, QueryError> {
+        let get = |key: &str| {
+            options
+                .get(key)
+                .ok_or_else(|| QueryError::MissingTableOptions {
+                    option_name: key.into(),
+                    table_name: table.into(),
+                })
+        };
+        let invalid = |key: &str, reason: String| QueryError::InvalidTableOption {
+            option_name: key.into(),
+            table_name: table.into(),
+            reason,
+        };
+
+        let brokers = get(KAFKA_BROKERS_KEY)?
+            .split(',')
+            .map(|e| e.trim().to_string())
+            .filter(|e| !e.is_empty())
+            .collect::<Vec<_>>();
+        if brokers.is_empty() {
+            return Err(invalid(KAFKA_BROKERS_KEY, "No broker specified.".into()));
+        }
+
+        let topic = get(KAFKA_TOPIC_KEY)?.to_string();
+
+        let partition = options
+            .get(KAFKA_PARTITION_KEY)
+            .map(|e| e.trim().parse::<i32>())
+            .transpose()
+            .map_err(|e| invalid(KAFKA_PARTITION_KEY, e.to_string()))?
+            .unwrap_or_default();
+
+        let format = MessageFormat::try_new(
+            options
+                .get(KAFKA_FORMAT_KEY)
+                .map(|e| e.as_str())
+                .unwrap_or(DEFAULT_FORMAT),
+            options.get(KAFKA_AVRO_SCHEMA_KEY).map(|e| e.as_str()),
+        )
+        .map_err(|e| invalid(KAFKA_FORMAT_KEY, e))?;
+
+        Ok(Self {
+            brokers,
+            topic,
+            partition,
+            format,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use datafusion::arrow::array::Float64Array;
+    use datafusion::arrow::datatypes::{DataType, Field, Schema, TimeUnit};
+    use datafusion::physical_plan::collect;
+    use datafusion::prelude::SessionContext;
+    use meta::model::meta_tenant::TenantMeta;
+    use models::schema::stream_table_schema::{StreamTable, Watermark};
+    use spi::query::datasource::stream::StreamProviderManager;
+    use spi::QueryError;
+
+    use super::factory::{KafkaStreamProviderFactory, KAFKA_STREAM_PROVIDER};
+    use super::mock::MockKafkaBroker;
+    use super::{KAFKA_BROKERS_KEY, KAFKA_FORMAT_KEY, KAFKA_TOPIC_KEY};
+    use crate::stream::offset_tracker::OffsetTracker;
+
+    fn stream_table(options: HashMap<String, String>) -> StreamTable {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new(
+                "time",
+                DataType::Timestamp(TimeUnit::Nanosecond, None),
+                false,
+            ),
+            Field::new("host", DataType::Utf8, true),
+            Field::new("usage", DataType::Float64, true),
+        ]));
+
+        StreamTable::new(
+            "tenant",
+            "db",
+            "cpu",
+            schema,
+            KAFKA_STREAM_PROVIDER,
+            Watermark {
+                column: "time".into(),
+                delay: Duration::default(),
+            },
+            options,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_kafka() -> Result<(), QueryError> {
+        let broker = MockKafkaBroker::default();
+        let mut manager = StreamProviderManager::default();
+        let meta = Arc::new(TenantMeta::mock());
+        manager.register_stream_provider_factory(
+            KAFKA_STREAM_PROVIDER,
+            Arc::new(KafkaStreamProviderFactory::new(Arc::new(broker.clone()))),
+        )?;
+
+        // error MissingTableOptions
+        let provider = manager.create_provider(meta.clone(), &stream_table(HashMap::new()));
+        assert!(matches!(
+            provider,
+            Err(QueryError::MissingTableOptions { .. })
+        ));
+
+        let table = stream_table(HashMap::from_iter([
+            (KAFKA_BROKERS_KEY.into(), "127.0.0.1:9092".into()),
+            (KAFKA_TOPIC_KEY.into(), "cpu".into()),
+            (KAFKA_FORMAT_KEY.into(), "line_protocol".into()),
+        ]));
+        let provider = manager.create_provider(meta, &table)?;
+        assert_eq!(provider.id(), "tenant.db.cpu");
+
+        // error unknown topic
+        assert!(provider.latest_available_offset().await.is_err());
+
+        for i in 0..5 {
+            broker.produce("cpu", 0, format!("cpu,host=a usage={i} {i}"));
+        }
+        // Records of other partitions are invisible
+        broker.produce("cpu", 1, "cpu,host=b usage=10 10");
+
+        let tracker = OffsetTracker::new();
+        let state = SessionContext::new().state();
+        let mut processed = vec![];
+        for expected in [vec![0.0, 1.0, 2.0, 3.0, 4.0], vec![5.0, 6.0]] {
+            let offset = provider.latest_available_offset().await?.unwrap();
+            tracker.update_available_offset(provider.id(), offset);
+            let range = tracker.available_offsets()[&provider.id()];
+
+            let plan = provider
+                .scan(&state, Some(&vec![2]), &[], None, Some(&range))
+                .await?;
+            let batches = collect(plan, state.task_ctx()).await?;
+            let usage = batches
+                .iter()
+                .flat_map(|b| {
+                    let array = b.column(0).as_any().downcast_ref::<Float64Array>().unwrap();
+                    array.values().to_vec()
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(usage, expected);
+
+            tracker.commit(i64::MAX);
+            provider.commit(offset).await?;
+            processed.push(tracker.processed_offsets()[&provider.id()]);
+
+            broker.produce("cpu", 0, "cpu,host=a usage=5 5");
+            broker.produce("cpu", 0, "cpu,host=a usage=6 6");
+        }
+        assert_eq!(processed, vec![4, 6]);
+
+        Ok(())
+    }
+}
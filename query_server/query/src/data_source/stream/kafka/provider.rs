@@ -0,0 +1,161 @@
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use datafusion::arrow::datatypes::SchemaRef;
+use datafusion::common::Result as DFResult;
+use datafusion::error::DataFusionError;
+use datafusion::execution::context::SessionState;
+use datafusion::logical_expr::logical_plan::AggWithGrouping;
+use datafusion::physical_plan::empty::EmptyExec;
+use datafusion::physical_plan::{project_schema, ExecutionPlan};
+use datafusion::prelude::Expr;
+use models::schema::stream_table_schema::Watermark;
+use spi::query::datasource::stream::StreamProvider;
+use tokio::sync::OnceCell;
+use trace::debug;
+
+use super::client::{KafkaConnectorRef, PartitionConsumerRef};
+use super::decoder::MessageDecoder;
+use super::scan::KafkaScanExec;
+use super::KafkaOptions;
+
+/// Max bytes of records returned by a fetch request, records of a scan are decoded
+/// one fetch at a time
+const MAX_FETCH_BYTES: i32 = 1024 * 1024;
+
+/// Stream source consuming a partition of a Kafka topic, the offsets are the offsets of the partition.
+pub struct KafkaStreamProvider {
+    id: String,
+    watermark: Watermark,
+    options: KafkaOptions,
+    decoder: Arc<MessageDecoder>,
+    used_schema: SchemaRef,
+    connector: KafkaConnectorRef,
+    consumer: OnceCell<PartitionConsumerRef>,
+    committed_offset: AtomicI64,
+}
+
+impl KafkaStreamProvider {
+    pub fn new(
+        id: String,
+        watermark: Watermark,
+        options: KafkaOptions,
+        decoder: MessageDecoder,
+        used_schema: SchemaRef,
+        connector: KafkaConnectorRef,
+    ) -> Self {
+        Self {
+            id,
+            watermark,
+            options,
+            decoder: Arc::new(decoder),
+            used_schema,
+            connector,
+            consumer: OnceCell::new(),
+            committed_offset: AtomicI64::new(-1),
+        }
+    }
+
+    /// Offset of the last record processed by the stream query, -1 if nothing has been committed
+    pub fn committed_offset(&self) -> i64 {
+        self.committed_offset.load(Ordering::Relaxed)
+    }
+
+    async fn consumer(&self) -> DFResult<&PartitionConsumerRef> {
+        self.consumer
+            .get_or_try_init(|| async {
+                self.connector
+                    .connect(
+                        &self.options.brokers,
+                        &self.options.topic,
+                        self.options.partition,
+                    )
+                    .await
+            })
+            .await
+            .map_err(|e| DataFusionError::External(Box::new(e)))
+    }
+}
+
+#[async_trait]
+impl StreamProvider for KafkaStreamProvider {
+    type Offset = i64;
+
+    fn id(&self) -> String {
+        self.id.clone()
+    }
+
+    /// Event time column of stream table
+    fn watermark(&self) -> &Watermark {
+        &self.watermark
+    }
+
+    /// Returns the offset of the last record of the partition
+    async fn latest_available_offset(&self) -> DFResult<Option<Self::Offset>> {
+        let high_watermark = self
+            .consumer()
+            .await?
+            .high_watermark()
+            .await
+            .map_err(|e| DataFusionError::External(Box::new(e)))?;
+
+        Ok((high_watermark > 0).then_some(high_watermark - 1))
+    }
+
+    async fn scan(
+        &self,
+        _state: &SessionState,
+        projection: Option<&Vec<usize>>,
+        _filters: &[Expr],
+        agg_with_grouping: Option<&AggWithGrouping>,
+        range: Option<&(Option<Self::Offset>, Self::Offset)>,
+    ) -> DFResult<Arc<dyn ExecutionPlan>> {
+        if agg_with_grouping.is_some() {
+            return Err(DataFusionError::NotImplemented(
+                "KafkaStreamProvider::scan with agg_with_grouping".to_string(),
+            ));
+        }
+
+        let (start, end) = match range {
+            Some(range) => *range,
+            None => {
+                // No offset range, return an empty scan
+                let projected_schema = project_schema(&self.used_schema, projection)?;
+                return Ok(Arc::new(EmptyExec::new(false, projected_schema)));
+            }
+        };
+
+        debug!(
+            "Scan offsets [{:?}, {}] of kafka topic {}/{}",
+            start, end, self.options.topic, self.options.partition
+        );
+
+        Ok(Arc::new(KafkaScanExec::try_new(
+            self.consumer().await?.clone(),
+            self.decoder.clone(),
+            self.used_schema.clone(),
+            projection,
+            start,
+            end,
+            MAX_FETCH_BYTES,
+        )?))
+    }
+
+    /// Informs the source that stream has completed processing all data for offsets less than or
+    /// equal to `end` and will only request offsets greater than `end` in the future.
+    async fn commit(&self, end: Self::Offset) -> DFResult<()> {
+        // The processed offsets are persisted by the checkpoint of the stream query,
+        // so there is no need to commit them to the consumer group of the broker
+        let _ = self.committed_offset.fetch_max(end, Ordering::Relaxed);
+        debug!(
+            "Kafka stream source {} commit offset: {end}",
+            self.options.topic
+        );
+        Ok(())
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.used_schema.clone()
+    }
+}
@@ -0,0 +1,271 @@
+use std::any::Any;
+use std::fmt::{self, Debug, Formatter};
+use std::sync::Arc;
+
+use datafusion::arrow::datatypes::SchemaRef;
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::common::Result as DFResult;
+use datafusion::error::DataFusionError;
+use datafusion::execution::context::TaskContext;
+use datafusion::physical_expr::PhysicalSortExpr;
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+use datafusion::physical_plan::{
+    project_schema, DisplayFormatType, ExecutionPlan, Partitioning, SendableRecordBatchStream,
+    Statistics,
+};
+use futures::stream;
+
+use super::client::PartitionConsumerRef;
+use super::decoder::{decode_messages, MessageDecoder};
+
+/// Scan the records of offsets in `[start, end]` of a topic partition.
+///
+/// The records are fetched page by page, each page is at most about `max_fetch_bytes`
+/// and is decoded into a record batch before the next page is fetched, so that a large
+/// range of offsets is never held in memory at once.
+pub struct KafkaScanExec {
+    consumer: PartitionConsumerRef,
+    decoder: Arc<MessageDecoder>,
+    schema: SchemaRef,
+    projection: Option<Vec<usize>>,
+    projected_schema: SchemaRef,
+    /// The earliest offset of the partition if not set
+    start: Option<i64>,
+    end: i64,
+    max_fetch_bytes: i32,
+}
+
+impl KafkaScanExec {
+    pub fn try_new(
+        consumer: PartitionConsumerRef,
+        decoder: Arc<MessageDecoder>,
+        schema: SchemaRef,
+        projection: Option<&Vec<usize>>,
+        start: Option<i64>,
+        end: i64,
+        max_fetch_bytes: i32,
+    ) -> DFResult<Self> {
+        let projected_schema = project_schema(&schema, projection)?;
+        Ok(Self {
+            consumer,
+            decoder,
+            schema,
+            projection: projection.cloned(),
+            projected_schema,
+            start,
+            end,
+            max_fetch_bytes,
+        })
+    }
+}
+
+impl Debug for KafkaScanExec {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KafkaScanExec")
+            .field("projected_schema", &self.projected_schema)
+            .field("start", &self.start)
+            .field("end", &self.end)
+            .field("max_fetch_bytes", &self.max_fetch_bytes)
+            .finish()
+    }
+}
+
+impl ExecutionPlan for KafkaScanExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.projected_schema.clone()
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        Partitioning::UnknownPartitioning(1)
+    }
+
+    fn output_ordering(&self) -> Option<&[PhysicalSortExpr]> {
+        None
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        _: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> DFResult<Arc<dyn ExecutionPlan>> {
+        Ok(self)
+    }
+
+    fn execute(
+        &self,
+        _partition: usize,
+        _context: Arc<TaskContext>,
+    ) -> DFResult<SendableRecordBatchStream> {
+        let pages = PageReader {
+            consumer: self.consumer.clone(),
+            decoder: self.decoder.clone(),
+            schema: self.schema.clone(),
+            projection: self.projection.clone(),
+            offset: self.start,
+            end: self.end,
+            max_fetch_bytes: self.max_fetch_bytes,
+        };
+        let stream = stream::try_unfold(pages, |mut pages| async move {
+            let page = pages.next_page().await?;
+            Ok::<_, DataFusionError>(page.map(|batch| (batch, pages)))
+        });
+
+        Ok(Box::pin(RecordBatchStreamAdapter::new(
+            self.projected_schema.clone(),
+            stream,
+        )))
+    }
+
+    fn fmt_as(&self, t: DisplayFormatType, f: &mut Formatter) -> fmt::Result {
+        match t {
+            DisplayFormatType::Default | DisplayFormatType::Verbose => {
+                let fields = self
+                    .projected_schema
+                    .fields()
+                    .iter()
+                    .map(|x| x.name().to_owned())
+                    .collect::<Vec<String>>();
+                write!(
+                    f,
+                    "KafkaScan: offsets=[{:?}, {}], projection=[{}]",
+                    self.start,
+                    self.end,
+                    fields.join(","),
+                )
+            }
+        }
+    }
+
+    fn statistics(&self) -> Statistics {
+        Statistics::default()
+    }
+}
+
+struct PageReader {
+    consumer: PartitionConsumerRef,
+    decoder: Arc<MessageDecoder>,
+    schema: SchemaRef,
+    projection: Option<Vec<usize>>,
+    /// Offset of the next record to fetch
+    offset: Option<i64>,
+    end: i64,
+    max_fetch_bytes: i32,
+}
+
+impl PageReader {
+    /// Fetch and decode the next page of records, `None` if all records up to `end` are read.
+    async fn next_page(&mut self) -> DFResult<Option<RecordBatch>> {
+        let mut offset = match self.offset {
+            Some(offset) => offset,
+            None => self
+                .consumer
+                .earliest_offset()
+                .await
+                .map_err(|e| DataFusionError::External(Box::new(e)))?,
+        };
+
+        while offset <= self.end {
+            let fetched = self
+                .consumer
+                .fetch(offset, self.max_fetch_bytes)
+                .await
+                .map_err(|e| DataFusionError::External(Box::new(e)))?;
+            let Some(last_offset) = fetched.last().map(|r| r.offset) else {
+                break;
+            };
+
+            // A fetch may return the records before `offset` in the same record batch
+            let batch = decode_messages(
+                &self.decoder,
+                self.schema.clone(),
+                fetched
+                    .iter()
+                    .filter(|r| r.offset >= offset && r.offset <= self.end)
+                    .filter_map(|r| r.value.as_deref().map(|v| (r.offset, v))),
+            )?;
+            offset = last_offset + 1;
+            self.offset = Some(offset);
+
+            if batch.num_rows() > 0 {
+                let batch = match &self.projection {
+                    Some(projection) => batch.project(projection)?,
+                    None => batch,
+                };
+                return Ok(Some(batch));
+            }
+        }
+
+        self.offset = Some(offset);
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use datafusion::arrow::array::Float64Array;
+    use datafusion::arrow::datatypes::{DataType, Field, Schema, TimeUnit};
+    use datafusion::physical_plan::collect;
+    use datafusion::prelude::SessionContext;
+
+    use super::KafkaScanExec;
+    use crate::data_source::stream::kafka::client::KafkaConnector;
+    use crate::data_source::stream::kafka::decoder::{MessageDecoder, MessageFormat};
+    use crate::data_source::stream::kafka::mock::MockKafkaBroker;
+
+    #[tokio::test]
+    async fn test_scan_pages() {
+        let broker = MockKafkaBroker::default();
+        for i in 0..5 {
+            broker.produce("cpu", 0, format!("cpu,host=a usage={i} {i}"));
+        }
+        let consumer = broker.connect(&[], "cpu", 0).await.unwrap();
+        let schema = Arc::new(Schema::new(vec![
+            Field::new(
+                "time",
+                DataType::Timestamp(TimeUnit::Nanosecond, None),
+                false,
+            ),
+            Field::new("host", DataType::Utf8, true),
+            Field::new("usage", DataType::Float64, true),
+        ]));
+        let decoder = Arc::new(MessageDecoder::new(MessageFormat::LineProtocol, "time"));
+        let state = SessionContext::new().state();
+
+        // Every page only contains a record
+        for (start, end, expected) in [
+            (None, 4, vec![0.0, 1.0, 2.0, 3.0, 4.0]),
+            (Some(1), 3, vec![1.0, 2.0, 3.0]),
+            (Some(5), 9, vec![]),
+        ] {
+            let exec = KafkaScanExec::try_new(
+                consumer.clone(),
+                decoder.clone(),
+                schema.clone(),
+                Some(&vec![2]),
+                start,
+                end,
+                1,
+            )
+            .unwrap();
+            let batches = collect(Arc::new(exec), state.task_ctx()).await.unwrap();
+            assert_eq!(batches.len(), expected.len());
+            let usage = batches
+                .iter()
+                .flat_map(|b| {
+                    let array = b.column(0).as_any().downcast_ref::<Float64Array>().unwrap();
+                    array.values().to_vec()
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(usage, expected);
+        }
+    }
+}
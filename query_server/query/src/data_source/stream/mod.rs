@@ -5,10 +5,11 @@ use spi::QueryError;
 
 use crate::utils::duration::parse_duration;
 
+pub mod kafka;
 pub mod tskv;
 
 // Table option keys
-const EVENT_TIME_COLUMN_OPTION: &str = "event_time_column";
+pub(crate) const EVENT_TIME_COLUMN_OPTION: &str = "event_time_column";
 const WATERMARK_DELAY_OPTION: &str = "watermark_delay";
 
 pub fn get_event_time_column<'a>(
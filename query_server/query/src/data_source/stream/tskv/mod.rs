@@ -5,8 +5,8 @@ use spi::QueryError;
 pub mod factory;
 pub mod provider;
 
-const STREAM_DB_KEY: &str = "db";
-const STREAM_TABLE_KEY: &str = "table";
+pub(crate) const STREAM_DB_KEY: &str = "db";
+pub(crate) const STREAM_TABLE_KEY: &str = "table";
 
 pub fn get_target_db_name(options: &HashMap<String, String>) -> Option<&str> {
     options.get(STREAM_DB_KEY).map(|e| e.as_ref())
@@ -0,0 +1,289 @@
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use config::common::AuditConfig;
+use coordinator::service::CoordinatorRef;
+use models::audit::{audit_categories, mask_passwords, AuditCategory, AuditRecord, AuditSink};
+use models::schema::table_schema::TableSchema;
+use models::schema::{CLUSTER_SCHEMA, DEFAULT_CATALOG};
+use protocol_parser::Line;
+use protos::FieldValue;
+use spi::query::ast::{AlterTenantOperation, ExtStatement};
+use spi::query::logical_planner::{
+    AlterTenantAction, DDLPlan, GlobalObjectType, Plan, TenantObjectType,
+};
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::mpsc::{self, Receiver, Sender};
+use trace::warn;
+use utils::duration::CnosDuration;
+use utils::precision::Precision;
+
+pub const AUDIT_LOG: &str = "audit_log";
+
+const RETENTION_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+/// The records are dropped if this many records are waiting to be written.
+const AUDIT_CHANNEL_CAPACITY: usize = 10240;
+const AUDIT_WRITE_BATCH_SIZE: usize = 1024;
+
+/// Store the audit records in the `cluster_schema.audit_log` table of the `cnosdb` tenant,
+/// the records older than the retention are expired by the TTL of the table.
+pub struct AuditLogWriter {
+    categories: HashSet<AuditCategory>,
+    sender: Sender<Line<'static>>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl AuditLogWriter {
+    pub fn new(coord: CoordinatorRef, config: &AuditConfig) -> Arc<Self> {
+        let (sender, receiver) = mpsc::channel(AUDIT_CHANNEL_CAPACITY);
+        let dropped = Arc::new(AtomicU64::new(0));
+
+        let ttl = CnosDuration::new_with_duration(config.retention);
+        tokio::spawn(Self::keep_retention(coord.clone(), ttl));
+        tokio::spawn(Self::write_batches(coord, receiver, dropped.clone()));
+
+        Arc::new(Self {
+            categories: audit_categories(config),
+            sender,
+            dropped,
+        })
+    }
+
+    /// Write the records waiting in the channel in batches by a single task.
+    async fn write_batches(
+        coord: CoordinatorRef,
+        mut receiver: Receiver<Line<'static>>,
+        dropped: Arc<AtomicU64>,
+    ) {
+        while let Some(line) = receiver.recv().await {
+            let mut lines = vec![line];
+            while lines.len() < AUDIT_WRITE_BATCH_SIZE {
+                match receiver.try_recv() {
+                    Ok(line) => lines.push(line),
+                    Err(_) => break,
+                }
+            }
+
+            if let Err(err) = coord
+                .write_lines(DEFAULT_CATALOG, CLUSTER_SCHEMA, Precision::NS, lines, None)
+                .await
+            {
+                warn!("Failed to write the audit log: {:?}", err);
+            }
+
+            let dropped = dropped.swap(0, Ordering::Relaxed);
+            if dropped > 0 {
+                warn!(
+                    "Dropped {} audit records, the audit log is overloaded",
+                    dropped
+                );
+            }
+        }
+    }
+
+    /// The table is created by the first written record, so its TTL is checked periodically.
+    async fn keep_retention(coord: CoordinatorRef, ttl: CnosDuration) {
+        let mut interval = tokio::time::interval(RETENTION_CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            let Some(client) = coord.tenant_meta(DEFAULT_CATALOG).await else {
+                continue;
+            };
+            let schema = match client.get_tskv_table_schema(CLUSTER_SCHEMA, AUDIT_LOG) {
+                Ok(Some(schema)) if schema.ttl() != Some(&ttl) => schema,
+                Ok(_) => continue,
+                Err(err) => {
+                    warn!("Failed to get the schema of {}: {}", AUDIT_LOG, err);
+                    continue;
+                }
+            };
+
+            let mut schema = schema.as_ref().clone();
+            schema.set_ttl(Some(ttl.clone()));
+            schema.schema_version += 1;
+            if let Err(err) = client
+                .update_table(&TableSchema::TsKvTableSchema(Arc::new(schema)))
+                .await
+            {
+                warn!("Failed to set the retention of {}: {}", AUDIT_LOG, err);
+            }
+        }
+    }
+}
+
+impl AuditSink for AuditLogWriter {
+    fn is_enabled(&self, category: AuditCategory) -> bool {
+        self.categories.contains(&category)
+    }
+
+    fn record(&self, record: AuditRecord) {
+        let mut tags = vec![(
+            Cow::Borrowed("category"),
+            Cow::Borrowed(record.category.as_str()),
+        )];
+        if let Some(tenant) = record.tenant {
+            tags.push((Cow::Borrowed("tenant_name"), Cow::Owned(tenant)));
+        }
+        if let Some(user) = record.user {
+            tags.push((Cow::Borrowed("user_name"), Cow::Owned(user)));
+        }
+
+        let result = if record.error.is_none() {
+            "success"
+        } else {
+            "failure"
+        };
+        let mut fields = vec![
+            (
+                Cow::Borrowed("statement"),
+                FieldValue::Str(mask_passwords(&record.statement).into_bytes()),
+            ),
+            (
+                Cow::Borrowed("result"),
+                FieldValue::Str(result.as_bytes().to_vec()),
+            ),
+            (
+                Cow::Borrowed("duration"),
+                FieldValue::F64(record.duration.as_secs_f64()),
+            ),
+        ];
+        if let Some(client_addr) = record.client_addr {
+            fields.push((
+                Cow::Borrowed("client_addr"),
+                FieldValue::Str(client_addr.into_bytes()),
+            ));
+        }
+        if let Some(error) = record.error {
+            fields.push((Cow::Borrowed("error"), FieldValue::Str(error.into_bytes())));
+        }
+
+        let line = Line {
+            hash_id: 0,
+            table: Cow::Borrowed(AUDIT_LOG),
+            tags,
+            fields,
+            timestamp: record.time,
+        };
+        if let Err(TrySendError::Full(_)) = self.sender.try_send(line) {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// The audit category of the statement
+pub fn statement_audit_category(stmt: &ExtStatement) -> AuditCategory {
+    match stmt {
+        ExtStatement::CreateUser(_)
+        | ExtStatement::CreateRole(_)
+        | ExtStatement::AlterUser(_)
+        | ExtStatement::CreatePolicy(_)
+        | ExtStatement::GrantRevoke(_) => AuditCategory::Dcl,
+        ExtStatement::DropTenantObject(stmt) => tenant_object_audit_category(&stmt.obj_type),
+        ExtStatement::DropGlobalObject(stmt) => global_object_audit_category(&stmt.obj_type),
+        ExtStatement::AlterTenant(stmt) => match stmt.operation {
+            AlterTenantOperation::AddUser(..)
+            | AlterTenantOperation::SetUser(..)
+            | AlterTenantOperation::RemoveUser(_) => AuditCategory::Dcl,
+            AlterTenantOperation::Set(_) | AlterTenantOperation::UnSet(_) => AuditCategory::Ddl,
+        },
+        ExtStatement::SqlStatement(_)
+        | ExtStatement::Copy(_)
+        | ExtStatement::DescribeTable(_)
+        | ExtStatement::DescribeDatabase(_)
+        | ExtStatement::ShowDatabases()
+        | ExtStatement::ShowTables(_)
+        | ExtStatement::ShowSeries(_)
+        | ExtStatement::ShowTagValues(_)
+        | ExtStatement::ShowCardinality(_)
+        | ExtStatement::ShowStreams(_)
+        | ExtStatement::ShowQueries
+        | ExtStatement::ShowReplicas
+        | ExtStatement::Explain(_) => AuditCategory::Query,
+        _ => AuditCategory::Ddl,
+    }
+}
+
+/// The audit category of the logical plan
+pub fn plan_audit_category(plan: &Plan) -> AuditCategory {
+    let plan = match plan {
+        Plan::Query(_) | Plan::DML(_) => return AuditCategory::Query,
+        Plan::SYSTEM(_) => return AuditCategory::Ddl,
+        Plan::DDL(plan) => plan,
+    };
+    match plan {
+        DDLPlan::CreateUser(_)
+        | DDLPlan::CreateRole(_)
+        | DDLPlan::AlterUser(_)
+        | DDLPlan::CreatePolicy(_)
+        | DDLPlan::GrantRevoke(_) => AuditCategory::Dcl,
+        DDLPlan::DropTenantObject(plan) => tenant_object_audit_category(&plan.obj_type),
+        DDLPlan::DropGlobalObject(plan) => global_object_audit_category(&plan.obj_type),
+        DDLPlan::AlterTenant(plan) => match plan.alter_tenant_action {
+            AlterTenantAction::AddUser(_)
+            | AlterTenantAction::SetUser(_)
+            | AlterTenantAction::RemoveUser(_) => AuditCategory::Dcl,
+            AlterTenantAction::SetOption(_) => AuditCategory::Ddl,
+        },
+        DDLPlan::ShowReplicas => AuditCategory::Query,
+        _ => AuditCategory::Ddl,
+    }
+}
+
+fn tenant_object_audit_category(obj_type: &TenantObjectType) -> AuditCategory {
+    match obj_type {
+        TenantObjectType::Role | TenantObjectType::Policy => AuditCategory::Dcl,
+        TenantObjectType::Database | TenantObjectType::Function => AuditCategory::Ddl,
+    }
+}
+
+fn global_object_audit_category(obj_type: &GlobalObjectType) -> AuditCategory {
+    match obj_type {
+        GlobalObjectType::User => AuditCategory::Dcl,
+        GlobalObjectType::Tenant => AuditCategory::Ddl,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use models::audit::AuditCategory;
+
+    use super::statement_audit_category;
+    use crate::sql::parser::ExtParser;
+
+    fn category(sql: &str) -> AuditCategory {
+        let mut statements = ExtParser::parse_sql(sql).unwrap();
+        statement_audit_category(&statements.pop_front().unwrap())
+    }
+
+    #[test]
+    fn test_statement_audit_category() {
+        assert_eq!(category("CREATE DATABASE db1"), AuditCategory::Ddl);
+        assert_eq!(category("DROP TABLE t1"), AuditCategory::Ddl);
+        assert_eq!(category("DROP DATABASE db1"), AuditCategory::Ddl);
+
+        assert_eq!(
+            category("CREATE USER u1 WITH PASSWORD='123456'"),
+            AuditCategory::Dcl
+        );
+        assert_eq!(category("DROP USER u1"), AuditCategory::Dcl);
+        assert_eq!(
+            category("GRANT READ ON DATABASE db1 TO ROLE r1"),
+            AuditCategory::Dcl
+        );
+        assert_eq!(
+            category("ALTER TENANT cnosdb ADD USER u1 AS member"),
+            AuditCategory::Dcl
+        );
+
+        assert_eq!(category("SELECT * FROM t1"), AuditCategory::Query);
+        assert_eq!(
+            category("INSERT INTO t1 VALUES (1, 1)"),
+            AuditCategory::Query
+        );
+        assert_eq!(category("SHOW DATABASES"), AuditCategory::Query);
+    }
+}
@@ -8,6 +8,7 @@ use coordinator::service::CoordinatorRef;
 use memory_pool::MemoryPoolRef;
 use meta::error::MetaError;
 use meta::model::MetaClientRef;
+use models::audit::{AuditCategory, AuditRecord, AuditSinkRef};
 use models::auth::auth_cache::{AuthCache, AuthCacheKey};
 use models::auth::user::User;
 use models::meta_data::{MetaModifyType, NodeId};
@@ -21,7 +22,7 @@ use spi::query::datasource::stream::StreamProviderManagerRef;
 use spi::query::dispatcher::{QueryDispatcher, QueryStatus};
 use spi::query::execution::{Output, QueryStateMachine};
 use spi::query::function::FuncMetaManagerRef;
-use spi::query::logical_planner::{LogicalPlanner, Plan};
+use spi::query::logical_planner::{CreateContinuousAggregate, DDLPlan, LogicalPlanner, Plan};
 use spi::query::parser::Parser;
 use spi::query::session::{SessionCtx, SessionCtxFactory};
 use spi::service::protocol::{ContextBuilder, Query};
@@ -34,12 +35,11 @@ use tokio_retry::Retry;
 use trace::span_ext::SpanExt;
 use trace::{error, info, Span, SpanContext};
 
+use super::audit_log::{plan_audit_category, statement_audit_category};
 use super::query_tracker::QueryTracker;
 use crate::data_source::split::SplitManagerRef;
 use crate::execution::factory::QueryExecutionFactoryRef;
-use crate::metadata::{
-    BaseTableProvider, ContextProviderExtension, MetadataProvider, TableHandleProviderRef,
-};
+use crate::metadata::{BaseTableProvider, MetadataProvider, TableHandleProviderRef};
 use crate::sql::logical::planner::DefaultLogicalPlanner;
 
 #[derive(Clone)]
@@ -64,6 +64,8 @@ pub struct SimpleQueryDispatcher {
     async_task_joinhandle: Arc<Mutex<HashMap<String, JoinHandle<()>>>>,
     failed_task_joinhandle: Arc<Mutex<HashMap<String, JoinHandle<()>>>>,
     auth_cache: Arc<AuthCache<AuthCacheKey, User>>,
+    // records the statements if audit is enabled
+    audit_sink: Option<AuditSinkRef>,
 }
 
 #[async_trait]
@@ -91,62 +93,74 @@ impl QueryDispatcher for SimpleQueryDispatcher {
         query: &Query,
         span_ctx: Option<&SpanContext>,
     ) -> QueryResult<Output> {
-        let query_state_machine = {
-            let _span = Span::from_context("init session ctx", span_ctx);
-            self.build_query_state_machine(
-                tenant_id,
-                query_id,
-                query.clone(),
-                span_ctx,
-                self.auth_cache.clone(),
-            )
-            .await?
+        let start = Instant::now();
+        let statement = {
+            let _span = Span::from_context("parse sql", span_ctx);
+            self.parse_statement(query.content())
+        };
+        let category = match &statement {
+            Ok(Some(stmt)) => statement_audit_category(stmt),
+            _ => AuditCategory::Query,
         };
 
-        let logical_plan = self.build_logical_plan(query_state_machine.clone()).await?;
-        let logical_plan = match logical_plan {
-            Some(plan) => plan,
-            None => return Ok(Output::Nil(())),
+        let result = match statement {
+            Ok(Some(stmt)) => {
+                self.execute_statement(tenant_id, query_id, query, stmt, span_ctx)
+                    .await
+            }
+            Ok(None) => Ok(Output::Nil(())),
+            Err(err) => Err(err),
         };
-        let result = self
-            .execute_logical_plan(logical_plan, query_state_machine)
-            .await?;
-        Ok(result)
+        self.audit(category, query, start.elapsed(), &result);
+        result
     }
 
     async fn build_logical_plan(
         &self,
         query_state_machine: Arc<QueryStateMachine>,
     ) -> QueryResult<Option<Plan>> {
-        let session = &query_state_machine.session;
-        let query = &query_state_machine.query;
-
-        let scheme_provider = self.build_scheme_provider(session).await?;
-
-        let logical_planner = DefaultLogicalPlanner::new(&scheme_provider);
-
-        let span_recorder = session.get_child_span("parse sql");
-        let statements = self.parser.parse(query.content())?;
+        let statement = {
+            let _span_recorder = query_state_machine.session.get_child_span("parse sql");
+            self.parse_statement(query_state_machine.query.content())
+        };
+        let (category, logical_plan) = match statement {
+            Ok(Some(stmt)) => (
+                statement_audit_category(&stmt),
+                self.statement_to_logical_plan(stmt, query_state_machine.clone())
+                    .await,
+            ),
+            Ok(None) => return Ok(None),
+            Err(err) => (AuditCategory::Query, Err(err)),
+        };
 
-        // not allow multi statement
-        if statements.len() > 1 {
-            return Err(QueryError::MultiStatement {
-                num: statements.len(),
-                sql: query_state_machine.query.content().to_string(),
-            });
+        // the successful statements are audited after execution
+        if logical_plan.is_err() {
+            self.audit(
+                category,
+                &query_state_machine.query,
+                query_state_machine.duration(),
+                &logical_plan,
+            );
         }
+        logical_plan.map(Some)
+    }
 
-        let stmt = match statements.front() {
-            Some(stmt) => stmt.clone(),
-            None => return Ok(None),
-        };
-
-        drop(span_recorder);
+    async fn build_substrait_logical_plan(
+        &self,
+        query_state_machine: Arc<QueryStateMachine>,
+        substrait_plan: &[u8],
+    ) -> QueryResult<Plan> {
+        let session = &query_state_machine.session;
+        let scheme_provider = self.build_scheme_provider(session).await?;
+        let logical_planner = DefaultLogicalPlanner::new(&scheme_provider);
 
-        let logical_plan = self
-            .statement_to_logical_plan(stmt, &logical_planner, query_state_machine)
+        query_state_machine.begin_analyze();
+        let logical_plan = logical_planner
+            .create_substrait_logical_plan(substrait_plan, session)
             .await?;
-        Ok(Some(logical_plan))
+        query_state_machine.end_analyze();
+
+        Ok(logical_plan)
     }
 
     async fn execute_logical_plan(
@@ -154,8 +168,17 @@ impl QueryDispatcher for SimpleQueryDispatcher {
         logical_plan: Plan,
         query_state_machine: Arc<QueryStateMachine>,
     ) -> QueryResult<Output> {
-        self.execute_logical_plan(logical_plan, query_state_machine)
-            .await
+        let category = plan_audit_category(&logical_plan);
+        let result = self
+            .execute_logical_plan(logical_plan, query_state_machine.clone())
+            .await;
+        self.audit(
+            category,
+            &query_state_machine.query,
+            query_state_machine.duration(),
+            &result,
+        );
+        result
     }
 
     async fn build_query_state_machine(
@@ -472,12 +495,98 @@ impl SimpleQueryDispatcher {
         let _ = ResourceManager::do_operator(coord.clone(), resourceinfo.clone()).await;
     }
 
-    async fn statement_to_logical_plan<S: ContextProviderExtension + Send + Sync>(
+    fn parse_statement(&self, sql: &str) -> QueryResult<Option<ExtStatement>> {
+        let mut statements = self.parser.parse(sql)?;
+
+        // not allow multi statement
+        if statements.len() > 1 {
+            return Err(QueryError::MultiStatement {
+                num: statements.len(),
+                sql: sql.to_string(),
+            });
+        }
+
+        Ok(statements.pop_front())
+    }
+
+    async fn execute_statement(
+        &self,
+        tenant_id: Oid,
+        query_id: QueryId,
+        query: &Query,
+        stmt: ExtStatement,
+        span_ctx: Option<&SpanContext>,
+    ) -> QueryResult<Output> {
+        let query_state_machine = {
+            let _span = Span::from_context("init session ctx", span_ctx);
+            self.build_query_state_machine(
+                tenant_id,
+                query_id,
+                query.clone(),
+                span_ctx,
+                self.auth_cache.clone(),
+            )
+            .await?
+        };
+
+        let logical_plan = self
+            .statement_to_logical_plan(stmt, query_state_machine.clone())
+            .await?;
+        if let Plan::DDL(DDLPlan::CreateContinuousAggregate(stmt)) = &logical_plan {
+            let stmt = stmt.clone();
+            return self
+                .execute_create_continuous_aggregate(
+                    tenant_id,
+                    stmt,
+                    logical_plan,
+                    query_state_machine,
+                    span_ctx,
+                )
+                .await;
+        }
+        self.execute_logical_plan(logical_plan, query_state_machine)
+            .await
+    }
+
+    /// Record the statement to the audit sink if its category is audited.
+    fn audit<T>(
+        &self,
+        category: AuditCategory,
+        query: &Query,
+        duration: Duration,
+        result: &QueryResult<T>,
+    ) {
+        let Some(audit_sink) = &self.audit_sink else {
+            return;
+        };
+        // the persistent queries re-executed after restart have been audited
+        if query.context().is_old() || !audit_sink.is_enabled(category) {
+            return;
+        }
+
+        let context = query.context();
+        audit_sink.record(AuditRecord {
+            time: now_timestamp_nanos(),
+            category,
+            tenant: Some(context.tenant().to_string()),
+            user: Some(context.user().desc().name().to_string()),
+            client_addr: context.client_addr().map(|addr| addr.to_string()),
+            statement: query.content().to_string(),
+            error: result.as_ref().err().map(|err| err.to_string()),
+            duration,
+        });
+    }
+
+    async fn statement_to_logical_plan(
         &self,
         stmt: ExtStatement,
-        logical_planner: &DefaultLogicalPlanner<'_, S>,
         query_state_machine: Arc<QueryStateMachine>,
     ) -> QueryResult<Plan> {
+        let scheme_provider = self
+            .build_scheme_provider(&query_state_machine.session)
+            .await?;
+        let logical_planner = DefaultLogicalPlanner::new(&scheme_provider);
+
         // begin analyze
         query_state_machine.begin_analyze();
         let logical_plan = logical_planner
@@ -510,6 +619,40 @@ impl SimpleQueryDispatcher {
             .await
     }
 
+    /// Creates the tables of the continuous aggregate, then starts its stream query if the
+    /// continuous aggregate is newly created. Like the other stream queries, the stream query is
+    /// persisted and will be re-executed after restart.
+    async fn execute_create_continuous_aggregate(
+        &self,
+        tenant_id: Oid,
+        stmt: CreateContinuousAggregate,
+        logical_plan: Plan,
+        query_state_machine: Arc<QueryStateMachine>,
+        span_ctx: Option<&SpanContext>,
+    ) -> QueryResult<Output> {
+        let stream_table = &stmt.stream_table.name;
+        let exists = self
+            .build_current_session_meta_client(&query_state_machine.session)
+            .await?
+            .get_table_schema(stream_table.database(), stream_table.table())
+            .context(MetaSnafu)?
+            .is_some();
+
+        let output = self
+            .execute_logical_plan(logical_plan, query_state_machine.clone())
+            .await?;
+        if exists {
+            return Ok(output);
+        }
+
+        let query = Query::new(
+            query_state_machine.query.context().clone(),
+            stmt.stream_query,
+        );
+        self.execute_query(tenant_id, self.create_query_id(), &query, span_ctx)
+            .await
+    }
+
     async fn build_scheme_provider(&self, session: &SessionCtx) -> QueryResult<MetadataProvider> {
         let meta_client = self.build_current_session_meta_client(session).await?;
         let current_session_table_provider =
@@ -575,6 +718,7 @@ pub struct SimpleQueryDispatcherBuilder {
     stream_provider_manager: Option<StreamProviderManagerRef>,
     span_ctx: Option<SpanContext>,
     auth_cache: Option<Arc<AuthCache<AuthCacheKey, User>>>,
+    audit_sink: Option<AuditSinkRef>,
 }
 
 impl SimpleQueryDispatcherBuilder {
@@ -647,6 +791,11 @@ impl SimpleQueryDispatcherBuilder {
         self
     }
 
+    pub fn with_audit_sink(mut self, audit_sink: AuditSinkRef) -> Self {
+        self.audit_sink = Some(audit_sink);
+        self
+    }
+
     pub fn build(self) -> QueryResult<Arc<SimpleQueryDispatcher>> {
         let coord = self.coord.ok_or_else(|| QueryError::BuildQueryDispatcher {
             err: "lost of coord".to_string(),
@@ -729,6 +878,7 @@ impl SimpleQueryDispatcherBuilder {
             async_task_joinhandle: Arc::new(Mutex::new(HashMap::new())),
             failed_task_joinhandle: Arc::new(Mutex::new(HashMap::new())),
             auth_cache,
+            audit_sink: self.audit_sink,
         });
 
         let meta_task_receiver = dispatcher
@@ -3,6 +3,7 @@ use models::meta_data::NodeId;
 use models::schema::query_info::{QueryId, QueryInfo};
 use spi::QueryResult;
 
+pub mod audit_log;
 pub mod manager;
 pub mod persister;
 pub mod query_tracker;
@@ -119,6 +119,16 @@ impl DDLDefinitionTask for AlterTableTask {
                 alter_schema_func(&mut schema, old_column_name, new_column_name)?;
                 None
             }
+            AlterTableAction::SetTtl { ttl } => {
+                schema.set_ttl(ttl.clone());
+                schema.schema_version += 1;
+                None
+            }
+            AlterTableAction::SetMaxSeries { max_series } => {
+                schema.set_max_series(*max_series);
+                schema.schema_version += 1;
+                None
+            }
         };
 
         if let Some(info) = operator_info {
@@ -0,0 +1,64 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use meta::error::MetaError;
+use models::schema::table_schema::TableSchema;
+use snafu::ResultExt;
+use spi::query::execution::{Output, QueryStateMachineRef};
+use spi::query::logical_planner::AnalyzeTable;
+use spi::{CoordinatorSnafu, MetaSnafu, QueryResult};
+
+use crate::execution::ddl::DDLDefinitionTask;
+
+pub struct AnalyzeTableTask {
+    stmt: AnalyzeTable,
+}
+
+impl AnalyzeTableTask {
+    pub fn new(stmt: AnalyzeTable) -> AnalyzeTableTask {
+        Self { stmt }
+    }
+}
+
+#[async_trait]
+impl DDLDefinitionTask for AnalyzeTableTask {
+    async fn execute(&self, query_state_machine: QueryStateMachineRef) -> QueryResult<Output> {
+        let table_name = &self.stmt.table_name;
+        let tenant = table_name.tenant();
+        let client = query_state_machine
+            .meta
+            .tenant_meta(tenant)
+            .await
+            .ok_or_else(|| MetaError::TenantNotFound {
+                tenant: tenant.to_string(),
+            })
+            .context(MetaSnafu)?;
+
+        let statistics = query_state_machine
+            .coord
+            .table_statistics(tenant, table_name.database(), table_name.table())
+            .await
+            .context(CoordinatorSnafu)?;
+
+        // Fetch the schema after the statistics are collected,
+        // the table may be altered during the collection.
+        let mut schema = client
+            .get_tskv_table_schema(table_name.database(), table_name.table())
+            .context(MetaSnafu)?
+            .ok_or_else(|| MetaError::TableNotFound {
+                table: table_name.to_string(),
+            })
+            .context(MetaSnafu)?
+            .as_ref()
+            .clone();
+        schema.set_statistics(Some(statistics));
+        schema.schema_version += 1;
+
+        client
+            .update_table(&TableSchema::TsKvTableSchema(Arc::new(schema)))
+            .await
+            .context(MetaSnafu)?;
+
+        Ok(Output::Nil(()))
+    }
+}
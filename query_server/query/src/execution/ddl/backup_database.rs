@@ -0,0 +1,38 @@
+use async_trait::async_trait;
+use snafu::ResultExt;
+use spi::query::execution::{Output, QueryStateMachineRef};
+use spi::query::logical_planner::BackupDatabase;
+use spi::{CoordinatorSnafu, QueryResult};
+
+use super::DDLDefinitionTask;
+
+pub struct BackupDatabaseTask {
+    stmt: BackupDatabase,
+}
+
+impl BackupDatabaseTask {
+    #[inline(always)]
+    pub fn new(stmt: BackupDatabase) -> Self {
+        Self { stmt }
+    }
+}
+
+#[async_trait]
+impl DDLDefinitionTask for BackupDatabaseTask {
+    async fn execute(&self, query_state_machine: QueryStateMachineRef) -> QueryResult<Output> {
+        let BackupDatabase {
+            database_name,
+            url,
+            options,
+        } = &self.stmt;
+        let tenant = query_state_machine.session.tenant();
+
+        let coord = query_state_machine.coord.clone();
+        coord
+            .backup_database(tenant, database_name, url, options.clone())
+            .await
+            .context(CoordinatorSnafu)?;
+
+        Ok(Output::Nil(()))
+    }
+}
@@ -0,0 +1,69 @@
+use async_trait::async_trait;
+use meta::error::MetaError;
+use snafu::ResultExt;
+use spi::query::datasource::stream::checker::StreamTableCheckerRef;
+use spi::query::execution::{Output, QueryStateMachineRef};
+use spi::query::logical_planner::CreateContinuousAggregate;
+use spi::{MetaSnafu, QueryResult};
+
+use super::create_stream_table::CreateStreamTableTask;
+use super::create_table::CreateTableTask;
+use crate::execution::ddl::DDLDefinitionTask;
+
+/// Creates the rollup table and the stream table of the continuous aggregate,
+/// the stream query is started by the dispatcher after the tables are created.
+pub struct CreateContinuousAggregateTask {
+    checker: Option<StreamTableCheckerRef>,
+    stmt: CreateContinuousAggregate,
+}
+
+impl CreateContinuousAggregateTask {
+    pub fn new(checker: Option<StreamTableCheckerRef>, stmt: CreateContinuousAggregate) -> Self {
+        Self { checker, stmt }
+    }
+}
+
+#[async_trait]
+impl DDLDefinitionTask for CreateContinuousAggregateTask {
+    async fn execute(&self, query_state_machine: QueryStateMachineRef) -> QueryResult<Output> {
+        let CreateContinuousAggregate {
+            ref if_not_exists,
+            ref rollup_table,
+            ref stream_table,
+            ..
+        } = self.stmt;
+
+        let tenant = query_state_machine.session.tenant();
+        let client = query_state_machine
+            .meta
+            .tenant_meta(tenant)
+            .await
+            .ok_or_else(|| MetaError::TenantNotFound {
+                tenant: tenant.to_string(),
+            })
+            .context(MetaSnafu)?;
+        // The continuous aggregate exists if its stream table exists
+        let table = client
+            .get_table_schema(stream_table.name.database(), stream_table.name.table())
+            .context(MetaSnafu)?;
+
+        match (if_not_exists, table) {
+            // do not create if exists
+            (true, Some(_)) => Ok(Output::Nil(())),
+            // Report an error if it exists
+            (false, Some(_)) => Err(MetaError::TableAlreadyExists {
+                table_name: rollup_table.name.to_string(),
+            })
+            .context(MetaSnafu)?,
+            // does not exist, create
+            (_, None) => {
+                CreateTableTask::new(rollup_table.clone())
+                    .execute(query_state_machine.clone())
+                    .await?;
+                CreateStreamTableTask::new(self.checker.clone(), stream_table.clone())
+                    .execute(query_state_machine)
+                    .await
+            }
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use async_trait::async_trait;
+use meta::error::MetaError;
+use snafu::ResultExt;
+use spi::query::execution::{Output, QueryStateMachineRef};
+use spi::query::logical_planner::CreateFunction;
+use spi::{MetaSnafu, QueryError, QueryResult};
+use trace::debug;
+
+use crate::execution::ddl::DDLDefinitionTask;
+use crate::function::user_defined::invalidate_wasm_module;
+
+pub struct CreateFunctionTask {
+    stmt: CreateFunction,
+}
+
+impl CreateFunctionTask {
+    pub fn new(stmt: CreateFunction) -> Self {
+        Self { stmt }
+    }
+}
+
+#[async_trait]
+impl DDLDefinitionTask for CreateFunctionTask {
+    async fn execute(&self, query_state_machine: QueryStateMachineRef) -> QueryResult<Output> {
+        let CreateFunction {
+            ref or_replace,
+            ref if_not_exists,
+            ref function,
+        } = self.stmt;
+
+        let meta = query_state_machine
+            .meta
+            .tenant_meta(&function.tenant)
+            .await
+            .ok_or_else(|| QueryError::Meta {
+                source: MetaError::TenantNotFound {
+                    tenant: function.tenant.to_string(),
+                },
+            })?;
+
+        match (if_not_exists, meta.function(&function.name)) {
+            // do not create if exists
+            (true, Some(_)) => Ok(Output::Nil(())),
+            // create or replace
+            _ => {
+                debug!(
+                    "Create function {} of tenant {}, or replace: {}",
+                    function.name, function.tenant, or_replace
+                );
+
+                meta.create_function(function.clone(), *or_replace)
+                    .await
+                    .context(MetaSnafu)?;
+                invalidate_wasm_module(&function.tenant, &function.name);
+
+                Ok(Output::Nil(()))
+            }
+        }
+    }
+}
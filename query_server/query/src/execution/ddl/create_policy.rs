@@ -0,0 +1,58 @@
+use async_trait::async_trait;
+use meta::error::MetaError;
+use snafu::ResultExt;
+use spi::query::execution::{Output, QueryStateMachineRef};
+use spi::query::logical_planner::CreatePolicy;
+use spi::{MetaSnafu, QueryError, QueryResult};
+use trace::debug;
+
+use crate::execution::ddl::DDLDefinitionTask;
+
+pub struct CreatePolicyTask {
+    stmt: CreatePolicy,
+}
+
+impl CreatePolicyTask {
+    pub fn new(stmt: CreatePolicy) -> Self {
+        Self { stmt }
+    }
+}
+
+#[async_trait]
+impl DDLDefinitionTask for CreatePolicyTask {
+    async fn execute(&self, query_state_machine: QueryStateMachineRef) -> QueryResult<Output> {
+        let CreatePolicy {
+            ref or_replace,
+            ref if_not_exists,
+            ref policy,
+        } = self.stmt;
+
+        let meta = query_state_machine
+            .meta
+            .tenant_meta(&policy.tenant)
+            .await
+            .ok_or_else(|| QueryError::Meta {
+                source: MetaError::TenantNotFound {
+                    tenant: policy.tenant.to_string(),
+                },
+            })?;
+
+        match (if_not_exists, meta.policy(&policy.name)) {
+            // do not create if exists
+            (true, Some(_)) => Ok(Output::Nil(())),
+            // create or replace
+            _ => {
+                debug!(
+                    "Create policy {} on {}.{} of tenant {}, or replace: {}",
+                    policy.name, policy.database, policy.table, policy.tenant, or_replace
+                );
+
+                meta.create_policy(policy.clone(), *or_replace)
+                    .await
+                    .context(MetaSnafu)?;
+
+                Ok(Output::Nil(()))
+            }
+        }
+    }
+}
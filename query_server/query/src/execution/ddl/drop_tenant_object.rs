@@ -10,6 +10,7 @@ use spi::{CoordinatorSnafu, MetaSnafu, QueryError, QueryResult};
 use trace::debug;
 
 use super::DDLDefinitionTask;
+use crate::function::user_defined::invalidate_wasm_module;
 
 pub struct DropTenantObjectTask {
     stmt: DropTenantObject,
@@ -68,6 +69,37 @@ impl DDLDefinitionTask for DropTenantObjectTask {
                 Ok(Output::Nil(()))
             }
 
+            TenantObjectType::Function => {
+                debug!("Drop function {} of tenant {}", name, tenant_name);
+                let success = meta.drop_function(name).await.context(MetaSnafu)?;
+                invalidate_wasm_module(tenant_name, name);
+
+                if let (false, false) = (if_exist, success) {
+                    return Err(QueryError::Meta {
+                        source: MetaError::FunctionNotFound {
+                            function: name.to_string(),
+                        },
+                    });
+                }
+
+                Ok(Output::Nil(()))
+            }
+
+            TenantObjectType::Policy => {
+                debug!("Drop policy {} of tenant {}", name, tenant_name);
+                let success = meta.drop_policy(name).await.context(MetaSnafu)?;
+
+                if let (false, false) = (if_exist, success) {
+                    return Err(QueryError::Meta {
+                        source: MetaError::PolicyNotFound {
+                            policy: name.to_string(),
+                        },
+                    });
+                }
+
+                Ok(Output::Nil(()))
+            }
+
             TenantObjectType::Database => {
                 // 删除租户下的database
                 // tenant_id
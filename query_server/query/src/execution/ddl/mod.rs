@@ -8,7 +8,10 @@ use spi::QueryResult;
 
 use self::alter_tenant::AlterTenantTask;
 use self::alter_user::AlterUserTask;
+use self::create_continuous_aggregate::CreateContinuousAggregateTask;
 use self::create_external_table::CreateExternalTableTask;
+use self::create_function::CreateFunctionTask;
+use self::create_policy::CreatePolicyTask;
 use self::create_role::CreateRoleTask;
 use self::create_stream_table::CreateStreamTableTask;
 use self::create_table::CreateTableTask;
@@ -27,22 +30,30 @@ use self::replica_remove::ReplicaRemoveTask;
 use self::show_replica::ShowReplicasTask;
 use crate::execution::ddl::alter_database::AlterDatabaseTask;
 use crate::execution::ddl::alter_table::AlterTableTask;
+use crate::execution::ddl::analyze_table::AnalyzeTableTask;
+use crate::execution::ddl::backup_database::BackupDatabaseTask;
 use crate::execution::ddl::checksum_group::ChecksumGroupTask;
 use crate::execution::ddl::compact_vnode::CompactVnodeTask;
 use crate::execution::ddl::copy_vnode::CopyVnodeTask;
 use crate::execution::ddl::create_database::CreateDatabaseTask;
 use crate::execution::ddl::drop_vnode::DropVnodeTask;
 use crate::execution::ddl::move_node::MoveVnodeTask;
+use crate::execution::ddl::restore_database::RestoreDatabaseTask;
 
 mod alter_database;
 mod alter_table;
 mod alter_tenant;
 mod alter_user;
+mod analyze_table;
+mod backup_database;
 mod checksum_group;
 mod compact_vnode;
 mod copy_vnode;
+mod create_continuous_aggregate;
 mod create_database;
 mod create_external_table;
+mod create_function;
+mod create_policy;
 mod create_role;
 mod create_stream_table;
 mod create_table;
@@ -60,6 +71,7 @@ mod replica_add;
 mod replica_destory;
 mod replica_promote;
 mod replica_remove;
+mod restore_database;
 mod show_replica;
 
 /// Traits that DDL tasks should implement
@@ -171,6 +183,7 @@ impl DDLDefinitionTaskFactory {
             DDLPlan::CreateRole(sub_plan) => Box::new(CreateRoleTask::new(sub_plan.clone())),
             DDLPlan::AlterDatabase(sub_plan) => Box::new(AlterDatabaseTask::new(sub_plan.clone())),
             DDLPlan::AlterTable(sub_plan) => Box::new(AlterTableTask::new(sub_plan.clone())),
+            DDLPlan::AnalyzeTable(sub_plan) => Box::new(AnalyzeTableTask::new(sub_plan.clone())),
             DDLPlan::AlterTenant(sub_plan) => Box::new(AlterTenantTask::new(sub_plan.clone())),
             DDLPlan::AlterUser(sub_plan) => Box::new(AlterUserTask::new(sub_plan.clone())),
             DDLPlan::GrantRevoke(sub_plan) => Box::new(GrantRevokeTask::new(sub_plan.clone())),
@@ -181,11 +194,31 @@ impl DDLDefinitionTaskFactory {
             DDLPlan::ChecksumGroup(sub_plan) => {
                 Box::new(ChecksumGroupTask::new(sub_plan.clone(), self.plan.schema()))
             }
+            DDLPlan::BackupDatabase(sub_plan) => {
+                Box::new(BackupDatabaseTask::new(sub_plan.clone()))
+            }
+            DDLPlan::RestoreDatabase(sub_plan) => {
+                Box::new(RestoreDatabaseTask::new(sub_plan.clone()))
+            }
             DDLPlan::CreateStreamTable(sub_plan) => {
                 let checker = self.stream_checker_manager.checker(&sub_plan.stream_type);
 
                 Box::new(CreateStreamTableTask::new(checker, sub_plan.clone()))
             }
+            DDLPlan::CreateContinuousAggregate(sub_plan) => {
+                let checker = self
+                    .stream_checker_manager
+                    .checker(&sub_plan.stream_table.stream_type);
+
+                Box::new(CreateContinuousAggregateTask::new(
+                    checker,
+                    sub_plan.clone(),
+                ))
+            }
+            DDLPlan::CreateFunction(sub_plan) => {
+                Box::new(CreateFunctionTask::new(sub_plan.clone()))
+            }
+            DDLPlan::CreatePolicy(sub_plan) => Box::new(CreatePolicyTask::new(sub_plan.clone())),
             DDLPlan::RecoverDatabase(sub_plan) => {
                 Box::new(RecoverDatabaseTask::new(sub_plan.clone()))
             }
@@ -0,0 +1,39 @@
+use async_trait::async_trait;
+use snafu::ResultExt;
+use spi::query::execution::{Output, QueryStateMachineRef};
+use spi::query::logical_planner::RestoreDatabase;
+use spi::{CoordinatorSnafu, QueryResult};
+
+use super::DDLDefinitionTask;
+
+pub struct RestoreDatabaseTask {
+    stmt: RestoreDatabase,
+}
+
+impl RestoreDatabaseTask {
+    #[inline(always)]
+    pub fn new(stmt: RestoreDatabase) -> Self {
+        Self { stmt }
+    }
+}
+
+#[async_trait]
+impl DDLDefinitionTask for RestoreDatabaseTask {
+    async fn execute(&self, query_state_machine: QueryStateMachineRef) -> QueryResult<Output> {
+        let RestoreDatabase {
+            database_name,
+            url,
+            options,
+            as_of,
+        } = &self.stmt;
+        let tenant = query_state_machine.session.tenant();
+
+        let coord = query_state_machine.coord.clone();
+        coord
+            .restore_database(tenant, database_name, url, options.clone(), *as_of)
+            .await
+            .context(CoordinatorSnafu)?;
+
+        Ok(Output::Nil(()))
+    }
+}
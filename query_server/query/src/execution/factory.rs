@@ -21,6 +21,7 @@ use crate::execution::ddl::DDLExecution;
 use crate::extension::logical::plan_node::table_writer_merge::TableWriterMergePlanNode;
 use crate::extension::logical::utils::extract_stream_providers;
 use crate::extension::utils::downcast_plan_node;
+use crate::stream::state_store::disk::DiskStateStoreFactory;
 
 pub struct SqlQueryExecutionFactory {
     optimizer: Arc<dyn Optimizer + Send + Sync>,
@@ -29,6 +30,7 @@ pub struct SqlQueryExecutionFactory {
     trigger_executor_factory: TriggerExecutorFactoryRef,
     runtime: Arc<DedicatedExecutor>,
     stream_checker_manager: StreamCheckerManagerRef,
+    state_store_factory: Arc<DiskStateStoreFactory>,
 }
 
 impl SqlQueryExecutionFactory {
@@ -38,6 +40,7 @@ impl SqlQueryExecutionFactory {
         scheduler: SchedulerRef,
         query_tracker: Arc<QueryTracker>,
         stream_checker_manager: StreamCheckerManagerRef,
+        state_store_factory: Arc<DiskStateStoreFactory>,
         config: Arc<QueryOptions>,
     ) -> Self {
         // Only do periodic scheduling, no need for many threads
@@ -60,6 +63,7 @@ impl SqlQueryExecutionFactory {
             trigger_executor_factory,
             runtime,
             stream_checker_manager,
+            state_store_factory,
         }
     }
 }
@@ -104,6 +108,7 @@ impl QueryExecutionFactory for SqlQueryExecutionFactory {
                                 state_machine,
                                 self.scheduler.clone(),
                                 self.trigger_executor_factory.clone(),
+                                self.state_store_factory.clone(),
                                 self.runtime.clone(),
                             )
                             .await?;
@@ -25,7 +25,7 @@ use spi::query::scheduler::SchedulerRef;
 use spi::QueryResult;
 use trace::error;
 
-use self::trigger::executor::{TriggerExecutorFactoryRef, TriggerExecutorRef};
+use self::trigger::executor::{BatchProgress, TriggerExecutorFactoryRef, TriggerExecutorRef};
 use crate::extension::analyse::stream_checker::UnsupportedOperationChecker;
 use crate::extension::analyse::AnalyzerRule;
 use crate::extension::logical::utils::extract_stream_providers;
@@ -36,8 +36,8 @@ use crate::sql::logical::optimizer::{DefaultLogicalOptimizer, LogicalOptimizer};
 use crate::sql::physical::optimizer::PhysicalOptimizer;
 use crate::sql::physical::planner::DefaultPhysicalPlanner;
 use crate::stream::offset_tracker::{OffsetTracker, OffsetTrackerRef};
-use crate::stream::state_store::memory::MemoryStateStoreFactory;
-use crate::stream::state_store::StateStoreFactory;
+use crate::stream::state_store::disk::DiskStateStoreFactory;
+use crate::stream::state_store::{Checkpoint, StateStoreFactory};
 use crate::stream::watermark_tracker::{WatermarkTracker, WatermarkTrackerRef};
 
 #[derive(Debug, Clone)]
@@ -94,6 +94,7 @@ impl MicroBatchStreamExecutionBuilder {
         query_state_machine: QueryStateMachineRef,
         scheduler: SchedulerRef,
         trigger_executor_factory: TriggerExecutorFactoryRef,
+        state_store_factory: Arc<DiskStateStoreFactory>,
         runtime: Arc<DedicatedExecutor>,
     ) -> QueryResult<MicroBatchStreamExecution> {
         let MicroBatchStreamExecutionDesc {
@@ -115,6 +116,26 @@ impl MicroBatchStreamExecutionBuilder {
             )
             .await?,
         );
+        let offset_tracker = Arc::new(OffsetTracker::new());
+
+        let query_id = query_state_machine.query_id.to_string();
+        let mut start_batch_id = 0;
+        if query_state_machine.query.context().is_old() {
+            // Resume the stream query where it stopped
+            if let Some(checkpoint) = state_store_factory.last_checkpoint(&query_id)? {
+                trace::info!(
+                    "Resume stream query {} from checkpoint: {:?}",
+                    query_id,
+                    checkpoint
+                );
+                offset_tracker.restore(checkpoint.offsets);
+                watermark_tracker.update_watermark(checkpoint.watermark_ns, 0);
+                start_batch_id = checkpoint.batch_id + 1;
+            }
+        } else {
+            // Remove the states left by a stream query with the same id
+            state_store_factory.clear(&query_id)?;
+        }
 
         Ok(MicroBatchStreamExecution {
             query_state_machine,
@@ -123,8 +144,9 @@ impl MicroBatchStreamExecutionBuilder {
             scheduler,
             trigger_executor,
             watermark_tracker,
-            offset_tracker: Arc::new(OffsetTracker::new()),
-            state_store_factory: Arc::new(MemoryStateStoreFactory::default()),
+            offset_tracker,
+            state_store_factory,
+            start_batch_id,
             runtime,
             abort_handle: Mutex::new(None),
         })
@@ -137,9 +159,11 @@ pub struct MicroBatchStreamExecution {
     stream_providers: Vec<StreamProviderRef>,
     scheduler: SchedulerRef,
     trigger_executor: TriggerExecutorRef,
-    state_store_factory: Arc<MemoryStateStoreFactory>,
+    state_store_factory: Arc<DiskStateStoreFactory>,
     watermark_tracker: WatermarkTrackerRef,
     offset_tracker: OffsetTrackerRef,
+    /// Id of the first batch, following the batch of the last checkpoint
+    start_batch_id: i64,
     runtime: Arc<DedicatedExecutor>,
     abort_handle: Mutex<Option<Job<()>>>,
 }
@@ -159,14 +183,15 @@ impl MicroBatchStreamExecution {
         let state_store_factory = self.state_store_factory.clone();
         let runtime = self.runtime.clone();
         let offset_tracker = self.offset_tracker.clone();
+        let start_batch_id = self.start_batch_id;
 
         let result = self.trigger_executor.schedule(
-            move |current_batch_id| {
+            move |current_batch_id, flush| {
                 let exec = IncrementalExecution {
                     query_state_machine: query_state_machine.clone(),
                     plan: plan.clone(),
                     scheduler: scheduler.clone(),
-                    current_batch_id,
+                    current_batch_id: start_batch_id + current_batch_id,
                     stream_providers: stream_providers.clone(),
                     watermark_tracker: watermark_tracker.clone(),
                     state_store_factory: state_store_factory.clone(),
@@ -174,7 +199,7 @@ impl MicroBatchStreamExecution {
                 };
 
                 async move {
-                    exec.execute().await.map_err(|err| {
+                    exec.execute(flush).await.map_err(|err| {
                         error!("Execute stream query error: {err}");
                         err
                     })
@@ -201,6 +226,21 @@ async fn update_available_offsets(
     Ok(())
 }
 
+/// Inform the sources of the offsets committed to offset_tracker
+async fn commit_processed_offsets(
+    offset_tracker: &OffsetTracker,
+    stream_providers: &[StreamProviderRef],
+) -> DFResult<()> {
+    let processed_offsets = offset_tracker.processed_offsets();
+    for s in stream_providers {
+        if let Some(offset) = processed_offsets.get(&s.id()) {
+            s.commit(*offset).await?;
+        }
+    }
+
+    Ok(())
+}
+
 #[async_trait]
 impl QueryExecution for MicroBatchStreamExecution {
     fn query_type(&self) -> QueryType {
@@ -238,6 +278,15 @@ impl QueryExecution for MicroBatchStreamExecution {
         self.query_state_machine.cancel();
         // stop future task
         *self.abort_handle.lock() = None;
+        // a canceled stream query will never be resumed
+        let query_id = self.query_state_machine.query_id.to_string();
+        if let Err(err) = self.state_store_factory.clear(&query_id) {
+            trace::warn!(
+                "Failed to remove states of stream query {}: {}",
+                query_id,
+                err
+            );
+        }
 
         trace::info!(
             "Canceled sql query execution: query_id: {:?}, sql: {}, state: {:?}",
@@ -292,21 +341,28 @@ where
     T: StateStoreFactory + Send + Sync + Debug + 'static,
     T::SS: Send + Sync + Debug,
 {
-    async fn execute(&self) -> QueryResult<()> {
+    /// Execute a micro-batch, `flush` forces the execution without new data,
+    /// so that the states expired by the current watermark are emitted.
+    async fn execute(&self, flush: bool) -> QueryResult<BatchProgress> {
         // 1. Traverse the data source list of the execution plan, check whether there is new data, and update offset_tracker
         update_available_offsets(self.offset_tracker.clone(), &self.stream_providers).await?;
         trace::trace!("Traverse the data source list of the execution plan, check whether there is new data, and update offset_tracker");
 
         // 2. Exit this execution if there is no new data
-        if !self.offset_tracker.has_available_offsets() {
+        if !flush && !self.offset_tracker.has_available_offsets() {
             trace::trace!("Exit this execution if there is no new data");
-            return Ok(());
+            let watermark_ns = self.watermark_tracker.current_watermark_ns();
+            return Ok(BatchProgress {
+                processed: false,
+                watermark_ns_before: watermark_ns,
+                watermark_ns_after: watermark_ns,
+            });
         }
 
         self.execute_once().await
     }
 
-    async fn execute_once(&self) -> QueryResult<()> {
+    async fn execute_once(&self) -> QueryResult<BatchProgress> {
         let session = &self.query_state_machine.session;
         let current_watermark_ns = self.watermark_tracker.current_watermark_ns();
         let available_offsets = self.offset_tracker.available_offsets();
@@ -367,6 +423,17 @@ where
             // After processing a batch, the watermark is updated, then submit to offset_tracker
             // If not updated, it means that the data has not been processed
             self.offset_tracker.commit(after_process_watermark_ns);
+            commit_processed_offsets(&self.offset_tracker, &self.stream_providers).await?;
+            // Checkpoint the committed states together with the offsets,
+            // so that a restarted query resumes exactly where it stopped
+            self.state_store_factory.checkpoint(
+                &self.query_state_machine.query_id.to_string(),
+                Checkpoint {
+                    batch_id: self.current_batch_id,
+                    watermark_ns: after_process_watermark_ns,
+                    offsets: self.offset_tracker.processed_offsets(),
+                },
+            )?;
             // Persist watermark, in order to load the last watermark when restoring
             self.watermark_tracker
                 .commit(
@@ -379,6 +446,10 @@ where
                 .update_watermark(current_watermark_ns, 0);
         }
 
-        Ok(())
+        Ok(BatchProgress {
+            processed: true,
+            watermark_ns_before: current_watermark_ns,
+            watermark_ns_after: after_process_watermark_ns,
+        })
     }
 }
@@ -1,11 +1,47 @@
 use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
 use futures::Future;
 use models::runtime::executor::{DedicatedExecutor, Job};
 use spi::query::config::StreamTriggerInterval;
 use spi::QueryError;
 
+/// Pause of the continuous and watermark triggers after a batch without new data
+const IDLE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Progress made by a micro-batch, used by the triggers to decide when to run the next one.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct BatchProgress {
+    /// Whether new data has been consumed by the batch
+    pub processed: bool,
+    pub watermark_ns_before: i64,
+    pub watermark_ns_after: i64,
+}
+
+impl BatchProgress {
+    pub fn watermark_advanced(&self) -> bool {
+        self.watermark_ns_after > self.watermark_ns_before
+    }
+
+    /// Whether the watermark has passed the end of a window during the batch.
+    /// Windows end at multiples of `window`, `None` means any advance of the watermark.
+    pub fn closes_window(&self, window: Option<Duration>) -> bool {
+        if !self.watermark_advanced() {
+            return false;
+        }
+
+        match window {
+            None => true,
+            Some(window) => {
+                let window_ns = (window.as_nanos().min(i64::MAX as u128) as i64).max(1);
+                self.watermark_ns_before.div_euclid(window_ns)
+                    < self.watermark_ns_after.div_euclid(window_ns)
+            }
+        }
+    }
+}
+
 pub type TriggerExecutorFactoryRef = Arc<TriggerExecutorFactory>;
 
 pub struct TriggerExecutorFactory {
@@ -43,40 +79,69 @@ pub struct TriggerExecutor {
 }
 
 impl TriggerExecutor {
+    /// Schedule `task` according to the trigger.
+    ///
+    /// The task receives the id of the batch and whether the batch must be executed
+    /// even if there is no new data, which is used to emit the windows closed by the watermark.
     pub fn schedule<F, T>(&self, task: F, runtime: Arc<DedicatedExecutor>) -> Job<()>
     where
-        F: Fn(i64) -> T,
+        F: Fn(i64, bool) -> T,
         F: Send + Sync + 'static,
-        T: Future<Output = Result<(), QueryError>> + Send + 'static,
-        // T::Output: Send + 'static,
+        T: Future<Output = Result<BatchProgress, QueryError>> + Send + 'static,
     {
         let current_batch_id = AtomicI64::default();
         let fetch_add_batch_id = move || current_batch_id.fetch_add(1, Ordering::Relaxed);
         let processed_count = self.processed_count.clone();
         let err_counter = self.err_counter.clone();
-
-        match self.trigger {
-            StreamTriggerInterval::Once => self.runtime.spawn(async move {
-                match task(fetch_add_batch_id()).await {
-                    Ok(_) => {}
-                    Err(_) => {
+        let run_batch = move |flush: bool| {
+            let job = runtime.spawn(task(fetch_add_batch_id(), flush));
+            let processed_count = processed_count.clone();
+            let err_counter = err_counter.clone();
+            async move {
+                let progress = match job.await {
+                    Ok(Ok(progress)) => Some(progress),
+                    _ => {
+                        // Record failed status
                         let _ = err_counter.fetch_add(1, Ordering::Relaxed);
+                        None
                     }
-                }
+                };
                 let _ = processed_count.fetch_add(1, Ordering::Relaxed);
+                progress
+            }
+        };
+
+        match self.trigger {
+            StreamTriggerInterval::Once => self.runtime.spawn(async move {
+                let _ = run_batch(false).await;
             }),
             StreamTriggerInterval::Interval(d) => self.runtime.spawn(async move {
                 let mut ticker = tokio::time::interval(d);
                 loop {
-                    match runtime.spawn(task(fetch_add_batch_id())).await {
-                        Ok(Ok(_)) => {}
-                        _ => {
-                            // Record failed status
-                            let _ = err_counter.fetch_add(1, Ordering::Relaxed);
+                    let _ = run_batch(false).await;
+                    ticker.tick().await;
+                }
+            }),
+            StreamTriggerInterval::Continuous => self.runtime.spawn(async move {
+                loop {
+                    let progress = run_batch(false).await;
+                    if !progress.map(|p| p.processed).unwrap_or_default() {
+                        tokio::time::sleep(IDLE_INTERVAL).await;
+                    }
+                }
+            }),
+            StreamTriggerInterval::Watermark(window) => self.runtime.spawn(async move {
+                loop {
+                    let progress = run_batch(false).await;
+                    match progress {
+                        Some(p) if p.closes_window(window) => {
+                            // The windows closed by the new watermark are emitted
+                            // by the next batch, run it right now instead of waiting for new data
+                            let _ = run_batch(true).await;
                         }
+                        Some(p) if p.processed => {}
+                        _ => tokio::time::sleep(IDLE_INTERVAL).await,
                     }
-                    let _ = processed_count.fetch_add(1, Ordering::Relaxed);
-                    ticker.tick().await;
                 }
             }),
         }
@@ -90,3 +155,105 @@ impl TriggerExecutor {
         self.err_counter.load(Ordering::Relaxed)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::collections::VecDeque;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use models::runtime::executor::DedicatedExecutor;
+    use parking_lot::Mutex;
+    use spi::query::config::StreamTriggerInterval;
+    use spi::QueryError;
+
+    use super::{BatchProgress, TriggerExecutorFactory};
+
+    fn progress(before: i64, after: i64) -> BatchProgress {
+        BatchProgress {
+            processed: true,
+            watermark_ns_before: before,
+            watermark_ns_after: after,
+        }
+    }
+
+    #[test]
+    fn test_closes_window() {
+        let window = Some(Duration::from_nanos(10));
+
+        assert!(!progress(5, 5).closes_window(None));
+        assert!(progress(5, 6).closes_window(None));
+
+        assert!(!progress(1, 9).closes_window(window));
+        assert!(progress(9, 10).closes_window(window));
+        assert!(progress(5, 25).closes_window(window));
+        assert!(!progress(10, 19).closes_window(window));
+        assert!(progress(-5, 0).closes_window(window));
+        assert!(progress(i64::MIN, 1).closes_window(window));
+        assert!(!progress(20, 10).closes_window(window));
+    }
+
+    /// Late data are consumed without advancing the watermark, so they don't close windows.
+    #[tokio::test]
+    async fn test_watermark_trigger_with_late_data() {
+        let runtime = Arc::new(DedicatedExecutor::new("test-stream-trigger", 1));
+        let factory = TriggerExecutorFactory::new(runtime.clone());
+        let executor = factory.create(&StreamTriggerInterval::Watermark(Some(
+            Duration::from_nanos(10),
+        )));
+
+        // Whether new data are consumed and the watermark after each batch:
+        // data, late data, data closing the window [0, 10), late data of the closed window
+        let batches = VecDeque::from([(true, 5), (true, 5), (true, 12), (true, 12)]);
+        // Remained batches, watermark, (batch_id, flush) of the executed batches
+        let state = Arc::new(Mutex::new((batches, 0_i64, vec![])));
+        let task_state = state.clone();
+        let _job = executor.schedule(
+            move |batch_id, flush| {
+                let state = task_state.clone();
+                async move {
+                    let mut state = state.lock();
+                    let (batches, watermark, executed) = &mut *state;
+                    executed.push((batch_id, flush));
+                    let (processed, watermark_after) = match flush {
+                        true => (false, *watermark),
+                        false => batches.pop_front().unwrap_or((false, *watermark)),
+                    };
+                    let progress = BatchProgress {
+                        processed,
+                        watermark_ns_before: *watermark,
+                        watermark_ns_after: watermark_after,
+                    };
+                    *watermark = watermark_after;
+                    Ok::<_, QueryError>(progress)
+                }
+            },
+            runtime,
+        );
+
+        tokio::time::timeout(Duration::from_secs(10), async {
+            while executor.processed_count() < 7 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        let executed = state.lock().2.iter().take(7).copied().collect::<Vec<_>>();
+        assert_eq!(
+            executed,
+            vec![
+                (0, false),
+                (1, false),
+                (2, false),
+                // Only the batch closing the window is followed by a flush
+                (3, true),
+                (4, false),
+                // Idle
+                (5, false),
+                (6, false),
+            ]
+        );
+        assert_eq!(executor.error_count(), 0);
+    }
+}
@@ -18,7 +18,7 @@ use datafusion::physical_plan::{
 use futures::{Stream, StreamExt};
 use models::codec::Encoding;
 use models::datafusion::limit_record_batch::limit_record_batch;
-use models::predicate::domain::PredicateRef;
+use models::predicate::domain::{PredicateRef, TimeRanges};
 use models::predicate::PlacedSplit;
 use models::schema::tskv_table_schema::{
     ColumnType, TableColumn, TskvTableSchema, TskvTableSchemaRef,
@@ -169,8 +169,14 @@ impl ExecutionPlan for TskvExec {
     }
 
     fn statistics(&self) -> Statistics {
-        // TODO
-        Statistics::default()
+        let Some(statistics) = self.table_schema.statistics() else {
+            return Statistics::default();
+        };
+        let time_ranges = match self.filter.resolve(&self.table_schema) {
+            Ok(predicate) => predicate.time_ranges(),
+            Err(_) => Arc::new(TimeRanges::all()),
+        };
+        statistics.to_df_statistics(&self.table_schema, &self.proj_schema, &time_ranges)
     }
 
     fn metrics(&self) -> Option<MetricsSet> {
@@ -1 +1,3 @@
 pub mod simple_func_manager;
+pub mod user_defined;
+mod wasm;
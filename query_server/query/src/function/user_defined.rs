@@ -0,0 +1,244 @@
+//! Functions created by `CREATE FUNCTION` in a tenant.
+//!
+//! The calls of SQL functions are replaced by their bodies when planning,
+//! so the implementation of their [`ScalarUDF`] is never executed.
+
+use std::sync::Arc;
+
+use datafusion::arrow::array::{new_empty_array, ArrayRef};
+use datafusion::arrow::datatypes::DataType;
+use datafusion::common::{DataFusionError, Result as DFResult};
+use datafusion::logical_expr::{
+    AccumulatorFactoryFunction, AggregateUDF, ReturnTypeFunction, ScalarUDF, Signature,
+    StateTypeFunction, Volatility,
+};
+use datafusion::physical_expr::functions::make_scalar_function;
+use datafusion::physical_plan::Accumulator;
+use datafusion::scalar::ScalarValue;
+use models::schema::function_schema::{FunctionBody, FunctionKind, FunctionSchema};
+use wasmtime::{Val, ValType};
+
+use super::wasm::{
+    scalar_to_val, val_to_scalar, wasm_val_type, WasmInstance, WasmModule, WASM_AGGREGATE_INIT,
+    WASM_AGGREGATE_MERGE, WASM_AGGREGATE_UPDATE, WASM_MODULE_CACHE, WASM_SCALAR_EVAL,
+};
+
+fn signature(function: &FunctionSchema) -> Signature {
+    Signature::exact(function.arg_types(), Volatility::Immutable)
+}
+
+fn return_type_func(function: &FunctionSchema) -> ReturnTypeFunction {
+    let return_type = Arc::new(function.return_type.clone());
+    Arc::new(move |_| Ok(return_type.clone()))
+}
+
+fn wasm_types(function: &FunctionSchema) -> DFResult<(Vec<ValType>, ValType)> {
+    let unsupported = |data_type: &DataType| {
+        DataFusionError::Plan(format!(
+            "data type {data_type} is not supported by WASM function {}",
+            function.name
+        ))
+    };
+
+    let params = function
+        .args
+        .iter()
+        .map(|arg| wasm_val_type(&arg.data_type).ok_or_else(|| unsupported(&arg.data_type)))
+        .collect::<DFResult<Vec<_>>>()?;
+    let result =
+        wasm_val_type(&function.return_type).ok_or_else(|| unsupported(&function.return_type))?;
+
+    Ok((params, result))
+}
+
+/// Compile the module of the WASM function and check its exports, fails if it is a SQL function.
+pub fn check_wasm_function(function: &FunctionSchema) -> DFResult<WasmModule> {
+    let FunctionBody::Wasm(wasm) = &function.body else {
+        return Err(DataFusionError::Internal(format!(
+            "{} is not a WASM function",
+            function.name
+        )));
+    };
+
+    let module = WasmModule::try_new(&function.name, wasm)?;
+    let (params, result) = wasm_types(function)?;
+    match function.kind {
+        FunctionKind::Scalar => module.check_export(WASM_SCALAR_EVAL, &params, &result)?,
+        FunctionKind::Aggregate => {
+            let state_params = [&[result.clone()], params.as_slice()].concat();
+            module.check_export(WASM_AGGREGATE_INIT, &[], &result)?;
+            module.check_export(WASM_AGGREGATE_UPDATE, &state_params, &result)?;
+            module.check_export(
+                WASM_AGGREGATE_MERGE,
+                &[result.clone(), result.clone()],
+                &result,
+            )?;
+        }
+    }
+
+    Ok(module)
+}
+
+/// The compiled module of the WASM function, which is cached until the function
+/// is dropped or replaced.
+fn cached_wasm_module(function: &FunctionSchema) -> DFResult<WasmModule> {
+    WASM_MODULE_CACHE.get_or_compile(function, || check_wasm_function(function))
+}
+
+/// Remove the compiled module of the function, called when it is dropped or replaced.
+pub fn invalidate_wasm_module(tenant: &str, name: &str) {
+    WASM_MODULE_CACHE.invalidate(tenant, name)
+}
+
+pub fn user_defined_scalar_udf(function: &FunctionSchema) -> DFResult<ScalarUDF> {
+    let name = function.name.clone();
+    let fun = match &function.body {
+        FunctionBody::Sql(_) => make_scalar_function(move |_: &[ArrayRef]| {
+            Err(DataFusionError::Internal(format!(
+                "SQL function {name} should be expanded when planning"
+            )))
+        }),
+        FunctionBody::Wasm(_) => {
+            let module = cached_wasm_module(function)?;
+            let return_type = function.return_type.clone();
+            make_scalar_function(move |args: &[ArrayRef]| {
+                wasm_scalar_eval(&module, args, &return_type)
+            })
+        }
+    };
+
+    Ok(ScalarUDF::new(
+        &function.name,
+        &signature(function),
+        &return_type_func(function),
+        &fun,
+    ))
+}
+
+/// Call `eval` of the module for each row, returns null if any argument is null.
+fn wasm_scalar_eval(
+    module: &WasmModule,
+    args: &[ArrayRef],
+    return_type: &DataType,
+) -> DFResult<ArrayRef> {
+    let mut instance = module.instantiate()?;
+    let num_rows = args.first().map(|arg| arg.len()).unwrap_or(1);
+
+    let values = (0..num_rows)
+        .map(|row| match row_vals(args, row)? {
+            Some(vals) => val_to_scalar(&instance.call(WASM_SCALAR_EVAL, &vals)?, return_type),
+            None => ScalarValue::try_from(return_type),
+        })
+        .collect::<DFResult<Vec<_>>>()?;
+
+    if values.is_empty() {
+        return Ok(new_empty_array(return_type));
+    }
+    ScalarValue::iter_to_array(values)
+}
+
+/// Returns None if any value of the row is null.
+fn row_vals(args: &[ArrayRef], row: usize) -> DFResult<Option<Vec<Val>>> {
+    let mut vals = Vec::with_capacity(args.len());
+    for arg in args {
+        match scalar_to_val(&ScalarValue::try_from_array(arg, row)?)? {
+            Some(val) => vals.push(val),
+            None => return Ok(None),
+        }
+    }
+
+    Ok(Some(vals))
+}
+
+pub fn user_defined_aggregate_udf(function: &FunctionSchema) -> DFResult<AggregateUDF> {
+    let module = cached_wasm_module(function)?;
+    let state_type = Arc::new(vec![function.return_type.clone()]);
+    let state_type_func: StateTypeFunction = Arc::new(move |_, _| Ok(state_type.clone()));
+
+    let return_type = function.return_type.clone();
+    let accumulator: AccumulatorFactoryFunction = Arc::new(move |_, _| {
+        Ok(Box::new(WasmAccumulator::try_new(
+            &module,
+            return_type.clone(),
+        )?))
+    });
+
+    Ok(AggregateUDF::new(
+        &function.name,
+        &signature(function),
+        &return_type_func(function),
+        &accumulator,
+        &state_type_func,
+    ))
+}
+
+/// Accumulates the rows by the `update` of the module, the rows with any null argument are skipped.
+/// The state is the only intermediate value, and is also the result.
+///
+/// Each accumulator has its own instance, whose memories are accounted in [`Accumulator::size`],
+/// so aggregations with many groups are limited by the memory pool of the query.
+#[derive(Debug)]
+struct WasmAccumulator {
+    instance: WasmInstance,
+    memory_size: usize,
+    state: Val,
+    return_type: DataType,
+}
+
+impl WasmAccumulator {
+    fn try_new(module: &WasmModule, return_type: DataType) -> DFResult<Self> {
+        let mut instance = module.instantiate()?;
+        let state = instance.call(WASM_AGGREGATE_INIT, &[])?;
+        let memory_size = instance.memory_size();
+
+        Ok(Self {
+            instance,
+            memory_size,
+            state,
+            return_type,
+        })
+    }
+}
+
+impl Accumulator for WasmAccumulator {
+    fn state(&self) -> DFResult<Vec<ScalarValue>> {
+        Ok(vec![self.evaluate()?])
+    }
+
+    fn update_batch(&mut self, values: &[ArrayRef]) -> DFResult<()> {
+        self.instance.refuel()?;
+
+        let num_rows = values.first().map(|arg| arg.len()).unwrap_or(0);
+        for row in 0..num_rows {
+            if let Some(vals) = row_vals(values, row)? {
+                let args = [&[self.state.clone()], vals.as_slice()].concat();
+                self.state = self.instance.call(WASM_AGGREGATE_UPDATE, &args)?;
+            }
+        }
+        self.memory_size = self.instance.memory_size();
+
+        Ok(())
+    }
+
+    fn merge_batch(&mut self, states: &[ArrayRef]) -> DFResult<()> {
+        self.instance.refuel()?;
+
+        for row in 0..states[0].len() {
+            if let Some(vals) = row_vals(states, row)? {
+                let args = [self.state.clone(), vals[0].clone()];
+                self.state = self.instance.call(WASM_AGGREGATE_MERGE, &args)?;
+            }
+        }
+        self.memory_size = self.instance.memory_size();
+
+        Ok(())
+    }
+
+    fn evaluate(&self) -> DFResult<ScalarValue> {
+        val_to_scalar(&self.state, &self.return_type)
+    }
+
+    fn size(&self) -> usize {
+        std::mem::size_of_val(self) + self.memory_size
+    }
+}
@@ -0,0 +1,288 @@
+//! Sandboxed execution of WebAssembly user-defined functions.
+//!
+//! A module can not import anything from the host, and each instance is limited by
+//! the fuel (about the number of executed instructions) and the size of its memories.
+
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+
+use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
+use base64::Engine as _;
+use datafusion::arrow::datatypes::{DataType, TimeUnit};
+use datafusion::common::{DataFusionError, Result as DFResult};
+use datafusion::scalar::ScalarValue;
+use lazy_static::lazy_static;
+use models::schema::function_schema::{FunctionSchema, WasmFunction};
+use parking_lot::RwLock;
+use wasmtime::{
+    Config, Engine, ExternType, Instance, Module, Store, StoreLimits, StoreLimitsBuilder, Val,
+    ValType,
+};
+
+/// Exported by the module of a scalar function, called with the arguments of each row.
+pub const WASM_SCALAR_EVAL: &str = "eval";
+/// Exported by the module of an aggregate function, returns the initial state.
+pub const WASM_AGGREGATE_INIT: &str = "init";
+/// Exported by the module of an aggregate function, accumulates a row into the state.
+pub const WASM_AGGREGATE_UPDATE: &str = "update";
+/// Exported by the module of an aggregate function, merges two states.
+pub const WASM_AGGREGATE_MERGE: &str = "merge";
+
+lazy_static! {
+    static ref ENGINE: Engine = {
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        Engine::new(&config).expect("create WebAssembly engine")
+    };
+    pub static ref WASM_MODULE_CACHE: WasmModuleCache = WasmModuleCache::default();
+}
+
+fn wasm_err(name: &str, err: impl std::fmt::Display) -> DataFusionError {
+    DataFusionError::Execution(format!("WASM function {name}: {err}"))
+}
+
+/// The WebAssembly value type of the data types supported by WebAssembly functions.
+pub fn wasm_val_type(data_type: &DataType) -> Option<ValType> {
+    match data_type {
+        DataType::Int64 | DataType::UInt64 | DataType::Timestamp(_, _) => Some(ValType::I64),
+        DataType::Float64 => Some(ValType::F64),
+        DataType::Boolean => Some(ValType::I32),
+        _ => None,
+    }
+}
+
+fn val_type_eq(left: &ValType, right: &ValType) -> bool {
+    matches!(
+        (left, right),
+        (ValType::I32, ValType::I32) | (ValType::I64, ValType::I64) | (ValType::F64, ValType::F64)
+    )
+}
+
+/// Returns None if the value is null.
+pub fn scalar_to_val(value: &ScalarValue) -> DFResult<Option<Val>> {
+    let val = match value {
+        ScalarValue::Int64(v)
+        | ScalarValue::TimestampSecond(v, _)
+        | ScalarValue::TimestampMillisecond(v, _)
+        | ScalarValue::TimestampMicrosecond(v, _)
+        | ScalarValue::TimestampNanosecond(v, _) => v.map(Val::I64),
+        ScalarValue::UInt64(v) => v.map(|v| Val::I64(v as i64)),
+        ScalarValue::Float64(v) => v.map(|v| Val::F64(v.to_bits())),
+        ScalarValue::Boolean(v) => v.map(|v| Val::I32(v as i32)),
+        other => {
+            return Err(DataFusionError::NotImplemented(format!(
+                "data type {} of WASM function",
+                other.get_datatype()
+            )))
+        }
+    };
+
+    Ok(val)
+}
+
+pub fn val_to_scalar(val: &Val, data_type: &DataType) -> DFResult<ScalarValue> {
+    let value = match (val, data_type) {
+        (Val::I64(v), DataType::Int64) => ScalarValue::Int64(Some(*v)),
+        (Val::I64(v), DataType::UInt64) => ScalarValue::UInt64(Some(*v as u64)),
+        (Val::I64(v), DataType::Timestamp(unit, tz)) => match unit {
+            TimeUnit::Second => ScalarValue::TimestampSecond(Some(*v), tz.clone()),
+            TimeUnit::Millisecond => ScalarValue::TimestampMillisecond(Some(*v), tz.clone()),
+            TimeUnit::Microsecond => ScalarValue::TimestampMicrosecond(Some(*v), tz.clone()),
+            TimeUnit::Nanosecond => ScalarValue::TimestampNanosecond(Some(*v), tz.clone()),
+        },
+        (Val::F64(bits), DataType::Float64) => ScalarValue::Float64(Some(f64::from_bits(*bits))),
+        (Val::I32(v), DataType::Boolean) => ScalarValue::Boolean(Some(*v != 0)),
+        (val, data_type) => {
+            return Err(DataFusionError::Execution(format!(
+                "WASM function returns {:?}, expected {data_type}",
+                val.ty()
+            )))
+        }
+    };
+
+    Ok(value)
+}
+
+/// A compiled WebAssembly module.
+#[derive(Clone)]
+pub struct WasmModule {
+    name: String,
+    module: Module,
+    fuel: u64,
+    max_memory: usize,
+}
+
+impl Debug for WasmModule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WasmModule")
+            .field("name", &self.name)
+            .field("fuel", &self.fuel)
+            .field("max_memory", &self.max_memory)
+            .finish()
+    }
+}
+
+impl WasmModule {
+    pub fn try_new(name: &str, function: &WasmFunction) -> DFResult<Self> {
+        let binary = BASE64_STANDARD
+            .decode(&function.module)
+            .map_err(|e| wasm_err(name, format!("invalid base64 module, {e}")))?;
+        let module = Module::new(&ENGINE, binary).map_err(|e| wasm_err(name, e))?;
+
+        Ok(Self {
+            name: name.to_string(),
+            module,
+            fuel: function.fuel,
+            max_memory: function.max_memory as usize,
+        })
+    }
+
+    /// Check that the module exports the function with the given signature.
+    pub fn check_export(&self, export: &str, params: &[ValType], result: &ValType) -> DFResult<()> {
+        let func_type = match self.module.get_export(export) {
+            Some(ExternType::Func(func_type)) => func_type,
+            _ => {
+                return Err(wasm_err(
+                    &self.name,
+                    format!("missing export function {export}"),
+                ))
+            }
+        };
+
+        let params_match = func_type.params().len() == params.len()
+            && func_type
+                .params()
+                .zip(params)
+                .all(|(actual, expected)| val_type_eq(&actual, expected));
+        let results = func_type.results().collect::<Vec<_>>();
+        let result_match = results.len() == 1 && val_type_eq(&results[0], result);
+
+        if !params_match || !result_match {
+            return Err(wasm_err(
+                &self.name,
+                format!("the signature of export function {export} does not match"),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Create an instance in a new sandbox with the full fuel.
+    pub fn instantiate(&self) -> DFResult<WasmInstance> {
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(self.max_memory)
+            .instances(1)
+            .build();
+        let mut store = Store::new(&ENGINE, limits);
+        store.limiter(|limits| limits);
+        store
+            .set_fuel(self.fuel)
+            .map_err(|e| wasm_err(&self.name, e))?;
+        let instance =
+            Instance::new(&mut store, &self.module, &[]).map_err(|e| wasm_err(&self.name, e))?;
+
+        Ok(WasmInstance {
+            name: self.name.clone(),
+            fuel: self.fuel,
+            store,
+            instance,
+        })
+    }
+}
+
+/// Compiled modules of the WASM functions of each tenant by the function name and version,
+/// compiling a module is much slower than instantiating it.
+///
+/// A replaced function has a new version, so the query nodes which have not seen the
+/// replacement yet won't use the module of the new version as the old one.
+#[derive(Default)]
+pub struct WasmModuleCache {
+    tenants: RwLock<HashMap<String, HashMap<(String, u64), WasmModule>>>,
+}
+
+impl WasmModuleCache {
+    /// Get the module of the function, `compile` is called if it is not cached.
+    pub fn get_or_compile(
+        &self,
+        function: &FunctionSchema,
+        compile: impl FnOnce() -> DFResult<WasmModule>,
+    ) -> DFResult<WasmModule> {
+        let key = (function.name.clone(), function.version);
+        if let Some(module) = self
+            .tenants
+            .read()
+            .get(&function.tenant)
+            .and_then(|modules| modules.get(&key))
+        {
+            return Ok(module.clone());
+        }
+
+        let module = compile()?;
+        let mut tenants = self.tenants.write();
+        let modules = tenants.entry(function.tenant.clone()).or_default();
+        // Modules of the other versions are replaced.
+        modules.retain(|(name, _), _| *name != function.name);
+        modules.insert(key, module.clone());
+
+        Ok(module)
+    }
+
+    /// Remove the modules of the function, which is dropped or replaced.
+    pub fn invalidate(&self, tenant: &str, name: &str) {
+        if let Some(modules) = self.tenants.write().get_mut(tenant) {
+            modules.retain(|(function_name, _), _| function_name != name);
+        }
+    }
+}
+
+pub struct WasmInstance {
+    name: String,
+    fuel: u64,
+    store: Store<StoreLimits>,
+    instance: Instance,
+}
+
+impl Debug for WasmInstance {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WasmInstance")
+            .field("name", &self.name)
+            .field("fuel", &self.fuel)
+            .finish()
+    }
+}
+
+impl WasmInstance {
+    /// Refill the fuel, which is called before processing each batch of rows.
+    pub fn refuel(&mut self) -> DFResult<()> {
+        self.store
+            .set_fuel(self.fuel)
+            .map_err(|e| wasm_err(&self.name, e))
+    }
+
+    pub fn call(&mut self, export: &str, args: &[Val]) -> DFResult<Val> {
+        let func = self
+            .instance
+            .get_func(&mut self.store, export)
+            .ok_or_else(|| wasm_err(&self.name, format!("missing export function {export}")))?;
+
+        let mut results = [Val::I64(0)];
+        func.call(&mut self.store, args, &mut results)
+            .map_err(|e| wasm_err(&self.name, e))?;
+
+        let [result] = results;
+        Ok(result)
+    }
+
+    /// Size in bytes of the memories exported by the instance.
+    pub fn memory_size(&mut self) -> usize {
+        let memories = self
+            .instance
+            .exports(&mut self.store)
+            .filter_map(|export| export.into_memory())
+            .collect::<Vec<_>>();
+        memories
+            .iter()
+            .map(|memory| memory.data_size(&self.store))
+            .sum()
+    }
+}
@@ -26,9 +26,14 @@ use spi::{AuthSnafu, MetaSnafu, QueryResult};
 use trace::{debug, SpanContext};
 use tskv::kv_option::Options;
 
-use crate::auth::auth_control::{AccessControlImpl, AccessControlNoCheck};
+use crate::auth::auth_control::{external_authenticators, AccessControlImpl, AccessControlNoCheck};
 use crate::data_source::split::SplitManager;
+use crate::data_source::stream::kafka::client::RsKafkaConnector;
+use crate::data_source::stream::kafka::factory::{
+    KafkaStreamProviderFactory, KAFKA_STREAM_PROVIDER,
+};
 use crate::data_source::stream::tskv::factory::{TskvStreamProviderFactory, TSKV_STREAM_PROVIDER};
+use crate::dispatcher::audit_log::AuditLogWriter;
 use crate::dispatcher::manager::SimpleQueryDispatcherBuilder;
 use crate::dispatcher::persister::MetaQueryPersister;
 use crate::dispatcher::query_tracker::QueryTracker;
@@ -40,6 +45,7 @@ use crate::function::simple_func_manager::SimpleFunctionMetadataManager;
 use crate::metadata::BaseTableProvider;
 use crate::sql::optimizer::CascadeOptimizerBuilder;
 use crate::sql::parser::DefaultParser;
+use crate::stream::state_store::disk::DiskStateStoreFactory;
 use crate::variable::simple_sys_var_manager::SimpleSystemVarManager;
 
 pub const DEFAULT_CNOSDB_PATH: &str = ".cnosdb";
@@ -79,6 +85,14 @@ where
         Ok(user)
     }
 
+    async fn authenticate_token(&self, token: &str, tenant_name: &str) -> QueryResult<User> {
+        // not cached, the token may expire before the cache entry
+        self.access_control
+            .access_check_token(token, tenant_name)
+            .await
+            .context(AuthSnafu)
+    }
+
     async fn execute(
         &self,
         query: &Query,
@@ -135,6 +149,19 @@ where
         Ok(logical_plan)
     }
 
+    async fn build_substrait_logical_plan(
+        &self,
+        query_state_machine: QueryStateMachineRef,
+        substrait_plan: &[u8],
+    ) -> QueryResult<Option<Plan>> {
+        let logical_plan = self
+            .query_dispatcher
+            .build_substrait_logical_plan(query_state_machine, substrait_plan)
+            .await?;
+
+        Ok(Some(logical_plan))
+    }
+
     async fn execute_logical_plan(
         &self,
         logical_plan: Plan,
@@ -223,11 +250,23 @@ pub async fn make_cnosdbms(
         tskv_stream_provider_factory.clone(),
     )?;
 
+    // stream provider factory of kafka
+    let kafka_stream_provider_factory = Arc::new(KafkaStreamProviderFactory::new(Arc::new(
+        RsKafkaConnector::default(),
+    )));
+    stream_provider_manager.register_stream_provider_factory(
+        KAFKA_STREAM_PROVIDER,
+        kafka_stream_provider_factory.clone(),
+    )?;
+
     // init stream checker manager
     let mut stream_checker_manager = StreamCheckerManager::default();
     // stream table checker of tskv
     stream_checker_manager
         .register_stream_checker(TSKV_STREAM_PROVIDER, tskv_stream_provider_factory)?;
+    // stream table checker of kafka
+    stream_checker_manager
+        .register_stream_checker(KAFKA_STREAM_PROVIDER, kafka_stream_provider_factory)?;
 
     let query_persister = Arc::new(MetaQueryPersister::new(coord.meta_manager()));
     let query_tracker = Arc::new(QueryTracker::new(
@@ -241,6 +280,9 @@ pub async fn make_cnosdbms(
         scheduler,
         query_tracker.clone(),
         Arc::new(stream_checker_manager),
+        Arc::new(DiskStateStoreFactory::new(
+            options.storage.stream_state_dir(),
+        )),
         options.query.clone(),
     ));
 
@@ -266,7 +308,19 @@ pub async fn make_cnosdbms(
     let auth_cache: Arc<AuthCache<AuthCacheKey, User>> =
         Arc::new(AuthCache::new(1024, Some(Duration::from_secs(60 * 60))));
 
-    let query_dispatcher = SimpleQueryDispatcherBuilder::default()
+    let config = coord.get_config();
+    let audit_log_writer = config
+        .audit
+        .enable
+        .then(|| AuditLogWriter::new(coord.clone(), &config.audit));
+    if let Some(audit_log_writer) = &audit_log_writer {
+        // the meta commands are recorded by the data node proposing them
+        coord
+            .meta_manager()
+            .set_audit_sink(audit_log_writer.clone());
+    }
+
+    let mut query_dispatcher_builder = SimpleQueryDispatcherBuilder::default()
         .with_coord(coord)
         .with_default_table_provider(default_table_provider)
         .with_split_manager(split_manager)
@@ -277,15 +331,22 @@ pub async fn make_cnosdbms(
         .with_query_tracker(query_tracker)
         .with_func_manager(Arc::new(func_manager))
         .with_stream_provider_manager(stream_provider_manager)
-        .with_auth_cache(auth_cache.clone())
-        .build()?;
+        .with_auth_cache(auth_cache.clone());
+    if let Some(audit_log_writer) = audit_log_writer {
+        query_dispatcher_builder = query_dispatcher_builder.with_audit_sink(audit_log_writer);
+    }
+    let query_dispatcher = query_dispatcher_builder.build()?;
 
     let mut builder = CnosdbmsBuilder::default();
 
     let access_control_no_check = AccessControlNoCheck::new(meta_manager);
     if options.query.auth_enabled {
         debug!("build access control");
-        builder.access_control(Arc::new(AccessControlImpl::new(access_control_no_check)))
+        let authenticators = external_authenticators(&config.auth).context(AuthSnafu)?;
+        builder.access_control(Arc::new(
+            AccessControlImpl::new(access_control_no_check)
+                .with_external_authenticators(authenticators, config.auth.group_roles),
+        ))
     } else {
         debug!("build access control without check");
         builder.access_control(Arc::new(access_control_no_check))
@@ -8,6 +8,7 @@ mod execution;
 pub mod extension;
 pub mod function;
 pub mod instance;
+pub mod loki;
 pub mod metadata;
 pub mod prom;
 pub mod sql;
@@ -0,0 +1,63 @@
+pub use crate::prom::promql::ast::{Grouping, Matcher};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    /// Returns the log lines, e.g. `{job="api"} |= "error"`
+    Log(LogSelector),
+    /// Returns samples computed from the log lines, e.g. `rate({job="api"}[5m])`
+    Metric(MetricExpr),
+}
+
+/// A stream selector followed by line filters.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogSelector {
+    pub matchers: Vec<Matcher>,
+    pub line_filters: Vec<LineFilter>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineFilter {
+    pub op: LineFilterOp,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineFilterOp {
+    /// `|=`
+    Contains,
+    /// `!=`
+    NotContains,
+    /// `|~`
+    Match,
+    /// `!~`
+    NotMatch,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RangeOp {
+    /// Number of log lines in the range
+    CountOverTime,
+    /// Number of log lines per second in the range
+    Rate,
+}
+
+impl RangeOp {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "count_over_time" => Some(RangeOp::CountOverTime),
+            "rate" => Some(RangeOp::Rate),
+            _ => None,
+        }
+    }
+}
+
+/// A range aggregation over a log selector, optionally summed up.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricExpr {
+    pub op: RangeOp,
+    pub selector: LogSelector,
+    /// Nanoseconds
+    pub range: i64,
+    /// Grouping of the enclosing `sum`, None if the series are not aggregated
+    pub sum: Option<Grouping>,
+}
@@ -0,0 +1,9 @@
+//! A subset of LogQL: stream selectors, line filters, and the `count_over_time` and `rate`
+//! range aggregations, optionally summed up.
+//!
+//! Queries are translated to SQL over the table holding the logs,
+//! the labels of the streams are the tag columns of the table.
+
+pub mod ast;
+pub mod parser;
+pub mod sql;
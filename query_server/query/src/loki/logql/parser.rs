@@ -0,0 +1,623 @@
+use protos::prompb::prometheus::label_matcher::Type as MatchType;
+use regex::Regex;
+use spi::{QueryError, QueryResult};
+
+use super::ast::{
+    Expr, Grouping, LineFilter, LineFilterOp, LogSelector, Matcher, MetricExpr, RangeOp,
+};
+
+/// Parse a LogQL query, either a log query or a metric query.
+pub fn parse(input: &str) -> QueryResult<Expr> {
+    let tokens = Lexer::new(input).tokenize()?;
+    let mut parser = Parser { tokens, pos: 0 };
+    let expr = parser.parse_expr()?;
+    match parser.peek() {
+        Token::Eof => Ok(expr),
+        t => Err(syntax_error(format!("unexpected {:?} after query", t))),
+    }
+}
+
+/// Parse a LogQL duration such as `5m`, `1h30m` or `500ms` into nanoseconds.
+pub fn parse_duration(s: &str) -> QueryResult<i64> {
+    let bytes = s.as_bytes();
+    let mut pos = 0;
+    let mut total = 0_i64;
+    if bytes.is_empty() {
+        return Err(syntax_error("empty duration".to_string()));
+    }
+    while pos < bytes.len() {
+        let start = pos;
+        while pos < bytes.len() && bytes[pos].is_ascii_digit() {
+            pos += 1;
+        }
+        if start == pos {
+            return Err(syntax_error(format!("invalid duration '{}'", s)));
+        }
+        let num = s[start..pos]
+            .parse::<i64>()
+            .map_err(|_| syntax_error(format!("invalid duration '{}'", s)))?;
+        let unit_start = pos;
+        while pos < bytes.len() && bytes[pos].is_ascii_alphabetic() {
+            pos += 1;
+        }
+        let unit = match &s[unit_start..pos] {
+            "ns" => 1,
+            "us" => 1_000,
+            "ms" => 1_000_000,
+            "s" => 1_000_000_000,
+            "m" => 60_000_000_000,
+            "h" => 3_600_000_000_000,
+            "d" => 86_400_000_000_000,
+            "w" => 604_800_000_000_000,
+            "y" => 31_536_000_000_000_000,
+            _ => return Err(syntax_error(format!("invalid duration '{}'", s))),
+        };
+        total = num
+            .checked_mul(unit)
+            .and_then(|n| total.checked_add(n))
+            .ok_or_else(|| syntax_error(format!("duration '{}' is too large", s)))?;
+    }
+    Ok(total)
+}
+
+/// Parse a timestamp parameter of the Loki HTTP API into nanoseconds.
+///
+/// As in Loki, an integer of at most 10 digits is in seconds and a longer one is in
+/// nanoseconds, a float number is in seconds, anything else must be RFC3339.
+pub fn parse_timestamp(s: &str) -> QueryResult<i64> {
+    let invalid = || QueryError::InvalidParam {
+        reason: format!("cannot parse \"{}\" to a valid timestamp", s),
+    };
+    if s.contains('.') {
+        if let Ok(seconds) = s.parse::<f64>() {
+            let nanos = (seconds.fract() * 1e9).round() as i64;
+            return Ok(seconds.trunc() as i64 * 1_000_000_000 + nanos);
+        }
+    }
+    if let Ok(n) = s.parse::<i64>() {
+        if s.trim_start_matches('-').len() <= 10 {
+            return n.checked_mul(1_000_000_000).ok_or_else(invalid);
+        }
+        return Ok(n);
+    }
+    chrono::DateTime::parse_from_rfc3339(s)
+        .ok()
+        .and_then(|t| t.timestamp_nanos_opt())
+        .ok_or_else(invalid)
+}
+
+/// Parse the step parameter of the Loki HTTP API,
+/// either a duration such as `15s` or a float number of seconds, into nanoseconds.
+pub fn parse_step(s: &str) -> QueryResult<i64> {
+    let step = match s.parse::<f64>() {
+        Ok(seconds) => (seconds * 1e9).round() as i64,
+        Err(_) => parse_duration(s).map_err(|_| QueryError::InvalidParam {
+            reason: format!("cannot parse \"{}\" to a valid duration", s),
+        })?,
+    };
+    if step <= 0 {
+        return Err(QueryError::InvalidParam {
+            reason: "zero or negative query resolution step widths are not accepted".to_string(),
+        });
+    }
+    Ok(step)
+}
+
+fn syntax_error(reason: String) -> QueryError {
+    QueryError::InvalidLogQL { reason }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    String(String),
+    Duration(i64),
+    LeftParen,
+    RightParen,
+    LeftBrace,
+    RightBrace,
+    LeftBracket,
+    RightBracket,
+    Comma,
+    Assign,
+    Neq,
+    RegexMatch,
+    RegexNotMatch,
+    PipeExact,
+    PipeMatch,
+    Pipe,
+    Eof,
+}
+
+struct Lexer<'a> {
+    input: &'a str,
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(input: &'a str) -> Self {
+        Self {
+            input,
+            chars: input.chars().collect(),
+            pos: 0,
+        }
+    }
+
+    fn tokenize(mut self) -> QueryResult<Vec<Token>> {
+        let mut tokens = vec![];
+        loop {
+            let token = self.next_token()?;
+            let eof = token == Token::Eof;
+            tokens.push(token);
+            if eof {
+                return Ok(tokens);
+            }
+        }
+    }
+
+    fn peek_char(&self, n: usize) -> Option<char> {
+        self.chars.get(self.pos + n).copied()
+    }
+
+    fn next_token(&mut self) -> QueryResult<Token> {
+        while self.peek_char(0).is_some_and(|c| c.is_whitespace()) {
+            self.pos += 1;
+        }
+
+        let c = match self.peek_char(0) {
+            Some(c) => c,
+            None => return Ok(Token::Eof),
+        };
+        let next = self.peek_char(1);
+
+        let (token, len) = match (c, next) {
+            ('(', _) => (Token::LeftParen, 1),
+            (')', _) => (Token::RightParen, 1),
+            ('{', _) => (Token::LeftBrace, 1),
+            ('}', _) => (Token::RightBrace, 1),
+            ('[', _) => (Token::LeftBracket, 1),
+            (']', _) => (Token::RightBracket, 1),
+            (',', _) => (Token::Comma, 1),
+            ('=', Some('~')) => (Token::RegexMatch, 2),
+            ('=', _) => (Token::Assign, 1),
+            ('!', Some('=')) => (Token::Neq, 2),
+            ('!', Some('~')) => (Token::RegexNotMatch, 2),
+            ('|', Some('=')) => (Token::PipeExact, 2),
+            ('|', Some('~')) => (Token::PipeMatch, 2),
+            ('|', _) => (Token::Pipe, 1),
+            ('"', _) | ('`', _) => return self.lex_string(c),
+            (c, _) if c.is_ascii_digit() => return self.lex_duration(),
+            (c, _) if c.is_ascii_alphabetic() || c == '_' => return Ok(self.lex_identifier()),
+            (c, _) => {
+                return Err(syntax_error(format!(
+                    "unexpected character '{}' at position {}",
+                    c, self.pos
+                )))
+            }
+        };
+        self.pos += len;
+        Ok(token)
+    }
+
+    fn lex_identifier(&mut self) -> Token {
+        let start = self.pos;
+        while self
+            .peek_char(0)
+            .is_some_and(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            self.pos += 1;
+        }
+        Token::Ident(self.chars[start..self.pos].iter().collect())
+    }
+
+    fn lex_duration(&mut self) -> QueryResult<Token> {
+        let start = self.pos;
+        while self.peek_char(0).is_some_and(|c| c.is_ascii_alphanumeric()) {
+            self.pos += 1;
+        }
+        let text: String = self.chars[start..self.pos].iter().collect();
+        parse_duration(&text).map(Token::Duration)
+    }
+
+    fn lex_string(&mut self, quote: char) -> QueryResult<Token> {
+        let start = self.pos;
+        self.pos += 1;
+        let mut value = String::new();
+        loop {
+            let c = self.peek_char(0).ok_or_else(|| {
+                syntax_error(format!(
+                    "unterminated string starting at position {} of '{}'",
+                    start, self.input
+                ))
+            })?;
+            self.pos += 1;
+            if c == quote {
+                return Ok(Token::String(value));
+            }
+            if c == '\\' && quote != '`' {
+                let escaped = self
+                    .peek_char(0)
+                    .ok_or_else(|| syntax_error("unterminated escape sequence".to_string()))?;
+                self.pos += 1;
+                match escaped {
+                    'n' => value.push('\n'),
+                    't' => value.push('\t'),
+                    'r' => value.push('\r'),
+                    '\\' => value.push('\\'),
+                    '"' => value.push('"'),
+                    // Keep unknown escapes as is, regular expressions rely on them, e.g. "\\d+"
+                    other => {
+                        value.push('\\');
+                        value.push(other);
+                    }
+                }
+            } else {
+                value.push(c);
+            }
+        }
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos]
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.tokens[self.pos].clone();
+        if token != Token::Eof {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn expect(&mut self, expected: Token) -> QueryResult<()> {
+        let token = self.next();
+        if token == expected {
+            Ok(())
+        } else {
+            Err(syntax_error(format!(
+                "expected {:?}, found {:?}",
+                expected, token
+            )))
+        }
+    }
+
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Token::Ident(id) if id == keyword)
+    }
+
+    fn parse_expr(&mut self) -> QueryResult<Expr> {
+        match self.next() {
+            Token::LeftBrace => Ok(Expr::Log(self.parse_log_selector()?)),
+            Token::Ident(id) if id == "sum" => {
+                let leading = self.parse_grouping()?;
+                self.expect(Token::LeftParen)?;
+                let mut expr = match self.next() {
+                    Token::Ident(id) => self.parse_range_aggregation(&id)?,
+                    t => {
+                        return Err(syntax_error(format!(
+                            "expected range aggregation, found {:?}",
+                            t
+                        )))
+                    }
+                };
+                self.expect(Token::RightParen)?;
+                let trailing = self.parse_grouping()?;
+                expr.sum = match (leading, trailing) {
+                    (Some(_), Some(_)) => {
+                        return Err(syntax_error(
+                            "aggregation must only contain one grouping clause".to_string(),
+                        ))
+                    }
+                    (Some(g), None) | (None, Some(g)) => Some(g),
+                    (None, None) => Some(Grouping::By(vec![])),
+                };
+                Ok(Expr::Metric(expr))
+            }
+            Token::Ident(id) => Ok(Expr::Metric(self.parse_range_aggregation(&id)?)),
+            t => Err(syntax_error(format!("unexpected {:?}", t))),
+        }
+    }
+
+    /// Parse `count_over_time(<log selector> [<range>])`, the function name has been consumed.
+    fn parse_range_aggregation(&mut self, name: &str) -> QueryResult<MetricExpr> {
+        let op = match RangeOp::from_name(name) {
+            Some(op) => op,
+            None => {
+                return Err(QueryError::NotImplemented {
+                    err: format!("LogQL function {}", name),
+                })
+            }
+        };
+        self.expect(Token::LeftParen)?;
+        self.expect(Token::LeftBrace)?;
+        let selector = self.parse_log_selector()?;
+        self.expect(Token::LeftBracket)?;
+        let range = match self.next() {
+            Token::Duration(d) if d > 0 => d,
+            t => return Err(syntax_error(format!("expected duration, found {:?}", t))),
+        };
+        self.expect(Token::RightBracket)?;
+        self.expect(Token::RightParen)?;
+        Ok(MetricExpr {
+            op,
+            selector,
+            range,
+            sum: None,
+        })
+    }
+
+    /// Parse `name op "value", ...} |= "line" ...`, the left brace has been consumed.
+    fn parse_log_selector(&mut self) -> QueryResult<LogSelector> {
+        let matchers = self.parse_matchers()?;
+        if matchers.iter().all(matches_empty) {
+            return Err(syntax_error(
+                "queries require at least one regexp or equality matcher that does not have an empty-compatible value"
+                    .to_string(),
+            ));
+        }
+
+        let mut line_filters = vec![];
+        loop {
+            let op = match self.peek() {
+                Token::PipeExact => LineFilterOp::Contains,
+                Token::Neq => LineFilterOp::NotContains,
+                Token::PipeMatch => LineFilterOp::Match,
+                Token::RegexNotMatch => LineFilterOp::NotMatch,
+                Token::Pipe => {
+                    return Err(QueryError::NotImplemented {
+                        err: "LogQL parser and formatter expressions".to_string(),
+                    })
+                }
+                _ => {
+                    return Ok(LogSelector {
+                        matchers,
+                        line_filters,
+                    })
+                }
+            };
+            self.next();
+            let value = match self.next() {
+                Token::String(s) => s,
+                t => return Err(syntax_error(format!("expected string, found {:?}", t))),
+            };
+            if matches!(op, LineFilterOp::Match | LineFilterOp::NotMatch) {
+                check_regex(&value)?;
+            }
+            line_filters.push(LineFilter { op, value });
+        }
+    }
+
+    fn parse_matchers(&mut self) -> QueryResult<Vec<Matcher>> {
+        let mut matchers = vec![];
+        loop {
+            match self.next() {
+                Token::RightBrace => return Ok(matchers),
+                Token::Ident(name) => {
+                    let op = match self.next() {
+                        Token::Assign => MatchType::Eq,
+                        Token::Neq => MatchType::Neq,
+                        Token::RegexMatch => MatchType::Re,
+                        Token::RegexNotMatch => MatchType::Nre,
+                        t => {
+                            return Err(syntax_error(format!(
+                                "expected label matching operator, found {:?}",
+                                t
+                            )))
+                        }
+                    };
+                    let value = match self.next() {
+                        Token::String(s) => s,
+                        t => return Err(syntax_error(format!("expected string, found {:?}", t))),
+                    };
+                    if matches!(op, MatchType::Re | MatchType::Nre) {
+                        check_regex(&value)?;
+                    }
+                    matchers.push(Matcher { name, op, value });
+                    match self.next() {
+                        Token::Comma => continue,
+                        Token::RightBrace => return Ok(matchers),
+                        t => {
+                            return Err(syntax_error(format!(
+                                "expected ',' or '}}' in label matchers, found {:?}",
+                                t
+                            )))
+                        }
+                    }
+                }
+                t => return Err(syntax_error(format!("expected label name, found {:?}", t))),
+            }
+        }
+    }
+
+    fn parse_label_list(&mut self) -> QueryResult<Vec<String>> {
+        self.expect(Token::LeftParen)?;
+        let mut labels = vec![];
+        loop {
+            match self.next() {
+                Token::RightParen => return Ok(labels),
+                Token::Ident(label) => {
+                    labels.push(label);
+                    match self.next() {
+                        Token::Comma => continue,
+                        Token::RightParen => return Ok(labels),
+                        t => {
+                            return Err(syntax_error(format!(
+                                "expected ',' or ')' in label list, found {:?}",
+                                t
+                            )))
+                        }
+                    }
+                }
+                t => return Err(syntax_error(format!("expected label name, found {:?}", t))),
+            }
+        }
+    }
+
+    fn parse_grouping(&mut self) -> QueryResult<Option<Grouping>> {
+        if self.peek_keyword("by") {
+            self.next();
+            return Ok(Some(Grouping::By(self.parse_label_list()?)));
+        }
+        if self.peek_keyword("without") {
+            self.next();
+            return Ok(Some(Grouping::Without(self.parse_label_list()?)));
+        }
+        Ok(None)
+    }
+}
+
+/// Whether the matcher matches the empty value of an absent label.
+///
+/// The regular expression of the matcher must have been checked by the parser.
+pub fn matches_empty(m: &Matcher) -> bool {
+    let re = || Regex::new(&anchored(&m.value)).is_ok_and(|re| re.is_match(""));
+    match m.op {
+        MatchType::Eq => m.value.is_empty(),
+        MatchType::Neq => !m.value.is_empty(),
+        MatchType::Re => re(),
+        MatchType::Nre => !re(),
+    }
+}
+
+/// Label matchers are fully anchored as in Prometheus.
+pub fn anchored(re: &str) -> String {
+    format!("^(?:{})$", re)
+}
+
+fn check_regex(value: &str) -> QueryResult<()> {
+    Regex::new(value)
+        .map(|_| ())
+        .map_err(|e| syntax_error(format!("invalid regular expression '{}': {}", value, e)))
+}
+
+#[cfg(test)]
+mod test {
+    use protos::prompb::prometheus::label_matcher::Type as MatchType;
+
+    use super::{parse, parse_duration, parse_step, parse_timestamp};
+    use crate::loki::logql::ast::{
+        Expr, Grouping, LineFilter, LineFilterOp, LogSelector, Matcher, MetricExpr, RangeOp,
+    };
+
+    fn matcher(name: &str, op: MatchType, value: &str) -> Matcher {
+        Matcher {
+            name: name.to_string(),
+            op,
+            value: value.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("5m").unwrap(), 300_000_000_000);
+        assert_eq!(parse_duration("1h30m").unwrap(), 5_400_000_000_000);
+        assert_eq!(parse_duration("500ms").unwrap(), 500_000_000);
+        assert!(parse_duration("5x").is_err());
+        assert!(parse_duration("").is_err());
+    }
+
+    #[test]
+    fn test_parse_api_params() {
+        assert_eq!(
+            parse_timestamp("1700000000").unwrap(),
+            1_700_000_000_000_000_000
+        );
+        assert_eq!(
+            parse_timestamp("1700000000000000001").unwrap(),
+            1_700_000_000_000_000_001
+        );
+        assert_eq!(
+            parse_timestamp("1700000000.5").unwrap(),
+            1_700_000_000_500_000_000
+        );
+        assert_eq!(
+            parse_timestamp("2023-11-14T22:13:20Z").unwrap(),
+            1_700_000_000_000_000_000
+        );
+        assert!(parse_timestamp("yesterday").is_err());
+        assert_eq!(parse_step("15").unwrap(), 15_000_000_000);
+        assert_eq!(parse_step("1m").unwrap(), 60_000_000_000);
+        assert!(parse_step("0").is_err());
+    }
+
+    #[test]
+    fn test_parse_log_query() {
+        let expr =
+            parse(r#"{job="api", level=~"warn|error"} |= "timeout" != `retry` |~ "code=5\\d\\d""#)
+                .unwrap();
+        assert_eq!(
+            expr,
+            Expr::Log(LogSelector {
+                matchers: vec![
+                    matcher("job", MatchType::Eq, "api"),
+                    matcher("level", MatchType::Re, "warn|error"),
+                ],
+                line_filters: vec![
+                    LineFilter {
+                        op: LineFilterOp::Contains,
+                        value: "timeout".to_string(),
+                    },
+                    LineFilter {
+                        op: LineFilterOp::NotContains,
+                        value: "retry".to_string(),
+                    },
+                    LineFilter {
+                        op: LineFilterOp::Match,
+                        value: "code=5\\d\\d".to_string(),
+                    },
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_metric_query() {
+        let expr = parse(r#"count_over_time({job="api"} !~ "debug" [5m])"#).unwrap();
+        assert_eq!(
+            expr,
+            Expr::Metric(MetricExpr {
+                op: RangeOp::CountOverTime,
+                selector: LogSelector {
+                    matchers: vec![matcher("job", MatchType::Eq, "api")],
+                    line_filters: vec![LineFilter {
+                        op: LineFilterOp::NotMatch,
+                        value: "debug".to_string(),
+                    }],
+                },
+                range: 300_000_000_000,
+                sum: None,
+            })
+        );
+
+        let Expr::Metric(expr) = parse(r#"sum by (level) (rate({job="api"}[1m]))"#).unwrap() else {
+            panic!("expect metric query")
+        };
+        assert_eq!(expr.op, RangeOp::Rate);
+        assert_eq!(expr.sum, Some(Grouping::By(vec!["level".to_string()])));
+
+        let Expr::Metric(expr) = parse(r#"sum(rate({job="api"}[1m])) without (host)"#).unwrap()
+        else {
+            panic!("expect metric query")
+        };
+        assert_eq!(expr.sum, Some(Grouping::Without(vec!["host".to_string()])));
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        // At least one matcher must not match the empty string
+        assert!(parse(r#"{job=~".*"}"#).is_err());
+        assert!(parse(r#"{job="api"} |= error"#).is_err());
+        assert!(parse(r#"{job="api"} |~ "(""#).is_err());
+        assert!(parse(r#"count_over_time({job="api"})"#).is_err());
+        assert!(parse(r#"{job="api"} | json"#).is_err());
+        assert!(parse(r#"bytes_over_time({job="api"}[5m])"#).is_err());
+    }
+}
@@ -0,0 +1,382 @@
+use std::collections::BTreeMap;
+
+use models::schema::tskv_table_schema::TskvTableSchema;
+use models::schema::TIME_FIELD_NAME;
+use protos::prompb::prometheus::label_matcher::Type as MatchType;
+use spi::server::loki::LokiDirection;
+use spi::{QueryError, QueryResult};
+
+use super::ast::{Grouping, LineFilterOp, LogSelector, MetricExpr, RangeOp};
+use super::parser::{anchored, matches_empty};
+
+/// Same limit on the number of steps as Loki and Prometheus.
+const MAX_POINTS_PER_SERIES: i64 = 11_000;
+
+/// Timestamp of the rows in nanoseconds, whatever the precision of the database is.
+fn time_ns_expr() -> String {
+    format!("CAST(CAST({} AS TIMESTAMP) AS BIGINT)", TIME_FIELD_NAME)
+}
+
+/// A log query translated into SQL.
+///
+/// Each row is the timestamp in nanoseconds, the values of `labels` and the log line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogSql {
+    pub sql: String,
+    pub labels: Vec<String>,
+}
+
+/// A metric query translated into SQL.
+///
+/// The log lines are counted in buckets of `gcd(range, step)`, so that the lines in the
+/// range of every step are the lines of a run of whole buckets. Each row is the values
+/// of `labels`, the bucket number and the number of lines in the bucket.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricSql {
+    pub sql: String,
+    pub labels: Vec<String>,
+    op: RangeOp,
+    range: i64,
+    start: i64,
+    end: i64,
+    step: i64,
+    bucket: i64,
+}
+
+impl MetricSql {
+    /// Compute the samples at every step from the line counts of the buckets of a series.
+    ///
+    /// The range of a step at `t` is `(t - range, t]` as in Loki, steps without any
+    /// line are omitted. Timestamps of the samples are in milliseconds.
+    pub fn points(&self, buckets: &BTreeMap<i64, i64>) -> Vec<(i64, f64)> {
+        let mut points = vec![];
+        let mut offset = 0;
+        while self.start + offset <= self.end {
+            let first = offset / self.bucket;
+            let last = (offset + self.range) / self.bucket;
+            let count = buckets.range(first..last).map(|(_, c)| *c).sum::<i64>();
+            if count > 0 {
+                let value = match self.op {
+                    RangeOp::CountOverTime => count as f64,
+                    RangeOp::Rate => count as f64 * 1e9 / self.range as f64,
+                };
+                points.push(((self.start + offset) / 1_000_000, value));
+            }
+            offset += self.step;
+        }
+        points
+    }
+}
+
+/// Names of the labels of the streams in the table, which are the tag columns, sorted.
+pub fn label_names(table: &TskvTableSchema) -> Vec<String> {
+    let mut labels = table
+        .tag_indices()
+        .into_iter()
+        .flat_map(|idx| table.column_by_index(idx))
+        .map(|column| column.name.clone())
+        .collect::<Vec<_>>();
+    labels.sort();
+    labels
+}
+
+/// Translates LogQL into SQL over the table holding the logs.
+///
+/// The labels of a stream are the tag columns of the table, the log line is `line_column`.
+pub struct SqlBuilder<'a> {
+    table: &'a TskvTableSchema,
+    line_column: &'a str,
+}
+
+impl<'a> SqlBuilder<'a> {
+    pub fn new(table: &'a TskvTableSchema, line_column: &'a str) -> Self {
+        Self { table, line_column }
+    }
+
+    /// Select at most `limit` lines in `[start, end]` (ns), returns None if the selector never matches.
+    pub fn log_query(
+        &self,
+        selector: &LogSelector,
+        start: i64,
+        end: i64,
+        limit: usize,
+        direction: LokiDirection,
+    ) -> QueryResult<Option<LogSql>> {
+        let mut filters = match self.selector_filters(selector)? {
+            Some(filters) => filters,
+            None => return Ok(None),
+        };
+        filters.push(format!("{} >= {}", TIME_FIELD_NAME, start));
+        filters.push(format!("{} <= {}", TIME_FIELD_NAME, end));
+        self.check_line_column()?;
+
+        let labels = label_names(self.table);
+        let columns = std::iter::once(time_ns_expr())
+            .chain(labels.iter().map(|l| quote_ident(l)))
+            .chain(std::iter::once(quote_ident(self.line_column)))
+            .collect::<Vec<_>>();
+        let order = match direction {
+            LokiDirection::Forward => "ASC",
+            LokiDirection::Backward => "DESC",
+        };
+
+        Ok(Some(LogSql {
+            sql: format!(
+                "SELECT {} FROM {} WHERE {} ORDER BY {} {} LIMIT {}",
+                columns.join(", "),
+                quote_ident(&self.table.name),
+                filters.join(" AND "),
+                TIME_FIELD_NAME,
+                order,
+                limit
+            ),
+            labels,
+        }))
+    }
+
+    /// Count the lines for the steps in `[start, end]` (ns), returns None if the selector never matches.
+    pub fn metric_query(
+        &self,
+        expr: &MetricExpr,
+        start: i64,
+        end: i64,
+        step: i64,
+    ) -> QueryResult<Option<MetricSql>> {
+        if (end - start) / step >= MAX_POINTS_PER_SERIES {
+            return Err(QueryError::InvalidParam {
+                reason: format!(
+                    "exceeded maximum resolution of {} points per timeseries. Try increasing the value of the step parameter",
+                    MAX_POINTS_PER_SERIES
+                ),
+            });
+        }
+        let mut filters = match self.selector_filters(&expr.selector)? {
+            Some(filters) => filters,
+            None => return Ok(None),
+        };
+        let origin = start - expr.range + 1;
+        filters.push(format!("{} >= {}", TIME_FIELD_NAME, origin));
+        filters.push(format!("{} <= {}", TIME_FIELD_NAME, end));
+
+        // `sum` is pushed down, only the lines of the remaining labels are counted separately
+        let labels = label_names(self.table)
+            .into_iter()
+            .filter(|label| match &expr.sum {
+                None => true,
+                Some(Grouping::By(by)) => by.contains(label),
+                Some(Grouping::Without(without)) => !without.contains(label),
+            })
+            .collect::<Vec<_>>();
+        let bucket = gcd(expr.range, step);
+        let bucket_expr = format!("({} - {}) / {}", time_ns_expr(), origin, bucket);
+        let group_by = labels
+            .iter()
+            .map(|l| quote_ident(l))
+            .chain(std::iter::once(bucket_expr.clone()))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let columns = labels
+            .iter()
+            .map(|l| quote_ident(l))
+            .chain([bucket_expr, "COUNT(*)".to_string()])
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        Ok(Some(MetricSql {
+            sql: format!(
+                "SELECT {} FROM {} WHERE {} GROUP BY {}",
+                columns,
+                quote_ident(&self.table.name),
+                filters.join(" AND "),
+                group_by
+            ),
+            labels,
+            op: expr.op,
+            range: expr.range,
+            start,
+            end,
+            step,
+            bucket,
+        }))
+    }
+
+    fn check_line_column(&self) -> QueryResult<()> {
+        if self.table.column(self.line_column).is_none() {
+            return Err(QueryError::InvalidParam {
+                reason: format!(
+                    "table {} has no log line column {}",
+                    self.table.name, self.line_column
+                ),
+            });
+        }
+        Ok(())
+    }
+
+    fn selector_filters(&self, selector: &LogSelector) -> QueryResult<Option<Vec<String>>> {
+        let mut filters = vec![];
+        for m in selector.matchers.iter() {
+            if !self
+                .table
+                .column(&m.name)
+                .is_some_and(|c| c.column_type.is_tag())
+            {
+                // The label of a column that does not exist is empty, the matcher is a constant
+                if !matches_empty(m) {
+                    return Ok(None);
+                }
+                continue;
+            }
+            let name = quote_ident(&m.name);
+            let filter = match (m.op, m.value.is_empty()) {
+                (MatchType::Eq, true) => format!("{} IS NULL", name),
+                (MatchType::Neq, true) => format!("{} IS NOT NULL", name),
+                (MatchType::Eq, false) => format!("{} = {}", name, quote_literal(&m.value)),
+                (MatchType::Neq, false) => format!("{} != {}", name, quote_literal(&m.value)),
+                (MatchType::Re, _) => {
+                    format!("{} ~ {}", name, quote_literal(&anchored(&m.value)))
+                }
+                (MatchType::Nre, _) => {
+                    format!("{} !~ {}", name, quote_literal(&anchored(&m.value)))
+                }
+            };
+            filters.push(filter);
+        }
+
+        if !selector.line_filters.is_empty() {
+            self.check_line_column()?;
+        }
+        let line = quote_ident(self.line_column);
+        for f in selector.line_filters.iter() {
+            let value = quote_literal(&f.value);
+            let filter = match f.op {
+                LineFilterOp::Contains => format!("strpos({}, {}) > 0", line, value),
+                LineFilterOp::NotContains => format!("strpos({}, {}) = 0", line, value),
+                LineFilterOp::Match => format!("{} ~ {}", line, value),
+                LineFilterOp::NotMatch => format!("{} !~ {}", line, value),
+            };
+            filters.push(filter);
+        }
+
+        Ok(Some(filters))
+    }
+}
+
+fn quote_ident(ident: &str) -> String {
+    format!("\"{}\"", ident.replace('"', "\"\""))
+}
+
+fn quote_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+fn gcd(a: i64, b: i64) -> i64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use datafusion::arrow::datatypes::TimeUnit;
+    use models::schema::tskv_table_schema::{ColumnType, TableColumn, TskvTableSchema};
+    use models::ValueType;
+    use spi::server::loki::LokiDirection;
+
+    use super::SqlBuilder;
+    use crate::loki::logql::ast::Expr;
+    use crate::loki::logql::parser::parse;
+
+    const MINUTE: i64 = 60_000_000_000;
+
+    fn logs_table() -> TskvTableSchema {
+        TskvTableSchema::new(
+            "cnosdb".to_string(),
+            "public".to_string(),
+            "logs".to_string(),
+            vec![
+                TableColumn::new_time_column(0, TimeUnit::Nanosecond),
+                TableColumn::new_tag_column(1, "job".to_string()),
+                TableColumn::new_tag_column(2, "level".to_string()),
+                TableColumn::new_with_default(
+                    "msg".to_string(),
+                    ColumnType::Field(ValueType::String),
+                ),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_log_query_sql() {
+        let table = logs_table();
+        let builder = SqlBuilder::new(&table, "msg");
+        let Expr::Log(selector) =
+            parse(r#"{job="api", level!="", host=""} |= "it's" |~ "code=5\\d\\d""#).unwrap()
+        else {
+            panic!("expect log query")
+        };
+        let sql = builder
+            .log_query(&selector, 0, MINUTE, 100, LokiDirection::Backward)
+            .unwrap()
+            .unwrap();
+        assert_eq!(sql.labels, vec!["job", "level"]);
+        assert_eq!(
+            sql.sql,
+            "SELECT CAST(CAST(time AS TIMESTAMP) AS BIGINT), \"job\", \"level\", \"msg\" FROM \"logs\" \
+            WHERE \"job\" = 'api' AND \"level\" IS NOT NULL AND strpos(\"msg\", 'it''s') > 0 \
+            AND \"msg\" ~ 'code=5\\d\\d' AND time >= 0 AND time <= 60000000000 \
+            ORDER BY time DESC LIMIT 100"
+        );
+
+        // `host` is not a label of the table
+        let Expr::Log(selector) = parse(r#"{job="api", host="h1"}"#).unwrap() else {
+            panic!("expect log query")
+        };
+        assert!(builder
+            .log_query(&selector, 0, MINUTE, 100, LokiDirection::Forward)
+            .unwrap()
+            .is_none());
+
+        let builder = SqlBuilder::new(&table, "line");
+        let Expr::Log(selector) = parse(r#"{job="api"}"#).unwrap() else {
+            panic!("expect log query")
+        };
+        assert!(builder
+            .log_query(&selector, 0, MINUTE, 100, LokiDirection::Forward)
+            .is_err());
+    }
+
+    #[test]
+    fn test_metric_query_sql() {
+        let table = logs_table();
+        let builder = SqlBuilder::new(&table, "msg");
+        let Expr::Metric(expr) = parse(r#"sum by (level) (rate({job="api"}[2m]))"#).unwrap() else {
+            panic!("expect metric query")
+        };
+        let sql = builder
+            .metric_query(&expr, 10 * MINUTE, 20 * MINUTE, 3 * MINUTE)
+            .unwrap()
+            .unwrap();
+        assert_eq!(sql.labels, vec!["level"]);
+        assert_eq!(
+            sql.sql,
+            "SELECT \"level\", (CAST(CAST(time AS TIMESTAMP) AS BIGINT) - 480000000001) / 60000000000, COUNT(*) \
+            FROM \"logs\" WHERE \"job\" = 'api' AND time >= 480000000001 AND time <= 1200000000000 \
+            GROUP BY \"level\", (CAST(CAST(time AS TIMESTAMP) AS BIGINT) - 480000000001) / 60000000000"
+        );
+
+        // Minutes after the origin, the 6th minute is out of the range of every step
+        let buckets = BTreeMap::from([(0, 6), (1, 6), (4, 3), (5, 100), (6, 3)]);
+        assert_eq!(
+            sql.points(&buckets),
+            vec![(600_000, 0.1), (780_000, 0.025), (960_000, 0.025)]
+        );
+
+        assert!(builder
+            .metric_query(&expr, 0, 20 * MINUTE, 1_000_000)
+            .is_err());
+    }
+}
@@ -0,0 +1,8 @@
+//! Query the logs written through the Loki push API with LogQL,
+//! for the Loki HTTP API (`/loki/api/v1/query_range` and `/loki/api/v1/labels`).
+
+pub mod logql;
+pub mod server;
+
+/// Column of the log line written by the Loki push API.
+pub const DEFAULT_LOKI_LINE_COLUMN: &str = "msg";
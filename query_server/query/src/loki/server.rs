@@ -0,0 +1,196 @@
+use std::collections::BTreeMap;
+
+use async_trait::async_trait;
+use coordinator::service::CoordinatorRef;
+use datafusion::arrow::array::{Array, Int64Array, StringArray};
+use datafusion::arrow::record_batch::RecordBatch;
+use meta::error::MetaError;
+use models::schema::tskv_table_schema::TskvTableSchemaRef;
+use snafu::ResultExt;
+use spi::server::dbms::DBMSRef;
+use spi::server::loki::{LokiRangeQuery, LokiServer, LokiStream, LokiValue};
+use spi::server::prom::{PromLabels, PromRangeSeries};
+use spi::service::protocol::{Context, Query};
+use spi::{CommonSnafu, MetaSnafu, QueryResult};
+use trace::{debug, Span, SpanContext};
+
+use super::logql;
+use super::logql::ast::Expr;
+use super::logql::sql::{label_names, LogSql, MetricSql, SqlBuilder};
+
+/// Evaluate LogQL through SQL over the table holding the logs.
+pub struct LokiSqlServer {
+    db: DBMSRef,
+    coord: CoordinatorRef,
+}
+
+#[async_trait]
+impl LokiServer for LokiSqlServer {
+    async fn query_range(
+        &self,
+        ctx: &Context,
+        query: &LokiRangeQuery,
+        span_ctx: Option<&SpanContext>,
+    ) -> QueryResult<LokiValue> {
+        let expr = logql::parser::parse(&query.query)?;
+        let table = self.table_schema(ctx, &query.table).await?;
+        let builder = SqlBuilder::new(&table, &query.line_column);
+        let span = Span::from_context("logql range query", span_ctx);
+
+        match expr {
+            Expr::Log(selector) => {
+                let sql = builder.log_query(
+                    &selector,
+                    query.start,
+                    query.end,
+                    query.limit,
+                    query.direction,
+                )?;
+                let streams = match sql {
+                    Some(sql) => {
+                        let batches = self.execute(ctx, &sql.sql, &span).await?;
+                        read_streams(&sql, &batches)?
+                    }
+                    None => vec![],
+                };
+                Ok(LokiValue::Streams(streams))
+            }
+            Expr::Metric(expr) => {
+                let sql = builder.metric_query(&expr, query.start, query.end, query.step)?;
+                let series = match sql {
+                    Some(sql) => {
+                        let batches = self.execute(ctx, &sql.sql, &span).await?;
+                        read_matrix(&sql, &batches)?
+                    }
+                    None => vec![],
+                };
+                Ok(LokiValue::Matrix(series))
+            }
+        }
+    }
+
+    async fn labels(&self, ctx: &Context, table: &str) -> QueryResult<Vec<String>> {
+        let table = self.table_schema(ctx, table).await?;
+        Ok(label_names(&table))
+    }
+}
+
+impl LokiSqlServer {
+    pub fn new(db: DBMSRef, coord: CoordinatorRef) -> Self {
+        Self { db, coord }
+    }
+
+    async fn table_schema(&self, ctx: &Context, table: &str) -> QueryResult<TskvTableSchemaRef> {
+        let meta = self
+            .coord
+            .meta_manager()
+            .tenant_meta(ctx.tenant())
+            .await
+            .ok_or_else(|| MetaError::TenantNotFound {
+                tenant: ctx.tenant().to_string(),
+            })
+            .context(MetaSnafu)?;
+
+        meta.get_tskv_table_schema(ctx.database(), table)
+            .context(MetaSnafu)?
+            .ok_or_else(|| MetaError::TableNotFound {
+                table: table.to_string(),
+            })
+            .context(MetaSnafu)
+    }
+
+    async fn execute(
+        &self,
+        ctx: &Context,
+        sql: &str,
+        span: &Span,
+    ) -> QueryResult<Vec<RecordBatch>> {
+        debug!("Prepare to execute: {}", sql);
+        let query = Query::new(ctx.clone(), sql.to_string());
+        let result = self.db.execute(&query, span.context().as_ref()).await?;
+        result.result().chunk_result().await
+    }
+}
+
+/// Group the lines selected by a log query into streams, keeping the order of the lines.
+fn read_streams(sql: &LogSql, batches: &[RecordBatch]) -> QueryResult<Vec<LokiStream>> {
+    let label_count = sql.labels.len();
+    let mut streams = BTreeMap::<PromLabels, Vec<(i64, String)>>::new();
+    for batch in batches {
+        let times = column_as::<Int64Array>(batch, 0)?;
+        let labels = (1..=label_count)
+            .map(|idx| column_as::<StringArray>(batch, idx))
+            .collect::<QueryResult<Vec<_>>>()?;
+        let lines = column_as::<StringArray>(batch, label_count + 1)?;
+
+        for row in 0..batch.num_rows() {
+            let line = if lines.is_null(row) {
+                String::new()
+            } else {
+                lines.value(row).to_string()
+            };
+            streams
+                .entry(row_labels(&sql.labels, &labels, row))
+                .or_default()
+                .push((times.value(row), line));
+        }
+    }
+
+    Ok(streams
+        .into_iter()
+        .map(|(labels, entries)| LokiStream { labels, entries })
+        .collect())
+}
+
+/// Fold the line counts of the buckets of every series into the samples at every step.
+fn read_matrix(sql: &MetricSql, batches: &[RecordBatch]) -> QueryResult<Vec<PromRangeSeries>> {
+    let label_count = sql.labels.len();
+    let mut series = BTreeMap::<PromLabels, BTreeMap<i64, i64>>::new();
+    for batch in batches {
+        let labels = (0..label_count)
+            .map(|idx| column_as::<StringArray>(batch, idx))
+            .collect::<QueryResult<Vec<_>>>()?;
+        let buckets = column_as::<Int64Array>(batch, label_count)?;
+        let counts = column_as::<Int64Array>(batch, label_count + 1)?;
+
+        for row in 0..batch.num_rows() {
+            *series
+                .entry(row_labels(&sql.labels, &labels, row))
+                .or_default()
+                .entry(buckets.value(row))
+                .or_default() += counts.value(row);
+        }
+    }
+
+    Ok(series
+        .into_iter()
+        .filter_map(|(labels, buckets)| {
+            let points = sql.points(&buckets);
+            (!points.is_empty()).then_some(PromRangeSeries { labels, points })
+        })
+        .collect())
+}
+
+/// Labels of a row, the labels whose value is null are absent.
+fn row_labels(names: &[String], values: &[&StringArray], row: usize) -> PromLabels {
+    names
+        .iter()
+        .zip(values)
+        .filter(|(_, array)| !array.is_null(row))
+        .map(|(name, array)| (name.clone(), array.value(row).to_string()))
+        .collect()
+}
+
+fn column_as<T: Array + 'static>(batch: &RecordBatch, idx: usize) -> QueryResult<&T> {
+    let column = batch.column(idx);
+    column.as_any().downcast_ref::<T>().ok_or_else(|| {
+        CommonSnafu {
+            msg: format!(
+                "unexpected data type {} of column {} in the result of LogQL",
+                column.data_type(),
+                batch.schema().field(idx).name()
+            ),
+        }
+        .build()
+    })
+}
@@ -0,0 +1,143 @@
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+
+use datafusion::arrow::array::{Array, StringArray, StringBuilder, UInt64Array, UInt64Builder};
+use datafusion::arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::error::DataFusionError;
+use lazy_static::lazy_static;
+
+pub const SERIES_CARDINALITY_TENANT_NAME: &str = "tenant_name";
+pub const SERIES_CARDINALITY_DATABASE_NAME: &str = "database_name";
+pub const SERIES_CARDINALITY_TABLE_NAME: &str = "table_name";
+pub const SERIES_CARDINALITY_SERIES_COUNT: &str = "series_count";
+pub const SERIES_CARDINALITY_TAG_KEY: &str = "tag_key";
+pub const SERIES_CARDINALITY_TAG_VALUE_COUNT: &str = "tag_value_count";
+
+lazy_static! {
+    pub static ref SERIES_CARDINALITY_SCHEMA: SchemaRef = Arc::new(Schema::new(vec![
+        Field::new(SERIES_CARDINALITY_TENANT_NAME, DataType::Utf8, false),
+        Field::new(SERIES_CARDINALITY_DATABASE_NAME, DataType::Utf8, false),
+        Field::new(SERIES_CARDINALITY_TABLE_NAME, DataType::Utf8, false),
+        Field::new(SERIES_CARDINALITY_SERIES_COUNT, DataType::UInt64, false),
+        Field::new(SERIES_CARDINALITY_TAG_KEY, DataType::Utf8, true),
+        Field::new(SERIES_CARDINALITY_TAG_VALUE_COUNT, DataType::UInt64, true),
+    ]));
+}
+
+/// Builds the `information_schema.SERIES_CARDINALITY` table, the series counts and the
+/// tag value counts of a table are summed over the vnodes of the database.
+#[derive(Default)]
+pub struct InformationSchemaSeriesCardinalityBuilder {
+    tables: BTreeMap<(String, String, String), TableCardinality>,
+}
+
+#[derive(Default)]
+struct TableCardinality {
+    series_count: u64,
+    tag_value_counts: BTreeMap<String, u64>,
+}
+
+impl InformationSchemaSeriesCardinalityBuilder {
+    /// Add the series cardinality of a vnode,
+    /// the `record_batch` is in `tskv::series_cardinality_schema`.
+    pub fn append_vnode(
+        &mut self,
+        tenant_name: impl AsRef<str>,
+        database_name: impl AsRef<str>,
+        vnode_id: u32,
+        record_batch: &RecordBatch,
+    ) -> Result<(), DataFusionError> {
+        let column = |idx: usize| {
+            record_batch.columns().get(idx).cloned().ok_or_else(|| {
+                DataFusionError::Internal(format!(
+                    "Series cardinality of vnode {vnode_id} missing column {idx}"
+                ))
+            })
+        };
+        let table_names = column(0)?;
+        let series_counts = column(1)?;
+        let tag_keys = column(2)?;
+        let tag_value_counts = column(3)?;
+        let (Some(table_names), Some(series_counts), Some(tag_keys), Some(tag_value_counts)) = (
+            table_names.as_any().downcast_ref::<StringArray>(),
+            series_counts.as_any().downcast_ref::<UInt64Array>(),
+            tag_keys.as_any().downcast_ref::<StringArray>(),
+            tag_value_counts.as_any().downcast_ref::<UInt64Array>(),
+        ) else {
+            return Err(DataFusionError::Internal(format!(
+                "Invalid series cardinality schema of vnode {vnode_id}: {}",
+                record_batch.schema()
+            )));
+        };
+
+        // The series count of a table is repeated in the rows of its tag keys.
+        let mut counted_tables = HashSet::new();
+        for i in 0..record_batch.num_rows() {
+            let table_name = table_names.value(i);
+            let table = self
+                .tables
+                .entry((
+                    tenant_name.as_ref().to_string(),
+                    database_name.as_ref().to_string(),
+                    table_name.to_string(),
+                ))
+                .or_default();
+            if counted_tables.insert(table_name) {
+                table.series_count += series_counts.value(i);
+            }
+            if tag_keys.is_valid(i) {
+                *table
+                    .tag_value_counts
+                    .entry(tag_keys.value(i).to_string())
+                    .or_default() += tag_value_counts.value(i);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl TryFrom<InformationSchemaSeriesCardinalityBuilder> for RecordBatch {
+    type Error = DataFusionError;
+
+    fn try_from(value: InformationSchemaSeriesCardinalityBuilder) -> Result<Self, Self::Error> {
+        let mut tenant_names = StringBuilder::new();
+        let mut database_names = StringBuilder::new();
+        let mut table_names = StringBuilder::new();
+        let mut series_counts = UInt64Builder::new();
+        let mut tag_keys = StringBuilder::new();
+        let mut tag_value_counts = UInt64Builder::new();
+
+        for ((tenant_name, database_name, table_name), table) in value.tables {
+            let mut append = |tag_key: Option<&str>, tag_value_count: Option<u64>| {
+                tenant_names.append_value(&tenant_name);
+                database_names.append_value(&database_name);
+                table_names.append_value(&table_name);
+                series_counts.append_value(table.series_count);
+                tag_keys.append_option(tag_key);
+                tag_value_counts.append_option(tag_value_count);
+            };
+            if table.tag_value_counts.is_empty() {
+                append(None, None);
+            }
+            for (tag_key, count) in table.tag_value_counts.iter() {
+                append(Some(tag_key), Some(*count));
+            }
+        }
+
+        let batch = RecordBatch::try_new(
+            SERIES_CARDINALITY_SCHEMA.clone(),
+            vec![
+                Arc::new(tenant_names.finish()),
+                Arc::new(database_names.finish()),
+                Arc::new(table_names.finish()),
+                Arc::new(series_counts.finish()),
+                Arc::new(tag_keys.finish()),
+                Arc::new(tag_value_counts.finish()),
+            ],
+        )?;
+
+        Ok(batch)
+    }
+}
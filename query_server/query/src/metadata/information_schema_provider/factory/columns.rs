@@ -149,7 +149,7 @@ fn append_tskv_table(
             "NULL",
             col.nullable(),
             col.column_type.to_sql_type_str_with_unit(),
-            Some(col.encoding.as_str()),
+            Some(col.encoding.to_string()),
         );
     }
 }
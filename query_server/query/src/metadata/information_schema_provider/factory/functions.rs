@@ -0,0 +1,104 @@
+use std::any::Any;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use datafusion::arrow::datatypes::SchemaRef;
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::common::Result as DFResult;
+use datafusion::datasource::{TableProvider, TableType};
+use datafusion::execution::context::SessionState;
+use datafusion::logical_expr::logical_plan::AggWithGrouping;
+use datafusion::logical_expr::Expr;
+use datafusion::physical_plan::memory::MemoryExec;
+use datafusion::physical_plan::ExecutionPlan;
+use meta::model::MetaClientRef;
+use models::auth::user::User;
+use models::schema::function_schema::{data_type_sql, FunctionBody};
+
+use crate::dispatcher::query_tracker::QueryTracker;
+use crate::metadata::information_schema_provider::builder::functions::{
+    InformationSchemaFunctionsBuilder, FUNCTION_SCHEMA,
+};
+use crate::metadata::information_schema_provider::InformationSchemaTableFactory;
+
+const INFORMATION_SCHEMA_FUNCTIONS: &str = "FUNCTIONS";
+
+/// This view displays the functions created by `CREATE FUNCTION` under the tenant.
+///
+/// All records for this view are visible to all members of the current tenant.
+pub struct FunctionsFactory {}
+
+impl InformationSchemaTableFactory for FunctionsFactory {
+    fn table_name(&self) -> &'static str {
+        INFORMATION_SCHEMA_FUNCTIONS
+    }
+
+    fn create(
+        &self,
+        _user: &User,
+        metadata: MetaClientRef,
+        _query_tracker: Arc<QueryTracker>,
+    ) -> Arc<dyn TableProvider> {
+        Arc::new(InformationFunctionsTable::new(metadata))
+    }
+}
+
+pub struct InformationFunctionsTable {
+    metadata: MetaClientRef,
+}
+
+impl InformationFunctionsTable {
+    pub fn new(metadata: MetaClientRef) -> Self {
+        Self { metadata }
+    }
+}
+
+#[async_trait]
+impl TableProvider for InformationFunctionsTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        FUNCTION_SCHEMA.clone()
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Base
+    }
+
+    async fn scan(
+        &self,
+        _state: &SessionState,
+        projection: Option<&Vec<usize>>,
+        _filters: &[Expr],
+        _agg_with_grouping: Option<&AggWithGrouping>,
+        _limit: Option<usize>,
+    ) -> DFResult<Arc<dyn ExecutionPlan>> {
+        let mut builder = InformationSchemaFunctionsBuilder::default();
+
+        let mut functions = self.metadata.functions();
+        functions.sort_by(|a, b| a.name.cmp(&b.name));
+        for function in functions {
+            let definition = match &function.body {
+                FunctionBody::Sql(expr) => Some(expr.as_str()),
+                FunctionBody::Wasm(_) => None,
+            };
+            builder.append_row(
+                &function.name,
+                function.kind.to_string(),
+                function.language(),
+                function.args_sql(),
+                data_type_sql(&function.return_type),
+                definition,
+            );
+        }
+        let rb: RecordBatch = builder.try_into()?;
+
+        Ok(Arc::new(MemoryExec::try_new(
+            &[vec![rb]],
+            self.schema(),
+            projection.cloned(),
+        )?))
+    }
+}
@@ -2,8 +2,10 @@ pub mod columns;
 pub mod database_privileges;
 pub mod databases;
 pub mod enabled_roles;
+pub mod functions;
 pub mod members;
 pub mod queries;
 pub mod resource_status;
 pub mod roles;
+pub mod series_cardinality;
 pub mod tables;
@@ -0,0 +1,169 @@
+use std::any::Any;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use coordinator::service::CoordinatorRef;
+use datafusion::arrow::datatypes::SchemaRef;
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::common::{DataFusionError, Result as DFResult};
+use datafusion::datasource::{TableProvider, TableType};
+use datafusion::execution::context::SessionState;
+use datafusion::logical_expr::logical_plan::AggWithGrouping;
+use datafusion::logical_expr::{BinaryExpr, Operator, TableProviderFilterPushDown};
+use datafusion::physical_plan::memory::MemoryExec;
+use datafusion::physical_plan::ExecutionPlan;
+use datafusion::prelude::Expr;
+use datafusion::scalar::ScalarValue;
+use meta::model::MetaClientRef;
+use models::auth::user::User;
+use models::oid::Identifier;
+
+use crate::dispatcher::query_tracker::QueryTracker;
+use crate::metadata::information_schema_provider::builder::series_cardinality::{
+    InformationSchemaSeriesCardinalityBuilder, SERIES_CARDINALITY_DATABASE_NAME,
+    SERIES_CARDINALITY_SCHEMA, SERIES_CARDINALITY_TABLE_NAME,
+};
+use crate::metadata::information_schema_provider::InformationSchemaTableFactory;
+
+pub const INFORMATION_SCHEMA_SERIES_CARDINALITY: &str = "SERIES_CARDINALITY";
+
+/// This view displays the series count and the number of distinct values of each tag key
+/// of the tables, summed over the vnodes of the database, only for the databases the current user has Read permission or higher.
+pub struct SeriesCardinalityFactory {
+    coord: CoordinatorRef,
+}
+
+impl SeriesCardinalityFactory {
+    pub fn new(coord: CoordinatorRef) -> Self {
+        Self { coord }
+    }
+}
+
+impl InformationSchemaTableFactory for SeriesCardinalityFactory {
+    fn table_name(&self) -> &'static str {
+        INFORMATION_SCHEMA_SERIES_CARDINALITY
+    }
+
+    fn create(
+        &self,
+        user: &User,
+        metadata: MetaClientRef,
+        _query_tracker: Arc<QueryTracker>,
+    ) -> Arc<dyn TableProvider> {
+        Arc::new(InformationSeriesCardinalityTable::new(
+            metadata,
+            self.coord.clone(),
+            user.clone(),
+        ))
+    }
+}
+
+pub struct InformationSeriesCardinalityTable {
+    user: User,
+    metadata: MetaClientRef,
+    coord: CoordinatorRef,
+}
+
+impl InformationSeriesCardinalityTable {
+    pub fn new(metadata: MetaClientRef, coord: CoordinatorRef, user: User) -> Self {
+        Self {
+            user,
+            metadata,
+            coord,
+        }
+    }
+}
+
+#[async_trait]
+impl TableProvider for InformationSeriesCardinalityTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        SERIES_CARDINALITY_SCHEMA.clone()
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Base
+    }
+
+    async fn scan(
+        &self,
+        _state: &SessionState,
+        projection: Option<&Vec<usize>>,
+        filters: &[Expr],
+        _agg_with_grouping: Option<&AggWithGrouping>,
+        _limit: Option<usize>,
+    ) -> DFResult<Arc<dyn ExecutionPlan>> {
+        let mut builder = InformationSchemaSeriesCardinalityBuilder::default();
+
+        // Only the databases and tables in the filters are counted, the filters
+        // are evaluated again after the scan.
+        let database = equal_value(filters, SERIES_CARDINALITY_DATABASE_NAME);
+        let table = equal_value(filters, SERIES_CARDINALITY_TABLE_NAME);
+
+        let dbs = self
+            .metadata
+            .list_databases()
+            .map_err(|e| DataFusionError::Internal(format!("Failed to list databases: {}", e)))?;
+        let tenant = self.metadata.tenant();
+        let tenant_id = tenant.id();
+        let tenant_name = tenant.name();
+
+        for (db, info) in dbs {
+            if database.is_some_and(|d| d != db) {
+                continue;
+            }
+
+            // Check if the current user has at least read permission on this db, skip if not
+            if !self.user.can_read_database(*tenant_id, &db) {
+                continue;
+            }
+
+            if info.is_hidden() {
+                continue;
+            }
+
+            let vnodes = self
+                .coord
+                .series_cardinality(tenant_name, &db, table)
+                .await
+                .map_err(|e| DataFusionError::External(Box::new(e)))?;
+            for (vnode_id, record_batch) in vnodes {
+                builder.append_vnode(tenant_name, &db, vnode_id, &record_batch)?;
+            }
+        }
+        let rb: RecordBatch = builder.try_into()?;
+
+        Ok(Arc::new(MemoryExec::try_new(
+            &[vec![rb]],
+            self.schema(),
+            projection.cloned(),
+        )?))
+    }
+
+    fn supports_filter_pushdown(&self, _filter: &Expr) -> DFResult<TableProviderFilterPushDown> {
+        Ok(TableProviderFilterPushDown::Inexact)
+    }
+}
+
+/// Find the value of `column = 'value'` in the filters.
+fn equal_value<'a>(filters: &'a [Expr], column: &str) -> Option<&'a str> {
+    filters.iter().find_map(|filter| match filter {
+        Expr::BinaryExpr(BinaryExpr {
+            left,
+            op: Operator::Eq,
+            right,
+        }) => match (left.as_ref(), right.as_ref()) {
+            (Expr::Column(c), Expr::Literal(ScalarValue::Utf8(Some(v))))
+            | (Expr::Literal(ScalarValue::Utf8(Some(v))), Expr::Column(c))
+                if c.name == column =>
+            {
+                Some(v.as_str())
+            }
+            _ => None,
+        },
+        _ => None,
+    })
+}
@@ -12,14 +12,20 @@ pub use builder::databases::{
     DATABASES_STRICT_WRITE, DATABASES_TENANT_NAME, DATABASES_TTL, DATABASES_VNODE_DURATION,
     DATABASES_WAL_MAX_FILE_SIZE, DATABASES_WAL_SYNC,
 };
+pub use builder::series_cardinality::{
+    SERIES_CARDINALITY_DATABASE_NAME, SERIES_CARDINALITY_SERIES_COUNT,
+    SERIES_CARDINALITY_TABLE_NAME, SERIES_CARDINALITY_TAG_KEY, SERIES_CARDINALITY_TAG_VALUE_COUNT,
+};
 pub use builder::tables::{
     TABLES_TABLE_DATABASE, TABLES_TABLE_ENGINE, TABLES_TABLE_NAME, TABLES_TABLE_OPTIONS,
     TABLES_TABLE_TENANT, TABLES_TABLE_TYPE,
 };
+use coordinator::service::CoordinatorRef;
 use datafusion::datasource::TableProvider;
 pub use factory::columns::INFORMATION_SCHEMA_COLUMNS;
 pub use factory::databases::INFORMATION_SCHEMA_DATABASES;
 pub use factory::queries::INFORMATION_SCHEMA_QUERIES;
+pub use factory::series_cardinality::INFORMATION_SCHEMA_SERIES_CARDINALITY;
 pub use factory::tables::INFORMATION_SCHEMA_TABLES;
 use meta::error::MetaError;
 use meta::model::MetaClientRef;
@@ -29,10 +35,12 @@ use self::factory::columns::ColumnsFactory;
 use self::factory::database_privileges::DatabasePrivilegesFactory;
 use self::factory::databases::DatabasesFactory;
 use self::factory::enabled_roles::EnabledRolesFactory;
+use self::factory::functions::FunctionsFactory;
 use self::factory::members::MembersFactory;
 use self::factory::queries::QueriesFactory;
 use self::factory::resource_status::InformationSchemaResourceStatusFactory;
 use self::factory::roles::RolesFactory;
+use self::factory::series_cardinality::SeriesCardinalityFactory;
 use super::INFORMATION_SCHEMA;
 use crate::dispatcher::query_tracker::QueryTracker;
 use crate::metadata::information_schema_provider::factory::tables::TablesFactory;
@@ -47,7 +55,7 @@ pub struct InformationSchemaProvider {
 }
 
 impl InformationSchemaProvider {
-    pub fn new(query_tracker: Arc<QueryTracker>) -> Self {
+    pub fn new(coord: CoordinatorRef, query_tracker: Arc<QueryTracker>) -> Self {
         let mut provider = Self {
             query_tracker,
             table_factories: Default::default(),
@@ -60,8 +68,10 @@ impl InformationSchemaProvider {
         provider.register_table_factory(Box::new(RolesFactory {}));
         provider.register_table_factory(Box::new(DatabasePrivilegesFactory {}));
         provider.register_table_factory(Box::new(MembersFactory {}));
+        provider.register_table_factory(Box::new(FunctionsFactory {}));
         provider.register_table_factory(Box::new(QueriesFactory {}));
         provider.register_table_factory(Box::new(InformationSchemaResourceStatusFactory {}));
+        provider.register_table_factory(Box::new(SeriesCardinalityFactory::new(coord)));
 
         provider
     }
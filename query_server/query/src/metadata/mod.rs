@@ -21,19 +21,25 @@ pub use information_schema_provider::{
     DATABASES_REPLICA, DATABASES_SHARD, DATABASES_STRICT_WRITE, DATABASES_TENANT_NAME,
     DATABASES_TTL, DATABASES_VNODE_DURATION, DATABASES_WAL_MAX_FILE_SIZE, DATABASES_WAL_SYNC,
     INFORMATION_SCHEMA_COLUMNS, INFORMATION_SCHEMA_DATABASES, INFORMATION_SCHEMA_QUERIES,
-    INFORMATION_SCHEMA_TABLES, TABLES_TABLE_DATABASE, TABLES_TABLE_ENGINE, TABLES_TABLE_NAME,
-    TABLES_TABLE_OPTIONS, TABLES_TABLE_TENANT, TABLES_TABLE_TYPE,
+    INFORMATION_SCHEMA_SERIES_CARDINALITY, INFORMATION_SCHEMA_TABLES,
+    SERIES_CARDINALITY_DATABASE_NAME, SERIES_CARDINALITY_SERIES_COUNT,
+    SERIES_CARDINALITY_TABLE_NAME, SERIES_CARDINALITY_TAG_KEY, SERIES_CARDINALITY_TAG_VALUE_COUNT,
+    TABLES_TABLE_DATABASE, TABLES_TABLE_ENGINE, TABLES_TABLE_NAME, TABLES_TABLE_OPTIONS,
+    TABLES_TABLE_TENANT, TABLES_TABLE_TYPE,
 };
 use meta::error::MetaError;
 use meta::model::MetaClientRef;
 use models::auth::user::UserDesc;
 use models::meta_data::DatabaseInfo;
 use models::object_reference::{Resolve, ResolvedTable};
+use models::schema::function_schema::{FunctionKind, FunctionSchema};
+use models::schema::policy_schema::PolicySchema;
 use models::schema::tenant::Tenant;
 use models::schema::{DEFAULT_CATALOG, DEFAULT_DATABASE};
 use parking_lot::RwLock;
 use spi::query::function::FuncMetaManagerRef;
 use spi::query::session::SessionCtx;
+use trace::warn;
 use utils::precision::Precision;
 
 pub use self::base_table::BaseTableProvider;
@@ -41,6 +47,7 @@ use self::cluster_schema_provider::ClusterSchemaProvider;
 use self::information_schema_provider::InformationSchemaProvider;
 use crate::data_source::table_source::{TableHandle, TableSourceAdapter};
 use crate::dispatcher::query_tracker::QueryTracker;
+use crate::function::user_defined::{user_defined_aggregate_udf, user_defined_scalar_udf};
 use crate::metadata::usage_schema_provider::UsageSchemaProvider;
 
 mod base_table;
@@ -74,6 +81,14 @@ pub trait ContextProviderExtension: ContextProvider {
     ) -> Result<(), MetaError> {
         Ok(())
     }
+    /// The function created by `CREATE FUNCTION` in the current tenant
+    fn get_function_schema(&self, _name: &str) -> Option<FunctionSchema> {
+        None
+    }
+    /// The row-level security policies created by `CREATE POLICY` on the table
+    fn get_table_policies(&self, _database: &str, _table: &str) -> Vec<PolicySchema> {
+        vec![]
+    }
 }
 
 pub type TableHandleProviderRef = Arc<dyn TableHandleProvider + Send + Sync>;
@@ -110,13 +125,16 @@ impl MetadataProvider {
     ) -> Self {
         Self {
             current_session_table_provider,
+            information_schema_provider: InformationSchemaProvider::new(
+                coord.clone(),
+                query_tracker,
+            ),
             coord,
             // TODO refactor
             config_options: session.inner().config_options().clone(),
             session,
             meta_client,
             func_manager,
-            information_schema_provider: InformationSchemaProvider::new(query_tracker),
             cluster_schema_provider: ClusterSchemaProvider::new(),
             usage_schema_provider: UsageSchemaProvider::new(default_table_provider),
             access_databases: Default::default(),
@@ -303,6 +321,14 @@ impl ContextProviderExtension for MetadataProvider {
 
         Ok(())
     }
+
+    fn get_function_schema(&self, name: &str) -> Option<FunctionSchema> {
+        self.meta_client.function(name)
+    }
+
+    fn get_table_policies(&self, database: &str, table: &str) -> Vec<PolicySchema> {
+        self.meta_client.table_policies(database, table)
+    }
 }
 
 impl ContextProvider for MetadataProvider {
@@ -314,16 +340,31 @@ impl ContextProvider for MetadataProvider {
     }
 
     fn get_function_meta(&self, name: &str) -> Option<Arc<ScalarUDF>> {
-        self.func_manager.udf(name).ok().or(self
-            .session
-            .inner()
-            .scalar_functions()
-            .get(name)
-            .cloned())
+        self.func_manager
+            .udf(name)
+            .ok()
+            .or(self.session.inner().scalar_functions().get(name).cloned())
+            .or_else(|| {
+                let function = self
+                    .get_function_schema(name)
+                    .filter(|f| f.kind == FunctionKind::Scalar)?;
+                user_defined_scalar_udf(&function)
+                    .inspect_err(|err| warn!("Failed to load function {}: {}", name, err))
+                    .ok()
+                    .map(Arc::new)
+            })
     }
 
     fn get_aggregate_meta(&self, name: &str) -> Option<Arc<AggregateUDF>> {
-        self.func_manager.udaf(name).ok()
+        self.func_manager.udaf(name).ok().or_else(|| {
+            let function = self
+                .get_function_schema(name)
+                .filter(|f| f.kind == FunctionKind::Aggregate)?;
+            user_defined_aggregate_udf(&function)
+                .inspect_err(|err| warn!("Failed to load function {}: {}", name, err))
+                .ok()
+                .map(Arc::new)
+        })
     }
 
     fn get_variable_type(&self, variable_names: &[String]) -> Option<DataType> {
@@ -386,6 +427,10 @@ impl TableSet {
     pub fn push_table(&mut self, tbl: impl Into<String>) {
         self.tables.insert(tbl.into());
     }
+
+    pub fn tables(&self) -> Vec<&String> {
+        self.tables.iter().collect()
+    }
 }
 
 // "cnosdb" tenant additional check "public" and "CLUSTER_SCHEMA"
@@ -1,3 +1,4 @@
+pub mod promql;
 pub mod remote_server;
 pub mod time_series;
 
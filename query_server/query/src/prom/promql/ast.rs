@@ -0,0 +1,247 @@
+use std::fmt::{Display, Formatter};
+
+use protos::prompb::prometheus::label_matcher::Type as MatchType;
+use protos::prompb::prometheus::LabelMatcher;
+
+use crate::prom::METRIC_NAME_LABEL;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    NumberLiteral(f64),
+    StringLiteral(String),
+    VectorSelector(VectorSelector),
+    MatrixSelector(MatrixSelector),
+    Paren(Box<Expr>),
+    Unary(Box<Expr>),
+    Binary(BinaryExpr),
+    Aggregate(AggregateExpr),
+    Call(Call),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct VectorSelector {
+    pub name: Option<String>,
+    pub matchers: Vec<Matcher>,
+    /// Milliseconds
+    pub offset: i64,
+}
+
+impl VectorSelector {
+    /// All matchers of the selector, including the one implied by the metric name.
+    pub fn label_matchers(&self) -> Vec<LabelMatcher> {
+        let mut result = Vec::with_capacity(self.matchers.len() + 1);
+        if let Some(name) = &self.name {
+            result.push(LabelMatcher {
+                r#type: MatchType::Eq as i32,
+                name: METRIC_NAME_LABEL.to_string(),
+                value: name.clone(),
+            });
+        }
+        result.extend(self.matchers.iter().map(|m| LabelMatcher {
+            r#type: m.op as i32,
+            name: m.name.clone(),
+            value: m.value.clone(),
+        }));
+        result
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatrixSelector {
+    pub selector: VectorSelector,
+    /// Milliseconds
+    pub range: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Matcher {
+    pub name: String,
+    pub op: MatchType,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Pow,
+    Eql,
+    Neq,
+    Gtr,
+    Lss,
+    Gte,
+    Lte,
+    And,
+    Or,
+    Unless,
+}
+
+impl BinaryOp {
+    pub fn precedence(&self) -> u8 {
+        match self {
+            BinaryOp::Or => 1,
+            BinaryOp::And | BinaryOp::Unless => 2,
+            BinaryOp::Eql
+            | BinaryOp::Neq
+            | BinaryOp::Gtr
+            | BinaryOp::Lss
+            | BinaryOp::Gte
+            | BinaryOp::Lte => 3,
+            BinaryOp::Add | BinaryOp::Sub => 4,
+            BinaryOp::Mul | BinaryOp::Div | BinaryOp::Mod => 5,
+            BinaryOp::Pow => 6,
+        }
+    }
+
+    pub fn is_right_associative(&self) -> bool {
+        matches!(self, BinaryOp::Pow)
+    }
+
+    pub fn is_comparison(&self) -> bool {
+        matches!(
+            self,
+            BinaryOp::Eql
+                | BinaryOp::Neq
+                | BinaryOp::Gtr
+                | BinaryOp::Lss
+                | BinaryOp::Gte
+                | BinaryOp::Lte
+        )
+    }
+
+    pub fn is_set_operator(&self) -> bool {
+        matches!(self, BinaryOp::And | BinaryOp::Or | BinaryOp::Unless)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VectorMatchCardinality {
+    OneToOne,
+    /// group_left(<labels>)
+    ManyToOne(Vec<String>),
+    /// group_right(<labels>)
+    OneToMany(Vec<String>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VectorMatching {
+    pub card: VectorMatchCardinality,
+    /// Labels listed in `on(...)` or `ignoring(...)`
+    pub labels: Vec<String>,
+    /// `true` for `on(...)`, `false` for `ignoring(...)`
+    pub on: bool,
+}
+
+impl Default for VectorMatching {
+    fn default() -> Self {
+        Self {
+            card: VectorMatchCardinality::OneToOne,
+            labels: vec![],
+            on: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BinaryExpr {
+    pub op: BinaryOp,
+    pub lhs: Box<Expr>,
+    pub rhs: Box<Expr>,
+    /// `bool` modifier of comparison operators
+    pub return_bool: bool,
+    pub matching: VectorMatching,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AggregateOp {
+    Sum,
+    Avg,
+    Min,
+    Max,
+    Count,
+    Group,
+    Stddev,
+    Stdvar,
+    Topk,
+    Bottomk,
+    Quantile,
+}
+
+impl AggregateOp {
+    pub fn from_name(name: &str) -> Option<Self> {
+        let op = match name.to_ascii_lowercase().as_str() {
+            "sum" => AggregateOp::Sum,
+            "avg" => AggregateOp::Avg,
+            "min" => AggregateOp::Min,
+            "max" => AggregateOp::Max,
+            "count" => AggregateOp::Count,
+            "group" => AggregateOp::Group,
+            "stddev" => AggregateOp::Stddev,
+            "stdvar" => AggregateOp::Stdvar,
+            "topk" => AggregateOp::Topk,
+            "bottomk" => AggregateOp::Bottomk,
+            "quantile" => AggregateOp::Quantile,
+            _ => return None,
+        };
+        Some(op)
+    }
+
+    pub fn has_param(&self) -> bool {
+        matches!(
+            self,
+            AggregateOp::Topk | AggregateOp::Bottomk | AggregateOp::Quantile
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Grouping {
+    By(Vec<String>),
+    Without(Vec<String>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AggregateExpr {
+    pub op: AggregateOp,
+    pub expr: Box<Expr>,
+    pub param: Option<Box<Expr>>,
+    pub grouping: Grouping,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Call {
+    pub func: String,
+    pub args: Vec<Expr>,
+}
+
+impl Display for Matcher {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let op = match self.op {
+            MatchType::Eq => "=",
+            MatchType::Neq => "!=",
+            MatchType::Re => "=~",
+            MatchType::Nre => "!~",
+        };
+        write!(f, "{}{}{:?}", self.name, op, self.value)
+    }
+}
+
+impl Display for VectorSelector {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some(name) = &self.name {
+            write!(f, "{}", name)?;
+        }
+        if !self.matchers.is_empty() || self.name.is_none() {
+            let matchers = self
+                .matchers
+                .iter()
+                .map(|m| m.to_string())
+                .collect::<Vec<_>>();
+            write!(f, "{{{}}}", matchers.join(","))?;
+        }
+        Ok(())
+    }
+}
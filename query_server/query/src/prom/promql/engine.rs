@@ -0,0 +1,1176 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use async_trait::async_trait;
+use futures::future::try_join_all;
+use spi::server::prom::{PromInstantSample, PromLabels, PromRangeSeries, PromValue};
+use spi::{QueryError, QueryResult};
+
+use super::ast::{
+    AggregateExpr, AggregateOp, BinaryExpr, BinaryOp, Expr, Grouping, MatrixSelector,
+    VectorMatchCardinality, VectorMatching, VectorSelector,
+};
+use super::functions;
+use crate::prom::METRIC_NAME_LABEL;
+
+/// Same as the `--query.lookback-delta` default of Prometheus.
+pub const DEFAULT_LOOKBACK_DELTA_MS: i64 = 5 * 60 * 1000;
+
+/// Same as the resolution limit of the Prometheus HTTP API.
+const MAX_POINTS_PER_SERIES: i64 = 11_000;
+
+/// Same as the `--query.max-samples` default of Prometheus, the maximum number of
+/// samples a query may load into memory.
+pub const MAX_SAMPLES: usize = 50_000_000;
+
+/// Raw samples of one series, ordered by timestamp (ms).
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawSeries {
+    pub labels: PromLabels,
+    pub samples: Vec<(i64, f64)>,
+}
+
+/// Windows `(origin + k * interval, origin + (k + 1) * interval]` (ms) of which only
+/// the last sample is used, the windows end at the evaluation steps of an instant
+/// vector selector, and the latest sample before a step is the last one of a window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LastSampleWindows {
+    pub origin: i64,
+    pub interval: i64,
+}
+
+/// Provides the raw samples of the series selected by a vector selector.
+#[async_trait]
+pub trait SeriesSource {
+    /// Returns the samples in `[start, end]` (ms) of all series matched by `selector`,
+    /// only the last sample of each window is required if `windows` is set.
+    async fn select(
+        &self,
+        selector: &VectorSelector,
+        start: i64,
+        end: i64,
+        windows: Option<LastSampleWindows>,
+    ) -> QueryResult<Vec<RawSeries>>;
+}
+
+/// One series of an instant vector evaluated at every step, `None` where there is no sample.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StepSeries {
+    pub labels: PromLabels,
+    pub values: Vec<Option<f64>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// One value per step
+    Scalar(Vec<f64>),
+    String(String),
+    Vector(Vec<StepSeries>),
+    /// A range vector, only valid as an argument of functions
+    Matrix {
+        series: Vec<RawSeries>,
+        range: i64,
+        offset: i64,
+    },
+}
+
+impl Value {
+    fn type_name(&self) -> &'static str {
+        match self {
+            Value::Scalar(_) => "scalar",
+            Value::String(_) => "string",
+            Value::Vector(_) => "instant vector",
+            Value::Matrix { .. } => "range vector",
+        }
+    }
+}
+
+pub(super) fn type_error(expected: &str, value: &Value) -> QueryError {
+    QueryError::InvalidPromQL {
+        reason: format!(
+            "expected type {} in expression, got {}",
+            expected,
+            value.type_name()
+        ),
+    }
+}
+
+pub(super) fn eval_error(reason: impl Into<String>) -> QueryError {
+    QueryError::InvalidPromQL {
+        reason: reason.into(),
+    }
+}
+
+/// A selector of an expression and the range of a matrix selector, identifies the
+/// samples fetched for it. Equal selectors of an expression share the same samples.
+type SelectorKey<'a> = (&'a VectorSelector, Option<i64>);
+
+/// Evaluates PromQL expressions at every step of `[start, end]`.
+///
+/// Raw samples of all selectors are fetched up-front through a [`SeriesSource`],
+/// and the whole range is then evaluated at once, node by node. Instant vector
+/// selectors only fetch the last sample before each step.
+pub struct Evaluator {
+    start: i64,
+    end: i64,
+    step: i64,
+    lookback_delta: i64,
+}
+
+impl Evaluator {
+    pub fn try_new(start: i64, end: i64, step: i64) -> QueryResult<Self> {
+        if end < start {
+            return Err(eval_error("end timestamp must not be before start time"));
+        }
+        if step <= 0 {
+            return Err(eval_error(
+                "zero or negative query resolution step widths are not accepted",
+            ));
+        }
+        if (end - start) / step > MAX_POINTS_PER_SERIES {
+            return Err(eval_error(format!(
+                "exceeded maximum resolution of {} points per timeseries",
+                MAX_POINTS_PER_SERIES
+            )));
+        }
+        Ok(Self {
+            start,
+            end,
+            step,
+            lookback_delta: DEFAULT_LOOKBACK_DELTA_MS,
+        })
+    }
+
+    pub fn instant(time: i64) -> Self {
+        Self {
+            start: time,
+            end: time,
+            step: 1,
+            lookback_delta: DEFAULT_LOOKBACK_DELTA_MS,
+        }
+    }
+
+    pub(super) fn num_steps(&self) -> usize {
+        ((self.end - self.start) / self.step + 1) as usize
+    }
+
+    pub(super) fn step_time(&self, idx: usize) -> i64 {
+        self.start + idx as i64 * self.step
+    }
+
+    /// Evaluate `expr` as an instant query at `start`.
+    pub async fn evaluate_instant(
+        &self,
+        expr: &Expr,
+        source: &(dyn SeriesSource + Send + Sync),
+    ) -> QueryResult<PromValue> {
+        let time = self.start;
+        let value = self.evaluate(expr, source).await?;
+        let result = match value {
+            Value::Scalar(v) => PromValue::Scalar {
+                timestamp: time,
+                value: v[0],
+            },
+            Value::String(value) => PromValue::String {
+                timestamp: time,
+                value,
+            },
+            Value::Vector(series) => PromValue::Vector(
+                series
+                    .into_iter()
+                    .filter_map(|s| {
+                        s.values[0].map(|value| PromInstantSample {
+                            labels: s.labels,
+                            timestamp: time,
+                            value,
+                        })
+                    })
+                    .collect(),
+            ),
+            Value::Matrix {
+                series,
+                range,
+                offset,
+            } => {
+                let (window_start, window_end) = (time - offset - range, time - offset);
+                PromValue::Matrix(
+                    series
+                        .into_iter()
+                        .filter_map(|s| {
+                            let points = window(&s.samples, window_start, window_end).to_vec();
+                            (!points.is_empty()).then_some(PromRangeSeries {
+                                labels: s.labels,
+                                points,
+                            })
+                        })
+                        .collect(),
+                )
+            }
+        };
+        Ok(result)
+    }
+
+    /// Evaluate `expr` as a range query over `[start, end]`.
+    pub async fn evaluate_range(
+        &self,
+        expr: &Expr,
+        source: &(dyn SeriesSource + Send + Sync),
+    ) -> QueryResult<PromValue> {
+        let value = self.evaluate(expr, source).await?;
+        let series = match value {
+            Value::Scalar(values) => vec![StepSeries {
+                labels: PromLabels::new(),
+                values: values.into_iter().map(Some).collect(),
+            }],
+            Value::Vector(series) => series,
+            v => {
+                return Err(eval_error(format!(
+                "invalid expression type \"{}\" for range query, must be scalar or instant vector",
+                v.type_name()
+            )))
+            }
+        };
+
+        let matrix = series
+            .into_iter()
+            .filter_map(|s| {
+                let points = s
+                    .values
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(idx, v)| v.map(|v| (self.step_time(idx), v)))
+                    .collect::<Vec<_>>();
+                (!points.is_empty()).then_some(PromRangeSeries {
+                    labels: s.labels,
+                    points,
+                })
+            })
+            .collect();
+        Ok(PromValue::Matrix(matrix))
+    }
+
+    async fn evaluate(
+        &self,
+        expr: &Expr,
+        source: &(dyn SeriesSource + Send + Sync),
+    ) -> QueryResult<Value> {
+        let mut selectors = vec![];
+        collect_selectors(expr, &mut selectors);
+        let selectors = selectors.into_iter().collect::<HashSet<_>>();
+
+        let fetches = selectors.iter().map(|(selector, range)| {
+            let start = self.start - selector.offset - range.unwrap_or(self.lookback_delta);
+            let end = self.end - selector.offset;
+            let windows = range.is_none().then(|| LastSampleWindows {
+                origin: self.start - selector.offset,
+                interval: if self.num_steps() > 1 {
+                    self.step
+                } else {
+                    self.lookback_delta
+                },
+            });
+            async move {
+                let series = source.select(selector, start, end, windows).await?;
+                Ok::<_, QueryError>(((*selector, *range), series))
+            }
+        });
+        let data = try_join_all(fetches)
+            .await?
+            .into_iter()
+            .collect::<HashMap<_, _>>();
+
+        self.eval(expr, &data)
+    }
+
+    fn eval(&self, expr: &Expr, data: &HashMap<SelectorKey, Vec<RawSeries>>) -> QueryResult<Value> {
+        match expr {
+            Expr::NumberLiteral(n) => Ok(Value::Scalar(vec![*n; self.num_steps()])),
+            Expr::StringLiteral(s) => Ok(Value::String(s.clone())),
+            Expr::Paren(expr) => self.eval(expr, data),
+            Expr::VectorSelector(selector) => {
+                let series = fetched((selector, None), data);
+                Ok(Value::Vector(self.eval_vector_selector(selector, series)))
+            }
+            Expr::MatrixSelector(MatrixSelector { selector, range }) => Ok(Value::Matrix {
+                series: fetched((selector, Some(*range)), data).to_vec(),
+                range: *range,
+                offset: selector.offset,
+            }),
+            Expr::Unary(expr) => match self.eval(expr, data)? {
+                Value::Scalar(values) => Ok(Value::Scalar(values.iter().map(|v| -v).collect())),
+                Value::Vector(series) => Ok(Value::Vector(
+                    series
+                        .into_iter()
+                        .map(|s| StepSeries {
+                            labels: drop_metric_name(s.labels),
+                            values: s.values.iter().map(|v| v.map(|v| -v)).collect(),
+                        })
+                        .collect(),
+                )),
+                v => Err(type_error("scalar or instant vector", &v)),
+            },
+            Expr::Binary(binary) => self.eval_binary(binary, data),
+            Expr::Aggregate(aggregate) => self.eval_aggregate(aggregate, data),
+            Expr::Call(call) => {
+                let args = call
+                    .args
+                    .iter()
+                    .map(|arg| self.eval(arg, data))
+                    .collect::<QueryResult<Vec<_>>>()?;
+                functions::call(self, &call.func, args)
+            }
+        }
+    }
+
+    /// Pick the latest sample within the lookback window of every step.
+    fn eval_vector_selector(
+        &self,
+        selector: &VectorSelector,
+        series: &[RawSeries],
+    ) -> Vec<StepSeries> {
+        let num_steps = self.num_steps();
+        series
+            .iter()
+            .filter_map(|s| {
+                let mut values = Vec::with_capacity(num_steps);
+                let mut pos = 0;
+                for idx in 0..num_steps {
+                    let t = self.step_time(idx) - selector.offset;
+                    while pos < s.samples.len() && s.samples[pos].0 <= t {
+                        pos += 1;
+                    }
+                    let value = match pos.checked_sub(1).map(|p| s.samples[p]) {
+                        Some((ts, v)) if ts > t - self.lookback_delta => Some(v),
+                        _ => None,
+                    };
+                    values.push(value);
+                }
+                values.iter().any(Option::is_some).then(|| StepSeries {
+                    labels: s.labels.clone(),
+                    values,
+                })
+            })
+            .collect()
+    }
+
+    /// Apply `f` to the samples of each series within the range window of every step.
+    pub(super) fn eval_range_function(
+        &self,
+        value: Value,
+        keep_metric_name: bool,
+        f: impl Fn(&[(i64, f64)], i64, i64) -> Option<f64>,
+    ) -> QueryResult<Value> {
+        let Value::Matrix {
+            series,
+            range,
+            offset,
+        } = value
+        else {
+            return Err(type_error("range vector", &value));
+        };
+
+        let num_steps = self.num_steps();
+        let result = series
+            .into_iter()
+            .filter_map(|s| {
+                let values = (0..num_steps)
+                    .map(|idx| {
+                        let window_end = self.step_time(idx) - offset;
+                        let window_start = window_end - range;
+                        let samples = window(&s.samples, window_start, window_end);
+                        f(samples, window_start, window_end)
+                    })
+                    .collect::<Vec<_>>();
+                let labels = if keep_metric_name {
+                    s.labels
+                } else {
+                    drop_metric_name(s.labels)
+                };
+                values
+                    .iter()
+                    .any(Option::is_some)
+                    .then_some(StepSeries { labels, values })
+            })
+            .collect();
+        Ok(Value::Vector(result))
+    }
+
+    fn eval_aggregate(
+        &self,
+        aggregate: &AggregateExpr,
+        data: &HashMap<SelectorKey, Vec<RawSeries>>,
+    ) -> QueryResult<Value> {
+        let series = match self.eval(&aggregate.expr, data)? {
+            Value::Vector(series) => series,
+            v => return Err(type_error("instant vector", &v)),
+        };
+        let param = aggregate
+            .param
+            .as_ref()
+            .map(|p| match self.eval(p, data)? {
+                Value::Scalar(values) => Ok(values),
+                v => Err(type_error("scalar", &v)),
+            })
+            .transpose()?;
+
+        let mut groups: BTreeMap<PromLabels, Vec<usize>> = BTreeMap::new();
+        for (idx, s) in series.iter().enumerate() {
+            groups
+                .entry(grouping_labels(&s.labels, &aggregate.grouping))
+                .or_default()
+                .push(idx);
+        }
+
+        let num_steps = self.num_steps();
+        match aggregate.op {
+            AggregateOp::Topk | AggregateOp::Bottomk => {
+                let k = param.unwrap_or_else(|| vec![0.0; num_steps]);
+                let mut result = series
+                    .iter()
+                    .map(|s| StepSeries {
+                        labels: s.labels.clone(),
+                        values: vec![None; num_steps],
+                    })
+                    .collect::<Vec<_>>();
+                for members in groups.values() {
+                    for step in 0..num_steps {
+                        let k = k[step];
+                        if k < 1.0 {
+                            continue;
+                        }
+                        let mut candidates = members
+                            .iter()
+                            .filter_map(|idx| series[*idx].values[step].map(|v| (*idx, v)))
+                            .collect::<Vec<_>>();
+                        candidates.sort_by(|(_, a), (_, b)| {
+                            let ord = a.total_cmp(b);
+                            if aggregate.op == AggregateOp::Topk {
+                                ord.reverse()
+                            } else {
+                                ord
+                            }
+                        });
+                        for (idx, v) in candidates.into_iter().take(k as usize) {
+                            result[idx].values[step] = Some(v);
+                        }
+                    }
+                }
+                result.retain(|s| s.values.iter().any(Option::is_some));
+                Ok(Value::Vector(result))
+            }
+            op => {
+                let result = groups
+                    .into_iter()
+                    .filter_map(|(labels, members)| {
+                        let values = (0..num_steps)
+                            .map(|step| {
+                                let samples = members
+                                    .iter()
+                                    .filter_map(|idx| series[*idx].values[step])
+                                    .collect::<Vec<_>>();
+                                if samples.is_empty() {
+                                    return None;
+                                }
+                                let param = param.as_ref().map(|p| p[step]);
+                                Some(aggregate_samples(op, &samples, param))
+                            })
+                            .collect::<Vec<_>>();
+                        values
+                            .iter()
+                            .any(Option::is_some)
+                            .then_some(StepSeries { labels, values })
+                    })
+                    .collect();
+                Ok(Value::Vector(result))
+            }
+        }
+    }
+
+    fn eval_binary(
+        &self,
+        binary: &BinaryExpr,
+        data: &HashMap<SelectorKey, Vec<RawSeries>>,
+    ) -> QueryResult<Value> {
+        let lhs = self.eval(&binary.lhs, data)?;
+        let rhs = self.eval(&binary.rhs, data)?;
+        let op = binary.op;
+
+        match (lhs, rhs) {
+            (Value::Scalar(l), Value::Scalar(r)) => {
+                if op.is_set_operator() {
+                    return Err(eval_error(
+                        "set operator not allowed in binary scalar expression",
+                    ));
+                }
+                if op.is_comparison() && !binary.return_bool {
+                    return Err(eval_error(
+                        "comparisons between scalars must use BOOL modifier",
+                    ));
+                }
+                let values = l
+                    .iter()
+                    .zip(r.iter())
+                    .map(|(l, r)| {
+                        let (v, keep) = binary_op(op, *l, *r);
+                        if op.is_comparison() {
+                            bool_to_f64(keep)
+                        } else {
+                            v
+                        }
+                    })
+                    .collect();
+                Ok(Value::Scalar(values))
+            }
+            (Value::Vector(series), Value::Scalar(scalar)) => {
+                self.vector_scalar_binary(binary, series, scalar, false)
+            }
+            (Value::Scalar(scalar), Value::Vector(series)) => {
+                self.vector_scalar_binary(binary, series, scalar, true)
+            }
+            (Value::Vector(lhs), Value::Vector(rhs)) => {
+                if op.is_set_operator() {
+                    Ok(self.vector_set_binary(op, &binary.matching, lhs, rhs))
+                } else {
+                    self.vector_vector_binary(binary, lhs, rhs)
+                }
+            }
+            (Value::Vector(_), v) | (Value::Scalar(_), v) | (v, _) => {
+                Err(type_error("scalar or instant vector", &v))
+            }
+        }
+    }
+
+    fn vector_scalar_binary(
+        &self,
+        binary: &BinaryExpr,
+        series: Vec<StepSeries>,
+        scalar: Vec<f64>,
+        scalar_on_left: bool,
+    ) -> QueryResult<Value> {
+        let op = binary.op;
+        if op.is_set_operator() {
+            return Err(eval_error(
+                "set operator not allowed in binary scalar expression",
+            ));
+        }
+        let drop_name = should_drop_metric_name(op, binary.return_bool);
+
+        let result = series
+            .into_iter()
+            .filter_map(|s| {
+                let values = s
+                    .values
+                    .iter()
+                    .enumerate()
+                    .map(|(step, v)| {
+                        let v = (*v)?;
+                        let (l, r) = if scalar_on_left {
+                            (scalar[step], v)
+                        } else {
+                            (v, scalar[step])
+                        };
+                        let (value, keep) = binary_op(op, l, r);
+                        if !op.is_comparison() {
+                            Some(value)
+                        } else if binary.return_bool {
+                            Some(bool_to_f64(keep))
+                        } else {
+                            // Comparisons filter, and always keep the value of the vector
+                            keep.then_some(v)
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                let labels = if drop_name {
+                    drop_metric_name(s.labels)
+                } else {
+                    s.labels
+                };
+                values
+                    .iter()
+                    .any(Option::is_some)
+                    .then_some(StepSeries { labels, values })
+            })
+            .collect();
+        Ok(Value::Vector(result))
+    }
+
+    fn vector_set_binary(
+        &self,
+        op: BinaryOp,
+        matching: &VectorMatching,
+        lhs: Vec<StepSeries>,
+        rhs: Vec<StepSeries>,
+    ) -> Value {
+        let num_steps = self.num_steps();
+        let lhs_sigs = lhs
+            .iter()
+            .map(|s| matching_signature(&s.labels, matching))
+            .collect::<Vec<_>>();
+        let rhs_sigs = rhs
+            .iter()
+            .map(|s| matching_signature(&s.labels, matching))
+            .collect::<Vec<_>>();
+
+        let mut result: BTreeMap<PromLabels, Vec<Option<f64>>> = BTreeMap::new();
+        let mut put = |labels: &PromLabels, step: usize, v: f64| {
+            result
+                .entry(labels.clone())
+                .or_insert_with(|| vec![None; num_steps])[step] = Some(v);
+        };
+
+        for step in 0..num_steps {
+            let present = |series: &[StepSeries], sigs: &[PromLabels]| {
+                series
+                    .iter()
+                    .zip(sigs)
+                    .filter(|(s, _)| s.values[step].is_some())
+                    .map(|(_, sig)| sig.clone())
+                    .collect::<HashSet<_>>()
+            };
+            match op {
+                BinaryOp::And | BinaryOp::Unless => {
+                    let rhs_present = present(&rhs, &rhs_sigs);
+                    for (s, sig) in lhs.iter().zip(&lhs_sigs) {
+                        if let Some(v) = s.values[step] {
+                            if rhs_present.contains(sig) == (op == BinaryOp::And) {
+                                put(&s.labels, step, v);
+                            }
+                        }
+                    }
+                }
+                _ => {
+                    let lhs_present = present(&lhs, &lhs_sigs);
+                    for s in lhs.iter() {
+                        if let Some(v) = s.values[step] {
+                            put(&s.labels, step, v);
+                        }
+                    }
+                    for (s, sig) in rhs.iter().zip(&rhs_sigs) {
+                        if let Some(v) = s.values[step] {
+                            if !lhs_present.contains(sig) {
+                                put(&s.labels, step, v);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        Value::Vector(
+            result
+                .into_iter()
+                .map(|(labels, values)| StepSeries { labels, values })
+                .collect(),
+        )
+    }
+
+    fn vector_vector_binary(
+        &self,
+        binary: &BinaryExpr,
+        lhs: Vec<StepSeries>,
+        rhs: Vec<StepSeries>,
+    ) -> QueryResult<Value> {
+        let op = binary.op;
+        let matching = &binary.matching;
+        let num_steps = self.num_steps();
+
+        // For group_right the right hand side is the "many" side
+        let swapped = matches!(matching.card, VectorMatchCardinality::OneToMany(_));
+        let (many, one) = if swapped { (rhs, lhs) } else { (lhs, rhs) };
+        let many_sigs = many
+            .iter()
+            .map(|s| matching_signature(&s.labels, matching))
+            .collect::<Vec<_>>();
+        let one_sigs = one
+            .iter()
+            .map(|s| matching_signature(&s.labels, matching))
+            .collect::<Vec<_>>();
+
+        let mut result: BTreeMap<PromLabels, Vec<Option<f64>>> = BTreeMap::new();
+        for step in 0..num_steps {
+            let mut one_side: HashMap<&PromLabels, usize> = HashMap::new();
+            for (idx, (s, sig)) in one.iter().zip(&one_sigs).enumerate() {
+                if s.values[step].is_some() && one_side.insert(sig, idx).is_some() {
+                    return Err(eval_error(format!(
+                        "found duplicate series for the match group {:?} on the {} hand-side of the operation, \
+                        many-to-many matching not allowed: matching labels must be unique on one side",
+                        sig,
+                        if swapped { "left" } else { "right" }
+                    )));
+                }
+            }
+
+            let mut matched_many: HashSet<&PromLabels> = HashSet::new();
+            for (s, sig) in many.iter().zip(&many_sigs) {
+                let Some(many_value) = s.values[step] else {
+                    continue;
+                };
+                let Some(one_idx) = one_side.get(sig) else {
+                    continue;
+                };
+                if matching.card == VectorMatchCardinality::OneToOne && !matched_many.insert(sig) {
+                    return Err(eval_error(format!(
+                        "found duplicate series for the match group {:?} on the left hand-side of the operation, \
+                        many-to-many matching not allowed: matching labels must be unique on one side",
+                        sig
+                    )));
+                }
+
+                let one_series = &one[*one_idx];
+                let one_value = one_series.values[step].unwrap_or_default();
+                let (l, r) = if swapped {
+                    (one_value, many_value)
+                } else {
+                    (many_value, one_value)
+                };
+                let (value, keep) = binary_op(op, l, r);
+                let value = if !op.is_comparison() {
+                    value
+                } else if binary.return_bool {
+                    bool_to_f64(keep)
+                } else if keep {
+                    l
+                } else {
+                    continue;
+                };
+
+                let labels = result_labels(binary, &s.labels, &one_series.labels);
+                let values = result
+                    .entry(labels)
+                    .or_insert_with(|| vec![None; num_steps]);
+                if values[step].is_some() {
+                    return Err(eval_error(
+                        "multiple matches for labels: grouping labels must ensure unique matches",
+                    ));
+                }
+                values[step] = Some(value);
+            }
+        }
+
+        Ok(Value::Vector(
+            result
+                .into_iter()
+                .map(|(labels, values)| StepSeries { labels, values })
+                .collect(),
+        ))
+    }
+}
+
+/// Collect all selectors of `expr`, together with the range of matrix selectors.
+fn collect_selectors<'a>(expr: &'a Expr, out: &mut Vec<SelectorKey<'a>>) {
+    match expr {
+        Expr::NumberLiteral(_) | Expr::StringLiteral(_) => {}
+        Expr::VectorSelector(selector) => out.push((selector, None)),
+        Expr::MatrixSelector(MatrixSelector { selector, range }) => {
+            out.push((selector, Some(*range)))
+        }
+        Expr::Paren(expr) | Expr::Unary(expr) => collect_selectors(expr, out),
+        Expr::Binary(binary) => {
+            collect_selectors(&binary.lhs, out);
+            collect_selectors(&binary.rhs, out);
+        }
+        Expr::Aggregate(aggregate) => {
+            if let Some(param) = &aggregate.param {
+                collect_selectors(param, out);
+            }
+            collect_selectors(&aggregate.expr, out);
+        }
+        Expr::Call(call) => call.args.iter().for_each(|arg| collect_selectors(arg, out)),
+    }
+}
+
+fn fetched<'a>(
+    key: SelectorKey,
+    data: &'a HashMap<SelectorKey, Vec<RawSeries>>,
+) -> &'a [RawSeries] {
+    data.get(&key).map(|s| s.as_slice()).unwrap_or_default()
+}
+
+/// Samples with timestamp in `(start, end]`.
+pub(super) fn window(samples: &[(i64, f64)], start: i64, end: i64) -> &[(i64, f64)] {
+    let lo = samples.partition_point(|(t, _)| *t <= start);
+    let hi = samples.partition_point(|(t, _)| *t <= end);
+    &samples[lo..hi.max(lo)]
+}
+
+pub(super) fn drop_metric_name(mut labels: PromLabels) -> PromLabels {
+    labels.remove(METRIC_NAME_LABEL);
+    labels
+}
+
+fn should_drop_metric_name(op: BinaryOp, return_bool: bool) -> bool {
+    !op.is_comparison() || return_bool
+}
+
+fn bool_to_f64(b: bool) -> f64 {
+    if b {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+/// Returns the arithmetic result and, for comparisons, whether the comparison holds.
+fn binary_op(op: BinaryOp, l: f64, r: f64) -> (f64, bool) {
+    match op {
+        BinaryOp::Add => (l + r, true),
+        BinaryOp::Sub => (l - r, true),
+        BinaryOp::Mul => (l * r, true),
+        BinaryOp::Div => (l / r, true),
+        BinaryOp::Mod => (l % r, true),
+        BinaryOp::Pow => (l.powf(r), true),
+        BinaryOp::Eql => (l, l == r),
+        BinaryOp::Neq => (l, l != r),
+        BinaryOp::Gtr => (l, l > r),
+        BinaryOp::Lss => (l, l < r),
+        BinaryOp::Gte => (l, l >= r),
+        BinaryOp::Lte => (l, l <= r),
+        BinaryOp::And | BinaryOp::Or | BinaryOp::Unless => (l, true),
+    }
+}
+
+fn matching_signature(labels: &PromLabels, matching: &VectorMatching) -> PromLabels {
+    if matching.on {
+        labels
+            .iter()
+            .filter(|(k, _)| matching.labels.contains(k))
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect()
+    } else {
+        labels
+            .iter()
+            .filter(|(k, _)| *k != METRIC_NAME_LABEL && !matching.labels.contains(k))
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect()
+    }
+}
+
+fn result_labels(binary: &BinaryExpr, many: &PromLabels, one: &PromLabels) -> PromLabels {
+    let matching = &binary.matching;
+    let mut labels = many.clone();
+    if should_drop_metric_name(binary.op, binary.return_bool) {
+        labels.remove(METRIC_NAME_LABEL);
+    }
+
+    match &matching.card {
+        VectorMatchCardinality::OneToOne => {
+            if matching.on {
+                labels.retain(|k, _| matching.labels.contains(k));
+            } else {
+                labels.retain(|k, _| !matching.labels.contains(k));
+            }
+        }
+        VectorMatchCardinality::ManyToOne(include) | VectorMatchCardinality::OneToMany(include) => {
+            for name in include {
+                match one.get(name) {
+                    Some(v) if !v.is_empty() => {
+                        labels.insert(name.clone(), v.clone());
+                    }
+                    _ => {
+                        labels.remove(name);
+                    }
+                }
+            }
+        }
+    }
+    labels
+}
+
+fn grouping_labels(labels: &PromLabels, grouping: &Grouping) -> PromLabels {
+    match grouping {
+        Grouping::By(by) => labels
+            .iter()
+            .filter(|(k, _)| by.contains(k))
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect(),
+        Grouping::Without(without) => labels
+            .iter()
+            .filter(|(k, _)| *k != METRIC_NAME_LABEL && !without.contains(k))
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect(),
+    }
+}
+
+fn aggregate_samples(op: AggregateOp, samples: &[f64], param: Option<f64>) -> f64 {
+    let count = samples.len() as f64;
+    match op {
+        AggregateOp::Sum => samples.iter().sum(),
+        AggregateOp::Avg => samples.iter().sum::<f64>() / count,
+        AggregateOp::Min => samples.iter().copied().fold(f64::NAN, f64::min),
+        AggregateOp::Max => samples.iter().copied().fold(f64::NAN, f64::max),
+        AggregateOp::Count => count,
+        AggregateOp::Group => 1.0,
+        AggregateOp::Stddev => variance(samples).sqrt(),
+        AggregateOp::Stdvar => variance(samples),
+        AggregateOp::Quantile => quantile(param.unwrap_or(f64::NAN), samples.to_vec()),
+        // Handled separately since they keep the original series
+        AggregateOp::Topk | AggregateOp::Bottomk => f64::NAN,
+    }
+}
+
+pub(super) fn variance(samples: &[f64]) -> f64 {
+    let count = samples.len() as f64;
+    let mean = samples.iter().sum::<f64>() / count;
+    samples.iter().map(|v| (v - mean) * (v - mean)).sum::<f64>() / count
+}
+
+/// The φ-quantile of `values`, interpolating linearly between the closest ranks.
+pub(super) fn quantile(q: f64, mut values: Vec<f64>) -> f64 {
+    if values.is_empty() || q.is_nan() {
+        return f64::NAN;
+    }
+    if q < 0.0 {
+        return f64::NEG_INFINITY;
+    }
+    if q > 1.0 {
+        return f64::INFINITY;
+    }
+    values.sort_by(f64::total_cmp);
+
+    let rank = q * (values.len() - 1) as f64;
+    let lower = rank.floor().max(0.0) as usize;
+    let upper = (lower + 1).min(values.len() - 1);
+    let weight = rank - rank.floor();
+    values[lower] * (1.0 - weight) + values[upper] * weight
+}
+
+#[cfg(test)]
+pub(super) mod test {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use async_trait::async_trait;
+    use spi::server::prom::{PromLabels, PromRangeSeries, PromValue};
+    use spi::QueryResult;
+
+    use super::{Evaluator, LastSampleWindows, RawSeries, SeriesSource};
+    use crate::prom::promql::ast::VectorSelector;
+    use crate::prom::promql::parser::parse;
+    use crate::prom::METRIC_NAME_LABEL;
+
+    pub struct MemorySeriesSource {
+        pub series: Vec<RawSeries>,
+    }
+
+    #[async_trait]
+    impl SeriesSource for MemorySeriesSource {
+        async fn select(
+            &self,
+            selector: &VectorSelector,
+            start: i64,
+            end: i64,
+            windows: Option<LastSampleWindows>,
+        ) -> QueryResult<Vec<RawSeries>> {
+            Ok(self
+                .series
+                .iter()
+                .filter(|s| s.labels.get(METRIC_NAME_LABEL) == selector.name.as_ref())
+                .filter(|s| {
+                    selector
+                        .matchers
+                        .iter()
+                        .all(|m| s.labels.get(&m.name) == Some(&m.value))
+                })
+                .map(|s| {
+                    let mut samples = s
+                        .samples
+                        .iter()
+                        .filter(|(t, _)| *t >= start && *t <= end)
+                        .copied()
+                        .collect::<Vec<_>>();
+                    // Same as the last sample of each window selected by `SqlSeriesSource`
+                    if let Some(LastSampleWindows { origin, interval }) = windows {
+                        let window = |t: i64| (t - 1 - origin).div_euclid(interval);
+                        samples = samples
+                            .iter()
+                            .enumerate()
+                            .filter(|(i, (t, _))| {
+                                samples
+                                    .get(i + 1)
+                                    .map_or(true, |(next, _)| window(*next) != window(*t))
+                            })
+                            .map(|(_, sample)| *sample)
+                            .collect();
+                    }
+                    RawSeries {
+                        labels: s.labels.clone(),
+                        samples,
+                    }
+                })
+                .collect())
+        }
+    }
+
+    pub fn labels(pairs: &[(&str, &str)]) -> PromLabels {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    /// A counter increasing by `inc` every 10 seconds from 0s to 600s.
+    pub fn counter(name: &str, job: &str, inc: f64) -> RawSeries {
+        RawSeries {
+            labels: labels(&[(METRIC_NAME_LABEL, name), ("job", job)]),
+            samples: (0..=60).map(|i| (i * 10_000, i as f64 * inc)).collect(),
+        }
+    }
+
+    pub async fn range_query(
+        source: &MemorySeriesSource,
+        query: &str,
+        start: i64,
+        end: i64,
+        step: i64,
+    ) -> Vec<PromRangeSeries> {
+        let expr = parse(query).unwrap();
+        let evaluator = Evaluator::try_new(start, end, step).unwrap();
+        match evaluator.evaluate_range(&expr, source).await.unwrap() {
+            PromValue::Matrix(m) => m,
+            v => panic!("unexpected result {:?}", v),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_vector_selector_lookback() {
+        let source = MemorySeriesSource {
+            series: vec![counter("req", "a", 1.0)],
+        };
+        // No sample is newer than 600s, the lookback window is 5m
+        let result = range_query(&source, "req", 590_000, 1_000_000, 100_000).await;
+        assert_eq!(result.len(), 1);
+        assert_eq!(
+            result[0].points,
+            vec![
+                (590_000, 59.0),
+                (690_000, 60.0),
+                (790_000, 60.0),
+                (890_000, 60.0)
+            ]
+        );
+        assert_eq!(result[0].labels.get("job").map(String::as_str), Some("a"));
+    }
+
+    #[tokio::test]
+    async fn test_aggregate_and_binary() {
+        let source = MemorySeriesSource {
+            series: vec![
+                counter("req", "a", 1.0),
+                counter("req", "b", 2.0),
+                counter("limit", "a", 100.0),
+            ],
+        };
+
+        let result = range_query(&source, "sum(req)", 100_000, 100_000, 1_000).await;
+        assert_eq!(result[0].points, vec![(100_000, 30.0)]);
+        assert!(result[0].labels.is_empty());
+
+        let result = range_query(&source, "max by (job) (req) * 2", 100_000, 100_000, 1).await;
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].labels, labels(&[("job", "a")]));
+        assert_eq!(result[0].points, vec![(100_000, 20.0)]);
+        assert_eq!(result[1].points, vec![(100_000, 40.0)]);
+
+        let result = range_query(&source, "req > 15", 100_000, 100_000, 1).await;
+        assert_eq!(result.len(), 1);
+        assert_eq!(
+            result[0].labels,
+            labels(&[(METRIC_NAME_LABEL, "req"), ("job", "b")])
+        );
+
+        let result = range_query(&source, "req / limit", 100_000, 100_000, 1).await;
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].labels, labels(&[("job", "a")]));
+        assert_eq!(result[0].points, vec![(100_000, 0.01)]);
+
+        let result = range_query(&source, "req unless limit", 100_000, 100_000, 1).await;
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].labels.get("job").map(String::as_str), Some("b"));
+
+        let result = range_query(&source, "topk(1, req)", 100_000, 100_000, 1).await;
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].points, vec![(100_000, 20.0)]);
+    }
+
+    #[tokio::test]
+    async fn test_shared_selectors() {
+        struct CountingSource {
+            inner: MemorySeriesSource,
+            selects: AtomicUsize,
+        }
+
+        #[async_trait]
+        impl SeriesSource for CountingSource {
+            async fn select(
+                &self,
+                selector: &VectorSelector,
+                start: i64,
+                end: i64,
+                windows: Option<LastSampleWindows>,
+            ) -> QueryResult<Vec<RawSeries>> {
+                self.selects.fetch_add(1, Ordering::Relaxed);
+                self.inner.select(selector, start, end, windows).await
+            }
+        }
+
+        let expr = parse("req + req + count_over_time(req[1m])").unwrap();
+        let evaluator = Evaluator::try_new(100_000, 100_000, 1).unwrap();
+        // The samples are not bound to the address of the selectors,
+        // a cloned expression is evaluated the same.
+        for expr in [expr.clone(), expr] {
+            let source = CountingSource {
+                inner: MemorySeriesSource {
+                    series: vec![counter("req", "a", 1.0)],
+                },
+                selects: AtomicUsize::new(0),
+            };
+            let result = evaluator.evaluate_range(&expr, &source).await.unwrap();
+
+            assert_eq!(
+                result,
+                PromValue::Matrix(vec![PromRangeSeries {
+                    labels: labels(&[("job", "a")]),
+                    points: vec![(100_000, 26.0)],
+                }])
+            );
+            // The instant and the range selector of `req` are fetched once each
+            assert_eq!(source.selects.load(Ordering::Relaxed), 2);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_instant_query() {
+        let source = MemorySeriesSource {
+            series: vec![counter("req", "a", 1.0)],
+        };
+        let expr = parse("req[30s]").unwrap();
+        let value = Evaluator::instant(100_000)
+            .evaluate_instant(&expr, &source)
+            .await
+            .unwrap();
+        let PromValue::Matrix(m) = value else {
+            panic!("expect matrix")
+        };
+        assert_eq!(
+            m[0].points,
+            vec![(80_000, 8.0), (90_000, 9.0), (100_000, 10.0)]
+        );
+
+        let expr = parse("1 + 2 * 3").unwrap();
+        let value = Evaluator::instant(100_000)
+            .evaluate_instant(&expr, &source)
+            .await
+            .unwrap();
+        assert_eq!(
+            value,
+            PromValue::Scalar {
+                timestamp: 100_000,
+                value: 7.0
+            }
+        );
+
+        let expr = parse("1 > 2").unwrap();
+        assert!(Evaluator::instant(0)
+            .evaluate_instant(&expr, &source)
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,497 @@
+use std::collections::BTreeMap;
+
+use spi::server::prom::PromLabels;
+use spi::QueryResult;
+
+use super::engine::{
+    drop_metric_name, eval_error, quantile, type_error, variance, Evaluator, StepSeries, Value,
+};
+
+/// Evaluate the PromQL function `func` with the already evaluated `args`.
+pub(super) fn call(evaluator: &Evaluator, func: &str, args: Vec<Value>) -> QueryResult<Value> {
+    let num_steps = evaluator.num_steps();
+    match func {
+        "time" => {
+            check_arity(func, &args, 0)?;
+            Ok(Value::Scalar(
+                (0..num_steps)
+                    .map(|idx| evaluator.step_time(idx) as f64 / 1000.0)
+                    .collect(),
+            ))
+        }
+        "vector" => {
+            let [value] = take_args(func, args)?;
+            let values = scalar_arg(value)?;
+            Ok(Value::Vector(vec![StepSeries {
+                labels: PromLabels::new(),
+                values: values.into_iter().map(Some).collect(),
+            }]))
+        }
+        "scalar" => {
+            let [value] = take_args(func, args)?;
+            let series = vector_arg(value)?;
+            let values = (0..num_steps)
+                .map(|step| {
+                    let mut present = series.iter().filter_map(|s| s.values[step]);
+                    match (present.next(), present.next()) {
+                        (Some(v), None) => v,
+                        _ => f64::NAN,
+                    }
+                })
+                .collect();
+            Ok(Value::Scalar(values))
+        }
+
+        "rate" => range_function(evaluator, func, args, false, |s, start, end| {
+            extrapolated_rate(s, start, end, true, true)
+        }),
+        "increase" => range_function(evaluator, func, args, false, |s, start, end| {
+            extrapolated_rate(s, start, end, true, false)
+        }),
+        "delta" => range_function(evaluator, func, args, false, |s, start, end| {
+            extrapolated_rate(s, start, end, false, false)
+        }),
+        "irate" => range_function(evaluator, func, args, false, |s, _, _| {
+            instant_delta(s, true)
+        }),
+        "idelta" => range_function(evaluator, func, args, false, |s, _, _| {
+            instant_delta(s, false)
+        }),
+        "changes" => range_function(evaluator, func, args, false, |s, _, _| {
+            (!s.is_empty()).then(|| s.windows(2).filter(|w| w[0].1 != w[1].1).count() as f64)
+        }),
+        "resets" => range_function(evaluator, func, args, false, |s, _, _| {
+            (!s.is_empty()).then(|| s.windows(2).filter(|w| w[1].1 < w[0].1).count() as f64)
+        }),
+        "avg_over_time" => over_time(evaluator, func, args, |v| {
+            v.iter().sum::<f64>() / v.len() as f64
+        }),
+        "sum_over_time" => over_time(evaluator, func, args, |v| v.iter().sum()),
+        "count_over_time" => over_time(evaluator, func, args, |v| v.len() as f64),
+        "min_over_time" => over_time(evaluator, func, args, |v| {
+            v.iter().copied().fold(f64::NAN, f64::min)
+        }),
+        "max_over_time" => over_time(evaluator, func, args, |v| {
+            v.iter().copied().fold(f64::NAN, f64::max)
+        }),
+        "stddev_over_time" => over_time(evaluator, func, args, |v| variance(v).sqrt()),
+        "stdvar_over_time" => over_time(evaluator, func, args, variance),
+        "last_over_time" => range_function(evaluator, func, args, true, |s, _, _| {
+            s.last().map(|(_, v)| *v)
+        }),
+        "quantile_over_time" => {
+            let [q, matrix] = take_args(func, args)?;
+            let q = scalar_arg(q)?;
+            // The quantile is evaluated at the first step, like a constant parameter
+            let q = q.first().copied().unwrap_or(f64::NAN);
+            evaluator.eval_range_function(matrix, false, |s, _, _| {
+                (!s.is_empty()).then(|| quantile(q, s.iter().map(|(_, v)| *v).collect()))
+            })
+        }
+
+        "abs" => math(func, args, f64::abs),
+        "ceil" => math(func, args, f64::ceil),
+        "floor" => math(func, args, f64::floor),
+        "exp" => math(func, args, f64::exp),
+        "ln" => math(func, args, f64::ln),
+        "log2" => math(func, args, f64::log2),
+        "log10" => math(func, args, f64::log10),
+        "sqrt" => math(func, args, f64::sqrt),
+        "sgn" => math(func, args, |v| if v == 0.0 { 0.0 } else { v.signum() }),
+        "round" => {
+            let (vector, to_nearest) = if args.len() == 1 {
+                let [vector] = take_args(func, args)?;
+                (vector, vec![1.0; num_steps])
+            } else {
+                let [vector, to_nearest] = take_args(func, args)?;
+                (vector, scalar_arg(to_nearest)?)
+            };
+            vector_with_params(vector, vec![to_nearest], |v, p| {
+                let inverse = 1.0 / p[0];
+                (v * inverse + 0.5).floor() / inverse
+            })
+        }
+        "clamp_min" => {
+            let [vector, min] = take_args(func, args)?;
+            vector_with_params(vector, vec![scalar_arg(min)?], |v, p| v.max(p[0]))
+        }
+        "clamp_max" => {
+            let [vector, max] = take_args(func, args)?;
+            vector_with_params(vector, vec![scalar_arg(max)?], |v, p| v.min(p[0]))
+        }
+        "clamp" => {
+            let [vector, min, max] = take_args(func, args)?;
+            let params = vec![scalar_arg(min)?, scalar_arg(max)?];
+            vector_with_params(vector, params, |v, p| {
+                if p[1] < p[0] {
+                    f64::NAN
+                } else {
+                    v.max(p[0]).min(p[1])
+                }
+            })
+        }
+        "histogram_quantile" => {
+            let [q, vector] = take_args(func, args)?;
+            histogram_quantile(num_steps, &scalar_arg(q)?, vector_arg(vector)?)
+        }
+        _ => Err(eval_error(format!(
+            "unknown function with name \"{}\"",
+            func
+        ))),
+    }
+}
+
+fn arity_error(func: &str, expected: usize, got: usize) -> spi::QueryError {
+    eval_error(format!(
+        "expected {} argument(s) in call to \"{}\", got {}",
+        expected, func, got
+    ))
+}
+
+fn check_arity(func: &str, args: &[Value], expected: usize) -> QueryResult<()> {
+    if args.len() != expected {
+        return Err(arity_error(func, expected, args.len()));
+    }
+    Ok(())
+}
+
+fn take_args<const N: usize>(func: &str, args: Vec<Value>) -> QueryResult<[Value; N]> {
+    let len = args.len();
+    args.try_into().map_err(|_| arity_error(func, N, len))
+}
+
+fn scalar_arg(value: Value) -> QueryResult<Vec<f64>> {
+    match value {
+        Value::Scalar(values) => Ok(values),
+        v => Err(type_error("scalar", &v)),
+    }
+}
+
+fn vector_arg(value: Value) -> QueryResult<Vec<StepSeries>> {
+    match value {
+        Value::Vector(series) => Ok(series),
+        v => Err(type_error("instant vector", &v)),
+    }
+}
+
+fn range_function(
+    evaluator: &Evaluator,
+    func: &str,
+    args: Vec<Value>,
+    keep_metric_name: bool,
+    f: impl Fn(&[(i64, f64)], i64, i64) -> Option<f64>,
+) -> QueryResult<Value> {
+    let [matrix] = take_args(func, args)?;
+    evaluator.eval_range_function(matrix, keep_metric_name, f)
+}
+
+fn over_time(
+    evaluator: &Evaluator,
+    func: &str,
+    args: Vec<Value>,
+    f: impl Fn(&[f64]) -> f64,
+) -> QueryResult<Value> {
+    range_function(evaluator, func, args, false, |samples, _, _| {
+        if samples.is_empty() {
+            return None;
+        }
+        let values = samples.iter().map(|(_, v)| *v).collect::<Vec<_>>();
+        Some(f(&values))
+    })
+}
+
+fn math(func: &str, args: Vec<Value>, f: impl Fn(f64) -> f64) -> QueryResult<Value> {
+    let [vector] = take_args(func, args)?;
+    vector_with_params(vector, vec![], |v, _| f(v))
+}
+
+/// Apply `f` to every sample of the instant vector `vector`,
+/// with the values of the scalar `params` at the same step.
+fn vector_with_params(
+    vector: Value,
+    params: Vec<Vec<f64>>,
+    f: impl Fn(f64, &[f64]) -> f64,
+) -> QueryResult<Value> {
+    let series = vector_arg(vector)?;
+    let result = series
+        .into_iter()
+        .map(|s| {
+            let values = s
+                .values
+                .iter()
+                .enumerate()
+                .map(|(step, v)| {
+                    v.map(|v| {
+                        let p = params.iter().map(|p| p[step]).collect::<Vec<_>>();
+                        f(v, &p)
+                    })
+                })
+                .collect();
+            StepSeries {
+                labels: drop_metric_name(s.labels),
+                values,
+            }
+        })
+        .collect();
+    Ok(Value::Vector(result))
+}
+
+/// The same extrapolation as `extrapolatedRate` of Prometheus,
+/// used by `rate`, `increase` and `delta`.
+fn extrapolated_rate(
+    samples: &[(i64, f64)],
+    range_start: i64,
+    range_end: i64,
+    is_counter: bool,
+    is_rate: bool,
+) -> Option<f64> {
+    if samples.len() < 2 {
+        return None;
+    }
+    let (first_t, first_v) = samples[0];
+    let (last_t, last_v) = samples[samples.len() - 1];
+
+    let mut result = last_v - first_v;
+    if is_counter {
+        let mut prev = first_v;
+        for (_, v) in &samples[1..] {
+            if *v < prev {
+                result += prev;
+            }
+            prev = *v;
+        }
+    }
+
+    let mut duration_to_start = (first_t - range_start) as f64 / 1000.0;
+    let duration_to_end = (range_end - last_t) as f64 / 1000.0;
+    let sampled_interval = (last_t - first_t) as f64 / 1000.0;
+    let average_duration_between_samples = sampled_interval / (samples.len() - 1) as f64;
+
+    if is_counter && result > 0.0 && first_v >= 0.0 {
+        // Counters can't be negative, don't extrapolate below zero
+        let duration_to_zero = sampled_interval * (first_v / result);
+        if duration_to_zero < duration_to_start {
+            duration_to_start = duration_to_zero;
+        }
+    }
+
+    let extrapolation_threshold = average_duration_between_samples * 1.1;
+    let mut extrapolate_to_interval = sampled_interval;
+    extrapolate_to_interval += if duration_to_start < extrapolation_threshold {
+        duration_to_start
+    } else {
+        average_duration_between_samples / 2.0
+    };
+    extrapolate_to_interval += if duration_to_end < extrapolation_threshold {
+        duration_to_end
+    } else {
+        average_duration_between_samples / 2.0
+    };
+
+    result *= extrapolate_to_interval / sampled_interval;
+    if is_rate {
+        result /= (range_end - range_start) as f64 / 1000.0;
+    }
+    Some(result)
+}
+
+/// `irate` and `idelta`, computed from the last two samples.
+fn instant_delta(samples: &[(i64, f64)], is_rate: bool) -> Option<f64> {
+    if samples.len() < 2 {
+        return None;
+    }
+    let (prev_t, prev_v) = samples[samples.len() - 2];
+    let (last_t, last_v) = samples[samples.len() - 1];
+
+    let mut result = last_v - prev_v;
+    if is_rate {
+        if last_v < prev_v {
+            // Counter reset
+            result = last_v;
+        }
+        let interval = (last_t - prev_t) as f64 / 1000.0;
+        if interval == 0.0 {
+            return None;
+        }
+        result /= interval;
+    }
+    Some(result)
+}
+
+fn histogram_quantile(num_steps: usize, q: &[f64], series: Vec<StepSeries>) -> QueryResult<Value> {
+    const BUCKET_LABEL: &str = "le";
+
+    let mut groups: BTreeMap<PromLabels, Vec<(f64, &StepSeries)>> = BTreeMap::new();
+    for s in series.iter() {
+        let Some(upper_bound) = s
+            .labels
+            .get(BUCKET_LABEL)
+            .and_then(|le| parse_bucket_le(le))
+        else {
+            // Series without a valid `le` label are ignored, same as Prometheus
+            continue;
+        };
+        let mut labels = drop_metric_name(s.labels.clone());
+        labels.remove(BUCKET_LABEL);
+        groups.entry(labels).or_default().push((upper_bound, s));
+    }
+
+    let result = groups
+        .into_iter()
+        .filter_map(|(labels, buckets)| {
+            let values = (0..num_steps)
+                .map(|step| {
+                    let mut step_buckets = buckets
+                        .iter()
+                        .filter_map(|(upper_bound, s)| s.values[step].map(|v| (*upper_bound, v)))
+                        .collect::<Vec<_>>();
+                    if step_buckets.is_empty() {
+                        return None;
+                    }
+                    Some(bucket_quantile(q[step], &mut step_buckets))
+                })
+                .collect::<Vec<_>>();
+            values
+                .iter()
+                .any(Option::is_some)
+                .then_some(StepSeries { labels, values })
+        })
+        .collect();
+    Ok(Value::Vector(result))
+}
+
+fn parse_bucket_le(le: &str) -> Option<f64> {
+    match le {
+        "+Inf" | "Inf" | "inf" => Some(f64::INFINITY),
+        le => le.parse::<f64>().ok(),
+    }
+}
+
+/// The same interpolation as `bucketQuantile` of Prometheus.
+/// `buckets` are `(upper bound, cumulative count)` pairs.
+fn bucket_quantile(q: f64, buckets: &mut [(f64, f64)]) -> f64 {
+    if q.is_nan() {
+        return f64::NAN;
+    }
+    if q < 0.0 {
+        return f64::NEG_INFINITY;
+    }
+    if q > 1.0 {
+        return f64::INFINITY;
+    }
+    buckets.sort_by(|a, b| a.0.total_cmp(&b.0));
+    if buckets.len() < 2 || !buckets[buckets.len() - 1].0.is_infinite() {
+        return f64::NAN;
+    }
+
+    // Make the cumulative counts monotonic, they may be inconsistent due to scrape timing
+    let mut max = f64::NEG_INFINITY;
+    for bucket in buckets.iter_mut() {
+        if bucket.1 < max {
+            bucket.1 = max;
+        }
+        max = bucket.1;
+    }
+
+    let observations = buckets[buckets.len() - 1].1;
+    if observations == 0.0 {
+        return f64::NAN;
+    }
+    let mut rank = q * observations;
+    let b = buckets
+        .iter()
+        .position(|(_, count)| *count >= rank)
+        .unwrap_or(buckets.len() - 1);
+
+    if b == buckets.len() - 1 {
+        return buckets[buckets.len() - 2].0;
+    }
+    if b == 0 && buckets[0].0 <= 0.0 {
+        return buckets[0].0;
+    }
+
+    let mut bucket_start = 0.0;
+    let bucket_end = buckets[b].0;
+    let mut count = buckets[b].1;
+    if b > 0 {
+        bucket_start = buckets[b - 1].0;
+        count -= buckets[b - 1].1;
+        rank -= buckets[b - 1].1;
+    }
+    bucket_start + (bucket_end - bucket_start) * (rank / count)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{bucket_quantile, extrapolated_rate};
+    use crate::prom::promql::engine::test::{counter, labels, range_query, MemorySeriesSource};
+    use crate::prom::promql::engine::RawSeries;
+    use crate::prom::METRIC_NAME_LABEL;
+
+    #[test]
+    fn test_extrapolated_rate() {
+        // 1 per second, sampled every 10s over a 60s window
+        let samples = (1..=6)
+            .map(|i| (i * 10_000, i as f64 * 10.0))
+            .collect::<Vec<_>>();
+        let rate = extrapolated_rate(&samples, 0, 60_000, true, true).unwrap();
+        assert!((rate - 1.0).abs() < 1e-9);
+
+        // Counter reset
+        let samples = vec![(10_000, 10.0), (20_000, 20.0), (30_000, 5.0)];
+        let increase = extrapolated_rate(&samples, 10_000, 30_000, true, false).unwrap();
+        assert!((increase - 15.0).abs() < 1e-9);
+
+        assert!(extrapolated_rate(&samples[..1], 0, 60_000, true, true).is_none());
+    }
+
+    #[test]
+    fn test_bucket_quantile() {
+        let mut buckets = vec![
+            (0.1, 10.0),
+            (f64::INFINITY, 100.0),
+            (1.0, 100.0),
+            (0.5, 50.0),
+        ];
+        assert!((bucket_quantile(0.5, &mut buckets) - 0.5).abs() < 1e-9);
+        assert!((bucket_quantile(0.3, &mut buckets) - 0.3).abs() < 1e-9);
+        assert!(bucket_quantile(0.5, &mut [(1.0, 1.0)]).is_nan());
+    }
+
+    #[tokio::test]
+    async fn test_rate_and_histogram_quantile() {
+        let source = MemorySeriesSource {
+            series: vec![counter("req", "a", 10.0), counter("req", "b", 20.0)],
+        };
+        let result = range_query(
+            &source,
+            "sum by (job) (rate(req[1m]))",
+            120_000,
+            180_000,
+            60_000,
+        )
+        .await;
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].labels, labels(&[("job", "a")]));
+        assert!(result[0].points.iter().all(|(_, v)| (v - 1.0).abs() < 1e-9));
+        assert!(result[1].points.iter().all(|(_, v)| (v - 2.0).abs() < 1e-9));
+
+        let bucket = |le: &str, count: f64| RawSeries {
+            labels: labels(&[(METRIC_NAME_LABEL, "latency_bucket"), ("le", le)]),
+            samples: vec![(0, count)],
+        };
+        let source = MemorySeriesSource {
+            series: vec![
+                bucket("0.1", 0.0),
+                bucket("0.2", 50.0),
+                bucket("0.4", 100.0),
+                bucket("+Inf", 100.0),
+            ],
+        };
+        let result =
+            range_query(&source, "histogram_quantile(0.75, latency_bucket)", 0, 0, 1).await;
+        assert_eq!(result.len(), 1);
+        assert!(result[0].labels.is_empty());
+        assert!((result[0].points[0].1 - 0.3).abs() < 1e-9);
+    }
+}
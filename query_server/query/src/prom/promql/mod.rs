@@ -0,0 +1,9 @@
+//! A PromQL engine for the Prometheus HTTP query API (`/api/v1/query` and `/api/v1/query_range`).
+//!
+//! Vector selectors are translated to SQL over the tskv tables, in the same way as the
+//! remote read path, and the expression tree is evaluated over the fetched series.
+
+pub mod ast;
+pub mod engine;
+mod functions;
+pub mod parser;
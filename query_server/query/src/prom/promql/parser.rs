@@ -0,0 +1,847 @@
+use protos::prompb::prometheus::label_matcher::Type as MatchType;
+use spi::{QueryError, QueryResult};
+
+use super::ast::{
+    AggregateExpr, AggregateOp, BinaryExpr, BinaryOp, Call, Expr, Grouping, Matcher,
+    MatrixSelector, VectorMatchCardinality, VectorMatching, VectorSelector,
+};
+
+/// Parse a PromQL expression.
+pub fn parse(input: &str) -> QueryResult<Expr> {
+    let tokens = Lexer::new(input).tokenize()?;
+    let mut parser = Parser { tokens, pos: 0 };
+    let expr = parser.parse_expr(0)?;
+    match parser.peek() {
+        Token::Eof => Ok(expr),
+        t => Err(syntax_error(format!("unexpected {:?} after expression", t))),
+    }
+}
+
+/// Parse a Prometheus duration such as `5m`, `1h30m` or `500ms` into milliseconds.
+pub fn parse_duration(s: &str) -> QueryResult<i64> {
+    let bytes = s.as_bytes();
+    let mut pos = 0;
+    let mut total = 0_i64;
+    if bytes.is_empty() {
+        return Err(syntax_error("empty duration".to_string()));
+    }
+    while pos < bytes.len() {
+        let start = pos;
+        while pos < bytes.len() && bytes[pos].is_ascii_digit() {
+            pos += 1;
+        }
+        if start == pos {
+            return Err(syntax_error(format!("invalid duration '{}'", s)));
+        }
+        let num = s[start..pos]
+            .parse::<i64>()
+            .map_err(|_| syntax_error(format!("invalid duration '{}'", s)))?;
+        let unit_start = pos;
+        while pos < bytes.len() && bytes[pos].is_ascii_alphabetic() {
+            pos += 1;
+        }
+        let unit = match &s[unit_start..pos] {
+            "ms" => 1,
+            "s" => 1_000,
+            "m" => 60_000,
+            "h" => 3_600_000,
+            "d" => 86_400_000,
+            "w" => 604_800_000,
+            "y" => 31_536_000_000,
+            _ => return Err(syntax_error(format!("invalid duration '{}'", s))),
+        };
+        total += num * unit;
+    }
+    Ok(total)
+}
+
+/// Parse a timestamp parameter of the Prometheus HTTP API,
+/// either RFC3339 or a float number of seconds since epoch, into milliseconds.
+pub fn parse_timestamp(s: &str) -> QueryResult<i64> {
+    if let Ok(seconds) = s.parse::<f64>() {
+        return Ok((seconds * 1000.0).round() as i64);
+    }
+    chrono::DateTime::parse_from_rfc3339(s)
+        .map(|t| t.timestamp_millis())
+        .map_err(|_| QueryError::InvalidParam {
+            reason: format!("cannot parse \"{}\" to a valid timestamp", s),
+        })
+}
+
+/// Parse a duration parameter of the Prometheus HTTP API,
+/// either a duration such as `15s` or a float number of seconds, into milliseconds.
+pub fn parse_step(s: &str) -> QueryResult<i64> {
+    if let Ok(seconds) = s.parse::<f64>() {
+        return Ok((seconds * 1000.0).round() as i64);
+    }
+    parse_duration(s).map_err(|_| QueryError::InvalidParam {
+        reason: format!("cannot parse \"{}\" to a valid duration", s),
+    })
+}
+
+fn syntax_error(reason: String) -> QueryError {
+    QueryError::InvalidPromQL { reason }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(f64),
+    Duration(i64),
+    String(String),
+    LeftParen,
+    RightParen,
+    LeftBrace,
+    RightBrace,
+    LeftBracket,
+    RightBracket,
+    Comma,
+    Assign,
+    Neq,
+    RegexMatch,
+    RegexNotMatch,
+    Eql,
+    Lss,
+    Lte,
+    Gtr,
+    Gte,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Pow,
+    Eof,
+}
+
+struct Lexer<'a> {
+    input: &'a str,
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(input: &'a str) -> Self {
+        Self {
+            input,
+            chars: input.chars().collect(),
+            pos: 0,
+        }
+    }
+
+    fn tokenize(mut self) -> QueryResult<Vec<Token>> {
+        let mut tokens = vec![];
+        loop {
+            let token = self.next_token()?;
+            let eof = token == Token::Eof;
+            tokens.push(token);
+            if eof {
+                return Ok(tokens);
+            }
+        }
+    }
+
+    fn peek_char(&self, n: usize) -> Option<char> {
+        self.chars.get(self.pos + n).copied()
+    }
+
+    fn next_token(&mut self) -> QueryResult<Token> {
+        while let Some(c) = self.peek_char(0) {
+            if c.is_whitespace() {
+                self.pos += 1;
+            } else if c == '#' {
+                // Comment until the end of line
+                while let Some(c) = self.peek_char(0) {
+                    if c == '\n' {
+                        break;
+                    }
+                    self.pos += 1;
+                }
+            } else {
+                break;
+            }
+        }
+
+        let c = match self.peek_char(0) {
+            Some(c) => c,
+            None => return Ok(Token::Eof),
+        };
+        let next = self.peek_char(1);
+
+        let (token, len) = match (c, next) {
+            ('(', _) => (Token::LeftParen, 1),
+            (')', _) => (Token::RightParen, 1),
+            ('{', _) => (Token::LeftBrace, 1),
+            ('}', _) => (Token::RightBrace, 1),
+            ('[', _) => (Token::LeftBracket, 1),
+            (']', _) => (Token::RightBracket, 1),
+            (',', _) => (Token::Comma, 1),
+            ('=', Some('=')) => (Token::Eql, 2),
+            ('=', Some('~')) => (Token::RegexMatch, 2),
+            ('=', _) => (Token::Assign, 1),
+            ('!', Some('=')) => (Token::Neq, 2),
+            ('!', Some('~')) => (Token::RegexNotMatch, 2),
+            ('<', Some('=')) => (Token::Lte, 2),
+            ('<', _) => (Token::Lss, 1),
+            ('>', Some('=')) => (Token::Gte, 2),
+            ('>', _) => (Token::Gtr, 1),
+            ('+', _) => (Token::Add, 1),
+            ('-', _) => (Token::Sub, 1),
+            ('*', _) => (Token::Mul, 1),
+            ('/', _) => (Token::Div, 1),
+            ('%', _) => (Token::Mod, 1),
+            ('^', _) => (Token::Pow, 1),
+            ('"', _) | ('\'', _) | ('`', _) => return self.lex_string(c),
+            (c, _)
+                if c.is_ascii_digit() || (c == '.' && next.is_some_and(|n| n.is_ascii_digit())) =>
+            {
+                return self.lex_number_or_duration()
+            }
+            (c, _) if c.is_ascii_alphabetic() || c == '_' || c == ':' => {
+                return Ok(self.lex_identifier())
+            }
+            (c, _) => {
+                return Err(syntax_error(format!(
+                    "unexpected character '{}' at position {}",
+                    c, self.pos
+                )))
+            }
+        };
+        self.pos += len;
+        Ok(token)
+    }
+
+    fn lex_identifier(&mut self) -> Token {
+        let start = self.pos;
+        while let Some(c) = self.peek_char(0) {
+            if c.is_ascii_alphanumeric() || c == '_' || c == ':' {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+        Token::Ident(self.chars[start..self.pos].iter().collect())
+    }
+
+    fn lex_number_or_duration(&mut self) -> QueryResult<Token> {
+        let start = self.pos;
+        if self.peek_char(0) == Some('0') && matches!(self.peek_char(1), Some('x') | Some('X')) {
+            self.pos += 2;
+            while self.peek_char(0).is_some_and(|c| c.is_ascii_hexdigit()) {
+                self.pos += 1;
+            }
+            let text: String = self.chars[start + 2..self.pos].iter().collect();
+            return i64::from_str_radix(&text, 16)
+                .map(|v| Token::Number(v as f64))
+                .map_err(|_| syntax_error(format!("invalid hex number '0x{}'", text)));
+        }
+
+        while self.peek_char(0).is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+
+        // A number directly followed by a time unit is a duration, e.g. 5m or 1h30m
+        if self
+            .peek_char(0)
+            .is_some_and(|c| matches!(c, 's' | 'm' | 'h' | 'd' | 'w' | 'y'))
+        {
+            while self.peek_char(0).is_some_and(|c| c.is_ascii_alphanumeric()) {
+                self.pos += 1;
+            }
+            let text: String = self.chars[start..self.pos].iter().collect();
+            return parse_duration(&text).map(Token::Duration);
+        }
+
+        if self.peek_char(0) == Some('.') {
+            self.pos += 1;
+            while self.peek_char(0).is_some_and(|c| c.is_ascii_digit()) {
+                self.pos += 1;
+            }
+        }
+        if matches!(self.peek_char(0), Some('e') | Some('E')) {
+            let mut p = self.pos + 1;
+            if matches!(self.chars.get(p), Some('+') | Some('-')) {
+                p += 1;
+            }
+            if self.chars.get(p).is_some_and(|c| c.is_ascii_digit()) {
+                self.pos = p;
+                while self.peek_char(0).is_some_and(|c| c.is_ascii_digit()) {
+                    self.pos += 1;
+                }
+            }
+        }
+
+        let text: String = self.chars[start..self.pos].iter().collect();
+        text.parse::<f64>()
+            .map(Token::Number)
+            .map_err(|_| syntax_error(format!("invalid number '{}'", text)))
+    }
+
+    fn lex_string(&mut self, quote: char) -> QueryResult<Token> {
+        let start = self.pos;
+        self.pos += 1;
+        let mut value = String::new();
+        loop {
+            let c = self.peek_char(0).ok_or_else(|| {
+                syntax_error(format!(
+                    "unterminated string starting at position {} of '{}'",
+                    start, self.input
+                ))
+            })?;
+            self.pos += 1;
+            if c == quote {
+                return Ok(Token::String(value));
+            }
+            if c == '\\' && quote != '`' {
+                let escaped = self
+                    .peek_char(0)
+                    .ok_or_else(|| syntax_error("unterminated escape sequence".to_string()))?;
+                self.pos += 1;
+                match escaped {
+                    'n' => value.push('\n'),
+                    't' => value.push('\t'),
+                    'r' => value.push('\r'),
+                    '\\' => value.push('\\'),
+                    '"' => value.push('"'),
+                    '\'' => value.push('\''),
+                    // Keep unknown escapes as is, regular expressions rely on them, e.g. "\\d+"
+                    other => {
+                        value.push('\\');
+                        value.push(other);
+                    }
+                }
+            } else {
+                value.push(c);
+            }
+        }
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos]
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.tokens[self.pos].clone();
+        if token != Token::Eof {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn expect(&mut self, expected: Token) -> QueryResult<()> {
+        let token = self.next();
+        if token == expected {
+            Ok(())
+        } else {
+            Err(syntax_error(format!(
+                "expected {:?}, found {:?}",
+                expected, token
+            )))
+        }
+    }
+
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Token::Ident(id) if id.eq_ignore_ascii_case(keyword))
+    }
+
+    fn peek_binary_op(&self) -> Option<BinaryOp> {
+        let op = match self.peek() {
+            Token::Add => BinaryOp::Add,
+            Token::Sub => BinaryOp::Sub,
+            Token::Mul => BinaryOp::Mul,
+            Token::Div => BinaryOp::Div,
+            Token::Mod => BinaryOp::Mod,
+            Token::Pow => BinaryOp::Pow,
+            Token::Eql => BinaryOp::Eql,
+            Token::Neq => BinaryOp::Neq,
+            Token::Gtr => BinaryOp::Gtr,
+            Token::Lss => BinaryOp::Lss,
+            Token::Gte => BinaryOp::Gte,
+            Token::Lte => BinaryOp::Lte,
+            Token::Ident(id) => match id.to_ascii_lowercase().as_str() {
+                "and" => BinaryOp::And,
+                "or" => BinaryOp::Or,
+                "unless" => BinaryOp::Unless,
+                _ => return None,
+            },
+            _ => return None,
+        };
+        Some(op)
+    }
+
+    /// Precedence climbing over binary operators.
+    fn parse_expr(&mut self, min_precedence: u8) -> QueryResult<Expr> {
+        let mut lhs = self.parse_unary()?;
+        while let Some(op) = self.peek_binary_op() {
+            let precedence = op.precedence();
+            if precedence < min_precedence {
+                break;
+            }
+            self.next();
+
+            let mut return_bool = false;
+            if self.peek_keyword("bool") {
+                if !op.is_comparison() {
+                    return Err(syntax_error(
+                        "bool modifier can only be used on comparison operators".to_string(),
+                    ));
+                }
+                self.next();
+                return_bool = true;
+            }
+            let matching = self.parse_vector_matching(op)?;
+
+            let next_min = if op.is_right_associative() {
+                precedence
+            } else {
+                precedence + 1
+            };
+            let rhs = self.parse_expr(next_min)?;
+            lhs = Expr::Binary(BinaryExpr {
+                op,
+                lhs: Box::new(lhs),
+                rhs: Box::new(rhs),
+                return_bool,
+                matching,
+            });
+        }
+        Ok(lhs)
+    }
+
+    fn parse_vector_matching(&mut self, op: BinaryOp) -> QueryResult<VectorMatching> {
+        let mut matching = VectorMatching::default();
+        if self.peek_keyword("on") || self.peek_keyword("ignoring") {
+            matching.on = self.peek_keyword("on");
+            self.next();
+            matching.labels = self.parse_label_list()?;
+
+            if self.peek_keyword("group_left") || self.peek_keyword("group_right") {
+                if op.is_set_operator() {
+                    return Err(syntax_error(
+                        "no grouping allowed for set operations".to_string(),
+                    ));
+                }
+                let left = self.peek_keyword("group_left");
+                self.next();
+                let labels = if *self.peek() == Token::LeftParen {
+                    self.parse_label_list()?
+                } else {
+                    vec![]
+                };
+                matching.card = if left {
+                    VectorMatchCardinality::ManyToOne(labels)
+                } else {
+                    VectorMatchCardinality::OneToMany(labels)
+                };
+            }
+        }
+        Ok(matching)
+    }
+
+    fn parse_unary(&mut self) -> QueryResult<Expr> {
+        match self.peek() {
+            Token::Sub => {
+                self.next();
+                // Unary operators bind weaker than '^', so that -2^2 == -4
+                let expr = self.parse_expr(BinaryOp::Pow.precedence())?;
+                Ok(match expr {
+                    Expr::NumberLiteral(n) => Expr::NumberLiteral(-n),
+                    expr => Expr::Unary(Box::new(expr)),
+                })
+            }
+            Token::Add => {
+                self.next();
+                self.parse_expr(BinaryOp::Pow.precedence())
+            }
+            _ => self.parse_postfix(),
+        }
+    }
+
+    fn parse_postfix(&mut self) -> QueryResult<Expr> {
+        let mut expr = self.parse_primary()?;
+        loop {
+            if *self.peek() == Token::LeftBracket {
+                self.next();
+                let range = match self.next() {
+                    Token::Duration(d) => d,
+                    t => return Err(syntax_error(format!("expected duration, found {:?}", t))),
+                };
+                if *self.peek() != Token::RightBracket {
+                    // e.g. rate(x[5m])[30m:1m]
+                    return Err(QueryError::NotImplemented {
+                        err: "PromQL subqueries".to_string(),
+                    });
+                }
+                self.expect(Token::RightBracket)?;
+                expr = match expr {
+                    Expr::VectorSelector(selector) => {
+                        Expr::MatrixSelector(MatrixSelector { selector, range })
+                    }
+                    _ => {
+                        return Err(syntax_error(
+                            "ranges are only allowed for vector selectors".to_string(),
+                        ))
+                    }
+                };
+            } else if self.peek_keyword("offset") {
+                self.next();
+                let negative = if *self.peek() == Token::Sub {
+                    self.next();
+                    true
+                } else {
+                    false
+                };
+                let offset = match self.next() {
+                    Token::Duration(d) => d,
+                    t => return Err(syntax_error(format!("expected duration, found {:?}", t))),
+                };
+                let offset = if negative { -offset } else { offset };
+                match &mut expr {
+                    Expr::VectorSelector(selector) => selector.offset = offset,
+                    Expr::MatrixSelector(matrix) => matrix.selector.offset = offset,
+                    _ => {
+                        return Err(syntax_error(
+                            "offset modifier must be preceded by a selector".to_string(),
+                        ))
+                    }
+                }
+            } else {
+                return Ok(expr);
+            }
+        }
+    }
+
+    fn parse_primary(&mut self) -> QueryResult<Expr> {
+        match self.next() {
+            Token::Number(n) => Ok(Expr::NumberLiteral(n)),
+            Token::Duration(d) => Err(syntax_error(format!(
+                "unexpected duration {}ms in expression",
+                d
+            ))),
+            Token::String(s) => Ok(Expr::StringLiteral(s)),
+            Token::LeftParen => {
+                let expr = self.parse_expr(0)?;
+                self.expect(Token::RightParen)?;
+                Ok(Expr::Paren(Box::new(expr)))
+            }
+            Token::LeftBrace => {
+                let matchers = self.parse_matchers()?;
+                if !matchers.iter().any(|m| match m.op {
+                    MatchType::Eq | MatchType::Re => !m.value.is_empty(),
+                    MatchType::Neq | MatchType::Nre => false,
+                }) {
+                    return Err(syntax_error(
+                        "vector selector must contain at least one non-empty matcher".to_string(),
+                    ));
+                }
+                Ok(Expr::VectorSelector(VectorSelector {
+                    name: None,
+                    matchers,
+                    offset: 0,
+                }))
+            }
+            Token::Ident(id) => self.parse_identifier(id),
+            t => Err(syntax_error(format!("unexpected {:?}", t))),
+        }
+    }
+
+    fn parse_identifier(&mut self, id: String) -> QueryResult<Expr> {
+        if let Some(op) = AggregateOp::from_name(&id) {
+            if matches!(self.peek(), Token::LeftParen)
+                || self.peek_keyword("by")
+                || self.peek_keyword("without")
+            {
+                return self.parse_aggregate(op);
+            }
+        }
+
+        if *self.peek() == Token::LeftParen {
+            self.next();
+            let mut args = vec![];
+            if *self.peek() != Token::RightParen {
+                loop {
+                    args.push(self.parse_expr(0)?);
+                    if *self.peek() == Token::Comma {
+                        self.next();
+                    } else {
+                        break;
+                    }
+                }
+            }
+            self.expect(Token::RightParen)?;
+            return Ok(Expr::Call(Call {
+                func: id.to_ascii_lowercase(),
+                args,
+            }));
+        }
+
+        match id.to_ascii_lowercase().as_str() {
+            "inf" => return Ok(Expr::NumberLiteral(f64::INFINITY)),
+            "nan" => return Ok(Expr::NumberLiteral(f64::NAN)),
+            _ => {}
+        }
+
+        let matchers = if *self.peek() == Token::LeftBrace {
+            self.next();
+            self.parse_matchers()?
+        } else {
+            vec![]
+        };
+        Ok(Expr::VectorSelector(VectorSelector {
+            name: Some(id),
+            matchers,
+            offset: 0,
+        }))
+    }
+
+    /// Parse `name op "value", ...}`, the left brace has been consumed.
+    fn parse_matchers(&mut self) -> QueryResult<Vec<Matcher>> {
+        let mut matchers = vec![];
+        loop {
+            match self.next() {
+                Token::RightBrace => return Ok(matchers),
+                Token::Ident(name) => {
+                    let op = match self.next() {
+                        Token::Assign => MatchType::Eq,
+                        Token::Neq => MatchType::Neq,
+                        Token::RegexMatch => MatchType::Re,
+                        Token::RegexNotMatch => MatchType::Nre,
+                        t => {
+                            return Err(syntax_error(format!(
+                                "expected label matching operator, found {:?}",
+                                t
+                            )))
+                        }
+                    };
+                    let value = match self.next() {
+                        Token::String(s) => s,
+                        t => return Err(syntax_error(format!("expected string, found {:?}", t))),
+                    };
+                    matchers.push(Matcher { name, op, value });
+                    match self.next() {
+                        Token::Comma => continue,
+                        Token::RightBrace => return Ok(matchers),
+                        t => {
+                            return Err(syntax_error(format!(
+                                "expected ',' or '}}' in label matchers, found {:?}",
+                                t
+                            )))
+                        }
+                    }
+                }
+                t => return Err(syntax_error(format!("expected label name, found {:?}", t))),
+            }
+        }
+    }
+
+    fn parse_label_list(&mut self) -> QueryResult<Vec<String>> {
+        self.expect(Token::LeftParen)?;
+        let mut labels = vec![];
+        loop {
+            match self.next() {
+                Token::RightParen => return Ok(labels),
+                Token::Ident(label) => {
+                    labels.push(label);
+                    match self.next() {
+                        Token::Comma => continue,
+                        Token::RightParen => return Ok(labels),
+                        t => {
+                            return Err(syntax_error(format!(
+                                "expected ',' or ')' in label list, found {:?}",
+                                t
+                            )))
+                        }
+                    }
+                }
+                t => return Err(syntax_error(format!("expected label name, found {:?}", t))),
+            }
+        }
+    }
+
+    fn parse_grouping(&mut self) -> QueryResult<Option<Grouping>> {
+        if self.peek_keyword("by") {
+            self.next();
+            return Ok(Some(Grouping::By(self.parse_label_list()?)));
+        }
+        if self.peek_keyword("without") {
+            self.next();
+            return Ok(Some(Grouping::Without(self.parse_label_list()?)));
+        }
+        Ok(None)
+    }
+
+    fn parse_aggregate(&mut self, op: AggregateOp) -> QueryResult<Expr> {
+        let leading = self.parse_grouping()?;
+        self.expect(Token::LeftParen)?;
+        let param = if op.has_param() {
+            let param = self.parse_expr(0)?;
+            self.expect(Token::Comma)?;
+            Some(Box::new(param))
+        } else {
+            None
+        };
+        let expr = self.parse_expr(0)?;
+        self.expect(Token::RightParen)?;
+        let trailing = self.parse_grouping()?;
+
+        let grouping = match (leading, trailing) {
+            (Some(_), Some(_)) => {
+                return Err(syntax_error(
+                    "aggregation must only contain one grouping clause".to_string(),
+                ))
+            }
+            (Some(g), None) | (None, Some(g)) => g,
+            (None, None) => Grouping::By(vec![]),
+        };
+
+        Ok(Expr::Aggregate(AggregateExpr {
+            op,
+            expr: Box::new(expr),
+            param,
+            grouping,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use protos::prompb::prometheus::label_matcher::Type as MatchType;
+
+    use super::{parse, parse_duration, parse_step, parse_timestamp};
+    use crate::prom::promql::ast::{
+        AggregateExpr, AggregateOp, BinaryOp, Expr, Grouping, Matcher, VectorMatchCardinality,
+    };
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("5m").unwrap(), 300_000);
+        assert_eq!(parse_duration("1h30m").unwrap(), 5_400_000);
+        assert_eq!(parse_duration("500ms").unwrap(), 500);
+        assert!(parse_duration("5x").is_err());
+        assert!(parse_duration("").is_err());
+    }
+
+    #[test]
+    fn test_parse_api_params() {
+        assert_eq!(parse_timestamp("1700000000.5").unwrap(), 1_700_000_000_500);
+        assert_eq!(
+            parse_timestamp("2023-11-14T22:13:20Z").unwrap(),
+            1_700_000_000_000
+        );
+        assert!(parse_timestamp("yesterday").is_err());
+        assert_eq!(parse_step("15").unwrap(), 15_000);
+        assert_eq!(parse_step("1m").unwrap(), 60_000);
+    }
+
+    #[test]
+    fn test_parse_selector() {
+        let expr = parse(r#"http_requests_total{job="api", code=~"5.."}[5m] offset 1m"#).unwrap();
+        let Expr::MatrixSelector(matrix) = expr else {
+            panic!("expect matrix selector")
+        };
+        assert_eq!(matrix.range, 300_000);
+        assert_eq!(matrix.selector.offset, 60_000);
+        assert_eq!(matrix.selector.name.as_deref(), Some("http_requests_total"));
+        assert_eq!(
+            matrix.selector.matchers,
+            vec![
+                Matcher {
+                    name: "job".to_string(),
+                    op: MatchType::Eq,
+                    value: "api".to_string(),
+                },
+                Matcher {
+                    name: "code".to_string(),
+                    op: MatchType::Re,
+                    value: "5..".to_string(),
+                },
+            ]
+        );
+
+        assert!(parse(r#"{job=""}"#).is_err());
+        assert!(parse("rate(x[5m:1m])").is_err());
+    }
+
+    #[test]
+    fn test_parse_aggregate() {
+        let expr = parse("sum by (job) (rate(http_requests_total[5m]))").unwrap();
+        let Expr::Aggregate(AggregateExpr {
+            op,
+            expr,
+            grouping,
+            param,
+        }) = expr
+        else {
+            panic!("expect aggregation")
+        };
+        assert_eq!(op, AggregateOp::Sum);
+        assert_eq!(grouping, Grouping::By(vec!["job".to_string()]));
+        assert!(param.is_none());
+        assert!(matches!(*expr, Expr::Call(ref c) if c.func == "rate"));
+
+        let expr = parse("topk(3, x) without (instance)").unwrap();
+        assert!(matches!(
+            expr,
+            Expr::Aggregate(AggregateExpr {
+                op: AggregateOp::Topk,
+                grouping: Grouping::Without(_),
+                param: Some(_),
+                ..
+            })
+        ));
+
+        let expr = parse(
+            "histogram_quantile(0.9, sum by (le) (rate(http_request_duration_seconds_bucket[5m])))",
+        )
+        .unwrap();
+        assert!(matches!(expr, Expr::Call(ref c) if c.args.len() == 2));
+    }
+
+    #[test]
+    fn test_parse_binary() {
+        let expr = parse("1 + 2 * 3 ^ 2 ^ 1").unwrap();
+        let Expr::Binary(add) = expr else {
+            panic!("expect binary expression")
+        };
+        assert_eq!(add.op, BinaryOp::Add);
+        let Expr::Binary(mul) = *add.rhs else {
+            panic!("expect binary expression")
+        };
+        assert_eq!(mul.op, BinaryOp::Mul);
+        let Expr::Binary(pow) = *mul.rhs else {
+            panic!("expect binary expression")
+        };
+        assert_eq!(pow.op, BinaryOp::Pow);
+        assert!(matches!(*pow.rhs, Expr::Binary(_)));
+
+        let expr = parse("a > bool on (job) group_left (instance) b or c").unwrap();
+        let Expr::Binary(or) = expr else {
+            panic!("expect binary expression")
+        };
+        assert_eq!(or.op, BinaryOp::Or);
+        let Expr::Binary(gt) = *or.lhs else {
+            panic!("expect binary expression")
+        };
+        assert_eq!(gt.op, BinaryOp::Gtr);
+        assert!(gt.return_bool);
+        assert!(gt.matching.on);
+        assert_eq!(gt.matching.labels, vec!["job".to_string()]);
+        assert_eq!(
+            gt.matching.card,
+            VectorMatchCardinality::ManyToOne(vec!["instance".to_string()])
+        );
+
+        assert!(matches!(
+            parse("-2 ^ 2").unwrap(),
+            Expr::Unary(inner) if matches!(*inner, Expr::Binary(ref b) if b.op == BinaryOp::Pow)
+        ));
+        assert!(parse("a + bool b").is_err());
+    }
+}
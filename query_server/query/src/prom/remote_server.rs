@@ -1,11 +1,14 @@
 use std::borrow::Cow;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use async_trait::async_trait;
 use bytes::Bytes;
+use chrono::{SecondsFormat, TimeZone, Utc};
 use coordinator::service::CoordinatorRef;
 use datafusion::arrow::datatypes::ToByteSlice;
 use futures::future::join_all;
+use futures::TryStreamExt;
 use meta::error::MetaError;
 use meta::model::MetaClientRef;
 use models::schema::tskv_table_schema::TskvTableSchemaRef;
@@ -14,24 +17,34 @@ use models::snappy::SnappyCodec;
 use protocol_parser::Line;
 use protos::models_helper::{parse_prost_bytes, to_prost_bytes};
 use protos::prompb::prometheus::label_matcher::Type;
+use protos::prompb::prometheus::read_request::ResponseType;
 use protos::prompb::prometheus::{
-    Query as PromQuery, QueryResult as PromQueryResult, ReadRequest, ReadResponse, TimeSeries,
-    WriteRequest,
+    ChunkedReadResponse, ChunkedSeries, Label, LabelMatcher, Query as PromQuery,
+    QueryResult as PromQueryResult, ReadRequest, ReadResponse, TimeSeries, WriteRequest,
 };
 use protos::FieldValue;
 use regex::Regex;
 use snafu::ResultExt;
 use spi::server::dbms::DBMSRef;
-use spi::server::prom::PromRemoteServer;
+use spi::server::prom::{
+    PromLabels, PromRemoteServer, PromValue, RemoteReadResponse, RemoteReadStream,
+};
 use spi::service::protocol::{Context, Query, QueryHandle};
 use spi::{MetaSnafu, QueryError, QueryResult, SnappySnafu};
+use tokio::sync::mpsc::{self, Sender};
 use tokio::task;
 use trace::span_ext::SpanExt;
 use trace::{debug, warn, Span, SpanContext};
 
+use super::promql::ast::VectorSelector;
+use super::promql::engine::{Evaluator, LastSampleWindows, RawSeries, SeriesSource, MAX_SAMPLES};
+use super::time_series::chunk::{encode_frame, encode_xor_chunks};
 use super::time_series::writer::WriterBuilder;
 use super::{METRIC_NAME_LABEL, METRIC_SAMPLE_COLUMN_NAME};
-use crate::prom::DEFAULT_PROM_TABLE_NAME;
+use crate::prom::{promql, DEFAULT_PROM_TABLE_NAME};
+
+/// Number of frames buffered for the `STREAMED_XOR_CHUNKS` response.
+const STREAMED_RESPONSE_BUFFER: usize = 16;
 
 pub struct PromRemoteSqlServer {
     db: DBMSRef,
@@ -46,7 +59,7 @@ impl PromRemoteServer for PromRemoteSqlServer {
         ctx: &Context,
         req: Bytes,
         span_ctx: Option<&SpanContext>,
-    ) -> QueryResult<Vec<u8>> {
+    ) -> QueryResult<RemoteReadResponse> {
         let meta = self
             .coord
             .meta_manager()
@@ -62,13 +75,22 @@ impl PromRemoteServer for PromRemoteSqlServer {
         debug!("Received remote read request: {:?}", read_request);
 
         let span = Span::from_context("process read request", span_ctx);
-        let read_response = self
-            .process_read_requests(ctx, meta, read_request, span)
-            .await?;
+        match negotiate_response_type(&read_request.accepted_response_types)? {
+            ResponseType::Samples => {
+                let read_response = self
+                    .process_read_requests(ctx, meta, read_request, span)
+                    .await?;
 
-        debug!("Return remote read response: {:?}", read_response);
+                debug!("Return remote read response: {:?}", read_response);
 
-        self.serialize_read_response(read_response).await
+                self.serialize_read_response(read_response)
+                    .await
+                    .map(RemoteReadResponse::Samples)
+            }
+            ResponseType::StreamedXorChunks => Ok(RemoteReadResponse::StreamedXorChunks(
+                self.stream_read_requests(ctx, meta, read_request, span),
+            )),
+        }
     }
 
     fn remote_write(&self, req: Bytes) -> QueryResult<WriteRequest> {
@@ -112,6 +134,39 @@ impl PromRemoteServer for PromRemoteSqlServer {
 
         Ok(lines)
     }
+
+    async fn instant_query(
+        &self,
+        ctx: &Context,
+        query: &str,
+        time: i64,
+        span_ctx: Option<&SpanContext>,
+    ) -> QueryResult<PromValue> {
+        let expr = promql::parser::parse(query)?;
+        let span = Span::from_context("promql instant query", span_ctx);
+        let source = self.series_source(ctx, span).await?;
+
+        Evaluator::instant(time)
+            .evaluate_instant(&expr, &source)
+            .await
+    }
+
+    async fn range_query(
+        &self,
+        ctx: &Context,
+        query: &str,
+        start: i64,
+        end: i64,
+        step: i64,
+        span_ctx: Option<&SpanContext>,
+    ) -> QueryResult<PromValue> {
+        let expr = promql::parser::parse(query)?;
+        let evaluator = Evaluator::try_new(start, end, step)?;
+        let span = Span::from_context("promql range query", span_ctx);
+        let source = self.series_source(ctx, span).await?;
+
+        evaluator.evaluate_range(&expr, &source).await
+    }
 }
 
 impl PromRemoteSqlServer {
@@ -123,6 +178,26 @@ impl PromRemoteSqlServer {
         }
     }
 
+    async fn series_source(&self, ctx: &Context, span: Span) -> QueryResult<SqlSeriesSource> {
+        let meta = self
+            .coord
+            .meta_manager()
+            .tenant_meta(ctx.tenant())
+            .await
+            .ok_or_else(|| MetaError::TenantNotFound {
+                tenant: ctx.tenant().to_string(),
+            })
+            .context(MetaSnafu)?;
+
+        Ok(SqlSeriesSource {
+            db: self.db.clone(),
+            ctx: ctx.clone(),
+            meta,
+            span,
+            samples: AtomicUsize::new(0),
+        })
+    }
+
     async fn deserialize_read_request(&self, req: Bytes) -> QueryResult<ReadRequest> {
         let mut decompressed = Vec::new();
         let compressed = req.to_byte_slice();
@@ -196,7 +271,7 @@ impl PromRemoteSqlServer {
         meta: MetaClientRef,
         span: Span,
     ) -> QueryResult<Vec<TimeSeries>> {
-        let sqls = build_sql_with_table(&ctx, &meta, q)?;
+        let sqls = build_sql_with_table(&ctx, &meta, q, false)?;
         let len = sqls.len();
         debug!("Prepare to execute: {:?}", sqls);
 
@@ -233,20 +308,7 @@ impl PromRemoteSqlServer {
         sql: SqlWithTable,
         span: Span,
     ) -> QueryResult<Vec<TimeSeries>> {
-        let table_schema = sql.table;
-        let tag_name_indices = table_schema.tag_indices();
-        let sample_value_idx = table_schema
-            .column_index(METRIC_SAMPLE_COLUMN_NAME)
-            .ok_or_else(|| QueryError::ColumnNotExists {
-                table: table_schema.name.to_string(),
-                column: METRIC_SAMPLE_COLUMN_NAME.to_string(),
-            })?;
-        let sample_time_idx = table_schema.column_index(TIME_FIELD_NAME).ok_or_else(|| {
-            QueryError::ColumnNotExists {
-                table: table_schema.name.to_string(),
-                column: TIME_FIELD_NAME.to_string(),
-            }
-        })?;
+        let (tag_name_indices, sample_value_idx, sample_time_idx) = column_indices(&sql.table)?;
 
         let inner_query = Query::new(ctx.clone(), sql.sql);
         let result = db.execute(&inner_query, span.context().as_ref()).await?;
@@ -255,6 +317,85 @@ impl PromRemoteSqlServer {
         transform_time_series(result, tag_name_indices, sample_value_idx, sample_time_idx).await
     }
 
+    /// Execute the queries one after another, and send every series as soon as it is read,
+    /// so that no more than one series is kept in memory.
+    fn stream_read_requests(
+        &self,
+        ctx: &Context,
+        meta: MetaClientRef,
+        read_request: ReadRequest,
+        span: Span,
+    ) -> RemoteReadStream {
+        let (sender, mut receiver) = mpsc::channel(STREAMED_RESPONSE_BUFFER);
+        let db = self.db.clone();
+        let ctx = ctx.clone();
+        task::spawn(async move {
+            let result =
+                Self::send_chunked_series(db, ctx, meta, read_request.queries, span, &sender).await;
+            if let Err(e) = result {
+                let _ = sender.send(Err(e)).await;
+            }
+        });
+
+        Box::pin(futures::stream::poll_fn(move |cx| receiver.poll_recv(cx)))
+    }
+
+    async fn send_chunked_series(
+        db: DBMSRef,
+        ctx: Context,
+        meta: MetaClientRef,
+        queries: Vec<PromQuery>,
+        span: Span,
+        sender: &Sender<QueryResult<Vec<u8>>>,
+    ) -> QueryResult<()> {
+        for (query_index, q) in queries.into_iter().enumerate() {
+            let sqls = build_sql_with_table(&ctx, &meta, q, true)?;
+            debug!("Prepare to execute: {:?}", sqls);
+
+            for (idx, sql) in sqls.into_iter().enumerate() {
+                let span = Span::enter_with_parent(
+                    format!("stream_single_sql:{}:{}", query_index, idx),
+                    &span,
+                );
+                let metric_name = sql.table.name.to_string();
+                let (tag_name_indices, sample_value_idx, sample_time_idx) =
+                    column_indices(&sql.table)?;
+
+                let inner_query = Query::new(ctx.clone(), sql.sql);
+                let mut result = db
+                    .execute(&inner_query, span.context().as_ref())
+                    .await?
+                    .result();
+                let mut writer = WriterBuilder::try_new(
+                    tag_name_indices,
+                    sample_value_idx,
+                    sample_time_idx,
+                    result.schema(),
+                )?
+                .build_sorted();
+
+                while let Some(batch) = result.try_next().await? {
+                    for series in writer.write(&batch)? {
+                        let frame = chunked_series_frame(query_index, &metric_name, series);
+                        if sender.send(Ok(frame)).await.is_err() {
+                            debug!("Remote read client has gone, stop streaming");
+                            return Ok(());
+                        }
+                    }
+                }
+                if let Some(series) = writer.finish() {
+                    let frame = chunked_series_frame(query_index, &metric_name, series);
+                    if sender.send(Ok(frame)).await.is_err() {
+                        debug!("Remote read client has gone, stop streaming");
+                        return Ok(());
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     async fn serialize_read_response(&self, read_response: ReadResponse) -> QueryResult<Vec<u8>> {
         let mut compressed = Vec::new();
         let input_buf = to_prost_bytes(&read_response);
@@ -266,11 +407,228 @@ impl PromRemoteSqlServer {
     }
 }
 
+/// Fetch the series of PromQL selectors through the same SQL as remote read.
+struct SqlSeriesSource {
+    db: DBMSRef,
+    ctx: Context,
+    meta: MetaClientRef,
+    span: Span,
+    /// Number of the samples loaded by the query, limited by `MAX_SAMPLES`
+    samples: AtomicUsize,
+}
+
+#[async_trait]
+impl SeriesSource for SqlSeriesSource {
+    async fn select(
+        &self,
+        selector: &VectorSelector,
+        start: i64,
+        end: i64,
+        windows: Option<LastSampleWindows>,
+    ) -> QueryResult<Vec<RawSeries>> {
+        let query = PromQuery {
+            start_timestamp_ms: start,
+            end_timestamp_ms: end,
+            matchers: selector.label_matchers(),
+            hints: None,
+        };
+        let sqls = match windows {
+            Some(windows) => {
+                build_last_sample_sql_with_table(&self.ctx, &self.meta, query, windows)
+            }
+            None => build_sql_with_table(&self.ctx, &self.meta, query, false),
+        };
+        let sqls = match sqls {
+            Ok(sqls) => sqls,
+            // Selecting a metric that has never been written is not an error in PromQL
+            Err(QueryError::Meta {
+                source: MetaError::TableNotFound { .. },
+            }) => return Ok(vec![]),
+            Err(e) => return Err(e),
+        };
+        debug!("Prepare to execute: {:?}", sqls);
+
+        let mut result = vec![];
+        for (idx, sql) in sqls.into_iter().enumerate() {
+            let metric_name = sql.table.name.to_string();
+            let span = Span::enter_with_parent(format!("select:{}", idx), &self.span);
+            let (tag_name_indices, sample_value_idx, sample_time_idx) = match windows {
+                Some(_) => last_sample_column_indices(&sql.table),
+                None => column_indices(&sql.table)?,
+            };
+
+            // Push the remaining sample limit down, so that no more samples than the limit
+            // are buffered by the sort of the query before they are counted here.
+            let remaining = MAX_SAMPLES.saturating_sub(self.samples.load(Ordering::Relaxed));
+            let inner_query = Query::new(
+                self.ctx.clone(),
+                format!("{} LIMIT {}", sql.sql, remaining + 1),
+            );
+            let mut output = self
+                .db
+                .execute(&inner_query, span.context().as_ref())
+                .await?
+                .result();
+            let mut timeseries = HashMap::default();
+            {
+                let mut writer = WriterBuilder::try_new(
+                    tag_name_indices,
+                    sample_value_idx,
+                    sample_time_idx,
+                    output.schema(),
+                )?
+                .build(&mut timeseries);
+                while let Some(batch) = output.try_next().await? {
+                    let rows = batch.num_rows();
+                    if self.samples.fetch_add(rows, Ordering::Relaxed) + rows > MAX_SAMPLES {
+                        return Err(QueryError::InvalidPromQL {
+                            reason: "query processing would load too many samples into memory"
+                                .to_string(),
+                        });
+                    }
+                    writer.write(&batch)?;
+                }
+            }
+
+            result.extend(timeseries.into_values().map(|ts| {
+                let mut labels = ts
+                    .labels
+                    .into_iter()
+                    .map(|l| (l.name, l.value))
+                    .collect::<PromLabels>();
+                labels.insert(METRIC_NAME_LABEL.to_string(), metric_name.clone());
+                RawSeries {
+                    labels,
+                    samples: ts.samples.iter().map(|s| (s.timestamp, s.value)).collect(),
+                }
+            }));
+        }
+
+        Ok(result)
+    }
+}
+
+/// Select the tables of the metric name matchers, and build a SQL for each of them.
+///
+/// If `sort_by_series` is true, rows are ordered by tags before time,
+/// so that the samples of a series are contiguous.
 fn build_sql_with_table(
     ctx: &Context,
     meta: &MetaClientRef,
     query: PromQuery,
+    sort_by_series: bool,
+) -> QueryResult<Vec<SqlWithTable>> {
+    let (tables, filters) = tables_and_filters(ctx, meta, query)?;
+
+    let result = tables
+        .into_iter()
+        .map(|table| {
+            let order_by = if sort_by_series {
+                let mut tags = table
+                    .tag_indices()
+                    .into_iter()
+                    .flat_map(|idx| table.column_by_index(idx))
+                    .map(|column| column.name.as_str())
+                    .collect::<Vec<_>>();
+                tags.sort();
+                tags.iter()
+                    .map(|tag| format!("{} NULLS FIRST, ", quote_ident(tag)))
+                    .chain(std::iter::once("time".to_string()))
+                    .collect::<String>()
+            } else {
+                "time".to_string()
+            };
+            SqlWithTable {
+                sql: format!(
+                    "SELECT * FROM {} WHERE {} order by {}",
+                    quote_ident(&table.name),
+                    filters.join(" AND "),
+                    order_by
+                ),
+                table,
+            }
+        })
+        .collect();
+
+    Ok(result)
+}
+
+/// Select the tables of the metric name matchers, and build a SQL for each of them
+/// that only selects the last sample of each series in every window.
+///
+/// The columns are the tags in the order of the table, the sample time and the sample value,
+/// see `last_sample_column_indices`.
+fn build_last_sample_sql_with_table(
+    ctx: &Context,
+    meta: &MetaClientRef,
+    query: PromQuery,
+    windows: LastSampleWindows,
 ) -> QueryResult<Vec<SqlWithTable>> {
+    let (tables, filters) = tables_and_filters(ctx, meta, query)?;
+
+    let result = tables
+        .into_iter()
+        .map(|table| SqlWithTable {
+            sql: last_sample_sql(&table, &filters, windows),
+            table,
+        })
+        .collect();
+
+    Ok(result)
+}
+
+fn last_sample_sql(
+    table: &TskvTableSchemaRef,
+    filters: &[String],
+    windows: LastSampleWindows,
+) -> String {
+    let tags = table
+        .tag_indices()
+        .into_iter()
+        .flat_map(|idx| table.column_by_index(idx))
+        .map(|column| quote_ident(&column.name))
+        .collect::<Vec<_>>();
+    // The windows are left-open, date_bin puts the time of the window end
+    // into the next window, so the origin is moved 1ns later.
+    let origin = Utc.timestamp_nanos(windows.origin * 1_000_000 + 1);
+    let window = format!(
+        "date_bin(INTERVAL '{} milliseconds', {}, TIMESTAMP '{}')",
+        windows.interval,
+        TIME_FIELD_NAME,
+        origin.to_rfc3339_opts(SecondsFormat::Nanos, true)
+    );
+
+    format!(
+        "SELECT {}max({time}) AS sample_time, last({time}, \"{value}\") AS sample_value \
+        FROM {} WHERE {} GROUP BY {}{} ORDER BY sample_time",
+        tags.iter()
+            .map(|tag| format!("{tag}, "))
+            .collect::<String>(),
+        quote_ident(&table.name),
+        filters.join(" AND "),
+        tags.iter()
+            .map(|tag| format!("{tag}, "))
+            .collect::<String>(),
+        window,
+        time = TIME_FIELD_NAME,
+        value = METRIC_SAMPLE_COLUMN_NAME,
+    )
+}
+
+/// Indices of the tags, the sample value and the sample time in the result of
+/// `build_last_sample_sql_with_table`.
+fn last_sample_column_indices(table: &TskvTableSchemaRef) -> (Vec<usize>, usize, usize) {
+    let tag_num = table.tag_indices().len();
+    ((0..tag_num).collect(), tag_num + 1, tag_num)
+}
+
+/// Select the tables of the metric name matchers, and build the filters
+/// of the other matchers and the time range.
+fn tables_and_filters(
+    ctx: &Context,
+    meta: &MetaClientRef,
+    query: PromQuery,
+) -> QueryResult<(Vec<TskvTableSchemaRef>, Vec<String>)> {
     let PromQuery {
         start_timestamp_ms,
         end_timestamp_ms,
@@ -278,90 +636,216 @@ fn build_sql_with_table(
         hints: _,
     } = query;
 
-    let mut tables = Vec::new();
+    let mut name_matchers = Vec::new();
     let mut filters = Vec::with_capacity(matchers.len());
 
     for m in matchers {
         if METRIC_NAME_LABEL == m.name {
-            match m.r#type() {
-                Type::Eq => {
-                    // Get schema of the specified table
-                    let table_name = &m.value;
-                    let table = meta
-                        .get_tskv_table_schema(ctx.database(), table_name)
-                        .context(MetaSnafu)?
-                        .ok_or_else(|| MetaError::TableNotFound {
-                            table: table_name.to_string(),
-                        })
-                        .context(MetaSnafu)?;
-                    tables = vec![table];
-                }
-                Type::Re => {
-                    // Filter table names through regular expressions,
-                    // Get the schema of the remaining tables.
-                    let pattern =
-                        Regex::new(&m.value).map_err(|err| QueryError::InvalidRemoteReadReq {
-                            source: Box::new(err),
-                        })?;
-
-                    tables = meta
-                        .list_tables(ctx.database())
-                        .context(MetaSnafu)?
-                        .iter()
-                        .filter(|e| pattern.is_match(e))
-                        .flat_map(|table_name| {
-                            if let Ok(s) = meta.get_tskv_table_schema(ctx.database(), table_name) {
-                                s
-                            } else {
-                                warn!(
-                                    "The table {} may have just been dropped, or it may be a bug.",
-                                    table_name
-                                );
-                                None
-                            }
-                        })
-                        .collect::<Vec<_>>();
-                }
-                _ => {
-                    return Err(QueryError::InvalidRemoteReadReq { source: "non-equal or regex-non-equal matchers are not supported on the metric name yet".to_string().into() });
-                }
-            }
-
+            name_matchers.push(m);
             continue;
         }
 
-        match m.r#type() {
-            Type::Eq => {
-                filters.push(format!("{} = '{}'", m.name, m.value));
-            }
-            Type::Neq => {
-                filters.push(format!("{} != '{}'", m.name, m.value));
-            }
-            Type::Re => {
-                filters.push(format!("{} ~ '{}'", m.name, m.value));
-            }
-            Type::Nre => {
-                filters.push(format!("{} !~ '{}'", m.name, m.value));
-            }
-        }
+        filters.push(matcher_filter(&m));
     }
     // Convert to ns timestamp
     filters.push(format!("time >= {}", start_timestamp_ms * 1_000_000));
     filters.push(format!("time <= {}", end_timestamp_ms * 1_000_000));
 
-    let result = tables
+    let tables = select_tables(ctx, meta, &name_matchers)?;
+
+    Ok((tables, filters))
+}
+
+/// Build the SQL filter of a label matcher, the label name and value are quoted
+/// so that they are never interpreted as SQL.
+fn matcher_filter(m: &LabelMatcher) -> String {
+    let name = quote_ident(&m.name);
+    match m.r#type() {
+        Type::Eq => format!("{} = {}", name, quote_literal(&m.value)),
+        Type::Neq => format!("{} != {}", name, quote_literal(&m.value)),
+        // Prometheus regex matchers are fully anchored
+        Type::Re => format!(
+            "{} ~ {}",
+            name,
+            quote_literal(&format!("^(?:{})$", m.value))
+        ),
+        Type::Nre => format!(
+            "{} !~ {}",
+            name,
+            quote_literal(&format!("^(?:{})$", m.value))
+        ),
+    }
+}
+
+fn quote_ident(ident: &str) -> String {
+    format!("\"{}\"", ident.replace('"', "\"\""))
+}
+
+fn quote_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// Select the tables whose name satisfies all the matchers on the metric name.
+///
+/// Tables are sorted by name, which is the order of the `__name__` label of the series.
+fn select_tables(
+    ctx: &Context,
+    meta: &MetaClientRef,
+    name_matchers: &[LabelMatcher],
+) -> QueryResult<Vec<TskvTableSchemaRef>> {
+    if name_matchers.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let predicates = name_matchers
+        .iter()
+        .map(MetricNamePredicate::try_new)
+        .collect::<QueryResult<Vec<_>>>()?;
+
+    if let Some(MetricNamePredicate::Eq(table_name)) = predicates
+        .iter()
+        .find(|p| matches!(p, MetricNamePredicate::Eq(_)))
+    {
+        // Get schema of the specified table
+        let table = meta
+            .get_tskv_table_schema(ctx.database(), table_name)
+            .context(MetaSnafu)?
+            .ok_or_else(|| MetaError::TableNotFound {
+                table: table_name.to_string(),
+            })
+            .context(MetaSnafu)?;
+        if predicates.iter().all(|p| p.matches(table_name)) {
+            return Ok(vec![table]);
+        }
+        return Ok(vec![]);
+    }
+
+    // Filter table names through all the matchers,
+    // Get the schema of the remaining tables.
+    let mut table_names = meta
+        .list_tables(ctx.database())
+        .context(MetaSnafu)?
         .into_iter()
-        .map(|table| SqlWithTable {
-            sql: format!(
-                "SELECT * FROM \"{}\" WHERE {} order by time",
-                table.name,
-                filters.join(" AND ")
-            ),
-            table,
+        .filter(|table_name| predicates.iter().all(|p| p.matches(table_name)))
+        .collect::<Vec<_>>();
+    table_names.sort();
+
+    let tables = table_names
+        .iter()
+        .flat_map(|table_name| {
+            if let Ok(s) = meta.get_tskv_table_schema(ctx.database(), table_name) {
+                s
+            } else {
+                warn!(
+                    "The table {} may have just been dropped, or it may be a bug.",
+                    table_name
+                );
+                None
+            }
         })
-        .collect();
+        .collect::<Vec<_>>();
 
-    Ok(result)
+    Ok(tables)
+}
+
+/// Matcher on the metric name, regular expressions are fully anchored as in Prometheus.
+enum MetricNamePredicate {
+    Eq(String),
+    Neq(String),
+    Re(Regex),
+    Nre(Regex),
+}
+
+impl MetricNamePredicate {
+    fn try_new(matcher: &LabelMatcher) -> QueryResult<Self> {
+        let regex = || {
+            Regex::new(&format!("^(?:{})$", matcher.value)).map_err(|err| {
+                QueryError::InvalidRemoteReadReq {
+                    source: Box::new(err),
+                }
+            })
+        };
+        let predicate = match matcher.r#type() {
+            Type::Eq => Self::Eq(matcher.value.clone()),
+            Type::Neq => Self::Neq(matcher.value.clone()),
+            Type::Re => Self::Re(regex()?),
+            Type::Nre => Self::Nre(regex()?),
+        };
+        Ok(predicate)
+    }
+
+    fn matches(&self, table_name: &str) -> bool {
+        match self {
+            Self::Eq(name) => name == table_name,
+            Self::Neq(name) => name != table_name,
+            Self::Re(pattern) => pattern.is_match(table_name),
+            Self::Nre(pattern) => !pattern.is_match(table_name),
+        }
+    }
+}
+
+/// Indices of the tags, the sample value and the sample time in the table.
+fn column_indices(table_schema: &TskvTableSchemaRef) -> QueryResult<(Vec<usize>, usize, usize)> {
+    let tag_name_indices = table_schema.tag_indices();
+    let sample_value_idx = table_schema
+        .column_index(METRIC_SAMPLE_COLUMN_NAME)
+        .ok_or_else(|| QueryError::ColumnNotExists {
+            table: table_schema.name.to_string(),
+            column: METRIC_SAMPLE_COLUMN_NAME.to_string(),
+        })?;
+    let sample_time_idx =
+        table_schema
+            .column_index(TIME_FIELD_NAME)
+            .ok_or_else(|| QueryError::ColumnNotExists {
+                table: table_schema.name.to_string(),
+                column: TIME_FIELD_NAME.to_string(),
+            })?;
+    Ok((tag_name_indices, sample_value_idx, sample_time_idx))
+}
+
+/// Response types are taken from `accepted_response_types` in FIFO order,
+/// SAMPLES is used if the request does not contain any.
+fn negotiate_response_type(accepted_response_types: &[i32]) -> QueryResult<ResponseType> {
+    if accepted_response_types.is_empty() {
+        return Ok(ResponseType::Samples);
+    }
+    accepted_response_types
+        .iter()
+        .find_map(|t| ResponseType::from_i32(*t))
+        .ok_or_else(|| QueryError::InvalidRemoteReadReq {
+            source: format!(
+                "none of the accepted response types {:?} is supported",
+                accepted_response_types
+            )
+            .into(),
+        })
+}
+
+/// Encode a series as a frame of the `STREAMED_XOR_CHUNKS` response.
+fn chunked_series_frame(query_index: usize, metric_name: &str, series: TimeSeries) -> Vec<u8> {
+    let TimeSeries {
+        mut labels,
+        samples,
+        ..
+    } = series;
+    // Empty labels are equal to absent labels in Prometheus
+    labels.retain(|l| !l.value.is_empty());
+    if !labels.iter().any(|l| l.name == METRIC_NAME_LABEL) {
+        labels.push(Label {
+            name: METRIC_NAME_LABEL.to_string(),
+            value: metric_name.to_string(),
+        });
+    }
+    labels.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let response = ChunkedReadResponse {
+        chunked_series: vec![ChunkedSeries {
+            labels,
+            chunks: encode_xor_chunks(&samples),
+        }],
+        query_index: query_index as i64,
+    };
+    encode_frame(&response)
 }
 
 /// Convert the execution result of query to TimeSeries list of prometheus
@@ -405,12 +889,20 @@ mod test {
     use datafusion::arrow::record_batch::RecordBatch;
     use models::auth::user::{User, UserDesc, UserOptions};
     use models::schema::query_info::QueryId;
-    use protos::prompb::prometheus::{Label, Sample, TimeSeries};
+    use models::schema::tskv_table_schema::{ColumnType, TableColumn, TskvTableSchema};
+    use models::ValueType;
+    use protos::prompb::prometheus::label_matcher::Type;
+    use protos::prompb::prometheus::read_request::ResponseType;
+    use protos::prompb::prometheus::{Label, LabelMatcher, Sample, TimeSeries};
     use spi::query::execution::Output;
     use spi::query::recordbatch::RecordBatchStreamWrapper;
     use spi::service::protocol::{ContextBuilder, Query, QueryHandle};
 
-    use crate::prom::remote_server::transform_time_series;
+    use crate::prom::promql::engine::LastSampleWindows;
+    use crate::prom::remote_server::{
+        last_sample_column_indices, last_sample_sql, matcher_filter, negotiate_response_type,
+        transform_time_series, MetricNamePredicate,
+    };
 
     #[tokio::test]
     async fn test_transform_time_series() {
@@ -475,4 +967,114 @@ mod test {
 
         assert_eq!(vec![expect], time_series);
     }
+
+    #[test]
+    fn test_metric_name_predicate() {
+        let predicate = |r#type: Type, value: &str| {
+            MetricNamePredicate::try_new(&LabelMatcher {
+                r#type: r#type as i32,
+                name: "__name__".to_string(),
+                value: value.to_string(),
+            })
+            .unwrap()
+        };
+
+        assert!(predicate(Type::Eq, "cpu").matches("cpu"));
+        assert!(!predicate(Type::Eq, "cpu").matches("cpu_usage"));
+        assert!(predicate(Type::Neq, "cpu").matches("cpu_usage"));
+        assert!(!predicate(Type::Neq, "cpu").matches("cpu"));
+        // Regular expressions are fully anchored
+        assert!(predicate(Type::Re, "cpu.*").matches("cpu_usage"));
+        assert!(!predicate(Type::Re, "cpu").matches("cpu_usage"));
+        assert!(!predicate(Type::Re, "usage").matches("cpu_usage"));
+        assert!(predicate(Type::Nre, "mem.*|disk.*").matches("cpu_usage"));
+        assert!(!predicate(Type::Nre, "cpu.*").matches("cpu_usage"));
+
+        assert!(MetricNamePredicate::try_new(&LabelMatcher {
+            r#type: Type::Re as i32,
+            name: "__name__".to_string(),
+            value: "(".to_string(),
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_negotiate_response_type() {
+        assert_eq!(negotiate_response_type(&[]).unwrap(), ResponseType::Samples);
+        assert_eq!(
+            negotiate_response_type(&[
+                ResponseType::StreamedXorChunks as i32,
+                ResponseType::Samples as i32
+            ])
+            .unwrap(),
+            ResponseType::StreamedXorChunks
+        );
+        assert_eq!(
+            negotiate_response_type(&[100, ResponseType::Samples as i32]).unwrap(),
+            ResponseType::Samples
+        );
+        assert!(negotiate_response_type(&[100]).is_err());
+    }
+
+    #[test]
+    fn test_matcher_filter() {
+        let matcher = |r#type: Type, name: &str, value: &str| LabelMatcher {
+            r#type: r#type as i32,
+            name: name.to_string(),
+            value: value.to_string(),
+        };
+
+        assert_eq!(
+            matcher_filter(&matcher(Type::Eq, "job", "api")),
+            "\"job\" = 'api'"
+        );
+        assert_eq!(
+            matcher_filter(&matcher(Type::Neq, "job", "a' OR '1' = '1")),
+            "\"job\" != 'a'' OR ''1'' = ''1'"
+        );
+        assert_eq!(
+            matcher_filter(&matcher(Type::Re, "a\" = 'x", "it's|api")),
+            "\"a\"\" = 'x\" ~ '^(?:it''s|api)$'"
+        );
+        assert_eq!(
+            matcher_filter(&matcher(Type::Nre, "job", "api.*")),
+            "\"job\" !~ '^(?:api.*)$'"
+        );
+    }
+
+    #[test]
+    fn test_last_sample_sql() {
+        let table = Arc::new(TskvTableSchema::new(
+            "cnosdb".to_string(),
+            "public".to_string(),
+            "req".to_string(),
+            vec![
+                TableColumn::new_time_column(0, TimeUnit::Nanosecond),
+                TableColumn::new_tag_column(1, "job".to_string()),
+                TableColumn::new_tag_column(2, "instance".to_string()),
+                TableColumn::new_with_default(
+                    "value".to_string(),
+                    ColumnType::Field(ValueType::Float),
+                ),
+            ],
+        ));
+        let filters = vec![
+            "job = 'api'".to_string(),
+            "time >= 0".to_string(),
+            "time <= 600000000000".to_string(),
+        ];
+        let windows = LastSampleWindows {
+            origin: 600_000,
+            interval: 60_000,
+        };
+
+        assert_eq!(
+            last_sample_sql(&table, &filters, windows),
+            "SELECT \"job\", \"instance\", max(time) AS sample_time, last(time, \"value\") AS sample_value \
+            FROM \"req\" WHERE job = 'api' AND time >= 0 AND time <= 600000000000 \
+            GROUP BY \"job\", \"instance\", date_bin(INTERVAL '60000 milliseconds', time, \
+            TIMESTAMP '1970-01-01T00:10:00.000000001Z') ORDER BY sample_time"
+        );
+        assert_eq!(last_sample_column_indices(&table), (vec![0, 1], 3, 2));
+    }
 }
@@ -0,0 +1,380 @@
+//! Encoding of the `STREAMED_XOR_CHUNKS` remote read response.
+//!
+//! Samples are compressed into Prometheus XOR chunks (delta-of-delta timestamps, XOR'd values),
+//! and every `ChunkedReadResponse` is written as a frame of
+//! `uvarint(len) | crc32c(message) as big-endian u32 | message`.
+
+use protos::models_helper::to_prost_bytes;
+use protos::prompb::prometheus::chunk::Encoding;
+use protos::prompb::prometheus::{Chunk, ChunkedReadResponse, Sample};
+
+/// Same as the chunks of Prometheus TSDB.
+pub const MAX_SAMPLES_PER_CHUNK: usize = 120;
+
+const CASTAGNOLI: u32 = 0x82f6_3b78;
+
+const CRC32C_TABLE: [u32; 256] = {
+    let mut table = [0_u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut j = 0;
+        while j < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ CASTAGNOLI
+            } else {
+                crc >> 1
+            };
+            j += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+pub fn crc32c(data: &[u8]) -> u32 {
+    let mut crc = !0_u32;
+    for b in data {
+        crc = CRC32C_TABLE[((crc ^ *b as u32) & 0xff) as usize] ^ (crc >> 8);
+    }
+    !crc
+}
+
+fn put_uvarint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+fn put_varint(buf: &mut Vec<u8>, value: i64) {
+    put_uvarint(buf, ((value << 1) ^ (value >> 63)) as u64)
+}
+
+/// Encode a response as a delimited frame of the streamed response.
+pub fn encode_frame(response: &ChunkedReadResponse) -> Vec<u8> {
+    let message = to_prost_bytes(response);
+    let mut frame = Vec::with_capacity(message.len() + 14);
+    put_uvarint(&mut frame, message.len() as u64);
+    frame.extend_from_slice(&crc32c(&message).to_be_bytes());
+    frame.extend_from_slice(&message);
+    frame
+}
+
+/// Split the time ordered samples of a series into XOR chunks.
+pub fn encode_xor_chunks(samples: &[Sample]) -> Vec<Chunk> {
+    samples
+        .chunks(MAX_SAMPLES_PER_CHUNK)
+        .map(|samples| {
+            let mut encoder = XorEncoder::new();
+            samples
+                .iter()
+                .for_each(|s| encoder.append(s.timestamp, s.value));
+            Chunk {
+                min_time_ms: samples[0].timestamp,
+                max_time_ms: samples[samples.len() - 1].timestamp,
+                r#type: Encoding::Xor as i32,
+                data: encoder.finish(),
+            }
+        })
+        .collect()
+}
+
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    /// Number of unused bits in the last byte
+    free: u8,
+}
+
+impl BitWriter {
+    fn write_bit(&mut self, bit: bool) {
+        if self.free == 0 {
+            self.bytes.push(0);
+            self.free = 8;
+        }
+        self.free -= 1;
+        if bit {
+            if let Some(last) = self.bytes.last_mut() {
+                *last |= 1 << self.free;
+            }
+        }
+    }
+
+    fn write_bits(&mut self, value: u64, nbits: u8) {
+        for i in (0..nbits).rev() {
+            self.write_bit((value >> i) & 1 == 1);
+        }
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.write_bits(*b as u64, 8);
+        }
+    }
+}
+
+/// Encoder of the XOR chunk of Prometheus, the layout is
+/// `num_samples as big-endian u16 | bit stream`.
+struct XorEncoder {
+    stream: BitWriter,
+    num_samples: u16,
+    timestamp: i64,
+    timestamp_delta: i64,
+    value: u64,
+    leading: u8,
+    trailing: u8,
+}
+
+impl XorEncoder {
+    fn new() -> Self {
+        Self {
+            stream: BitWriter::default(),
+            num_samples: 0,
+            timestamp: 0,
+            timestamp_delta: 0,
+            value: 0,
+            leading: u8::MAX,
+            trailing: 0,
+        }
+    }
+
+    fn append(&mut self, timestamp: i64, value: f64) {
+        let mut buf = Vec::with_capacity(10);
+        match self.num_samples {
+            0 => {
+                put_varint(&mut buf, timestamp);
+                self.stream.write_bytes(&buf);
+                self.stream.write_bits(value.to_bits(), 64);
+            }
+            1 => {
+                self.timestamp_delta = timestamp.wrapping_sub(self.timestamp);
+                put_uvarint(&mut buf, self.timestamp_delta as u64);
+                self.stream.write_bytes(&buf);
+                self.write_value(value);
+            }
+            _ => {
+                let delta = timestamp.wrapping_sub(self.timestamp);
+                let dod = delta.wrapping_sub(self.timestamp_delta);
+                self.timestamp_delta = delta;
+                match dod {
+                    0 => self.stream.write_bit(false),
+                    _ if bit_range(dod, 14) => {
+                        self.stream.write_bits(0b10, 2);
+                        self.stream.write_bits(dod as u64, 14);
+                    }
+                    _ if bit_range(dod, 17) => {
+                        self.stream.write_bits(0b110, 3);
+                        self.stream.write_bits(dod as u64, 17);
+                    }
+                    _ if bit_range(dod, 20) => {
+                        self.stream.write_bits(0b1110, 4);
+                        self.stream.write_bits(dod as u64, 20);
+                    }
+                    _ => {
+                        self.stream.write_bits(0b1111, 4);
+                        self.stream.write_bits(dod as u64, 64);
+                    }
+                }
+                self.write_value(value);
+            }
+        }
+        self.timestamp = timestamp;
+        self.value = value.to_bits();
+        self.num_samples += 1;
+    }
+
+    fn write_value(&mut self, value: f64) {
+        let delta = value.to_bits() ^ self.value;
+        if delta == 0 {
+            self.stream.write_bit(false);
+            return;
+        }
+        self.stream.write_bit(true);
+
+        // Clamp number of leading zeros to avoid overflow when encoding.
+        let leading = (delta.leading_zeros() as u8).min(31);
+        let trailing = delta.trailing_zeros() as u8;
+        if self.leading != u8::MAX && leading >= self.leading && trailing >= self.trailing {
+            // Meaningful bits fall into the window of the previous value.
+            self.stream.write_bit(false);
+            self.stream
+                .write_bits(delta >> self.trailing, 64 - self.leading - self.trailing);
+            return;
+        }
+
+        self.leading = leading;
+        self.trailing = trailing;
+        let significant = 64 - leading - trailing;
+        self.stream.write_bit(true);
+        self.stream.write_bits(leading as u64, 5);
+        // 64 significant bits are written as 0, which never occurs otherwise.
+        self.stream.write_bits(significant as u64, 6);
+        self.stream.write_bits(delta >> trailing, significant);
+    }
+
+    fn finish(self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(self.stream.bytes.len() + 2);
+        data.extend_from_slice(&self.num_samples.to_be_bytes());
+        data.extend_from_slice(&self.stream.bytes);
+        data
+    }
+}
+
+fn bit_range(value: i64, nbits: u8) -> bool {
+    -((1 << (nbits - 1)) - 1) <= value && value <= 1 << (nbits - 1)
+}
+
+#[cfg(test)]
+mod test {
+    use protos::prompb::prometheus::chunk::Encoding;
+    use protos::prompb::prometheus::Sample;
+
+    use super::{crc32c, encode_xor_chunks, MAX_SAMPLES_PER_CHUNK};
+
+    struct BitReader<'a> {
+        bytes: &'a [u8],
+        pos: usize,
+    }
+
+    impl BitReader<'_> {
+        fn read_bit(&mut self) -> bool {
+            let bit = (self.bytes[self.pos / 8] >> (7 - self.pos % 8)) & 1 == 1;
+            self.pos += 1;
+            bit
+        }
+
+        fn read_bits(&mut self, nbits: u8) -> u64 {
+            (0..nbits).fold(0, |acc, _| (acc << 1) | self.read_bit() as u64)
+        }
+
+        fn read_uvarint(&mut self) -> u64 {
+            let mut value = 0;
+            for shift in (0..).step_by(7) {
+                let b = self.read_bits(8);
+                value |= (b & 0x7f) << shift;
+                if b < 0x80 {
+                    break;
+                }
+            }
+            value
+        }
+
+        fn read_signed(&mut self, nbits: u8) -> i64 {
+            let value = self.read_bits(nbits) as i64;
+            if nbits < 64 && value > 1 << (nbits - 1) {
+                value - (1 << nbits)
+            } else {
+                value
+            }
+        }
+    }
+
+    fn decode(data: &[u8]) -> Vec<(i64, f64)> {
+        let num = u16::from_be_bytes([data[0], data[1]]);
+        let mut reader = BitReader {
+            bytes: &data[2..],
+            pos: 0,
+        };
+        let mut result = vec![];
+        let (mut t, mut delta, mut v) = (0_i64, 0_i64, 0_u64);
+        let (mut leading, mut trailing) = (0_u8, 0_u8);
+        for i in 0..num {
+            match i {
+                0 => {
+                    let ux = reader.read_uvarint();
+                    t = (ux >> 1) as i64 ^ -((ux & 1) as i64);
+                    v = reader.read_bits(64);
+                    result.push((t, f64::from_bits(v)));
+                    continue;
+                }
+                1 => {
+                    delta = reader.read_uvarint() as i64;
+                }
+                _ => {
+                    let dod = if !reader.read_bit() {
+                        0
+                    } else if !reader.read_bit() {
+                        reader.read_signed(14)
+                    } else if !reader.read_bit() {
+                        reader.read_signed(17)
+                    } else if !reader.read_bit() {
+                        reader.read_signed(20)
+                    } else {
+                        reader.read_signed(64)
+                    };
+                    delta += dod;
+                }
+            }
+            t += delta;
+            if reader.read_bit() {
+                if reader.read_bit() {
+                    leading = reader.read_bits(5) as u8;
+                    let mut significant = reader.read_bits(6) as u8;
+                    if significant == 0 {
+                        significant = 64;
+                    }
+                    trailing = 64 - leading - significant;
+                }
+                let significant = 64 - leading - trailing;
+                v ^= reader.read_bits(significant) << trailing;
+            }
+            result.push((t, f64::from_bits(v)));
+        }
+        result
+    }
+
+    #[test]
+    fn test_crc32c() {
+        assert_eq!(crc32c(b"123456789"), 0xe306_9283);
+        assert_eq!(crc32c(b""), 0);
+    }
+
+    #[test]
+    fn test_xor_chunks_roundtrip() {
+        let mut timestamp = 1_673_069_176_267_i64;
+        let samples = (0..300)
+            .map(|i| {
+                // Irregular intervals and values to cover all the encoding buckets
+                timestamp += match i % 7 {
+                    0 => 15_000,
+                    1 => 15_001,
+                    2 => 30_000,
+                    3 => 100_000,
+                    4 => 1_000_000,
+                    5 => 10_000_000,
+                    _ => 15_000,
+                };
+                Sample {
+                    value: match i % 5 {
+                        0 => 1.0,
+                        1 => i as f64 * 0.1,
+                        2 => -(i as f64),
+                        3 => f64::MAX,
+                        _ => 1.0,
+                    },
+                    timestamp,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let chunks = encode_xor_chunks(&samples);
+        assert_eq!(chunks.len(), 3);
+
+        let mut decoded = vec![];
+        for (chunk, expected) in chunks.iter().zip(samples.chunks(MAX_SAMPLES_PER_CHUNK)) {
+            assert_eq!(chunk.r#type, Encoding::Xor as i32);
+            assert_eq!(chunk.min_time_ms, expected[0].timestamp);
+            assert_eq!(chunk.max_time_ms, expected[expected.len() - 1].timestamp);
+            decoded.extend(decode(&chunk.data));
+        }
+
+        let expected = samples
+            .iter()
+            .map(|s| (s.timestamp, s.value))
+            .collect::<Vec<_>>();
+        assert_eq!(decoded, expected);
+    }
+}
@@ -29,103 +29,14 @@ pub struct Writer<'a> {
 impl Writer<'_> {
     /// Convert a record to a metric
     fn apply(&mut self, batch: &[ArrayRef], row_index: usize) -> QueryResult<()> {
-        let mut labels = Vec::with_capacity(self.tag_name_indices.len());
-        for (tag_idx, tag_name) in self.tag_name_indices.iter().zip(&self.tag_names) {
-            let col = &batch[*tag_idx];
-            let tag_value = match col.data_type() {
-                DataType::Utf8 => col
-                    .as_any()
-                    .downcast_ref::<StringArray>()
-                    .expect("Invalid data, this maybe DataFusion's bug.")
-                    .value(row_index)
-                    .to_owned(),
-                _ => {
-                    return Err(CommonSnafu {
-                        msg: "Tag noly support string type".to_string(),
-                    }
-                    .build());
-                }
-            };
-            labels.push(Label {
-                name: tag_name.to_owned(),
-                value: tag_value,
-            });
-        }
-
-        let col = &batch[self.sample_value_idx];
-        let sample_value = unsafe {
-            match col.data_type() {
-                DataType::Float64 => col
-                    .as_any()
-                    .downcast_ref::<PrimitiveArray<Float64Type>>()
-                    .unwrap_unchecked()
-                    .value(row_index),
-                DataType::Float32 => f64::from(
-                    col.as_any()
-                        .downcast_ref::<PrimitiveArray<Float32Type>>()
-                        .unwrap_unchecked()
-                        .value(row_index),
-                ),
-                DataType::Float16 => f64::from(
-                    col.as_any()
-                        .downcast_ref::<PrimitiveArray<Float16Type>>()
-                        .unwrap_unchecked()
-                        .value(row_index),
-                ),
-                _ => {
-                    return Err(CommonSnafu {
-                        msg: "Prom sample value noly support float type".to_string(),
-                    }
-                    .build());
-                }
-            }
-        };
-
-        let col = &batch[self.sample_time_idx];
-        let sample_timestamp_ms = unsafe {
-            match col.data_type() {
-                DataType::Timestamp(time_unit, _) => match time_unit {
-                    TimeUnit::Second => {
-                        col.as_any()
-                            .downcast_ref::<PrimitiveArray<TimestampSecondType>>()
-                            .unwrap_unchecked()
-                            .value(row_index)
-                            * 1_000
-                    }
-                    TimeUnit::Millisecond => col
-                        .as_any()
-                        .downcast_ref::<PrimitiveArray<TimestampMillisecondType>>()
-                        .unwrap_unchecked()
-                        .value(row_index),
-                    TimeUnit::Microsecond => {
-                        col.as_any()
-                            .downcast_ref::<PrimitiveArray<TimestampMicrosecondType>>()
-                            .unwrap_unchecked()
-                            .value(row_index)
-                            / 1_000
-                    }
-                    TimeUnit::Nanosecond => {
-                        col.as_any()
-                            .downcast_ref::<PrimitiveArray<TimestampNanosecondType>>()
-                            .unwrap_unchecked()
-                            .value(row_index)
-                            / 1_000_000
-                    }
-                },
-                _ => {
-                    return Err(CommonSnafu {
-                        msg: "Prom sample value noly support TimestampMillisecondType".to_string(),
-                    }
-                    .build());
-                }
-            }
-        };
-
-        // construct sample
-        let sample = Sample {
-            value: sample_value,
-            timestamp: sample_timestamp_ms,
-        };
+        let (labels, sample) = read_row(
+            &self.tag_name_indices,
+            &self.tag_names,
+            self.sample_value_idx,
+            self.sample_time_idx,
+            batch,
+            row_index,
+        )?;
         // save Sample
         let labels_str = concat_labels(&labels);
         debug!(
@@ -157,6 +68,168 @@ impl Writer<'_> {
     }
 }
 
+/// Writer of records sorted by tags, a series is completed as soon as the tags change,
+/// so only the series being written is kept in memory.
+#[derive(Debug)]
+pub struct SortedWriter {
+    tag_name_indices: Vec<usize>,
+    // The column name of the tag_name_indices index
+    tag_names: Vec<String>,
+    sample_value_idx: usize,
+    sample_time_idx: usize,
+    schema: SchemaRef,
+
+    current: Option<TimeSeries>,
+}
+
+impl SortedWriter {
+    /// Write recordbatch, return the series completed by it
+    pub fn write(&mut self, batch: &RecordBatch) -> QueryResult<Vec<TimeSeries>> {
+        debug_assert_eq!(self.schema.fields(), batch.schema().fields());
+
+        let columns = batch.columns();
+        let mut completed = vec![];
+
+        for row_index in 0..batch.num_rows() {
+            let (labels, sample) = read_row(
+                &self.tag_name_indices,
+                &self.tag_names,
+                self.sample_value_idx,
+                self.sample_time_idx,
+                columns,
+                row_index,
+            )?;
+            match self.current.as_mut() {
+                Some(series) if series.labels == labels => series.samples.push(sample),
+                _ => {
+                    let series = TimeSeries {
+                        labels,
+                        samples: vec![sample],
+                        ..Default::default()
+                    };
+                    completed.extend(self.current.replace(series));
+                }
+            }
+        }
+
+        Ok(completed)
+    }
+
+    /// Return the last series
+    pub fn finish(self) -> Option<TimeSeries> {
+        self.current
+    }
+}
+
+/// Convert a record to the labels and sample of a metric
+fn read_row(
+    tag_name_indices: &[usize],
+    tag_names: &[String],
+    sample_value_idx: usize,
+    sample_time_idx: usize,
+    batch: &[ArrayRef],
+    row_index: usize,
+) -> QueryResult<(Vec<Label>, Sample)> {
+    let mut labels = Vec::with_capacity(tag_name_indices.len());
+    for (tag_idx, tag_name) in tag_name_indices.iter().zip(tag_names) {
+        let col = &batch[*tag_idx];
+        let tag_value = match col.data_type() {
+            DataType::Utf8 => col
+                .as_any()
+                .downcast_ref::<StringArray>()
+                .expect("Invalid data, this maybe DataFusion's bug.")
+                .value(row_index)
+                .to_owned(),
+            _ => {
+                return Err(CommonSnafu {
+                    msg: "Tag noly support string type".to_string(),
+                }
+                .build());
+            }
+        };
+        labels.push(Label {
+            name: tag_name.to_owned(),
+            value: tag_value,
+        });
+    }
+
+    let col = &batch[sample_value_idx];
+    let sample_value = unsafe {
+        match col.data_type() {
+            DataType::Float64 => col
+                .as_any()
+                .downcast_ref::<PrimitiveArray<Float64Type>>()
+                .unwrap_unchecked()
+                .value(row_index),
+            DataType::Float32 => f64::from(
+                col.as_any()
+                    .downcast_ref::<PrimitiveArray<Float32Type>>()
+                    .unwrap_unchecked()
+                    .value(row_index),
+            ),
+            DataType::Float16 => f64::from(
+                col.as_any()
+                    .downcast_ref::<PrimitiveArray<Float16Type>>()
+                    .unwrap_unchecked()
+                    .value(row_index),
+            ),
+            _ => {
+                return Err(CommonSnafu {
+                    msg: "Prom sample value noly support float type".to_string(),
+                }
+                .build());
+            }
+        }
+    };
+
+    let col = &batch[sample_time_idx];
+    let sample_timestamp_ms = unsafe {
+        match col.data_type() {
+            DataType::Timestamp(time_unit, _) => match time_unit {
+                TimeUnit::Second => {
+                    col.as_any()
+                        .downcast_ref::<PrimitiveArray<TimestampSecondType>>()
+                        .unwrap_unchecked()
+                        .value(row_index)
+                        * 1_000
+                }
+                TimeUnit::Millisecond => col
+                    .as_any()
+                    .downcast_ref::<PrimitiveArray<TimestampMillisecondType>>()
+                    .unwrap_unchecked()
+                    .value(row_index),
+                TimeUnit::Microsecond => {
+                    col.as_any()
+                        .downcast_ref::<PrimitiveArray<TimestampMicrosecondType>>()
+                        .unwrap_unchecked()
+                        .value(row_index)
+                        / 1_000
+                }
+                TimeUnit::Nanosecond => {
+                    col.as_any()
+                        .downcast_ref::<PrimitiveArray<TimestampNanosecondType>>()
+                        .unwrap_unchecked()
+                        .value(row_index)
+                        / 1_000_000
+                }
+            },
+            _ => {
+                return Err(CommonSnafu {
+                    msg: "Prom sample value noly support TimestampMillisecondType".to_string(),
+                }
+                .build());
+            }
+        }
+    };
+
+    // construct sample
+    let sample = Sample {
+        value: sample_value,
+        timestamp: sample_timestamp_ms,
+    };
+    Ok((labels, sample))
+}
+
 /// A CSV writer builder
 #[derive(Debug)]
 pub struct WriterBuilder {
@@ -227,6 +300,18 @@ impl WriterBuilder {
             labels_to_series,
         }
     }
+
+    /// Create a new `SortedWriter`
+    pub fn build_sorted(self) -> SortedWriter {
+        SortedWriter {
+            tag_name_indices: self.tag_name_indices,
+            tag_names: self.tag_names,
+            sample_value_idx: self.sample_value_idx,
+            sample_time_idx: self.sample_time_idx,
+            schema: self.schema,
+            current: None,
+        }
+    }
 }
 
 fn concat_labels(labels: &[Label]) -> String {
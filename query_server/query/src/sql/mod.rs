@@ -5,3 +5,6 @@ pub mod optimizer;
 pub mod parser;
 pub mod physical;
 pub mod planner;
+pub mod rollup;
+pub mod sql_function;
+pub mod substrait;
@@ -18,13 +18,14 @@ use serde_json::Value as JsonValue;
 use snafu::ResultExt;
 use spi::query::ast::{
     self, parse_string_value, Action, AlterDatabase, AlterTable, AlterTableAction, AlterTenant,
-    AlterTenantOperation, AlterUser, AlterUserOperation, ChecksumGroup, ColumnOption,
-    CompactDatabase, CompactVnode, CopyIntoLocation, CopyIntoTable, CopyTarget, CopyVnode,
-    CreateDatabase, CreateRole, CreateStream, CreateTable, CreateTenant, CreateUser,
-    DatabaseConfig, DatabaseOptions, DescribeDatabase, DescribeTable, DropDatabaseObject,
-    DropGlobalObject, DropTenantObject, DropVnode, Explain, ExtStatement, GrantRevoke, MoveVnode,
-    OutputMode, Privilege, RecoverDatabase, RecoverTenant, ShowSeries, ShowTagBody, ShowTagValues,
-    Trigger, UriLocation, With,
+    AlterTenantOperation, AlterUser, AlterUserOperation, AnalyzeTable, BackupDatabase,
+    ChecksumGroup, ColumnOption, CompactDatabase, CompactVnode, CopyIntoLocation, CopyIntoTable,
+    CopyTarget, CopyVnode, CreateContinuousAggregate, CreateDatabase, CreateFunction, CreatePolicy,
+    CreateRole, CreateStream, CreateTable, CreateTenant, CreateUser, DatabaseConfig,
+    DatabaseOptions, DescribeDatabase, DescribeTable, DropDatabaseObject, DropGlobalObject,
+    DropTenantObject, DropVnode, Explain, ExtStatement, GrantRevoke, MoveVnode, OutputMode,
+    Privilege, PrivilegeObject, RecoverDatabase, RecoverTenant, RestoreDatabase, ShowCardinality,
+    ShowSeries, ShowTagBody, ShowTagValues, Trigger, UriLocation, With,
 };
 use spi::query::logical_planner::{DatabaseObjectType, GlobalObjectType, TenantObjectType};
 use spi::query::parser::Parser as CnosdbParser;
@@ -60,6 +61,8 @@ enum CnosKeyWord {
     REPLICA,
     #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
     PRECISION,
+    #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+    MAX_SERIES,
 
     #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
     QUERIES,
@@ -74,6 +77,8 @@ enum CnosKeyWord {
     REMOVE,
     #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
     SERIES,
+    #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+    CARDINALITY,
 
     #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
     FILES,
@@ -101,6 +106,18 @@ enum CnosKeyWord {
     #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
     TRIGGER,
     #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+    CONTINUOUS,
+    #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+    AGGREGATE,
+    #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+    ROLLUP,
+    #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+    RETURNS,
+    #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+    LANGUAGE,
+    #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+    POLICY,
+    #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
     WATERMARK,
     #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
     OUTPUT_MODE,
@@ -126,6 +143,10 @@ enum CnosKeyWord {
     DESTORY,
     #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
     REPLICAS,
+    #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+    BACKUP,
+    #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+    RESTORE,
 
     #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
     MAX_MEMCACHE_SIZE,
@@ -154,6 +175,7 @@ impl FromStr for CnosKeyWord {
             "VNODE_DURATION" => Ok(CnosKeyWord::VNODE_DURATION),
             "REPLICA" => Ok(CnosKeyWord::REPLICA),
             "PRECISION" => Ok(CnosKeyWord::PRECISION),
+            "MAX_SERIES" => Ok(CnosKeyWord::MAX_SERIES),
             "DATABASES" => Ok(CnosKeyWord::DATABASES),
             "QUERIES" => Ok(CnosKeyWord::QUERIES),
             "TENANT" => Ok(CnosKeyWord::TENANT),
@@ -162,6 +184,7 @@ impl FromStr for CnosKeyWord {
             "WRITE" => Ok(CnosKeyWord::WRITE),
             "REMOVE" => Ok(CnosKeyWord::REMOVE),
             "SERIES" => Ok(CnosKeyWord::SERIES),
+            "CARDINALITY" => Ok(CnosKeyWord::CARDINALITY),
             "FILES" => Ok(CnosKeyWord::FILES),
             "PATTERN" => Ok(CnosKeyWord::PATTERN),
             "FILE_FORMAT" => Ok(CnosKeyWord::FILE_FORMAT),
@@ -174,6 +197,12 @@ impl FromStr for CnosKeyWord {
             "STREAM" => Ok(CnosKeyWord::STREAM),
             "STREAMS" => Ok(CnosKeyWord::STREAMS),
             "TRIGGER" => Ok(CnosKeyWord::TRIGGER),
+            "CONTINUOUS" => Ok(CnosKeyWord::CONTINUOUS),
+            "AGGREGATE" => Ok(CnosKeyWord::AGGREGATE),
+            "ROLLUP" => Ok(CnosKeyWord::ROLLUP),
+            "RETURNS" => Ok(CnosKeyWord::RETURNS),
+            "LANGUAGE" => Ok(CnosKeyWord::LANGUAGE),
+            "POLICY" => Ok(CnosKeyWord::POLICY),
             "WATERMARK" => Ok(CnosKeyWord::WATERMARK),
             "OUTPUT_MODE" => Ok(CnosKeyWord::OUTPUT_MODE),
             "ONCE" => Ok(CnosKeyWord::ONCE),
@@ -187,6 +216,8 @@ impl FromStr for CnosKeyWord {
             "PROMOTE" => Ok(CnosKeyWord::PROMOTE),
             "DESTORY" => Ok(CnosKeyWord::DESTORY),
             "REPLICAS" => Ok(CnosKeyWord::REPLICAS),
+            "BACKUP" => Ok(CnosKeyWord::BACKUP),
+            "RESTORE" => Ok(CnosKeyWord::RESTORE),
             "MAX_MEMCACHE_SIZE" => Ok(CnosKeyWord::MAX_MEMCACHE_SIZE),
             "MEMCACHE_PARTITIONS" => Ok(CnosKeyWord::MEMCACHE_PARTITIONS),
             "WAL_MAX_FILE_SIZE" => Ok(CnosKeyWord::WAL_MAX_FILE_SIZE),
@@ -316,6 +347,10 @@ impl<'a> ExtParser<'a> {
                     self.parser.next_token();
                     self.parse_explain()
                 }
+                Keyword::ANALYZE => {
+                    self.parser.next_token();
+                    self.parse_analyze()
+                }
                 Keyword::UPDATE => {
                     self.parser.next_token();
                     let update_ast = self.parser.parse_update()?;
@@ -344,6 +379,14 @@ impl<'a> ExtParser<'a> {
                                 self.parser.next_token();
                                 self.parse_replica()
                             }
+                            CnosKeyWord::BACKUP => {
+                                self.parser.next_token();
+                                self.parse_backup()
+                            }
+                            CnosKeyWord::RESTORE => {
+                                self.parser.next_token();
+                                self.parse_restore()
+                            }
                             _ => Ok(ExtStatement::SqlStatement(Box::new(
                                 self.parser.parse_statement()?,
                             ))),
@@ -376,6 +419,8 @@ impl<'a> ExtParser<'a> {
             self.parse_show_databases()
         } else if self.parse_cnos_keyword(CnosKeyWord::SERIES) {
             self.parse_show_series()
+        } else if self.parse_cnos_keyword(CnosKeyWord::CARDINALITY) {
+            self.parse_show_cardinality()
         } else if self.parse_cnos_keyword(CnosKeyWord::TAG) {
             if self.parser.parse_keyword(Keyword::VALUES) {
                 self.parse_show_tag_values()
@@ -547,6 +592,27 @@ impl<'a> ExtParser<'a> {
         Ok(ExtStatement::ShowTables(self.parse_on_database()?))
     }
 
+    /// Parse `SHOW CARDINALITY [ON database] [FROM table] [LIMIT n]`
+    fn parse_show_cardinality(&mut self) -> Result<ExtStatement> {
+        let database_name = self.parse_on_database()?;
+        let table = if self.parser.parse_keyword(Keyword::FROM) {
+            Some(self.parser.parse_identifier()?)
+        } else {
+            None
+        };
+        let limit = if self.parser.parse_keyword(Keyword::LIMIT) {
+            self.parser.parse_limit()?
+        } else {
+            None
+        };
+
+        Ok(ExtStatement::ShowCardinality(ShowCardinality {
+            database_name,
+            table,
+            limit,
+        }))
+    }
+
     fn parse_show_replicas(&mut self) -> Result<ExtStatement> {
         Ok(ExtStatement::ShowReplicas)
     }
@@ -595,6 +661,13 @@ impl<'a> ExtParser<'a> {
         }
     }
 
+    /// Parse: ANALYZE TABLE table_name
+    fn parse_analyze(&mut self) -> Result<ExtStatement> {
+        self.parser.expect_keyword(Keyword::TABLE)?;
+        let table_name = self.parser.parse_object_name()?;
+        Ok(ExtStatement::AnalyzeTable(AnalyzeTable { table_name }))
+    }
+
     fn parse_alter_table(&mut self) -> Result<ExtStatement> {
         let table_name = self.parser.parse_object_name()?;
 
@@ -607,11 +680,36 @@ impl<'a> ExtParser<'a> {
         } else if self.parser.parse_keyword(Keyword::RENAME) {
             let alter_tbl = self.parse_alter_table_rename(table_name)?;
             Ok(ExtStatement::AlterTable(alter_tbl))
+        } else if self.parser.parse_keyword(Keyword::SET) {
+            self.parse_alter_table_set(table_name)
         } else {
-            self.expected("ADD or ALTER or DROP or RENAME", self.parser.peek_token())
+            self.expected(
+                "ADD or ALTER or DROP or RENAME or SET",
+                self.parser.peek_token(),
+            )
         }
     }
 
+    fn parse_alter_table_set(&mut self, table_name: ObjectName) -> Result<ExtStatement> {
+        let alter_action = if self.parse_cnos_keyword(CnosKeyWord::TTL) {
+            // parse: SET TTL [=] 'duration'
+            let _ = self.parser.consume_token(&Token::Eq);
+            let ttl = self.parse_string_value()?;
+            AlterTableAction::SetTtl { ttl }
+        } else if self.parse_cnos_keyword(CnosKeyWord::MAX_SERIES) {
+            // parse: SET MAX_SERIES [=] number
+            let _ = self.parser.consume_token(&Token::Eq);
+            let max_series = self.parse_number::<u64>()?;
+            AlterTableAction::SetMaxSeries { max_series }
+        } else {
+            return self.expected("TTL or MAX_SERIES", self.parser.peek_token());
+        };
+        Ok(ExtStatement::AlterTable(AlterTable {
+            table_name,
+            alter_action,
+        }))
+    }
+
     fn parse_alter_table_add_column(&mut self, table_name: ObjectName) -> Result<ExtStatement> {
         if self.parse_cnos_keyword(CnosKeyWord::FIELD) {
             let column = self.parse_cnos_field()?;
@@ -684,7 +782,7 @@ impl<'a> ExtParser<'a> {
             ));
         }
         if config.has_some() {
-            return parser_err!("database config is unmodifiable, only can modify database option: TTL, SHARD, VNODE_DURATION, REPLICA".to_string());
+            return parser_err!("database config is unmodifiable, only can modify database option: TTL, SHARD, VNODE_DURATION, REPLICA, MAX_SERIES".to_string());
         }
         Ok(ExtStatement::AlterDatabase(
             AlterDatabase {
@@ -1093,6 +1191,9 @@ impl<'a> ExtParser<'a> {
                 return parser_err!("replica number should be greater than 0");
             }
             options.replica = Some(replica);
+        } else if self.parse_cnos_keyword(CnosKeyWord::MAX_SERIES) {
+            let _ = self.parser.expect_token(&Token::Eq);
+            options.max_series = Some(self.parse_number::<u64>()?);
         } else if self.parse_cnos_keyword(CnosKeyWord::PRECISION) {
             let _ = self.parser.expect_token(&Token::Eq);
             config.precision = Some(self.parse_string_value()?);
@@ -1224,16 +1325,37 @@ impl<'a> ExtParser<'a> {
 
     fn parse_privilege(&mut self) -> Result<Privilege, ParserError> {
         let action = self.parse_grant_permission()?;
+        let columns = if self.parser.consume_token(&Token::LParen) {
+            let columns = self
+                .parser
+                .parse_comma_separated(|parser| parser.parse_identifier())?;
+            self.parser.expect_token(&Token::RParen)?;
+            Some(columns)
+        } else {
+            None
+        };
+
         self.parser.expect_keyword(Keyword::ON)?;
-        self.parser.expect_keyword(Keyword::DATABASE)?;
-        let database = self.parser.parse_identifier()?;
-        Ok(Privilege { action, database })
+        let object = if self.parser.parse_keyword(Keyword::TABLE) {
+            let table = self.parser.parse_object_name()?;
+            PrivilegeObject::Table(table, columns)
+        } else {
+            self.parser.expect_keyword(Keyword::DATABASE)?;
+            if columns.is_some() {
+                return parser_err!("column list is only supported on TABLE");
+            }
+            PrivilegeObject::Database(self.parser.parse_identifier()?)
+        };
+
+        Ok(Privilege { action, object })
     }
 
     fn parse_grant(&mut self) -> Result<ExtStatement> {
         // grant read on database "db1" to [role] rrr;
         // grant write on database "db2" to rrr;
         // grant all on database "db3" to rrr;
+        // grant read on table "db1"."t1" to rrr;
+        // grant read (c1, c2) on table t1 to rrr;
         let privileges = self.parse_comma_separated(ExtParser::parse_privilege)?;
 
         self.parser.expect_keyword(Keyword::TO)?;
@@ -1252,6 +1374,7 @@ impl<'a> ExtParser<'a> {
         // revoke read on database "db1" from [role] rrr;
         // revoke write on database "db2" from rrr;
         // revoke all on database "db3" from rrr;
+        // revoke read (c1, c2) on table t1 from rrr;
         let privileges = self.parse_comma_separated(ExtParser::parse_privilege)?;
 
         self.parser.expect_keyword(Keyword::FROM)?;
@@ -1656,10 +1779,129 @@ impl<'a> ExtParser<'a> {
         }))
     }
 
+    /// e.g.
+    /// CREATE CONTINUOUS AGGREGATE IF NOT EXISTS cpu_1h
+    /// WITH (watermark_delay = '1m')
+    /// AS SELECT date_bin(INTERVAL '1 hour', time) AS time, host, avg(usage) AS usage
+    /// FROM cpu
+    /// GROUP BY date_bin(INTERVAL '1 hour', time), host;
+    fn parse_create_continuous_aggregate(&mut self) -> Result<ExtStatement> {
+        let if_not_exists =
+            self.parser
+                .parse_keywords(&[Keyword::IF, Keyword::NOT, Keyword::EXISTS]);
+
+        let name = self.parser.parse_object_name()?;
+        let with_options = self.parser.parse_options(Keyword::WITH)?;
+
+        self.parser.expect_keyword(Keyword::AS)?;
+        let query = Box::new(self.parser.parse_query()?);
+
+        Ok(ExtStatement::CreateContinuousAggregate(
+            CreateContinuousAggregate {
+                if_not_exists,
+                name,
+                with_options,
+                query,
+            },
+        ))
+    }
+
+    /// e.g.
+    /// CREATE FUNCTION f_to_c(f DOUBLE) RETURNS DOUBLE LANGUAGE SQL AS '(f - 32) / 1.8';
+    /// CREATE AGGREGATE FUNCTION wasm_sum(x BIGINT) RETURNS BIGINT LANGUAGE WASM AS 'AGFzbQEAAAA...'
+    /// WITH (fuel = 1000000, max_memory = 65536);
+    fn parse_create_function(
+        &mut self,
+        or_replace: bool,
+        is_aggregate: bool,
+    ) -> Result<ExtStatement> {
+        let if_not_exists =
+            self.parser
+                .parse_keywords(&[Keyword::IF, Keyword::NOT, Keyword::EXISTS]);
+
+        let name = self.parser.parse_identifier()?;
+        check_name_not_contain_illegal_character(&ObjectName(vec![name.clone()]))?;
+
+        self.parser.expect_token(&Token::LParen)?;
+        let args = if self.parser.consume_token(&Token::RParen) {
+            vec![]
+        } else {
+            let args = self.parser.parse_comma_separated(|parser| {
+                Ok((parser.parse_identifier()?, parser.parse_data_type()?))
+            })?;
+            self.parser.expect_token(&Token::RParen)?;
+            args
+        };
+
+        self.expect_cnos_keyword(CnosKeyWord::RETURNS)?;
+        let return_type = self.parser.parse_data_type()?;
+        self.expect_cnos_keyword(CnosKeyWord::LANGUAGE)?;
+        let language = self.parser.parse_identifier()?;
+        self.parser.expect_keyword(Keyword::AS)?;
+        let body = self.parse_string_value()?;
+        let with_options = self.parser.parse_options(Keyword::WITH)?;
+
+        Ok(ExtStatement::CreateFunction(CreateFunction {
+            or_replace,
+            if_not_exists,
+            is_aggregate,
+            name,
+            args,
+            return_type,
+            language,
+            body,
+            with_options,
+        }))
+    }
+
+    /// Parse a row-level security policy
+    ///
+    /// CREATE POLICY p ON air TO r1, r2 USING (station = current_user());
+    fn parse_create_policy(&mut self, or_replace: bool) -> Result<ExtStatement> {
+        let if_not_exists =
+            self.parser
+                .parse_keywords(&[Keyword::IF, Keyword::NOT, Keyword::EXISTS]);
+
+        let name = self.parser.parse_identifier()?;
+        check_name_not_contain_illegal_character(&ObjectName(vec![name.clone()]))?;
+
+        self.parser.expect_keyword(Keyword::ON)?;
+        let table = self.parser.parse_object_name()?;
+
+        let roles = if self.parser.parse_keyword(Keyword::TO) {
+            let _ = self.parser.parse_keyword(Keyword::ROLE);
+            self.parser
+                .parse_comma_separated(|parser| parser.parse_identifier())?
+        } else {
+            vec![]
+        };
+
+        self.parser.expect_keyword(Keyword::USING)?;
+        self.parser.expect_token(&Token::LParen)?;
+        let using = self.parser.parse_expr()?;
+        self.parser.expect_token(&Token::RParen)?;
+
+        Ok(ExtStatement::CreatePolicy(CreatePolicy {
+            or_replace,
+            if_not_exists,
+            name,
+            table,
+            roles,
+            using,
+        }))
+    }
+
     /// Parse a SQL CREATE statement
     fn parse_create(&mut self) -> Result<ExtStatement> {
         // Currently only supports the creation of external tables
-        if self.parser.parse_keyword(Keyword::EXTERNAL) {
+        if self.parser.parse_keywords(&[Keyword::OR, Keyword::REPLACE]) {
+            if self.parse_cnos_keyword(CnosKeyWord::POLICY) {
+                return self.parse_create_policy(true);
+            }
+            let is_aggregate = self.parse_cnos_keyword(CnosKeyWord::AGGREGATE);
+            self.parser.expect_keyword(Keyword::FUNCTION)?;
+            self.parse_create_function(true, is_aggregate)
+        } else if self.parser.parse_keyword(Keyword::EXTERNAL) {
             self.parse_create_external_table(false)
         } else if self.parser.parse_keyword(Keyword::UNBOUNDED) {
             self.parser.expect_keyword(Keyword::EXTERNAL)?;
@@ -1676,6 +1918,18 @@ impl<'a> ExtParser<'a> {
             self.parse_create_role()
         } else if self.parse_cnos_keyword(CnosKeyWord::STREAM) {
             self.parse_create_stream()
+        } else if self.parse_cnos_keyword(CnosKeyWord::CONTINUOUS) {
+            self.expect_cnos_keyword(CnosKeyWord::AGGREGATE)?;
+            self.parse_create_continuous_aggregate()
+        } else if self.parse_cnos_keyword(CnosKeyWord::ROLLUP) {
+            self.parse_create_continuous_aggregate()
+        } else if self.parser.parse_keyword(Keyword::FUNCTION) {
+            self.parse_create_function(false, false)
+        } else if self.parse_cnos_keyword(CnosKeyWord::AGGREGATE) {
+            self.parser.expect_keyword(Keyword::FUNCTION)?;
+            self.parse_create_function(false, true)
+        } else if self.parse_cnos_keyword(CnosKeyWord::POLICY) {
+            self.parse_create_policy(false)
         } else {
             self.expected("an object type after CREATE", self.parser.peek_token())
         }
@@ -1878,6 +2132,60 @@ impl<'a> ExtParser<'a> {
         Ok(ast)
     }
 
+    /// Parse a backup statement
+    /// BACKUP DATABASE <db> TO '<url>' [CONNECTION = (...)]
+    fn parse_backup(&mut self) -> Result<ExtStatement> {
+        if !self.parser.parse_keyword(Keyword::DATABASE) {
+            return self.expected("DATABASE after BACKUP", self.parser.peek_token());
+        }
+        let database_name = self.parser.parse_identifier()?;
+        self.parser.expect_keyword(Keyword::TO)?;
+        let path = self.parser.parse_literal_string()?;
+        let connection_options = if self.parser.parse_keyword(Keyword::CONNECTION) {
+            self.parse_options()?
+        } else {
+            Default::default()
+        };
+
+        Ok(ExtStatement::BackupDatabase(BackupDatabase {
+            database_name,
+            location: UriLocation {
+                path,
+                connection_options,
+            },
+        }))
+    }
+
+    /// Parse a restore statement
+    /// RESTORE DATABASE <db> FROM '<url>' [CONNECTION = (...)] [AS OF '<timestamp>']
+    fn parse_restore(&mut self) -> Result<ExtStatement> {
+        if !self.parser.parse_keyword(Keyword::DATABASE) {
+            return self.expected("DATABASE after RESTORE", self.parser.peek_token());
+        }
+        let database_name = self.parser.parse_identifier()?;
+        self.parser.expect_keyword(Keyword::FROM)?;
+        let path = self.parser.parse_literal_string()?;
+        let connection_options = if self.parser.parse_keyword(Keyword::CONNECTION) {
+            self.parse_options()?
+        } else {
+            Default::default()
+        };
+        let as_of = if self.parser.parse_keywords(&[Keyword::AS, Keyword::OF]) {
+            Some(self.parser.parse_literal_string()?)
+        } else {
+            None
+        };
+
+        Ok(ExtStatement::RestoreDatabase(RestoreDatabase {
+            database_name,
+            location: UriLocation {
+                path,
+                connection_options,
+            },
+            as_of,
+        }))
+    }
+
     /// Parse a SQL DROP statement
     fn parse_drop(&mut self) -> Result<ExtStatement> {
         let ast = if self.parser.parse_keyword(Keyword::TABLE) {
@@ -1932,6 +2240,24 @@ impl<'a> ExtParser<'a> {
                 obj_type: TenantObjectType::Role,
                 after: None,
             })
+        } else if self.parser.parse_keyword(Keyword::FUNCTION) {
+            let if_exist = self.parser.parse_keywords(&[Keyword::IF, Keyword::EXISTS]);
+            let object_name = self.parser.parse_identifier()?;
+            ExtStatement::DropTenantObject(DropTenantObject {
+                object_name,
+                if_exist,
+                obj_type: TenantObjectType::Function,
+                after: None,
+            })
+        } else if self.parse_cnos_keyword(CnosKeyWord::POLICY) {
+            let if_exist = self.parser.parse_keywords(&[Keyword::IF, Keyword::EXISTS]);
+            let object_name = self.parser.parse_identifier()?;
+            ExtStatement::DropTenantObject(DropTenantObject {
+                object_name,
+                if_exist,
+                obj_type: TenantObjectType::Policy,
+                after: None,
+            })
         } else if self.parse_cnos_keyword(CnosKeyWord::VNODE) {
             let vnode_id = self.parse_number::<VnodeId>()?;
             ExtStatement::DropVnode(DropVnode { vnode_id })
@@ -1941,7 +2267,7 @@ impl<'a> ExtParser<'a> {
             ExtStatement::DropStream(ast::DropStream { if_exist, name })
         } else {
             return self.expected(
-                "TABLE,DATABASE,TENANT,USER,ROLE,VNODE,STREAM after DROP",
+                "TABLE,DATABASE,TENANT,USER,ROLE,FUNCTION,POLICY,VNODE,STREAM after DROP",
                 self.parser.peek_token(),
             );
         };
@@ -2147,13 +2473,22 @@ impl<'a> ExtParser<'a> {
     }
 
     fn parse_codec_encoding(&mut self) -> Result<Encoding, String> {
-        self.parser
-            .peek_token()
-            .to_string()
-            .parse()
-            .inspect(|_encoding| {
-                self.parser.next_token();
-            })
+        let name = self.parser.peek_token().to_string();
+        if let Ok(encoding) = name.parse() {
+            self.parser.next_token();
+            return Ok(encoding);
+        }
+
+        // parse lossy encodings with a tolerance: SDT(0.5)
+        self.parser.next_token();
+        if !self.parser.consume_token(&Token::LParen) {
+            return Err(name);
+        }
+        let tolerance = self.parser.parse_number_value().map_err(|_| name.clone())?;
+        if !self.parser.consume_token(&Token::RParen) {
+            return Err(name);
+        }
+        format!("{name}({tolerance})").parse()
     }
 
     fn parse_codec_type(&mut self) -> Result<Encoding> {
@@ -2535,6 +2870,42 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_create_table_lossy_codec() {
+        let sql =
+            "CREATE TABLE test(f1 DOUBLE CODEC(SDT(0.5)), f2 DOUBLE CODEC(deadband(2)), TAGS(t))";
+        let statements = ExtParser::parse_sql(sql).unwrap();
+        match &statements[0] {
+            ExtStatement::CreateTable(CreateTable { columns, .. }) => {
+                let encodings = columns
+                    .iter()
+                    .map(|c| c.encoding.map(|e| e.to_string()))
+                    .collect::<Vec<_>>();
+                assert_eq!(
+                    encodings,
+                    vec![
+                        None,
+                        Some("SDT(0.5)".to_string()),
+                        Some("DEADBAND(2)".to_string())
+                    ]
+                );
+            }
+            _ => panic!("failed"),
+        }
+
+        let sql = "ALTER TABLE m ALTER f SET CODEC(SDT(0.1))";
+        ExtParser::parse_sql(sql).unwrap();
+
+        for sql in [
+            "CREATE TABLE test(f1 DOUBLE CODEC(SDT), TAGS(t))",
+            "CREATE TABLE test(f1 DOUBLE CODEC(SDT(abc)), TAGS(t))",
+            "CREATE TABLE test(f1 DOUBLE CODEC(SDT(-1)), TAGS(t))",
+            "CREATE TABLE test(f1 DOUBLE CODEC(GORILLA(1)), TAGS(t))",
+        ] {
+            assert!(ExtParser::parse_sql(sql).is_err(), "{sql}");
+        }
+    }
+
     #[test]
     fn test_insert_values() {
         let sql = "insert public.test(TIME, ta, tb, fa, fb)
@@ -2678,6 +3049,7 @@ mod tests {
                         shard_num: Some(5),
                         vnode_duration: Some("3d".to_string()),
                         replica: Some(10),
+                        max_series: None,
                     },
                     config: DatabaseConfig {
                         precision: Some("us".to_string()),
@@ -2697,7 +3069,7 @@ mod tests {
 
     #[test]
     fn test_create_database0() {
-        let sql = "create database test with ttl 'inf' shard 6 vnode_duration '730.5d' replica 1 precision 'us' max_memcache_size '128MiB' memcache_partitions 10 wal_max_file_size '300M' wal_sync 'true' strict_write 'true' max_cache_readers 100;";
+        let sql = "create database test with ttl 'inf' shard 6 vnode_duration '730.5d' replica 1 max_series 100000 precision 'us' max_memcache_size '128MiB' memcache_partitions 10 wal_max_file_size '300M' wal_sync 'true' strict_write 'true' max_cache_readers 100;";
         let statements = ExtParser::parse_sql(sql).unwrap();
         assert_eq!(statements.len(), 1);
         match statements[0] {
@@ -2713,6 +3085,7 @@ mod tests {
                         shard_num: Some(6),
                         vnode_duration: Some("730.5d".to_string()),
                         replica: Some(1),
+                        max_series: Some(100000),
                     },
                     config: DatabaseConfig {
                         precision: Some("us".to_string()),
@@ -2736,6 +3109,21 @@ mod tests {
         ExtParser::parse_sql(sql).unwrap();
     }
 
+    #[test]
+    fn test_analyze_table() {
+        let sql = "ANALYZE TABLE db1.m";
+        let statement = ExtParser::parse_sql(sql).unwrap();
+        assert_eq!(
+            statement.front().unwrap(),
+            &ExtStatement::AnalyzeTable(AnalyzeTable {
+                table_name: ObjectName(vec![Ident::from("db1"), Ident::from("m")]),
+            })
+        );
+
+        let sql = "ANALYZE m";
+        assert!(ExtParser::parse_sql(sql).is_err());
+    }
+
     #[test]
     fn test_alter_table() {
         let sql = r#"
@@ -2745,6 +3133,8 @@ mod tests {
             ALTER TABLE m DROP f;
             ALTER TABLE m ALTER f SET CODEC(DEFAULT);
             ALTER TABLE m ALTER TIME SET CODEC(NULL);
+            ALTER TABLE m SET TTL '7d';
+            ALTER TABLE m SET MAX_SERIES = 10000;
         "#;
         let statement = ExtParser::parse_sql(sql).unwrap();
         let statement: Vec<AlterTable> = statement
@@ -2804,6 +3194,16 @@ mod tests {
                         column_name: Ident::from("TIME"),
                         encoding: Encoding::Null
                     }
+                },
+                AlterTable {
+                    table_name: ObjectName(vec![Ident::from("m")]),
+                    alter_action: AlterTableAction::SetTtl {
+                        ttl: "7d".to_string()
+                    }
+                },
+                AlterTable {
+                    table_name: ObjectName(vec![Ident::from("m")]),
+                    alter_action: AlterTableAction::SetMaxSeries { max_series: 10000 }
                 }
             ]
         );
@@ -2885,6 +3285,29 @@ mod tests {
         assert_eq!(statement[0], ExtStatement::ShowReplicas);
     }
 
+    #[test]
+    fn test_show_cardinality() {
+        let statement = ExtParser::parse_sql("show cardinality;").unwrap();
+        assert_eq!(
+            statement[0],
+            ExtStatement::ShowCardinality(ShowCardinality {
+                database_name: None,
+                table: None,
+                limit: None,
+            })
+        );
+
+        let statement = ExtParser::parse_sql("show cardinality on db1 from cpu limit 10;").unwrap();
+        assert_eq!(
+            statement[0],
+            ExtStatement::ShowCardinality(ShowCardinality {
+                database_name: Some(Ident::from("db1")),
+                table: Some(Ident::from("cpu")),
+                limit: Some(Expr::Value(Value::Number("10".to_string(), false))),
+            })
+        );
+    }
+
     #[test]
     fn test_vnode_sql() {
         let sql1 = "move vnode 1 to node 2;";
@@ -2942,6 +3365,42 @@ mod tests {
         let _ = ExtParser::parse_sql(sql).unwrap();
     }
 
+    #[test]
+    fn test_parse_backup_restore_database() {
+        let sql =
+            "backup database db1 to 's3://bucket/backup' CONNECTION = (region = 'us-east-1');";
+        let statement = ExtParser::parse_sql(sql).unwrap();
+        assert_eq!(
+            statement[0],
+            ExtStatement::BackupDatabase(BackupDatabase {
+                database_name: Ident::new("db1"),
+                location: UriLocation {
+                    path: "s3://bucket/backup".to_string(),
+                    connection_options: vec![SqlOption {
+                        name: "region".into(),
+                        value: Value::SingleQuotedString("us-east-1".to_string()),
+                    }],
+                },
+            })
+        );
+
+        let sql = "restore database db2 from 'file:///tmp/backup' as of '2024-01-01T00:00:00Z';";
+        let statement = ExtParser::parse_sql(sql).unwrap();
+        assert_eq!(
+            statement[0],
+            ExtStatement::RestoreDatabase(RestoreDatabase {
+                database_name: Ident::new("db2"),
+                location: UriLocation {
+                    path: "file:///tmp/backup".to_string(),
+                    connection_options: vec![],
+                },
+                as_of: Some("2024-01-01T00:00:00Z".to_string()),
+            })
+        );
+
+        assert!(ExtParser::parse_sql("backup table t1 to 'file:///tmp/backup'").is_err());
+    }
+
     #[test]
     fn test_parse_copy_into_location() {
         let sql = r#"
@@ -3109,6 +3568,212 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_create_continuous_aggregate() {
+        let statement = parse_sql(
+            "CREATE CONTINUOUS AGGREGATE IF NOT EXISTS rollup_db.cpu_1h
+            WITH (watermark_delay = '1m')
+            AS SELECT date_bin(INTERVAL '1 hour', time) AS time, host, avg(usage) AS usage
+            FROM cpu
+            GROUP BY date_bin(INTERVAL '1 hour', time), host;",
+        );
+
+        match statement {
+            ExtStatement::CreateContinuousAggregate(CreateContinuousAggregate {
+                if_not_exists,
+                name,
+                with_options,
+                query,
+            }) => {
+                assert!(if_not_exists);
+                assert_eq!("rollup_db.cpu_1h", &name.to_string());
+                assert_eq!(
+                    vec![SqlOption {
+                        name: "watermark_delay".into(),
+                        value: Value::SingleQuotedString("1m".into()),
+                    }],
+                    with_options
+                );
+                assert!(query
+                    .to_string()
+                    .ends_with("FROM cpu GROUP BY date_bin(INTERVAL '1 hour', time), host"));
+            }
+            _ => panic!("expect CreateContinuousAggregate"),
+        }
+
+        let statement = parse_sql(
+            "CREATE ROLLUP cpu_1h AS SELECT date_bin(INTERVAL '1 hour', time) AS time, max(usage) AS usage FROM cpu GROUP BY date_bin(INTERVAL '1 hour', time)",
+        );
+        assert!(matches!(
+            statement,
+            ExtStatement::CreateContinuousAggregate(CreateContinuousAggregate {
+                if_not_exists: false,
+                ..
+            })
+        ));
+
+        let result = ExtParser::parse_sql("CREATE CONTINUOUS cpu_1h AS SELECT 1");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_create_function() {
+        let statement = parse_sql(
+            "CREATE FUNCTION IF NOT EXISTS f_to_c(f DOUBLE) RETURNS DOUBLE LANGUAGE SQL AS '(f - 32) / 1.8';",
+        );
+        assert_eq!(
+            statement,
+            ExtStatement::CreateFunction(CreateFunction {
+                or_replace: false,
+                if_not_exists: true,
+                is_aggregate: false,
+                name: Ident::from("f_to_c"),
+                args: vec![(Ident::from("f"), DataType::Double)],
+                return_type: DataType::Double,
+                language: Ident::from("SQL"),
+                body: "(f - 32) / 1.8".to_string(),
+                with_options: vec![],
+            })
+        );
+
+        let statement = parse_sql(
+            "CREATE OR REPLACE AGGREGATE FUNCTION wasm_sum(x BIGINT) RETURNS BIGINT LANGUAGE WASM
+            AS 'AGFzbQEAAAA=' WITH (fuel = 1000, max_memory = 65536);",
+        );
+        match statement {
+            ExtStatement::CreateFunction(CreateFunction {
+                or_replace,
+                is_aggregate,
+                args,
+                language,
+                with_options,
+                ..
+            }) => {
+                assert!(or_replace);
+                assert!(is_aggregate);
+                assert_eq!(vec![(Ident::from("x"), DataType::BigInt(None))], args);
+                assert_eq!(Ident::from("WASM"), language);
+                assert_eq!(2, with_options.len());
+            }
+            _ => panic!("expect CreateFunction"),
+        }
+
+        let statement = parse_sql("CREATE FUNCTION now_ns() RETURNS BIGINT LANGUAGE SQL AS '1'");
+        assert!(matches!(
+            statement,
+            ExtStatement::CreateFunction(CreateFunction { ref args, .. }) if args.is_empty()
+        ));
+
+        let result = ExtParser::parse_sql("CREATE FUNCTION f(x DOUBLE) LANGUAGE SQL AS 'x'");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_drop_function() {
+        let statement = parse_sql("DROP FUNCTION IF EXISTS f_to_c");
+        assert_eq!(
+            statement,
+            ExtStatement::DropTenantObject(DropTenantObject {
+                object_name: Ident::from("f_to_c"),
+                if_exist: true,
+                obj_type: TenantObjectType::Function,
+                after: None,
+            })
+        );
+    }
+
+    #[test]
+    fn test_grant_table_privilege() {
+        let statement = parse_sql(
+            "GRANT READ (station, temperature) ON TABLE db1.air, WRITE ON DATABASE db2 TO ROLE r1",
+        );
+        assert_eq!(
+            statement,
+            ExtStatement::GrantRevoke(GrantRevoke {
+                is_grant: true,
+                privileges: vec![
+                    Privilege {
+                        action: Action::Read,
+                        object: PrivilegeObject::Table(
+                            ObjectName(vec![Ident::from("db1"), Ident::from("air")]),
+                            Some(vec![Ident::from("station"), Ident::from("temperature")]),
+                        ),
+                    },
+                    Privilege {
+                        action: Action::Write,
+                        object: PrivilegeObject::Database(Ident::from("db2")),
+                    },
+                ],
+                role_name: Ident::from("r1"),
+            })
+        );
+
+        let statement = parse_sql("REVOKE ALL ON TABLE air FROM r1");
+        assert_eq!(
+            statement,
+            ExtStatement::GrantRevoke(GrantRevoke {
+                is_grant: false,
+                privileges: vec![Privilege {
+                    action: Action::All,
+                    object: PrivilegeObject::Table(ObjectName(vec![Ident::from("air")]), None),
+                }],
+                role_name: Ident::from("r1"),
+            })
+        );
+
+        let result = ExtParser::parse_sql("GRANT READ (station) ON DATABASE db1 TO r1");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_create_policy() {
+        let statement = parse_sql(
+            "CREATE POLICY IF NOT EXISTS p1 ON db1.air TO ROLE r1, r2 USING (station = current_user())",
+        );
+        match statement {
+            ExtStatement::CreatePolicy(CreatePolicy {
+                or_replace,
+                if_not_exists,
+                name,
+                table,
+                roles,
+                using,
+            }) => {
+                assert!(!or_replace);
+                assert!(if_not_exists);
+                assert_eq!(Ident::from("p1"), name);
+                assert_eq!("db1.air", table.to_string());
+                assert_eq!(vec![Ident::from("r1"), Ident::from("r2")], roles);
+                assert_eq!("station = current_user()", using.to_string());
+            }
+            _ => panic!("expect CreatePolicy"),
+        }
+
+        let statement = parse_sql("CREATE OR REPLACE POLICY p1 ON air USING (true)");
+        assert!(matches!(
+            statement,
+            ExtStatement::CreatePolicy(CreatePolicy {
+                or_replace: true,
+                ref roles,
+                ..
+            }) if roles.is_empty()
+        ));
+
+        let result = ExtParser::parse_sql("CREATE POLICY p1 ON air station = 'a'");
+        assert!(result.is_err());
+
+        let statement = parse_sql("DROP POLICY IF EXISTS p1");
+        assert_eq!(
+            statement,
+            ExtStatement::DropTenantObject(DropTenantObject {
+                object_name: Ident::from("p1"),
+                if_exist: true,
+                obj_type: TenantObjectType::Policy,
+                after: None,
+            })
+        );
+    }
+
     #[test]
     fn test_alter_table_rename_column() {
         let statement = parse_sql("ALTER TABLE TskvTable RENAME COLUMN tag1 to tag2;");
@@ -1,4 +1,4 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::option::Option;
 use std::str::FromStr;
 use std::sync::Arc;
@@ -6,10 +6,11 @@ use std::{iter, vec};
 
 use async_recursion::async_recursion;
 use async_trait::async_trait;
-use datafusion::arrow::datatypes::{DataType, SchemaRef, TimeUnit};
+use datafusion::arrow::compute::kernels::cast_utils::string_to_timestamp_nanos;
+use datafusion::arrow::datatypes::{DataType, Schema, SchemaRef, TimeUnit};
 use datafusion::arrow::error::ArrowError;
 use datafusion::common::parsers::CompressionTypeVariant;
-use datafusion::common::tree_node::TreeNode;
+use datafusion::common::tree_node::{TreeNode, VisitRecursion};
 use datafusion::common::{
     Column, DFField, DFSchema, OwnedTableReference, Result as DFResult, ToDFSchema,
 };
@@ -30,12 +31,16 @@ use datafusion::logical_expr::expr_rewriter::rewrite_preserving_name;
 use datafusion::logical_expr::logical_plan::Analyze;
 use datafusion::logical_expr::utils::expr_to_columns;
 use datafusion::logical_expr::{
-    lit, BinaryExpr, BuiltinScalarFunction, Case, CreateExternalTable as PlanCreateExternalTable,
-    EmptyRelation, Explain, Expr, Extension, LogicalPlan, LogicalPlanBuilder, Operator, PlanType,
-    SubqueryAlias, TableSource, ToStringifiedPlan, Union,
+    lit, AggregateFunction, BinaryExpr, BuiltinScalarFunction, Case,
+    CreateExternalTable as PlanCreateExternalTable, EmptyRelation, Explain, Expr, Extension,
+    LogicalPlan, LogicalPlanBuilder, Operator, PlanType, SubqueryAlias, TableSource,
+    ToStringifiedPlan, Union,
 };
 use datafusion::optimizer::analyzer::type_coercion::TypeCoercionRewriter;
+use datafusion::optimizer::optimizer::Optimizer;
+use datafusion::optimizer::push_down_projection::PushDownProjection;
 use datafusion::optimizer::simplify_expressions::ConstEvaluator;
+use datafusion::optimizer::OptimizerContext;
 use datafusion::physical_expr::execution_props::ExecutionProps;
 use datafusion::prelude::col;
 use datafusion::scalar::ScalarValue;
@@ -52,7 +57,7 @@ use lazy_static::__Deref;
 use meta::error::MetaError;
 use models::auth::bcrypt_verify;
 use models::auth::privilege::{
-    DatabasePrivilege, GlobalPrivilege, Privilege, TenantObjectPrivilege,
+    DatabasePrivilege, GlobalPrivilege, Privilege, TablePrivilege, TenantObjectPrivilege,
 };
 use models::auth::role::{SystemTenantRole, TenantRoleIdentifier};
 use models::auth::user::User;
@@ -60,6 +65,11 @@ use models::gis::data_type::{Geometry, GeometryType};
 use models::object_reference::{Resolve, ResolvedTable};
 use models::oid::{Identifier, Oid};
 use models::schema::database_schema::{DatabaseConfigBuilder, DatabaseOptionsBuilder};
+use models::schema::function_schema::{
+    FunctionArg, FunctionBody, FunctionKind, FunctionSchema, WasmFunction, DEFAULT_WASM_FUEL,
+    DEFAULT_WASM_MAX_MEMORY,
+};
+use models::schema::policy_schema::PolicySchema;
 use models::schema::stream_table_schema::Watermark;
 use models::schema::tenant::Tenant;
 use models::schema::tskv_table_schema::{
@@ -74,6 +84,7 @@ use spi::query::ast;
 use spi::query::ast::{
     AlterDatabase as ASTAlterDatabase, AlterTable as ASTAlterTable,
     AlterTableAction as ASTAlterTableAction, AlterTenantOperation, AlterUserOperation,
+    AnalyzeTable as ASTAnalyzeTable, BackupDatabase as ASTBackupDatabase,
     ChecksumGroup as ASTChecksumGroup, ColumnOption, CompactDatabase as ASTCompactDatabase,
     CompactVnode as ASTCompactVnode, CopyIntoTable, CopyTarget, CopyVnode as ASTCopyVnode,
     CreateDatabase as ASTCreateDatabase, CreateTable as ASTCreateTable,
@@ -82,6 +93,7 @@ use spi::query::ast::{
     DropVnode as ASTDropVnode, ExtStatement, MoveVnode as ASTMoveVnode,
     ReplicaAdd as ASTReplicaAdd, ReplicaDestory as ASTReplicaDestory,
     ReplicaPromote as ASTReplicaPromote, ReplicaRemove as ASTReplicaRemove,
+    RestoreDatabase as ASTRestoreDatabase, ShowCardinality as ASTShowCardinality,
     ShowSeries as ASTShowSeries, ShowTagBody, ShowTagValues as ASTShowTagValues, UriLocation, With,
 };
 use spi::query::datasource::{self, UriSchema};
@@ -90,13 +102,14 @@ use spi::query::logical_planner::{
     sql_option_to_alter_tenant_action, sql_options_to_map, sql_options_to_tenant_options,
     sql_options_to_user_options, unset_option_to_alter_tenant_action, AlterDatabase, AlterTable,
     AlterTableAction, AlterTenant, AlterTenantAction, AlterTenantAddUser, AlterTenantSetUser,
-    AlterUser, AlterUserAction, ChecksumGroup, CompactVnode, CopyOptions, CopyOptionsBuilder,
-    CopyVnode, CreateDatabase, CreateRole, CreateStreamTable, CreateTable, CreateTenant,
+    AlterUser, AlterUserAction, AnalyzeTable, BackupDatabase, ChecksumGroup, CompactVnode,
+    CopyOptions, CopyOptionsBuilder, CopyVnode, CreateContinuousAggregate, CreateDatabase,
+    CreateFunction, CreatePolicy, CreateRole, CreateStreamTable, CreateTable, CreateTenant,
     CreateUser, DDLPlan, DMLPlan, DatabaseObjectType, DeleteFromTable, DropDatabaseObject,
     DropGlobalObject, DropTenantObject, DropVnode, FileFormatOptions, FileFormatOptionsBuilder,
     GlobalObjectType, GrantRevoke, LogicalPlanner, MoveVnode, Plan, PlanWithPrivileges, QueryPlan,
     RecoverDatabase, RecoverTenant, ReplicaAdd, ReplicaDestory, ReplicaPromote, ReplicaRemove,
-    SYSPlan, TenantObjectType, TENANT_OPTION_LIMITER,
+    RestoreDatabase, SYSPlan, TenantObjectType, TENANT_OPTION_LIMITER,
 };
 use spi::query::session::SessionCtx;
 use spi::{
@@ -109,11 +122,14 @@ use utils::byte_nums::CnosByteNumber;
 use utils::duration::CnosDuration;
 use utils::precision::Precision;
 
+use crate::auth::row_level_policy::{contains_subquery, RowLevelPolicyRewriter};
 use crate::data_source::source_downcast_adapter;
+use crate::data_source::stream::tskv::factory::TSKV_STREAM_PROVIDER;
 use crate::data_source::stream::{get_event_time_column, get_watermark_delay};
 use crate::data_source::table_source::{TableHandle, TableSourceAdapter, TEMP_LOCATION_TABLE_NAME};
 use crate::extension::logical::logical_plan_builder::LogicalPlanBuilderExt;
 use crate::extension::logical::plan_node::update::UpdateNode;
+use crate::function::user_defined::check_wasm_function;
 use crate::metadata::{
     is_system_database, ContextProviderExtension, DatabaseSet, COLUMNS_COLUMN_NAME,
     COLUMNS_COLUMN_TYPE, COLUMNS_COMPRESSION_CODEC, COLUMNS_DATABASE_NAME, COLUMNS_DATA_TYPE,
@@ -122,8 +138,14 @@ use crate::metadata::{
     DATABASES_REPLICA, DATABASES_SHARD, DATABASES_STRICT_WRITE, DATABASES_TTL,
     DATABASES_VNODE_DURATION, DATABASES_WAL_MAX_FILE_SIZE, DATABASES_WAL_SYNC, INFORMATION_SCHEMA,
     INFORMATION_SCHEMA_COLUMNS, INFORMATION_SCHEMA_DATABASES, INFORMATION_SCHEMA_QUERIES,
-    INFORMATION_SCHEMA_TABLES, TABLES_TABLE_DATABASE, TABLES_TABLE_NAME,
+    INFORMATION_SCHEMA_SERIES_CARDINALITY, INFORMATION_SCHEMA_TABLES,
+    SERIES_CARDINALITY_DATABASE_NAME, SERIES_CARDINALITY_SERIES_COUNT,
+    SERIES_CARDINALITY_TABLE_NAME, SERIES_CARDINALITY_TAG_KEY, SERIES_CARDINALITY_TAG_VALUE_COUNT,
+    TABLES_TABLE_DATABASE, TABLES_TABLE_NAME,
 };
+use crate::sql::rollup::{self, RollupDefinition, RollupRewriter};
+use crate::sql::sql_function::{sql_function_body_to_expr, SqlFunctionRewriter};
+use crate::sql::substrait::substrait_to_df_plan;
 
 /// CnosDB SQL query planner
 pub struct SqlPlanner<'a, S: ContextProviderExtension> {
@@ -152,6 +174,39 @@ impl<'a, S: ContextProviderExtension + Send + Sync> LogicalPlanner for SqlPlanne
         check_privilege(session.user(), privileges)?;
         Ok(plan)
     }
+
+    async fn create_substrait_logical_plan(
+        &self,
+        substrait_plan: &[u8],
+        session: &SessionCtx,
+    ) -> QueryResult<Plan> {
+        let df_plan = {
+            let span = session.get_child_span("substrait to logical plan");
+            substrait_to_df_plan(self.schema_provider, session, substrait_plan)
+                .await
+                .inspect_err(|err| {
+                    span.error(err.to_string());
+                })?
+        };
+
+        let access_databases = self.schema_provider.reset_access_databases();
+        let privileges = tables_privileges(
+            DatabasePrivilege::Read,
+            *session.tenant_id(),
+            access_databases,
+            Some(&df_plan),
+        );
+
+        let df_plan = self.row_level_policy_rewriter(session).rewrite(df_plan)?;
+        let plan = Plan::Query(QueryPlan {
+            df_plan,
+            is_tag_scan: false,
+        });
+
+        let _ = session.get_child_span("check privilege");
+        check_privilege(session.user(), privileges)?;
+        Ok(plan)
+    }
 }
 
 impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlanner<'a, S> {
@@ -212,7 +267,9 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlanner<'a, S> {
                 .await
             }
             ExtStatement::ShowTagValues(stmt) => self.show_tag_values(*stmt, session),
+            ExtStatement::ShowCardinality(stmt) => self.show_cardinality_to_plan(stmt, session),
             ExtStatement::AlterTable(stmt) => self.alter_table_to_plan(stmt, session),
+            ExtStatement::AnalyzeTable(stmt) => self.analyze_table_to_plan(stmt, session),
             ExtStatement::AlterTenant(stmt) => self.alter_tenant_to_plan(stmt).await,
             ExtStatement::AlterUser(stmt) => {
                 self.alter_user_to_plan(stmt, session.user(), false).await
@@ -226,6 +283,8 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlanner<'a, S> {
             ExtStatement::CompactVnode(stmt) => self.compact_vnode_to_plan(stmt),
             ExtStatement::CompactDatabase(stmt) => self.compact_database_to_plan(stmt),
             ExtStatement::ChecksumGroup(stmt) => self.checksum_group_to_plan(stmt),
+            ExtStatement::BackupDatabase(stmt) => self.backup_database_to_plan(stmt),
+            ExtStatement::RestoreDatabase(stmt) => self.restore_database_to_plan(stmt),
             ExtStatement::CreateStream(_) => Err(QueryError::NotImplemented {
                 err: "CreateStream Planner.".to_string(),
             }),
@@ -238,6 +297,11 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlanner<'a, S> {
             ExtStatement::CreateStreamTable(stmt) => {
                 self.create_stream_table_to_plan(stmt, session)
             }
+            ExtStatement::CreateContinuousAggregate(stmt) => {
+                self.create_continuous_aggregate_to_plan(stmt, session)
+            }
+            ExtStatement::CreateFunction(stmt) => self.create_function_to_plan(stmt, session),
+            ExtStatement::CreatePolicy(stmt) => self.create_policy_to_plan(stmt, session),
             ExtStatement::RecoverTenant(stmt) => self.recovertenant_to_plan(stmt),
             ExtStatement::RecoverDatabase(stmt) => self.recoverdatabase_to_plan(stmt, session),
             ExtStatement::ShowReplicas => self.show_replicas_to_plan(),
@@ -256,18 +320,27 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlanner<'a, S> {
         match stmt {
             Statement::Query(_) => {
                 let df_plan = self.df_planner.sql_statement_to_plan(stmt)?;
-                let plan = Plan::Query(QueryPlan {
-                    df_plan,
-                    is_tag_scan: false,
-                });
+                let df_plan = SqlFunctionRewriter::new(self.schema_provider, &self.df_planner)
+                    .rewrite(df_plan)?;
+                // Read the rollup tables if the data of the raw tables has expired,
+                // the privileges and the row level policies apply to the tables actually read
+                let df_plan = RollupRewriter::new(self.schema_provider).rewrite(df_plan)?;
 
                 // privileges
                 let access_databases = self.schema_provider.reset_access_databases();
-                let privileges = databases_privileges(
+                let privileges = tables_privileges(
                     DatabasePrivilege::Read,
                     *session.tenant_id(),
                     access_databases,
+                    Some(&df_plan),
                 );
+
+                let df_plan = self.row_level_policy_rewriter(session).rewrite(df_plan)?;
+                let plan = Plan::Query(QueryPlan {
+                    df_plan,
+                    is_tag_scan: false,
+                });
+
                 Ok(PlanWithPrivileges { plan, privileges })
             }
             Statement::Insert {
@@ -406,10 +479,11 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlanner<'a, S> {
         });
 
         // privileges
-        let write_privileges = databases_privileges(
+        let write_privileges = tables_privileges(
             DatabasePrivilege::Write,
             *session.tenant_id(),
             self.schema_provider.reset_access_databases(),
+            None,
         );
         Ok(PlanWithPrivileges {
             plan,
@@ -478,14 +552,20 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlanner<'a, S> {
         let source_plan = self
             .df_planner
             .sql_statement_to_plan(Statement::Query(source))?;
+        let source_plan = SqlFunctionRewriter::new(self.schema_provider, &self.df_planner)
+            .rewrite(source_plan)?;
 
-        // save database read privileges
+        // save table read privileges
         // This operation must be done before fetching the target table metadata
-        let mut read_privileges = databases_privileges(
+        let mut read_privileges = tables_privileges(
             DatabasePrivilege::Read,
             *session.tenant_id(),
             self.schema_provider.reset_access_databases(),
+            Some(&source_plan),
         );
+        let source_plan = self
+            .row_level_policy_rewriter(session)
+            .rewrite(source_plan)?;
 
         let table_ref = normalize_sql_object_name(sql_object_name)?;
         let columns = sql_column_names
@@ -512,10 +592,11 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlanner<'a, S> {
         });
 
         // privileges
-        let mut write_privileges = databases_privileges(
+        let mut write_privileges = tables_privileges(
             DatabasePrivilege::Write,
             *session.tenant_id(),
             self.schema_provider.reset_access_databases(),
+            None,
         );
         write_privileges.append(&mut read_privileges);
         Ok(PlanWithPrivileges {
@@ -590,8 +671,25 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlanner<'a, S> {
 
         valid_delete(schema.as_ref(), &selection)?;
 
+        // the rows invisible to the user can not be deleted either
+        if self
+            .row_level_policy_rewriter(session)
+            .is_restricted(&schema.db, &schema.name)
+        {
+            return Err(QueryError::InsufficientPrivileges {
+                privilege: bypass_row_policies_privilege(session, &schema).to_string(),
+            });
+        }
+
         let table_name = object_name_to_resolved_table(session, table_name)?;
-        let database = table_name.database().to_string();
+        let privilege = Privilege::TenantObject(
+            TenantObjectPrivilege::Table(
+                TablePrivilege::new(DatabasePrivilege::Write, None),
+                table_name.database().to_string(),
+                table_name.table().to_string(),
+            ),
+            Some(*session.tenant_id()),
+        );
         let plan = Plan::DML(DMLPlan::DeleteFromTable(DeleteFromTable {
             table_name,
             selection,
@@ -599,10 +697,7 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlanner<'a, S> {
 
         Ok(PlanWithPrivileges {
             plan,
-            privileges: vec![Privilege::TenantObject(
-                TenantObjectPrivilege::Database(DatabasePrivilege::Write, Some(database)),
-                Some(*session.tenant_id()),
-            )],
+            privileges: vec![privilege],
         })
     }
 
@@ -705,6 +800,29 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlanner<'a, S> {
                     Privilege::TenantObject(TenantObjectPrivilege::RoleFull, Some(tenant_id)),
                 )
             }
+            TenantObjectType::Function => (
+                DDLPlan::DropTenantObject(DropTenantObject {
+                    tenant_name: tenant_name.to_string(),
+                    name: normalize_ident(object_name),
+                    if_exist,
+                    obj_type: TenantObjectType::Function,
+                    after: after_duration,
+                }),
+                Privilege::TenantObject(
+                    TenantObjectPrivilege::Database(DatabasePrivilege::Full, None),
+                    Some(tenant_id),
+                ),
+            ),
+            TenantObjectType::Policy => (
+                DDLPlan::DropTenantObject(DropTenantObject {
+                    tenant_name: tenant_name.to_string(),
+                    name: normalize_ident(object_name),
+                    if_exist,
+                    obj_type: TenantObjectType::Policy,
+                    after: after_duration,
+                }),
+                Privilege::TenantObject(TenantObjectPrivilege::RoleFull, Some(tenant_id)),
+            ),
         };
 
         Ok(PlanWithPrivileges {
@@ -1220,6 +1338,15 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlanner<'a, S> {
                     new_column_name,
                 }
             }
+            ASTAlterTableAction::SetTtl { ttl } => {
+                let ttl = self.str_to_duration(&ttl)?;
+                // An infinite TTL means the data of the table never expires.
+                let ttl = (ttl != CnosDuration::new_inf()).then_some(ttl);
+                AlterTableAction::SetTtl { ttl }
+            }
+            ASTAlterTableAction::SetMaxSeries { max_series } => {
+                AlterTableAction::SetMaxSeries { max_series }
+            }
         };
         let plan = Plan::DDL(DDLPlan::AlterTable(AlterTable {
             table_name,
@@ -1239,6 +1366,40 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlanner<'a, S> {
         })
     }
 
+    fn analyze_table_to_plan(
+        &self,
+        statement: ASTAnalyzeTable,
+        session: &SessionCtx,
+    ) -> QueryResult<PlanWithPrivileges> {
+        let table_ref = normalize_sql_object_name(statement.table_name)?;
+        let table_name = table_ref
+            .clone()
+            .resolve_object(session.tenant(), session.default_database())?;
+        let handle = self.get_table_handle(table_ref)?;
+        let table_schema = match handle {
+            TableHandle::Tskv(t) => t.table_schema(),
+            _ => {
+                return Err(QueryError::NotImplemented {
+                    err: "only tskv table support analyze".to_string(),
+                })
+            }
+        };
+
+        let plan = Plan::DDL(DDLPlan::AnalyzeTable(AnalyzeTable { table_name }));
+
+        // privileges
+        Ok(PlanWithPrivileges {
+            plan,
+            privileges: vec![Privilege::TenantObject(
+                TenantObjectPrivilege::Database(
+                    DatabasePrivilege::Write,
+                    Some(table_schema.db.clone()),
+                ),
+                Some(*session.tenant_id()),
+            )],
+        })
+    }
+
     fn show_databases_to_plan(&self, session: &SessionCtx) -> QueryResult<PlanWithPrivileges> {
         let projections = vec![col(DATABASES_DATABASE_NAME)];
         let sorts = vec![col(DATABASES_DATABASE_NAME).sort(true, true)];
@@ -1311,6 +1472,66 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlanner<'a, S> {
         })
     }
 
+    fn show_cardinality_to_plan(
+        &self,
+        stmt: ASTShowCardinality,
+        session: &SessionCtx,
+    ) -> QueryResult<PlanWithPrivileges> {
+        let ASTShowCardinality {
+            database_name,
+            table,
+            limit,
+        } = stmt;
+        let db_name = database_name
+            .map(normalize_ident)
+            .unwrap_or_else(|| session.default_database().to_string());
+
+        let projections = vec![
+            col(SERIES_CARDINALITY_TABLE_NAME),
+            col(SERIES_CARDINALITY_SERIES_COUNT),
+            col(SERIES_CARDINALITY_TAG_KEY),
+            col(SERIES_CARDINALITY_TAG_VALUE_COUNT),
+        ];
+        // Tag keys with the most distinct values first
+        let sorts = vec![
+            col(SERIES_CARDINALITY_TAG_VALUE_COUNT).sort(false, false),
+            col(SERIES_CARDINALITY_TABLE_NAME).sort(true, true),
+            col(SERIES_CARDINALITY_TAG_KEY).sort(true, true),
+        ];
+
+        let table_ref =
+            TableReference::partial(INFORMATION_SCHEMA, INFORMATION_SCHEMA_SERIES_CARDINALITY);
+        let table_source = self.get_table_source(table_ref.clone())?;
+
+        let mut filter = col(SERIES_CARDINALITY_DATABASE_NAME).eq(lit(db_name.clone()));
+        if let Some(table) = table {
+            filter = filter.and(col(SERIES_CARDINALITY_TABLE_NAME).eq(lit(normalize_ident(table))));
+        }
+
+        let mut builder = LogicalPlanBuilder::scan(table_ref, table_source, None)?
+            .filter(filter)?
+            .project(projections)?
+            .sort(sorts)?;
+        if limit.is_some() {
+            builder = self.limit_offset_to_plan(limit, None, builder)?;
+        }
+        let df_plan = builder.build()?;
+
+        let plan = Plan::Query(QueryPlan {
+            df_plan,
+            is_tag_scan: false,
+        });
+
+        // privileges
+        Ok(PlanWithPrivileges {
+            plan,
+            privileges: vec![Privilege::TenantObject(
+                TenantObjectPrivilege::Database(DatabasePrivilege::Read, Some(db_name)),
+                Some(*session.tenant_id()),
+            )],
+        })
+    }
+
     fn show_tag_body(
         &self,
         session: &SessionCtx,
@@ -1365,6 +1586,24 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlanner<'a, S> {
             plan_builder = plan_builder.filter(selection)?;
         }
 
+        // the tag values are filtered by the row-level security policies too
+        if let Some(predicate) = self.row_level_policy_rewriter(session).table_predicate(
+            &table_schema.db,
+            &table_schema.name,
+            plan_builder.schema(),
+        )? {
+            let mut policy_columns = HashSet::new();
+            expr_to_columns(&predicate, &mut policy_columns)?;
+            // the tag scan can not evaluate the policies on the fields
+            if check_show_series_expr(&policy_columns, &table_schema).is_err() {
+                return Err(QueryError::InsufficientPrivileges {
+                    privilege: bypass_row_policies_privilege(session, &table_schema).to_string(),
+                });
+            }
+            plan_builder = plan_builder.filter(predicate)?;
+            columns.extend(policy_columns);
+        }
+
         // get where has time column
         let where_contain_time = columns
             .iter()
@@ -1588,6 +1827,9 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlanner<'a, S> {
         if let Some(vnode_duration) = options.vnode_duration {
             plan_options.with_vnode_duration(self.str_to_duration(&vnode_duration)?);
         }
+        if let Some(max_series) = options.max_series {
+            plan_options.with_max_series(max_series);
+        }
         Ok(plan_options)
     }
 
@@ -1722,6 +1964,18 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlanner<'a, S> {
         Ok(self.schema_provider.get_table_source(table_name)?)
     }
 
+    fn row_level_policy_rewriter<'r>(
+        &'r self,
+        session: &'r SessionCtx,
+    ) -> RowLevelPolicyRewriter<'r, 'a, S> {
+        RowLevelPolicyRewriter::new(
+            self.schema_provider,
+            &self.df_planner,
+            session.user(),
+            *session.tenant_id(),
+        )
+    }
+
     fn create_tenant_to_plan(&self, stmt: ast::CreateTenant) -> QueryResult<PlanWithPrivileges> {
         let ast::CreateTenant {
             name,
@@ -2060,19 +2314,40 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlanner<'a, S> {
             return Err(err);
         }
 
-        let database_privileges = privileges
-            .into_iter()
-            .map(|ast::Privilege { action, database }| {
-                let database_privilege = match action {
-                    ast::Action::Read => DatabasePrivilege::Read,
-                    ast::Action::Write => DatabasePrivilege::Write,
-                    ast::Action::All => DatabasePrivilege::Full,
-                };
-                let database_name = normalize_ident(database);
-
-                (database_privilege, database_name)
-            })
-            .collect::<Vec<(DatabasePrivilege, String)>>();
+        let mut database_privileges = vec![];
+        let mut table_privileges = vec![];
+        for ast::Privilege { action, object } in privileges {
+            let database_privilege = match action {
+                ast::Action::Read => DatabasePrivilege::Read,
+                ast::Action::Write => DatabasePrivilege::Write,
+                ast::Action::All => DatabasePrivilege::Full,
+            };
+            match object {
+                ast::PrivilegeObject::Database(database) => {
+                    database_privileges.push((database_privilege, normalize_ident(database)));
+                }
+                ast::PrivilegeObject::Table(table, columns) => {
+                    // Only the columns to read can be limited
+                    if columns.is_some() && database_privilege != DatabasePrivilege::Read {
+                        return Err(QueryError::Semantic {
+                            err: format!(
+                                "Column list is only supported by the READ privilege, but got {}",
+                                database_privilege.as_str()
+                            ),
+                        });
+                    }
+                    let table = object_name_to_resolved_table(session, table)?;
+                    let columns =
+                        columns.map(|columns| columns.into_iter().map(normalize_ident).collect());
+
+                    table_privileges.push((
+                        TablePrivilege::new(database_privilege, columns),
+                        table.database().to_string(),
+                        table.table().to_string(),
+                    ));
+                }
+            }
+        }
 
         let privileges = vec![Privilege::TenantObject(
             TenantObjectPrivilege::RoleFull,
@@ -2082,6 +2357,7 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlanner<'a, S> {
         let plan = Plan::DDL(DDLPlan::GrantRevoke(GrantRevoke {
             is_grant,
             database_privileges,
+            table_privileges,
             tenant_name: tenant_name.to_string(),
             role_name,
         }));
@@ -2190,6 +2466,65 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlanner<'a, S> {
         })
     }
 
+    fn backup_database_to_plan(&self, stmt: ASTBackupDatabase) -> QueryResult<PlanWithPrivileges> {
+        let ASTBackupDatabase {
+            database_name,
+            location,
+        } = stmt;
+
+        let database_name = normalize_ident(database_name);
+        if self
+            .schema_provider
+            .get_db_info(&database_name)
+            .context(MetaSnafu)?
+            .is_none()
+        {
+            return Err(QueryError::DatabaseNotFound {
+                name: database_name,
+            });
+        }
+
+        let plan = Plan::DDL(DDLPlan::BackupDatabase(BackupDatabase {
+            database_name,
+            url: location.path,
+            options: sql_options_to_map(&location.connection_options),
+        }));
+        Ok(PlanWithPrivileges {
+            plan,
+            privileges: vec![Privilege::Global(GlobalPrivilege::System)],
+        })
+    }
+
+    fn restore_database_to_plan(
+        &self,
+        stmt: ASTRestoreDatabase,
+    ) -> QueryResult<PlanWithPrivileges> {
+        let ASTRestoreDatabase {
+            database_name,
+            location,
+            as_of,
+        } = stmt;
+
+        let as_of = as_of
+            .map(|ts| {
+                string_to_timestamp_nanos(&ts).map_err(|e| QueryError::Semantic {
+                    err: format!("Invalid timestamp '{ts}' after AS OF: {e}"),
+                })
+            })
+            .transpose()?;
+
+        let plan = Plan::DDL(DDLPlan::RestoreDatabase(RestoreDatabase {
+            database_name: normalize_ident(database_name),
+            url: location.path,
+            options: sql_options_to_map(&location.connection_options),
+            as_of,
+        }));
+        Ok(PlanWithPrivileges {
+            plan,
+            privileges: vec![Privilege::Global(GlobalPrivilege::System)],
+        })
+    }
+
     fn checksum_group_to_plan(&self, stmt: ASTChecksumGroup) -> QueryResult<PlanWithPrivileges> {
         let ASTChecksumGroup { replication_set_id } = stmt;
 
@@ -2326,6 +2661,359 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlanner<'a, S> {
         })
     }
 
+    fn create_continuous_aggregate_to_plan(
+        &self,
+        stmt: ast::CreateContinuousAggregate,
+        session: &SessionCtx,
+    ) -> QueryResult<PlanWithPrivileges> {
+        let ast::CreateContinuousAggregate {
+            if_not_exists,
+            name,
+            with_options,
+            mut query,
+        } = stmt;
+
+        let rollup_table = object_name_to_resolved_table(session, name)?;
+        let rollup_db = rollup_table.database().to_string();
+
+        let df_plan = self
+            .df_planner
+            .sql_statement_to_plan(Statement::Query(query.clone()))?;
+        let raw_table = rollup::extract_raw_table(&df_plan)?;
+
+        // The rollup table: time column, tags selected from the raw table, and the aggregated fields
+        let id_generator = SeqIdGenerator::default();
+        let unit: TimeUnit = self.get_db_precision(&rollup_db)?.into();
+        let mut schema = Vec::with_capacity(df_plan.schema().fields().len());
+        for field in df_plan.schema().fields() {
+            let col_id = id_generator.next_id() as ColumnId;
+            let name = field.name();
+            let column = if name == TIME_FIELD_NAME
+                && matches!(field.data_type(), DataType::Timestamp(_, _))
+            {
+                TableColumn::new_time_column(col_id, unit.clone())
+            } else if raw_table
+                .column(name)
+                .is_some_and(|c| c.column_type.is_tag())
+                && field.data_type() == &DataType::Utf8
+            {
+                TableColumn::new_tag_column(col_id, name.clone())
+            } else {
+                let column_type = ColumnType::from(field.data_type().clone());
+                if !column_type.is_field() || column_type == ColumnType::Field(ValueType::Unknown) {
+                    return Err(AnalyzerSnafu {
+                        err: format!(
+                            "Unsupported data type {} of column '{name}' of continuous aggregate {rollup_table}",
+                            field.data_type()
+                        ),
+                    }
+                    .build());
+                }
+                TableColumn::new(col_id, name.clone(), column_type, Default::default())
+            };
+            schema.push(column);
+        }
+
+        if !schema.iter().any(|e| e.column_type.is_time()) {
+            return Err(AnalyzerSnafu {
+                err: format!(
+                    "The query of continuous aggregate {rollup_table} must output the timestamp column '{TIME_FIELD_NAME}'"
+                ),
+            }
+            .build());
+        }
+        let mut column_name = HashSet::new();
+        for col in schema.iter() {
+            if !column_name.insert(col.name.as_str()) {
+                return Err(QueryError::SameColumnName {
+                    column: col.name.to_string(),
+                });
+            }
+        }
+
+        // The stream table over the raw table
+        let stream_table_name = ObjectName(vec![
+            Ident::with_quote('"', &raw_table.db),
+            Ident::with_quote(
+                '"',
+                rollup::rollup_stream_table_name(&rollup_db, rollup_table.table()),
+            ),
+        ]);
+        let stream_table = object_name_to_resolved_table(session, stream_table_name.clone())?;
+
+        let mut extra_options = sql_options_to_map(&with_options);
+        extra_options.extend(rollup::rollup_stream_table_options(
+            &raw_table,
+            &rollup_db,
+            rollup_table.table(),
+            RollupDefinition::try_from_plan(&df_plan).as_ref(),
+        ));
+        let watermark = Watermark {
+            column: get_event_time_column(stream_table.table(), &extra_options)?.into(),
+            delay: get_watermark_delay(stream_table.table(), &extra_options)?.unwrap_or_default(),
+        };
+
+        // The stream query reads the stream table instead of the raw table
+        let is_raw_table = |name: &ObjectName| {
+            object_name_to_resolved_table(session, name.clone())
+                .is_ok_and(|t| t.database() == raw_table.db && t.table() == raw_table.name)
+        };
+        rollup::replace_table_in_query(&mut query, &is_raw_table, &stream_table_name);
+        let columns = schema
+            .iter()
+            .map(|c| Ident::with_quote('"', &c.name).to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let stream_query = format!(
+            "INSERT INTO {}.{} ({columns}) {query}",
+            Ident::with_quote('"', &rollup_db),
+            Ident::with_quote('"', rollup_table.table()),
+        );
+
+        let plan = Plan::DDL(DDLPlan::CreateContinuousAggregate(
+            CreateContinuousAggregate {
+                if_not_exists,
+                rollup_table: CreateTable {
+                    schema,
+                    name: rollup_table,
+                    if_not_exists: false,
+                },
+                stream_table: CreateStreamTable {
+                    if_not_exists: false,
+                    name: stream_table,
+                    schema: Schema::empty(),
+                    watermark,
+                    stream_type: TSKV_STREAM_PROVIDER.to_string(),
+                    extra_options,
+                },
+                stream_query,
+            },
+        ));
+
+        // privileges: read the raw table, create tables in the databases of raw table and rollup table
+        let access_databases = self.schema_provider.reset_access_databases();
+        let mut privileges = databases_privileges(
+            DatabasePrivilege::Read,
+            *session.tenant_id(),
+            access_databases,
+        );
+        for database_name in [raw_table.db.clone(), rollup_db] {
+            privileges.push(Privilege::TenantObject(
+                TenantObjectPrivilege::Database(DatabasePrivilege::Full, Some(database_name)),
+                Some(*session.tenant_id()),
+            ));
+        }
+
+        Ok(PlanWithPrivileges { plan, privileges })
+    }
+
+    fn create_function_to_plan(
+        &self,
+        stmt: ast::CreateFunction,
+        session: &SessionCtx,
+    ) -> QueryResult<PlanWithPrivileges> {
+        let ast::CreateFunction {
+            or_replace,
+            if_not_exists,
+            is_aggregate,
+            name,
+            args,
+            return_type,
+            language,
+            body,
+            with_options,
+        } = stmt;
+
+        if or_replace && if_not_exists {
+            return Err(QueryError::Semantic {
+                err: "OR REPLACE and IF NOT EXISTS cannot both be specified".to_string(),
+            });
+        }
+
+        let name = normalize_ident(name);
+        // Built-in functions can not be shadowed
+        if self.schema_provider.get_function_schema(&name).is_none()
+            && (BuiltinScalarFunction::from_str(&name).is_ok()
+                || AggregateFunction::from_str(&name).is_ok()
+                || self.schema_provider.get_function_meta(&name).is_some()
+                || self.schema_provider.get_aggregate_meta(&name).is_some())
+        {
+            return Err(QueryError::FunctionExists { name });
+        }
+
+        let mut arg_names = HashSet::with_capacity(args.len());
+        let args = args
+            .into_iter()
+            .map(|(arg, data_type)| {
+                let arg_name = normalize_ident(arg);
+                if !arg_names.insert(arg_name.clone()) {
+                    return Err(QueryError::Semantic {
+                        err: format!("Duplicate argument {arg_name} of function {name}"),
+                    });
+                }
+                let column_type =
+                    self.make_data_type(&arg_name, &data_type, TimeUnit::Nanosecond)?;
+                Ok(FunctionArg {
+                    name: arg_name,
+                    data_type: column_type.into(),
+                })
+            })
+            .collect::<QueryResult<Vec<_>>>()?;
+        let return_type = self
+            .make_data_type(&name, &return_type, TimeUnit::Nanosecond)?
+            .into();
+
+        let kind = if is_aggregate {
+            FunctionKind::Aggregate
+        } else {
+            FunctionKind::Scalar
+        };
+        let body = match normalize_ident(language).as_str() {
+            "sql" => {
+                if is_aggregate {
+                    return Err(QueryError::NotImplemented {
+                        err: "Aggregate function in SQL language".to_string(),
+                    });
+                }
+                if !with_options.is_empty() {
+                    return Err(QueryError::Semantic {
+                        err: format!("SQL function {name} does not support options"),
+                    });
+                }
+                FunctionBody::Sql(body)
+            }
+            "wasm" => {
+                let mut wasm = WasmFunction {
+                    module: body,
+                    fuel: DEFAULT_WASM_FUEL,
+                    max_memory: DEFAULT_WASM_MAX_MEMORY,
+                };
+                for (key, value) in sql_options_to_map(&with_options) {
+                    let parse_u64 = || {
+                        value.parse::<u64>().map_err(|_| QueryError::Semantic {
+                            err: format!("Option {key} of function {name} should be an unsigned number, but got {value}"),
+                        })
+                    };
+                    match key.as_str() {
+                        "fuel" => wasm.fuel = parse_u64()?,
+                        "max_memory" => wasm.max_memory = parse_u64()?,
+                        _ => {
+                            return Err(QueryError::Semantic {
+                                err: format!("Unknown option {key} of function {name}, expected fuel or max_memory"),
+                            })
+                        }
+                    }
+                }
+                FunctionBody::Wasm(wasm)
+            }
+            other => {
+                return Err(QueryError::Semantic {
+                    err: format!(
+                        "Unsupported language {other} of function {name}, expected SQL or WASM"
+                    ),
+                })
+            }
+        };
+
+        let function = FunctionSchema {
+            tenant: session.tenant().to_string(),
+            name,
+            kind,
+            args,
+            return_type,
+            body,
+            version: 0,
+        };
+        // Check the body of the function
+        match &function.body {
+            FunctionBody::Sql(body) => {
+                sql_function_body_to_expr(&self.df_planner, &function, body)?;
+            }
+            FunctionBody::Wasm(_) => {
+                check_wasm_function(&function)?;
+            }
+        }
+
+        let plan = Plan::DDL(DDLPlan::CreateFunction(CreateFunction {
+            or_replace,
+            if_not_exists,
+            function,
+        }));
+        // Functions are visible in all databases of the tenant
+        let privilege = Privilege::TenantObject(
+            TenantObjectPrivilege::Database(DatabasePrivilege::Full, None),
+            Some(*session.tenant_id()),
+        );
+
+        Ok(PlanWithPrivileges {
+            plan,
+            privileges: vec![privilege],
+        })
+    }
+
+    fn create_policy_to_plan(
+        &self,
+        stmt: ast::CreatePolicy,
+        session: &SessionCtx,
+    ) -> QueryResult<PlanWithPrivileges> {
+        let ast::CreatePolicy {
+            or_replace,
+            if_not_exists,
+            name,
+            table,
+            roles,
+            using,
+        } = stmt;
+
+        if or_replace && if_not_exists {
+            return Err(QueryError::Semantic {
+                err: "OR REPLACE and IF NOT EXISTS cannot both be specified".to_string(),
+            });
+        }
+
+        let name = normalize_ident(name);
+        let table = object_name_to_resolved_table(session, table)?;
+
+        // Check the expression against the columns of the table
+        let table_ref = TableReference::full(table.tenant(), table.database(), table.table());
+        let source = self.get_table_source(table_ref.clone());
+        let _ = self.schema_provider.reset_access_databases();
+        let scan = LogicalPlanBuilder::scan(table_ref, source?, None)?.build()?;
+        let expr = self.df_planner.sql_to_expr(
+            using.clone(),
+            scan.schema(),
+            &mut PlannerContext::new(),
+        )?;
+        if contains_subquery(&expr) {
+            return Err(QueryError::Semantic {
+                err: format!("Subquery is not supported in the policy {name}"),
+            });
+        }
+
+        let policy = PolicySchema {
+            tenant: session.tenant().to_string(),
+            name,
+            database: table.database().to_string(),
+            table: table.table().to_string(),
+            roles: roles.into_iter().map(normalize_ident).collect(),
+            using: using.to_string(),
+        };
+
+        let plan = Plan::DDL(DDLPlan::CreatePolicy(CreatePolicy {
+            or_replace,
+            if_not_exists,
+            policy,
+        }));
+        // Policies restrict the access of roles like privileges
+        let privilege =
+            Privilege::TenantObject(TenantObjectPrivilege::RoleFull, Some(*session.tenant_id()));
+
+        Ok(PlanWithPrivileges {
+            plan,
+            privileges: vec![privilege],
+        })
+    }
+
     fn get_table_handle(&self, table_ref: TableReference) -> QueryResult<TableHandle> {
         let source = self.get_table_source(table_ref.clone())?;
         let adapter = source_downcast_adapter(&source)?;
@@ -2381,9 +3069,10 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlanner<'a, S> {
                 Ok(PlanWithPrivileges {
                     plan,
                     privileges: vec![Privilege::TenantObject(
-                        TenantObjectPrivilege::Database(
-                            DatabasePrivilege::Write,
-                            Some(target_table.database_name().into()),
+                        TenantObjectPrivilege::Table(
+                            TablePrivilege::new(DatabasePrivilege::Write, None),
+                            target_table.database_name().into(),
+                            target_table.table_name().into(),
                         ),
                         Some(tenant_id),
                     )],
@@ -2398,7 +3087,7 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlanner<'a, S> {
 
                 let database_set = self.schema_provider.reset_access_databases();
                 let privileges =
-                    databases_privileges(DatabasePrivilege::Read, tenant_id, database_set);
+                    tables_privileges(DatabasePrivilege::Read, tenant_id, database_set, None);
                 Ok(PlanWithPrivileges { plan, privileges })
             }
         }
@@ -2497,6 +3186,9 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlanner<'a, S> {
 
         // 2. build source plan
         let source_plan = self.create_relation(from, &Default::default())?;
+        let source_plan = self
+            .row_level_policy_rewriter(session)
+            .rewrite(source_plan)?;
         let source_schem = SchemaRef::new(source_plan.schema().deref().into());
 
         // 3. According to the external path, construct the external table
@@ -2924,6 +3616,94 @@ fn check_privilege(user: &User, privileges: Vec<Privilege<Oid>>) -> QueryResult<
     Ok(())
 }
 
+/// The privilege of the users not restricted by the row-level security policies of the table
+fn bypass_row_policies_privilege(session: &SessionCtx, schema: &TskvTableSchema) -> Privilege<Oid> {
+    Privilege::TenantObject(
+        TenantObjectPrivilege::Table(
+            TablePrivilege::new(DatabasePrivilege::Full, None),
+            schema.db.clone(),
+            schema.name.clone(),
+        ),
+        Some(*session.tenant_id()),
+    )
+}
+
+/// The privileges on the accessed tables. If `plan` is given, only the columns read by the
+/// plan are required for each table, otherwise all columns are.
+fn tables_privileges(
+    db_priv: DatabasePrivilege,
+    tenant_id: Oid,
+    databases: DatabaseSet,
+    plan: Option<&LogicalPlan>,
+) -> Vec<Privilege<Oid>> {
+    let mut scanned_columns = plan.map(scanned_columns).unwrap_or_default();
+
+    let mut privileges = vec![];
+    for db in databases.dbs() {
+        let tables = databases
+            .table_set(db)
+            .map(|t| t.tables())
+            .unwrap_or_default();
+        for table in tables {
+            let columns = scanned_columns.remove(&(db.clone(), table.clone()));
+            privileges.push(Privilege::TenantObject(
+                TenantObjectPrivilege::Table(
+                    TablePrivilege::new(db_priv.clone(), columns),
+                    db.clone(),
+                    table.clone(),
+                ),
+                Some(tenant_id),
+            ));
+        }
+    }
+    privileges
+}
+
+/// The columns of the tables read by the plan, which are the columns of the table scans after
+/// pushing down the projections.
+///
+/// The tables not found in the result may be read entirely, which are all the tables if the
+/// plan has subqueries, for the columns referenced by the subqueries are not tracked.
+fn scanned_columns(plan: &LogicalPlan) -> HashMap<(String, String), BTreeSet<String>> {
+    let mut columns: HashMap<_, BTreeSet<String>> = HashMap::new();
+
+    let mut has_subquery = false;
+    let _ = plan.apply(&mut |plan| {
+        if plan.expressions().iter().any(contains_subquery) {
+            has_subquery = true;
+            return Ok(VisitRecursion::Stop);
+        }
+        Ok(VisitRecursion::Continue)
+    });
+    if has_subquery {
+        return columns;
+    }
+
+    let optimizer = Optimizer::with_rules(vec![Arc::new(PushDownProjection::new(false))]);
+    let Ok(plan) = optimizer.optimize(plan, &OptimizerContext::new(), |_, _| {}) else {
+        return columns;
+    };
+    let _ = plan.apply(&mut |plan| {
+        if let LogicalPlan::TableScan(scan) = plan {
+            if let Ok(adapter) = source_downcast_adapter(&scan.source) {
+                let table = (
+                    adapter.database_name().to_string(),
+                    adapter.table_name().to_string(),
+                );
+                columns.entry(table).or_default().extend(
+                    scan.projected_schema
+                        .fields()
+                        .iter()
+                        .map(|f| f.name().clone()),
+                );
+            }
+        }
+        Ok(VisitRecursion::Continue)
+    });
+
+    columns
+}
+
 fn databases_privileges(
     db_priv: DatabasePrivilege,
     tenant_id: Oid,
@@ -0,0 +1,941 @@
+//! Continuous aggregates, also known as rollups.
+//!
+//! A continuous aggregate is made up of:
+//! - a tskv table (the rollup table) storing the aggregated data,
+//! - a tskv stream table over the raw table, which is named by [`rollup_stream_table_name`] and
+//!   records the rollup table in its options,
+//! - a stream query aggregating the stream table into the rollup table.
+//!
+//! The aggregate queries on the raw table whose time range has been expired by the TTL are
+//! rewritten to read the rollup table by [`RollupRewriter`].
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+
+use datafusion::arrow::datatypes::{IntervalDayTimeType, IntervalMonthDayNanoType, SchemaRef};
+use datafusion::common::tree_node::{Transformed, TreeNode, TreeNodeVisitor, VisitRecursion};
+use datafusion::common::{Column, DFSchemaRef, Result as DFResult};
+use datafusion::error::DataFusionError;
+use datafusion::logical_expr::aggregate_function::AggregateFunction;
+use datafusion::logical_expr::expr::{AggregateFunction as AggregateFunctionExpr, ScalarFunction};
+use datafusion::logical_expr::expr_rewriter::rewrite_preserving_name;
+use datafusion::logical_expr::utils::expr_to_columns;
+use datafusion::logical_expr::{
+    Aggregate, BuiltinScalarFunction, Filter, LogicalPlan, LogicalPlanBuilder, Projection,
+    TableScan, TableSource,
+};
+use datafusion::optimizer::analyzer::type_coercion::TypeCoercionRewriter;
+use datafusion::optimizer::simplify_expressions::ConstEvaluator;
+use datafusion::physical_expr::execution_props::ExecutionProps;
+use datafusion::prelude::Expr;
+use datafusion::scalar::ScalarValue;
+use datafusion::sql::sqlparser::ast::{ObjectName, Query, SetExpr, TableFactor, TableWithJoins};
+use datafusion::sql::TableReference;
+use models::meta_data::DatabaseInfo;
+use models::predicate::domain::TimeRanges;
+use models::predicate::transformation::RowExpressionToDomainsVisitor;
+use models::predicate::utils::filter_to_time_ranges;
+use models::schema::stream_table_schema::StreamTable;
+use models::schema::table_schema::TableSchema;
+use models::schema::tskv_table_schema::TskvTableSchemaRef;
+use models::schema::TIME_FIELD_NAME;
+use snafu::ResultExt;
+use spi::{AnalyzerSnafu, MetaSnafu, QueryResult};
+use trace::debug;
+use utils::precision::Precision;
+
+use crate::data_source::source_downcast_adapter;
+use crate::data_source::stream::tskv::factory::TSKV_STREAM_PROVIDER;
+use crate::data_source::stream::tskv::{STREAM_DB_KEY, STREAM_TABLE_KEY};
+use crate::data_source::stream::EVENT_TIME_COLUMN_OPTION;
+use crate::data_source::table_source::{TableHandle, TableSourceAdapter};
+use crate::metadata::ContextProviderExtension;
+
+const ROLLUP_STREAM_TABLE_PREFIX: &str = "__rollup_";
+// Stream table option keys
+const ROLLUP_DB_KEY: &str = "rollup_db";
+const ROLLUP_TABLE_KEY: &str = "rollup_table";
+const ROLLUP_INTERVAL_KEY: &str = "rollup_interval";
+const ROLLUP_ORIGIN_KEY: &str = "rollup_origin";
+const ROLLUP_AGGREGATES_KEY: &str = "rollup_aggregates";
+
+/// The aggregate functions whose results over the buckets can be aggregated again by themselves
+const ROLLUP_FUNCTIONS: [(AggregateFunction, &str); 3] = [
+    (AggregateFunction::Min, "min"),
+    (AggregateFunction::Max, "max"),
+    (AggregateFunction::Sum, "sum"),
+];
+
+const NANOS_PER_DAY: i64 = 86_400_000_000_000;
+
+/// Name of the stream table of the continuous aggregate, which is created in the database of the raw table
+pub fn rollup_stream_table_name(rollup_db: &str, rollup_table: &str) -> String {
+    format!("{ROLLUP_STREAM_TABLE_PREFIX}{rollup_db}_{rollup_table}")
+}
+
+/// Options of the stream table of the continuous aggregate
+pub fn rollup_stream_table_options(
+    raw_table: &TskvTableSchemaRef,
+    rollup_db: &str,
+    rollup_table: &str,
+    definition: Option<&RollupDefinition>,
+) -> HashMap<String, String> {
+    let mut options = HashMap::from_iter([
+        (STREAM_DB_KEY.to_string(), raw_table.db.clone()),
+        (STREAM_TABLE_KEY.to_string(), raw_table.name.clone()),
+        (
+            EVENT_TIME_COLUMN_OPTION.to_string(),
+            TIME_FIELD_NAME.to_string(),
+        ),
+        (ROLLUP_DB_KEY.to_string(), rollup_db.to_string()),
+        (ROLLUP_TABLE_KEY.to_string(), rollup_table.to_string()),
+    ]);
+    if let Some(definition) = definition {
+        options.extend(definition.to_options());
+    }
+    options
+}
+
+/// How the rows of the rollup table are aggregated from the raw table.
+///
+/// The time column of the rollup table is `date_bin(interval, time, origin)` of the raw table, every
+/// other column is either a group column or the min/max/sum of the raw column of the same name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RollupDefinition {
+    /// Width of the time buckets in nanoseconds
+    interval: i64,
+    /// Origin of the time buckets in nanoseconds
+    origin: i64,
+    /// The aggregated columns and their aggregate functions
+    aggregates: HashMap<String, AggregateFunction>,
+}
+
+impl RollupDefinition {
+    /// Returns the definition of the query of a continuous aggregate,
+    /// `None` if the rollup table can not answer the aggregate queries on the raw table.
+    pub fn try_from_plan(plan: &LogicalPlan) -> Option<Self> {
+        let LogicalPlan::Projection(Projection { expr, input, .. }) = plan else {
+            return None;
+        };
+        let LogicalPlan::Aggregate(aggregate) = input.as_ref() else {
+            return None;
+        };
+        // The output columns of the aggregate are the group columns followed by the aggregated columns
+        let aggregate_exprs = aggregate
+            .group_expr
+            .iter()
+            .chain(aggregate.aggr_expr.iter())
+            .collect::<Vec<_>>();
+
+        let mut bucket = None;
+        let mut aggregates = HashMap::new();
+        for (expr, field) in expr.iter().zip(plan.schema().fields()) {
+            let Expr::Column(column) = unalias(expr) else {
+                return None;
+            };
+            let index = aggregate.schema.index_of_column(column).ok()?;
+            let name = field.name();
+            match aggregate_exprs.get(index)? {
+                expr if name == TIME_FIELD_NAME => bucket = Some(date_bin_of_time(expr)?),
+                Expr::Column(column) if &column.name == name => {}
+                expr => {
+                    let (fun, column) = aggregate_of_column(expr)?;
+                    if column != name || !ROLLUP_FUNCTIONS.iter().any(|(f, _)| f == fun) {
+                        return None;
+                    }
+                    aggregates.insert(name.clone(), fun.clone());
+                }
+            }
+        }
+
+        let (interval, origin) = bucket?;
+        Some(Self {
+            interval,
+            origin,
+            aggregates,
+        })
+    }
+
+    fn to_options(&self) -> [(String, String); 3] {
+        let aggregates = self
+            .aggregates
+            .iter()
+            .filter_map(|(column, fun)| {
+                let (_, name) = ROLLUP_FUNCTIONS.iter().find(|(f, _)| f == fun)?;
+                Some((column.as_str(), *name))
+            })
+            .collect::<BTreeMap<_, _>>();
+        [
+            (ROLLUP_INTERVAL_KEY.to_string(), self.interval.to_string()),
+            (ROLLUP_ORIGIN_KEY.to_string(), self.origin.to_string()),
+            (
+                ROLLUP_AGGREGATES_KEY.to_string(),
+                serde_json::to_string(&aggregates).unwrap_or_default(),
+            ),
+        ]
+    }
+
+    fn from_options(options: &HashMap<String, String>) -> Option<Self> {
+        let interval = options.get(ROLLUP_INTERVAL_KEY)?.parse().ok()?;
+        let origin = options.get(ROLLUP_ORIGIN_KEY)?.parse().ok()?;
+        let aggregates: HashMap<String, String> =
+            serde_json::from_str(options.get(ROLLUP_AGGREGATES_KEY)?).ok()?;
+        let aggregates = aggregates
+            .into_iter()
+            .map(|(column, name)| {
+                let (fun, _) = ROLLUP_FUNCTIONS.iter().find(|(_, n)| *n == name)?;
+                Some((column, fun.clone()))
+            })
+            .collect::<Option<HashMap<_, _>>>()?;
+
+        Some(Self {
+            interval,
+            origin,
+            aggregates,
+        })
+    }
+
+    /// Whether the aggregate over the raw rows selected by the predicate can be computed from the
+    /// rollup table, `unit` is the nanoseconds of a unit of the time column.
+    fn can_answer(
+        &self,
+        aggregate: &Aggregate,
+        predicate: &Expr,
+        time_ranges: &TimeRanges,
+        unit: i64,
+    ) -> bool {
+        let is_group_column =
+            |c: &Column| c.name != TIME_FIELD_NAME && !self.aggregates.contains_key(&c.name);
+        let interval = self.interval as i128;
+        let is_aligned = |ts: i128| (ts - self.origin as i128).rem_euclid(interval) == 0;
+
+        // Every time bucket of the query is made up of whole buckets of the rollup table
+        let group_by_compatible = aggregate.group_expr.iter().all(|expr| match expr {
+            Expr::Column(c) => is_group_column(c),
+            expr => date_bin_of_time(expr).is_some_and(|(query_interval, query_origin)| {
+                query_interval as i128 % interval == 0 && is_aligned(query_origin as i128)
+            }),
+        });
+
+        // The aggregate of the aggregated buckets is the aggregate of the raw rows
+        let aggregates_compatible = aggregate.aggr_expr.iter().all(|expr| {
+            aggregate_of_column(expr)
+                .is_some_and(|(fun, column)| self.aggregates.get(column) == Some(fun))
+        });
+
+        // The aggregated values can not be filtered like the raw values
+        let mut predicate_columns = HashSet::new();
+        let predicate_compatible = expr_to_columns(predicate, &mut predicate_columns).is_ok()
+            && predicate_columns
+                .iter()
+                .all(|c| c.name == TIME_FIELD_NAME || is_group_column(c));
+
+        // Only whole buckets are selected
+        let unit = unit as i128;
+        let time_ranges_compatible = time_ranges.time_ranges().all(|r| {
+            (r.min_ts == i64::MIN || is_aligned(r.min_ts as i128 * unit))
+                && (r.max_ts == i64::MAX || is_aligned((r.max_ts as i128 + 1) * unit))
+        });
+
+        group_by_compatible
+            && aggregates_compatible
+            && predicate_compatible
+            && time_ranges_compatible
+    }
+}
+
+fn unalias(expr: &Expr) -> &Expr {
+    match expr {
+        Expr::Alias(expr, _) => unalias(expr),
+        expr => expr,
+    }
+}
+
+/// Returns the `(interval, origin)` in nanoseconds of `date_bin(interval, time[, origin])`
+fn date_bin_of_time(expr: &Expr) -> Option<(i64, i64)> {
+    let Expr::ScalarFunction(ScalarFunction {
+        fun: BuiltinScalarFunction::DateBin,
+        args,
+    }) = expr
+    else {
+        return None;
+    };
+    let (interval, time, origin) = match args.as_slice() {
+        [interval, time] => (interval, time, None),
+        [interval, time, origin] => (interval, time, Some(origin)),
+        _ => return None,
+    };
+    if !matches!(time, Expr::Column(c) if c.name == TIME_FIELD_NAME) {
+        return None;
+    }
+
+    let interval = match interval {
+        Expr::Literal(ScalarValue::IntervalDayTime(Some(v))) => {
+            let (days, ms) = IntervalDayTimeType::to_parts(*v);
+            (days as i64)
+                .checked_mul(NANOS_PER_DAY)?
+                .checked_add(ms as i64 * 1_000_000)?
+        }
+        Expr::Literal(ScalarValue::IntervalMonthDayNano(Some(v))) => {
+            let (months, days, nanos) = IntervalMonthDayNanoType::to_parts(*v);
+            if months != 0 {
+                return None;
+            }
+            (days as i64)
+                .checked_mul(NANOS_PER_DAY)?
+                .checked_add(nanos)?
+        }
+        _ => return None,
+    };
+    let origin = match origin {
+        None => 0,
+        Some(Expr::Literal(ScalarValue::TimestampNanosecond(Some(v), _))) => *v,
+        Some(Expr::Literal(ScalarValue::TimestampMicrosecond(Some(v), _))) => {
+            v.checked_mul(1_000)?
+        }
+        Some(Expr::Literal(ScalarValue::TimestampMillisecond(Some(v), _))) => {
+            v.checked_mul(1_000_000)?
+        }
+        Some(Expr::Literal(ScalarValue::TimestampSecond(Some(v), _))) => {
+            v.checked_mul(1_000_000_000)?
+        }
+        _ => return None,
+    };
+
+    (interval > 0).then_some((interval, origin))
+}
+
+/// Returns the `(function, column)` of an aggregate over a single column
+fn aggregate_of_column(expr: &Expr) -> Option<(&AggregateFunction, &str)> {
+    match unalias(expr) {
+        Expr::AggregateFunction(AggregateFunctionExpr {
+            fun,
+            args,
+            distinct: false,
+            filter: None,
+            order_by: None,
+            ..
+        }) => match args.as_slice() {
+            [Expr::Column(c)] => Some((fun, c.name.as_str())),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn precision_nanos(precision: Precision) -> i64 {
+    match precision {
+        Precision::MS => 1_000_000,
+        Precision::US => 1_000,
+        Precision::NS => 1,
+    }
+}
+
+/// Returns the `(database, table)` of the rollup table and the rollup definition if the stream
+/// table belongs to a continuous aggregate over the `raw_table`
+fn rollup_of_stream_table<'a>(
+    stream_table: &'a StreamTable,
+    raw_table: &str,
+) -> Option<(&'a str, &'a str, RollupDefinition)> {
+    if stream_table.stream_type() != TSKV_STREAM_PROVIDER
+        || !stream_table.name().starts_with(ROLLUP_STREAM_TABLE_PREFIX)
+    {
+        return None;
+    }
+
+    let options = stream_table.extra_options();
+    if options.get(STREAM_TABLE_KEY).map(|e| e.as_str()) != Some(raw_table) {
+        return None;
+    }
+
+    Some((
+        options.get(ROLLUP_DB_KEY)?.as_str(),
+        options.get(ROLLUP_TABLE_KEY)?.as_str(),
+        RollupDefinition::from_options(options)?,
+    ))
+}
+
+/// The rollup tables of the continuous aggregates over `raw_table` and their definitions, ordered by name
+fn find_rollup_tables(
+    db_info: &DatabaseInfo,
+    raw_table: &str,
+) -> Vec<(String, String, RollupDefinition)> {
+    let mut rollups = db_info
+        .tables
+        .values()
+        .filter_map(|table| match table {
+            TableSchema::StreamTableSchema(stream_table) => {
+                rollup_of_stream_table(stream_table, raw_table)
+            }
+            _ => None,
+        })
+        .map(|(db, table, definition)| (db.to_string(), table.to_string(), definition))
+        .collect::<Vec<_>>();
+    rollups.sort_by(|a, b| (&a.0, &a.1).cmp(&(&b.0, &b.1)));
+    rollups
+}
+
+/// Returns the schema of the only tskv table read by the aggregate query of a continuous aggregate
+pub fn extract_raw_table(plan: &LogicalPlan) -> QueryResult<TskvTableSchemaRef> {
+    let mut visitor = ExtractTableScan::default();
+    plan.visit(&mut visitor)?;
+
+    let err = |reason: &str| {
+        Err(AnalyzerSnafu {
+            err: format!("The query of continuous aggregate {reason}"),
+        }
+        .build())
+    };
+
+    match visitor.sources.as_slice() {
+        [source] => match source_downcast_adapter(source)?.table_handle() {
+            TableHandle::Tskv(table) => Ok(table.table_schema()),
+            _ => err("must read from a tskv table"),
+        },
+        [] => err("must read from a table"),
+        _ => err("can only read from a single table"),
+    }
+}
+
+#[derive(Default)]
+struct ExtractTableScan {
+    sources: Vec<Arc<dyn TableSource>>,
+}
+
+impl TreeNodeVisitor for ExtractTableScan {
+    type N = LogicalPlan;
+
+    fn pre_visit(&mut self, plan: &LogicalPlan) -> DFResult<VisitRecursion> {
+        if let LogicalPlan::TableScan(TableScan { source, .. }) = plan {
+            self.sources.push(source.clone());
+        }
+
+        Ok(VisitRecursion::Continue)
+    }
+}
+
+/// Replaces the tables in the query matched by `is_raw_table` with `source`
+pub fn replace_table_in_query(
+    query: &mut Query,
+    is_raw_table: &impl Fn(&ObjectName) -> bool,
+    source: &ObjectName,
+) {
+    if let Some(with) = query.with.as_mut() {
+        for cte in with.cte_tables.iter_mut() {
+            replace_table_in_query(&mut cte.query, is_raw_table, source);
+        }
+    }
+
+    replace_table_in_set_expr(&mut query.body, is_raw_table, source);
+}
+
+fn replace_table_in_set_expr(
+    set_expr: &mut SetExpr,
+    is_raw_table: &impl Fn(&ObjectName) -> bool,
+    source: &ObjectName,
+) {
+    match set_expr {
+        SetExpr::Select(select) => {
+            for table in select.from.iter_mut() {
+                replace_table_in_table_with_joins(table, is_raw_table, source);
+            }
+        }
+        SetExpr::Query(query) => replace_table_in_query(query, is_raw_table, source),
+        SetExpr::SetOperation { left, right, .. } => {
+            replace_table_in_set_expr(left, is_raw_table, source);
+            replace_table_in_set_expr(right, is_raw_table, source);
+        }
+        _ => {}
+    }
+}
+
+fn replace_table_in_table_with_joins(
+    table: &mut TableWithJoins,
+    is_raw_table: &impl Fn(&ObjectName) -> bool,
+    source: &ObjectName,
+) {
+    replace_table_factor(&mut table.relation, is_raw_table, source);
+    for join in table.joins.iter_mut() {
+        replace_table_factor(&mut join.relation, is_raw_table, source);
+    }
+}
+
+fn replace_table_factor(
+    factor: &mut TableFactor,
+    is_raw_table: &impl Fn(&ObjectName) -> bool,
+    source: &ObjectName,
+) {
+    match factor {
+        TableFactor::Table { name, .. } if is_raw_table(name) => *name = source.clone(),
+        TableFactor::Derived { subquery, .. } => {
+            replace_table_in_query(subquery, is_raw_table, source)
+        }
+        TableFactor::NestedJoin {
+            table_with_joins, ..
+        } => replace_table_in_table_with_joins(table_with_joins, is_raw_table, source),
+        _ => {}
+    }
+}
+
+/// Returns the time ranges of the time column selected by the predicate,
+/// the timestamps have the same unit as the time column.
+fn predicate_time_ranges(predicate: &Expr, schema: &DFSchemaRef) -> DFResult<TimeRanges> {
+    let mut rewriter = TypeCoercionRewriter::new(schema.clone());
+    let expr = rewrite_preserving_name(predicate.clone(), &mut rewriter)?;
+    let props = ExecutionProps::new();
+    let mut const_evaluator = ConstEvaluator::try_new(&props)?;
+    let expr = expr.rewrite(&mut const_evaluator)?;
+
+    let domains = RowExpressionToDomainsVisitor::expr_to_column_domains(&expr)
+        .map_err(|e| DataFusionError::External(Box::new(e)))?;
+    let time_domain = domains
+        .translate_column(|c| (c.name == TIME_FIELD_NAME).then(|| TIME_FIELD_NAME.to_string()));
+
+    Ok(TimeRanges::new(filter_to_time_ranges(&time_domain)))
+}
+
+/// Names of all columns referenced by the expressions of the plan
+fn referenced_columns(plan: &LogicalPlan) -> DFResult<HashSet<String>> {
+    let mut visitor = ReferencedColumns::default();
+    plan.visit(&mut visitor)?;
+
+    Ok(visitor.columns.into_iter().map(|c| c.name).collect())
+}
+
+#[derive(Default)]
+struct ReferencedColumns {
+    columns: HashSet<Column>,
+}
+
+impl TreeNodeVisitor for ReferencedColumns {
+    type N = LogicalPlan;
+
+    fn pre_visit(&mut self, plan: &LogicalPlan) -> DFResult<VisitRecursion> {
+        for expr in plan.expressions() {
+            expr_to_columns(&expr, &mut self.columns)?;
+        }
+
+        Ok(VisitRecursion::Continue)
+    }
+}
+
+/// Rewrite the aggregates over the scans of tskv tables to read the rollup tables of their continuous aggregates
+///
+/// Triggering conditions:
+/// 1. The time range selected by the filter on the table has been expired by the TTL of the database or the table
+/// 2. The aggregate can be computed from the rollup table, see [`RollupDefinition`]
+/// 3. All columns of the table referenced by the query exist in the rollup table with the same data type
+pub struct RollupRewriter<'a, S> {
+    schema_provider: &'a S,
+}
+
+impl<'a, S: ContextProviderExtension> RollupRewriter<'a, S> {
+    pub fn new(schema_provider: &'a S) -> Self {
+        Self { schema_provider }
+    }
+
+    pub fn rewrite(&self, plan: LogicalPlan) -> QueryResult<LogicalPlan> {
+        let columns = referenced_columns(&plan)?;
+
+        let plan = plan.transform_up(&|plan| {
+            self.rewrite_aggregate(plan, &columns)
+                .map_err(|e| DataFusionError::External(Box::new(e)))
+        })?;
+
+        Ok(plan)
+    }
+
+    fn rewrite_aggregate(
+        &self,
+        plan: LogicalPlan,
+        columns: &HashSet<String>,
+    ) -> QueryResult<Transformed<LogicalPlan>> {
+        let LogicalPlan::Aggregate(aggregate) = &plan else {
+            return Ok(Transformed::No(plan));
+        };
+        let LogicalPlan::Filter(Filter {
+            predicate, input, ..
+        }) = aggregate.input.as_ref()
+        else {
+            return Ok(Transformed::No(plan));
+        };
+
+        // The table may be aliased
+        let (scan, alias) = match input.as_ref() {
+            LogicalPlan::TableScan(scan) => (scan, None),
+            LogicalPlan::SubqueryAlias(alias) => match alias.input.as_ref() {
+                LogicalPlan::TableScan(scan) => (scan, Some(input.as_ref())),
+                _ => return Ok(Transformed::No(plan)),
+            },
+            _ => return Ok(Transformed::No(plan)),
+        };
+
+        let new_scan =
+            match self.rewrite_scan(scan, aggregate, predicate, input.schema(), columns)? {
+                Some(new_scan) => new_scan,
+                None => return Ok(Transformed::No(plan)),
+            };
+
+        let new_input = match alias {
+            Some(alias) => alias.with_new_inputs(&[new_scan])?,
+            None => new_scan,
+        };
+        let new_filter = aggregate.input.with_new_inputs(&[new_input])?;
+
+        Ok(Transformed::Yes(plan.with_new_inputs(&[new_filter])?))
+    }
+
+    fn rewrite_scan(
+        &self,
+        scan: &TableScan,
+        aggregate: &Aggregate,
+        predicate: &Expr,
+        schema: &DFSchemaRef,
+        columns: &HashSet<String>,
+    ) -> QueryResult<Option<LogicalPlan>> {
+        let Ok(adapter) = source_downcast_adapter(&scan.source) else {
+            return Ok(None);
+        };
+        let TableHandle::Tskv(raw_table) = adapter.table_handle() else {
+            return Ok(None);
+        };
+        let raw_table = raw_table.table_schema();
+
+        let time_ranges = predicate_time_ranges(predicate, schema)?;
+        if time_ranges.is_empty() {
+            return Ok(None);
+        }
+
+        let Some(db_info) = self
+            .schema_provider
+            .get_db_info(adapter.database_name())
+            .context(MetaSnafu)?
+        else {
+            return Ok(None);
+        };
+
+        // The data is removed by both the TTL of the database and the TTL of the table
+        let db_time_to_expired = db_info.schema.time_to_expired();
+        let time_to_expired = raw_table
+            .time_to_expired()
+            .map_or(db_time_to_expired, |t| t.max(db_time_to_expired));
+        if time_ranges.max_ts() >= time_to_expired {
+            return Ok(None);
+        }
+
+        let unit = precision_nanos(raw_table.time_column_precision());
+        for (rollup_db, rollup_table, definition) in
+            find_rollup_tables(&db_info, adapter.table_name())
+        {
+            if !definition.can_answer(aggregate, predicate, &time_ranges, unit) {
+                continue;
+            }
+            let Some(rollup_schema) = self.rollup_table_schema(&rollup_db, &rollup_table)? else {
+                continue;
+            };
+            if !all_columns_exist(&adapter.schema(), &rollup_schema, columns) {
+                continue;
+            }
+
+            // Only the rollup table actually read is recorded as accessed by the query
+            let rollup_source = self
+                .schema_provider
+                .get_table_source(TableReference::partial(
+                    rollup_db.as_str(),
+                    rollup_table.as_str(),
+                ))?;
+
+            debug!(
+                "Rewrite the scan of table {}.{} to rollup table {rollup_db}.{rollup_table}",
+                adapter.database_name(),
+                adapter.table_name()
+            );
+            return Ok(Some(scan_rollup_table(
+                scan,
+                adapter.schema(),
+                rollup_source,
+            )?));
+        }
+
+        Ok(None)
+    }
+
+    fn rollup_table_schema(&self, database: &str, table: &str) -> QueryResult<Option<SchemaRef>> {
+        let db_info = self
+            .schema_provider
+            .get_db_info(database)
+            .context(MetaSnafu)?;
+
+        Ok(db_info.and_then(|db_info| match db_info.tables.get(table) {
+            Some(TableSchema::TsKvTableSchema(schema)) => Some(schema.to_arrow_schema()),
+            _ => None,
+        }))
+    }
+}
+
+fn all_columns_exist(
+    raw_schema: &SchemaRef,
+    rollup_schema: &SchemaRef,
+    columns: &HashSet<String>,
+) -> bool {
+    raw_schema
+        .fields()
+        .iter()
+        .filter(|f| columns.contains(f.name()))
+        .all(|f| {
+            rollup_schema
+                .field_with_name(f.name())
+                .is_ok_and(|rf| rf.data_type() == f.data_type())
+        })
+}
+
+fn scan_rollup_table(
+    scan: &TableScan,
+    raw_schema: SchemaRef,
+    rollup_source: Arc<TableSourceAdapter>,
+) -> QueryResult<LogicalPlan> {
+    let rollup_schema = rollup_source.schema();
+    let projection = scan
+        .projection
+        .as_ref()
+        .map(|indices| {
+            indices
+                .iter()
+                .map(|i| rollup_schema.index_of(raw_schema.field(*i).name()))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .transpose()
+        .map_err(DataFusionError::ArrowError)?;
+
+    let plan = LogicalPlanBuilder::scan_with_filters(
+        scan.table_name.clone(),
+        rollup_source as Arc<dyn TableSource>,
+        projection,
+        scan.filters.clone(),
+    )?
+    .build()?;
+
+    Ok(plan)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use datafusion::arrow::datatypes::{
+        DataType, Field, IntervalMonthDayNanoType, Schema, TimeUnit,
+    };
+    use datafusion::common::{Column, DFSchema, ToDFSchema};
+    use datafusion::logical_expr::aggregate_function::AggregateFunction;
+    use datafusion::logical_expr::expr::ScalarFunction;
+    use datafusion::logical_expr::{table_scan, Aggregate, BuiltinScalarFunction, LogicalPlan};
+    use datafusion::prelude::{avg, col, lit, max, sum, Expr};
+    use datafusion::scalar::ScalarValue;
+    use datafusion::sql::sqlparser::ast::{Ident, ObjectName, Statement};
+    use datafusion::sql::sqlparser::dialect::GenericDialect;
+    use datafusion::sql::sqlparser::parser::Parser;
+
+    use super::{predicate_time_ranges, replace_table_in_query, RollupDefinition};
+
+    const HOUR: i64 = 3_600_000_000_000;
+
+    fn ts(ns: i64) -> Expr {
+        lit(ScalarValue::TimestampNanosecond(Some(ns), None))
+    }
+
+    fn arrow_schema() -> Schema {
+        Schema::new(vec![
+            Field::new(
+                "time",
+                DataType::Timestamp(TimeUnit::Nanosecond, None),
+                false,
+            ),
+            Field::new("host", DataType::Utf8, true),
+            Field::new("usage", DataType::Float64, true),
+        ])
+    }
+
+    fn schema() -> Arc<DFSchema> {
+        arrow_schema().to_dfschema_ref().unwrap()
+    }
+
+    fn date_bin(interval: i64) -> Expr {
+        let interval = IntervalMonthDayNanoType::make_value(0, 0, interval);
+        Expr::ScalarFunction(ScalarFunction {
+            fun: BuiltinScalarFunction::DateBin,
+            args: vec![
+                lit(ScalarValue::IntervalMonthDayNano(Some(interval))),
+                col("time"),
+            ],
+        })
+    }
+
+    fn aggregate(predicate: Expr, group_expr: Vec<Expr>, aggr_expr: Vec<Expr>) -> Aggregate {
+        let plan = table_scan(Some("cpu"), &arrow_schema(), None)
+            .unwrap()
+            .filter(predicate)
+            .unwrap()
+            .aggregate(group_expr, aggr_expr)
+            .unwrap()
+            .build()
+            .unwrap();
+        match plan {
+            LogicalPlan::Aggregate(aggregate) => aggregate,
+            _ => panic!("expect aggregate"),
+        }
+    }
+
+    /// The plan of `SELECT date_bin(interval, time) AS time, host, <aggr_expr> AS usage FROM cpu GROUP BY 1, 2`
+    fn continuous_aggregate(interval: i64, aggr_expr: Expr) -> LogicalPlan {
+        let output = |e: &Expr| Expr::Column(Column::from_name(e.display_name().unwrap()));
+        table_scan(Some("cpu"), &arrow_schema(), None)
+            .unwrap()
+            .aggregate(
+                vec![date_bin(interval), col("host")],
+                vec![aggr_expr.clone()],
+            )
+            .unwrap()
+            .project(vec![
+                output(&date_bin(interval)).alias("time"),
+                col("host"),
+                output(&aggr_expr).alias("usage"),
+            ])
+            .unwrap()
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_replace_table_in_query() {
+        let sql = "WITH t AS (SELECT * FROM db.cpu) \
+            SELECT date_bin(INTERVAL '1 hour', time) AS time, avg(usage) AS usage \
+            FROM (SELECT * FROM cpu) AS c JOIN t ON c.time = t.time JOIN mem ON c.time = mem.time \
+            GROUP BY date_bin(INTERVAL '1 hour', time)";
+        let mut statements = Parser::parse_sql(&GenericDialect {}, sql).unwrap();
+        let Statement::Query(mut query) = statements.remove(0) else {
+            panic!("expect query")
+        };
+
+        let source = ObjectName(vec![Ident::new("db"), Ident::new("__rollup_cpu")]);
+        let is_raw_table =
+            |name: &ObjectName| name.0.last().map(|e| e.value.as_str()) == Some("cpu");
+        replace_table_in_query(&mut query, &is_raw_table, &source);
+
+        assert_eq!(
+            "WITH t AS (SELECT * FROM db.__rollup_cpu) \
+            SELECT date_bin(INTERVAL '1 hour', time) AS time, avg(usage) AS usage \
+            FROM (SELECT * FROM db.__rollup_cpu) AS c JOIN t ON c.time = t.time JOIN mem ON c.time = mem.time \
+            GROUP BY date_bin(INTERVAL '1 hour', time)",
+            query.to_string()
+        );
+    }
+
+    #[test]
+    fn test_predicate_time_ranges() {
+        let schema = schema();
+        let max_ts = |predicate: &Expr| {
+            let time_ranges = predicate_time_ranges(predicate, &schema).unwrap();
+            (!time_ranges.is_empty()).then(|| time_ranges.max_ts())
+        };
+
+        let predicate = col("time")
+            .lt(lit("1970-01-01T00:00:01"))
+            .and(col("usage").gt(lit(0.5)));
+        assert_eq!(max_ts(&predicate), Some(999_999_999));
+
+        let predicate = col("time").lt_eq(ts(1_000));
+        assert_eq!(max_ts(&predicate), Some(1_000));
+
+        // unbounded
+        let predicate = col("time").gt(ts(1_000));
+        assert_eq!(max_ts(&predicate), Some(i64::MAX));
+        let predicate = col("usage").gt(lit(0.5));
+        assert_eq!(max_ts(&predicate), Some(i64::MAX));
+
+        // no data is selected
+        let predicate = col("time").lt(ts(1_000)).and(col("time").gt(ts(2_000)));
+        assert_eq!(max_ts(&predicate), None);
+    }
+
+    #[test]
+    fn test_rollup_definition() {
+        let definition =
+            RollupDefinition::try_from_plan(&continuous_aggregate(HOUR, max(col("usage"))))
+                .unwrap();
+        assert_eq!(
+            definition,
+            RollupDefinition {
+                interval: HOUR,
+                origin: 0,
+                aggregates: HashMap::from([("usage".to_string(), AggregateFunction::Max)]),
+            }
+        );
+
+        let options = HashMap::from_iter(definition.to_options());
+        assert_eq!(RollupDefinition::from_options(&options), Some(definition));
+
+        // The average of the averages of the buckets is not the average of the rows
+        assert_eq!(
+            RollupDefinition::try_from_plan(&continuous_aggregate(HOUR, avg(col("usage")))),
+            None
+        );
+    }
+
+    #[test]
+    fn test_rollup_can_answer() {
+        let schema = schema();
+        let definition =
+            RollupDefinition::try_from_plan(&continuous_aggregate(HOUR, max(col("usage"))))
+                .unwrap();
+        let can_answer = |predicate: Expr, group_expr: Vec<Expr>, aggr_expr: Vec<Expr>| {
+            let time_ranges = predicate_time_ranges(&predicate, &schema).unwrap();
+            let aggregate = aggregate(predicate.clone(), group_expr, aggr_expr);
+            definition.can_answer(&aggregate, &predicate, &time_ranges, 1)
+        };
+        let whole_hours = col("time")
+            .gt_eq(ts(HOUR))
+            .and(col("time").lt(ts(5 * HOUR)));
+
+        assert!(can_answer(
+            whole_hours.clone(),
+            vec![date_bin(2 * HOUR), col("host")],
+            vec![max(col("usage"))]
+        ));
+        assert!(can_answer(
+            whole_hours.clone().and(col("host").eq(lit("a"))),
+            vec![],
+            vec![max(col("usage"))]
+        ));
+
+        // Incompatible aggregate functions
+        assert!(!can_answer(
+            whole_hours.clone(),
+            vec![date_bin(2 * HOUR)],
+            vec![sum(col("usage"))]
+        ));
+        assert!(!can_answer(
+            whole_hours.clone(),
+            vec![date_bin(2 * HOUR)],
+            vec![avg(col("usage"))]
+        ));
+        // Finer buckets
+        assert!(!can_answer(
+            whole_hours.clone(),
+            vec![date_bin(HOUR / 2)],
+            vec![max(col("usage"))]
+        ));
+        // Partial buckets
+        assert!(!can_answer(
+            col("time").lt(ts(HOUR / 2)),
+            vec![],
+            vec![max(col("usage"))]
+        ));
+        // Filter on the aggregated column
+        assert!(!can_answer(
+            whole_hours.and(col("usage").gt(lit(0.5))),
+            vec![],
+            vec![max(col("usage"))]
+        ));
+    }
+}
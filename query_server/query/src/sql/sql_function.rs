@@ -0,0 +1,174 @@
+//! SQL functions created by `CREATE FUNCTION ... LANGUAGE SQL`.
+//!
+//! The body of a SQL function is an expression of its arguments, the calls of the function are
+//! replaced by the body with the arguments substituted by [`SqlFunctionRewriter`] when planning.
+
+use std::collections::HashMap;
+
+use datafusion::common::tree_node::{Transformed, TreeNode, TreeNodeRewriter, VisitRecursion};
+use datafusion::common::{DFField, DFSchema, Result as DFResult};
+use datafusion::error::DataFusionError;
+use datafusion::logical_expr::expr::{Cast, ScalarUDF};
+use datafusion::logical_expr::expr_rewriter::rewrite_preserving_name;
+use datafusion::logical_expr::utils::from_plan;
+use datafusion::logical_expr::LogicalPlan;
+use datafusion::prelude::Expr;
+use datafusion::sql::planner::{PlannerContext, SqlToRel};
+use datafusion::sql::sqlparser::ast::Expr as SQLExpr;
+use datafusion::sql::sqlparser::parser::Parser;
+use models::schema::function_schema::{FunctionBody, FunctionSchema};
+use spi::QueryResult;
+
+use super::dialect::CnosDBDialect;
+use crate::metadata::ContextProviderExtension;
+
+/// Maximum depth of the SQL functions calling other SQL functions
+const MAX_EXPANSION_DEPTH: usize = 16;
+
+/// Parse the body of a SQL function
+pub fn parse_sql_function_body(body: &str) -> DFResult<SQLExpr> {
+    let expr = Parser::new(&CnosDBDialect {})
+        .try_with_sql(body)?
+        .parse_expr()?;
+    Ok(expr)
+}
+
+/// Plan the body of the SQL function, whose columns are the arguments
+pub fn sql_function_body_to_expr<S: ContextProviderExtension>(
+    df_planner: &SqlToRel<'_, S>,
+    function: &FunctionSchema,
+    body: &str,
+) -> DFResult<Expr> {
+    let fields = function
+        .args
+        .iter()
+        .map(|arg| DFField::new_unqualified(&arg.name, arg.data_type.clone(), true))
+        .collect::<Vec<_>>();
+    let schema = DFSchema::new_with_metadata(fields, HashMap::new())?;
+
+    df_planner.sql_to_expr(
+        parse_sql_function_body(body)?,
+        &schema,
+        &mut PlannerContext::new(),
+    )
+}
+
+/// Replace the calls of SQL functions by their bodies
+pub struct SqlFunctionRewriter<'a, 'b, S: ContextProviderExtension> {
+    schema_provider: &'a S,
+    df_planner: &'a SqlToRel<'b, S>,
+}
+
+impl<'a, 'b, S: ContextProviderExtension> SqlFunctionRewriter<'a, 'b, S> {
+    pub fn new(schema_provider: &'a S, df_planner: &'a SqlToRel<'b, S>) -> Self {
+        Self {
+            schema_provider,
+            df_planner,
+        }
+    }
+
+    pub fn rewrite(&self, plan: LogicalPlan) -> QueryResult<LogicalPlan> {
+        let plan = plan.transform_up(&|plan| self.rewrite_plan(plan))?;
+        Ok(plan)
+    }
+
+    fn rewrite_plan(&self, plan: LogicalPlan) -> DFResult<Transformed<LogicalPlan>> {
+        let exprs = plan.expressions();
+        if !exprs.iter().any(|expr| self.contains_sql_function(expr)) {
+            return Ok(Transformed::No(plan));
+        }
+
+        let mut expander = SqlFunctionExpander {
+            rewriter: self,
+            depth: 0,
+        };
+        let new_exprs = exprs
+            .into_iter()
+            .map(|expr| rewrite_preserving_name(expr, &mut expander))
+            .collect::<DFResult<Vec<_>>>()?;
+        let inputs = plan.inputs().into_iter().cloned().collect::<Vec<_>>();
+
+        Ok(Transformed::Yes(from_plan(&plan, &new_exprs, &inputs)?))
+    }
+
+    fn sql_function(&self, expr: &Expr) -> Option<(FunctionSchema, String)> {
+        let Expr::ScalarUDF(ScalarUDF { fun, .. }) = expr else {
+            return None;
+        };
+        let function = self.schema_provider.get_function_schema(&fun.name)?;
+        match &function.body {
+            FunctionBody::Sql(body) => {
+                let body = body.clone();
+                Some((function, body))
+            }
+            FunctionBody::Wasm(_) => None,
+        }
+    }
+
+    fn contains_sql_function(&self, expr: &Expr) -> bool {
+        let mut found = false;
+        let _ = expr.apply(&mut |e| {
+            if self.sql_function(e).is_some() {
+                found = true;
+                return Ok(VisitRecursion::Stop);
+            }
+            Ok(VisitRecursion::Continue)
+        });
+        found
+    }
+
+    /// The body with the arguments substituted, which is cast to the return type of the function.
+    fn expand(&self, function: &FunctionSchema, body: &str, args: &[Expr]) -> DFResult<Expr> {
+        let body = sql_function_body_to_expr(self.df_planner, function, body)?;
+        let expr = body.transform_up(&|expr| {
+            let Expr::Column(column) = &expr else {
+                return Ok(Transformed::No(expr));
+            };
+            match function.args.iter().position(|arg| arg.name == column.name) {
+                Some(idx) => Ok(Transformed::Yes(args[idx].clone())),
+                None => Ok(Transformed::No(expr)),
+            }
+        })?;
+
+        Ok(Expr::Cast(Cast::new(
+            Box::new(expr),
+            function.return_type.clone(),
+        )))
+    }
+}
+
+struct SqlFunctionExpander<'r, 'a, 'b, S: ContextProviderExtension> {
+    rewriter: &'r SqlFunctionRewriter<'a, 'b, S>,
+    depth: usize,
+}
+
+impl<'r, 'a, 'b, S: ContextProviderExtension> TreeNodeRewriter
+    for SqlFunctionExpander<'r, 'a, 'b, S>
+{
+    type N = Expr;
+
+    fn mutate(&mut self, expr: Expr) -> DFResult<Expr> {
+        let Some((function, body)) = self.rewriter.sql_function(&expr) else {
+            return Ok(expr);
+        };
+        let Expr::ScalarUDF(ScalarUDF { args, .. }) = &expr else {
+            return Ok(expr);
+        };
+
+        if self.depth >= MAX_EXPANSION_DEPTH {
+            return Err(DataFusionError::Plan(format!(
+                "SQL function {} exceeds the max expansion depth {}, it may be recursive",
+                function.name, MAX_EXPANSION_DEPTH
+            )));
+        }
+
+        let expanded = self.rewriter.expand(&function, &body, args)?;
+
+        // The body may call other SQL functions
+        self.depth += 1;
+        let expanded = expanded.rewrite(self);
+        self.depth -= 1;
+
+        expanded
+    }
+}
@@ -0,0 +1,221 @@
+//! Substrait plans, consumed by the DataFusion substrait consumer.
+//!
+//! The consumer reads the named tables of a plan from a [`SessionContext`], so the tables are
+//! resolved by the [`ContextProviderExtension`] beforehand, which also records the accessed
+//! databases for the privilege check, and are registered into the context of the session.
+
+use std::sync::Arc;
+
+use datafusion::catalog::catalog::{CatalogProvider, MemoryCatalogProvider};
+use datafusion::catalog::schema::{MemorySchemaProvider, SchemaProvider};
+use datafusion::datasource::TableProvider;
+use datafusion::logical_expr::LogicalPlan;
+use datafusion::prelude::SessionContext;
+use datafusion::sql::{OwnedTableReference, TableReference};
+use datafusion_substrait::logical_plan::consumer::from_substrait_plan;
+use datafusion_substrait::serializer::deserialize_bytes;
+use datafusion_substrait::substrait::proto::plan_rel::RelType as PlanRelType;
+use datafusion_substrait::substrait::proto::read_rel::ReadType;
+use datafusion_substrait::substrait::proto::rel::RelType;
+use datafusion_substrait::substrait::proto::{Plan, Rel};
+use spi::query::session::SessionCtx;
+use spi::{QueryError, QueryResult};
+
+use crate::data_source::table_source::TableHandle;
+use crate::metadata::ContextProviderExtension;
+
+/// Convert the bytes of a serialized substrait plan into a DataFusion logical plan.
+pub async fn substrait_to_df_plan<S: ContextProviderExtension>(
+    schema_provider: &S,
+    session: &SessionCtx,
+    bytes: &[u8],
+) -> QueryResult<LogicalPlan> {
+    let plan = deserialize_bytes(bytes.to_vec()).await?;
+
+    let mut ctx = SessionContext::with_state(session.inner().clone());
+    for table_ref in named_tables(&plan) {
+        let table_source = schema_provider.get_table_source(table_ref.clone())?;
+        let table: Arc<dyn TableProvider> = match table_source.table_handle() {
+            TableHandle::TableProvider(t) => t.clone(),
+            TableHandle::External(t) => t.clone(),
+            TableHandle::Tskv(t) => t.clone(),
+            TableHandle::StreamProvider(_) => {
+                return Err(QueryError::NotImplemented {
+                    err: format!("Substrait plan reading stream table {}", table_ref),
+                })
+            }
+        };
+        register_table(&ctx, table_ref, table)?;
+    }
+
+    Ok(from_substrait_plan(&mut ctx, &plan).await?)
+}
+
+/// Register the table where the consumer looks for it, i.e. resolved by the default catalog and
+/// schema of the context.
+fn register_table(
+    ctx: &SessionContext,
+    table_ref: OwnedTableReference,
+    table: Arc<dyn TableProvider>,
+) -> QueryResult<()> {
+    let state = ctx.state();
+    let options = &state.config_options().catalog;
+    let resolved = table_ref.resolve(&options.default_catalog, &options.default_schema);
+
+    let catalog = match ctx.catalog(&resolved.catalog) {
+        Some(catalog) => catalog,
+        None => {
+            let catalog: Arc<dyn CatalogProvider> = Arc::new(MemoryCatalogProvider::new());
+            ctx.register_catalog(resolved.catalog.as_ref(), catalog.clone());
+            catalog
+        }
+    };
+    let schema = match catalog.schema(&resolved.schema) {
+        Some(schema) => schema,
+        None => {
+            let schema: Arc<dyn SchemaProvider> = Arc::new(MemorySchemaProvider::new());
+            catalog.register_schema(&resolved.schema, schema.clone())?;
+            schema
+        }
+    };
+    if !schema.table_exist(&resolved.table) {
+        schema.register_table(resolved.table.to_string(), table)?;
+    }
+
+    Ok(())
+}
+
+/// Names of the tables read by the plan, without duplicates.
+pub fn named_tables(plan: &Plan) -> Vec<OwnedTableReference> {
+    let mut tables = vec![];
+    for plan_rel in &plan.relations {
+        match &plan_rel.rel_type {
+            Some(PlanRelType::Rel(rel)) => collect_named_tables(rel, &mut tables),
+            Some(PlanRelType::Root(root)) => {
+                if let Some(rel) = &root.input {
+                    collect_named_tables(rel, &mut tables)
+                }
+            }
+            None => {}
+        }
+    }
+    tables
+}
+
+fn collect_named_tables(rel: &Rel, tables: &mut Vec<OwnedTableReference>) {
+    let inputs: Vec<&Rel> = match &rel.rel_type {
+        Some(RelType::Read(read)) => {
+            if let Some(ReadType::NamedTable(named_table)) = &read.read_type {
+                if let Some(table) = to_table_reference(&named_table.names) {
+                    if !tables.contains(&table) {
+                        tables.push(table);
+                    }
+                }
+            }
+            vec![]
+        }
+        Some(RelType::Filter(r)) => r.input.as_deref().into_iter().collect(),
+        Some(RelType::Fetch(r)) => r.input.as_deref().into_iter().collect(),
+        Some(RelType::Aggregate(r)) => r.input.as_deref().into_iter().collect(),
+        Some(RelType::Sort(r)) => r.input.as_deref().into_iter().collect(),
+        Some(RelType::Project(r)) => r.input.as_deref().into_iter().collect(),
+        Some(RelType::ExtensionSingle(r)) => r.input.as_deref().into_iter().collect(),
+        Some(RelType::Join(r)) => [r.left.as_deref(), r.right.as_deref()]
+            .into_iter()
+            .flatten()
+            .collect(),
+        Some(RelType::Cross(r)) => [r.left.as_deref(), r.right.as_deref()]
+            .into_iter()
+            .flatten()
+            .collect(),
+        Some(RelType::Set(r)) => r.inputs.iter().collect(),
+        Some(RelType::ExtensionMulti(r)) => r.inputs.iter().collect(),
+        _ => vec![],
+    };
+
+    for input in inputs {
+        collect_named_tables(input, tables);
+    }
+}
+
+/// The same as the substrait consumer: `[table]`, `[schema, table]` or `[catalog, schema, table]`.
+fn to_table_reference(names: &[String]) -> Option<OwnedTableReference> {
+    match names {
+        [] => None,
+        [table] => Some(TableReference::bare(table.clone())),
+        [schema, table] => Some(TableReference::partial(schema.clone(), table.clone())),
+        [catalog, schema, table, ..] => Some(TableReference::full(
+            catalog.clone(),
+            schema.clone(),
+            table.clone(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use datafusion::sql::TableReference;
+    use datafusion_substrait::substrait::proto::plan_rel::RelType as PlanRelType;
+    use datafusion_substrait::substrait::proto::read_rel::{NamedTable, ReadType};
+    use datafusion_substrait::substrait::proto::rel::RelType;
+    use datafusion_substrait::substrait::proto::{
+        JoinRel, Plan, PlanRel, ProjectRel, ReadRel, Rel, RelRoot,
+    };
+
+    use super::named_tables;
+
+    fn read(names: &[&str]) -> Rel {
+        Rel {
+            rel_type: Some(RelType::Read(Box::new(ReadRel {
+                read_type: Some(ReadType::NamedTable(NamedTable {
+                    names: names.iter().map(|n| n.to_string()).collect(),
+                    ..Default::default()
+                })),
+                ..Default::default()
+            }))),
+        }
+    }
+
+    #[test]
+    fn test_named_tables() {
+        let project = Rel {
+            rel_type: Some(RelType::Project(Box::new(ProjectRel {
+                input: Some(Box::new(read(&["db1", "cpu"]))),
+                ..Default::default()
+            }))),
+        };
+        let join = Rel {
+            rel_type: Some(RelType::Join(Box::new(JoinRel {
+                left: Some(Box::new(read(&["air"]))),
+                right: Some(Box::new(project)),
+                ..Default::default()
+            }))),
+        };
+        let plan = Plan {
+            relations: vec![
+                PlanRel {
+                    rel_type: Some(PlanRelType::Root(RelRoot {
+                        input: Some(join),
+                        names: vec![],
+                    })),
+                },
+                PlanRel {
+                    rel_type: Some(PlanRelType::Rel(read(&["cnosdb", "public", "air"]))),
+                },
+                PlanRel {
+                    rel_type: Some(PlanRelType::Rel(read(&["air"]))),
+                },
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            named_tables(&plan),
+            vec![
+                TableReference::bare("air"),
+                TableReference::partial("db1", "cpu"),
+                TableReference::full("cnosdb", "public", "air"),
+            ]
+        );
+    }
+}
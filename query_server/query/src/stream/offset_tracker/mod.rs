@@ -21,6 +21,16 @@ impl OffsetTracker {
         }
     }
 
+    /// Resume from the processed offsets of a checkpoint
+    pub fn restore(&self, processed_offsets: HashMap<String, Offset>) {
+        *self.processed_offsets.write() = processed_offsets;
+        self.available_offsets.write().clear();
+    }
+
+    pub fn processed_offsets(&self) -> HashMap<String, Offset> {
+        self.processed_offsets.read().clone()
+    }
+
     pub fn has_available_offsets(&self) -> bool {
         !self.available_offsets.read().is_empty()
     }
@@ -0,0 +1,447 @@
+//! Disk-backed state store, so that a stream query can be resumed after restart.
+//!
+//! Layout under the base directory of [`DiskStateStoreFactory`]:
+//!
+//! ```text
+//! <query_id>/
+//!     checkpoint.json          the last checkpoint, replaced atomically
+//!     <partition>_<operator>/
+//!         <version>.arrow      states committed at the version, in Arrow IPC file format
+//! ```
+//!
+//! A version committed by [`StateStore::commit`] is only recovered after a checkpoint refers to it,
+//! segments of later versions are discarded when the state store is reopened.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Write};
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+
+use datafusion::arrow::error::ArrowError;
+use datafusion::arrow::ipc::reader::FileReader;
+use datafusion::arrow::ipc::writer::FileWriter;
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::common::{DataFusionError, Result};
+use datafusion::physical_plan::expressions::NotExpr;
+use datafusion::physical_plan::PhysicalExpr;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+
+use super::{Checkpoint, StateStore, StateStoreFactory};
+use crate::extension::utils::batch_filter;
+
+const CHECKPOINT_FILE_NAME: &str = "checkpoint.json";
+const SEGMENT_FILE_EXTENSION: &str = "arrow";
+const TMP_FILE_EXTENSION: &str = "tmp";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CheckpointFile {
+    checkpoint: Checkpoint,
+    /// Committed version of every state store, keyed by `<partition>_<operator>`
+    versions: BTreeMap<String, i64>,
+}
+
+#[derive(Debug)]
+pub struct DiskStateStoreFactory {
+    base_dir: PathBuf,
+    state_store_map: RwLock<HashMap<(String, usize, usize), Arc<DiskStateStore>>>,
+}
+
+impl DiskStateStoreFactory {
+    pub fn new(base_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            base_dir: base_dir.into(),
+            state_store_map: Default::default(),
+        }
+    }
+
+    /// Remove all the states and the checkpoint of the query
+    pub fn clear(&self, query_id: &str) -> Result<()> {
+        self.state_store_map
+            .write()
+            .retain(|(id, _, _), _| id != query_id);
+
+        let dir = self.query_dir(query_id);
+        if dir.exists() {
+            trace::debug!("Remove states of stream query {} in {:?}", query_id, dir);
+            fs::remove_dir_all(dir)?;
+        }
+
+        Ok(())
+    }
+
+    fn query_dir(&self, query_id: &str) -> PathBuf {
+        self.base_dir.join(query_id)
+    }
+
+    fn read_checkpoint_file(&self, query_id: &str) -> Result<Option<CheckpointFile>> {
+        let path = self.query_dir(query_id).join(CHECKPOINT_FILE_NAME);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let reader = BufReader::new(File::open(path)?);
+        let file = serde_json::from_reader(reader)
+            .map_err(|err| DataFusionError::External(Box::new(err)))?;
+
+        Ok(Some(file))
+    }
+}
+
+impl StateStoreFactory for DiskStateStoreFactory {
+    type SS = DiskStateStore;
+
+    fn get_or_default(
+        &self,
+        query_id: String,
+        partition_id: usize,
+        operator_id: usize,
+    ) -> Result<Arc<Self::SS>> {
+        let key = (query_id, partition_id, operator_id);
+        if let Some(state_store) = self.state_store_map.read().get(&key) {
+            return Ok(state_store.clone());
+        }
+
+        let mut state_store_map = self.state_store_map.write();
+        if let Some(state_store) = state_store_map.get(&key) {
+            return Ok(state_store.clone());
+        }
+
+        let name = state_store_name(partition_id, operator_id);
+        let version = self
+            .read_checkpoint_file(&key.0)?
+            .and_then(|file| file.versions.get(&name).copied())
+            .unwrap_or_default();
+        let dir = self.query_dir(&key.0).join(name);
+        let state_store = Arc::new(DiskStateStore::try_open(dir, version)?);
+        state_store_map.insert(key, state_store.clone());
+
+        Ok(state_store)
+    }
+
+    fn checkpoint(&self, query_id: &str, checkpoint: Checkpoint) -> Result<()> {
+        let state_stores = self
+            .state_store_map
+            .read()
+            .iter()
+            .filter(|((id, _, _), _)| id == query_id)
+            .map(|((_, partition_id, operator_id), state_store)| {
+                (
+                    state_store_name(*partition_id, *operator_id),
+                    state_store.clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        // State stores that have not been opened since restart keep their versions
+        let mut versions = self
+            .read_checkpoint_file(query_id)?
+            .map(|file| file.versions)
+            .unwrap_or_default();
+        for (name, state_store) in state_stores.iter() {
+            versions.insert(name.clone(), state_store.version());
+        }
+
+        let file = CheckpointFile {
+            checkpoint,
+            versions,
+        };
+        trace::trace!("Checkpoint stream query {}: {:?}", query_id, file);
+        let content =
+            serde_json::to_vec(&file).map_err(|err| DataFusionError::External(Box::new(err)))?;
+        let dir = self.query_dir(query_id);
+        fs::create_dir_all(&dir)?;
+        write_atomically(&dir.join(CHECKPOINT_FILE_NAME), |writer| {
+            writer.write_all(&content)?;
+            Ok(())
+        })?;
+
+        // Segments before the checkpoint will never be read again
+        for (name, state_store) in state_stores {
+            if let Some(version) = file.versions.get(&name) {
+                state_store.remove_segments(|v| v < *version)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn last_checkpoint(&self, query_id: &str) -> Result<Option<Checkpoint>> {
+        Ok(self
+            .read_checkpoint_file(query_id)?
+            .map(|file| file.checkpoint))
+    }
+}
+
+#[derive(Debug)]
+pub struct DiskStateStore {
+    dir: PathBuf,
+    /// Version of the committed states
+    version: AtomicI64,
+    committed: RwLock<Vec<RecordBatch>>,
+    uncommitted: RwLock<Vec<RecordBatch>>,
+}
+
+impl DiskStateStore {
+    /// Open the state store at the version of the last checkpoint
+    fn try_open(dir: PathBuf, version: i64) -> Result<Self> {
+        fs::create_dir_all(&dir)?;
+
+        let committed = if version > 0 {
+            read_segment(&segment_path(&dir, version))?
+        } else {
+            vec![]
+        };
+        let state_store = Self {
+            dir,
+            version: AtomicI64::new(version),
+            committed: RwLock::new(committed),
+            uncommitted: Default::default(),
+        };
+        // Versions committed after the checkpoint are not consistent with the checkpoint
+        state_store.remove_segments(|v| v > version)?;
+
+        Ok(state_store)
+    }
+
+    pub fn version(&self) -> i64 {
+        self.version.load(Ordering::Acquire)
+    }
+
+    /// Remove segment files of the versions matching the predicate, and files not completely written
+    fn remove_segments(&self, predicate: impl Fn(i64) -> bool) -> Result<()> {
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            let remove = match path.extension().and_then(|e| e.to_str()) {
+                Some(SEGMENT_FILE_EXTENSION) => path
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .and_then(|s| s.parse::<i64>().ok())
+                    .map(&predicate)
+                    .unwrap_or(false),
+                Some(TMP_FILE_EXTENSION) => true,
+                _ => false,
+            };
+            if remove {
+                trace::trace!("Remove state segment {:?}", path);
+                fs::remove_file(path)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl StateStore for DiskStateStore {
+    fn put(&self, batch: RecordBatch) -> Result<()> {
+        trace::trace!("Write batch to DiskStateStore: {:?}", batch);
+        self.uncommitted.write().push(batch);
+
+        Ok(())
+    }
+
+    fn expire(&self, predicate: Arc<dyn PhysicalExpr>) -> Result<Vec<RecordBatch>> {
+        trace::debug!("Remove batches match {} from DiskStateStore", predicate);
+        let remained: Arc<dyn PhysicalExpr> = Arc::new(NotExpr::new(predicate.clone()));
+
+        let mut uncommitted = self.uncommitted.write();
+        let expired_data = uncommitted
+            .iter()
+            .map(|e| batch_filter(e, &predicate))
+            .collect::<Result<Vec<_>>>()?;
+        *uncommitted = uncommitted
+            .iter()
+            .map(|e| batch_filter(e, &remained))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(expired_data)
+    }
+
+    fn commit(&self) -> Result<i64> {
+        let batches = mem::take(&mut *self.uncommitted.write());
+        let version = self.version() + 1;
+        trace::trace!("DiskStateStore {:?} commit version {}", self.dir, version);
+
+        write_segment(&segment_path(&self.dir, version), &batches)?;
+        *self.committed.write() = batches;
+        self.version.store(version, Ordering::Release);
+
+        Ok(version)
+    }
+
+    fn state(&self) -> Result<Vec<RecordBatch>> {
+        trace::trace!("Read all states from DiskStateStore");
+
+        Ok(self.committed.read().clone())
+    }
+}
+
+fn state_store_name(partition_id: usize, operator_id: usize) -> String {
+    format!("{}_{}", partition_id, operator_id)
+}
+
+fn segment_path(dir: &Path, version: i64) -> PathBuf {
+    dir.join(format!("{}.{}", version, SEGMENT_FILE_EXTENSION))
+}
+
+/// Read the states of a segment, an empty segment means no states
+fn read_segment(path: &Path) -> Result<Vec<RecordBatch>> {
+    if !path.exists() {
+        return Err(DataFusionError::Execution(format!(
+            "State segment {:?} of the checkpoint is missing",
+            path
+        )));
+    }
+    if fs::metadata(path)?.len() == 0 {
+        return Ok(vec![]);
+    }
+
+    let corrupted = |err: ArrowError| {
+        DataFusionError::Execution(format!("State segment {:?} is corrupted: {}", path, err))
+    };
+    let reader = FileReader::try_new(BufReader::new(File::open(path)?), None).map_err(corrupted)?;
+    reader
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(corrupted)
+}
+
+fn write_segment(path: &Path, batches: &[RecordBatch]) -> Result<()> {
+    // An empty segment, so that it is not taken as missing
+    let schema = match batches.first() {
+        Some(batch) => batch.schema(),
+        None => return write_atomically(path, |_| Ok(())),
+    };
+
+    write_atomically(path, |writer| {
+        let mut file_writer = FileWriter::try_new(writer, &schema)?;
+        for batch in batches {
+            file_writer.write(batch)?;
+        }
+        file_writer.finish()?;
+        Ok(())
+    })
+}
+
+/// Write to a temporary file, then rename it to the path,
+/// so that the file at the path is always complete.
+fn write_atomically(
+    path: &Path,
+    write: impl FnOnce(&mut BufWriter<&File>) -> Result<()>,
+) -> Result<()> {
+    let tmp_path = path.with_extension(TMP_FILE_EXTENSION);
+    let file = File::create(&tmp_path)?;
+    {
+        let mut writer = BufWriter::new(&file);
+        write(&mut writer)?;
+        writer.flush()?;
+    }
+    file.sync_all()?;
+    fs::rename(tmp_path, path)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use datafusion::arrow::array::Int64Array;
+    use datafusion::arrow::datatypes::{DataType, Field, Schema};
+    use datafusion::arrow::record_batch::RecordBatch;
+
+    use super::DiskStateStoreFactory;
+    use crate::stream::state_store::{Checkpoint, StateStore, StateStoreFactory};
+
+    fn batch(values: Vec<i64>) -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int64, false)]));
+        RecordBatch::try_new(schema, vec![Arc::new(Int64Array::from(values))]).unwrap()
+    }
+
+    fn checkpoint(batch_id: i64) -> Checkpoint {
+        Checkpoint {
+            batch_id,
+            watermark_ns: batch_id * 1000,
+            offsets: HashMap::from([("source".to_string(), batch_id * 10)]),
+        }
+    }
+
+    #[test]
+    fn test_restore_from_checkpoint() {
+        let dir = tempfile::tempdir().unwrap();
+        let query_id = "1";
+
+        {
+            let factory = DiskStateStoreFactory::new(dir.path());
+            assert_eq!(factory.last_checkpoint(query_id).unwrap(), None);
+
+            let state_store = factory.get_or_default(query_id.to_string(), 0, 0).unwrap();
+            state_store.put(batch(vec![1, 2])).unwrap();
+            assert_eq!(state_store.commit().unwrap(), 1);
+            factory.checkpoint(query_id, checkpoint(1)).unwrap();
+
+            // Committed, but the checkpoint is not written
+            state_store.put(batch(vec![3])).unwrap();
+            assert_eq!(state_store.commit().unwrap(), 2);
+            assert_eq!(state_store.state().unwrap(), vec![batch(vec![3])]);
+        }
+
+        let factory = DiskStateStoreFactory::new(dir.path());
+        assert_eq!(
+            factory.last_checkpoint(query_id).unwrap(),
+            Some(checkpoint(1))
+        );
+        let state_store = factory.get_or_default(query_id.to_string(), 0, 0).unwrap();
+        assert_eq!(state_store.version(), 1);
+        assert_eq!(state_store.state().unwrap(), vec![batch(vec![1, 2])]);
+
+        // Empty states
+        assert_eq!(state_store.commit().unwrap(), 2);
+        factory.checkpoint(query_id, checkpoint(2)).unwrap();
+        let factory = DiskStateStoreFactory::new(dir.path());
+        let state_store = factory.get_or_default(query_id.to_string(), 0, 0).unwrap();
+        assert!(state_store.state().unwrap().is_empty());
+
+        factory.clear(query_id).unwrap();
+        assert_eq!(factory.last_checkpoint(query_id).unwrap(), None);
+        assert!(!dir.path().join(query_id).exists());
+    }
+
+    #[test]
+    fn test_restore_from_corrupted_checkpoint() {
+        let dir = tempfile::tempdir().unwrap();
+        let query_id = "1";
+
+        let factory = DiskStateStoreFactory::new(dir.path());
+        let state_store = factory.get_or_default(query_id.to_string(), 0, 0).unwrap();
+        state_store.put(batch(vec![1, 2])).unwrap();
+        assert_eq!(state_store.commit().unwrap(), 1);
+        factory.checkpoint(query_id, checkpoint(1)).unwrap();
+
+        // Corrupted segment
+        let segment = dir.path().join(query_id).join("0_0").join("1.arrow");
+        std::fs::write(&segment, b"not an arrow file").unwrap();
+        let factory = DiskStateStoreFactory::new(dir.path());
+        let err = factory
+            .get_or_default(query_id.to_string(), 0, 0)
+            .unwrap_err();
+        assert!(err.to_string().contains("is corrupted"), "{err}");
+
+        // Missing segment
+        std::fs::remove_file(&segment).unwrap();
+        let factory = DiskStateStoreFactory::new(dir.path());
+        let err = factory
+            .get_or_default(query_id.to_string(), 0, 0)
+            .unwrap_err();
+        assert!(err.to_string().contains("is missing"), "{err}");
+
+        // Corrupted checkpoint
+        std::fs::write(dir.path().join(query_id).join("checkpoint.json"), b"{").unwrap();
+        let factory = DiskStateStoreFactory::new(dir.path());
+        assert!(factory.last_checkpoint(query_id).is_err());
+        assert!(factory.get_or_default(query_id.to_string(), 0, 0).is_err());
+    }
+}
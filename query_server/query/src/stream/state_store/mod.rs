@@ -1,10 +1,14 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use datafusion::arrow::record_batch::RecordBatch;
 use datafusion::common::Result;
 use datafusion::physical_plan::PhysicalExpr;
+use serde::{Deserialize, Serialize};
+use spi::query::datasource::stream::Offset;
 
 use self::memory::MemoryStateStoreFactory;
+pub mod disk;
 pub mod memory;
 
 pub fn create_memory_state_store_factory() -> Arc<MemoryStateStoreFactory> {
@@ -21,6 +25,26 @@ pub trait StateStoreFactory {
         partition_id: usize,
         operator_id: usize,
     ) -> Result<Arc<Self::SS>>;
+
+    /// Record the committed versions of all the state stores of the query together with the checkpoint,
+    /// a restarted query resumes from the states and the checkpoint as a whole.
+    fn checkpoint(&self, _query_id: &str, _checkpoint: Checkpoint) -> Result<()> {
+        Ok(())
+    }
+
+    /// The last checkpoint of the query, if states are persistent
+    fn last_checkpoint(&self, _query_id: &str) -> Result<Option<Checkpoint>> {
+        Ok(None)
+    }
+}
+
+/// Progress of a stream query when all the state stores were committed
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub batch_id: i64,
+    pub watermark_ns: i64,
+    /// Processed offset of every stream source
+    pub offsets: HashMap<String, Offset>,
 }
 
 pub type StateStoreRef = Arc<dyn StateStore>;
@@ -589,6 +589,25 @@ pub enum QueryError {
     Models {
         source: ModelError,
     },
+
+    #[snafu(display("Invalid PromQL: {}", reason))]
+    #[error_code(code = 80)]
+    InvalidPromQL {
+        reason: String,
+    },
+
+    #[snafu(display("Stream source {} error: {}", stream_type, reason))]
+    #[error_code(code = 81)]
+    StreamSource {
+        stream_type: String,
+        reason: String,
+    },
+
+    #[snafu(display("Invalid LogQL: {}", reason))]
+    #[error_code(code = 82)]
+    InvalidLogQL {
+        reason: String,
+    },
 }
 
 impl From<DataFusionError> for QueryError {
@@ -2,8 +2,8 @@ use std::fmt;
 
 use datafusion::sql::parser::CreateExternalTable;
 use datafusion::sql::sqlparser::ast::{
-    AnalyzeFormat, DataType, Expr, Ident, ObjectName, Offset, OrderByExpr, SqlOption, Statement,
-    TableFactor, Value,
+    AnalyzeFormat, DataType, Expr, Ident, ObjectName, Offset, OrderByExpr, Query, SqlOption,
+    Statement, TableFactor, Value,
 };
 use datafusion::sql::sqlparser::parser::ParserError;
 use models::codec::Encoding;
@@ -32,6 +32,12 @@ pub enum ExtStatement {
     DropStream(DropStream),
     ShowStreams(ShowStreams),
 
+    CreateContinuousAggregate(CreateContinuousAggregate),
+
+    CreateFunction(CreateFunction),
+
+    CreatePolicy(CreatePolicy),
+
     DropDatabaseObject(DropDatabaseObject),
     DropTenantObject(DropTenantObject),
     DropGlobalObject(DropGlobalObject),
@@ -44,6 +50,7 @@ pub enum ExtStatement {
     ShowTables(Option<Ident>),
     ShowSeries(Box<ShowSeries>),
     ShowTagValues(Box<ShowTagValues>),
+    ShowCardinality(ShowCardinality),
     Explain(Explain),
 
     // system cmd
@@ -52,6 +59,7 @@ pub enum ExtStatement {
     AlterTable(AlterTable),
     AlterTenant(AlterTenant),
     AlterUser(AlterUser),
+    AnalyzeTable(AnalyzeTable),
 
     // vnode cmd
     DropVnode(DropVnode),
@@ -65,6 +73,10 @@ pub enum ExtStatement {
     RecoverTenant(RecoverTenant),
     RecoverDatabase(RecoverDatabase),
 
+    // backup cmd
+    BackupDatabase(BackupDatabase),
+    RestoreDatabase(RestoreDatabase),
+
     // replica cmd
     ShowReplicas,
     ReplicaDestory(ReplicaDestory),
@@ -111,6 +123,20 @@ pub struct CompactDatabase {
     pub database_name: Ident,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackupDatabase {
+    pub database_name: Ident,
+    pub location: UriLocation,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RestoreDatabase {
+    pub database_name: Ident,
+    pub location: UriLocation,
+    /// Timestamp string of the point in time to restore to.
+    pub as_of: Option<String>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MoveVnode {
     pub vnode_id: VnodeId,
@@ -166,6 +192,11 @@ pub struct AlterTable {
     pub alter_action: AlterTableAction,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnalyzeTable {
+    pub table_name: ObjectName,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Explain {
     pub analyze: bool,
@@ -191,6 +222,14 @@ pub enum AlterTableAction {
         old_column_name: Ident,
         new_column_name: Ident,
     },
+    /// `SET TTL <duration>`
+    SetTtl {
+        ttl: String,
+    },
+    /// `SET MAX_SERIES <number>`
+    SetMaxSeries {
+        max_series: u64,
+    },
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -295,7 +334,14 @@ pub struct GrantRevoke {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Privilege {
     pub action: Action,
-    pub database: Ident,
+    pub object: PrivilegeObject,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PrivilegeObject {
+    Database(Ident),
+    /// The table and the allowed columns, all columns if None
+    Table(ObjectName, Option<Vec<Ident>>),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -370,6 +416,8 @@ pub struct DatabaseOptions {
     // shard coverage time range
     pub vnode_duration: Option<String>,
     pub replica: Option<u64>,
+    // max number of series of the database
+    pub max_series: Option<u64>,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Eq)]
@@ -426,6 +474,16 @@ pub struct ShowTagBody {
     pub offset: Option<Offset>,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShowCardinality {
+    // on db
+    pub database_name: Option<Ident>,
+    // from
+    pub table: Option<Ident>,
+    // limit
+    pub limit: Option<Expr>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct ShowSeries {
     pub body: ShowTagBody,
@@ -478,6 +536,45 @@ pub struct CreateStream {
     pub statement: Box<Statement>,
 }
 
+/// CREATE CONTINUOUS AGGREGATE | ROLLUP [IF NOT EXISTS] name [WITH (...)] AS query
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CreateContinuousAggregate {
+    pub if_not_exists: bool,
+    /// The table storing the aggregated data
+    pub name: ObjectName,
+    pub with_options: Vec<SqlOption>,
+    /// The aggregate query over the raw table
+    pub query: Box<Query>,
+}
+
+/// CREATE [OR REPLACE] [AGGREGATE] FUNCTION [IF NOT EXISTS] name ([arg data_type, ...])
+/// RETURNS data_type LANGUAGE { SQL | WASM } AS 'body' [WITH (...)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CreateFunction {
+    pub or_replace: bool,
+    pub if_not_exists: bool,
+    pub is_aggregate: bool,
+    pub name: Ident,
+    pub args: Vec<(Ident, DataType)>,
+    pub return_type: DataType,
+    pub language: Ident,
+    /// The SQL expression or the base64 encoded WebAssembly module
+    pub body: String,
+    pub with_options: Vec<SqlOption>,
+}
+
+/// CREATE [OR REPLACE] POLICY [IF NOT EXISTS] name ON table [TO role, ...] USING (expr)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CreatePolicy {
+    pub or_replace: bool,
+    pub if_not_exists: bool,
+    pub name: Ident,
+    pub table: ObjectName,
+    /// Applies to all roles if empty
+    pub roles: Vec<Ident>,
+    pub using: Expr,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct DropStream {
     pub if_exist: bool,
@@ -13,5 +13,42 @@ pub type AccessControlRef = Arc<dyn AccessControl + Send + Sync>;
 pub trait AccessControl {
     async fn access_check(&self, user_info: &UserInfo, tenant_name: &str) -> Result<User>;
 
+    /// Check the bearer token issued by the external identity provider.
+    async fn access_check_token(&self, _token: &str, _tenant_name: &str) -> Result<User> {
+        Err(AuthError::AccessDenied {
+            user_name: "".to_string(),
+            auth_type: "bearer token".to_string(),
+            err: "bearer token authentication is not enabled".to_string(),
+        })
+    }
+
     async fn tenant_id(&self, tenant_name: &str) -> Result<Oid>;
 }
+
+/// The credentials presented by the client
+#[derive(Debug, Clone, Copy)]
+pub enum Credentials<'a> {
+    /// User name and password of the basic authorization
+    Basic(&'a UserInfo),
+    /// Bearer token of the authorization, e.g. a JSON Web Token
+    Bearer(&'a str),
+}
+
+/// The user verified by an [`Authenticator`], the groups are mapped to the roles of the tenants.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternalIdentity {
+    pub user: String,
+    pub groups: Vec<String>,
+}
+
+pub type AuthenticatorRef = Arc<dyn Authenticator + Send + Sync>;
+
+/// An external identity provider, e.g. LDAP or OpenID Connect.
+#[async_trait]
+pub trait Authenticator {
+    /// Name of the identity provider, used in the error messages
+    fn name(&self) -> &str;
+
+    /// Verify the credentials, return `None` if the kind of credentials is not supported.
+    async fn authenticate(&self, credentials: Credentials<'_>) -> Result<Option<ExternalIdentity>>;
+}
@@ -1,10 +1,19 @@
 use std::str::FromStr;
 use std::time::Duration;
 
+use models::meta_data::VnodeId;
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum StreamTriggerInterval {
     Once,
+    /// Processing-time trigger, run a micro-batch on every tick of the wall clock
     Interval(Duration),
+    /// Run micro-batches one after another, as soon as new data is available
+    Continuous,
+    /// Event-time trigger, emit the windows as soon as the watermark passes their end.
+    /// Windows end at multiples of the duration (the slide of sliding windows),
+    /// `None` means at any time the watermark advances, e.g. for session windows.
+    Watermark(Option<Duration>),
 }
 
 impl FromStr for StreamTriggerInterval {
@@ -50,6 +59,15 @@ impl FromStr for StreamTriggerInterval {
     ///
     /// let interval = StreamTriggerInterval::from_str("1m+30s").unwrap();
     /// assert_eq!(interval, StreamTriggerInterval::Interval(std::time::Duration::from_secs(90)));
+    ///
+    /// let interval = StreamTriggerInterval::from_str("continuous").unwrap();
+    /// assert_eq!(interval, StreamTriggerInterval::Continuous);
+    ///
+    /// let interval = StreamTriggerInterval::from_str("watermark").unwrap();
+    /// assert_eq!(interval, StreamTriggerInterval::Watermark(None));
+    ///
+    /// let interval = StreamTriggerInterval::from_str("watermark:5m").unwrap();
+    /// assert_eq!(interval, StreamTriggerInterval::Watermark(Some(std::time::Duration::from_secs(300))));
     /// ```
     ///
     /// # Errors
@@ -64,9 +82,20 @@ impl FromStr for StreamTriggerInterval {
     /// assert!(interval.is_err());
     /// ```
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.to_lowercase().trim() {
+        let s = s.to_lowercase();
+        match s.trim() {
             "once" => Ok(StreamTriggerInterval::Once),
-            _ => {
+            "continuous" => Ok(StreamTriggerInterval::Continuous),
+            "watermark" => Ok(StreamTriggerInterval::Watermark(None)),
+            s if s.starts_with("watermark:") => {
+                let duration = duration_str::parse_std(s.trim_start_matches("watermark:").trim())
+                    .map_err(|err| err.to_string())?;
+                if duration.is_zero() {
+                    return Err("window of watermark trigger must be greater than 0".to_string());
+                }
+                Ok(StreamTriggerInterval::Watermark(Some(duration)))
+            }
+            s => {
                 let duration = duration_str::parse_std(s).map_err(|err| err.to_string())?;
                 Ok(StreamTriggerInterval::Interval(duration))
             }
@@ -74,6 +103,11 @@ impl FromStr for StreamTriggerInterval {
     }
 }
 
+/// The vnodes which the tskv table scans of a query are restricted to,
+/// so that a query is split into the partitions of vnodes and each partition is executed alone.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScanVnodes(pub Vec<VnodeId>);
+
 #[cfg(test)]
 mod test {
     use std::str::FromStr;
@@ -141,5 +175,20 @@ mod test {
 
         let interval = StreamTriggerInterval::from_str("1.5");
         assert!(interval.is_err());
+
+        let interval = StreamTriggerInterval::from_str("Continuous").unwrap();
+        assert_eq!(interval, StreamTriggerInterval::Continuous);
+
+        let interval = StreamTriggerInterval::from_str("watermark").unwrap();
+        assert_eq!(interval, StreamTriggerInterval::Watermark(None));
+
+        let interval = StreamTriggerInterval::from_str("watermark:1m").unwrap();
+        assert_eq!(
+            interval,
+            StreamTriggerInterval::Watermark(Some(std::time::Duration::from_secs(60)))
+        );
+
+        let interval = StreamTriggerInterval::from_str("watermark:0s");
+        assert!(interval.is_err());
     }
 }
@@ -37,6 +37,12 @@ pub trait QueryDispatcher: Send + Sync {
         query_state_machine: Arc<QueryStateMachine>,
     ) -> QueryResult<Option<Plan>>;
 
+    async fn build_substrait_logical_plan(
+        &self,
+        query_state_machine: Arc<QueryStateMachine>,
+        substrait_plan: &[u8],
+    ) -> QueryResult<Plan>;
+
     async fn execute_logical_plan(
         &self,
         logical_plan: Plan,
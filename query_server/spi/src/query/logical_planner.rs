@@ -19,13 +19,15 @@ use datafusion::prelude::{col, Expr};
 use datafusion::sql::sqlparser::ast::{Ident, ObjectName, SqlOption, Value};
 use datafusion::sql::sqlparser::parser::ParserError;
 use lazy_static::lazy_static;
-use models::auth::privilege::{DatabasePrivilege, GlobalPrivilege, Privilege};
+use models::auth::privilege::{DatabasePrivilege, GlobalPrivilege, Privilege, TablePrivilege};
 use models::auth::role::{SystemTenantRole, TenantRoleIdentifier};
 use models::auth::user::{UserOptions, UserOptionsBuilder};
 use models::meta_data::{NodeId, ReplicationSetId, VnodeId};
 use models::object_reference::ResolvedTable;
 use models::oid::{Identifier, Oid};
 use models::schema::database_schema::{DatabaseConfigBuilder, DatabaseOptionsBuilder};
+use models::schema::function_schema::FunctionSchema;
+use models::schema::policy_schema::PolicySchema;
 use models::schema::query_info::QueryId;
 use models::schema::stream_table_schema::Watermark;
 use models::schema::tenant::{Tenant, TenantOptions, TenantOptionsBuilder};
@@ -118,7 +120,7 @@ pub enum DDLPlan {
     DropDatabaseObject(DropDatabaseObject),
     // e.g. drop user/tenant
     DropGlobalObject(DropGlobalObject),
-    // e.g. drop database/role
+    // e.g. drop database/role/function/policy
     DropTenantObject(DropTenantObject),
 
     /// Create external table. such as parquet\csv...
@@ -128,6 +130,12 @@ pub enum DDLPlan {
 
     CreateStreamTable(CreateStreamTable),
 
+    CreateContinuousAggregate(CreateContinuousAggregate),
+
+    CreateFunction(CreateFunction),
+
+    CreatePolicy(CreatePolicy),
+
     CreateDatabase(CreateDatabase),
 
     CreateTenant(Box<CreateTenant>),
@@ -140,6 +148,8 @@ pub enum DDLPlan {
 
     AlterTable(AlterTable),
 
+    AnalyzeTable(AnalyzeTable),
+
     AlterTenant(AlterTenant),
 
     AlterUser(AlterUser),
@@ -156,6 +166,10 @@ pub enum DDLPlan {
 
     ChecksumGroup(ChecksumGroup),
 
+    BackupDatabase(BackupDatabase),
+
+    RestoreDatabase(RestoreDatabase),
+
     RecoverDatabase(RecoverDatabase),
 
     RecoverTenant(RecoverTenant),
@@ -193,6 +207,22 @@ pub struct CompactVnode {
     pub vnode_ids: Vec<VnodeId>,
 }
 
+#[derive(Debug, Clone)]
+pub struct BackupDatabase {
+    pub database_name: String,
+    pub url: String,
+    pub options: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RestoreDatabase {
+    pub database_name: String,
+    pub url: String,
+    pub options: HashMap<String, String>,
+    /// Nanosecond timestamp of the point in time to restore to.
+    pub as_of: Option<i64>,
+}
+
 #[derive(Debug, Clone)]
 pub struct MoveVnode {
     pub vnode_id: VnodeId,
@@ -268,6 +298,8 @@ pub struct DropTenantObject {
 pub enum TenantObjectType {
     Role,
     Database,
+    Function,
+    Policy,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -333,6 +365,38 @@ pub struct CreateStreamTable {
     pub extra_options: HashMap<String, String>,
 }
 
+/// A continuous aggregate is made up of a rollup table storing the aggregated data,
+/// and a stream query continuously aggregating the raw table into the rollup table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CreateContinuousAggregate {
+    /// Option to not error if the continuous aggregate already exists
+    pub if_not_exists: bool,
+    /// The rollup table
+    pub rollup_table: CreateTable,
+    /// The stream table over the raw table, which also records the definition of the continuous aggregate
+    pub stream_table: CreateStreamTable,
+    /// The sql of the stream query writing into the rollup table
+    pub stream_query: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CreateFunction {
+    /// Option to replace the function if it already exists
+    pub or_replace: bool,
+    /// Option to not error if the function already exists
+    pub if_not_exists: bool,
+    pub function: FunctionSchema,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CreatePolicy {
+    /// Option to replace the policy if it already exists
+    pub or_replace: bool,
+    /// Option to not error if the policy already exists
+    pub if_not_exists: bool,
+    pub policy: PolicySchema,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CreateDatabase {
     pub name: String,
@@ -561,6 +625,8 @@ pub struct GrantRevoke {
     pub is_grant: bool,
     // privilege, db name
     pub database_privileges: Vec<(DatabasePrivilege, String)>,
+    // privilege, db name, table name
+    pub table_privileges: Vec<(TablePrivilege, String, String)>,
     pub tenant_name: String,
     pub role_name: String,
 }
@@ -615,6 +681,11 @@ pub struct AlterTable {
     pub alter_action: AlterTableAction,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnalyzeTable {
+    pub table_name: ResolvedTable,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum AlterTableAction {
     AddColumn {
@@ -631,6 +702,14 @@ pub enum AlterTableAction {
         old_column_name: String,
         new_column_name: String,
     },
+    /// Set the TTL of the table, `None` means the data never expires.
+    SetTtl {
+        ttl: Option<CnosDuration>,
+    },
+    /// Set the max number of series of the table, 0 means unlimited.
+    SetMaxSeries {
+        max_series: u64,
+    },
 }
 
 #[async_trait]
@@ -641,6 +720,13 @@ pub trait LogicalPlanner {
         session: &SessionCtx,
         auth_enable: bool,
     ) -> QueryResult<Plan>;
+
+    /// Create the query plan of a serialized substrait plan.
+    async fn create_substrait_logical_plan(
+        &self,
+        substrait_plan: &[u8],
+        session: &SessionCtx,
+    ) -> QueryResult<Plan>;
 }
 
 /// Additional output information
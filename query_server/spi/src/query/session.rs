@@ -15,7 +15,7 @@ use models::oid::Oid;
 use trace::span_ext::SpanExt;
 use trace::{Span, SpanContext};
 
-use super::config::StreamTriggerInterval;
+use super::config::{ScanVnodes, StreamTriggerInterval};
 use super::variable::VarProviderRef;
 use crate::service::protocol::Context;
 use crate::QueryResult;
@@ -212,4 +212,10 @@ impl CnosSessionConfig {
         self.inner = self.inner.with_extension(Arc::new(interval));
         self
     }
+
+    /// Restrict the tskv table scans to the vnodes
+    pub fn with_scan_vnodes(mut self, vnodes: ScanVnodes) -> Self {
+        self.inner = self.inner.with_extension(Arc::new(vnodes));
+        self
+    }
 }
@@ -22,6 +22,7 @@ pub type DBMSRef = Arc<dyn DatabaseManagerSystem + Send + Sync>;
 pub trait DatabaseManagerSystem {
     async fn start(&self) -> QueryResult<()>;
     async fn authenticate(&self, user_info: &UserInfo, tenant_name: &str) -> QueryResult<User>;
+    async fn authenticate_token(&self, token: &str, tenant_name: &str) -> QueryResult<User>;
     async fn execute(
         &self,
         query: &Query,
@@ -36,6 +37,11 @@ pub trait DatabaseManagerSystem {
         &self,
         query_state_machine: QueryStateMachineRef,
     ) -> QueryResult<Option<Plan>>;
+    async fn build_substrait_logical_plan(
+        &self,
+        query_state_machine: QueryStateMachineRef,
+        substrait_plan: &[u8],
+    ) -> QueryResult<Option<Plan>>;
     async fn execute_logical_plan(
         &self,
         logical_plan: Plan,
@@ -65,6 +71,12 @@ impl DatabaseManagerSystem for DatabaseManagerSystemMock {
         Ok(mock_user)
     }
 
+    async fn authenticate_token(&self, _token: &str, _tenant_name: &str) -> QueryResult<User> {
+        let mock_desc = UserDesc::new(0_u128, "token".to_string(), Default::default(), true);
+        let mock_user = User::new(mock_desc, UserRole::Dba.to_privileges(), None);
+        Ok(mock_user)
+    }
+
     async fn execute(
         &self,
         query: &Query,
@@ -118,6 +130,14 @@ impl DatabaseManagerSystem for DatabaseManagerSystemMock {
         Ok(None)
     }
 
+    async fn build_substrait_logical_plan(
+        &self,
+        _query_state_machine: QueryStateMachineRef,
+        _substrait_plan: &[u8],
+    ) -> QueryResult<Option<Plan>> {
+        Ok(None)
+    }
+
     async fn execute_logical_plan(
         &self,
         _logical_plan: Plan,
@@ -0,0 +1,161 @@
+use std::str::FromStr;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use trace::SpanContext;
+
+use super::prom::{prom_point, PromLabels, PromRangeSeries};
+use crate::service::protocol::Context;
+use crate::{QueryError, QueryResult};
+
+pub type LokiServerRef = Arc<dyn LokiServer + Send + Sync>;
+
+/// Order of the log lines returned by a log query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LokiDirection {
+    Forward,
+    #[default]
+    Backward,
+}
+
+impl FromStr for LokiDirection {
+    type Err = QueryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "forward" => Ok(LokiDirection::Forward),
+            "backward" => Ok(LokiDirection::Backward),
+            _ => Err(QueryError::InvalidParam {
+                reason: format!("invalid direction \"{}\", expect forward or backward", s),
+            }),
+        }
+    }
+}
+
+/// A `/loki/api/v1/query_range` request over the logs of one table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LokiRangeQuery {
+    pub table: String,
+    /// Column holding the log line
+    pub line_column: String,
+    pub query: String,
+    /// Nanoseconds
+    pub start: i64,
+    /// Nanoseconds
+    pub end: i64,
+    /// Nanoseconds
+    pub step: i64,
+    /// Max number of log lines returned by a log query
+    pub limit: usize,
+    pub direction: LokiDirection,
+}
+
+#[async_trait]
+pub trait LokiServer {
+    /// Evaluate a LogQL log query or metric query over `[start, end]`.
+    async fn query_range(
+        &self,
+        ctx: &Context,
+        query: &LokiRangeQuery,
+        span_ctx: Option<&SpanContext>,
+    ) -> QueryResult<LokiValue>;
+
+    /// Names of the labels of the table, which are its tag columns.
+    async fn labels(&self, ctx: &Context, table: &str) -> QueryResult<Vec<String>>;
+}
+
+/// Log lines sharing the same labels.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LokiStream {
+    pub labels: PromLabels,
+    /// (timestamp in ns, line), in the order of the query direction
+    pub entries: Vec<(i64, String)>,
+}
+
+/// The result of a LogQL evaluation, as returned by the Loki HTTP API.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LokiValue {
+    Streams(Vec<LokiStream>),
+    Matrix(Vec<PromRangeSeries>),
+}
+
+impl LokiValue {
+    pub fn result_type(&self) -> &'static str {
+        match self {
+            LokiValue::Streams(_) => "streams",
+            LokiValue::Matrix(_) => "matrix",
+        }
+    }
+
+    /// Build the `data` object of a successful Loki HTTP API response.
+    pub fn to_json(&self) -> Value {
+        let result = match self {
+            LokiValue::Streams(streams) => Value::Array(
+                streams
+                    .iter()
+                    .map(|s| {
+                        json!({
+                            "stream": s.labels,
+                            "values": s
+                                .entries
+                                .iter()
+                                .map(|(t, line)| json!([t.to_string(), line]))
+                                .collect::<Vec<_>>(),
+                        })
+                    })
+                    .collect(),
+            ),
+            // Samples of a matrix are encoded in the same way as in Prometheus
+            LokiValue::Matrix(series) => Value::Array(
+                series
+                    .iter()
+                    .map(|s| {
+                        json!({
+                            "metric": s.labels,
+                            "values": s
+                                .points
+                                .iter()
+                                .map(|(t, v)| prom_point(*t, *v))
+                                .collect::<Vec<_>>(),
+                        })
+                    })
+                    .collect(),
+            ),
+        };
+
+        json!({
+            "resultType": self.result_type(),
+            "result": result,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::{LokiStream, LokiValue};
+    use crate::server::prom::PromLabels;
+
+    #[test]
+    fn test_loki_value_to_json() {
+        let mut labels = PromLabels::new();
+        labels.insert("job".to_string(), "api".to_string());
+        let value = LokiValue::Streams(vec![LokiStream {
+            labels,
+            entries: vec![(1_700_000_000_000_000_001, "GET /ping 200".to_string())],
+        }]);
+
+        assert_eq!(
+            value.to_json(),
+            json!({
+                "resultType": "streams",
+                "result": [{
+                    "stream": {"job": "api"},
+                    "values": [["1700000000000000001", "GET /ping 200"]],
+                }],
+            })
+        );
+    }
+}
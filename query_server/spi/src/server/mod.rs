@@ -1,2 +1,3 @@
 pub mod dbms;
+pub mod loki;
 pub mod prom;
@@ -1,9 +1,13 @@
+use std::collections::BTreeMap;
+use std::pin::Pin;
 use std::sync::Arc;
 
 use async_trait::async_trait;
 use bytes::Bytes;
+use futures::Stream;
 use protocol_parser::Line;
 use protos::prompb::prometheus::WriteRequest;
+use serde_json::{json, Value};
 use trace::SpanContext;
 
 use crate::service::protocol::Context;
@@ -11,6 +15,23 @@ use crate::QueryResult;
 
 pub type PromRemoteServerRef = Arc<dyn PromRemoteServer + Send + Sync>;
 
+pub type RemoteReadStream = Pin<Box<dyn Stream<Item = QueryResult<Vec<u8>>> + Send>>;
+
+/// Content type of the `SAMPLES` remote read response.
+pub const SAMPLES_CONTENT_TYPE: &str = "application/x-protobuf";
+
+/// Content type of the `STREAMED_XOR_CHUNKS` remote read response.
+pub const STREAMED_XOR_CHUNKS_CONTENT_TYPE: &str =
+    "application/x-streamed-protobuf; proto=prometheus.ChunkedReadResponse";
+
+/// Remote read response in the type negotiated by `ReadRequest.accepted_response_types`.
+pub enum RemoteReadResponse {
+    /// Snappy compressed `ReadResponse`.
+    Samples(Vec<u8>),
+    /// Delimited `ChunkedReadResponse` frames, sent series by series.
+    StreamedXorChunks(RemoteReadStream),
+}
+
 #[async_trait]
 pub trait PromRemoteServer {
     async fn remote_read(
@@ -18,9 +39,153 @@ pub trait PromRemoteServer {
         ctx: &Context,
         req: Bytes,
         span_ctx: Option<&SpanContext>,
-    ) -> QueryResult<Vec<u8>>;
+    ) -> QueryResult<RemoteReadResponse>;
 
     fn remote_write(&self, req: Bytes) -> QueryResult<WriteRequest>;
 
     fn prom_write_request_to_lines<'a>(&self, req: &'a WriteRequest) -> QueryResult<Vec<Line<'a>>>;
+
+    /// Evaluate a PromQL expression at a single point in time (ms).
+    async fn instant_query(
+        &self,
+        ctx: &Context,
+        query: &str,
+        time: i64,
+        span_ctx: Option<&SpanContext>,
+    ) -> QueryResult<PromValue>;
+
+    /// Evaluate a PromQL expression over `[start, end]` (ms) at every `step` (ms).
+    async fn range_query(
+        &self,
+        ctx: &Context,
+        query: &str,
+        start: i64,
+        end: i64,
+        step: i64,
+        span_ctx: Option<&SpanContext>,
+    ) -> QueryResult<PromValue>;
+}
+
+pub type PromLabels = BTreeMap<String, String>;
+
+/// One sample of an instant vector.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PromInstantSample {
+    pub labels: PromLabels,
+    pub timestamp: i64,
+    pub value: f64,
+}
+
+/// One series of a range vector (matrix).
+#[derive(Debug, Clone, PartialEq)]
+pub struct PromRangeSeries {
+    pub labels: PromLabels,
+    /// (timestamp in ms, value), ordered by timestamp
+    pub points: Vec<(i64, f64)>,
+}
+
+/// The result of a PromQL evaluation, as returned by the Prometheus HTTP API.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PromValue {
+    Scalar { timestamp: i64, value: f64 },
+    String { timestamp: i64, value: String },
+    Vector(Vec<PromInstantSample>),
+    Matrix(Vec<PromRangeSeries>),
+}
+
+impl PromValue {
+    pub fn result_type(&self) -> &'static str {
+        match self {
+            PromValue::Scalar { .. } => "scalar",
+            PromValue::String { .. } => "string",
+            PromValue::Vector(_) => "vector",
+            PromValue::Matrix(_) => "matrix",
+        }
+    }
+
+    /// Build the `data` object of a successful Prometheus HTTP API response.
+    pub fn to_json(&self) -> Value {
+        let result = match self {
+            PromValue::Scalar { timestamp, value } => prom_point(*timestamp, *value),
+            PromValue::String { timestamp, value } => {
+                json!([*timestamp as f64 / 1000.0, value])
+            }
+            PromValue::Vector(samples) => Value::Array(
+                samples
+                    .iter()
+                    .map(|s| {
+                        json!({
+                            "metric": s.labels,
+                            "value": prom_point(s.timestamp, s.value),
+                        })
+                    })
+                    .collect(),
+            ),
+            PromValue::Matrix(series) => Value::Array(
+                series
+                    .iter()
+                    .map(|s| {
+                        json!({
+                            "metric": s.labels,
+                            "values": s
+                                .points
+                                .iter()
+                                .map(|(t, v)| prom_point(*t, *v))
+                                .collect::<Vec<_>>(),
+                        })
+                    })
+                    .collect(),
+            ),
+        };
+
+        json!({
+            "resultType": self.result_type(),
+            "result": result,
+        })
+    }
+}
+
+/// Prometheus encodes a sample as `[<unix seconds>, "<value>"]`.
+pub(crate) fn prom_point(timestamp: i64, value: f64) -> Value {
+    json!([timestamp as f64 / 1000.0, format_prom_float(value)])
+}
+
+fn format_prom_float(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 {
+            "+Inf".to_string()
+        } else {
+            "-Inf".to_string()
+        }
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::{PromInstantSample, PromLabels, PromValue};
+
+    #[test]
+    fn test_prom_value_to_json() {
+        let mut labels = PromLabels::new();
+        labels.insert("__name__".to_string(), "up".to_string());
+        let value = PromValue::Vector(vec![PromInstantSample {
+            labels,
+            timestamp: 1_500,
+            value: f64::INFINITY,
+        }]);
+
+        assert_eq!(
+            value.to_json(),
+            json!({
+                "resultType": "vector",
+                "result": [{"metric": {"__name__": "up"}, "value": [1.5, "+Inf"]}],
+            })
+        );
+    }
 }
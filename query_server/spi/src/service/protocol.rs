@@ -2,7 +2,7 @@ use models::auth::user::User;
 use models::schema::query_info::QueryId;
 use models::schema::{DEFAULT_CATALOG, DEFAULT_DATABASE, DEFAULT_PRECISION};
 
-use crate::query::config::StreamTriggerInterval;
+use crate::query::config::{ScanVnodes, StreamTriggerInterval};
 use crate::query::execution::Output;
 use crate::query::session::CnosSessionConfig;
 
@@ -19,6 +19,7 @@ pub struct Context {
     chunked: bool,
     session_config: CnosSessionConfig,
     is_old: bool,
+    client_addr: Option<String>,
 }
 
 impl Context {
@@ -47,6 +48,10 @@ impl Context {
     pub fn is_old(&self) -> bool {
         self.is_old
     }
+    /// The address of the client sending the query
+    pub fn client_addr(&self) -> Option<&str> {
+        self.client_addr.as_deref()
+    }
 }
 
 pub struct ContextBuilder {
@@ -57,6 +62,7 @@ pub struct ContextBuilder {
     chunked: bool,
     session_config: CnosSessionConfig,
     is_old: bool,
+    client_addr: Option<String>,
 }
 
 impl ContextBuilder {
@@ -69,6 +75,7 @@ impl ContextBuilder {
             chunked: Default::default(),
             session_config: Default::default(),
             is_old: Default::default(),
+            client_addr: Default::default(),
         }
     }
 
@@ -109,6 +116,13 @@ impl ContextBuilder {
         self
     }
 
+    pub fn with_scan_vnodes(mut self, vnodes: Option<ScanVnodes>) -> Self {
+        if let Some(vnodes) = vnodes {
+            self.session_config = self.session_config.with_scan_vnodes(vnodes);
+        }
+        self
+    }
+
     pub fn with_chunked(mut self, chunked: Option<bool>) -> Self {
         if let Some(chunked) = chunked {
             self.chunked = chunked;
@@ -123,6 +137,13 @@ impl ContextBuilder {
         self
     }
 
+    pub fn with_client_addr(mut self, client_addr: Option<String>) -> Self {
+        if let Some(client_addr) = client_addr {
+            self.client_addr = Some(client_addr);
+        }
+        self
+    }
+
     pub fn build(self) -> Context {
         Context {
             user: self.user,
@@ -132,6 +153,7 @@ impl ContextBuilder {
             chunked: self.chunked,
             session_config: self.session_config,
             is_old: self.is_old,
+            client_addr: self.client_addr,
         }
     }
 }
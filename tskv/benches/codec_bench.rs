@@ -0,0 +1,101 @@
+use arrow::buffer::NullBuffer;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use models::codec::{CodecTolerance, Encoding};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use tskv::tsm::codec::{get_f64_codec, get_str_codec};
+
+const ROWS: usize = 1024;
+
+fn sensor_values() -> Vec<f64> {
+    let mut rng = StdRng::seed_from_u64(42);
+    let mut value = 20.0_f64;
+    (0..ROWS)
+        .map(|_| {
+            value += rng.gen_range(-50..=50) as f64 / 100.0;
+            (value * 100.0).round() / 100.0
+        })
+        .collect()
+}
+
+fn random_values() -> Vec<f64> {
+    let mut rng = StdRng::seed_from_u64(42);
+    (0..ROWS).map(|_| rng.gen::<f64>() * 1000.0).collect()
+}
+
+fn log_lines() -> Vec<String> {
+    let mut rng = StdRng::seed_from_u64(42);
+    (0..ROWS)
+        .map(|i| {
+            format!(
+                "level=info host=server-{:03} path=/api/v1/write status={} latency={}ms",
+                rng.gen_range(0..50),
+                [200, 204, 500][i % 3],
+                rng.gen_range(0..1000)
+            )
+        })
+        .collect()
+}
+
+fn float_codec(c: &mut Criterion) {
+    let bit_set = NullBuffer::new_valid(ROWS);
+    for (name, values) in [("sensor", sensor_values()), ("random", random_values())] {
+        let mut group = c.benchmark_group(format!("f64_codec_{name}"));
+        group.throughput(Throughput::Elements(ROWS as u64));
+        for encoding in [
+            Encoding::Gorilla,
+            Encoding::Quantile,
+            Encoding::Chimp,
+            Encoding::Alp,
+            Encoding::Sdt(CodecTolerance::new(0.5).unwrap()),
+            Encoding::Deadband(CodecTolerance::new(0.5).unwrap()),
+        ] {
+            let codec = get_f64_codec(encoding);
+            let mut encoded = vec![];
+            codec.encode(&values, &mut encoded).unwrap();
+            println!("f64 {name} {encoding}: {} bytes", encoded.len());
+
+            group.bench_function(BenchmarkId::new("encode", encoding), |b| {
+                b.iter(|| {
+                    let mut dst = Vec::with_capacity(encoded.len());
+                    codec.encode(&values, &mut dst).unwrap();
+                    dst
+                })
+            });
+            group.bench_function(BenchmarkId::new("decode", encoding), |b| {
+                b.iter(|| codec.decode_to_array(&encoded, &bit_set).unwrap())
+            });
+        }
+        group.finish();
+    }
+}
+
+fn string_codec(c: &mut Criterion) {
+    let bit_set = NullBuffer::new_valid(ROWS);
+    let lines = log_lines();
+    let values = lines.iter().map(|l| l.as_bytes()).collect::<Vec<_>>();
+
+    let mut group = c.benchmark_group("str_codec");
+    group.throughput(Throughput::Elements(ROWS as u64));
+    for encoding in [Encoding::Snappy, Encoding::Zstd, Encoding::ZstdDict] {
+        let codec = get_str_codec(encoding);
+        let mut encoded = vec![];
+        codec.encode(&values, &mut encoded).unwrap();
+        println!("str {}: {} bytes", encoding.as_str(), encoded.len());
+
+        group.bench_function(BenchmarkId::new("encode", encoding.as_str()), |b| {
+            b.iter(|| {
+                let mut dst = Vec::with_capacity(encoded.len());
+                codec.encode(&values, &mut dst).unwrap();
+                dst
+            })
+        });
+        group.bench_function(BenchmarkId::new("decode", encoding.as_str()), |b| {
+            b.iter(|| codec.decode_to_array(&encoded, &bit_set).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, float_codec, string_codec);
+criterion_main!(benches);
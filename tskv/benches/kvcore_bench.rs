@@ -90,6 +90,7 @@ fn big_write(c: &mut Criterion) {
                 let request = WriteDataRequest {
                     data: points,
                     precision: Precision::NS as u32,
+                    series_limits: None,
                 };
 
                 tskv_write(
@@ -119,6 +120,7 @@ fn run(c: &mut Criterion) {
     let request = WriteDataRequest {
         data: points,
         precision: Precision::NS as u32,
+        series_limits: None,
     };
 
     // maybe 500 us
@@ -0,0 +1,552 @@
+//! # Backup
+//!
+//! `BACKUP DATABASE` ships the flushed files and the WAL segments of a vnode of each
+//! replication set to an object store, and `RESTORE DATABASE` rebuilds the vnodes from them.
+//!
+//! ## Layout
+//! ```text
+//! {prefix}/backup_{backup_id}.json                          meta of the database
+//! {prefix}/vnode_{vnode_id}/tsm/_{file_id}.tsm              TSM files
+//! {prefix}/vnode_{vnode_id}/delta/_{file_id}.delta          delta files
+//! {prefix}/vnode_{vnode_id}/wal/_{wal_id}.wal               WAL segments
+//! {prefix}/vnode_{vnode_id}/{backup_id}/manifest.json       files of the vnode in the backup
+//! {prefix}/vnode_{vnode_id}/{backup_id}/_{file_id}.tombstone
+//! {prefix}/vnode_{vnode_id}/latest.json                     the latest manifest of the vnode
+//! ```
+//!
+//! Column files are never modified and the column file ID is never reused in a vnode,
+//! so files that were shipped by earlier backups are not uploaded again. Tombstones are
+//! shipped by every backup, and a WAL segment is shipped again if it's size changed.
+//!
+//! The series index is not shipped, it is rebuilt from the series keys of the restored
+//! files, as what is done when a vnode installs a raft snapshot.
+
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use bytes::Bytes;
+use models::codec::Encoding;
+use models::meta_data::{DatabaseInfo, ReplicationSetId, VnodeId};
+use object_store::path::Path as ObjectPath;
+use object_store::ObjectStore;
+use openraft::EntryPayload;
+use protos::kv_service::RaftWriteCommand;
+use protos::models_helper::parse_prost_bytes;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use snafu::{IntoError, ResultExt};
+use trace::warn;
+
+use crate::cold_tier::{build_object_store, ObjectStoreOptions};
+use crate::error::{
+    DecodeSnafu, DeserializeSnafu, IOSnafu, ObjectStoreSnafu, SerializeSnafu, TskvError, TskvResult,
+};
+use crate::tsfamily::version::{CompactMeta, VersionEdit};
+use crate::wal::reader::WalReader;
+use crate::{file_utils, ColumnFileId};
+
+const LATEST_MANIFEST: &str = "latest.json";
+const MANIFEST: &str = "manifest.json";
+
+/// Meta of a backup of a database.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DatabaseBackup {
+    /// Start time of the backup in nanoseconds, also the ID of the backup.
+    pub backup_id: i64,
+    pub database: DatabaseInfo,
+    /// The vnode whose files were shipped for each replication set.
+    pub replica_vnodes: HashMap<ReplicationSetId, VnodeId>,
+}
+
+/// A WAL segment shipped to the backup storage.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct WalSegment {
+    pub id: u64,
+    pub size: u64,
+    /// Last modified time of the segment in nanoseconds, all entries of the segment
+    /// were written before it.
+    pub modified_time: i64,
+}
+
+/// Files of a vnode in a backup.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct VnodeBackupManifest {
+    pub backup_id: i64,
+    pub vnode_id: VnodeId,
+    /// Contains all column files of the vnode in the backup, `seq_no` is the last
+    /// sequence of the WAL that was flushed into the files.
+    pub version_edit: VersionEdit,
+    /// Column files that have tombstones in the backup.
+    pub tombstones: Vec<ColumnFileId>,
+    /// Column files shipped by the backup and the earlier backups.
+    pub shipped_files: BTreeSet<ColumnFileId>,
+    /// WAL segments shipped by the backup and the earlier backups, ordered by ID.
+    pub wal_segments: Vec<WalSegment>,
+    pub wal_compress: Encoding,
+}
+
+impl VnodeBackupManifest {
+    pub fn last_seq(&self) -> u64 {
+        self.version_edit.seq_no
+    }
+}
+
+pub struct BackupStorage {
+    store: Arc<dyn ObjectStore>,
+    prefix: ObjectPath,
+}
+
+impl BackupStorage {
+    /// Open the backup storage at `url` (`s3://`, `gcs://`, `azblob://` or `file://`),
+    /// `options` are the endpoint and credentials of the object store.
+    pub fn new(url: &str, options: &HashMap<String, String>) -> TskvResult<Self> {
+        let options = ObjectStoreOptions::from_map(options)?;
+        let (store, prefix) = build_object_store(url, &options)?;
+        Ok(Self::with_object_store(store, prefix))
+    }
+
+    pub fn with_object_store(store: Arc<dyn ObjectStore>, prefix: ObjectPath) -> Self {
+        Self { store, prefix }
+    }
+
+    fn vnode_location(&self, vnode_id: VnodeId) -> ObjectPath {
+        self.prefix.child(format!("vnode_{vnode_id}"))
+    }
+
+    /// Object path of a column file: `{prefix}/vnode_{vnode_id}/{tsm|delta}/_{file_id}.{tsm|delta}`.
+    pub fn file_location(&self, vnode_id: VnodeId, file: &CompactMeta) -> ObjectPath {
+        let mut location = self.vnode_location(vnode_id);
+        for part in file.relative_path().iter() {
+            location = location.child(part.to_string_lossy().as_ref());
+        }
+        location
+    }
+
+    pub fn tombstone_location(
+        &self,
+        vnode_id: VnodeId,
+        backup_id: i64,
+        file_id: ColumnFileId,
+    ) -> ObjectPath {
+        self.vnode_location(vnode_id)
+            .child(backup_id.to_string())
+            .child(file_utils::make_tsm_tombstone_file_name(file_id))
+    }
+
+    pub fn wal_location(&self, vnode_id: VnodeId, wal_id: u64) -> ObjectPath {
+        let file_name = file_utils::make_wal_file("", wal_id);
+        self.vnode_location(vnode_id)
+            .child("wal")
+            .child(file_name.to_string_lossy().as_ref())
+    }
+
+    pub async fn put_file(&self, path: impl AsRef<Path>, location: &ObjectPath) -> TskvResult<()> {
+        let data = tokio::fs::read(path.as_ref()).await.context(IOSnafu)?;
+        self.store
+            .put(location, Bytes::from(data))
+            .await
+            .context(ObjectStoreSnafu)
+    }
+
+    pub async fn get_file(&self, location: &ObjectPath, path: impl AsRef<Path>) -> TskvResult<()> {
+        let data = self
+            .store
+            .get(location)
+            .await
+            .context(ObjectStoreSnafu)?
+            .bytes()
+            .await
+            .context(ObjectStoreSnafu)?;
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir).await.context(IOSnafu)?;
+        }
+        tokio::fs::write(path, data).await.context(IOSnafu)
+    }
+
+    async fn put_json<T: Serialize>(&self, location: &ObjectPath, value: &T) -> TskvResult<()> {
+        let data = serde_json::to_vec(value).map_err(|e| SerializeSnafu.into_error(Box::new(e)))?;
+        self.store
+            .put(location, Bytes::from(data))
+            .await
+            .context(ObjectStoreSnafu)
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, location: &ObjectPath) -> TskvResult<Option<T>> {
+        let data = match self.store.get(location).await {
+            Ok(result) => result.bytes().await.context(ObjectStoreSnafu)?,
+            Err(object_store::Error::NotFound { .. }) => return Ok(None),
+            Err(e) => return Err(e).context(ObjectStoreSnafu),
+        };
+        let value =
+            serde_json::from_slice(&data).map_err(|e| DeserializeSnafu.into_error(Box::new(e)))?;
+        Ok(Some(value))
+    }
+
+    pub async fn put_database_backup(&self, backup: &DatabaseBackup) -> TskvResult<()> {
+        let location = self
+            .prefix
+            .child(format!("backup_{}.json", backup.backup_id));
+        self.put_json(&location, backup).await
+    }
+
+    /// Get the latest backup of the database, which started no later than `as_of`
+    /// if it is given.
+    pub async fn database_backup(&self, as_of: Option<i64>) -> TskvResult<Option<DatabaseBackup>> {
+        let list = self
+            .store
+            .list_with_delimiter(Some(&self.prefix))
+            .await
+            .context(ObjectStoreSnafu)?;
+        let backup_id = list
+            .objects
+            .iter()
+            .filter_map(|object| {
+                let file_name = object.location.filename()?;
+                file_name
+                    .strip_prefix("backup_")?
+                    .strip_suffix(".json")?
+                    .parse::<i64>()
+                    .ok()
+            })
+            .filter(|backup_id| as_of.map_or(true, |as_of| *backup_id <= as_of))
+            .max();
+
+        match backup_id {
+            Some(backup_id) => {
+                let location = self.prefix.child(format!("backup_{backup_id}.json"));
+                self.get_json(&location).await
+            }
+            None => Ok(None),
+        }
+    }
+
+    pub async fn put_vnode_manifest(&self, manifest: &VnodeBackupManifest) -> TskvResult<()> {
+        let vnode_location = self.vnode_location(manifest.vnode_id);
+        let location = vnode_location
+            .child(manifest.backup_id.to_string())
+            .child(MANIFEST);
+        self.put_json(&location, manifest).await?;
+        self.put_json(&vnode_location.child(LATEST_MANIFEST), manifest)
+            .await
+    }
+
+    pub async fn vnode_manifest(
+        &self,
+        vnode_id: VnodeId,
+        backup_id: i64,
+    ) -> TskvResult<Option<VnodeBackupManifest>> {
+        let location = self
+            .vnode_location(vnode_id)
+            .child(backup_id.to_string())
+            .child(MANIFEST);
+        self.get_json(&location).await
+    }
+
+    pub async fn latest_vnode_manifest(
+        &self,
+        vnode_id: VnodeId,
+    ) -> TskvResult<Option<VnodeBackupManifest>> {
+        let location = self.vnode_location(vnode_id).child(LATEST_MANIFEST);
+        self.get_json(&location).await
+    }
+
+    /// Read the commands in the shipped WAL segments of a vnode one by one, whose sequence
+    /// is greater than `after_seq`. If `as_of` is given, only the commands proposed no later
+    /// than it are read. Segments are downloaded to `dir` one at a time.
+    pub fn wal_reader(
+        &self,
+        manifest: &VnodeBackupManifest,
+        after_seq: u64,
+        as_of: Option<i64>,
+        dir: impl AsRef<Path>,
+    ) -> BackupWalReader<'_> {
+        BackupWalReader {
+            storage: self,
+            vnode_id: manifest.vnode_id,
+            wal_compress: manifest.wal_compress,
+            segments: manifest.wal_segments.clone().into_iter(),
+            after_seq,
+            as_of,
+            dir: dir.as_ref().to_path_buf(),
+            current: None,
+            finished: false,
+        }
+    }
+}
+
+struct OpenedWalSegment {
+    segment: WalSegment,
+    path: PathBuf,
+    reader: WalReader,
+}
+
+/// Reader of the commands in the shipped WAL segments of a vnode, see
+/// [`BackupStorage::wal_reader`].
+pub struct BackupWalReader<'a> {
+    storage: &'a BackupStorage,
+    vnode_id: VnodeId,
+    wal_compress: Encoding,
+    segments: std::vec::IntoIter<WalSegment>,
+    after_seq: u64,
+    as_of: Option<i64>,
+    dir: PathBuf,
+    current: Option<OpenedWalSegment>,
+    /// Set if a command proposed after `as_of` is read.
+    finished: bool,
+}
+
+impl BackupWalReader<'_> {
+    /// Get the next command, `None` if there are no more commands.
+    pub async fn next_command(&mut self) -> TskvResult<Option<RaftWriteCommand>> {
+        while !self.finished {
+            let Some(current) = self.current.as_mut() else {
+                if !self.open_next_segment().await? {
+                    break;
+                }
+                continue;
+            };
+            let modified_time = current.segment.modified_time;
+            let next = current.reader.next_wal_entry().await;
+            let record = match next {
+                Ok(Some(record)) => record,
+                Ok(None) => {
+                    self.close_segment().await?;
+                    continue;
+                }
+                Err(TskvError::WalTruncated { .. }) => {
+                    // The segment was being written when it was shipped.
+                    warn!("WAL segment {} of backup is truncated", current.segment.id);
+                    self.close_segment().await?;
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            if record.seq <= self.after_seq {
+                continue;
+            }
+            let EntryPayload::Normal(request) = record.block.payload else {
+                continue;
+            };
+            let command = parse_prost_bytes::<RaftWriteCommand>(&request)
+                .map_err(|e| DecodeSnafu.into_error(Box::new(e)))?;
+            if let Some(as_of) = self.as_of {
+                // Commands written by old versions have no timestamp, all of them
+                // were proposed before the segment was last modified.
+                let timestamp = if command.timestamp > 0 {
+                    command.timestamp
+                } else {
+                    modified_time
+                };
+                if timestamp > as_of {
+                    self.finished = true;
+                    self.close_segment().await?;
+                    break;
+                }
+            }
+            return Ok(Some(command));
+        }
+
+        Ok(None)
+    }
+
+    /// Download and open the next segment, returns false if there are no more segments.
+    async fn open_next_segment(&mut self) -> TskvResult<bool> {
+        let Some(segment) = self.segments.next() else {
+            return Ok(false);
+        };
+        let path = file_utils::make_wal_file(&self.dir, segment.id);
+        self.storage
+            .get_file(&self.storage.wal_location(self.vnode_id, segment.id), &path)
+            .await?;
+        let reader = WalReader::open(&path, self.wal_compress).await?;
+        self.current = Some(OpenedWalSegment {
+            segment,
+            path,
+            reader,
+        });
+        Ok(true)
+    }
+
+    async fn close_segment(&mut self) -> TskvResult<()> {
+        if let Some(current) = self.current.take() {
+            drop(current.reader);
+            tokio::fs::remove_file(&current.path)
+                .await
+                .context(IOSnafu)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for BackupStorage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BackupStorage")
+            .field("prefix", &self.prefix)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::{BTreeSet, HashMap};
+    use std::sync::Arc;
+
+    use models::codec::Encoding;
+    use models::meta_data::DatabaseInfo;
+    use object_store::local::LocalFileSystem;
+    use object_store::path::Path as ObjectPath;
+    use openraft::EntryPayload;
+    use protos::kv_service::RaftWriteCommand;
+    use protos::models_helper::to_prost_bytes;
+
+    use super::{BackupStorage, DatabaseBackup, VnodeBackupManifest, WalSegment};
+    use crate::file_utils;
+    use crate::kv_option::WalOptions;
+    use crate::tsfamily::version::{CompactMeta, VersionEdit};
+    use crate::wal::wal_store::RaftEntry;
+    use crate::wal::writer::WalWriter;
+
+    #[tokio::test]
+    async fn test_backup_meta() {
+        let dir = "/tmp/test/backup/test_backup_meta";
+        let _ = std::fs::remove_dir_all(dir);
+        std::fs::create_dir_all(dir).unwrap();
+        let store = Arc::new(LocalFileSystem::new_with_prefix(dir).unwrap());
+        let storage = BackupStorage::with_object_store(store, ObjectPath::default());
+
+        let tsm = CompactMeta {
+            file_id: 3,
+            ..Default::default()
+        };
+        assert_eq!(
+            storage.file_location(1, &tsm).to_string(),
+            "vnode_1/tsm/_000003.tsm"
+        );
+        assert_eq!(
+            storage.tombstone_location(1, 100, 3).to_string(),
+            "vnode_1/100/_000003.tombstone"
+        );
+        assert_eq!(
+            storage.wal_location(1, 2).to_string(),
+            "vnode_1/wal/_000002.wal"
+        );
+
+        assert!(storage.database_backup(None).await.unwrap().is_none());
+        for backup_id in [100, 200] {
+            let backup = DatabaseBackup {
+                backup_id,
+                database: DatabaseInfo::default(),
+                replica_vnodes: HashMap::from([(1, 1)]),
+            };
+            storage.put_database_backup(&backup).await.unwrap();
+        }
+        let backup = storage.database_backup(None).await.unwrap().unwrap();
+        assert_eq!(backup.backup_id, 200);
+        let backup = storage.database_backup(Some(150)).await.unwrap().unwrap();
+        assert_eq!(backup.backup_id, 100);
+        assert!(storage.database_backup(Some(50)).await.unwrap().is_none());
+
+        for backup_id in [100, 200] {
+            let manifest = VnodeBackupManifest {
+                backup_id,
+                vnode_id: 1,
+                version_edit: VersionEdit::new_add_vnode(1, "cnosdb.db".to_string(), 10),
+                tombstones: vec![],
+                shipped_files: BTreeSet::from([3]),
+                wal_segments: vec![],
+                wal_compress: Encoding::Zstd,
+            };
+            storage.put_vnode_manifest(&manifest).await.unwrap();
+        }
+        let manifest = storage.vnode_manifest(1, 100).await.unwrap().unwrap();
+        assert_eq!(manifest.backup_id, 100);
+        assert_eq!(manifest.last_seq(), 10);
+        let manifest = storage.latest_vnode_manifest(1).await.unwrap().unwrap();
+        assert_eq!(manifest.backup_id, 200);
+        assert!(storage.latest_vnode_manifest(2).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_wal_reader_as_of() {
+        let dir = "/tmp/test/backup/test_wal_reader_as_of";
+        let _ = std::fs::remove_dir_all(dir);
+        let store_dir = format!("{dir}/store");
+        let wal_dir = format!("{dir}/wal");
+        let restore_dir = format!("{dir}/restore");
+        std::fs::create_dir_all(&store_dir).unwrap();
+        std::fs::create_dir_all(&wal_dir).unwrap();
+        let store = Arc::new(LocalFileSystem::new_with_prefix(&store_dir).unwrap());
+        let storage = BackupStorage::with_object_store(store, ObjectPath::default());
+
+        let wal_options = Arc::new(WalOptions {
+            path: wal_dir.clone().into(),
+            wal_max_file_size: 1024 * 1024,
+            compress: Encoding::Zstd,
+            wal_sync: false,
+        });
+        // Commands in segment 1 were written without timestamps.
+        let segments = [
+            (1, [(1, 0), (2, 0), (3, 0)], 100),
+            (2, [(4, 150), (5, 200), (6, 250)], 300),
+        ];
+        let mut wal_segments = vec![];
+        for (wal_id, commands, modified_time) in segments {
+            let path = file_utils::make_wal_file(&wal_dir, wal_id);
+            let mut writer = WalWriter::open(wal_options.clone(), wal_id, &path)
+                .await
+                .unwrap();
+            for (seq, timestamp) in commands {
+                let command = RaftWriteCommand {
+                    replica_id: seq as u32,
+                    timestamp,
+                    ..Default::default()
+                };
+                let mut entry = RaftEntry::default();
+                entry.log_id.index = seq;
+                entry.payload = EntryPayload::Normal(to_prost_bytes(&command));
+                writer.append_raft_entry(&entry).await.unwrap();
+            }
+            writer.close().await.unwrap();
+            storage
+                .put_file(&path, &storage.wal_location(1, wal_id))
+                .await
+                .unwrap();
+            wal_segments.push(WalSegment {
+                id: wal_id,
+                size: 0,
+                modified_time,
+            });
+        }
+        let manifest = VnodeBackupManifest {
+            backup_id: 100,
+            vnode_id: 1,
+            version_edit: VersionEdit::new_add_vnode(1, "cnosdb.db".to_string(), 0),
+            tombstones: vec![],
+            shipped_files: BTreeSet::new(),
+            wal_segments,
+            wal_compress: Encoding::Zstd,
+        };
+
+        for (after_seq, as_of, expected) in [
+            (0, None, vec![1, 2, 3, 4, 5, 6]),
+            (2, None, vec![3, 4, 5, 6]),
+            (0, Some(50), vec![]),
+            (0, Some(120), vec![1, 2, 3]),
+            (2, Some(200), vec![3, 4, 5]),
+        ] {
+            let mut reader = storage.wal_reader(&manifest, after_seq, as_of, &restore_dir);
+            let mut replica_ids = vec![];
+            while let Some(command) = reader.next_command().await.unwrap() {
+                replica_ids.push(command.replica_id);
+            }
+            assert_eq!(
+                replica_ids, expected,
+                "after_seq: {after_seq}, as_of: {as_of:?}"
+            );
+            // Downloaded segments are removed after being read.
+            assert_eq!(std::fs::read_dir(&restore_dir).unwrap().count(), 0);
+        }
+    }
+}
@@ -0,0 +1,405 @@
+//! Change data capture of the commands applied to vnodes.
+//!
+//! Every applied command is published to a [`ChangeFeed`], subscribers receive the
+//! commands applied after they subscribed, the commands applied before that are
+//! read from the WAL of the vnode.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use arrow_buffer::{BooleanBuffer, Buffer, NullBuffer};
+use datafusion::arrow::array::{
+    ArrayRef, BooleanArray, Float64Array, Int64Array, PrimitiveArray, StringArray, UInt64Array,
+};
+use datafusion::arrow::datatypes::{
+    Field, Schema, TimestampMicrosecondType, TimestampMillisecondType, TimestampNanosecondType,
+};
+use datafusion::arrow::record_batch::RecordBatch;
+use models::meta_data::VnodeId;
+use parking_lot::Mutex;
+use protos::kv_service::RaftWriteCommand;
+use protos::models::{Column, ColumnType, FieldType, Points};
+use snafu::{OptionExt, ResultExt};
+use tokio::sync::broadcast;
+use utils::precision::Precision;
+
+use crate::error::{ArrowSnafu, CommonSnafu, InvalidFlatbufferSnafu, InvalidPointTableSnafu};
+use crate::TskvResult;
+
+pub type ChangeFeedRef = Arc<ChangeFeed>;
+
+/// Maximum number of changes buffered for a subscriber, subscribers
+/// lagging further behind are disconnected.
+pub const CHANGE_FEED_CAPACITY: usize = 4096;
+
+/// A command applied to a vnode.
+#[derive(Debug, Clone)]
+pub struct ChangeEvent {
+    pub vnode_id: VnodeId,
+    /// Raft log index of the command, the same in all vnodes of the replication set.
+    pub index: u64,
+    pub command: RaftWriteCommand,
+}
+
+pub struct ChangeFeed {
+    inner: Mutex<ChangeFeedInner>,
+}
+
+struct ChangeFeedInner {
+    sender: broadcast::Sender<Arc<ChangeEvent>>,
+    /// Index of the last command applied to each vnode.
+    applied: HashMap<VnodeId, u64>,
+}
+
+impl ChangeFeed {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        Self {
+            inner: Mutex::new(ChangeFeedInner {
+                sender,
+                applied: HashMap::new(),
+            }),
+        }
+    }
+
+    /// Record the index of the command applied to the vnode, and send the command
+    /// to the subscribers if there is any, `command` is only called in that case.
+    pub fn publish(
+        &self,
+        vnode_id: VnodeId,
+        index: u64,
+        command: impl FnOnce() -> Option<RaftWriteCommand>,
+    ) {
+        let mut inner = self.inner.lock();
+        inner.applied.insert(vnode_id, index);
+        if inner.sender.receiver_count() == 0 {
+            return;
+        }
+        if let Some(command) = command() {
+            let _ = inner.sender.send(Arc::new(ChangeEvent {
+                vnode_id,
+                index,
+                command,
+            }));
+        }
+    }
+
+    /// Returns a receiver of the commands applied from now on, and the index of
+    /// the last command applied to each vnode before that.
+    pub fn subscribe(&self) -> (broadcast::Receiver<Arc<ChangeEvent>>, HashMap<VnodeId, u64>) {
+        let inner = self.inner.lock();
+        (inner.sender.subscribe(), inner.applied.clone())
+    }
+
+    pub fn remove_vnode(&self, vnode_id: VnodeId) {
+        self.inner.lock().applied.remove(&vnode_id);
+    }
+}
+
+impl Default for ChangeFeed {
+    fn default() -> Self {
+        Self::new(CHANGE_FEED_CAPACITY)
+    }
+}
+
+impl std::fmt::Debug for ChangeFeed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChangeFeed").finish()
+    }
+}
+
+/// Convert the rows of a write command into a record batch for each table,
+/// the tables not accepted by `filter` are skipped.
+pub fn write_data_to_record_batches(
+    points: &[u8],
+    precision: Precision,
+    filter: impl Fn(&str) -> bool,
+) -> TskvResult<Vec<(String, RecordBatch)>> {
+    let fb_points = flatbuffers::root::<Points>(points).context(InvalidFlatbufferSnafu)?;
+    let tables = fb_points.tables().context(InvalidPointTableSnafu)?;
+
+    let mut batches = Vec::with_capacity(tables.len());
+    for table in tables.iter() {
+        let table_name = table.tab_ext()?;
+        if !filter(table_name) {
+            continue;
+        }
+
+        let num_rows = table.num_rows() as usize;
+        let mut fields = vec![];
+        let mut arrays = vec![];
+        for column in table.columns_iter_ext()? {
+            let (field, array) = column_to_array(&column, num_rows, precision)?;
+            fields.push(field);
+            arrays.push(array);
+        }
+        let batch =
+            RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays).context(ArrowSnafu)?;
+        batches.push((table_name.to_string(), batch));
+    }
+
+    Ok(batches)
+}
+
+fn column_to_array(
+    column: &Column,
+    num_rows: usize,
+    precision: Precision,
+) -> TskvResult<(Field, ArrayRef)> {
+    let name = column.name_ext()?;
+    let nullbits = column.nullbit_ext()?;
+    let nulls = NullBuffer::new(BooleanBuffer::new(
+        Buffer::from_slice_ref(nullbits.bytes()),
+        0,
+        num_rows,
+    ));
+    let column_values_len = |len: usize| -> TskvResult<()> {
+        if len < num_rows {
+            return Err(CommonSnafu {
+                reason: format!("column '{name}' has {len} values, but {num_rows} rows expected"),
+            }
+            .build());
+        }
+        Ok(())
+    };
+
+    let array: ArrayRef = match (column.column_type(), column.field_type()) {
+        (ColumnType::Time, _) => {
+            column_values_len(column.int_values_len()?)?;
+            let values = column
+                .int_values()?
+                .iter()
+                .take(num_rows)
+                .collect::<Vec<_>>();
+            // The time column is never null.
+            match precision {
+                Precision::MS => Arc::new(PrimitiveArray::<TimestampMillisecondType>::new(
+                    values.into(),
+                    None,
+                )),
+                Precision::US => Arc::new(PrimitiveArray::<TimestampMicrosecondType>::new(
+                    values.into(),
+                    None,
+                )),
+                Precision::NS => Arc::new(PrimitiveArray::<TimestampNanosecondType>::new(
+                    values.into(),
+                    None,
+                )),
+            }
+        }
+        (ColumnType::Tag, _) | (ColumnType::Field, FieldType::String) => {
+            column_values_len(column.string_values_len()?)?;
+            let values = column.string_values()?;
+            Arc::new(
+                (0..num_rows)
+                    .map(|i| nulls.is_valid(i).then(|| values.get(i)))
+                    .collect::<StringArray>(),
+            )
+        }
+        (ColumnType::Field, FieldType::Float) => {
+            column_values_len(column.float_values_len()?)?;
+            let values = column
+                .float_values()?
+                .iter()
+                .take(num_rows)
+                .collect::<Vec<_>>();
+            Arc::new(Float64Array::new(values.into(), Some(nulls)))
+        }
+        (ColumnType::Field, FieldType::Integer) => {
+            column_values_len(column.int_values_len()?)?;
+            let values = column
+                .int_values()?
+                .iter()
+                .take(num_rows)
+                .collect::<Vec<_>>();
+            Arc::new(Int64Array::new(values.into(), Some(nulls)))
+        }
+        (ColumnType::Field, FieldType::Unsigned) => {
+            column_values_len(column.uint_values_len()?)?;
+            let values = column
+                .uint_values()?
+                .iter()
+                .take(num_rows)
+                .collect::<Vec<_>>();
+            Arc::new(UInt64Array::new(values.into(), Some(nulls)))
+        }
+        (ColumnType::Field, FieldType::Boolean) => {
+            column_values_len(column.bool_values_len()?)?;
+            let values = column.bool_values()?;
+            Arc::new(
+                (0..num_rows)
+                    .map(|i| nulls.is_valid(i).then(|| values.get(i)))
+                    .collect::<BooleanArray>(),
+            )
+        }
+        (column_type, field_type) => {
+            return Err(CommonSnafu {
+                reason: format!("column '{name}' has unknown type {column_type:?} {field_type:?}"),
+            }
+            .build());
+        }
+    };
+
+    let nullable = column.column_type() != ColumnType::Time;
+    Ok((Field::new(name, array.data_type().clone(), nullable), array))
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use datafusion::arrow::array::{Array, AsArray};
+    use datafusion::arrow::datatypes::{DataType, Float64Type, TimeUnit};
+    use protos::kv_service::{raft_write_command, RaftWriteCommand, WriteDataRequest};
+    use protos::models::{
+        Column, ColumnArgs, ColumnType, FieldType, Points, PointsArgs, Table, TableArgs, Values,
+        ValuesArgs,
+    };
+    use utils::precision::Precision;
+
+    use super::{write_data_to_record_batches, ChangeFeed};
+
+    /// Table "cpu" with columns time, host (tag) and usage (float field, the second row is null).
+    fn cpu_points() -> Vec<u8> {
+        let mut fbb = flatbuffers::FlatBufferBuilder::new();
+        let all_valid = fbb.create_vector(&[0b111_u8]);
+        let second_null = fbb.create_vector(&[0b101_u8]);
+
+        let time_values = fbb.create_vector(&[1_i64, 2, 3]);
+        let time_values = Values::create(
+            &mut fbb,
+            &ValuesArgs {
+                int_value: Some(time_values),
+                ..Default::default()
+            },
+        );
+        let name = fbb.create_string("time");
+        let time = Column::create(
+            &mut fbb,
+            &ColumnArgs {
+                name: Some(name),
+                column_type: ColumnType::Time,
+                field_type: FieldType::Integer,
+                col_values: Some(time_values),
+                nullbits: Some(all_valid),
+            },
+        );
+
+        let hosts = ["a", "b", "c"].map(|h| fbb.create_string(h));
+        let hosts = fbb.create_vector(&hosts);
+        let host_values = Values::create(
+            &mut fbb,
+            &ValuesArgs {
+                string_value: Some(hosts),
+                ..Default::default()
+            },
+        );
+        let name = fbb.create_string("host");
+        let host = Column::create(
+            &mut fbb,
+            &ColumnArgs {
+                name: Some(name),
+                column_type: ColumnType::Tag,
+                field_type: FieldType::String,
+                col_values: Some(host_values),
+                nullbits: Some(all_valid),
+            },
+        );
+
+        let usages = fbb.create_vector(&[0.5_f64, 0.0, 1.5]);
+        let usage_values = Values::create(
+            &mut fbb,
+            &ValuesArgs {
+                float_value: Some(usages),
+                ..Default::default()
+            },
+        );
+        let name = fbb.create_string("usage");
+        let usage = Column::create(
+            &mut fbb,
+            &ColumnArgs {
+                name: Some(name),
+                column_type: ColumnType::Field,
+                field_type: FieldType::Float,
+                col_values: Some(usage_values),
+                nullbits: Some(second_null),
+            },
+        );
+
+        let columns = fbb.create_vector(&[time, host, usage]);
+        let tab = fbb.create_string("cpu");
+        let table = Table::create(
+            &mut fbb,
+            &TableArgs {
+                tab: Some(tab),
+                columns: Some(columns),
+                num_rows: 3,
+            },
+        );
+        let tables = fbb.create_vector(&[table]);
+        let db = fbb.create_string("db");
+        let points = Points::create(
+            &mut fbb,
+            &PointsArgs {
+                db: Some(db),
+                tables: Some(tables),
+            },
+        );
+        fbb.finish(points, None);
+        fbb.finished_data().to_vec()
+    }
+
+    #[test]
+    fn test_write_data_to_record_batches() {
+        let points = cpu_points();
+
+        let batches = write_data_to_record_batches(&points, Precision::MS, |_| true).unwrap();
+        assert_eq!(batches.len(), 1);
+        let (table, batch) = &batches[0];
+        assert_eq!(table, "cpu");
+        assert_eq!(batch.num_rows(), 3);
+        let schema = batch.schema();
+        assert_eq!(
+            schema.field_with_name("time").unwrap().data_type(),
+            &DataType::Timestamp(TimeUnit::Millisecond, None)
+        );
+        let host = batch.column(1).as_string::<i32>();
+        assert_eq!(host.value(2), "c");
+        let usage = batch.column(2).as_primitive::<Float64Type>();
+        assert_eq!(usage.null_count(), 1);
+        assert!(usage.is_null(1));
+        assert_eq!(usage.value(2), 1.5);
+
+        let batches = write_data_to_record_batches(&points, Precision::MS, |t| t != "cpu").unwrap();
+        assert!(batches.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_change_feed() {
+        let feed = ChangeFeed::new(16);
+        let command = RaftWriteCommand {
+            tenant: "cnosdb".to_string(),
+            db_name: "db".to_string(),
+            replica_id: 1,
+            timestamp: 0,
+            command: Some(raft_write_command::Command::WriteData(WriteDataRequest {
+                data: vec![],
+                precision: 0,
+                series_limits: None,
+            })),
+        };
+        // Published before subscribing, only the index is recorded.
+        feed.publish(3, 10, || unreachable!());
+
+        let (mut receiver, applied) = feed.subscribe();
+        assert_eq!(applied, HashMap::from([(3, 10)]));
+
+        feed.publish(3, 11, || Some(command.clone()));
+        let event = receiver.recv().await.unwrap();
+        assert_eq!((event.vnode_id, event.index), (3, 11));
+        assert_eq!(event.command, command);
+
+        feed.remove_vnode(3);
+        assert!(feed.subscribe().1.is_empty());
+    }
+}
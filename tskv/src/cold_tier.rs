@@ -0,0 +1,619 @@
+//! # Cold tier
+//!
+//! TSM files whose data are all older than `storage.cold_tier.cold_duration` are uploaded
+//! to an object store (S3, GCS, Azure Blob Storage or local filesystem) with their
+//! tombstones, and then the local files are removed.
+//!
+//! The object path of a moved file is recorded in [`CompactMeta::remote_path`], the file
+//! is read by blocks from the object store, and the blocks are cached in memory.
+//!
+//! Files in the cold tier are not picked by level compactions, but delta compactions may
+//! merge late data into them, and the merged file is written to the local `tsm` directory.
+//! Tombstones of the moved files are kept in the `cold` directory of the vnode, and are
+//! uploaded again by [`ColumnFile::upload_tombstone`] whenever they are written, so that
+//! a lost local tombstone can be restored from the object store.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::Arc;
+
+use cache::{AsyncCache, ShardedAsyncCache};
+use config::tskv::ColdTierConfig;
+use models::utils::{now_timestamp_micros, now_timestamp_millis, now_timestamp_nanos};
+use models::Timestamp;
+use object_store::aws::AmazonS3Builder;
+use object_store::azure::MicrosoftAzureBuilder;
+use object_store::gcp::GoogleCloudStorageBuilder;
+use object_store::local::LocalFileSystem as LocalObjectStore;
+use object_store::path::Path as ObjectPath;
+use object_store::ObjectStore;
+use snafu::ResultExt;
+use tokio::io::AsyncWriteExt;
+use trace::{error, info};
+use url::Url;
+use utils::precision::Precision;
+use utils::BloomFilter;
+
+use crate::error::{CommonSnafu, IOSnafu, ObjectStoreSnafu, TskvResult};
+use crate::file_system::async_filesystem::LocalFileSystem;
+use crate::file_system::file::remote_file::{BlockCache, RemoteFile};
+use crate::file_system::file::stream_reader::FileStreamReader;
+use crate::file_system::FileSystem;
+use crate::tsfamily::column_file::ColumnFile;
+use crate::tsfamily::summary::SummaryRequest;
+use crate::tsfamily::version::{CompactMeta, VersionEdit};
+use crate::tsm::TOMBSTONE_FILE_SUFFIX;
+use crate::vnode_store::VnodeStorage;
+use crate::{file_utils, ColumnFileId, VnodeId};
+
+pub type ColdTierRef = Arc<ColdTier>;
+
+pub struct ColdTier {
+    config: ColdTierConfig,
+    store: Arc<dyn ObjectStore>,
+    /// Prefix of the object paths of all files.
+    prefix: ObjectPath,
+    block_cache: Arc<BlockCache>,
+}
+
+impl ColdTier {
+    pub fn new(config: ColdTierConfig) -> TskvResult<Self> {
+        let (store, prefix) = build_object_store(&config.url, &ObjectStoreOptions::from(&config))?;
+        Ok(Self::with_object_store(config, store, prefix))
+    }
+
+    pub fn with_object_store(
+        config: ColdTierConfig,
+        store: Arc<dyn ObjectStore>,
+        prefix: ObjectPath,
+    ) -> Self {
+        let block_cache = Arc::new(ShardedAsyncCache::create_lru_sharded_cache(
+            config.max_cached_blocks,
+        ));
+        Self {
+            config,
+            store,
+            prefix,
+            block_cache,
+        }
+    }
+
+    pub fn config(&self) -> &ColdTierConfig {
+        &self.config
+    }
+
+    /// Object path of a TSM file: `{prefix}/{owner}/{vnode_id}/_{file_id}.tsm`.
+    pub fn tsm_location(
+        &self,
+        owner: &str,
+        vnode_id: VnodeId,
+        file_id: ColumnFileId,
+    ) -> ObjectPath {
+        self.prefix
+            .child(owner)
+            .child(vnode_id.to_string())
+            .child(file_utils::make_tsm_file_name(file_id))
+    }
+
+    /// Object path of the tombstone of the TSM file stored at `tsm_location`.
+    pub fn tombstone_location(tsm_location: &ObjectPath) -> ObjectPath {
+        let mut parts = tsm_location.parts().collect::<Vec<_>>();
+        if let Some(file_name) = parts.pop() {
+            let file_name: &str = file_name.as_ref();
+            let file_name = file_name.strip_suffix(".tsm").unwrap_or(file_name);
+            parts.push(format!("{file_name}.{TOMBSTONE_FILE_SUFFIX}").into());
+        }
+        ObjectPath::from_iter(parts)
+    }
+
+    /// Returns the max timestamp of the data which should be moved to the cold tier.
+    pub fn cold_time_threshold(&self, precision: Precision) -> Timestamp {
+        let cold_duration = self.config.cold_duration;
+        match precision {
+            Precision::MS => now_timestamp_millis() - cold_duration.as_millis() as i64,
+            Precision::US => now_timestamp_micros() - cold_duration.as_micros() as i64,
+            Precision::NS => now_timestamp_nanos() - cold_duration.as_nanos() as i64,
+        }
+    }
+
+    /// Upload the file to `location` by a multipart upload, the file is read in chunks.
+    pub async fn upload(&self, path: impl AsRef<Path>, location: &ObjectPath) -> TskvResult<()> {
+        let mut file = tokio::fs::File::open(path.as_ref())
+            .await
+            .context(IOSnafu)?;
+        let (multipart_id, mut writer) = self
+            .store
+            .put_multipart(location)
+            .await
+            .context(ObjectStoreSnafu)?;
+        let result = match tokio::io::copy(&mut file, &mut writer).await {
+            Ok(_) => writer.shutdown().await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            if let Err(abort_err) = self.store.abort_multipart(location, &multipart_id).await {
+                error!("Failed to abort the multipart upload to '{location}': {abort_err}");
+            }
+            return Err(e).context(IOSnafu);
+        }
+        Ok(())
+    }
+
+    /// Download the object to `path`, returns false if the object does not exist.
+    pub async fn download(
+        &self,
+        location: &ObjectPath,
+        path: impl AsRef<Path>,
+    ) -> TskvResult<bool> {
+        let data = match self.store.get(location).await {
+            Ok(result) => result.bytes().await.context(ObjectStoreSnafu)?,
+            Err(object_store::Error::NotFound { .. }) => return Ok(false),
+            Err(e) => return Err(e).context(ObjectStoreSnafu),
+        };
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir).await.context(IOSnafu)?;
+        }
+        tokio::fs::write(path, data).await.context(IOSnafu)?;
+        Ok(true)
+    }
+
+    /// Delete a TSM file and its tombstone from the object store.
+    pub async fn delete(&self, tsm_location: &ObjectPath) -> TskvResult<()> {
+        for location in [Self::tombstone_location(tsm_location), tsm_location.clone()] {
+            match self.store.delete(&location).await {
+                Ok(_) | Err(object_store::Error::NotFound { .. }) => {}
+                Err(e) => return Err(e).context(ObjectStoreSnafu),
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove the cached blocks of a file of `size` bytes.
+    pub async fn evict_blocks(&self, location: &ObjectPath, size: usize) {
+        let block_size = self.block_size();
+        for block_idx in 0..=size / block_size {
+            self.block_cache
+                .remove(&(location.clone(), block_idx))
+                .await;
+        }
+    }
+
+    pub async fn open_file_reader(
+        &self,
+        location: &ObjectPath,
+    ) -> TskvResult<Box<FileStreamReader>> {
+        let file = RemoteFile::open(
+            self.store.clone(),
+            location.clone(),
+            self.block_size(),
+            self.block_cache.clone(),
+        )
+        .await
+        .context(IOSnafu)?;
+        Ok(Box::new(FileStreamReader::new(
+            Box::new(file),
+            location.to_string().into(),
+        )))
+    }
+
+    fn block_size(&self) -> usize {
+        (self.config.block_size as usize).max(1)
+    }
+
+    /// Move the TSM files of the vnode whose data are all older than the `cold_duration`
+    /// to the object store. Delta files are not moved.
+    pub async fn move_cold_files(&self, vnode: &VnodeStorage) -> TskvResult<()> {
+        let ts_family = vnode.ts_family();
+        let (version, owner, vnode_id, precision) = {
+            let tsf = ts_family.read().await;
+            if !tsf.can_compaction() {
+                return Ok(());
+            }
+            (
+                tsf.version(),
+                tsf.owner(),
+                tsf.tf_id(),
+                *tsf.db_config().precision(),
+            )
+        };
+        let threshold = self.cold_time_threshold(precision);
+
+        let mut picked_files = vec![];
+        for level in version.levels_info().iter().skip(1) {
+            for file in level.files.iter() {
+                if file.is_cold() || file.is_deleted() || file.time_range().max_ts >= threshold {
+                    continue;
+                }
+                // Mark the file as compacting, so it won't be picked by compactions.
+                if file.mark_compacting().await {
+                    picked_files.push(file.clone());
+                }
+            }
+        }
+        if picked_files.is_empty() {
+            return Ok(());
+        }
+
+        let picked_file_ids = picked_files
+            .iter()
+            .map(|f| f.file_id())
+            .collect::<HashSet<_>>();
+        let cold_dir = version.storage_opt().cold_dir(&owner, vnode_id);
+        let mut version_edit =
+            VersionEdit::new_update_vnode(vnode_id, owner.to_string(), version.last_seq());
+        let mut file_metas = HashMap::with_capacity(picked_files.len());
+        let mut moved = Vec::with_capacity(picked_files.len());
+        for file in picked_files.iter() {
+            let location = self.tsm_location(&owner, vnode_id, file.file_id());
+            match self.upload_column_file(file, &location, &cold_dir).await {
+                Ok(bloom_filter) => {
+                    file_metas.insert(file.file_id(), bloom_filter);
+                }
+                Err(e) => {
+                    error!(
+                        "Failed to move tsm file {} of vnode {vnode_id} to cold tier: {e}",
+                        file.file_id()
+                    );
+                    for location in moved.iter() {
+                        let _ = self.delete(location).await;
+                    }
+                    version.unmark_compacting_files(&picked_file_ids).await;
+                    return Err(e);
+                }
+            }
+
+            let mut meta = CompactMeta::from(file.as_ref());
+            meta.tsf_id = vnode_id;
+            meta.remote_path = Some(location.to_string());
+            version_edit.del_file(file.level(), file.file_id(), file.is_delta());
+            version_edit.add_file(meta, version.max_level_ts());
+            moved.push(location);
+        }
+
+        info!(
+            "Moving {} tsm files of vnode {vnode_id} to cold tier: {:?}",
+            moved.len(),
+            moved
+        );
+        let request = SummaryRequest {
+            version_edit,
+            mem_caches: None,
+            file_metas: Some(file_metas),
+            ts_family,
+        };
+        let result = vnode
+            .get_summary()
+            .write()
+            .await
+            .apply_version_edit(&request)
+            .await;
+        if let Err(e) = result {
+            error!("Failed to apply version edit of moving tsm files of vnode {vnode_id}: {e}");
+            version.unmark_compacting_files(&picked_file_ids).await;
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Upload a TSM file and its tombstone to `location`, the tombstone is also copied
+    /// to `cold_dir` so that it can be updated locally.
+    async fn upload_column_file(
+        &self,
+        file: &ColumnFile,
+        location: &ObjectPath,
+        cold_dir: &Path,
+    ) -> TskvResult<Arc<BloomFilter>> {
+        let bloom_filter = file.load_bloom_filter().await?;
+        self.upload(file.file_path(), location).await?;
+        let tombstone_path = file.tombstone_path();
+        if LocalFileSystem::try_exists(&tombstone_path) {
+            self.upload(&tombstone_path, &Self::tombstone_location(location))
+                .await?;
+            tokio::fs::create_dir_all(cold_dir).await.context(IOSnafu)?;
+            let cold_tombstone_path = file_utils::make_tsm_tombstone_file(cold_dir, file.file_id());
+            tokio::fs::copy(&tombstone_path, &cold_tombstone_path)
+                .await
+                .context(IOSnafu)?;
+        }
+        Ok(bloom_filter)
+    }
+}
+
+impl std::fmt::Debug for ColdTier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ColdTier")
+            .field("url", &self.config.url)
+            .field("prefix", &self.prefix)
+            .finish()
+    }
+}
+
+/// Endpoint and credentials of an object store, only the options of the scheme
+/// of the url are used.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ObjectStoreOptions {
+    pub region: Option<String>,
+    pub endpoint_url: Option<String>,
+    pub access_key_id: Option<String>,
+    pub secret_access_key: Option<String>,
+    pub service_account_path: Option<String>,
+    pub account_name: Option<String>,
+    pub access_key: Option<String>,
+}
+
+impl ObjectStoreOptions {
+    /// Build the options from key-value pairs, keys are the field names of [`ObjectStoreOptions`].
+    pub fn from_map(options: &HashMap<String, String>) -> TskvResult<Self> {
+        let mut ret = Self::default();
+        for (key, value) in options {
+            let field = match key.to_ascii_lowercase().as_str() {
+                "region" => &mut ret.region,
+                "endpoint_url" => &mut ret.endpoint_url,
+                "access_key_id" => &mut ret.access_key_id,
+                "secret_access_key" => &mut ret.secret_access_key,
+                "service_account_path" => &mut ret.service_account_path,
+                "account_name" => &mut ret.account_name,
+                "access_key" => &mut ret.access_key,
+                _ => {
+                    return Err(CommonSnafu {
+                        reason: format!("Unknown object store option '{key}'"),
+                    }
+                    .build())
+                }
+            };
+            *field = Some(value.clone());
+        }
+        Ok(ret)
+    }
+}
+
+impl From<&ColdTierConfig> for ObjectStoreOptions {
+    fn from(config: &ColdTierConfig) -> Self {
+        Self {
+            region: config.region.clone(),
+            endpoint_url: config.endpoint_url.clone(),
+            access_key_id: config.access_key_id.clone(),
+            secret_access_key: config.secret_access_key.clone(),
+            service_account_path: config.service_account_path.clone(),
+            account_name: config.account_name.clone(),
+            access_key: config.access_key.clone(),
+        }
+    }
+}
+
+/// Build the object store by the url, returns the store and the prefix of the object paths.
+pub(crate) fn build_object_store(
+    url: &str,
+    options: &ObjectStoreOptions,
+) -> TskvResult<(Arc<dyn ObjectStore>, ObjectPath)> {
+    let url = Url::parse(url).map_err(|e| {
+        CommonSnafu {
+            reason: format!("Invalid url of object store '{url}': {e}"),
+        }
+        .build()
+    })?;
+    let bucket = url.host_str().unwrap_or_default();
+    let prefix = ObjectPath::from(url.path());
+
+    let store: Arc<dyn ObjectStore> = match url.scheme() {
+        "s3" => {
+            let mut builder = AmazonS3Builder::new()
+                .with_bucket_name(bucket)
+                .with_allow_http(true);
+            if let Some(region) = &options.region {
+                builder = builder.with_region(region);
+            }
+            if let Some(endpoint_url) = &options.endpoint_url {
+                builder = builder.with_endpoint(endpoint_url);
+            }
+            if let Some(access_key_id) = &options.access_key_id {
+                builder = builder.with_access_key_id(access_key_id);
+            }
+            if let Some(secret_access_key) = &options.secret_access_key {
+                builder = builder.with_secret_access_key(secret_access_key);
+            }
+            Arc::new(builder.build().context(ObjectStoreSnafu)?)
+        }
+        "gcs" => {
+            let mut builder = GoogleCloudStorageBuilder::new().with_bucket_name(bucket);
+            if let Some(path) = &options.service_account_path {
+                builder = builder.with_service_account_path(path);
+            }
+            Arc::new(builder.build().context(ObjectStoreSnafu)?)
+        }
+        "azblob" => {
+            let mut builder = MicrosoftAzureBuilder::new().with_container_name(bucket);
+            if let Some(account_name) = &options.account_name {
+                builder = builder.with_account(account_name);
+            }
+            if let Some(access_key) = &options.access_key {
+                builder = builder.with_access_key(access_key);
+            }
+            Arc::new(builder.build().context(ObjectStoreSnafu)?)
+        }
+        "file" => {
+            std::fs::create_dir_all(url.path()).context(IOSnafu)?;
+            let store = LocalObjectStore::new_with_prefix(url.path()).context(ObjectStoreSnafu)?;
+            return Ok((Arc::new(store), ObjectPath::default()));
+        }
+        scheme => {
+            return Err(CommonSnafu {
+                reason: format!("Unsupported object store '{scheme}'"),
+            }
+            .build())
+        }
+    };
+
+    Ok((store, prefix))
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+    use std::sync::{Arc, Weak};
+
+    use arrow::datatypes::TimeUnit;
+    use arrow_array::RecordBatch;
+    use config::tskv::ColdTierConfig;
+    use models::codec::Encoding;
+    use models::predicate::domain::TimeRange;
+    use models::schema::tskv_table_schema::{
+        ColumnType, TableColumn, TskvTableSchema, TskvTableSchemaRef,
+    };
+    use models::{SeriesKey, ValueType};
+    use object_store::path::Path as ObjectPath;
+    use tokio::sync::RwLock;
+
+    use super::{ColdTier, ColdTierRef};
+    use crate::file_utils;
+    use crate::tsfamily::column_file::ColumnFile;
+    use crate::tsfamily::version::CompactMeta;
+    use crate::tsm::reader::decode_pages;
+    use crate::tsm::tombstone::TsmTombstone;
+    use crate::tsm::writer::test::{i64_column, ts_column};
+    use crate::tsm::writer::TsmWriter;
+
+    #[test]
+    fn test_tombstone_location() {
+        let tsm_location = ObjectPath::from("cold/cnosdb.public/1/_000010.tsm");
+        assert_eq!(
+            ColdTier::tombstone_location(&tsm_location),
+            ObjectPath::from("cold/cnosdb.public/1/_000010.tombstone")
+        );
+    }
+
+    async fn write_tsm_file(tsm_dir: &str) -> (TskvTableSchemaRef, RecordBatch, PathBuf) {
+        let schema = Arc::new(TskvTableSchema::new(
+            "cnosdb".to_string(),
+            "public".to_string(),
+            "test0".to_string(),
+            vec![
+                TableColumn::new(
+                    0,
+                    "time".to_string(),
+                    ColumnType::Time(TimeUnit::Nanosecond),
+                    Encoding::default(),
+                ),
+                TableColumn::new(
+                    1,
+                    "f1".to_string(),
+                    ColumnType::Field(ValueType::Integer),
+                    Encoding::default(),
+                ),
+            ],
+        ));
+        let data = RecordBatch::try_new(
+            schema.to_record_data_schema(),
+            vec![
+                ts_column((1..=1000).collect()),
+                i64_column((1..=1000).collect()),
+            ],
+        )
+        .unwrap();
+        let mut writer = TsmWriter::open(&tsm_dir, 1, 0, false, Encoding::Null)
+            .await
+            .unwrap();
+        writer
+            .write_record_batch(1, SeriesKey::default(), schema.clone(), data.clone())
+            .await
+            .unwrap();
+        writer.finish().await.unwrap();
+        (schema, data, writer.path().to_path_buf())
+    }
+
+    fn cold_column_file(
+        cold_tier: &ColdTierRef,
+        location: &ObjectPath,
+        cold_dir: &str,
+    ) -> ColumnFile {
+        let meta = CompactMeta {
+            file_id: 1,
+            level: 1,
+            min_ts: 1,
+            max_ts: 1000,
+            remote_path: Some(location.to_string()),
+            ..Default::default()
+        };
+        ColumnFile::with_compact_data(
+            &meta,
+            file_utils::make_tsm_file(cold_dir, 1),
+            RwLock::new(None),
+            Weak::new(),
+            Some(cold_tier.clone()),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_read_cold_column_file() {
+        let dir = "/tmp/test/cold_tier/test_read_cold_column_file";
+        let _ = std::fs::remove_dir_all(dir);
+        let tsm_dir = format!("{dir}/tsm");
+        let cold_dir = format!("{dir}/cold");
+        let (schema, data, tsm_path) = write_tsm_file(&tsm_dir).await;
+
+        // Read by small blocks to read across blocks.
+        let config = ColdTierConfig {
+            url: format!("file://{dir}/object_store"),
+            block_size: 64,
+            max_cached_blocks: 16,
+            ..Default::default()
+        };
+        let cold_tier = Arc::new(ColdTier::new(config).unwrap());
+        let location = cold_tier.tsm_location("cnosdb.public", 1, 1);
+        cold_tier.upload(&tsm_path, &location).await.unwrap();
+        std::fs::remove_file(&tsm_path).unwrap();
+
+        let column_file = cold_column_file(&cold_tier, &location, &cold_dir);
+        assert!(column_file.is_cold());
+        assert!(column_file.maybe_contains_series_id(1).await.unwrap());
+
+        let reader = column_file.open_reader().await.unwrap();
+        let pages = reader.read_series_pages(1, 0).await.unwrap();
+        let data_read = decode_pages(pages, schema.meta(), None).unwrap();
+        assert_eq!(data, data_read);
+
+        cold_tier.delete(&location).await.unwrap();
+        assert!(column_file.open_reader().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_upload_cold_tombstone() {
+        let dir = "/tmp/test/cold_tier/test_upload_cold_tombstone";
+        let _ = std::fs::remove_dir_all(dir);
+        let tsm_dir = format!("{dir}/tsm");
+        let cold_dir = format!("{dir}/cold");
+        let (_, _, tsm_path) = write_tsm_file(&tsm_dir).await;
+
+        let config = ColdTierConfig {
+            url: format!("file://{dir}/object_store"),
+            ..Default::default()
+        };
+        let cold_tier = Arc::new(ColdTier::new(config).unwrap());
+        let location = cold_tier.tsm_location("cnosdb.public", 1, 1);
+        cold_tier.upload(&tsm_path, &location).await.unwrap();
+        std::fs::remove_file(&tsm_path).unwrap();
+        let column_file = cold_column_file(&cold_tier, &location, &cold_dir);
+
+        // Delete data of the file after it is moved to the cold tier.
+        std::fs::create_dir_all(&cold_dir).unwrap();
+        let tombstone = TsmTombstone::open(&cold_dir, 1).await.unwrap();
+        tombstone
+            .add_range(&[(1, 1)], TimeRange::new(1, 500), None)
+            .await
+            .unwrap();
+        tombstone.flush().await.unwrap();
+        drop(tombstone);
+        column_file.upload_tombstone().await.unwrap();
+
+        // The lost local tombstone is restored from the object store.
+        std::fs::remove_file(column_file.tombstone_path()).unwrap();
+        let reader = column_file.open_reader().await.unwrap();
+        assert_eq!(
+            reader
+                .tombstone()
+                .get_column_overlapped_time_ranges(1, 1, &TimeRange::new(1, 1000)),
+            vec![TimeRange::new(1, 500)]
+        );
+    }
+}
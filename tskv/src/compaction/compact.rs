@@ -526,7 +526,7 @@ pub async fn run_normal_compaction_job(
     let mut tsm_readers = Vec::new();
     for file in request.files.iter() {
         tsm_file_metas_will_delete.push(CompactMeta::from(file.as_ref()));
-        let tsm_reader = request.version.get_tsm_reader(file.file_path()).await?;
+        let tsm_reader = request.version.get_column_file_reader(file).await?;
         tsm_readers.push(tsm_reader);
     }
 
@@ -564,13 +564,13 @@ pub async fn run_delta_compaction_job(
         None => Vec::with_capacity(delta_files.len()),
         Some(f) => {
             let mut tsm_readers = Vec::with_capacity(1 + delta_files.len());
-            tsm_readers.push(request.version.get_tsm_reader(f.file_path()).await?);
+            tsm_readers.push(request.version.get_column_file_reader(f).await?);
             tsm_readers
         }
     };
 
     for file in delta_files {
-        let l0_file_reader = request.version.get_tsm_reader(file.file_path()).await?;
+        let l0_file_reader = request.version.get_column_file_reader(file).await?;
         let compacted_all_excluded_time_range = l0_file_reader
             .add_tombstone_and_compact_to_tmp(out_time_range)
             .await?;
@@ -20,6 +20,7 @@ use crate::tsfamily::level_info::LevelInfo;
 use crate::tsfamily::version::Version;
 use crate::tsm::writer::TsmWriter;
 use crate::tsm::TsmTombstone;
+use crate::vnode_store::expire_column_files;
 use crate::ColumnFileId;
 
 #[tokio::test]
@@ -1306,3 +1307,112 @@ async fn test_big_compaction_2() {
 
     check_column_file(dir, version_edit, expected_data, out_level).await;
 }
+
+/// Test table TTL: column files whose data are all expired are returned to be deleted,
+/// expired data in other column files are marked by tombstones and removed by compaction.
+#[tokio::test]
+async fn test_compaction_table_ttl() {
+    let schema = TskvTableSchema::new(
+        "cnosdb".to_string(),
+        "public".to_string(),
+        "test0".to_string(),
+        vec![
+            TableColumn::new(
+                0,
+                "time".to_string(),
+                ColumnType::Time(TimeUnit::Nanosecond),
+                Encoding::default(),
+            ),
+            TableColumn::new(
+                1,
+                "f1".to_string(),
+                ColumnType::Field(ValueType::Integer),
+                Encoding::default(),
+            ),
+        ],
+    );
+    let schema = Arc::new(schema);
+    let data1 = RecordBatch::try_new(
+        schema.to_record_data_schema(),
+        vec![
+            timestamp_column(vec![1, 2, 3]),
+            i64_column(vec![111, 112, 113]),
+        ],
+    )
+    .unwrap();
+    let data2 = RecordBatch::try_new(
+        schema.to_record_data_schema(),
+        vec![
+            timestamp_column(vec![4, 5, 6]),
+            i64_column(vec![214, 215, 216]),
+        ],
+    )
+    .unwrap();
+    let data3 = RecordBatch::try_new(
+        schema.to_record_data_schema(),
+        vec![timestamp_column(vec![8, 9]), i64_column(vec![318, 319])],
+    )
+    .unwrap();
+
+    let expected_data = RecordBatch::try_new(
+        schema.to_record_data_schema(),
+        vec![
+            timestamp_column(vec![5, 6, 8, 9]),
+            i64_column(vec![215, 216, 318, 319]),
+        ],
+    )
+    .unwrap();
+
+    let data = vec![
+        HashMap::from([(1, data1)]),
+        HashMap::from([(1, data2)]),
+        HashMap::from([(1, data3)]),
+    ];
+
+    let expected_data = HashMap::from([(1 as SeriesId, vec![expected_data])]);
+
+    let dir = "/tmp/test/compaction/table_ttl";
+    let _ = std::fs::remove_dir_all(dir);
+    let tenant_database = Arc::new("cnosdb.dba".to_string());
+    let opt = create_options(dir.to_string(), 1);
+    let dir = opt.storage.tsm_dir(&tenant_database, 1);
+    let max_level_ts = 9;
+
+    let (next_file_id, files) =
+        write_data_blocks_to_column_file(&dir, data, schema.clone(), 1).await;
+    let mut compact_req = prepare_compaction(
+        tenant_database,
+        opt,
+        next_file_id,
+        files[1..].to_vec(),
+        max_level_ts,
+    );
+    let mut version = compact_req.version.inner();
+    for f in files.iter() {
+        version.levels_info_mut()[f.level() as usize].push_column_file(f.clone());
+    }
+    compact_req.version = Arc::new(version);
+
+    // Data before 5 are expired.
+    let tables_min_ts = HashMap::from([("test0".to_string(), (5, schema))]);
+    let expired_files = expire_column_files(&compact_req.version, &tables_min_ts)
+        .await
+        .unwrap();
+    let expired_file_ids = expired_files
+        .iter()
+        .map(|f| f.file_id())
+        .collect::<Vec<_>>();
+    assert_eq!(expired_file_ids, vec![files[0].file_id()]);
+    let tombstone = TsmTombstone::open(&dir, files[1].file_id()).await.unwrap();
+    assert_eq!(
+        tombstone.get_column_overlapped_time_ranges(1, 0, &TimeRange::new(4, 6)),
+        vec![TimeRange::new(i64::MIN, 4)]
+    );
+
+    let out_level = compact_req.out_level;
+    let (version_edit, _) = run_normal_compaction_job(compact_req, VnodeCompactionMetrics::fake())
+        .await
+        .unwrap()
+        .unwrap();
+    check_column_file(dir, version_edit, expected_data, out_level).await;
+}
@@ -198,7 +198,8 @@ impl LevelCompactionPicker {
 
         let mut picking_file_size = 0_u64;
         for file in src_files.iter() {
-            if !file.mark_compacting().await {
+            // Files in cold tier are not compacted by levels.
+            if file.is_cold() || !file.mark_compacting().await {
                 continue;
             }
             picking_file_size += file.size();
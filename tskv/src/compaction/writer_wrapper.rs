@@ -73,6 +73,7 @@ impl WriterWrapper {
                 min_ts: tsm_writer.min_ts(),
                 max_ts: tsm_writer.max_ts(),
                 is_delta: false,
+                remote_path: None,
             };
             self.version_edit.add_file(cm, self.max_level_ts);
             let bloom_filter = tsm_writer.into_series_bloom_filter();
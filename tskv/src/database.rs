@@ -9,16 +9,18 @@ use models::predicate::domain::TimeRange;
 use models::schema::database_schema::{DatabaseConfig, DatabaseSchema};
 use models::schema::tskv_table_schema::{TskvTableSchema, TskvTableSchemaRef};
 use models::{SeriesId, SeriesKey};
+use protos::kv_service::SeriesLimits;
 use protos::models::{Column, ColumnType, FieldType, Table};
 use snafu::{OptionExt, ResultExt};
 use tokio::sync::RwLock;
 use utils::precision::Precision;
 
 use crate::error::{
-    CommonSnafu, IndexErrSnafu, ModelSnafu, SchemaSnafu, TableNotFoundSnafu, TskvResult,
+    CommonSnafu, IndexErrSnafu, ModelSnafu, SchemaSnafu, SeriesLimitExceededSnafu,
+    TableNotFoundSnafu, TskvError, TskvResult,
 };
-use crate::index::ts_index::TSIndex;
-use crate::index::IndexResult;
+use crate::index::ts_index::{SeriesLimit, TSIndex};
+use crate::index::{IndexError, IndexResult};
 use crate::kv_option::StorageOptions;
 use crate::mem_cache::row_data::{OrderedRowsData, RowData};
 use crate::mem_cache::series_data::RowGroup;
@@ -203,6 +205,7 @@ impl Database {
         ts_index: Arc<RwLock<TSIndex>>,
         recover_from_wal: bool,
         strict_write: Option<bool>,
+        series_limits: Option<&SeriesLimits>,
     ) -> TskvResult<HashMap<SeriesId, (SeriesKey, RowGroup)>> {
         let strict_write = strict_write.unwrap_or(self.config.strict_write());
 
@@ -231,6 +234,14 @@ impl Database {
                     .context(SchemaSnafu)?
             };
 
+            // The limits are in the write request so that the replicas apply the same limits,
+            // the series replayed from WAL were accepted before, they are not limited.
+            let series_limit = match series_limits {
+                Some(limits) if !recover_from_wal => {
+                    SeriesLimit::new(limits.database, limits.tables.get(fb_schema.table).copied())
+                }
+                _ => SeriesLimit::default(),
+            };
             let sids = Self::build_index(
                 &fb_schema,
                 &columns,
@@ -238,6 +249,7 @@ impl Database {
                 num_rows,
                 ts_index.clone(),
                 recover_from_wal,
+                series_limit,
             )
             .await?;
             // every row produces a sid
@@ -291,6 +303,7 @@ impl Database {
         row_num: usize,
         ts_index: Arc<RwLock<TSIndex>>,
         recover_from_wal: bool,
+        series_limit: SeriesLimit,
     ) -> TskvResult<Vec<(u32, SeriesKey)>> {
         let mut res_sids = Vec::with_capacity(row_num);
         let mut series_keys = Vec::with_capacity(row_num);
@@ -333,9 +346,14 @@ impl Database {
         let mut ids = ts_index
             .write()
             .await
-            .add_series_if_not_exists(series_keys)
+            .add_series_if_not_exists(series_keys, series_limit)
             .await
-            .context(IndexErrSnafu)?
+            .map_err(|source| match source {
+                IndexError::SeriesLimitExceeded { scope, limit, .. } => {
+                    SeriesLimitExceededSnafu { scope, limit }.build()
+                }
+                source => TskvError::IndexErr { source },
+            })?
             .into_iter();
         for item in res_sids.iter_mut() {
             if item.is_none() {
@@ -417,6 +417,27 @@ pub enum TskvError {
         source: FileSystemError,
     },
 
+    #[error_code(code = 59)]
+    #[snafu(display("Object store error: {}", source))]
+    ObjectStore {
+        source: object_store::Error,
+        location: Location,
+        backtrace: Backtrace,
+    },
+
+    #[error_code(code = 60)]
+    #[snafu(display(
+        "Too many series, {} has reached max_series, {} series are allowed in the replica set",
+        scope,
+        limit
+    ))]
+    SeriesLimitExceeded {
+        scope: String,
+        limit: u64,
+        location: Location,
+        backtrace: Backtrace,
+    },
+
     #[snafu(display("ModelError: {}", source))]
     #[error_code(code = 89)]
     ModelError {
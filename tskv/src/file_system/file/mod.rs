@@ -2,6 +2,7 @@ pub(crate) mod async_file;
 pub(crate) mod mmap_file;
 mod os;
 mod raw_file;
+pub(crate) mod remote_file;
 pub mod stream_reader;
 pub mod stream_writer;
 
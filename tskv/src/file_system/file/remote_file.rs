@@ -0,0 +1,149 @@
+use std::io::{Error, ErrorKind, Result};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use cache::{AsyncCache, ShardedAsyncCache};
+use object_store::path::Path;
+use object_store::ObjectStore;
+
+use crate::file_system::file::ReadableFile;
+
+/// Cache of the blocks read from object store, key is the location and the index of the block.
+pub type BlockCache = ShardedAsyncCache<(Path, usize), Bytes>;
+
+/// A read-only file in object store, the file is read by blocks, and the blocks
+/// are cached in the shared [`BlockCache`].
+pub struct RemoteFile {
+    store: Arc<dyn ObjectStore>,
+    location: Path,
+    size: usize,
+    block_size: usize,
+    block_cache: Arc<BlockCache>,
+}
+
+impl RemoteFile {
+    pub async fn open(
+        store: Arc<dyn ObjectStore>,
+        location: Path,
+        block_size: usize,
+        block_cache: Arc<BlockCache>,
+    ) -> Result<Self> {
+        let meta = store
+            .head(&location)
+            .await
+            .map_err(object_store_io_error)?;
+
+        Ok(Self {
+            store,
+            location,
+            size: meta.size,
+            block_size: block_size.max(1),
+            block_cache,
+        })
+    }
+
+    async fn read_block(&self, block_idx: usize) -> Result<Bytes> {
+        let key = (self.location.clone(), block_idx);
+        if let Some(block) = self.block_cache.get(&key).await {
+            return Ok(block);
+        }
+
+        let start = block_idx * self.block_size;
+        let end = (start + self.block_size).min(self.size);
+        let block = self
+            .store
+            .get_range(&self.location, start..end)
+            .await
+            .map_err(object_store_io_error)?;
+        self.block_cache.insert(key, block.clone()).await;
+
+        Ok(block)
+    }
+}
+
+#[async_trait]
+impl ReadableFile for RemoteFile {
+    async fn read_at(&self, pos: usize, data: &mut [u8]) -> Result<usize> {
+        let end = (pos + data.len()).min(self.size);
+        let mut offset = pos;
+        while offset < end {
+            let block_idx = offset / self.block_size;
+            let block = self.read_block(block_idx).await?;
+            let block_offset = offset - block_idx * self.block_size;
+            if block.len() <= block_offset {
+                // The object is shorter than it's metadata.
+                break;
+            }
+            let len = (block.len() - block_offset).min(end - offset);
+            let data_offset = offset - pos;
+            data[data_offset..data_offset + len]
+                .copy_from_slice(&block[block_offset..block_offset + len]);
+            offset += len;
+        }
+
+        Ok(offset.saturating_sub(pos))
+    }
+
+    fn file_size(&self) -> usize {
+        self.size
+    }
+}
+
+fn object_store_io_error(e: object_store::Error) -> Error {
+    match e {
+        object_store::Error::NotFound { .. } => Error::new(ErrorKind::NotFound, e),
+        _ => Error::new(ErrorKind::Other, e),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use bytes::Bytes;
+    use cache::{AsyncCache, ShardedAsyncCache};
+    use object_store::local::LocalFileSystem;
+    use object_store::path::Path;
+    use object_store::ObjectStore;
+
+    use super::RemoteFile;
+    use crate::file_system::file::ReadableFile;
+
+    #[tokio::test]
+    async fn test_remote_file_read_at() {
+        let dir = "/tmp/test/file_system/test_remote_file_read_at";
+        let _ = std::fs::remove_dir_all(dir);
+        std::fs::create_dir_all(dir).unwrap();
+
+        let store: Arc<dyn ObjectStore> = Arc::new(LocalFileSystem::new_with_prefix(dir).unwrap());
+        let location = Path::from("a/b.tsm");
+        let content = (0..100_u8).collect::<Vec<_>>();
+        store
+            .put(&location, Bytes::from(content.clone()))
+            .await
+            .unwrap();
+
+        let block_cache = Arc::new(ShardedAsyncCache::create_lru_sharded_cache(16));
+        let file = RemoteFile::open(store, location.clone(), 16, block_cache.clone())
+            .await
+            .unwrap();
+        assert_eq!(file.file_size(), 100);
+
+        // Read across blocks.
+        let mut buf = vec![0_u8; 40];
+        assert_eq!(file.read_at(10, &mut buf).await.unwrap(), 40);
+        assert_eq!(buf, content[10..50]);
+        assert!(block_cache.get(&(location.clone(), 0)).await.is_some());
+        assert!(block_cache.get(&(location.clone(), 3)).await.is_some());
+        assert!(block_cache.get(&(location.clone(), 4)).await.is_none());
+
+        // Read the tail of the file.
+        let mut buf = vec![0_u8; 40];
+        assert_eq!(file.read_at(90, &mut buf).await.unwrap(), 10);
+        assert_eq!(buf[..10], content[90..]);
+
+        // Read out of the file.
+        assert_eq!(file.read_at(100, &mut buf).await.unwrap(), 0);
+    }
+}
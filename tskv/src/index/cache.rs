@@ -216,6 +216,19 @@ impl IndexMemCache {
         self.id_map.get(&id).map(|info| info.key.clone())
     }
 
+    /// Number of the new series not flushed yet.
+    pub fn series_count(&self) -> usize {
+        self.id_map.len()
+    }
+
+    /// Tag values and their series ids of each tag key of the table.
+    pub fn get_inverted_by_table(
+        &self,
+        tab: &str,
+    ) -> Option<&HashMap<Vec<u8>, BTreeMap<Vec<u8>, roaring::RoaringBitmap>>> {
+        self.inverted.get(tab)
+    }
+
     pub fn get_inverted_by_range(
         &self,
         tab: &str,
@@ -236,6 +249,27 @@ impl IndexMemCache {
         bitmap
     }
 
+    /// Union the series ids of the tag values in range for which `filter` returns true.
+    pub fn get_inverted_by_range_filter(
+        &self,
+        tab: &str,
+        tag_key: &str,
+        range: impl RangeBounds<Vec<u8>>,
+        filter: impl Fn(&[u8]) -> bool,
+    ) -> roaring::RoaringBitmap {
+        let tag_map = self
+            .inverted
+            .get(tab)
+            .and_then(|item| item.get(tag_key.as_bytes()));
+        let mut bitmap = roaring::RoaringBitmap::new();
+        if let Some(bt) = tag_map {
+            for (_, rb) in bt.range(range).filter(|(value, _)| filter(value)) {
+                bitmap = bitmap.bitor(rb);
+            }
+        }
+        bitmap
+    }
+
     pub fn get_inverted_by_tags(&self, tab: &str, tags: &[models::Tag]) -> roaring::RoaringBitmap {
         if tags.is_empty() {
             let mut bitmap = roaring::RoaringBitmap::new();
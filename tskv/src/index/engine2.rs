@@ -165,6 +165,44 @@ impl IndexEngine2 {
         Ok(())
     }
 
+    /// Count the keys starting with `prefix` for which `filter` returns true.
+    pub fn count_prefix(&self, prefix: &[u8], filter: impl Fn(&[u8]) -> bool) -> IndexResult<u64> {
+        let reader = self.reader_txn()?;
+        let it = self
+            .db
+            .prefix_iter(&reader, prefix)
+            .map_err(|e| IndexStorageSnafu { msg: e.to_string() }.build())?;
+        let mut count = 0;
+        for val in it {
+            let val = val.map_err(|e| IndexStorageSnafu { msg: e.to_string() }.build())?;
+            if filter(&val.0) {
+                count += 1;
+            }
+        }
+
+        Ok(count)
+    }
+
+    /// Get all the keys starting with `prefix` and their bitmaps.
+    pub fn get_rb_by_prefix(
+        &self,
+        prefix: &[u8],
+    ) -> IndexResult<Vec<(Vec<u8>, roaring::RoaringBitmap)>> {
+        let reader = self.reader_txn()?;
+        let it = self
+            .db
+            .prefix_iter(&reader, prefix)
+            .map_err(|e| IndexStorageSnafu { msg: e.to_string() }.build())?;
+        let mut res = vec![];
+        for val in it {
+            let val = val.map_err(|e| IndexStorageSnafu { msg: e.to_string() }.build())?;
+            let rb = RoaringBitmap::deserialize_from(&*val.1).context(RoaringBitmapSnafu)?;
+            res.push((val.0.to_vec(), rb));
+        }
+
+        Ok(res)
+    }
+
     pub fn get_rb(&self, key: &[u8]) -> IndexResult<Option<roaring::RoaringBitmap>> {
         if let Some(data) = self.get(key)? {
             let rb = RoaringBitmap::deserialize_from(&*data).context(RoaringBitmapSnafu)?;
@@ -244,6 +282,34 @@ impl IndexEngine2 {
         Ok(bitmap)
     }
 
+    /// Union the series ids of the keys in range for which `filter` returns true.
+    pub fn get_series_id_by_range_filter(
+        &self,
+        range: impl RangeBounds<Vec<u8>>,
+        filter: impl Fn(&[u8]) -> bool,
+    ) -> IndexResult<roaring::RoaringBitmap> {
+        let range = (
+            convert_bound(range.start_bound()),
+            convert_bound(range.end_bound()),
+        );
+        let reader = self.reader_txn()?;
+        let iter = self
+            .db
+            .range(&reader, &range)
+            .map_err(|e| IndexStorageSnafu { msg: e.to_string() }.build())?;
+        let mut bitmap = roaring::RoaringBitmap::new();
+        for val in iter {
+            let val = val.map_err(|e| IndexStorageSnafu { msg: e.to_string() }.build())?;
+            if !filter(&val.0) {
+                continue;
+            }
+            let rb = RoaringBitmap::deserialize_from(&*val.1).context(RoaringBitmapSnafu)?;
+            bitmap = bitmap.bitor(rb);
+        }
+
+        Ok(bitmap)
+    }
+
     pub fn get_series_id_by_tags(
         &self,
         tab: &str,
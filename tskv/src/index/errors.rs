@@ -85,6 +85,18 @@ pub enum IndexError {
 
     #[snafu(display("file system error: {}", source))]
     FileSystemError { source: FileSystemError },
+
+    #[snafu(display(
+        "Too many series, {} has reached max_series, {} series are allowed in the replica set",
+        scope,
+        limit
+    ))]
+    SeriesLimitExceeded {
+        scope: String,
+        limit: u64,
+        location: Location,
+        backtrace: Backtrace,
+    },
 }
 
 pub type IndexResult<T> = Result<T, IndexError>;
@@ -6,7 +6,7 @@ use std::sync::Arc;
 
 use datafusion::arrow::datatypes::DataType;
 use datafusion::scalar::ScalarValue;
-use models::predicate::domain::{utf8_from, ColumnDomains, Domain, Range};
+use models::predicate::domain::{utf8_from, ColumnDomains, Domain, Range, TagPattern};
 use models::schema::tskv_table_schema::TskvTableSchema;
 use models::{tag, SeriesId, SeriesKey, Tag, TagKey, TagValue};
 use snafu::{OptionExt, ResultExt};
@@ -15,7 +15,7 @@ use trace::info;
 
 use super::cache::IndexCache;
 use super::engine2::IndexEngine2;
-use super::{DecodeSeriesKeySnafu, IndexResult};
+use super::{DecodeSeriesKeySnafu, IndexResult, SeriesLimitExceededSnafu};
 use crate::error::{ColumnNotFoundSnafu, IndexErrSnafu};
 use crate::index::{IndexEngine, SeriesAlreadyExistsSnafu};
 use crate::{byte_utils, TskvError, UpdateSetValue};
@@ -25,6 +25,31 @@ const SERIES_KEY_PREFIX: &str = "_key_";
 const TOMBSTONE_PREFIX: &str = "_tomb_";
 const AUTO_INCR_ID_KEY: &str = "_auto_incr_id";
 
+/// Maximum number of series allowed in the vnode, None means unlimited.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SeriesLimit {
+    pub database: Option<u64>,
+    pub table: Option<u64>,
+}
+
+impl SeriesLimit {
+    pub fn new(database: Option<u64>, table: Option<u64>) -> Self {
+        Self { database, table }
+    }
+
+    pub fn is_unlimited(&self) -> bool {
+        self.database.is_none() && self.table.is_none()
+    }
+}
+
+/// Series cardinality of a table in a vnode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableCardinality {
+    pub series_count: u64,
+    /// Number of distinct values of each tag key, in descending order of the number.
+    pub tag_value_counts: Vec<(String, u64)>,
+}
+
 /// Used to maintain forward and inverted indexes
 ///
 /// # Example
@@ -87,6 +112,11 @@ pub struct TSIndex {
 
     cache: IndexCache,
     storage: IndexEngine2,
+
+    /// Number of series in the vnode, loaded when a series limit is checked.
+    series_count: Option<u64>,
+    /// Number of series of the tables, loaded when a series limit is checked.
+    table_series_count: HashMap<String, u64>,
 }
 
 impl TSIndex {
@@ -116,6 +146,8 @@ impl TSIndex {
             incr_id: AtomicU32::new(incr_id),
             write_count: AtomicU32::new(0),
             cache: IndexCache::new(cap as usize),
+            series_count: None,
+            table_series_count: HashMap::new(),
         };
 
         trace::info!(
@@ -142,13 +174,23 @@ impl TSIndex {
         }
 
         self.cache.write(id, key.clone());
+        self.reset_series_count();
         Ok(())
     }
 
+    /// Get or create the series ids of the series keys.
+    ///
+    /// Returns `SeriesLimitExceeded` without creating any series if the new series
+    /// would exceed the `limit`.
     pub async fn add_series_if_not_exists(
         &mut self,
         series_keys: Vec<SeriesKey>,
+        limit: SeriesLimit,
     ) -> IndexResult<Vec<(u32, SeriesKey)>> {
+        if !limit.is_unlimited() {
+            self.check_series_limit(&series_keys, limit).await?;
+        }
+
         let mut ids = Vec::with_capacity(series_keys.len());
         for series_key in series_keys.into_iter() {
             let key_buf = encode_series_key(series_key.table(), series_key.tags());
@@ -167,6 +209,12 @@ impl TSIndex {
 
             // write index memcache
             trace::debug!("Index add new series id:{}, key: {}", id, series_key);
+            if let Some(count) = self.series_count.as_mut() {
+                *count += 1;
+            }
+            if let Some(count) = self.table_series_count.get_mut(series_key.table()) {
+                *count += 1;
+            }
             self.cache.write(id, series_key);
 
             let _ = self.check_to_flush(false).await;
@@ -175,6 +223,85 @@ impl TSIndex {
         Ok(ids)
     }
 
+    async fn check_series_limit(
+        &mut self,
+        series_keys: &[SeriesKey],
+        limit: SeriesLimit,
+    ) -> IndexResult<()> {
+        let mut new_series = HashSet::new();
+        let mut new_table_series: HashMap<&str, u64> = HashMap::new();
+        for series_key in series_keys {
+            if self.cache.get_series_id_by_key(series_key).is_some() {
+                continue;
+            }
+            let key_buf = encode_series_key(series_key.table(), series_key.tags());
+            if new_series.contains(&key_buf) || self.storage.exist(&key_buf)? {
+                continue;
+            }
+            new_series.insert(key_buf);
+            *new_table_series.entry(series_key.table()).or_default() += 1;
+        }
+        if new_series.is_empty() {
+            return Ok(());
+        }
+
+        if let Some(db_limit) = limit.database {
+            let count = self.load_series_count()?;
+            if count + new_series.len() as u64 > db_limit {
+                return SeriesLimitExceededSnafu {
+                    scope: "the database".to_string(),
+                    limit: db_limit,
+                }
+                .fail();
+            }
+        }
+        if let Some(table_limit) = limit.table {
+            for (tab, new_count) in new_table_series {
+                let count = self.load_table_series_count(tab).await?;
+                if count + new_count > table_limit {
+                    return SeriesLimitExceededSnafu {
+                        scope: format!("table '{}'", tab),
+                        limit: table_limit,
+                    }
+                    .fail();
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn load_series_count(&mut self) -> IndexResult<u64> {
+        if let Some(count) = self.series_count {
+            return Ok(count);
+        }
+
+        let id_key_len = SERIES_ID_PREFIX.len() + 4;
+        let stored = self
+            .storage
+            .count_prefix(SERIES_ID_PREFIX.as_bytes(), |key| key.len() == id_key_len)?;
+        let count = stored + self.cache.write_cache.series_count() as u64;
+        self.series_count = Some(count);
+
+        Ok(count)
+    }
+
+    async fn load_table_series_count(&mut self, tab: &str) -> IndexResult<u64> {
+        if let Some(count) = self.table_series_count.get(tab) {
+            return Ok(*count);
+        }
+
+        let count = self.get_series_id_bitmap(tab, &[]).await?.len();
+        self.table_series_count.insert(tab.to_string(), count);
+
+        Ok(count)
+    }
+
+    fn reset_series_count(&mut self) {
+        self.series_count = None;
+        self.table_series_count.clear();
+    }
+
     pub async fn get_series_id(&self, series_key: &SeriesKey) -> IndexResult<Option<u32>> {
         if let Some(id) = self.cache.get_series_id_by_key(series_key) {
             return Ok(Some(id));
@@ -238,6 +365,7 @@ impl TSIndex {
     }
 
     pub async fn del_series_info(&mut self, sid: u32) -> IndexResult<()> {
+        self.reset_series_count();
         let series_key = self.get_series_key(sid).await?;
         let _ = self.storage.delete(&encode_series_id_key(sid));
         if let Some(series_key) = series_key {
@@ -324,6 +452,53 @@ impl TSIndex {
         Ok(cache_rb.bitor(&engine_rb))
     }
 
+    /// Get the number of series of the table and the number of distinct values of each tag key.
+    pub async fn get_table_cardinality(&self, tab: &str) -> IndexResult<TableCardinality> {
+        let mut tag_values: HashMap<Vec<u8>, HashMap<Vec<u8>, roaring::RoaringBitmap>> =
+            HashMap::new();
+        let prefix = encode_inverted_index_key(tab, &[], &[]);
+        // The prefix of the inverted index keys of the table is 'tab.'
+        let prefix = &prefix[..prefix.len() - 1];
+        for (key, rb) in self.storage.get_rb_by_prefix(prefix)? {
+            let tag = &key[prefix.len()..];
+            let Some(pos) = tag.iter().position(|b| *b == b'=') else {
+                continue;
+            };
+            tag_values
+                .entry(tag[..pos].to_vec())
+                .or_default()
+                .insert(tag[pos + 1..].to_vec(), rb);
+        }
+        if let Some(tags) = self.cache.write_cache.get_inverted_by_table(tab) {
+            for (tag_key, values) in tags {
+                let entry = tag_values.entry(tag_key.clone()).or_default();
+                for (tag_value, rb) in values {
+                    *entry.entry(tag_value.clone()).or_default() |= rb;
+                }
+            }
+        }
+
+        let mut series = roaring::RoaringBitmap::new();
+        let mut tag_value_counts = Vec::with_capacity(tag_values.len());
+        for (tag_key, values) in tag_values {
+            let mut count = 0;
+            for rb in values.values().filter(|rb| !rb.is_empty()) {
+                series |= rb;
+                count += 1;
+            }
+            // Series without tags are indexed with an empty tag key
+            if !tag_key.is_empty() && count > 0 {
+                tag_value_counts.push((String::from_utf8_lossy(&tag_key).to_string(), count));
+            }
+        }
+        tag_value_counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        Ok(TableCardinality {
+            series_count: series.len(),
+            tag_value_counts,
+        })
+    }
+
     /// 获取所有匹配的旧的series key及其更新后的series key，以及对应的series id
     /// (old_series_keys, new_series_keys, sids)
     pub async fn prepare_update_tags_value(
@@ -427,6 +602,21 @@ impl TSIndex {
                     .context(IndexErrSnafu)?;
                 series_ids.push(rb);
             }
+            for (k, patterns) in tag_domains.patterns().iter() {
+                let id = table_schema
+                    .column(k)
+                    .context(ColumnNotFoundSnafu {
+                        column: k.to_string(),
+                    })?
+                    .id
+                    .to_string();
+
+                let rb = self
+                    .get_series_ids_by_patterns(tab, &id, patterns)
+                    .await
+                    .context(IndexErrSnafu)?;
+                series_ids.push(rb);
+            }
 
             trace::debug!(
                 "Index get sids: filter scan result series_ids: {:?}",
@@ -507,6 +697,70 @@ impl TSIndex {
         Ok(bitmap)
     }
 
+    /// Get the series whose value of the tag matches all the patterns.
+    ///
+    /// Only the tag values starting with the longest literal prefix of the patterns are scanned.
+    async fn get_series_ids_by_patterns(
+        &self,
+        tab: &str,
+        tag_key: &str,
+        patterns: &[TagPattern],
+    ) -> IndexResult<roaring::RoaringBitmap> {
+        let Some(matchers) = patterns
+            .iter()
+            .map(|p| p.matcher())
+            .collect::<Option<Vec<_>>>()
+        else {
+            // Invalid patterns are reported when the filter is evaluated by the query
+            return self.get_series_id_bitmap(tab, &[]).await;
+        };
+        let is_match = |value: &[u8]| matchers.iter().all(|m| m.is_match(value));
+
+        let prefix = patterns
+            .iter()
+            .filter_map(|p| p.prefix())
+            .max_by_key(|p| p.len());
+        let value_range = prefix_to_value_range(prefix.as_deref().unwrap_or_default());
+
+        let mut bitmap = self.cache.write_cache.get_inverted_by_range_filter(
+            tab,
+            tag_key,
+            value_range.clone(),
+            is_match,
+        );
+
+        let key_prefix = encode_inverted_index_key(tab, tag_key.as_bytes(), &[]);
+        let translate_bound = |bound: Bound<Vec<u8>>, is_lower: bool| match bound {
+            Bound::Unbounded => {
+                let buf = if is_lower {
+                    encode_inverted_min_index_key(tab, tag_key.as_bytes())
+                } else {
+                    encode_inverted_max_index_key(tab, tag_key.as_bytes())
+                };
+                Bound::Included(buf)
+            }
+            Bound::Included(v) => {
+                Bound::Included(encode_inverted_index_key(tab, tag_key.as_bytes(), &v))
+            }
+            Bound::Excluded(v) => {
+                Bound::Excluded(encode_inverted_index_key(tab, tag_key.as_bytes(), &v))
+            }
+        };
+        let key_range = (
+            translate_bound(value_range.0, true),
+            translate_bound(value_range.1, false),
+        );
+        let engine_rb = self
+            .storage
+            .get_series_id_by_range_filter(key_range, |key| {
+                key.strip_prefix(key_prefix.as_slice())
+                    .is_some_and(is_match)
+            })?;
+        bitmap = bitmap.bitor(engine_rb);
+
+        Ok(bitmap)
+    }
+
     pub async fn flush(&mut self) -> IndexResult<()> {
         self.check_to_flush(true).await?;
 
@@ -572,6 +826,28 @@ pub fn filter_range_to_value_range(range: &Range) -> impl RangeBounds<Vec<u8>> {
     (translate_bound(start_bound), translate_bound(end_bound))
 }
 
+/// The range of the tag values starting with prefix.
+fn prefix_to_value_range(prefix: &str) -> (Bound<Vec<u8>>, Bound<Vec<u8>>) {
+    if prefix.is_empty() {
+        return (Bound::Unbounded, Bound::Unbounded);
+    }
+    // The smallest value greater than all the values starting with prefix
+    let mut upper = prefix.as_bytes().to_vec();
+    while let Some(last) = upper.pop() {
+        if last < u8::MAX {
+            upper.push(last + 1);
+            return (
+                Bound::Included(prefix.as_bytes().to_vec()),
+                Bound::Excluded(upper),
+            );
+        }
+    }
+    (
+        Bound::Included(prefix.as_bytes().to_vec()),
+        Bound::Unbounded,
+    )
+}
+
 pub fn scalar_value_to_tag_value(v: &ScalarValue) -> Vec<u8> {
     // Tag can only be of string type
     assert_eq!(DataType::Utf8, v.get_datatype());
@@ -654,10 +930,12 @@ mod test {
     use std::time::Duration;
 
     use datafusion::arrow::datatypes::{DataType, Field, Schema};
+    use models::predicate::domain::TagPattern;
     use models::schema::external_table_schema::ExternalTableSchema;
     use models::{SeriesId, SeriesKey, Tag};
 
-    use super::TSIndex;
+    use super::{SeriesLimit, TSIndex, TableCardinality};
+    use crate::index::IndexError;
     use crate::UpdateSetValue;
 
     /// ( sid, database, table, [(tag_key, tag_value)] )
@@ -715,7 +993,7 @@ mod test {
             let mut series_keys_sids = Vec::with_capacity(series_keys_desc.len());
             for (i, series_key) in series_keys.iter().enumerate() {
                 let sid = ts_index
-                    .add_series_if_not_exists(vec![series_key.clone()])
+                    .add_series_if_not_exists(vec![series_key.clone()], SeriesLimit::default())
                     .await
                     .unwrap();
                 tokio::time::sleep(Duration::from_millis(100)).await;
@@ -797,7 +1075,7 @@ mod test {
             let prev_max_sid = max_sid;
             for (i, series_key) in series_keys.iter().enumerate() {
                 let sid = ts_index
-                    .add_series_if_not_exists(vec![series_key.clone()])
+                    .add_series_if_not_exists(vec![series_key.clone()], SeriesLimit::default())
                     .await
                     .unwrap();
                 tokio::time::sleep(Duration::from_millis(100)).await;
@@ -829,7 +1107,7 @@ mod test {
         let prev_max_sid = max_sid;
         for (i, series_key) in series_keys.iter().enumerate() {
             let sid = ts_index
-                .add_series_if_not_exists(vec![series_key.clone()])
+                .add_series_if_not_exists(vec![series_key.clone()], SeriesLimit::default())
                 .await
                 .unwrap();
             tokio::time::sleep(Duration::from_millis(100)).await;
@@ -905,7 +1183,7 @@ mod test {
 
         // 添加series
         let sids = ts_index
-            .add_series_if_not_exists(series_keys.clone())
+            .add_series_if_not_exists(series_keys.clone(), SeriesLimit::default())
             .await
             .unwrap();
 
@@ -956,4 +1234,195 @@ mod test {
             }
         }
     }
+
+    #[tokio::test]
+    async fn test_get_series_ids_by_patterns() {
+        let dir = "/tmp/test/cnosdb/ts_index/patterns";
+        let _ = std::fs::remove_dir_all(dir);
+
+        let ts_index = TSIndex::new(dir, 10000).await.unwrap();
+        let mut ts_index = ts_index.write().await;
+
+        let series_keys = ["web-01", "web-02", "web_03", "db-01"]
+            .into_iter()
+            .map(|host| SeriesKey {
+                tags: vec![Tag::new(b"host".to_vec(), host.as_bytes().to_vec())],
+                table: "tbl".to_string(),
+            })
+            .chain(std::iter::once(SeriesKey {
+                tags: vec![Tag::new(b"loc".to_vec(), b"web-01".to_vec())],
+                table: "tbl".to_string(),
+            }))
+            .collect::<Vec<_>>();
+        let sids = ts_index
+            .add_series_if_not_exists(series_keys, SeriesLimit::default())
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|(sid, _)| sid)
+            .collect::<Vec<_>>();
+
+        let cases = vec![
+            (vec![TagPattern::like("web-%", false, false)], vec![0, 1]),
+            (vec![TagPattern::like("web\\_%", false, false)], vec![2]),
+            (vec![TagPattern::like("%-01", false, true)], vec![1, 2]),
+            (vec![TagPattern::like("WEB%", true, false)], vec![0, 1, 2]),
+            (
+                vec![TagPattern::regex("^(?:web.0[12])$", false, false)],
+                vec![0, 1],
+            ),
+            (vec![TagPattern::regex("01", false, false)], vec![0, 3]),
+            (
+                vec![
+                    TagPattern::regex("^web", false, false),
+                    TagPattern::regex("01", false, true),
+                ],
+                vec![1, 2],
+            ),
+            (vec![TagPattern::regex("^redis", false, false)], vec![]),
+        ];
+
+        // Patterns are evaluated over both cached and flushed inverted index
+        for flushed in [false, true] {
+            if flushed {
+                ts_index.flush().await.unwrap();
+            }
+            for (patterns, expected) in cases.iter() {
+                let rb = ts_index
+                    .get_series_ids_by_patterns("tbl", "host", patterns)
+                    .await
+                    .unwrap();
+                let expected = expected.iter().map(|i| sids[*i]).collect::<Vec<_>>();
+                assert_eq!(
+                    rb.into_iter().collect::<Vec<_>>(),
+                    expected,
+                    "patterns: {patterns:?}, flushed: {flushed}"
+                );
+            }
+        }
+    }
+
+    fn host_series_keys(table: &str, hosts: &[&str]) -> Vec<SeriesKey> {
+        hosts
+            .iter()
+            .map(|host| SeriesKey {
+                tags: vec![
+                    Tag::new(b"host".to_vec(), host.as_bytes().to_vec()),
+                    Tag::new(b"region".to_vec(), b"r1".to_vec()),
+                ],
+                table: table.to_string(),
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_series_limit() {
+        let dir = "/tmp/test/cnosdb/ts_index/series_limit";
+        let _ = std::fs::remove_dir_all(dir);
+
+        let ts_index = TSIndex::new(dir, 10000).await.unwrap();
+        let mut ts_index = ts_index.write().await;
+
+        let limit = SeriesLimit::new(Some(5), Some(3));
+        ts_index
+            .add_series_if_not_exists(host_series_keys("cpu", &["h1", "h2"]), limit)
+            .await
+            .unwrap();
+        ts_index.flush().await.unwrap();
+
+        // Existing and duplicated series are not counted.
+        ts_index
+            .add_series_if_not_exists(host_series_keys("cpu", &["h1", "h2", "h3", "h3"]), limit)
+            .await
+            .unwrap();
+        let err = ts_index
+            .add_series_if_not_exists(host_series_keys("cpu", &["h1", "h4"]), limit)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, IndexError::SeriesLimitExceeded { limit: 3, .. }),
+            "{err}"
+        );
+        // None of the series is added if the limit is exceeded.
+        assert_eq!(
+            ts_index.get_series_id_list("cpu", &[]).await.unwrap().len(),
+            3
+        );
+
+        ts_index
+            .add_series_if_not_exists(host_series_keys("mem", &["h1", "h2"]), limit)
+            .await
+            .unwrap();
+        let err = ts_index
+            .add_series_if_not_exists(host_series_keys("mem", &["h3"]), limit)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, IndexError::SeriesLimitExceeded { limit: 5, .. }),
+            "{err}"
+        );
+
+        // The limit of 0 allows no series, the other replica sets have reached max_series.
+        let err = ts_index
+            .add_series_if_not_exists(
+                host_series_keys("disk", &["h1"]),
+                SeriesLimit::new(None, Some(0)),
+            )
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, IndexError::SeriesLimitExceeded { limit: 0, .. }),
+            "{err}"
+        );
+
+        // Deleted series are not counted.
+        let sid = ts_index.get_series_id_list("mem", &[]).await.unwrap()[0];
+        ts_index.del_series_info(sid).await.unwrap();
+        ts_index
+            .add_series_if_not_exists(host_series_keys("mem", &["h3"]), limit)
+            .await
+            .unwrap();
+        ts_index
+            .add_series_if_not_exists(host_series_keys("mem", &["h4"]), SeriesLimit::default())
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_get_table_cardinality() {
+        let dir = "/tmp/test/cnosdb/ts_index/cardinality";
+        let _ = std::fs::remove_dir_all(dir);
+
+        let ts_index = TSIndex::new(dir, 10000).await.unwrap();
+        let mut ts_index = ts_index.write().await;
+
+        ts_index
+            .add_series_if_not_exists(
+                host_series_keys("cpu", &["h1", "h2"]),
+                SeriesLimit::default(),
+            )
+            .await
+            .unwrap();
+        ts_index.flush().await.unwrap();
+        ts_index
+            .add_series_if_not_exists(
+                host_series_keys("cpu", &["h2", "h3"]),
+                SeriesLimit::default(),
+            )
+            .await
+            .unwrap();
+
+        let cardinality = ts_index.get_table_cardinality("cpu").await.unwrap();
+        assert_eq!(
+            cardinality,
+            TableCardinality {
+                series_count: 3,
+                tag_value_counts: vec![("host".to_string(), 3), ("region".to_string(), 1)],
+            }
+        );
+
+        let cardinality = ts_index.get_table_cardinality("mem").await.unwrap();
+        assert_eq!(cardinality.series_count, 0);
+        assert!(cardinality.tag_value_counts.is_empty());
+    }
 }
@@ -3,7 +3,7 @@ use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
 
-use config::tskv::Config;
+use config::tskv::{ColdTierConfig, Config};
 use models::codec::Encoding;
 use models::meta_data::{NodeId, VnodeId};
 
@@ -12,6 +12,8 @@ pub const INDEX_PATH: &str = "index";
 pub const DATA_PATH: &str = "data";
 pub const TSM_PATH: &str = "tsm";
 pub const DELTA_PATH: &str = "delta";
+pub const COLD_PATH: &str = "cold";
+pub const STREAM_STATE_PATH: &str = "stream_state";
 
 #[derive(Debug, Clone)]
 pub struct Options {
@@ -47,11 +49,13 @@ pub struct StorageOptions {
     pub max_datablock_size: u64,
     pub index_cache_capacity: u64,
     pub tsm_meta_compress: Encoding,
+    pub cold_tier: Option<ColdTierConfig>,
 }
 
 // database/data/ts_family_id/tsm
 // database/data/ts_family_id/delta
 // database/data/ts_family_id/index
+// database/data/ts_family_id/cold
 impl StorageOptions {
     pub fn level_max_file_size(&self, lvl: u32) -> u64 {
         // TODO(zipper): size of lvl-0 is zero?
@@ -85,6 +89,15 @@ impl StorageOptions {
     pub fn delta_dir(&self, owner: &str, ts_family_id: VnodeId) -> PathBuf {
         self.ts_family_dir(owner, ts_family_id).join(DELTA_PATH)
     }
+
+    /// Directory of the local files (tombstones) of the TSM files moved to the cold tier.
+    pub fn cold_dir(&self, owner: &str, ts_family_id: VnodeId) -> PathBuf {
+        self.ts_family_dir(owner, ts_family_id).join(COLD_PATH)
+    }
+
+    pub fn stream_state_dir(&self) -> PathBuf {
+        self.path.join(STREAM_STATE_PATH)
+    }
 }
 
 impl From<&Config> for StorageOptions {
@@ -111,6 +124,7 @@ impl From<&Config> for StorageOptions {
             max_datablock_size: config.storage.max_datablock_size,
             index_cache_capacity: config.storage.index_cache_capacity,
             tsm_meta_compress,
+            cold_tier: config.storage.cold_tier.clone(),
         }
     }
 }
@@ -9,19 +9,23 @@ use metrics::metric_register::MetricsRegister;
 use models::meta_data::VnodeId;
 use models::predicate::domain::ColumnDomains;
 use models::schema::database_schema::{make_owner, split_owner};
+use models::schema::table_statistics::TableStatistics;
 use models::{SeriesId, SeriesKey};
-use snafu::ResultExt;
+use snafu::{OptionExt, ResultExt};
 use tokio::runtime::Runtime;
 use tokio::sync::broadcast::{self, Sender as BroadcastSender};
 use tokio::sync::mpsc::{self, Sender};
 use tokio::sync::RwLock;
 use trace::{debug, error, info, warn};
 
+use crate::backup::{BackupStorage, VnodeBackupManifest};
+use crate::cdc::{ChangeEvent, ChangeFeed, ChangeFeedRef};
+use crate::cold_tier::ColdTier;
 use crate::compaction::job::CompactJob;
 use crate::compaction::metrics::{CompactionType, VnodeCompactionMetrics};
 use crate::compaction::{self, check, pick_compaction, CompactTask};
 use crate::database::Database;
-use crate::error::{IndexErrSnafu, MetaSnafu, TskvResult};
+use crate::error::{IndexErrSnafu, MetaSnafu, TskvResult, VnodeNotFoundSnafu};
 use crate::file_system::async_filesystem::LocalFileSystem;
 use crate::file_system::FileSystem;
 use crate::index::IndexResult;
@@ -48,6 +52,10 @@ impl TsKv {
     ) -> TskvResult<TsKv> {
         let options = Arc::new(options);
         let (compact_task_sender, compact_task_receiver) = mpsc::channel(1024);
+        let cold_tier = match options.storage.cold_tier.clone() {
+            Some(config) => Some(Arc::new(ColdTier::new(config)?)),
+            None => None,
+        };
         let ctx = Arc::new(TsKvContext {
             metrics,
             memory_pool,
@@ -55,6 +63,8 @@ impl TsKv {
             compact_task_sender,
             options: options.clone(),
             runtime: runtime.clone(),
+            cold_tier,
+            change_feed: Arc::new(ChangeFeed::default()),
         });
 
         let old_summary = file_utils::make_summary_file(options.storage.summary_dir(), 0);
@@ -68,6 +78,8 @@ impl TsKv {
         let compact_job = CompactJob::new(ctx.clone(), version_set.clone());
         compact_job.start_jobs(compact_task_receiver).await;
         Self::run_flush_cold_vnode_job(ctx.clone(), version_set.clone());
+        Self::run_move_cold_files_job(ctx.clone(), version_set.clone());
+        Self::run_table_ttl_job(ctx.clone(), version_set.clone());
 
         let (close_sender, _close_receiver) = broadcast::channel(1);
         let core = Self {
@@ -122,6 +134,45 @@ impl TsKv {
         });
     }
 
+    fn run_move_cold_files_job(ctx: Arc<TsKvContext>, version_set: Arc<RwLock<VersionSet>>) {
+        let cold_tier = match ctx.cold_tier.clone() {
+            Some(cold_tier) => cold_tier,
+            None => return,
+        };
+
+        ctx.runtime.spawn(async move {
+            let mut check_interval = tokio::time::interval(cold_tier.config().check_interval);
+            loop {
+                check_interval.tick().await;
+
+                let vnodes = version_set.read().await.vnodes();
+                for (tf_id, vnode) in vnodes.iter() {
+                    if let Err(e) = cold_tier.move_cold_files(vnode).await {
+                        warn!("Failed to move cold files of vnode {tf_id} to cold tier: {e}");
+                    }
+                }
+            }
+        });
+    }
+
+    /// Applies the TTL of tables to all vnodes periodically, idle vnodes that are
+    /// never compacted also get their expired data removed.
+    fn run_table_ttl_job(ctx: Arc<TsKvContext>, version_set: Arc<RwLock<VersionSet>>) {
+        ctx.runtime.spawn(async move {
+            let mut check_interval = tokio::time::interval(Duration::from_secs(60));
+            loop {
+                check_interval.tick().await;
+
+                let vnodes = version_set.read().await.vnodes();
+                for (tf_id, vnode) in vnodes.iter() {
+                    if let Err(e) = vnode.apply_table_ttl().await {
+                        warn!("Failed to apply table ttl on vnode {tf_id}: {e}");
+                    }
+                }
+            }
+        });
+    }
+
     async fn sync_indexs(&self) -> IndexResult<()> {
         let vs_guard = self.version_set.read().await;
         for (_, vnode_storage) in vs_guard.vnodes().iter() {
@@ -224,6 +275,7 @@ impl Engine for TsKv {
         vnode_id: VnodeId,
     ) -> TskvResult<()> {
         self.version_set.write().await.remove_vnode(vnode_id);
+        self.ctx.change_feed.remove_vnode(vnode_id);
         if let Some(db) = self.version_set.read().await.get_db(tenant, database) {
             db.write().await.del_tsfamily_index(vnode_id);
         }
@@ -374,6 +426,56 @@ impl Engine for TsKv {
         Ok(())
     }
 
+    async fn backup_vnode(
+        &self,
+        vnode_id: VnodeId,
+        storage: &BackupStorage,
+        backup_id: i64,
+    ) -> TskvResult<VnodeBackupManifest> {
+        let vnode = self
+            .version_set
+            .read()
+            .await
+            .get_vnode(vnode_id)
+            .cloned()
+            .context(VnodeNotFoundSnafu { vnode_id })?;
+
+        vnode.backup(storage, backup_id).await
+    }
+
+    async fn restore_vnode(
+        &self,
+        tenant: &str,
+        database: &str,
+        vnode_id: VnodeId,
+        storage: &BackupStorage,
+        manifest: &VnodeBackupManifest,
+    ) -> TskvResult<()> {
+        let vnode = self.open_tsfamily(tenant, database, vnode_id).await?;
+        vnode.restore_backup(storage, manifest).await
+    }
+
+    fn change_feed(&self) -> ChangeFeedRef {
+        self.ctx.change_feed.clone()
+    }
+
+    async fn read_changes(
+        &self,
+        vnode_id: VnodeId,
+        after_index: u64,
+        until_index: u64,
+    ) -> TskvResult<Vec<ChangeEvent>> {
+        let vnode = self
+            .version_set
+            .read()
+            .await
+            .get_vnode(vnode_id)
+            .cloned()
+            .context(VnodeNotFoundSnafu { vnode_id })?;
+
+        vnode.read_changes(after_index, until_index).await
+    }
+
     async fn get_vnode_hash_tree(&self, vnode_id: VnodeId) -> TskvResult<RecordBatch> {
         let vnode_opt = self.version_set.read().await.get_vnode(vnode_id).cloned();
         if let Some(vnode) = vnode_opt {
@@ -389,6 +491,38 @@ impl Engine for TsKv {
         Ok(RecordBatch::new_empty(check::vnode_table_checksum_schema()))
     }
 
+    async fn get_series_cardinality(
+        &self,
+        vnode_id: VnodeId,
+        table: Option<&str>,
+    ) -> TskvResult<RecordBatch> {
+        let vnode = self
+            .version_set
+            .read()
+            .await
+            .get_vnode(vnode_id)
+            .cloned()
+            .context(VnodeNotFoundSnafu { vnode_id })?;
+
+        vnode.series_cardinality(table).await
+    }
+
+    async fn get_table_statistics(
+        &self,
+        vnode_id: VnodeId,
+        table: &str,
+    ) -> TskvResult<TableStatistics> {
+        let vnode = self
+            .version_set
+            .read()
+            .await
+            .get_vnode(vnode_id)
+            .cloned()
+            .context(VnodeNotFoundSnafu { vnode_id })?;
+
+        vnode.table_statistics(table).await
+    }
+
     async fn close(&self) {
         let (tx, mut rx) = mpsc::channel(1);
         if let Err(e) = self.close_sender.send(tx) {
@@ -4,6 +4,9 @@ use std::fmt::{Debug, Display, Formatter};
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use backup::{BackupStorage, VnodeBackupManifest};
+use cdc::{ChangeEvent, ChangeFeedRef};
+use cold_tier::ColdTierRef;
 pub use compaction::check::vnode_table_checksum_schema;
 use compaction::CompactTask;
 use datafusion::arrow::record_batch::RecordBatch;
@@ -12,11 +15,13 @@ use meta::model::MetaRef;
 use metrics::metric_register::MetricsRegister;
 use models::meta_data::{NodeId, VnodeId};
 use models::predicate::domain::ColumnDomains;
+use models::schema::table_statistics::TableStatistics;
 use models::{SeriesId, SeriesKey};
 use serde::{Deserialize, Serialize};
 use tokio::runtime::Runtime;
 use tokio::sync::mpsc::Sender;
 use tsfamily::version::{Version, VersionEdit};
+pub use vnode_store::series_cardinality_schema;
 use vnode_store::VnodeStorage;
 
 pub use crate::error::{TskvError, TskvResult};
@@ -29,7 +34,10 @@ pub use crate::tsfamily::summary::print_summary_statistics;
 use crate::tsfamily::super_version::SuperVersion;
 pub use crate::wal::print_wal_statistics;
 
+pub mod backup;
 pub mod byte_utils;
+pub mod cdc;
+mod cold_tier;
 mod compaction;
 mod compute;
 pub mod database;
@@ -127,9 +135,54 @@ pub trait Engine: Send + Sync + Debug {
     /// files into larger files.
     async fn compact(&self, vnode_ids: Vec<VnodeId>) -> TskvResult<()>;
 
+    /// Flush all caches of the storage unit, then ship the files to the backup storage.
+    async fn backup_vnode(
+        &self,
+        vnode_id: VnodeId,
+        storage: &BackupStorage,
+        backup_id: i64,
+    ) -> TskvResult<VnodeBackupManifest>;
+
+    /// Restore the files of a storage unit in the backup storage to the storage unit.
+    async fn restore_vnode(
+        &self,
+        tenant: &str,
+        database: &str,
+        vnode_id: VnodeId,
+        storage: &BackupStorage,
+        manifest: &VnodeBackupManifest,
+    ) -> TskvResult<()>;
+
+    /// Get the feed of the commands applied to the storage units.
+    fn change_feed(&self) -> ChangeFeedRef;
+
+    /// Read the commands applied to the storage unit from its WAL, whose raft log
+    /// index is in `(after_index, until_index]`.
+    async fn read_changes(
+        &self,
+        vnode_id: VnodeId,
+        after_index: u64,
+        until_index: u64,
+    ) -> TskvResult<Vec<ChangeEvent>>;
+
     /// Get a compressed hash_tree(ID and checksum of each vnode) of engine.
     async fn get_vnode_hash_tree(&self, vnode_id: VnodeId) -> TskvResult<RecordBatch>;
 
+    /// Get the series count and the number of distinct values of each tag key
+    /// of the tables in the storage unit.
+    async fn get_series_cardinality(
+        &self,
+        vnode_id: VnodeId,
+        table: Option<&str>,
+    ) -> TskvResult<RecordBatch>;
+
+    /// Get the estimated statistics of the table in the storage unit.
+    async fn get_table_statistics(
+        &self,
+        vnode_id: VnodeId,
+        table: &str,
+    ) -> TskvResult<TableStatistics>;
+
     /// Close all background jobs of engine.
     async fn close(&self);
 }
@@ -142,6 +195,8 @@ pub struct TsKvContext {
     pub metrics: Arc<MetricsRegister>,
     pub memory_pool: Arc<dyn MemoryPool>,
     pub compact_task_sender: Sender<CompactTask>,
+    pub cold_tier: Option<ColdTierRef>,
+    pub change_feed: ChangeFeedRef,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
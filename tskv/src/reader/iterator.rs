@@ -169,7 +169,7 @@ impl SeriesGroupBatchReaderFactory {
         {
             let _timer = metrics.elapsed_get_tsm_readers_time().timer();
             for f in column_files {
-                let reader = super_version.version.get_tsm_reader(f.file_path()).await?;
+                let reader = super_version.version.get_column_file_reader(f).await?;
                 column_files_with_reader.push((f, reader));
             }
         }
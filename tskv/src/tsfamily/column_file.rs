@@ -7,13 +7,15 @@ use cache::{AsyncCache, ShardedAsyncCache};
 use models::codec::Encoding;
 use models::predicate::domain::TimeRange;
 use models::{FieldId, SeriesId, SeriesKey};
+use object_store::path::Path as ObjectPath;
 use snafu::ResultExt;
 use tokio::sync::{RwLock as AsyncRwLock, RwLockWriteGuard as AsyncRwLockWriteGuard};
 use trace::{debug, error, info};
 use utils::BloomFilter;
 
 use super::version::CompactMeta;
-use crate::error::{FileSystemSnafu, TskvResult};
+use crate::cold_tier::{ColdTier, ColdTierRef};
+use crate::error::{CommonSnafu, FileSystemSnafu, IOSnafu, TskvResult};
 use crate::file_system::async_filesystem::{LocalFileSystem, LocalFileType};
 use crate::file_system::FileSystem;
 use crate::tsm::reader::TsmReader;
@@ -33,6 +35,11 @@ pub struct ColumnFile {
 
     path: PathBuf,
     tsm_reader_cache: Weak<ShardedAsyncCache<String, Arc<TsmReader>>>,
+
+    /// Object path of the file if it has been moved to the cold tier, the `path`
+    /// is then only used to find the tombstone.
+    remote_path: Option<String>,
+    cold_tier: Option<ColdTierRef>,
 }
 
 impl ColumnFile {
@@ -41,6 +48,7 @@ impl ColumnFile {
         path: impl AsRef<Path>,
         series_id_filter: AsyncRwLock<Option<Arc<BloomFilter>>>,
         tsm_reader_cache: Weak<ShardedAsyncCache<String, Arc<TsmReader>>>,
+        cold_tier: Option<ColdTierRef>,
     ) -> Self {
         Self {
             file_id: meta.file_id,
@@ -52,6 +60,8 @@ impl ColumnFile {
             compacting: Arc::new(AsyncRwLock::new(false)),
             path: path.as_ref().into(),
             tsm_reader_cache,
+            remote_path: meta.remote_path.clone(),
+            cold_tier,
         }
     }
 
@@ -79,12 +89,69 @@ impl ColumnFile {
         &self.path
     }
 
+    pub fn remote_path(&self) -> Option<&str> {
+        self.remote_path.as_deref()
+    }
+
+    /// Returns true if the file has been moved to the cold tier.
+    pub fn is_cold(&self) -> bool {
+        self.remote_path.is_some()
+    }
+
+    fn cold_tier(&self) -> TskvResult<&ColdTierRef> {
+        self.cold_tier.as_ref().ok_or_else(|| {
+            CommonSnafu {
+                reason: format!(
+                    "tsm file {} is in cold tier '{}', but cold tier is not configured",
+                    self.file_id,
+                    self.remote_path.as_deref().unwrap_or_default()
+                ),
+            }
+            .build()
+        })
+    }
+
+    /// Open a reader of the file, reads from the object store if the file is in cold tier.
+    pub async fn open_reader(&self) -> TskvResult<TsmReader> {
+        let remote_path = match self.remote_path.as_ref() {
+            Some(p) => ObjectPath::from(p.as_str()),
+            None => return TsmReader::open(&self.path).await,
+        };
+        let cold_tier = self.cold_tier()?;
+        let tombstone_path = self.tombstone_path();
+        if !LocalFileSystem::try_exists(&tombstone_path) {
+            // Local tombstone may be lost, restore it from object store.
+            cold_tier
+                .download(&ColdTier::tombstone_location(&remote_path), &tombstone_path)
+                .await?;
+        }
+        let reader = cold_tier.open_file_reader(&remote_path).await?;
+        let tombstone_dir = self.path.parent().unwrap_or_else(|| Path::new("/"));
+        TsmReader::open_with_reader(self.file_id, reader, tombstone_dir).await
+    }
+
     pub fn tombstone_path(&self) -> PathBuf {
         let mut path = self.path.clone();
         path.set_extension(tsm::TOMBSTONE_FILE_SUFFIX);
         path
     }
 
+    /// Upload the local tombstone if the file is in cold tier, should be called after
+    /// the tombstone is written, otherwise the tombstone in object store is stale.
+    pub async fn upload_tombstone(&self) -> TskvResult<()> {
+        let remote_path = match self.remote_path.as_ref() {
+            Some(p) => ObjectPath::from(p.as_str()),
+            None => return Ok(()),
+        };
+        let tombstone_path = self.tombstone_path();
+        if !LocalFileSystem::try_exists(&tombstone_path) {
+            return Ok(());
+        }
+        self.cold_tier()?
+            .upload(&tombstone_path, &ColdTier::tombstone_location(&remote_path))
+            .await
+    }
+
     pub fn overlap(&self, time_range: &TimeRange) -> bool {
         self.time_range.overlaps(time_range)
     }
@@ -112,7 +179,7 @@ impl ColumnFile {
             {
                 Some(r) => r,
                 None => {
-                    let reader = TsmReader::open(&self.path).await?;
+                    let reader = self.open_reader().await?;
                     let reader = Arc::new(reader);
                     tsm_reader_cache
                         .insert(self.path.display().to_string(), reader.clone())
@@ -122,7 +189,7 @@ impl ColumnFile {
             };
             reader.footer().series().bloom_filter().clone()
         } else {
-            TsmReader::open(&self.path)
+            self.open_reader()
                 .await?
                 .footer()
                 .series()
@@ -156,15 +223,21 @@ impl ColumnFile {
             .contains_any_series_id(&series.keys().copied().collect::<Vec<_>>())
             .await?
         {
-            TsmReader::open(&self.path)
-                .await?
-                .tsm_meta_data()
-                .as_ref()
-                .clone()
+            self.open_reader().await?.tsm_meta_data().as_ref().clone()
         } else {
             return Ok(None);
         };
         meta.update_tag_value(series)?;
+
+        // Files in cold tier are downloaded, updated and then uploaded back.
+        let remote_path = self
+            .remote_path
+            .as_ref()
+            .map(|p| ObjectPath::from(p.as_str()));
+        if let Some(remote_path) = remote_path.as_ref() {
+            self.cold_tier()?.download(remote_path, &self.path).await?;
+        }
+
         let local_file_system = LocalFileSystem::new(LocalFileType::ThreadPool);
         let writer = local_file_system
             .open_file_writer(&self.path, 1024)
@@ -178,6 +251,14 @@ impl ColumnFile {
             encode_tsm_meta,
         )
         .await?;
+        if let Some(remote_path) = remote_path.as_ref() {
+            let cold_tier = self.cold_tier()?;
+            cold_tier.upload(&self.path, remote_path).await?;
+            cold_tier
+                .evict_blocks(remote_path, self.size as usize)
+                .await;
+            tokio::fs::remove_file(&self.path).await.context(IOSnafu)?;
+        }
         Ok(Some(self.path.clone()))
     }
 }
@@ -224,7 +305,27 @@ impl Drop for ColumnFile {
                     cache.remove(&k).await;
                 });
             }
-            if let Err(e) = std::fs::remove_file(path) {
+            if let Some(remote_path) = self.remote_path.as_ref() {
+                match self.cold_tier.clone() {
+                    Some(cold_tier) => {
+                        let file_id = self.file_id;
+                        let size = self.size as usize;
+                        let location = ObjectPath::from(remote_path.as_str());
+                        tokio::spawn(async move {
+                            cold_tier.evict_blocks(&location, size).await;
+                            if let Err(e) = cold_tier.delete(&location).await {
+                                error!("Failed to remove tsm file {file_id} at '{location}': {e}");
+                            } else {
+                                info!("Removed tsm file {file_id} at '{location}'");
+                            }
+                        });
+                    }
+                    None => error!(
+                        "Failed to remove tsm file {} at '{remote_path}': cold tier is not configured",
+                        self.file_id
+                    ),
+                }
+            } else if let Err(e) = std::fs::remove_file(path) {
                 error!(
                     "Failed to remove tsm file {} at '{}': {e}",
                     self.file_id,
@@ -302,6 +403,8 @@ impl ColumnFile {
             compacting: Arc::new(AsyncRwLock::new(false)),
             path: path.as_ref().into(),
             tsm_reader_cache: Weak::new(),
+            remote_path: None,
+            cold_tier: None,
         }
     }
 
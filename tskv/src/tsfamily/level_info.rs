@@ -7,6 +7,7 @@ use tokio::sync::RwLock as TokioRwLock;
 use utils::BloomFilter;
 
 use super::version::CompactMeta;
+use crate::cold_tier::ColdTierRef;
 use crate::error::TskvResult;
 use crate::file_utils::{make_delta_file, make_tsm_file};
 use crate::kv_option::StorageOptions;
@@ -69,10 +70,15 @@ impl LevelInfo {
         compact_meta: &CompactMeta,
         series_filter: TokioRwLock<Option<Arc<BloomFilter>>>,
         tsm_reader_cache: Weak<ShardedAsyncCache<String, Arc<TsmReader>>>,
+        cold_tier: Option<ColdTierRef>,
     ) {
         let file_path = if compact_meta.is_delta {
             let base_dir = self.storage_opt.delta_dir(&self.owner, self.tsf_id);
             make_delta_file(base_dir, compact_meta.file_id)
+        } else if compact_meta.remote_path.is_some() {
+            // Tombstones of the files in cold tier are stored in the cold directory.
+            let base_dir = self.storage_opt.cold_dir(&self.owner, self.tsf_id);
+            make_tsm_file(base_dir, compact_meta.file_id)
         } else {
             let base_dir = self.storage_opt.tsm_dir(&self.owner, self.tsf_id);
             make_tsm_file(base_dir, compact_meta.file_id)
@@ -82,6 +88,7 @@ impl LevelInfo {
             file_path,
             series_filter,
             tsm_reader_cache,
+            cold_tier,
         )));
         self.tsf_id = compact_meta.tsf_id;
         self.cur_size += compact_meta.file_size;
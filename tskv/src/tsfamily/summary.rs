@@ -116,18 +116,22 @@ impl Summary {
                 &meta,
                 RwLock::new(None),
                 weak_tsm_reader_cache.clone(),
+                self.ctx.cold_tier.clone(),
             );
         }
 
-        Ok(Some(Version::new(
-            self.tsf_id,
-            owner,
-            self.ctx.options.storage.clone(),
-            max_seq_no,
-            levels,
-            max_level_ts,
-            tsm_reader_cache,
-        )))
+        Ok(Some(
+            Version::new(
+                self.tsf_id,
+                owner,
+                self.ctx.options.storage.clone(),
+                max_seq_no,
+                levels,
+                max_level_ts,
+                tsm_reader_cache,
+            )
+            .with_cold_tier(self.ctx.cold_tier.clone()),
+        ))
     }
 
     /// Write VersionEdits into summary file, generate and then apply new Versions for TseriesFamilies.
@@ -257,7 +261,16 @@ async fn install_tombstones_for_tsm_readers(
             let path = path_display.to_string();
             match cache_inner.get_tsm_reader(&path).await {
                 Ok(tsm_reader) => {
-                    tsm_reader.replace_tombstone_with_compact_tmp().await
+                    tsm_reader.replace_tombstone_with_compact_tmp().await?;
+                    let column_file = cache_inner
+                        .levels_info()
+                        .iter()
+                        .flat_map(|level| level.files.iter())
+                        .find(|file| file.file_path() == &tsm_path);
+                    match column_file {
+                        Some(column_file) => column_file.upload_tombstone().await,
+                        None => Ok(()),
+                    }
                 }
                 Err(e) => {
                     trace::error!(
@@ -592,6 +605,7 @@ mod test {
                 min_ts: 1,
                 max_ts: 1,
                 tsf_id: VNODE_ID,
+                remote_path: None,
             };
             edit.add_file(meta, 1);
 
@@ -113,8 +113,7 @@ impl SuperVersion {
         }
 
         for (column_file, valid_series) in file_series_map.into_values() {
-            let path = column_file.file_path().as_path().display().to_string();
-            let reader = self.version.get_tsm_reader(&path).await?;
+            let reader = self.version.get_column_file_reader(&column_file).await?;
             let bloom_filter = column_file.load_bloom_filter().await?;
             let mut columns = Vec::new();
             for sid in valid_series {
@@ -127,6 +126,7 @@ impl SuperVersion {
                 .add_range(&columns, *time_range, Some(bloom_filter))
                 .await?;
             reader.tombstone().flush().await?;
+            column_file.upload_tombstone().await?;
         }
         Ok(())
     }
@@ -332,7 +332,7 @@ impl TseriesFamily {
         // tsm index
         for level in self.version().levels_info().iter() {
             for file in level.files.iter() {
-                let reader = self.version().get_tsm_reader(file.file_path()).await?;
+                let reader = self.version().get_column_file_reader(file).await?;
                 for chunk in reader.chunk().values() {
                     index_w
                         .add_series_for_rebuild(chunk.series_id(), chunk.series_key())
@@ -355,6 +355,10 @@ impl TseriesFamily {
         self.owner.clone()
     }
 
+    pub fn db_config(&self) -> Arc<DatabaseConfig> {
+        self.db_config.clone()
+    }
+
     pub fn cache(&self) -> &Arc<RwLock<MemCache>> {
         &self.mut_cache
     }
@@ -666,6 +670,7 @@ pub mod test_tseries_family {
                 min_ts: 3051,
                 max_ts: 3150,
                 is_delta: false,
+                remote_path: None,
             },
             3100,
         );
@@ -759,6 +764,7 @@ pub mod test_tseries_family {
                 min_ts: 3001,
                 max_ts: 3150,
                 is_delta: false,
+                remote_path: None,
             },
             3150,
         );
@@ -772,6 +778,7 @@ pub mod test_tseries_family {
                 min_ts: 1,
                 max_ts: 2000,
                 is_delta: false,
+                remote_path: None,
             },
             3150,
         );
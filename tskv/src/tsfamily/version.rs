@@ -13,6 +13,7 @@ use tokio::sync::RwLock;
 use trace::error;
 use utils::BloomFilter;
 
+use crate::cold_tier::ColdTierRef;
 use crate::error::{RecordFileDecodeSnafu, RecordFileEncodeSnafu, TskvResult};
 use crate::kv_option::{StorageOptions, DELTA_PATH, TSM_PATH};
 use crate::tsfamily::column_file::ColumnFile;
@@ -33,6 +34,7 @@ pub struct Version {
     max_level_ts: i64,
     levels_info: [LevelInfo; 5],
     tsm_reader_cache: Arc<ShardedAsyncCache<String, Arc<TsmReader>>>,
+    cold_tier: Option<ColdTierRef>,
 }
 
 impl Version {
@@ -54,9 +56,16 @@ impl Version {
             max_level_ts,
             levels_info,
             tsm_reader_cache,
+            cold_tier: None,
         }
     }
 
+    /// Set the cold tier to read the column files moved to object store.
+    pub fn with_cold_tier(mut self, cold_tier: Option<ColdTierRef>) -> Self {
+        self.cold_tier = cold_tier;
+        self
+    }
+
     /// Creates new Version using current Version and `VersionEdit`s.
     pub fn copy_apply_version_edits(
         &self,
@@ -99,6 +108,7 @@ impl Version {
                     file,
                     RwLock::new(Some(series_filter)),
                     weak_tsm_reader_cache.clone(),
+                    self.cold_tier.clone(),
                 );
             }
             new_levels[level.level as usize].update_time_range();
@@ -112,6 +122,7 @@ impl Version {
             max_level_ts: self.max_level_ts,
             levels_info: new_levels,
             tsm_reader_cache: self.tsm_reader_cache.clone(),
+            cold_tier: self.cold_tier.clone(),
         };
         new_version.update_max_level_ts();
         new_version
@@ -171,6 +182,24 @@ impl Version {
         Ok(tsm_reader)
     }
 
+    /// Get the reader of the column file, the file may be in the cold tier.
+    pub async fn get_column_file_reader(&self, file: &ColumnFile) -> TskvResult<Arc<TsmReader>> {
+        if !file.is_cold() {
+            return self.get_tsm_reader(file.file_path()).await;
+        }
+
+        let path = file.file_path().display().to_string();
+        let tsm_reader = match self.tsm_reader_cache.get(&path).await {
+            Some(val) => val,
+            None => {
+                let tsm_reader = Arc::new(file.open_reader().await?);
+                self.tsm_reader_cache.insert(path, tsm_reader.clone()).await;
+                tsm_reader
+            }
+        };
+        Ok(tsm_reader)
+    }
+
     pub fn max_level_ts(&self) -> i64 {
         self.max_level_ts
     }
@@ -194,7 +223,7 @@ impl Version {
                 if file.is_deleted() || !file.overlap(&time_predicate) {
                     continue;
                 }
-                let reader = self.get_tsm_reader(file.file_path()).await.unwrap();
+                let reader = self.get_column_file_reader(file).await.unwrap();
                 let fid = reader.file_id();
                 let sts = reader.statistics(series_ids, time_predicate).await.unwrap();
                 result.insert(fid, sts);
@@ -237,6 +266,7 @@ impl Version {
             max_level_ts: self.max_level_ts,
             levels_info: self.levels_info.clone(),
             tsm_reader_cache: self.tsm_reader_cache.clone(),
+            cold_tier: self.cold_tier.clone(),
         }
     }
 }
@@ -250,6 +280,8 @@ pub struct CompactMeta {
     pub min_ts: Timestamp,
     pub max_ts: Timestamp,
     pub is_delta: bool,
+    /// Object path of the file if it has been moved to the cold tier.
+    pub remote_path: Option<String>,
 }
 
 impl Default for CompactMeta {
@@ -262,6 +294,7 @@ impl Default for CompactMeta {
             min_ts: Timestamp::MAX,
             max_ts: Timestamp::MIN,
             is_delta: false,
+            remote_path: None,
         }
     }
 }
@@ -279,6 +312,7 @@ impl From<&ColumnFile> for CompactMeta {
             min_ts: file.time_range().min_ts,
             max_ts: file.time_range().max_ts,
             is_delta: file.is_delta(),
+            remote_path: file.remote_path().map(|p| p.to_string()),
             ..Default::default()
         }
     }
@@ -301,6 +335,7 @@ impl CompactMeta {
             min_ts,
             max_ts,
             is_delta: level == 0,
+            remote_path: None,
         }
     }
 
@@ -329,6 +364,9 @@ impl CompactMeta {
         if self.is_delta {
             let base_dir = storage_opt.delta_dir(owner, ts_family_id);
             file_utils::make_delta_file(base_dir, self.file_id)
+        } else if self.remote_path.is_some() {
+            let base_dir = storage_opt.cold_dir(owner, ts_family_id);
+            file_utils::make_tsm_file(base_dir, self.file_id)
         } else {
             let base_dir = storage_opt.tsm_dir(owner, ts_family_id);
             file_utils::make_tsm_file(base_dir, self.file_id)
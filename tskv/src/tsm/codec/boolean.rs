@@ -25,7 +25,7 @@ pub fn bool_bitpack_encode(src: &[bool], dst: &mut Vec<u8>) -> Result<(), CodecE
     if src.is_empty() {
         return Ok(());
     }
-    dst.push(Encoding::BitPack as u8);
+    dst.push(Encoding::BitPack.id());
 
     let size = HEADER_LEN + 8 + ((src.len() + 7) / 8); // Header + Num bools + bool data.
     dst.resize(size + 1, 0);
@@ -64,7 +64,7 @@ pub fn bool_bitpack_encode(src: &[bool], dst: &mut Vec<u8>) -> Result<(), CodecE
 }
 
 pub fn bool_without_compress_encode(src: &[bool], dst: &mut Vec<u8>) -> Result<(), CodecError> {
-    dst.push(Encoding::Null as u8);
+    dst.push(Encoding::Null.id());
     for i in src {
         if *i {
             dst.push(1);
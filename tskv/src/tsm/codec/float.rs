@@ -2,12 +2,13 @@ use std::sync::Arc;
 
 use arrow::buffer::NullBuffer;
 use arrow_array::builder::Float64Builder;
-use arrow_array::{ArrayRef, Float64Array};
+use arrow_array::{Array, ArrayRef, Float64Array};
+use integer_encoding::VarInt;
 use pco::standalone::{simple_decompress, simpler_compress};
 use pco::DEFAULT_COMPRESSION_LEVEL;
 
 use super::CodecError;
-use crate::byte_utils::decode_be_f64;
+use crate::byte_utils::{decode_be_f64, decode_be_u32};
 use crate::tsm::codec::Encoding;
 
 // SENTINEL is used to terminate a float-encoded block. A sentinel marker value
@@ -33,7 +34,7 @@ pub fn f64_gorilla_encode(src: &[f64], dst: &mut Vec<u8>) -> Result<(), CodecErr
     if src.is_empty() {
         return Ok(());
     }
-    dst.push(Encoding::Gorilla as u8);
+    dst.push(Encoding::Gorilla.id());
     if dst.capacity() < 10 {
         dst.reserve_exact(10 - dst.capacity()); // room for encoding type, block
                                                 // size and a value
@@ -247,7 +248,7 @@ pub fn f64_pco_encode(src: &[f64], dst: &mut Vec<u8>) -> Result<(), CodecError>
         return Ok(());
     }
 
-    dst.push(Encoding::Quantile as u8);
+    dst.push(Encoding::Quantile.id());
 
     dst.append(&mut simpler_compress(src, DEFAULT_COMPRESSION_LEVEL)?);
     Ok(())
@@ -258,7 +259,7 @@ pub fn f64_without_compress_encode(src: &[f64], dst: &mut Vec<u8>) -> Result<(),
         return Ok(());
     }
 
-    dst.push(Encoding::Null as u8);
+    dst.push(Encoding::Null.id());
 
     for i in src.iter() {
         dst.extend_from_slice((*i).to_be_bytes().as_slice());
@@ -266,6 +267,460 @@ pub fn f64_without_compress_encode(src: &[f64], dst: &mut Vec<u8>) -> Result<(),
     Ok(())
 }
 
+/// Number of previous values Chimp128 may reference instead of the immediately previous one.
+const CHIMP_PREVIOUS_VALUES: usize = 128;
+const CHIMP_PREVIOUS_VALUES_LOG2: u32 = 7;
+/// A previous value is only referenced if its xor has more trailing zeros than this.
+const CHIMP_THRESHOLD: u32 = 6 + CHIMP_PREVIOUS_VALUES_LOG2;
+/// Mask of the lowest bits used to look up previous values with the same trailing bits.
+const CHIMP_INDICES_MASK: u64 = (1 << (CHIMP_THRESHOLD + 1)) - 1;
+/// Leading zeros are rounded down to one of these values, so they can be stored in 3 bits.
+const CHIMP_LEADING_ROUND: [u32; 8] = [0, 8, 12, 16, 18, 20, 22, 24];
+
+fn chimp_leading_representation(leading_zeros: u32) -> u64 {
+    CHIMP_LEADING_ROUND
+        .iter()
+        .rposition(|round| *round <= leading_zeros)
+        .unwrap_or(0) as u64
+}
+
+/// Writes values of variable bit width into a byte buffer, most significant bit first.
+struct BitWriter<'a> {
+    dst: &'a mut Vec<u8>,
+    acc: u128,
+    acc_bits: u32,
+}
+
+impl<'a> BitWriter<'a> {
+    fn new(dst: &'a mut Vec<u8>) -> Self {
+        Self {
+            dst,
+            acc: 0,
+            acc_bits: 0,
+        }
+    }
+
+    fn write(&mut self, value: u64, bits: u32) {
+        if bits == 0 {
+            return;
+        }
+        let value = if bits == 64 {
+            value
+        } else {
+            value & ((1 << bits) - 1)
+        };
+        self.acc = (self.acc << bits) | value as u128;
+        self.acc_bits += bits;
+        while self.acc_bits >= 8 {
+            self.acc_bits -= 8;
+            self.dst.push((self.acc >> self.acc_bits) as u8);
+        }
+        self.acc &= (1 << self.acc_bits) - 1;
+    }
+
+    /// Flushes the remaining bits, padding the last byte with zeros.
+    fn finish(self) {
+        if self.acc_bits > 0 {
+            self.dst.push((self.acc << (8 - self.acc_bits)) as u8);
+        }
+    }
+}
+
+/// Reads values written by `BitWriter`.
+struct BitReader<'a> {
+    src: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(src: &'a [u8]) -> Self {
+        Self { src, pos: 0 }
+    }
+
+    fn read(&mut self, bits: u32) -> Result<u64, CodecError> {
+        if self.pos + bits as usize > self.src.len() * 8 {
+            return Err("unexpected end of bit stream".into());
+        }
+        let mut value = 0_u64;
+        let mut remaining = bits;
+        while remaining > 0 {
+            let byte = self.src[self.pos / 8] as u64;
+            let available = 8 - (self.pos % 8) as u32;
+            let take = available.min(remaining);
+            let chunk = (byte >> (available - take)) & ((1 << take) - 1);
+            value = (value << take) | chunk;
+            remaining -= take;
+            self.pos += take as usize;
+        }
+        Ok(value)
+    }
+}
+
+/// Splits `len` bytes from the head of `src`.
+fn take_bytes<'a>(src: &mut &'a [u8], len: usize) -> Result<&'a [u8], CodecError> {
+    if src.len() < len {
+        return Err("unexpected end of encoded block".into());
+    }
+    let (head, tail) = src.split_at(len);
+    *src = tail;
+    Ok(head)
+}
+
+/// Encodes a slice of floats with Chimp128.
+///
+/// Like Gorilla each value is xor-ed with a previous value, but Chimp looks
+/// back up to 128 values for one sharing the lowest bits, and uses shorter
+/// flags for the common cases of time series data: equal values, xor with
+/// many trailing zeros, and xor with the same number of leading zeros.
+pub fn f64_chimp_encode(src: &[f64], dst: &mut Vec<u8>) -> Result<(), CodecError> {
+    if src.is_empty() {
+        return Ok(());
+    }
+    dst.push(Encoding::Chimp.id());
+    dst.extend_from_slice(&(src.len() as u32).to_be_bytes());
+
+    let mut writer = BitWriter::new(dst);
+    let mut stored = [0_u64; CHIMP_PREVIOUS_VALUES];
+    let mut indices = vec![0_usize; CHIMP_INDICES_MASK as usize + 1];
+    let mut stored_leading_zeros = u32::MAX;
+
+    let first = src[0].to_bits();
+    writer.write(first, 64);
+    stored[0] = first;
+    indices[(first & CHIMP_INDICES_MASK) as usize] = 0;
+
+    for (index, value) in src.iter().enumerate().skip(1) {
+        let value = value.to_bits();
+        let key = (value & CHIMP_INDICES_MASK) as usize;
+
+        let mut previous_index = (index - 1) % CHIMP_PREVIOUS_VALUES;
+        let mut xor = stored[previous_index] ^ value;
+        let mut use_reference = false;
+        let candidate = indices[key];
+        if index - candidate <= CHIMP_PREVIOUS_VALUES {
+            let candidate_xor = stored[candidate % CHIMP_PREVIOUS_VALUES] ^ value;
+            if candidate_xor.trailing_zeros() > CHIMP_THRESHOLD {
+                previous_index = candidate % CHIMP_PREVIOUS_VALUES;
+                xor = candidate_xor;
+                use_reference = true;
+            }
+        }
+
+        if xor == 0 {
+            // 00: equal to the referenced value.
+            writer.write(previous_index as u64, 9);
+            stored_leading_zeros = u32::MAX;
+        } else {
+            let leading_representation = chimp_leading_representation(xor.leading_zeros());
+            let leading_zeros = CHIMP_LEADING_ROUND[leading_representation as usize];
+            if use_reference {
+                // 01: index of the referenced value, leading zeros and the center bits.
+                let trailing_zeros = xor.trailing_zeros();
+                let significant_bits = 64 - leading_zeros - trailing_zeros;
+                writer.write(
+                    (1 << 16)
+                        | ((previous_index as u64) << 9)
+                        | (leading_representation << 6)
+                        | significant_bits as u64,
+                    18,
+                );
+                writer.write(xor >> trailing_zeros, significant_bits);
+                stored_leading_zeros = u32::MAX;
+            } else if leading_zeros == stored_leading_zeros {
+                // 10: same leading zeros as the last value.
+                writer.write(0b10, 2);
+                writer.write(xor, 64 - leading_zeros);
+            } else {
+                // 11: new leading zeros.
+                stored_leading_zeros = leading_zeros;
+                writer.write((0b11 << 3) | leading_representation, 5);
+                writer.write(xor, 64 - leading_zeros);
+            }
+        }
+
+        stored[index % CHIMP_PREVIOUS_VALUES] = value;
+        indices[key] = index;
+    }
+    writer.finish();
+
+    Ok(())
+}
+
+/// Number of values ALP encodes with the same exponent and factor.
+const ALP_VECTOR_SIZE: usize = 1024;
+/// Number of values sampled from a vector to choose the exponent and factor.
+const ALP_SAMPLES: usize = 32;
+const ALP_MAX_EXPONENT: usize = 18;
+/// Estimated bits of an exception: the position and the raw value.
+const ALP_EXCEPTION_BITS: usize = 16 + 64;
+/// Marks a vector stored uncompressed, in place of the exponent.
+const ALP_RAW_VECTOR: u8 = u8::MAX;
+/// Scaled values must fit in an i64 after rounding.
+const ALP_MAX_SCALED: f64 = 4611686018427387904.0; // 2^62
+
+const ALP_F10: [f64; ALP_MAX_EXPONENT + 1] = [
+    1.0, 1e1, 1e2, 1e3, 1e4, 1e5, 1e6, 1e7, 1e8, 1e9, 1e10, 1e11, 1e12, 1e13, 1e14, 1e15, 1e16,
+    1e17, 1e18,
+];
+const ALP_IF10: [f64; ALP_MAX_EXPONENT + 1] = [
+    1.0, 1e-1, 1e-2, 1e-3, 1e-4, 1e-5, 1e-6, 1e-7, 1e-8, 1e-9, 1e-10, 1e-11, 1e-12, 1e-13, 1e-14,
+    1e-15, 1e-16, 1e-17, 1e-18,
+];
+
+fn alp_encode_value(value: f64, exponent: usize, factor: usize) -> Option<i64> {
+    let scaled = value * ALP_F10[exponent] * ALP_IF10[factor];
+    if !scaled.is_finite() || scaled.abs() >= ALP_MAX_SCALED {
+        return None;
+    }
+    let digits = scaled.round() as i64;
+    // Only lossless if decoding gives back exactly the same bits.
+    (alp_decode_value(digits, exponent, factor).to_bits() == value.to_bits()).then_some(digits)
+}
+
+fn alp_decode_value(digits: i64, exponent: usize, factor: usize) -> f64 {
+    // Dividing by the exact power of ten gives the nearest double of the decimal,
+    // the same value as parsing it from text.
+    digits as f64 * ALP_F10[factor] / ALP_F10[exponent]
+}
+
+fn alp_bit_width(min: i64, max: i64) -> u32 {
+    64 - ((max - min) as u64).leading_zeros()
+}
+
+/// Chooses the exponent and factor giving the smallest estimated size for sampled values.
+fn alp_find_exponent_factor(values: &[f64]) -> (usize, usize) {
+    let step = (values.len() / ALP_SAMPLES).max(1);
+    let samples = values.iter().step_by(step).copied().collect::<Vec<_>>();
+
+    let mut best = (0, 0);
+    let mut best_size = usize::MAX;
+    for exponent in 0..=ALP_MAX_EXPONENT {
+        for factor in 0..=exponent {
+            let mut exceptions = 0;
+            let (mut min, mut max) = (i64::MAX, i64::MIN);
+            for value in samples.iter() {
+                match alp_encode_value(*value, exponent, factor) {
+                    Some(digits) => {
+                        min = min.min(digits);
+                        max = max.max(digits);
+                    }
+                    None => exceptions += 1,
+                }
+            }
+            let bit_width = if min > max {
+                0
+            } else {
+                alp_bit_width(min, max) as usize
+            };
+            let size = samples.len() * bit_width + exceptions * ALP_EXCEPTION_BITS;
+            if size < best_size {
+                best_size = size;
+                best = (exponent, factor);
+            }
+        }
+    }
+    best
+}
+
+/// Encodes a slice of floats with ALP (adaptive lossless floating point).
+///
+/// Values are split into vectors of 1024, each vector chooses an exponent `e`
+/// and factor `f` so that most values are exactly `digits * 10^f / 10^e` for
+/// an integer `digits`. The integers are stored frame-of-reference bit-packed,
+/// values that can't be converted losslessly are stored as exceptions. Works
+/// best for decimal data like prices and sensor readings, use Gorilla or Chimp
+/// for values with full double precision, vectors of which are stored uncompressed.
+pub fn f64_alp_encode(src: &[f64], dst: &mut Vec<u8>) -> Result<(), CodecError> {
+    if src.is_empty() {
+        return Ok(());
+    }
+    dst.push(Encoding::Alp.id());
+    dst.extend_from_slice(&(src.len() as u32).to_be_bytes());
+
+    for vector in src.chunks(ALP_VECTOR_SIZE) {
+        let (exponent, factor) = alp_find_exponent_factor(vector);
+        let encoded = vector
+            .iter()
+            .map(|v| alp_encode_value(*v, exponent, factor))
+            .collect::<Vec<_>>();
+        // Exceptions take the place of an encoded value, so they don't widen the frame.
+        let fill = encoded.iter().flatten().next().copied().unwrap_or(0);
+        let digits = encoded
+            .iter()
+            .map(|d| d.unwrap_or(fill))
+            .collect::<Vec<_>>();
+        let base = digits.iter().min().copied().unwrap_or(0);
+        let max = digits.iter().max().copied().unwrap_or(0);
+        let bit_width = alp_bit_width(base, max);
+        let exceptions = encoded
+            .iter()
+            .enumerate()
+            .filter(|(_, d)| d.is_none())
+            .map(|(i, _)| (i as u16, vector[i].to_bits()))
+            .collect::<Vec<_>>();
+
+        let packed_len = (vector.len() * bit_width as usize + 7) / 8;
+        if 13 + packed_len + exceptions.len() * 10 >= 1 + vector.len() * 8 {
+            // Not decimal data, storing it uncompressed is smaller.
+            dst.push(ALP_RAW_VECTOR);
+            for value in vector {
+                dst.extend_from_slice(&value.to_be_bytes());
+            }
+            continue;
+        }
+
+        dst.push(exponent as u8);
+        dst.push(factor as u8);
+        dst.extend_from_slice(&base.to_be_bytes());
+        dst.push(bit_width as u8);
+        dst.extend_from_slice(&(exceptions.len() as u16).to_be_bytes());
+        let mut writer = BitWriter::new(dst);
+        for d in digits {
+            writer.write((d - base) as u64, bit_width);
+        }
+        writer.finish();
+        for (position, _) in exceptions.iter() {
+            dst.extend_from_slice(&position.to_be_bytes());
+        }
+        for (_, bits) in exceptions.iter() {
+            dst.extend_from_slice(&bits.to_be_bytes());
+        }
+    }
+
+    Ok(())
+}
+
+/// Encodes the points kept by a lossy codec: the number of values, the
+/// varint index deltas of the kept points, then their values with Gorilla.
+fn lossy_points_encode(
+    encoding: Encoding,
+    count: usize,
+    points: &[(usize, f64)],
+    dst: &mut Vec<u8>,
+) -> Result<(), CodecError> {
+    dst.push(encoding.id());
+    dst.extend_from_slice(&(count as u32).to_be_bytes());
+    dst.extend_from_slice(&(points.len() as u32).to_be_bytes());
+    for w in points.windows(2) {
+        dst.extend_from_slice(&((w[1].0 - w[0].0) as u64).encode_var_vec());
+    }
+    let values = points.iter().map(|(_, v)| *v).collect::<Vec<_>>();
+    f64_gorilla_encode(&values, dst)
+}
+
+/// Encodes a slice of floats with Swinging Door Trending.
+///
+/// Values are approximated by line segments between kept points, a segment is
+/// extended as long as one line stays within `tolerance` of every value it
+/// covers. Reads interpolate between the kept points, so every value decodes
+/// within `tolerance` of the original. Points are indexed by their position in
+/// `src`, not by timestamp. Values too large to approximate and non-finite
+/// values are kept as is.
+///
+/// Compactions that merge overlapping blocks encode the decoded values again,
+/// each such rewrite may add up to another `tolerance` of error.
+pub fn f64_sdt_encode(src: &[f64], tolerance: f64, dst: &mut Vec<u8>) -> Result<(), CodecError> {
+    if src.is_empty() {
+        return Ok(());
+    }
+    let mut points = vec![(0, src[0])];
+    let (mut slope_min, mut slope_max) = (f64::NEG_INFINITY, f64::INFINITY);
+    for (i, value) in src.iter().copied().enumerate().skip(1) {
+        let start = points[points.len() - 1];
+        if let Some((lower, upper)) = sdt_doors(start, i, value, tolerance) {
+            let (lower, upper) = (slope_min.max(lower), slope_max.min(upper));
+            if lower <= upper {
+                (slope_min, slope_max) = (lower, upper);
+                continue;
+            }
+        }
+
+        // The doors closed, end the segment at the previous value.
+        if i - 1 > start.0 {
+            let end = sdt_segment_end(start, i - 1, src[i - 1], slope_min, slope_max);
+            points.push(end);
+        }
+        let start = points[points.len() - 1];
+        match sdt_doors(start, i, value, tolerance) {
+            Some(doors) => (slope_min, slope_max) = doors,
+            None => {
+                points.push((i, value));
+                (slope_min, slope_max) = (f64::NEG_INFINITY, f64::INFINITY);
+            }
+        }
+    }
+    let last = src.len() - 1;
+    let start = points[points.len() - 1];
+    if last > start.0 {
+        points.push(sdt_segment_end(
+            start, last, src[last], slope_min, slope_max,
+        ));
+    }
+
+    lossy_points_encode(Encoding::Sdt(Default::default()), src.len(), &points, dst)
+}
+
+/// The range of slopes from `start` that keep `value` within `tolerance`.
+fn sdt_doors(start: (usize, f64), index: usize, value: f64, tolerance: f64) -> Option<(f64, f64)> {
+    let distance = (index - start.0) as f64;
+    let lower = (value - tolerance - start.1) / distance;
+    let upper = (value + tolerance - start.1) / distance;
+    (lower.is_finite() && upper.is_finite()).then_some((lower, upper))
+}
+
+/// The end point of a segment, as close to `value` as the doors allow.
+fn sdt_segment_end(
+    start: (usize, f64),
+    index: usize,
+    value: f64,
+    slope_min: f64,
+    slope_max: f64,
+) -> (usize, f64) {
+    let distance = (index - start.0) as f64;
+    let slope = ((value - start.1) / distance).clamp(slope_min, slope_max);
+    (index, start.1 + slope * distance)
+}
+
+/// Encodes a slice of floats with a deadband filter.
+///
+/// A value is only kept when it moves more than `tolerance` away from the last
+/// kept value, the values in between decode as the last kept value. Points are
+/// indexed by their position in `src`, not by timestamp.
+pub fn f64_deadband_encode(
+    src: &[f64],
+    tolerance: f64,
+    dst: &mut Vec<u8>,
+) -> Result<(), CodecError> {
+    if src.is_empty() {
+        return Ok(());
+    }
+    let mut points = vec![(0, src[0])];
+    let mut held = (0, src[0]);
+    for (i, value) in src.iter().copied().enumerate().skip(1) {
+        // NaN is never within the band.
+        let within_band = (value - held.1).abs() <= tolerance;
+        if !within_band {
+            // Keep the held value up to here, so interpolation stays flat.
+            if i - 1 > held.0 {
+                points.push((i - 1, held.1));
+            }
+            held = (i, value);
+            points.push(held);
+        }
+    }
+    let last = src.len() - 1;
+    if last > held.0 {
+        points.push((last, held.1));
+    }
+
+    lossy_points_encode(
+        Encoding::Deadband(Default::default()),
+        src.len(),
+        &points,
+        dst,
+    )
+}
+
 // BIT_MASK contains a lookup table where the index is the number of bits
 // and the value is a mask. The table is always read by ANDing the index
 // with 0x3f, such that if the index is 64, position 0 will be read, which
@@ -412,6 +867,189 @@ pub fn f64_without_compress_decode(
     Ok(Arc::new(array))
 }
 
+fn f64_array_with_nulls(values: Vec<f64>, bit_set: &NullBuffer) -> Result<ArrayRef, CodecError> {
+    let mut builder = Float64Builder::with_capacity(bit_set.len());
+    let mut iter = values.into_iter();
+    for is_valid in bit_set.iter() {
+        if is_valid {
+            match iter.next() {
+                Some(value) => builder.append_value(value),
+                None => return Err("Mismatch between bit set and decoded values".into()),
+            }
+        } else {
+            builder.append_null();
+        }
+    }
+    let array = builder.finish();
+    Ok(Arc::new(array))
+}
+
+pub fn f64_chimp_decode(src: &[u8], bit_set: &NullBuffer) -> Result<ArrayRef, CodecError> {
+    if src.is_empty() {
+        let null_value: Vec<Option<f64>> = vec![None; bit_set.len()];
+        let array = Float64Array::from(null_value);
+        return Ok(Arc::new(array));
+    }
+    let mut src = &src[1..];
+    let count = decode_be_u32(take_bytes(&mut src, 4)?) as usize;
+
+    let mut reader = BitReader::new(src);
+    let mut values = Vec::with_capacity(count);
+    let mut stored = [0_u64; CHIMP_PREVIOUS_VALUES];
+    let mut stored_leading_zeros = u32::MAX;
+
+    let first = reader.read(64)?;
+    stored[0] = first;
+    values.push(f64::from_bits(first));
+    for index in 1..count {
+        let previous = stored[(index - 1) % CHIMP_PREVIOUS_VALUES];
+        let value = match reader.read(2)? {
+            0b00 => {
+                stored_leading_zeros = u32::MAX;
+                stored[reader.read(7)? as usize]
+            }
+            0b01 => {
+                let previous_index = reader.read(7)? as usize;
+                let leading_zeros = CHIMP_LEADING_ROUND[reader.read(3)? as usize];
+                let significant_bits = reader.read(6)? as u32;
+                if significant_bits == 0 || leading_zeros + significant_bits > 64 {
+                    return Err("invalid chimp significant bits".into());
+                }
+                let trailing_zeros = 64 - leading_zeros - significant_bits;
+                stored_leading_zeros = u32::MAX;
+                stored[previous_index] ^ (reader.read(significant_bits)? << trailing_zeros)
+            }
+            0b10 => {
+                if stored_leading_zeros == u32::MAX {
+                    return Err("invalid chimp block, no leading zeros stored".into());
+                }
+                previous ^ reader.read(64 - stored_leading_zeros)?
+            }
+            _ => {
+                stored_leading_zeros = CHIMP_LEADING_ROUND[reader.read(3)? as usize];
+                previous ^ reader.read(64 - stored_leading_zeros)?
+            }
+        };
+        stored[index % CHIMP_PREVIOUS_VALUES] = value;
+        values.push(f64::from_bits(value));
+    }
+
+    f64_array_with_nulls(values, bit_set)
+}
+
+pub fn f64_alp_decode(src: &[u8], bit_set: &NullBuffer) -> Result<ArrayRef, CodecError> {
+    if src.is_empty() {
+        let null_value: Vec<Option<f64>> = vec![None; bit_set.len()];
+        let array = Float64Array::from(null_value);
+        return Ok(Arc::new(array));
+    }
+    let mut src = &src[1..];
+    let count = decode_be_u32(take_bytes(&mut src, 4)?) as usize;
+
+    let mut values = Vec::with_capacity(count);
+    while values.len() < count {
+        let len = (count - values.len()).min(ALP_VECTOR_SIZE);
+        let exponent = take_bytes(&mut src, 1)?[0];
+        if exponent == ALP_RAW_VECTOR {
+            for value in take_bytes(&mut src, len * 8)?.chunks(8) {
+                values.push(decode_be_f64(value));
+            }
+            continue;
+        }
+        let exponent = exponent as usize;
+
+        let header = take_bytes(&mut src, 12)?;
+        let factor = header[0] as usize;
+        if exponent > ALP_MAX_EXPONENT || factor > exponent {
+            return Err("invalid alp exponent or factor".into());
+        }
+        let base = i64::from_be_bytes(header[1..9].try_into()?);
+        let bit_width = header[9] as u32;
+        if bit_width > 64 {
+            return Err("invalid alp bit width".into());
+        }
+        let exception_count = u16::from_be_bytes(header[10..12].try_into()?) as usize;
+
+        let packed = take_bytes(&mut src, (len * bit_width as usize + 7) / 8)?;
+        let mut reader = BitReader::new(packed);
+        let offset = values.len();
+        for _ in 0..len {
+            let digits = base.wrapping_add(reader.read(bit_width)? as i64);
+            values.push(alp_decode_value(digits, exponent, factor));
+        }
+
+        let positions = take_bytes(&mut src, exception_count * 2)?;
+        let exceptions = take_bytes(&mut src, exception_count * 8)?;
+        for (position, bits) in positions.chunks(2).zip(exceptions.chunks(8)) {
+            let position = u16::from_be_bytes(position.try_into()?) as usize;
+            if position >= len {
+                return Err("invalid alp exception position".into());
+            }
+            values[offset + position] = f64::from_bits(u64::from_be_bytes(bits.try_into()?));
+        }
+    }
+
+    f64_array_with_nulls(values, bit_set)
+}
+
+/// Decodes the pages of both SDT and deadband, linearly interpolating the
+/// values between the kept points.
+pub fn f64_lossy_decode(src: &[u8], bit_set: &NullBuffer) -> Result<ArrayRef, CodecError> {
+    if src.is_empty() {
+        let null_value: Vec<Option<f64>> = vec![None; bit_set.len()];
+        let array = Float64Array::from(null_value);
+        return Ok(Arc::new(array));
+    }
+    let mut src = &src[1..];
+    let count = decode_be_u32(take_bytes(&mut src, 4)?) as usize;
+    let kept = decode_be_u32(take_bytes(&mut src, 4)?) as usize;
+    if kept == 0 || kept > count {
+        return Err("invalid lossy block, bad number of kept points".into());
+    }
+
+    let mut indices = Vec::with_capacity(kept);
+    indices.push(0_usize);
+    for _ in 1..kept {
+        let (delta, len) = u64::decode_var(src).ok_or("invalid lossy block index")?;
+        src = &src[len..];
+        let previous = indices[indices.len() - 1];
+        if delta == 0 || delta >= (count - previous) as u64 {
+            return Err("invalid lossy block index".into());
+        }
+        indices.push(previous + delta as usize);
+    }
+    if indices[kept - 1] != count - 1 {
+        return Err("invalid lossy block, last value is not kept".into());
+    }
+    if src.len() < 9 {
+        return Err("invalid lossy block, missing values".into());
+    }
+    let kept_values = f64_gorilla_decode(src, &NullBuffer::new_valid(kept))?;
+    let kept_values = kept_values
+        .as_any()
+        .downcast_ref::<Float64Array>()
+        .ok_or("invalid lossy block values")?;
+
+    let points = indices
+        .into_iter()
+        .zip(kept_values.values().iter().copied())
+        .collect::<Vec<_>>();
+
+    let mut values = Vec::with_capacity(count);
+    values.push(points[0].1);
+    for w in points.windows(2) {
+        let ((start, start_value), (end, end_value)) = (w[0], w[1]);
+        let distance = (end - start) as f64;
+        for index in start + 1..end {
+            let ratio = (index - start) as f64 / distance;
+            values.push(start_value + (end_value - start_value) * ratio);
+        }
+        values.push(end_value);
+    }
+
+    f64_array_with_nulls(values, bit_set)
+}
+
 /// decode decodes a slice of bytes into a vector of floats.
 #[allow(clippy::many_single_char_names)]
 #[allow(clippy::useless_let_if_seq)]
@@ -615,8 +1253,11 @@ mod tests {
     use arrow_array::{Array, Float64Array};
 
     use crate::tsm::codec::float::{
-        f64_gorilla_decode, f64_gorilla_encode, f64_pco_decode, f64_pco_encode,
+        f64_alp_decode, f64_alp_encode, f64_chimp_decode, f64_chimp_encode, f64_deadband_encode,
+        f64_gorilla_decode, f64_gorilla_encode, f64_lossy_decode, f64_pco_decode, f64_pco_encode,
+        f64_sdt_encode, f64_without_compress_encode,
     };
+    use crate::tsm::codec::CodecError;
 
     #[test]
     fn encode_no_values() {
@@ -1825,5 +2466,259 @@ mod tests {
 
             assert_eq!(*array, expected, "{}", test.name);
         }
+
+        for test in tests.iter() {
+            let src = test.input.clone();
+            let null_bitset = NullBuffer::new_valid(src.len());
+            let expected = Float64Array::from_iter(src.iter().cloned());
+
+            let mut dst = vec![];
+            f64_chimp_encode(&src, &mut dst).expect("failed to encode");
+            let array_ref = f64_chimp_decode(&dst, &null_bitset).expect("failed to decode");
+            let array = array_ref.as_any().downcast_ref::<Float64Array>().unwrap();
+            assert_eq!(*array, expected, "chimp {}", test.name);
+
+            let mut dst = vec![];
+            f64_alp_encode(&src, &mut dst).expect("failed to encode");
+            let array_ref = f64_alp_decode(&dst, &null_bitset).expect("failed to decode");
+            let array = array_ref.as_any().downcast_ref::<Float64Array>().unwrap();
+            assert_eq!(*array, expected, "alp {}", test.name);
+        }
+    }
+
+    fn special_values() -> Vec<f64> {
+        vec![
+            100.0,
+            222.12,
+            f64::from_bits(0x7ff8000000000001),
+            45.324,
+            f64::NAN,
+            2453.023,
+            -1234.235312132,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            9123419329123.1234,
+            f64::from_bits(0x7ff0000000000002),
+            -19292929929292929292.22,
+            -0.0000000000000000000000000092,
+            0.0,
+            -0.0,
+            f64::MIN_POSITIVE,
+            f64::MAX,
+            f64::MIN,
+        ]
+    }
+
+    /// Decimal values like sensor readings, with repeats and a few full precision values.
+    fn decimal_values(len: usize) -> Vec<f64> {
+        (0..len)
+            .map(|i| match i % 100 {
+                0 => std::f64::consts::PI * i as f64,
+                1..=20 => 21.5,
+                _ => ((i * 37) % 1000) as f64 / 100.0,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn encode_special_values_chimp_alp() {
+        let src = special_values();
+        let null_bitset = NullBuffer::new_valid(src.len());
+        let expected = Float64Array::from_iter(src.iter().cloned());
+
+        let mut dst = vec![];
+        f64_chimp_encode(&src, &mut dst).expect("failed to encode src");
+        let array_ref = f64_chimp_decode(&dst, &null_bitset).expect("failed to decode");
+        let array = array_ref.as_any().downcast_ref::<Float64Array>().unwrap();
+        assert_eq!(*array, expected);
+
+        let mut dst = vec![];
+        f64_alp_encode(&src, &mut dst).expect("failed to encode src");
+        let array_ref = f64_alp_decode(&dst, &null_bitset).expect("failed to decode");
+        let array = array_ref.as_any().downcast_ref::<Float64Array>().unwrap();
+        assert_eq!(*array, expected);
+
+        // Encode nothing.
+        let mut dst = vec![];
+        f64_chimp_encode(&[], &mut dst).unwrap();
+        f64_alp_encode(&[], &mut dst).unwrap();
+        assert!(dst.is_empty());
+    }
+
+    #[test]
+    fn encode_chimp_alp_with_nulls() {
+        // More than one ALP vector and more than the Chimp look back window.
+        let src = decimal_values(3000);
+        let bits = (0..src.len() + 300)
+            .map(|i| i % 11 != 5)
+            .collect::<Vec<_>>();
+        assert_eq!(bits.iter().filter(|b| **b).count(), src.len());
+        let null_bitset = NullBuffer::from(bits.clone());
+
+        let mut iter = src.iter();
+        let expected = bits
+            .iter()
+            .map(|valid| valid.then(|| *iter.next().unwrap()))
+            .collect::<Float64Array>();
+
+        let mut dst = vec![];
+        f64_chimp_encode(&src, &mut dst).expect("failed to encode src");
+        let array_ref = f64_chimp_decode(&dst, &null_bitset).expect("failed to decode");
+        let array = array_ref.as_any().downcast_ref::<Float64Array>().unwrap();
+        assert_eq!(*array, expected);
+
+        let mut dst = vec![];
+        f64_alp_encode(&src, &mut dst).expect("failed to encode src");
+        let array_ref = f64_alp_decode(&dst, &null_bitset).expect("failed to decode");
+        let array = array_ref.as_any().downcast_ref::<Float64Array>().unwrap();
+        assert_eq!(*array, expected);
+    }
+
+    #[test]
+    fn compression_ratio_chimp_alp() {
+        let src = decimal_values(10000);
+
+        let mut raw = vec![];
+        f64_without_compress_encode(&src, &mut raw).unwrap();
+        let mut gorilla = vec![];
+        f64_gorilla_encode(&src, &mut gorilla).unwrap();
+        let mut chimp = vec![];
+        f64_chimp_encode(&src, &mut chimp).unwrap();
+        let mut alp = vec![];
+        f64_alp_encode(&src, &mut alp).unwrap();
+
+        assert!(chimp.len() < raw.len());
+        assert!(alp.len() < gorilla.len());
+        assert!(alp.len() < raw.len() / 3);
+    }
+
+    #[test]
+    fn decode_corrupted_chimp_alp() {
+        let src = decimal_values(200);
+        let null_bitset = NullBuffer::new_valid(src.len());
+
+        let mut dst = vec![];
+        f64_chimp_encode(&src, &mut dst).unwrap();
+        assert!(f64_chimp_decode(&dst[..dst.len() / 2], &null_bitset).is_err());
+
+        let mut dst = vec![];
+        f64_alp_encode(&src, &mut dst).unwrap();
+        assert!(f64_alp_decode(&dst[..dst.len() / 2], &null_bitset).is_err());
+    }
+
+    /// Plant sensor readings: plateaus and ramps with a little noise.
+    fn sensor_values(len: usize) -> Vec<f64> {
+        let mut seed = 42_u64;
+        (0..len)
+            .map(|i| {
+                seed = seed
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                let noise = ((seed >> 33) % 1000) as f64 / 5000.0 - 0.1;
+                let step = (i % 500) as f64 * 0.05;
+                let base = match (i / 500) % 4 {
+                    0 => 20.0,
+                    1 => 20.0 + step,
+                    2 => 45.0,
+                    _ => 45.0 - step,
+                };
+                base + noise
+            })
+            .collect()
+    }
+
+    type LossyEncode = fn(&[f64], f64, &mut Vec<u8>) -> Result<(), CodecError>;
+
+    const LOSSY_CODECS: [(&str, LossyEncode); 2] =
+        [("sdt", f64_sdt_encode), ("deadband", f64_deadband_encode)];
+
+    fn assert_within_tolerance(name: &str, src: &[f64], decoded: &Float64Array, tolerance: f64) {
+        let decoded = decoded.iter().flatten().collect::<Vec<_>>();
+        assert_eq!(src.len(), decoded.len(), "{name}");
+        for (v, d) in src.iter().zip(decoded) {
+            if v.is_finite() {
+                // Allow for rounding in the interpolation.
+                let error = (v - d).abs();
+                assert!(error <= tolerance + 1e-9, "{name}: {v} decoded as {d}");
+            } else {
+                assert_eq!(v.to_bits(), d.to_bits(), "{name}");
+            }
+        }
+    }
+
+    #[test]
+    fn encode_lossy_within_tolerance() {
+        let src = sensor_values(3000);
+        let bits = (0..src.len() + 300)
+            .map(|i| i % 11 != 5)
+            .collect::<Vec<_>>();
+        let null_bitset = NullBuffer::from(bits.clone());
+
+        for (name, encode) in LOSSY_CODECS {
+            for tolerance in [0.0, 0.05, 0.5, 2.0] {
+                let mut dst = vec![];
+                encode(&src, tolerance, &mut dst).expect("failed to encode src");
+                let array_ref = f64_lossy_decode(&dst, &null_bitset).expect("failed to decode");
+                let array = array_ref.as_any().downcast_ref::<Float64Array>().unwrap();
+                assert_eq!(array.len(), bits.len());
+                for (i, valid) in bits.iter().enumerate() {
+                    assert_eq!(array.is_valid(i), *valid);
+                }
+                assert_within_tolerance(name, &src, array, tolerance);
+            }
+        }
+    }
+
+    #[test]
+    fn encode_special_values_lossy() {
+        let src = special_values();
+        let null_bitset = NullBuffer::new_valid(src.len());
+        for (name, encode) in LOSSY_CODECS {
+            let mut dst = vec![];
+            encode(&src, 0.5, &mut dst).expect("failed to encode src");
+            let array_ref = f64_lossy_decode(&dst, &null_bitset).expect("failed to decode");
+            let array = array_ref.as_any().downcast_ref::<Float64Array>().unwrap();
+            assert_within_tolerance(name, &src, array, 0.5);
+
+            // Encode nothing.
+            let mut dst = vec![];
+            encode(&[], 0.5, &mut dst).unwrap();
+            assert!(dst.is_empty());
+
+            // A single value.
+            let mut dst = vec![];
+            encode(&[1.5], 0.5, &mut dst).unwrap();
+            let array_ref = f64_lossy_decode(&dst, &NullBuffer::new_valid(1)).unwrap();
+            let array = array_ref.as_any().downcast_ref::<Float64Array>().unwrap();
+            assert_eq!(*array, Float64Array::from(vec![1.5]));
+        }
+    }
+
+    #[test]
+    fn compression_ratio_lossy() {
+        let src = sensor_values(10000);
+
+        let mut gorilla = vec![];
+        f64_gorilla_encode(&src, &mut gorilla).unwrap();
+        let mut sdt = vec![];
+        f64_sdt_encode(&src, 0.5, &mut sdt).unwrap();
+        let mut deadband = vec![];
+        f64_deadband_encode(&src, 0.5, &mut deadband).unwrap();
+
+        assert!(sdt.len() * 10 < gorilla.len());
+        assert!(deadband.len() < gorilla.len());
+        assert!(sdt.len() < deadband.len());
+    }
+
+    #[test]
+    fn decode_corrupted_lossy() {
+        let src = sensor_values(2000);
+        let null_bitset = NullBuffer::new_valid(src.len());
+        for (_, encode) in LOSSY_CODECS {
+            let mut dst = vec![];
+            encode(&src, 0.5, &mut dst).unwrap();
+            assert!(f64_lossy_decode(&dst[..dst.len() / 2], &null_bitset).is_err());
+            assert!(f64_lossy_decode(&dst[..6], &null_bitset).is_err());
+        }
     }
 }
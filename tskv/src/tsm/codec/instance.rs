@@ -9,8 +9,9 @@ use crate::tsm::codec::boolean::{
     bool_without_compress_encode,
 };
 use crate::tsm::codec::float::{
-    f64_gorilla_decode, f64_gorilla_encode, f64_pco_decode, f64_pco_encode,
-    f64_without_compress_decode, f64_without_compress_encode,
+    f64_alp_decode, f64_alp_encode, f64_chimp_decode, f64_chimp_encode, f64_deadband_encode,
+    f64_gorilla_decode, f64_gorilla_encode, f64_lossy_decode, f64_pco_decode, f64_pco_encode,
+    f64_sdt_encode, f64_without_compress_decode, f64_without_compress_encode,
 };
 use crate::tsm::codec::integer::{
     i64_pco_decode_to_array, i64_pco_encode, i64_without_compress_decode_to_array,
@@ -21,7 +22,8 @@ use crate::tsm::codec::string::{
     str_gzip_decode_to_array, str_gzip_encode, str_snappy_decode, str_snappy_decode_to_array,
     str_snappy_encode, str_without_compress_decode, str_without_compress_decode_to_array,
     str_without_compress_encode, str_zlib_decode, str_zlib_decode_to_array, str_zlib_encode,
-    str_zstd_decode, str_zstd_decode_to_array, str_zstd_encode,
+    str_zstd_decode, str_zstd_decode_to_array, str_zstd_dict_decode, str_zstd_dict_decode_to_array,
+    str_zstd_dict_encode, str_zstd_encode,
 };
 use crate::tsm::codec::timestamp::{
     ts_pco_decode_to_array, ts_pco_encode, ts_without_compress_decode_to_array,
@@ -181,6 +183,54 @@ impl FloatCodec for QuantileFloatCodec {
     }
 }
 
+struct ChimpFloatCodec();
+
+impl FloatCodec for ChimpFloatCodec {
+    fn encode(&self, src: &[f64], dst: &mut Vec<u8>) -> Result<(), CodecError> {
+        f64_chimp_encode(src, dst)
+    }
+
+    fn decode_to_array(&self, src: &[u8], bit_set: &NullBuffer) -> Result<ArrayRef, CodecError> {
+        f64_chimp_decode(src, bit_set)
+    }
+}
+
+struct AlpFloatCodec();
+
+impl FloatCodec for AlpFloatCodec {
+    fn encode(&self, src: &[f64], dst: &mut Vec<u8>) -> Result<(), CodecError> {
+        f64_alp_encode(src, dst)
+    }
+
+    fn decode_to_array(&self, src: &[u8], bit_set: &NullBuffer) -> Result<ArrayRef, CodecError> {
+        f64_alp_decode(src, bit_set)
+    }
+}
+
+struct SdtFloatCodec(f64);
+
+impl FloatCodec for SdtFloatCodec {
+    fn encode(&self, src: &[f64], dst: &mut Vec<u8>) -> Result<(), CodecError> {
+        f64_sdt_encode(src, self.0, dst)
+    }
+
+    fn decode_to_array(&self, src: &[u8], bit_set: &NullBuffer) -> Result<ArrayRef, CodecError> {
+        f64_lossy_decode(src, bit_set)
+    }
+}
+
+struct DeadbandFloatCodec(f64);
+
+impl FloatCodec for DeadbandFloatCodec {
+    fn encode(&self, src: &[f64], dst: &mut Vec<u8>) -> Result<(), CodecError> {
+        f64_deadband_encode(src, self.0, dst)
+    }
+
+    fn decode_to_array(&self, src: &[u8], bit_set: &NullBuffer) -> Result<ArrayRef, CodecError> {
+        f64_lossy_decode(src, bit_set)
+    }
+}
+
 pub trait UnsignedCodec {
     fn encode(&self, src: &[u64], dst: &mut Vec<u8>) -> Result<(), CodecError>;
 
@@ -339,6 +389,22 @@ impl StringCodec for ZstdStringCodec {
     }
 }
 
+struct ZstdDictStringCodec();
+
+impl StringCodec for ZstdDictStringCodec {
+    fn encode(&self, src: &[&[u8]], dst: &mut Vec<u8>) -> Result<(), CodecError> {
+        str_zstd_dict_encode(src, dst)
+    }
+
+    fn decode(&self, src: &[u8], dst: &mut Vec<MiniVec<u8>>) -> Result<(), CodecError> {
+        str_zstd_dict_decode(src, dst)
+    }
+
+    fn decode_to_array(&self, src: &[u8], bit_set: &NullBuffer) -> Result<ArrayRef, CodecError> {
+        str_zstd_dict_decode_to_array(src, bit_set)
+    }
+}
+
 struct ZlibStringCodec();
 
 impl StringCodec for ZlibStringCodec {
@@ -396,6 +462,10 @@ pub fn get_f64_codec(algo: Encoding) -> Box<dyn FloatCodec + Send + Sync> {
         Encoding::Null => Box::new(NullFloatCodec()),
         Encoding::Gorilla => Box::new(GorillaFloatCodec()),
         Encoding::Quantile => Box::new(QuantileFloatCodec()),
+        Encoding::Chimp => Box::new(ChimpFloatCodec()),
+        Encoding::Alp => Box::new(AlpFloatCodec()),
+        Encoding::Sdt(t) => Box::new(SdtFloatCodec(t.value())),
+        Encoding::Deadband(t) => Box::new(DeadbandFloatCodec(t.value())),
         _ => Box::new(GorillaFloatCodec()),
     }
 }
@@ -408,6 +478,7 @@ pub fn get_str_codec(algo: Encoding) -> Box<dyn StringCodec + Send + Sync> {
         Encoding::Snappy => Box::new(SnappyStringCodec()),
         Encoding::Zstd => Box::new(ZstdStringCodec()),
         Encoding::Zlib => Box::new(ZlibStringCodec()),
+        Encoding::ZstdDict => Box::new(ZstdDictStringCodec()),
         _ => Box::new(SnappyStringCodec()),
     }
 }
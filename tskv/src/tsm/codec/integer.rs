@@ -41,7 +41,7 @@ pub fn i64_zigzag_simple8b_encode(src: &[i64], dst: &mut Vec<u8>) -> Result<(),
     if src.is_empty() {
         return Ok(());
     }
-    dst.push(Encoding::Delta as u8);
+    dst.push(Encoding::Delta.id());
 
     let mut max: u64 = 0;
     let mut deltas = i64_to_u64_vector(src);
@@ -1,5 +1,5 @@
 use std::convert::TryInto;
-use std::io::Write;
+use std::io::{Read, Write};
 use std::sync::Arc;
 
 use arrow::buffer::NullBuffer;
@@ -13,7 +13,7 @@ use integer_encoding::VarInt;
 use minivec::MiniVec;
 
 use super::CodecError;
-use crate::byte_utils::decode_be_u64;
+use crate::byte_utils::{decode_be_u32, decode_be_u64};
 use crate::tsm::codec::Encoding;
 
 /// A compressed encoding using Snappy compression. Snappy is the only available
@@ -26,6 +26,10 @@ const MAX_I32: usize = i32::MAX as usize;
 
 /// zstd compress level, select from -5 ~ 17
 const ZSTD_COMPRESS_LEVEL: i32 = 3;
+/// Pages with fewer values are compressed without a trained dictionary.
+const ZSTD_DICT_MIN_SAMPLES: usize = 64;
+/// Max size of the dictionary trained for a page.
+const ZSTD_DICT_MAX_SIZE: usize = 16 * 1024;
 
 /// Encodes a slice of byte slices representing string data into a vector of
 /// bytes. Currently uses Snappy compression.
@@ -33,7 +37,7 @@ pub fn str_snappy_encode(src: &[&[u8]], dst: &mut Vec<u8>) -> Result<(), CodecEr
     if src.is_empty() {
         return Ok(());
     }
-    dst.push(Encoding::Snappy as u8);
+    dst.push(Encoding::Snappy.id());
 
     // strings shouldn't be longer than 64kb
     let length_of_lengths = src.len() * super::MAX_VAR_INT_32;
@@ -99,11 +103,46 @@ pub fn str_zstd_encode(src: &[&[u8]], dst: &mut Vec<u8>) -> Result<(), CodecErro
         data.extend_from_slice(s);
     }
 
-    dst.push(Encoding::Zstd as u8);
+    dst.push(Encoding::Zstd.id());
     zstd::stream::copy_encode(data.as_slice(), dst, ZSTD_COMPRESS_LEVEL)?;
     Ok(())
 }
 
+/// Encodes strings with zstd and a dictionary trained on the values of the page.
+///
+/// The dictionary is stored before the compressed data: `[dict_len: u32][dict][data]`.
+/// Training learns the substrings shared by many values (e.g. prefixes of tag
+/// values or the templates of log lines). If the page has too few values or
+/// training fails, the dictionary is empty and plain zstd is used.
+pub fn str_zstd_dict_encode(src: &[&[u8]], dst: &mut Vec<u8>) -> Result<(), CodecError> {
+    if src.is_empty() {
+        return Ok(());
+    }
+
+    let mut data = vec![];
+    for s in src {
+        let len = s.len() as u64;
+        data.extend_from_slice(len.to_be_bytes().as_slice());
+        data.extend_from_slice(s);
+    }
+
+    let dict = if src.len() >= ZSTD_DICT_MIN_SAMPLES {
+        let max_size = (data.len() / 8).min(ZSTD_DICT_MAX_SIZE);
+        zstd::dict::from_samples(src, max_size).unwrap_or_default()
+    } else {
+        vec![]
+    };
+
+    dst.push(Encoding::ZstdDict.id());
+    dst.extend_from_slice(&(dict.len() as u32).to_be_bytes());
+    dst.extend_from_slice(&dict);
+    let mut encoder =
+        zstd::stream::write::Encoder::with_dictionary(vec![], ZSTD_COMPRESS_LEVEL, &dict)?;
+    encoder.write_all(&data)?;
+    dst.append(&mut encoder.finish()?);
+    Ok(())
+}
+
 pub fn str_gzip_encode(src: &[&[u8]], dst: &mut Vec<u8>) -> Result<(), CodecError> {
     if src.is_empty() {
         return Ok(());
@@ -119,7 +158,7 @@ pub fn str_gzip_encode(src: &[&[u8]], dst: &mut Vec<u8>) -> Result<(), CodecErro
     let mut encoder = GzEncoder::new(vec![], CompressionFlate::default());
     encoder.write_all(&data)?;
 
-    dst.push(Encoding::Gzip as u8);
+    dst.push(Encoding::Gzip.id());
     dst.append(&mut encoder.finish()?);
     Ok(())
 }
@@ -141,7 +180,7 @@ pub fn str_bzip_encode(src: &[&[u8]], dst: &mut Vec<u8>) -> Result<(), CodecErro
         .write_all(&data)
         .map_err(|e| Box::new(e) as CodecError)?;
 
-    dst.push(Encoding::Bzip as u8);
+    dst.push(Encoding::Bzip.id());
     dst.append(&mut encoder.finish()?);
     Ok(())
 }
@@ -161,7 +200,7 @@ pub fn str_zlib_encode(src: &[&[u8]], dst: &mut Vec<u8>) -> Result<(), CodecErro
     encoder
         .write_all(&data)
         .map_err(|e| Box::new(e) as CodecError)?;
-    dst.push(Encoding::Zlib as u8);
+    dst.push(Encoding::Zlib.id());
     dst.append(&mut encoder.finish()?);
     Ok(())
 }
@@ -171,7 +210,7 @@ pub fn str_without_compress_encode(src: &[&[u8]], dst: &mut Vec<u8>) -> Result<(
         return Ok(());
     }
 
-    dst.push(Encoding::Null as u8);
+    dst.push(Encoding::Null.id());
 
     for s in src {
         let len = s.len() as u64;
@@ -337,6 +376,46 @@ pub fn str_zstd_decode_to_array(src: &[u8], bit_set: &NullBuffer) -> Result<Arra
     split_stream_to_array(&data, bit_set)
 }
 
+fn zstd_dict_decompress(src: &[u8]) -> Result<Vec<u8>, CodecError> {
+    if src.len() < 4 {
+        return Err("unexpected end of zstd dictionary block".into());
+    }
+    let dict_len = decode_be_u32(&src[..4]) as usize;
+    let src = &src[4..];
+    if src.len() < dict_len {
+        return Err("unexpected end of zstd dictionary block".into());
+    }
+    let (dict, compressed) = src.split_at(dict_len);
+
+    let mut data = vec![];
+    let mut decoder = zstd::stream::read::Decoder::with_dictionary(compressed, dict)?;
+    decoder.read_to_end(&mut data)?;
+    Ok(data)
+}
+
+pub fn str_zstd_dict_decode(src: &[u8], dst: &mut Vec<MiniVec<u8>>) -> Result<(), CodecError> {
+    if src.is_empty() {
+        return Ok(());
+    }
+    let data = zstd_dict_decompress(&src[1..])?;
+
+    split_stream(&data, dst)?;
+    Ok(())
+}
+
+pub fn str_zstd_dict_decode_to_array(
+    src: &[u8],
+    bit_set: &NullBuffer,
+) -> Result<ArrayRef, CodecError> {
+    if src.is_empty() {
+        let null_value: Vec<Option<String>> = vec![None; bit_set.len()];
+        let array = StringArray::from(null_value);
+        return Ok(Arc::new(array));
+    }
+    let data = zstd_dict_decompress(&src[1..])?;
+    split_stream_to_array(&data, bit_set)
+}
+
 pub fn str_bzip_decode(src: &[u8], dst: &mut Vec<MiniVec<u8>>) -> Result<(), CodecError> {
     if src.is_empty() {
         return Ok(());
@@ -491,6 +570,8 @@ mod tests {
         assert_eq!(dst.to_vec().len(), 0);
         str_zstd_encode(&src, &mut dst).unwrap();
         assert_eq!(dst.to_vec().len(), 0);
+        str_zstd_dict_encode(&src, &mut dst).unwrap();
+        assert_eq!(dst.to_vec().len(), 0);
         str_bzip_encode(&src, &mut dst).unwrap();
         assert_eq!(dst.to_vec().len(), 0);
         str_without_compress_encode(&src, &mut dst).unwrap();
@@ -726,6 +807,16 @@ mod tests {
         dst.clear();
         got.clear();
 
+        str_zstd_dict_encode(&data, &mut dst).unwrap();
+        str_zstd_dict_decode(&dst, &mut got).unwrap();
+        assert_eq!(data_exp, got);
+        let array_ref =
+            str_zstd_dict_decode_to_array(&dst, &null_bitset).expect("failed to encode src");
+        let array = array_ref.as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!(*array, expected);
+        dst.clear();
+        got.clear();
+
         str_zlib_encode(&data, &mut dst).unwrap();
         str_zlib_decode(&dst, &mut got).unwrap();
         assert_eq!(data_exp, got);
@@ -752,4 +843,57 @@ mod tests {
         let array = array_ref.as_any().downcast_ref::<StringArray>().unwrap();
         assert_eq!(*array, expected);
     }
+
+    #[test]
+    fn test_zstd_dict_encode_decode() {
+        let values = (0..4096)
+            .map(|i| {
+                format!(
+                    "level=info host=server-{:03} msg=\"request finished\" path=/api/v1/{} \
+                     status={} request_id={} latency={}ms",
+                    i % 50,
+                    ["write", "sql", "ping"][i % 3],
+                    [200, 204, 500][i % 7 % 3],
+                    i,
+                    i * 7919 % 1000
+                )
+            })
+            .collect::<Vec<_>>();
+        let data = values.iter().map(|v| v.as_bytes()).collect::<Vec<_>>();
+
+        let mut dst = vec![];
+        str_zstd_dict_encode(&data, &mut dst).unwrap();
+        let dict_len = decode_be_u32(&dst[1..5]) as usize;
+        assert!(
+            dict_len > 0,
+            "dictionary should be trained for a large page"
+        );
+
+        let mut got = vec![];
+        str_zstd_dict_decode(&dst, &mut got).unwrap();
+        let expected = data.iter().map(|v| MiniVec::from(*v)).collect::<Vec<_>>();
+        assert_eq!(expected, got);
+
+        // Some nulls in the page.
+        let bits = (0..data.len() + 2)
+            .map(|i| i != 3 && i != 10)
+            .collect::<Vec<_>>();
+        let null_bitset = NullBuffer::new(BooleanBuffer::from(bits.clone()));
+        let array_ref = str_zstd_dict_decode_to_array(&dst, &null_bitset).unwrap();
+        let array = array_ref.as_any().downcast_ref::<StringArray>().unwrap();
+        let mut iter = values.iter();
+        let expected = bits
+            .iter()
+            .map(|valid| valid.then(|| iter.next().unwrap().as_str()))
+            .collect::<StringArray>();
+        assert_eq!(*array, expected);
+
+        // Too few values to train a dictionary.
+        dst.clear();
+        got.clear();
+        str_zstd_dict_encode(&data[..3], &mut dst).unwrap();
+        assert_eq!(decode_be_u32(&dst[1..5]), 0);
+        str_zstd_dict_decode(&dst, &mut got).unwrap();
+        assert_eq!(got.len(), 3);
+    }
 }
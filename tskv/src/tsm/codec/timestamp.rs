@@ -22,7 +22,7 @@ pub fn ts_without_compress_encode(src: &[i64], dst: &mut Vec<u8>) -> Result<(),
     if src.is_empty() {
         return Ok(());
     }
-    dst.push(Encoding::Null as u8);
+    dst.push(Encoding::Null.id());
 
     for i in src.iter() {
         dst.extend_from_slice(i.to_be_bytes().as_slice());
@@ -35,7 +35,7 @@ pub fn ts_pco_encode(src: &[i64], dst: &mut Vec<u8>) -> Result<(), CodecError> {
         return Ok(());
     }
 
-    dst.push(Encoding::Quantile as u8);
+    dst.push(Encoding::Quantile.id());
 
     dst.append(&mut simpler_compress(src, DEFAULT_COMPRESSION_LEVEL)?);
     Ok(())
@@ -52,7 +52,7 @@ pub fn ts_zigzag_simple8b_encode(src: &[i64], dst: &mut Vec<u8>) -> Result<(), C
     if src.is_empty() {
         return Ok(());
     }
-    dst.push(Encoding::DeltaTs as u8);
+    dst.push(Encoding::DeltaTs.id());
 
     let mut max: u64 = 0;
     let mut deltas = i64_to_u64_vector(src);
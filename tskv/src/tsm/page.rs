@@ -612,6 +612,18 @@ pub enum PageStatistics {
     Bytes(ValueStatistics<Vec<u8>>),
 }
 
+impl PageStatistics {
+    pub fn null_count(&self) -> u64 {
+        match self {
+            PageStatistics::Bool(s) => s.null_count(),
+            PageStatistics::F64(s) => s.null_count(),
+            PageStatistics::I64(s) => s.null_count(),
+            PageStatistics::U64(s) => s.null_count(),
+            PageStatistics::Bytes(s) => s.null_count(),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct PageWriteSpec {
     pub(crate) offset: u64,
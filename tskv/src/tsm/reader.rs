@@ -125,9 +125,18 @@ impl TsmReader {
             .open_file_reader(&path)
             .await
             .map_err(|e| TskvError::FileSystemError { source: e })?;
-
         let file_id = file_utils::get_tsm_file_id_by_path(&path)?;
+        let tombstone_dir = path.parent().unwrap_or_else(|| Path::new("/"));
+
+        Self::open_with_reader(file_id, reader, tombstone_dir).await
+    }
 
+    /// Open a TSM file by the given reader, the tombstone of the file is in `tombstone_dir`.
+    pub async fn open_with_reader(
+        file_id: ColumnFileId,
+        reader: Box<FileStreamReader>,
+        tombstone_dir: impl AsRef<Path>,
+    ) -> TskvResult<Self> {
         let footer = Arc::new(read_footer(&reader).await?);
         let mut target = Vec::new();
         let buffer = read_tsm_meta_buffer(&reader, &footer).await?;
@@ -148,8 +157,7 @@ impl TsmReader {
         let chunk_group = read_chunk_groups(tsm_meta_buffer, &chunk_group_meta).await?;
         let chunk = read_chunk(tsm_meta_buffer, &chunk_group).await?;
 
-        let tombstone_path = path.parent().unwrap_or_else(|| Path::new("/"));
-        let tombstone = Arc::new(TsmTombstone::open(tombstone_path, file_id).await?);
+        let tombstone = Arc::new(TsmTombstone::open(tombstone_dir, file_id).await?);
 
         let tsm_meta = Arc::new(TsmMetaData::new(
             footer,
@@ -513,7 +521,14 @@ pub fn decode_pages(
                 reason: "time field not found".to_string(),
             })?;
         let (time_array, time_range, time) = get_time_page_meta(time_page)?;
-        let filters = tomb.get_all_fields_excluded_time_range(&time_range);
+        // Rows are removed if all fields or the time column of the series are excluded,
+        // e.g. by DELETE or table TTL.
+        let mut filters = tomb.get_all_fields_excluded_time_range(&time_range);
+        filters.extend(tomb.get_column_overlapped_time_ranges(
+            series_id,
+            time_page.meta.column.id,
+            &time_range,
+        ));
         let time_null_bits = {
             if filters.is_empty() {
                 None
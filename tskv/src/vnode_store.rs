@@ -1,28 +1,49 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::path::Path;
 use std::sync::Arc;
 
+use datafusion::arrow::array::{StringBuilder, UInt64Builder};
+use datafusion::arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use datafusion::arrow::record_batch::RecordBatch;
 use metrics::average::U64Average;
 use models::meta_data::VnodeId;
 use models::predicate::domain::{ResolvedPredicate, TimeRange, TimeRanges};
+use models::schema::table_statistics::TableStatistics;
+use models::schema::tskv_table_schema::{ColumnType, TskvTableSchemaRef};
 use models::utils::now_timestamp_secs;
-use models::{ColumnId, SeriesId, SeriesKey};
+use models::{ColumnId, SeriesId, SeriesKey, Timestamp};
+use object_store::path::Path as ObjectPath;
+use openraft::EntryPayload;
 use protos::kv_service::{raft_write_command, WritePointsResponse, *};
+use protos::models_helper::parse_prost_bytes;
 use replication::EngineMetrics;
-use snafu::{OptionExt, ResultExt};
+use snafu::{IntoError, OptionExt, ResultExt};
 use tokio::sync::RwLock;
 use trace::span_ext::SpanExt;
 use trace::{debug, error, info, Span, SpanContext};
 use utils::precision::Precision;
 
+use crate::backup::{BackupStorage, VnodeBackupManifest, WalSegment};
+use crate::cdc::ChangeEvent;
 use crate::compaction::job::FlushJob;
 use crate::compaction::FlushReq;
 use crate::database::Database;
-use crate::error::{IndexErrSnafu, InvalidParamSnafu, InvalidPointTableSnafu, TskvResult};
+use crate::error::{
+    ArrowSnafu, CommonSnafu, DecodeSnafu, IOSnafu, IndexErrSnafu, InvalidParamSnafu,
+    InvalidPointTableSnafu, SchemaSnafu, TskvError, TskvResult,
+};
+use crate::file_system::async_filesystem::LocalFileSystem;
+use crate::file_system::FileSystem;
 use crate::index::ts_index::TSIndex;
 use crate::schema::error::{FieldNotFoundSnafu, TableNotFoundSnafu};
-use crate::tsfamily::summary::Summary;
+use crate::tsfamily::column_file::ColumnFile;
+use crate::tsfamily::summary::{Summary, SummaryRequest};
 use crate::tsfamily::tseries_family::TseriesFamily;
+use crate::tsfamily::version::{CompactMeta, Version, VersionEdit};
+use crate::tsm::page::PageStatistics;
+use crate::tsm::reader::TsmReader;
+use crate::tsm::TOMBSTONE_FILE_SUFFIX;
+use crate::wal::reader::WalReader;
 use crate::{file_utils, TsKvContext, VnodeSnapshot};
 
 #[derive(Clone)]
@@ -87,7 +108,11 @@ impl VnodeStorage {
         match command {
             raft_write_command::Command::WriteData(cmd) => {
                 let precision = Precision::from(cmd.precision as u8);
-                if let Err(err) = self.write(ctx, cmd.data, precision, None).await {
+                let series_limits = cmd.series_limits.as_ref();
+                if let Err(err) = self
+                    .write(ctx, cmd.data, precision, series_limits, None)
+                    .await
+                {
                     if ctx.apply_type == replication::APPLY_TYPE_WAL {
                         info!("recover: write points: {}", err);
                     } else {
@@ -263,6 +288,7 @@ impl VnodeStorage {
         ctx: &replication::ApplyContext,
         points: Vec<u8>,
         precision: Precision,
+        series_limits: Option<&SeriesLimits>,
         span_context: Option<&SpanContext>,
     ) -> TskvResult<WritePointsResponse> {
         let write_start = std::time::Instant::now();
@@ -287,6 +313,7 @@ impl VnodeStorage {
                     self.ts_index.clone(),
                     recover_from_wal,
                     strict_write,
+                    series_limits,
                 )
                 .await
                 .inspect_err(|err| {
@@ -605,6 +632,341 @@ impl VnodeStorage {
         Ok(())
     }
 
+    /// Applies the TTL of tables to the column files of the vnode, column files whose
+    /// data are all expired are deleted, expired data in other column files are marked
+    /// by tombstones and then removed by compactions.
+    pub async fn apply_table_ttl(&self) -> TskvResult<()> {
+        let schemas = self.db.read().await.get_schemas();
+        let mut tables_min_ts = HashMap::new();
+        for table in schemas.list_tables().await? {
+            if let Some(schema) = schemas.get_table_schema(&table).await? {
+                if let Some(min_ts) = schema.time_to_expired() {
+                    tables_min_ts.insert(table, (min_ts, schema));
+                }
+            }
+        }
+        if tables_min_ts.is_empty() {
+            return Ok(());
+        }
+
+        let (version, owner) = {
+            let tsf = self.ts_family.read().await;
+            if !tsf.can_compaction() {
+                return Ok(());
+            }
+            (tsf.version(), tsf.owner())
+        };
+        let expired_files = expire_column_files(&version, &tables_min_ts).await?;
+
+        let mut picked_file_ids = HashSet::with_capacity(expired_files.len());
+        let mut version_edit =
+            VersionEdit::new_update_vnode(self.id, owner.to_string(), version.last_seq());
+        for file in expired_files {
+            // Mark the file as compacting, so it won't be picked by compactions.
+            if file.mark_compacting().await {
+                info!(
+                    "vnode {}: removing tsm file {} since all data are expired",
+                    self.id,
+                    file.file_id()
+                );
+                picked_file_ids.insert(file.file_id());
+                version_edit.del_file(file.level(), file.file_id(), file.is_delta());
+            }
+        }
+        if picked_file_ids.is_empty() {
+            return Ok(());
+        }
+
+        let request = SummaryRequest {
+            version_edit,
+            mem_caches: None,
+            file_metas: None,
+            ts_family: self.ts_family.clone(),
+        };
+        let result = self
+            .summary
+            .write()
+            .await
+            .apply_version_edit(&request)
+            .await;
+        if let Err(e) = result {
+            error!(
+                "Failed to apply version edit of removing expired tsm files of vnode {}: {e}",
+                self.id
+            );
+            version.unmark_compacting_files(&picked_file_ids).await;
+            return Err(e);
+        }
+
+        Ok(())
+    }
+
+    /// Ships the flushed column files, tombstones and WAL segments of the vnode to the
+    /// backup storage, column files that were shipped by earlier backups are skipped.
+    pub async fn backup(
+        &self,
+        storage: &BackupStorage,
+        backup_id: i64,
+    ) -> TskvResult<VnodeBackupManifest> {
+        self.flush(true, true, false).await?;
+
+        // The version holds the column files, so they won't be deleted while uploading.
+        let (version, mut version_edit) = {
+            let tsf = self.ts_family.write().await;
+            (tsf.version(), tsf.build_version_edit())
+        };
+        let owner = version.owner();
+        let (mut shipped_files, mut wal_segments) =
+            match storage.latest_vnode_manifest(self.id).await? {
+                Some(manifest) => (manifest.shipped_files, manifest.wal_segments),
+                None => (BTreeSet::new(), vec![]),
+            };
+
+        let mut tombstones = vec![];
+        for level in version.levels_info().iter() {
+            for file in level.files.iter() {
+                if !shipped_files.contains(&file.file_id()) {
+                    let location =
+                        storage.file_location(self.id, &CompactMeta::from(file.as_ref()));
+                    match file.remote_path() {
+                        Some(remote_path) => {
+                            self.ship_cold_file(storage, remote_path, &location).await?
+                        }
+                        None => storage.put_file(file.file_path(), &location).await?,
+                    }
+                    shipped_files.insert(file.file_id());
+                }
+                let tombstone_path = file.tombstone_path();
+                if LocalFileSystem::try_exists(&tombstone_path) {
+                    let location = storage.tombstone_location(self.id, backup_id, file.file_id());
+                    storage.put_file(&tombstone_path, &location).await?;
+                    tombstones.push(file.file_id());
+                }
+            }
+        }
+        // Files are restored to the local directories.
+        for file in version_edit.add_files.iter_mut() {
+            file.remote_path = None;
+        }
+
+        let wal_dir = self.ctx.options.wal.wal_dir(&owner, self.id);
+        for file_name in LocalFileSystem::list_file_names(&wal_dir) {
+            let wal_id = match file_utils::get_wal_file_id(&file_name) {
+                Ok(id) => id,
+                Err(_) => continue,
+            };
+            let path = wal_dir.join(&file_name);
+            let metadata = tokio::fs::metadata(&path).await.context(IOSnafu)?;
+            let size = metadata.len();
+            if wal_segments
+                .iter()
+                .any(|s| s.id == wal_id && s.size == size)
+            {
+                continue;
+            }
+            let modified_time = metadata
+                .modified()
+                .context(IOSnafu)?
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_nanos() as i64)
+                .unwrap_or_default();
+            storage
+                .put_file(&path, &storage.wal_location(self.id, wal_id))
+                .await?;
+            wal_segments.retain(|s| s.id != wal_id);
+            wal_segments.push(WalSegment {
+                id: wal_id,
+                size,
+                modified_time,
+            });
+        }
+        wal_segments.sort_by_key(|s| s.id);
+
+        let manifest = VnodeBackupManifest {
+            backup_id,
+            vnode_id: self.id,
+            version_edit,
+            tombstones,
+            shipped_files,
+            wal_segments,
+            wal_compress: self.ctx.options.wal.compress,
+        };
+        storage.put_vnode_manifest(&manifest).await?;
+        info!(
+            "Backup {backup_id}: shipped vnode {} with {} column files",
+            self.id,
+            manifest.version_edit.add_files.len()
+        );
+
+        Ok(manifest)
+    }
+
+    async fn ship_cold_file(
+        &self,
+        storage: &BackupStorage,
+        remote_path: &str,
+        location: &ObjectPath,
+    ) -> TskvResult<()> {
+        let cold_tier = self.ctx.cold_tier.as_ref().context(CommonSnafu {
+            reason: format!("Cold tier is not configured, can not read file {remote_path}"),
+        })?;
+        let tmp_path = self
+            .ctx
+            .options
+            .storage
+            .path()
+            .join(format!("backup_{}", self.id))
+            .join(location.filename().unwrap_or_default());
+        cold_tier
+            .download(&ObjectPath::from(remote_path), &tmp_path)
+            .await?;
+        let result = storage.put_file(&tmp_path, location).await;
+        let _ = tokio::fs::remove_file(&tmp_path).await;
+        result
+    }
+
+    /// Read the commands applied to the vnode from its WAL, whose raft log index is
+    /// in `(after_index, until_index]`. Fails if the WAL no longer retains the command
+    /// right after `after_index`.
+    pub async fn read_changes(
+        &self,
+        after_index: u64,
+        until_index: u64,
+    ) -> TskvResult<Vec<ChangeEvent>> {
+        if after_index >= until_index {
+            return Ok(vec![]);
+        }
+
+        let owner = self.ts_family.read().await.owner();
+        let wal_dir = self.ctx.options.wal.wal_dir(&owner, self.id);
+        let mut wal_ids = LocalFileSystem::list_file_names(&wal_dir)
+            .iter()
+            .filter_map(|file_name| file_utils::get_wal_file_id(file_name).ok())
+            .collect::<Vec<_>>();
+        wal_ids.sort_unstable();
+
+        // Entries overwritten by a new raft leader are appended again, the later one wins.
+        let mut commands = BTreeMap::new();
+        let mut first_index = None;
+        for wal_id in wal_ids {
+            let path = file_utils::make_wal_file(&wal_dir, wal_id);
+            let mut reader = WalReader::open(&path, self.ctx.options.wal.compress).await?;
+            loop {
+                let record = match reader.next_wal_entry().await {
+                    Ok(Some(record)) => record,
+                    Ok(None) | Err(TskvError::WalTruncated { .. }) => break,
+                    Err(e) => return Err(e),
+                };
+                first_index = Some(first_index.map_or(record.seq, |i: u64| i.min(record.seq)));
+                if record.seq <= after_index || record.seq > until_index {
+                    continue;
+                }
+                if let EntryPayload::Normal(request) = record.block.payload {
+                    let command = parse_prost_bytes::<RaftWriteCommand>(&request)
+                        .map_err(|e| DecodeSnafu.into_error(Box::new(e)))?;
+                    commands.insert(record.seq, command);
+                }
+            }
+        }
+
+        if first_index.map_or(true, |i| i > after_index + 1) {
+            return Err(CommonSnafu {
+                reason: format!(
+                    "Changes of vnode {} after index {after_index} are no longer retained in WAL",
+                    self.id
+                ),
+            }
+            .build());
+        }
+
+        Ok(commands
+            .into_iter()
+            .map(|(index, command)| ChangeEvent {
+                vnode_id: self.id,
+                index,
+                command,
+            })
+            .collect())
+    }
+
+    /// Restores the column files of a vnode backup to the vnode, the series index is
+    /// rebuilt from the series keys of the restored files.
+    pub async fn restore_backup(
+        &self,
+        storage: &BackupStorage,
+        manifest: &VnodeBackupManifest,
+    ) -> TskvResult<()> {
+        let (version, owner) = {
+            let tsf = self.ts_family.read().await;
+            (tsf.version(), tsf.owner())
+        };
+        let storage_opt = version.storage_opt();
+        let mut version_edit =
+            VersionEdit::new_update_vnode(self.id, owner.to_string(), version.last_seq());
+        let mut file_metas = HashMap::with_capacity(manifest.version_edit.add_files.len());
+        let mut ts_index = self.ts_index.write().await;
+        for backup_file in manifest.version_edit.add_files.iter() {
+            let file = CompactMeta {
+                file_id: self.summary.read().await.next_file_id(),
+                tsf_id: self.id,
+                remote_path: None,
+                ..backup_file.clone()
+            };
+            let path = file.file_path(&storage_opt, &owner, self.id);
+            storage
+                .get_file(
+                    &storage.file_location(manifest.vnode_id, backup_file),
+                    &path,
+                )
+                .await?;
+            if manifest.tombstones.contains(&backup_file.file_id) {
+                let location = storage.tombstone_location(
+                    manifest.vnode_id,
+                    manifest.backup_id,
+                    backup_file.file_id,
+                );
+                storage
+                    .get_file(&location, path.with_extension(TOMBSTONE_FILE_SUFFIX))
+                    .await?;
+            }
+
+            let reader = TsmReader::open(&path).await?;
+            for chunk in reader.chunk().values() {
+                ts_index
+                    .add_series_for_rebuild(chunk.series_id(), chunk.series_key())
+                    .await
+                    .context(IndexErrSnafu)?;
+            }
+            let bloom_filter = Arc::new(reader.footer().series().bloom_filter().clone());
+            file_metas.insert(file.file_id, bloom_filter);
+            version_edit.add_file(file, manifest.version_edit.max_level_ts);
+        }
+        ts_index.flush().await.context(IndexErrSnafu)?;
+        drop(ts_index);
+
+        info!(
+            "Restoring {} column files of vnode {} of backup {} to vnode {}",
+            version_edit.add_files.len(),
+            manifest.vnode_id,
+            manifest.backup_id,
+            self.id
+        );
+        if version_edit.add_files.is_empty() {
+            return Ok(());
+        }
+        let request = SummaryRequest {
+            version_edit,
+            mem_caches: None,
+            file_metas: Some(file_metas),
+            ts_family: self.ts_family.clone(),
+        };
+        self.summary
+            .write()
+            .await
+            .apply_version_edit(&request)
+            .await
+    }
+
     pub fn get_summary(&self) -> Arc<RwLock<Summary>> {
         self.summary.clone()
     }
@@ -616,4 +978,246 @@ impl VnodeStorage {
     pub async fn sync_index(&self) {
         let _ = self.ts_index.write().await.flush().await;
     }
+
+    /// Get the series cardinality of the tables in the vnode, or of the `table` if specified,
+    /// returns RecordBatch with a row for each tag key of each table, for example:
+    ///
+    /// | table_name | series_count | tag_key | tag_value_count |
+    /// | ---------- | ------------ | ------- | --------------- |
+    /// | cpu        | 100          | host    | 100             |
+    /// | cpu        | 100          | region  | 4               |
+    /// | mem        | 1            | NULL    | NULL            |
+    pub async fn series_cardinality(&self, table: Option<&str>) -> TskvResult<RecordBatch> {
+        let tables = match table {
+            Some(table) => vec![table.to_string()],
+            None => self
+                .db
+                .read()
+                .await
+                .get_schemas()
+                .list_tables()
+                .await
+                .context(SchemaSnafu)?,
+        };
+
+        let mut table_names = StringBuilder::new();
+        let mut series_counts = UInt64Builder::new();
+        let mut tag_keys = StringBuilder::new();
+        let mut tag_value_counts = UInt64Builder::new();
+        let ts_index = self.ts_index.read().await;
+        for table in tables {
+            let cardinality = ts_index
+                .get_table_cardinality(&table)
+                .await
+                .context(IndexErrSnafu)?;
+            if cardinality.series_count == 0 {
+                continue;
+            }
+            if cardinality.tag_value_counts.is_empty() {
+                table_names.append_value(&table);
+                series_counts.append_value(cardinality.series_count);
+                tag_keys.append_null();
+                tag_value_counts.append_null();
+            }
+            for (tag_key, count) in cardinality.tag_value_counts {
+                table_names.append_value(&table);
+                series_counts.append_value(cardinality.series_count);
+                tag_keys.append_value(tag_key);
+                tag_value_counts.append_value(count);
+            }
+        }
+
+        RecordBatch::try_new(
+            series_cardinality_schema(),
+            vec![
+                Arc::new(table_names.finish()),
+                Arc::new(series_counts.finish()),
+                Arc::new(tag_keys.finish()),
+                Arc::new(tag_value_counts.finish()),
+            ],
+        )
+        .context(ArrowSnafu)
+    }
+
+    /// Get the estimated statistics of the table in the vnode:
+    /// - Row count and time range are from the time pages of the column files and the
+    ///   timestamps in the caches, rows in overlapped files and caches are counted repeatedly.
+    /// - Null counts of the fields are from the pages of the column files.
+    /// - Distinct counts of the tags are from the index.
+    pub async fn table_statistics(&self, table: &str) -> TskvResult<TableStatistics> {
+        let db_name = self.db.read().await.db_name();
+        let schema = self
+            .db
+            .read()
+            .await
+            .get_table_schema(table)
+            .await?
+            .context(TableNotFoundSnafu {
+                database: db_name.to_string(),
+                table: table.to_string(),
+            })?;
+
+        let (series_ids, cardinality) = {
+            let ts_index = self.ts_index.read().await;
+            let series_ids = ts_index
+                .get_series_id_list(table, &[])
+                .await
+                .context(IndexErrSnafu)?;
+            let cardinality = ts_index
+                .get_table_cardinality(table)
+                .await
+                .context(IndexErrSnafu)?;
+            (series_ids, cardinality)
+        };
+
+        let mut statistics = TableStatistics::default();
+        if series_ids.is_empty() {
+            return Ok(statistics);
+        }
+
+        let mut time_range = TimeRange::none();
+        let super_version = self.ts_family.read().await.super_version();
+        let files = super_version
+            .version
+            .statistics(&series_ids, TimeRange::all())
+            .await;
+        for page in files
+            .into_values()
+            .flat_map(|series| series.into_values())
+            .flatten()
+            .flat_map(|(_, pages)| pages)
+        {
+            match page.column.column_type {
+                ColumnType::Time(_) => {
+                    statistics.row_count += page.num_values as u64;
+                    if let PageStatistics::I64(s) = &page.statistics {
+                        if let (Some(min_ts), Some(max_ts)) = (s.min(), s.max()) {
+                            time_range.merge(&TimeRange::new(*min_ts, *max_ts));
+                        }
+                    }
+                }
+                ColumnType::Field(_) => {
+                    let column = statistics.columns.entry(page.column.id).or_default();
+                    *column.null_count.get_or_insert(0) += page.statistics.null_count();
+                }
+                _ => {}
+            }
+        }
+        super_version.caches.read_series_timestamps(
+            &series_ids,
+            &TimeRanges::all(),
+            |ts| {
+                statistics.row_count += 1;
+                time_range.merge(&TimeRange::new(ts, ts));
+            },
+        );
+        if !time_range.is_none() {
+            statistics.time_range = Some(time_range);
+        }
+
+        for (tag_key, count) in cardinality.tag_value_counts {
+            if let Some(column) = schema.column(&tag_key) {
+                statistics.columns.entry(column.id).or_default().distinct_count = Some(count);
+            }
+        }
+
+        Ok(statistics)
+    }
+}
+
+pub fn series_cardinality_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("table_name", DataType::Utf8, false),
+        Field::new("series_count", DataType::UInt64, false),
+        Field::new("tag_key", DataType::Utf8, true),
+        Field::new("tag_value_count", DataType::UInt64, true),
+    ]))
+}
+
+/// Marks the data older than the minimum timestamp of their tables in `tables_min_ts`
+/// by tombstones, returns the column files whose data are all expired.
+///
+/// Column files are checked by the time range in the version first, only those which
+/// may have expired data are opened.
+pub(crate) async fn expire_column_files(
+    version: &Version,
+    tables_min_ts: &HashMap<String, (Timestamp, TskvTableSchemaRef)>,
+) -> TskvResult<Vec<Arc<ColumnFile>>> {
+    let max_min_ts = match tables_min_ts.values().map(|(min_ts, _)| *min_ts).max() {
+        Some(ts) => ts,
+        None => return Ok(vec![]),
+    };
+
+    let mut expired_files = vec![];
+    for level in version.levels_info().iter() {
+        for file in level.files.iter() {
+            if file.is_deleted()
+                || file.time_range().min_ts >= max_min_ts
+                || file.is_compacting().await
+            {
+                continue;
+            }
+            let reader = version.get_column_file_reader(file).await?;
+            let tombstone = reader.tombstone();
+            let mut all_expired = true;
+            let mut expired_columns: HashMap<&str, Vec<(SeriesId, ColumnId)>> = HashMap::new();
+            for chunk in reader.chunk().values() {
+                let (min_ts, schema) = match tables_min_ts.get(chunk.table_name()) {
+                    Some(v) => v,
+                    None => {
+                        all_expired = false;
+                        continue;
+                    }
+                };
+                let chunk_time_range = chunk.time_range();
+                if chunk_time_range.max_ts >= *min_ts {
+                    all_expired = false;
+                }
+                if chunk_time_range.min_ts >= *min_ts {
+                    continue;
+                }
+                let expired_time_range = TimeRange::new(chunk_time_range.min_ts, *min_ts - 1);
+                let time_column_id = schema.time_column().id;
+                if tombstone
+                    .get_column_overlapped_time_ranges(
+                        chunk.series_id(),
+                        time_column_id,
+                        &expired_time_range,
+                    )
+                    .iter()
+                    .any(|tr| tr.includes(&expired_time_range))
+                {
+                    // Already marked by tombstones.
+                    continue;
+                }
+                expired_columns
+                    .entry(schema.name.as_str())
+                    .or_default()
+                    .extend(
+                        schema
+                            .column_ids()
+                            .into_iter()
+                            .map(|c| (chunk.series_id(), c)),
+                    );
+            }
+
+            if all_expired {
+                expired_files.push(file.clone());
+                continue;
+            }
+            if expired_columns.is_empty() {
+                continue;
+            }
+            for (table, columns) in expired_columns {
+                let (min_ts, _) = &tables_min_ts[table];
+                tombstone
+                    .add_range(&columns, TimeRange::new(i64::MIN, *min_ts - 1), None)
+                    .await?;
+            }
+            tombstone.flush().await?;
+            file.upload_tombstone().await?;
+        }
+    }
+
+    Ok(expired_files)
 }
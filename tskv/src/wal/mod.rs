@@ -35,7 +35,7 @@
 //! +------------+---------------+--------------+--------------+
 //! ```
 
-mod reader;
+pub(crate) mod reader;
 pub mod wal_store;
 pub mod writer;
 
@@ -163,6 +163,7 @@ mod tests {
         let request = WriteDataRequest {
             data: points,
             precision: Precision::NS as u32,
+            series_limits: None,
         };
 
         tskv_write(rt.clone(), &tskv, "cnosdb", "public", 0, 1, request);
@@ -200,6 +201,7 @@ mod tests {
         let request = WriteDataRequest {
             data: points,
             precision: Precision::NS as u32,
+            series_limits: None,
         };
 
         tskv_write(rt.clone(), &tskv, "cnosdb", "db", 0, 1, request.clone());
@@ -246,6 +248,7 @@ mod tests {
             let request = WriteDataRequest {
                 data: points,
                 precision: Precision::NS as u32,
+                series_limits: None,
             };
 
             tskv_write(rt.clone(), &tskv, "cnosdb", "public", 0, i, request.clone());
@@ -271,6 +274,7 @@ mod tests {
         let request = WriteDataRequest {
             data: points,
             precision: Precision::NS as u32,
+            series_limits: None,
         };
 
         tskv_write(rt.clone(), &tskv, "cnosdb", database, 0, 1, request.clone());
@@ -337,6 +341,7 @@ mod tests {
         let request = WriteDataRequest {
             data: points,
             precision: Precision::NS as u32,
+            series_limits: None,
         };
 
         tskv_write(rt.clone(), &tskv, "cnosdb", "public", 0, 1, request.clone());
@@ -373,6 +378,7 @@ mod tests {
             let request = WriteDataRequest {
                 data: fbb.finished_data().to_vec(),
                 precision: Precision::NS as u32,
+                series_limits: None,
             };
 
             tskv_write(